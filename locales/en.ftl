@@ -0,0 +1,734 @@
+# English messages. Every message must be here; other languages fall back
+# to these for anything they don't translate.
+
+## General
+
+error = Error: { $error }
+warning = Warning: { $message }
+input-closed = Input closed.
+cancelled = Cancelled.
+status-completed = completed
+status-pending = pending
+status-doing = in progress
+status-cancelled = cancelled
+
+## Adding tasks
+
+add-missing-text = Missing task description for 'add' command.
+    Example: todo_cli add "Buy milk"
+    {"         "}some-command | todo_cli add -
+    {"         "}todo_cli add -i
+add-needs-terminal = 'add -i' needs a terminal to ask questions on.
+task-adding = Adding task: { $task }
+add-duplicate = Task { $number } is already on the list: { $task }
+add-duplicate-refused = Not adding '{ $task }': task { $number } is already on the list ({ $existing }).
+confirm-bump = Bump task { $number } instead of adding a new one?
+task-bumped = Bumped task { $number } to { $priority } priority: { $task }
+prompt-task = Task
+prompt-due = Due date (e.g. 2025-06-30, tomorrow, fri, +3d)
+prompt-priority = Priority (low, medium, high)
+prompt-tags = Tags (separated by spaces or commas)
+prompt-list = List (leave empty for the main list)
+paste-empty = The clipboard is empty; copy some text first.
+paste-failed = Could not read the clipboard: { $error }
+template-none = No templates yet. Add them to the [templates] table of the config file;
+    see 'todo_cli help template'.
+template-line = { $name } ({ $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    }){ $vars }
+template-unknown = There is no template called '{ $name }'. Templates: { $known }
+template-missing-var = The template needs a value for '{ $name }'; give it with --var { $name }=VALUE.
+template-var-invalid = '{ $input }' is not a variable. Write it as NAME=VALUE, like version=1.4.
+
+## Listing tasks
+
+list-empty = No tasks yet! Add one with the 'add' command.
+list-no-matches = No tasks match.
+list-header = --- To-Do List ---
+list-footer = ------------------
+plain-list-end = { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    } listed.
+plain-task = Task { $number }{ $details }: { $task }
+plain-done = done
+plain-done-today = done for today
+plain-priority-high = high priority
+plain-priority-medium = medium priority
+plain-priority-low = low priority
+plain-due = due { $when }
+plain-overdue = overdue, was due { $when }
+plain-today = today
+plain-tomorrow = tomorrow
+plain-yesterday = yesterday
+plain-at = { $day } at { $time }
+plain-project = in project { $project }
+plain-context = in context { $context }
+plain-tags = { $count ->
+        [one] tag
+       *[other] tags
+    } { $tags }
+plain-and = and
+plain-steps = { $done } of { $total } steps done
+plain-pinned = pinned
+plain-secret = secret
+plain-waiting = waiting for { $person }
+plain-habit = repeats { $schedule }, streak { $streak }
+plain-timing = timer running for { $time }
+plain-age = added { $days } days ago
+plain-blocked = blocked by { $count ->
+        [one] task
+       *[other] tasks
+    } { $tasks }
+task-due = (due { $date })
+watch-header = Every { $seconds }s, or when the list changes. Updated { $time }; Ctrl-C to stop.
+due-heading = { $bucket } ({ $count })
+due-overdue = Overdue
+due-today = Today
+due-tomorrow = Tomorrow
+due-this-week = This week
+due-later = Later
+due-none = No pending task has a due date.
+task-waiting = (waiting for { $person })
+task-status = ({ $status })
+task-blocked = (blocked by { $tasks })
+task-habit = ({ $schedule }, streak { $streak })
+show-status = Status
+show-id = ID
+show-hash = Hash
+show-owner = Added by
+show-added = Added
+show-added-ago = { $time } ({ $days ->
+        [0] today
+        [one] 1 day ago
+       *[other] { $days } days ago
+    })
+show-due = Due
+show-priority = Priority
+show-urgency = Urgency
+show-project = Project
+show-contexts = Contexts
+show-tags = Tags
+show-reminders = Reminders
+show-waiting = Waiting for
+show-depends = Depends on
+show-habit = Habit
+show-checklist = Checklist
+show-someday = Someday
+show-secret = Secret
+show-secret-locked = locked (--unlock to read)
+show-secret-unlocked = unlocked
+show-secret-wrong = locked (wrong passphrase)
+show-pinned = Pinned
+show-pinned-yes = yes
+show-someday-since = parked since { $date }
+show-waiting-since = { $person }, since { $date } ({ $days ->
+        [one] 1 day
+       *[other] { $days } days
+    })
+show-notes = Notes
+show-comments = Comments
+
+## Changing tasks
+
+task-completed = Completed task { $number }: { $task }
+task-already-completed = Task { $number } was already completed.
+habit-daily = daily
+habit-weekly = weekly
+habit-every = every { $days } days
+habit-weekdays = every weekday
+habit-every-business = every { $days } business days
+habit-every-invalid = '{ $input }' is not a schedule. Use daily, weekly, weekdays, or a number of days, weeks or business days like 3d, 2w or 2bd.
+habit-done = Done habit { $number } for today: { $task } (streak { $streak }, best { $best })
+habit-already-done = Habit { $number } was already done today.
+habit-none = No habits yet. Add one with 'add --habit daily'.
+habit-line = { $number }. { $task }: { $details }
+habit-streaks = { $schedule }, streak { $streak } (best { $best }), last done { $last }
+habit-never = never
+habit-set = Task { $number } is now a habit ({ $schedule }): { $task }
+habit-stopped = Task { $number } is no longer a habit: { $task }
+habit-not = Task { $number } isn't a habit.
+task-unblocked = Unblocked task { $number }: { $task }
+depend-none = Task { $number } doesn't depend on any other task.
+depend-line = { $number }. { $task } ({ $status })
+depend-gone = id { $id } (removed)
+depend-added = Task { $number } now depends on task { $other }: { $task }
+depend-removed = Task { $number } no longer depends on task { $other }.
+depend-cleared = Task { $number } no longer depends on any task.
+depend-not = Task { $number } doesn't depend on task { $other }.
+depend-self = A task can't depend on itself.
+depend-cycle = Task { $other } already depends on task { $number }, so task { $number } can't depend on it.
+next-header = Next up:
+next-none = Nothing can be started right now: every pending task is blocked, waiting or parked.
+status-changed = Task { $number } is now { $status }: { $task }
+status-unchanged = Task { $number } is already { $status }.
+status-current = Task { $number } is { $status }: { $task }
+status-invalid = '{ $status }' is not a status. A status is a single word, like doing.
+status-unknown = '{ $status }' is not in the workflow. Use one of: { $statuses }, or add it to the 'workflow' setting.
+status-no-start = The workflow has no status between todo and done to start a task in. Add one with 'config set workflow todo,doing,done'.
+task-deleted = Deleted task { $number }: { $task }
+task-updated = Updated task { $number }: { $task }
+task-text-empty = Task text cannot be empty.
+task-text-too-long = The task text is { $length } characters long; the limit is { $max }.
+    Put the details in the notes, or raise max_task_length.
+priority-invalid = '{ $input }' is not a priority. Use low, medium or high (or l, m, h).
+tag-invalid = '{ $tag }' is not a tag. Tags are single words, like 'work' or '#home'.
+project-invalid = '{ $project }' is not a project. Projects are single words, like 'garden' or '+garden'.
+context-invalid = '{ $context }' is not a context. Contexts are single words, like 'phone' or '@phone'.
+confirm-delete = Delete task { $number }: { $task }?
+clear-nothing = Nothing to clear.
+confirm-clear-all = Remove { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    }?
+confirm-clear-completed = Remove { $count ->
+        [one] 1 completed task
+       *[other] { $count } completed tasks
+    }?
+cleared-all = Removed { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    }.
+cleared-completed = Removed { $count ->
+        [one] 1 completed task
+       *[other] { $count } completed tasks
+    }.
+check-empty = Task { $number } has no checklist yet. Add an item with 'check { $number } add TEXT'.
+check-added = Added item { $item } to task { $number }: { $text }
+check-done = Checked off item { $item } of task { $number }.
+check-undone = Item { $item } of task { $number } is no longer done.
+check-removed = Removed item { $item } from task { $number }.
+check-progress = { $done }/{ $total } done.
+check-missing-item = Give the numbers of the checklist items.
+check-no-item = Task { $number } has no checklist item '{ $item }'; its items are numbered 1 to { $count }.
+check-unknown-action = Unknown checklist action '{ $action }'. Use list, add, done, undo or remove.
+comment-added = Commented on task { $number }: { $task }
+comment-empty = A comment can't be empty.
+comments-none = No one has commented on task { $number } yet.
+
+## Confirmation
+
+# The answer hint shown after a question, and the answers that mean yes.
+confirm-choices = [y/N]
+confirm-yes = y, yes
+confirm-refused = Refusing to continue without confirmation.
+    Re-run with --yes to confirm when not running interactively.
+
+## Editing in $EDITOR
+
+edit-header = # Editing task { $number }. Lines starting with '#' are ignored.
+    # Save and quit to apply your changes. Delete everything to cancel.
+edit-no-changes = No changes made.
+edit-cancelled = Edit cancelled.
+edit-kept = { $error }
+    Your edits were kept in { $path }
+editor-start-failed = Could not start editor '{ $editor }': { $error }
+editor-failed = Editor '{ $editor }' exited with { $status }. No changes made.
+edit-not-string = '{ $field }' must be a string
+edit-tags-not-list = 'tags' must be a list of strings
+edit-contexts-not-list = 'contexts' must be a list of strings
+edit-checklist-not-list = 'checklist' must be a list of strings, like "[ ] book hotel"
+edit-reminders-not-list = 'reminders' must be a list of times, like "2025-06-30 09:00"
+edit-completed-not-bool = 'completed' must be true or false
+edit-unknown-field = Unknown field '{ $field }'
+
+## Changing many tasks
+
+modify-no-filter = Say which tasks to change, with text or options like --tag, or use --all.
+modify-no-edits = Nothing to change: use --replace, --set, --add-tag or --remove-tag.
+modify-replace-invalid = '{ $input }' is not a replacement. Write it as /old/new/.
+modify-set-invalid = '{ $input }' is not a field to set. Write it as field:value, like prio:high or due:fri.
+modify-unknown-field = Can't set '{ $field }'; use priority, due or project.
+modify-done = Changed { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    } of { $matched } matching.
+
+## Dry runs
+
+dry-run-header = Dry run: no changes were saved.
+dry-run-nothing = (nothing would change)
+dry-run-notify = Dry run: nothing was sent. It would announce:
+dry-run-encrypted = would be encrypted
+dry-run-decrypted = would no longer be secret
+dry-run-sealed = (its encrypted text or notes would change)
+dry-run-config = Dry run: would set { $key } = { $value }
+dry-run-unset = Dry run: would remove { $key }
+dry-run-rpc = --dry-run can't be used with --rpc.
+dry-run-mcp = --dry-run can't be used with mcp.
+
+## Notifications and the reminder daemon
+
+notify-nothing = Nothing is due.
+notify-failed = Notification failed: { $error }
+notify-overdue-one = 1 task overdue
+notify-due-today = { $count ->
+        [one] 1 task due today
+       *[other] { $count } tasks due today
+    }
+notify-due-overdue = { $count } tasks due, { $overdue } overdue
+notify-overdue-since = { $number }. { $task } (overdue since { $date })
+reminder-coming-up = { $count ->
+        [one] 1 task coming up
+       *[other] { $count } tasks coming up
+    }
+reminder-line = { $number }. { $task } (due { $days ->
+        [1] tomorrow
+       *[other] in { $days } days
+    })
+reminder-title = { $count ->
+        [one] Reminder
+       *[other] { $count } reminders
+    }
+reminder-set = Task { $number } will remind you at { $time }: { $task }
+reminder-past = { $time } has already passed; reminders must be in the future.
+reminders-cleared = Removed the reminders of task { $number }: { $task }
+reminders-none = Task { $number } has no reminders.
+wait-set = Task { $number } is waiting for { $person }: { $task }
+wait-cleared = Task { $number } is no longer waiting: { $task }
+wait-not-waiting = Task { $number } isn't waiting on anyone.
+wait-missing-for = Say who the task is waiting for with --for, or stop waiting with --clear.
+waiting-person-empty = Who a task is waiting for can't be empty.
+someday-set = Parked task { $number } for someday: { $task }
+someday-cleared = Task { $number } is back on the list: { $task }
+someday-already = Task { $number } is already parked for someday.
+someday-not-parked = Task { $number } isn't parked for someday.
+pin-set = Pinned task { $number }: { $task }
+pin-cleared = Unpinned task { $number }: { $task }
+pin-already = Task { $number } is already pinned.
+pin-not-pinned = Task { $number } isn't pinned.
+review-nothing = No someday/maybe tasks are due for a look.
+review-someday = { $count ->
+        [one] 1 someday/maybe task to look at again:
+       *[other] { $count } someday/maybe tasks to look at again:
+    }
+review-line = { $number }. { $task } (parked since { $date })
+confirm-activate = Bring it back into the list?
+waiting-none = Nothing is waiting on anyone.
+waiting-line = { $number }. { $task } ({ $person }, { $days ->
+        [one] 1 day
+       *[other] { $days } days
+    })
+nudge-title = { $count ->
+        [one] 1 task waiting too long
+       *[other] { $count } tasks waiting too long
+    }
+nudge-line = { $number }. { $task } (waiting for { $person } for { $days } days)
+daemon-started = Started the reminder daemon (pid { $pid }).
+    Its output goes to { $path }
+daemon-stopped = Stopped the reminder daemon (pid { $pid }).
+daemon-running = The reminder daemon is running (pid { $pid }).
+daemon-not-running = The reminder daemon is not running.
+daemon-already-running = The reminder daemon is already running (pid { $pid }).
+daemon-watching = Reminder daemon running (pid { $pid }), watching { $path }
+daemon-exited = The reminder daemon exited with { $status }. See { $path }
+
+## Hooks and webhooks
+
+hook-failed = hook '{ $hook }' exited with { $status }.
+hook-run-failed = could not run hook '{ $hook }': { $error }
+webhook-status = webhook { $url } responded with { $status }.
+webhook-failed = could not send webhook to { $url }: { $error }
+
+## Other commands
+
+api-token = API token: { $token }
+accept-failed = Could not accept a connection: { $error }
+token-failed = Could not generate a token ({ $error }); use --token.
+sync-listening = Syncing the { $list } list as { $name } on port { $port }.
+sync-done = Synced with { $peer }: { $here ->
+        [one] 1 change
+       *[other] { $here } changes
+    } here, { $there } there.
+sync-conflicts = { $count ->
+        [one] 1 task was
+       *[other] { $count } tasks were
+    } changed on both sides; where both changed the same field, this side's change was kept.
+sync-failed = Could not sync with { $peer }: { $error }
+sync-token-missing = Instances must share a token to sync: set TODO_SYNC_TOKEN (or --token) to the same secret on every machine, or pass --insecure to let anyone on the network sync.
+sync-incomplete = Could not sync with { $count ->
+        [one] 1 instance
+       *[other] { $count } instances
+    }.
+sync-no-peers = No other instances are syncing this list on the network.
+sync-browse-failed = Could not look for other instances: { $error }
+sync-announce-failed = Other instances can't find this one ({ $error }); sync with it using --peer.
+sync-other-list = This instance syncs the { $list } list.
+store-id-taken = A task with id { $id } already exists.
+rpc-with-command = --rpc can't be combined with a command ('{ $command }').
+script-not-found = No script named '{ $name }'.
+config-set = Set { $key } = { $value }
+config-not-set = Config key '{ $key }' is not set.
+config-unknown-key = Unknown config key '{ $key }'.
+log-file-failed = Could not open the log file { $path }: { $error }
+history-header = History of '{ $task }' (id { $id }):
+history-empty = No changes to this task have been recorded.
+history-added = added by { $user } via { $via }
+history-updated = changed by { $user } via { $via }
+history-removed = removed by { $user } via { $via }
+history-command = { $what }, running '{ $command }'
+history-list-empty = No changes to this list have been recorded.
+history-unset = (none)
+heatmap-total = { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    } completed in the last { $weeks } weeks
+heatmap-legend = Less { $shades } More
+heatmap-plain-week = Week of { $date }: { $count } completed
+heatmap-no-weeks = --weeks must be 1 or more.
+holidays-none = No holidays are set. Choose them with 'config set holidays us' (or gb, de, fr, es, or an .ics file).
+holidays-empty = No holidays in that time.
+holidays-bad-year = { $year } is not a year that can be shown.
+holidays-unreadable = Could not read the holidays in { $path }: { $error }
+stats-total = Tasks: { $total } ({ $pending } pending, { $completed } completed, { $overdue } overdue)
+stats-week = Last 7 days: { $added } added, { $completed } completed
+stats-by-tag = By tag:
+stats-by-project = By project:
+stats-count = { $pending } pending, { $completed } completed
+stats-last-used = (last used { $date })
+tags-none = No task has a tag yet.
+tag-renamed = Renamed #{ $from } to #{ $to } on { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    }.
+tag-merged = Merged #{ $from } into #{ $to } on { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    }.
+tag-same = Both tags are '{ $tag }'.
+tag-unused = No task is tagged '{ $tag }'.
+tag-in-use = Some tasks are tagged '{ $to }' already. To fold '{ $from }' into it, use 'tag merge { $from } { $to }'.
+snapshot-name-invalid = '{ $name }' is not a snapshot name. Use letters, digits, '-', '_' and '.'.
+snapshot-exists = There is already a snapshot called '{ $name }'.
+snapshot-not-found = There is no snapshot called '{ $name }'. 'snapshots' lists them.
+snapshot-taken = Saved snapshot '{ $name }' of { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    }.
+snapshot-dry-run = Dry run: would save snapshot '{ $name }' of { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    }.
+snapshot-tasks = { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    }
+snapshots-none = No snapshots yet. 'snapshot' takes one.
+confirm-restore = Replace the list's { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    } with snapshot '{ $name }'?
+restore-nothing = The list already matches snapshot '{ $name }'.
+restored = Restored snapshot '{ $name }', changing { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    }.
+diff-none = No differences.
+diff-summary = { $added } added, { $removed } removed, { $changed } changed
+undo-no-changes = No changes to this list have been recorded.
+undo-unknown = No change '{ $change }' was recorded for this list. 'history' shows the change ids.
+undo-conflict-removed = id:{ $id } '{ $task }' has been removed since.
+undo-conflict-changed = id:{ $id } '{ $task }': { $field } has been changed since.
+undo-conflict-unrecorded = id:{ $id } '{ $task }': the change was logged without what undoing it needs.
+undo-conflict-sealed = id:{ $id } '{ $task }' was made secret; 'secret --clear' takes that back.
+undo-conflicts = Change { $change } can't be undone because of later changes; nothing was changed.
+undo-nothing = Change { $change } is already undone.
+undone = Undid change { $change }, changing { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    }.
+trash-empty = The trash is empty.
+trash-not-found = No task with id '{ $id }' is in the trash. 'trash list' shows them.
+trash-on-list = id:{ $id } '{ $task }' is on the list already.
+trash-restored = Restored id:{ $id } as task { $number }: { $task }
+confirm-empty-trash = Remove { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    } in the trash for good?
+trash-emptied = Removed { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    } from the trash for good.
+trash-dry-run = Dry run: would remove { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    } from the trash for good.
+task-archived = Moved it to the archive.
+prune-no-age = Say how long ago tasks must have been completed, e.g. --completed-before 90d, or set prune_after.
+prune-nothing = No task was completed more than { $days } days ago.
+pruned-archived = Archived { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    } completed more than { $days } days ago.
+pruned-deleted = Removed { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    } completed more than { $days } days ago.
+estimate-set = Task { $number } ({ $task }) is expected to take { $time }.
+estimate-cleared = Task { $number } ({ $task }) no longer has an estimate.
+duration-invalid = '{ $input }' is not a duration (expected e.g. 45m, 2h or 1h30m)
+time-group-invalid = '{ $input }' is not something to group by (expected task, project or tag)
+time-started = Started timing task { $number }: { $task }
+time-already-running = Task { $number } ({ $task }) has been timed since { $time }.
+time-stopped = Stopped timing task { $number } ({ $task }) after { $time }; { $total } spent on it in all.
+time-not-running = No task is being timed.
+time-running = Timing task { $number } ({ $task }): { $time } since { $since }
+time-logged = Logged { $time } on task { $number } ({ $task }); { $total } spent on it in all.
+time-log-empty = The time logged has to be at least a minute.
+time-task-completed = Task { $number } ({ $task }) is already completed.
+time-no-estimates = No task has an estimate. Set one with 'estimate TASK 2h'.
+time-no-project = (no project)
+estimates-tag = Tag
+time-no-tag = (no tag)
+time-report-until = Time spent up to { $date }
+time-report-hours = Time
+time-report-tasks = Tasks
+estimates-task = Task
+estimates-project = Project
+estimates-estimate = Estimate
+estimates-actual = Actual
+estimates-total = Total
+estimates-summary = { $side ->
+        [over] The work took { $percent }% of its estimates: { $time } more than planned.
+        [under] The work took { $percent }% of its estimates: { $time } less than planned.
+       *[exact] The work took exactly as long as estimated.
+    }
+task-timing = ⏱ { $time }
+show-estimate = Estimate
+show-tracked = Time spent
+show-tracked-sessions = { $time } in { $count ->
+        [one] 1 session
+       *[other] { $count } sessions
+    }
+show-timer-running = Timer running since { $time }
+time-spent-on = Time spent on { $date }
+time-spent-between = Time spent from { $first } to { $last }
+time-spent-none = No time was tracked.
+push-no-service = No time service is set up. Set time_service to toggl or harvest.
+push-no-account = Pushing to { $service } needs time_account and time_token.
+push-id-invalid = '{ $id }' is not a valid id: ids are numbers
+push-no-harvest-project = Project '{ $project }' has no Harvest PROJECT_ID/TASK_ID in [time_projects].
+push-entry = Sent { $time } ({ $duration }): { $task }
+push-entry-dry-run = Would send { $time } ({ $duration }): { $task }
+push-harvest-needs-project = '{ $task }' has no project, which Harvest needs.
+push-rejected = the service answered { $status }: { $body }
+push-failed = Could not send time on '{ $task }': { $error }
+push-done = Sent { $count ->
+        [one] 1 time entry
+       *[other] { $count } time entries
+    } to { $service }.
+push-some-failed = { $count ->
+        [one] 1 session was
+       *[other] { $count } sessions were
+    } not sent; they will be tried again next time.
+search-regex-invalid = Invalid regular expression '{ $pattern }': { $error }.
+query-is-invalid = '{ $input }' is not something is: asks about (expected pending, done, overdue, waiting, someday or habit).
+query-unknown-field = Unknown condition '{ $field }:' (expected prio, due, due.before, due.after, tag, project, context, status, owner or is).
+query-unexpected = Unexpected '{ $token }' in the expression.
+query-unclosed-quote = A quote in the expression is never closed.
+query-unclosed-paren = A parenthesis in the expression is never closed.
+query-missing = Nothing follows '{ $after }' in the expression.
+view-format-invalid = '{ $input }' is not a view format (expected list, compact or json).
+view-unknown = There is no view called '{ $name }'. Views: { $known }
+view-name-taken = '{ $name }' is already a command or alias, so a view can't be called that.
+view-saved = Saved view '{ $name }'.
+view-deleted = Deleted view '{ $name }'.
+view-none = No views yet. Save one with 'view save NAME EXPRESSION'.
+context-unknown = There is no context called '{ $name }'. Contexts: { $known }
+context-note = Context '{ $name }' is on ('context none' turns it off).
+context-set = Switched to context '{ $name }'.
+context-cleared = No context is on now.
+context-current = Context: { $name }
+context-off = No context is on.
+context-defined = Saved context '{ $name }'.
+context-deleted = Deleted context '{ $name }'.
+context-name-missing = Give the context's name.
+context-expression-missing = Give the expression of context '{ $name }', e.g. +work.
+context-name-reserved = '{ $name }' is a word of the 'context' command, so a context can't be called that.
+context-none = No contexts yet. Define one with 'context define NAME EXPRESSION'.
+move-moved = Task { $number } is now { $position } of { $count } in the manual order: { $task }
+move-unchanged = Task { $number } is already { $position } of { $count } in the manual order: { $task }
+move-no-place = Say where to move it: --before TASK, --after TASK, --top or --bottom.
+import-format-missing = Say what the file is: --markdown or --ics.
+import-unreadable = Could not read { $file }: { $error }
+import-nothing = No checklist items in { $file }.
+import-no-events = No events in { $file } in those days.
+import-location = Location: { $location }
+import-skipped = Skipping '{ $task }', which is already on the list.
+import-done = Imported { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    } from { $file }.
+secret-set = Made task {$number} secret: {$task}
+secret-cleared = Task {$number} is no longer secret: {$task}
+secret-already = Task {$number} is already secret.
+secret-not-secret = Task {$number} is not secret.
+secret-edit = Task {$number} is secret; make it plain with 'secret {$number} --clear' before editing it.
+secret-prompt = Passphrase
+secret-prompt-again = Passphrase again
+secret-mismatch = The passphrases don't match.
+secret-needs-passphrase = A passphrase is needed: run this at a terminal or set TODO_PASSPHRASE.
+secret-wrong-passphrase = That passphrase doesn't open the secret tasks.
+dashboard-serving = Serving the dashboard on {$url}
+dashboard-title = To-Do List
+dashboard-title-list = To-Do List: {$list}
+dashboard-text = Text
+dashboard-tag = Tag
+dashboard-project = Project
+dashboard-context = Context
+dashboard-filter = Filter
+dashboard-clear = Clear
+dashboard-empty = Nothing to do.
+dashboard-updated = Updated {$time}
+qr-too-long = That is too much for a QR code ({$length} bytes; 2331 at most).
+dashboard-task-missing = No task has that id.
+dashboard-back = All tasks
+ingest-source-missing = Say where to read mail from: --imap, --mbox FILE or --maildir DIR.
+ingest-imap-missing = No mailbox is set; set imap_url, e.g. imaps://mail.example.com/INBOX.
+ingest-unreadable = Could not read mail from {$source}: {$error}
+ingest-no-subject = (no subject)
+ingest-nothing = No new messages to add from {$source}.
+ingest-done = Added { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    } from {$source}.
+mail-smtp-missing = No mail server is set; set smtp_url, e.g. smtps://smtp.example.com.
+mail-from-missing = Say who the mail is from: set mail_from, e.g. "Ana <ana@example.com>".
+mail-failed = Could not send the mail: {$error}
+mail-subject = { $count ->
+        [one] 1 task outstanding
+       *[other] { $count } tasks outstanding
+    }
+mail-intro = { $count ->
+        [one] Here is the 1 task
+       *[other] Here are the { $count } tasks
+    } outstanding as of {$date}:
+mail-sent = Sent { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    } to {$to}.
+slack-missing = Slack isn't set up; set slack_webhook_url to an incoming webhook, or slack_token and slack_channel.
+slack-channel-missing = Say where to post: set slack_channel to a channel, e.g. #team, or a member ID for a direct message.
+slack-failed = Could not post to Slack: {$error}
+slack-posted = Posted the summary to Slack.
+slack-alerted = Alerted Slack of { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    }.
+slack-alert-overdue = :red_circle: Overdue since {$date}: {$task}
+slack-alert-high = :exclamation: High priority: {$task}
+slack-summary-title = *Tasks for {$date}*
+slack-summary-title-list = *Tasks on {$list} for {$date}*
+slack-summary-overdue = Overdue
+slack-summary-today = Due today
+slack-summary-high = High priority
+slack-summary-clear = Nothing is overdue, due today or high priority.
+slack-summary-pending = { $count ->
+        [one] 1 task pending
+       *[other] { $count } tasks pending
+    }
+discord-missing = The list {$list} has no Discord webhook; set discord_webhooks.{$list}.
+discord-failed = Could not post to Discord: {$error}
+discord-posted = Posted { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    } coming due to Discord.
+discord-done = :white_check_mark: Done: {$task}
+discord-done-by = :white_check_mark: {$person} completed {$task}
+discord-due-title = **{ $count ->
+        [one] 1 task coming due
+       *[other] { $count } tasks coming due
+    }**
+discord-due-overdue = :red_circle: {$task}, overdue since {$date}
+discord-due-today = :orange_circle: {$task}, due today
+discord-due-on = :calendar: {$task}, due {$date}
+telegram-token-missing = No bot token is set; make a bot with @BotFather and set telegram_token.
+telegram-no-chats = No chat may use the bot yet: write to it for your chat ID, then set telegram_chats.
+telegram-running = Answering as @{$bot}; press Ctrl-C to stop.
+telegram-failed = Telegram: {$error}
+telegram-not-allowed = This chat may not use the bot. To allow it, add its ID, {$chat}, to telegram_chats.
+telegram-help = Send a task to add it, or:
+    /add TEXT to add a task, e.g. /add milk #shopping due:fri
+    /list to see the pending tasks
+    /done TASK to complete one, by number or part of its text
+telegram-unknown = I don't know /{$command}; /help lists what I do.
+telegram-add-missing = What should I add? E.g. /add call the dentist
+telegram-done-missing = Which task? E.g. /done 3
+telegram-added = Added {$number}. {$task}
+telegram-done = Done: {$number}. {$task}
+telegram-already-done = {$task} is already done.
+telegram-empty = Nothing to do.
+telegram-more = …and { $count } more.
+projects-none = No task is in a project yet.
+projects-percent = { $percent }% done
+projects-next-due = next due { $date }
+projects-main-list = (main list)
+board-todo = To do
+board-doing = Doing
+board-waiting = Waiting
+board-someday = Someday
+board-done = Done
+board-cancelled = Cancelled
+board-no-tag = No tag
+board-no-project = No project
+board-plain-column = { $column }, { $count ->
+        [one] 1 task
+       *[other] { $count } tasks
+    }:
+board-plain-card = Task { $number }: { $task }
+bar-overdue = { $count } overdue
+bar-today = { $count } due today
+bar-pending = { $count } pending
+segment-format-invalid = '{ $input }' is not a segment format. Use starship or powerline.
+board-grouping-invalid = '{ $input }' is not a way to group the board. Use status, tag or project.
+board-unknown-column = There is no '{ $column }' column. Use one of: { $columns }.
+board-moved = Moved task { $number } to { $column }: { $task }
+board-no-such-tag = The task isn't tagged '{ $tag }'.
+board-waiting-for = Say who the task is waiting for with --for.
+board-which-tag = The task has several tags ({ $tags }); say which to move it from with --from.
+reindexed = Rebuilt the index for { $path }
+reindex-none = { $path } has no index to rebuild.
+man-written = Wrote { $count } man pages to { $dir }
+
+## Errors about tasks
+
+error-missing-query = Missing task number or text.
+error-invalid-number = Task number must be 1 or greater.
+error-no-such-number = No task found with number { $number }.
+error-stale-number = Task { $number } as last listed has since been removed; nothing was changed.
+    Run 'list' again to see the current numbers.
+error-no-match = No task matches '{ $query }'.
+error-ambiguous = '{ $query }' matches { $count } tasks:
+error-ambiguous-hint = Use the task number, or more of the text.
+
+error-parse = Could not read { $path }: { $error } (line { $line }, column { $column }).
+    The file might be corrupted; fix it by hand or restore a backup.
+task-skipped = Skipping entry { $number } of { $path }, which is not a valid task: { $error }
+tasks-quarantined = Moved { $count ->
+        [one] 1 invalid entry
+       *[other] { $count } invalid entries
+    } from { $path } to { $quarantine }.
+error-read-only = { $path } is open read-only, so nothing was saved.
+error-conflict = { $path } was changed by another program after it was read, so nothing was saved.
+    Run the command again to work on the new list.
+error-merge-failed = Could not merge: the task '{ $task }' was also changed or removed by another program. Nothing was saved.
+list-reloaded = Another program changed the list meanwhile; its changes were read in.
+edit-task-gone = '{ $task }' was deleted by another program while you were editing it, so your changes were not saved.
+edit-merged = Another program changed this task meanwhile; where you both changed the same field, your change was kept.
+list-changed = { $path } was changed by another program after it was read.
+confirm-merge = Apply your changes to the new list?
+
+## Dates
+
+# Words accepted for due dates, besides the English ones, which always work.
+date-today = today, tod
+date-tomorrow = tomorrow, tom
+date-yesterday = yesterday
+date-next-week = next week
+date-weekdays = Monday, Tuesday, Wednesday, Thursday, Friday, Saturday, Sunday
+date-months = January, February, March, April, May, June, July, August, September, October, November, December
+date-invalid = '{ $input }' is not a date. Use YYYY-MM-DD, today, tomorrow, a weekday, next week, or +3d/+3bd/+2w/+1m.
+date-too-many-business-days = A date can be moved by at most { $max } business days.
+date-no-business-day = No business day in the { $days } days from { $date }; check the 'holidays' setting.
+reminder-invalid = '{ $input }' is not a time. Use a date and time of day (tomorrow 9am, fri 14:30), a time alone, or +30min/+2h.
@@ -0,0 +1,737 @@
+# Mensajes en español.
+
+## General
+
+error = Error: { $error }
+warning = Aviso: { $message }
+input-closed = Entrada cerrada.
+cancelled = Cancelado.
+status-completed = completada
+status-pending = pendiente
+status-doing = en curso
+status-cancelled = cancelada
+
+## Añadir tareas
+
+add-missing-text = Falta la descripción de la tarea para el comando 'add'.
+    Ejemplo: todo_cli add "Comprar leche"
+    {"         "}algún-comando | todo_cli add -
+    {"         "}todo_cli add -i
+add-needs-terminal = 'add -i' necesita una terminal para hacer preguntas.
+task-adding = Añadiendo tarea: { $task }
+add-duplicate = La tarea { $number } ya está en la lista: { $task }
+add-duplicate-refused = No se añade '{ $task }': la tarea { $number } ya está en la lista ({ $existing }).
+confirm-bump = ¿Subir la prioridad de la tarea { $number } en lugar de añadir una nueva?
+task-bumped = La tarea { $number } pasa a prioridad { $priority }: { $task }
+prompt-task = Tarea
+prompt-due = Fecha límite (p. ej. 2025-06-30, mañana, vie, +3d)
+prompt-priority = Prioridad (low, medium, high)
+prompt-tags = Etiquetas (separadas por espacios o comas)
+prompt-list = Lista (vacío para la lista principal)
+paste-empty = El portapapeles está vacío; copia algún texto primero.
+paste-failed = No se pudo leer el portapapeles: { $error }
+template-none = Aún no hay plantillas. Añádelas a la tabla [templates] del archivo de configuración;
+    consulta 'todo_cli help template'.
+template-line = { $name } ({ $count ->
+        [one] 1 tarea
+       *[other] { $count } tareas
+    }){ $vars }
+template-unknown = No hay ninguna plantilla llamada '{ $name }'. Plantillas: { $known }
+template-missing-var = La plantilla necesita un valor para '{ $name }'; dáselo con --var { $name }=VALOR.
+template-var-invalid = '{ $input }' no es una variable. Escríbela como NOMBRE=VALOR, como version=1.4.
+
+## Listar tareas
+
+list-empty = ¡Aún no hay tareas! Añade una con el comando 'add'.
+list-no-matches = Ninguna tarea coincide.
+list-header = --- Lista de tareas ---
+list-footer = ----------------------
+plain-list-end = { $count ->
+        [one] 1 tarea
+       *[other] { $count } tareas
+    } en la lista.
+plain-task = Tarea { $number }{ $details }: { $task }
+plain-done = completada
+plain-done-today = hecha hoy
+plain-priority-high = prioridad alta
+plain-priority-medium = prioridad media
+plain-priority-low = prioridad baja
+plain-due = vence { $when }
+plain-overdue = vencida, vencía { $when }
+plain-today = hoy
+plain-tomorrow = mañana
+plain-yesterday = ayer
+plain-at = { $day } a las { $time }
+plain-project = en el proyecto { $project }
+plain-context = en el contexto { $context }
+plain-tags = { $count ->
+        [one] etiqueta
+       *[other] etiquetas
+    } { $tags }
+plain-and = y
+plain-steps = { $done } de { $total } pasos hechos
+plain-pinned = fijada
+plain-secret = secreta
+plain-waiting = esperando a { $person }
+plain-habit = se repite { $schedule }, racha { $streak }
+plain-timing = cronómetro en marcha desde hace { $time }
+plain-age = añadida hace { $days } días
+plain-blocked = bloqueada por { $count ->
+        [one] la tarea
+       *[other] las tareas
+    } { $tasks }
+task-due = (vence { $date })
+watch-header = Cada { $seconds } s, o cuando cambia la lista. Actualizado { $time }; Ctrl-C para salir.
+due-heading = { $bucket } ({ $count })
+due-overdue = Vencidas
+due-today = Hoy
+due-tomorrow = Mañana
+due-this-week = Esta semana
+due-later = Más adelante
+due-none = Ninguna tarea pendiente tiene fecha de vencimiento.
+task-waiting = (esperando a { $person })
+task-status = ({ $status })
+task-blocked = (bloqueada por { $tasks })
+task-habit = ({ $schedule }, racha { $streak })
+show-status = Estado
+show-id = ID
+show-hash = Hash
+show-owner = Añadida por
+show-added = Añadida
+show-added-ago = { $time } ({ $days ->
+        [0] hoy
+        [one] hace 1 día
+       *[other] hace { $days } días
+    })
+show-due = Vence
+show-priority = Prioridad
+show-urgency = Urgencia
+show-project = Proyecto
+show-contexts = Contextos
+show-tags = Etiquetas
+show-reminders = Recordatorios
+show-waiting = Esperando a
+show-depends = Depende de
+show-habit = Hábito
+show-checklist = Lista de pasos
+show-someday = Algún día
+show-secret = Secreta
+show-secret-locked = bloqueada (--unlock para leerla)
+show-secret-unlocked = desbloqueada
+show-secret-wrong = bloqueada (frase de paso incorrecta)
+show-pinned = Fijada
+show-pinned-yes = sí
+show-someday-since = aparcada desde el { $date }
+show-waiting-since = { $person }, desde { $date } ({ $days ->
+        [one] 1 día
+       *[other] { $days } días
+    })
+show-notes = Notas
+show-comments = Comentarios
+
+## Cambiar tareas
+
+task-completed = Tarea { $number } completada: { $task }
+task-already-completed = La tarea { $number } ya estaba completada.
+habit-daily = a diario
+habit-weekly = semanal
+habit-every = cada { $days } días
+habit-weekdays = cada día laborable
+habit-every-business = cada { $days } días laborables
+habit-every-invalid = '{ $input }' no es una frecuencia. Usa daily, weekly, weekdays, o un número de días, semanas o días laborables como 3d, 2w o 2bd.
+habit-done = Hábito { $number } hecho hoy: { $task } (racha { $streak }, mejor { $best })
+habit-already-done = El hábito { $number } ya se hizo hoy.
+habit-none = Aún no hay hábitos. Añade uno con 'add --habit daily'.
+habit-line = { $number }. { $task }: { $details }
+habit-streaks = { $schedule }, racha { $streak } (mejor { $best }), hecho por última vez { $last }
+habit-never = nunca
+habit-set = La tarea { $number } es ahora un hábito ({ $schedule }): { $task }
+habit-stopped = La tarea { $number } ya no es un hábito: { $task }
+habit-not = La tarea { $number } no es un hábito.
+task-unblocked = Tarea { $number } desbloqueada: { $task }
+depend-none = La tarea { $number } no depende de ninguna otra.
+depend-line = { $number }. { $task } ({ $status })
+depend-gone = id { $id } (eliminada)
+depend-added = La tarea { $number } depende ahora de la tarea { $other }: { $task }
+depend-removed = La tarea { $number } ya no depende de la tarea { $other }.
+depend-cleared = La tarea { $number } ya no depende de ninguna tarea.
+depend-not = La tarea { $number } no depende de la tarea { $other }.
+depend-self = Una tarea no puede depender de sí misma.
+depend-cycle = La tarea { $other } ya depende de la tarea { $number }, así que la tarea { $number } no puede depender de ella.
+next-header = Lo siguiente:
+next-none = Ahora mismo no se puede empezar nada: todas las tareas pendientes están bloqueadas, esperando o aparcadas.
+status-changed = La tarea { $number } está ahora { $status }: { $task }
+status-unchanged = La tarea { $number } ya está { $status }.
+status-current = La tarea { $number } está { $status }: { $task }
+status-invalid = '{ $status }' no es un estado. Un estado es una sola palabra, como doing.
+status-unknown = '{ $status }' no está en el flujo de trabajo. Usa uno de: { $statuses }, o añádelo al ajuste 'workflow'.
+status-no-start = El flujo de trabajo no tiene ningún estado entre todo y done en el que empezar una tarea. Añade uno con 'config set workflow todo,doing,done'.
+task-deleted = Tarea { $number } eliminada: { $task }
+task-updated = Tarea { $number } actualizada: { $task }
+task-text-empty = El texto de la tarea no puede estar vacío.
+task-text-too-long = El texto de la tarea tiene { $length } caracteres; el límite es { $max }.
+    Pon los detalles en las notas, o aumenta max_task_length.
+priority-invalid = '{ $input }' no es una prioridad. Usa low, medium o high (o l, m, h).
+tag-invalid = '{ $tag }' no es una etiqueta. Las etiquetas son una sola palabra, como 'trabajo' o '#casa'.
+project-invalid = '{ $project }' no es un proyecto. Los proyectos son una sola palabra, como 'jardín' o '+jardín'.
+context-invalid = '{ $context }' no es un contexto. Los contextos son una sola palabra, como 'teléfono' o '@teléfono'.
+confirm-delete = ¿Eliminar la tarea { $number }: { $task }?
+clear-nothing = No hay nada que borrar.
+confirm-clear-all = ¿Eliminar { $count ->
+        [one] 1 tarea
+       *[other] { $count } tareas
+    }?
+confirm-clear-completed = ¿Eliminar { $count ->
+        [one] 1 tarea completada
+       *[other] { $count } tareas completadas
+    }?
+cleared-all = { $count ->
+        [one] Se eliminó 1 tarea.
+       *[other] Se eliminaron { $count } tareas.
+    }
+cleared-completed = { $count ->
+        [one] Se eliminó 1 tarea completada.
+       *[other] Se eliminaron { $count } tareas completadas.
+    }
+check-empty = La tarea { $number } aún no tiene lista de pasos. Añade uno con 'check { $number } add TEXTO'.
+check-added = Se añadió el paso { $item } a la tarea { $number }: { $text }
+check-done = Paso { $item } de la tarea { $number } hecho.
+check-undone = El paso { $item } de la tarea { $number } ya no está hecho.
+check-removed = Se quitó el paso { $item } de la tarea { $number }.
+check-progress = { $done }/{ $total } hechos.
+check-missing-item = Indica los números de los pasos.
+check-no-item = La tarea { $number } no tiene ningún paso '{ $item }'; sus pasos van del 1 al { $count }.
+check-unknown-action = Acción desconocida '{ $action }'. Usa list, add, done, undo o remove.
+comment-added = Comentario añadido a la tarea { $number }: { $task }
+comment-empty = Un comentario no puede estar vacío.
+comments-none = Nadie ha comentado aún la tarea { $number }.
+
+## Confirmación
+
+confirm-choices = [s/N]
+confirm-yes = s, si, sí, y, yes
+confirm-refused = No se continúa sin confirmación.
+    Vuelve a ejecutarlo con --yes para confirmar cuando no sea interactivo.
+
+## Edición en $EDITOR
+
+edit-header = # Editando la tarea { $number }. Las líneas que empiezan por '#' se ignoran.
+    # Guarda y sal para aplicar los cambios. Borra todo para cancelar.
+edit-no-changes = No se hicieron cambios.
+edit-cancelled = Edición cancelada.
+edit-kept = { $error }
+    Tus cambios se guardaron en { $path }
+editor-start-failed = No se pudo iniciar el editor '{ $editor }': { $error }
+editor-failed = El editor '{ $editor }' terminó con { $status }. No se hicieron cambios.
+edit-not-string = '{ $field }' debe ser un texto
+edit-tags-not-list = 'tags' debe ser una lista de textos
+edit-contexts-not-list = 'contexts' debe ser una lista de textos
+edit-checklist-not-list = 'checklist' debe ser una lista de textos, como "[ ] reservar hotel"
+edit-reminders-not-list = 'reminders' debe ser una lista de horas, como "2025-06-30 09:00"
+edit-completed-not-bool = 'completed' debe ser true o false
+edit-unknown-field = Campo desconocido '{ $field }'
+
+## Cambiar muchas tareas
+
+modify-no-filter = Indica qué tareas cambiar, con texto u opciones como --tag, o usa --all.
+modify-no-edits = No hay nada que cambiar: usa --replace, --set, --add-tag o --remove-tag.
+modify-replace-invalid = '{ $input }' no es un reemplazo. Escríbelo como /viejo/nuevo/.
+modify-set-invalid = '{ $input }' no es un campo que asignar. Escríbelo como campo:valor, como prio:high o due:vie.
+modify-unknown-field = No se puede asignar '{ $field }'; usa priority, due o project.
+modify-done = Se { $count ->
+        [one] cambió 1 tarea
+       *[other] cambiaron { $count } tareas
+    } de { $matched } que coinciden.
+
+## Simulaciones
+
+dry-run-header = Simulación: no se guardó ningún cambio.
+dry-run-nothing = (nada cambiaría)
+dry-run-notify = Simulación: no se envió nada. Se anunciaría:
+dry-run-encrypted = se cifraría
+dry-run-decrypted = dejaría de ser secreta
+dry-run-sealed = (cambiarían su texto o notas cifrados)
+dry-run-config = Simulación: se establecería { $key } = { $value }
+dry-run-unset = Simulación: se quitaría { $key }
+dry-run-rpc = --dry-run no se puede usar con --rpc.
+dry-run-mcp = --dry-run no se puede usar con mcp.
+
+## Notificaciones y el demonio de recordatorios
+
+notify-nothing = No vence nada.
+notify-failed = Falló la notificación: { $error }
+notify-overdue-one = 1 tarea vencida
+notify-due-today = { $count ->
+        [one] 1 tarea vence hoy
+       *[other] { $count } tareas vencen hoy
+    }
+notify-due-overdue = { $count } tareas pendientes, { $overdue } vencidas
+notify-overdue-since = { $number }. { $task } (vencida desde { $date })
+reminder-coming-up = { $count ->
+        [one] 1 tarea próxima
+       *[other] { $count } tareas próximas
+    }
+reminder-line = { $number }. { $task } (vence { $days ->
+        [1] mañana
+       *[other] en { $days } días
+    })
+reminder-title = { $count ->
+        [one] Recordatorio
+       *[other] { $count } recordatorios
+    }
+reminder-set = La tarea { $number } te avisará el { $time }: { $task }
+reminder-past = { $time } ya pasó; los recordatorios deben ser en el futuro.
+reminders-cleared = Se quitaron los recordatorios de la tarea { $number }: { $task }
+reminders-none = La tarea { $number } no tiene recordatorios.
+wait-set = La tarea { $number } está esperando a { $person }: { $task }
+wait-cleared = La tarea { $number } ya no está esperando: { $task }
+wait-not-waiting = La tarea { $number } no está esperando a nadie.
+wait-missing-for = Indica a quién espera la tarea con --for, o deja de esperar con --clear.
+waiting-person-empty = A quién espera una tarea no puede quedar vacío.
+someday-set = Tarea { $number } aparcada para algún día: { $task }
+someday-cleared = La tarea { $number } vuelve a la lista: { $task }
+someday-already = La tarea { $number } ya está aparcada para algún día.
+someday-not-parked = La tarea { $number } no está aparcada para algún día.
+pin-set = Tarea { $number } fijada: { $task }
+pin-cleared = Tarea { $number } desfijada: { $task }
+pin-already = La tarea { $number } ya está fijada.
+pin-not-pinned = La tarea { $number } no está fijada.
+review-nothing = No hay tareas de algún día/quizás que revisar.
+review-someday = { $count ->
+        [one] 1 tarea de algún día/quizás que revisar:
+       *[other] { $count } tareas de algún día/quizás que revisar:
+    }
+review-line = { $number }. { $task } (aparcada desde el { $date })
+confirm-activate = ¿Devolverla a la lista?
+waiting-none = No hay nada esperando a nadie.
+waiting-line = { $number }. { $task } ({ $person }, { $days ->
+        [one] 1 día
+       *[other] { $days } días
+    })
+nudge-title = { $count ->
+        [one] 1 tarea lleva demasiado esperando
+       *[other] { $count } tareas llevan demasiado esperando
+    }
+nudge-line = { $number }. { $task } (esperando a { $person } desde hace { $days } días)
+daemon-started = Demonio de recordatorios iniciado (pid { $pid }).
+    Su salida va a { $path }
+daemon-stopped = Demonio de recordatorios detenido (pid { $pid }).
+daemon-running = El demonio de recordatorios está en marcha (pid { $pid }).
+daemon-not-running = El demonio de recordatorios no está en marcha.
+daemon-already-running = El demonio de recordatorios ya está en marcha (pid { $pid }).
+daemon-watching = Demonio de recordatorios en marcha (pid { $pid }), vigilando { $path }
+daemon-exited = El demonio de recordatorios terminó con { $status }. Consulta { $path }
+
+## Hooks y webhooks
+
+hook-failed = el hook '{ $hook }' terminó con { $status }.
+hook-run-failed = no se pudo ejecutar el hook '{ $hook }': { $error }
+webhook-status = el webhook { $url } respondió { $status }.
+webhook-failed = no se pudo enviar el webhook a { $url }: { $error }
+
+## Otros comandos
+
+api-token = Token de la API: { $token }
+accept-failed = No se pudo aceptar una conexión: { $error }
+token-failed = No se pudo generar un token ({ $error }); usa --token.
+sync-listening = Sincronizando la lista { $list } como { $name } en el puerto { $port }.
+sync-done = Sincronizado con { $peer }: { $here ->
+        [one] 1 cambio
+       *[other] { $here } cambios
+    } aquí, { $there } allí.
+sync-conflicts = { $count ->
+        [one] 1 tarea cambió
+       *[other] { $count } tareas cambiaron
+    } en ambos lados; donde los dos cambiaron el mismo campo, se conservó el cambio de este lado.
+sync-failed = No se pudo sincronizar con { $peer }: { $error }
+sync-token-missing = Las instancias deben compartir un token para sincronizar: define TODO_SYNC_TOKEN (o --token) con el mismo secreto en cada máquina, o usa --insecure para que cualquiera en la red pueda sincronizar.
+sync-incomplete = No se pudo sincronizar con { $count ->
+        [one] 1 instancia
+       *[other] { $count } instancias
+    }.
+sync-no-peers = No hay otras instancias sincronizando esta lista en la red.
+sync-browse-failed = No se pudieron buscar otras instancias: { $error }
+sync-announce-failed = Otras instancias no pueden encontrar esta ({ $error }); sincroniza con ella usando --peer.
+sync-other-list = Esta instancia sincroniza la lista { $list }.
+store-id-taken = Ya existe una tarea con el id { $id }.
+rpc-with-command = --rpc no se puede combinar con un comando ('{ $command }').
+script-not-found = No hay ningún script llamado '{ $name }'.
+config-set = Guardado: { $key } = { $value }
+config-not-set = La clave de configuración '{ $key }' no está definida.
+config-unknown-key = Clave de configuración desconocida '{ $key }'.
+log-file-failed = No se pudo abrir el archivo de registro { $path }: { $error }
+history-header = Historial de '{ $task }' (id { $id }):
+history-empty = No hay cambios registrados en esta tarea.
+history-added = añadida por { $user } mediante { $via }
+history-updated = cambiada por { $user } mediante { $via }
+history-removed = eliminada por { $user } mediante { $via }
+history-command = { $what }, con '{ $command }'
+history-list-empty = No hay cambios registrados en esta lista.
+history-unset = (ninguno)
+heatmap-total = { $count ->
+        [one] 1 tarea completada
+       *[other] { $count } tareas completadas
+    } en las últimas { $weeks } semanas
+heatmap-legend = Menos { $shades } Más
+heatmap-plain-week = Semana del { $date }: { $count } completadas
+heatmap-no-weeks = --weeks debe ser 1 o más.
+holidays-none = No hay festivos configurados. Elígelos con 'config set holidays es' (o us, gb, de, fr, o un archivo .ics).
+holidays-empty = No hay festivos en ese tiempo.
+holidays-bad-year = { $year } no es un año que se pueda mostrar.
+holidays-unreadable = No se pudieron leer los festivos de { $path }: { $error }
+stats-total = Tareas: { $total } ({ $pending } pendientes, { $completed } completadas, { $overdue } vencidas)
+stats-week = Últimos 7 días: { $added } añadidas, { $completed } completadas
+stats-by-tag = Por etiqueta:
+stats-by-project = Por proyecto:
+stats-count = { $pending } pendientes, { $completed } completadas
+stats-last-used = (usada por última vez el { $date })
+tags-none = Ninguna tarea tiene etiquetas aún.
+tag-renamed = #{ $from } renombrada a #{ $to } en { $count ->
+        [one] 1 tarea
+       *[other] { $count } tareas
+    }.
+tag-merged = #{ $from } fusionada con #{ $to } en { $count ->
+        [one] 1 tarea
+       *[other] { $count } tareas
+    }.
+tag-same = Las dos etiquetas son '{ $tag }'.
+tag-unused = Ninguna tarea tiene la etiqueta '{ $tag }'.
+tag-in-use = Algunas tareas ya tienen la etiqueta '{ $to }'. Para fusionar '{ $from }' con ella, usa 'tag merge { $from } { $to }'.
+snapshot-name-invalid = '{ $name }' no es un nombre de instantánea. Usa letras, dígitos, '-', '_' y '.'.
+snapshot-exists = Ya hay una instantánea llamada '{ $name }'.
+snapshot-not-found = No hay ninguna instantánea llamada '{ $name }'. 'snapshots' las muestra.
+snapshot-taken = Instantánea '{ $name }' guardada con { $count ->
+        [one] 1 tarea
+       *[other] { $count } tareas
+    }.
+snapshot-dry-run = Simulación: se guardaría la instantánea '{ $name }' con { $count ->
+        [one] 1 tarea
+       *[other] { $count } tareas
+    }.
+snapshot-tasks = { $count ->
+        [one] 1 tarea
+       *[other] { $count } tareas
+    }
+snapshots-none = Aún no hay instantáneas. 'snapshot' guarda una.
+confirm-restore = ¿Reemplazar { $count ->
+        [one] la tarea
+       *[other] las { $count } tareas
+    } de la lista con la instantánea '{ $name }'?
+restore-nothing = La lista ya coincide con la instantánea '{ $name }'.
+restored = Instantánea '{ $name }' restaurada, con { $count ->
+        [one] 1 tarea cambiada
+       *[other] { $count } tareas cambiadas
+    }.
+diff-none = No hay diferencias.
+diff-summary = { $added } añadidas, { $removed } eliminadas, { $changed } cambiadas
+undo-no-changes = No hay cambios registrados en esta lista.
+undo-unknown = No hay ningún cambio '{ $change }' registrado en esta lista. 'history' muestra los ids de los cambios.
+undo-conflict-removed = id:{ $id } '{ $task }' se ha eliminado desde entonces.
+undo-conflict-changed = id:{ $id } '{ $task }': { $field } ha cambiado desde entonces.
+undo-conflict-unrecorded = id:{ $id } '{ $task }': el cambio se registró sin lo necesario para deshacerlo.
+undo-conflict-sealed = id:{ $id } '{ $task }' se hizo secreta; 'secret --clear' lo deshace.
+undo-conflicts = El cambio { $change } no se puede deshacer por cambios posteriores; no se ha cambiado nada.
+undo-nothing = El cambio { $change } ya está deshecho.
+undone = Cambio { $change } deshecho, con { $count ->
+        [one] 1 tarea cambiada
+       *[other] { $count } tareas cambiadas
+    }.
+trash-empty = La papelera está vacía.
+trash-not-found = No hay ninguna tarea con id '{ $id }' en la papelera. 'trash list' las muestra.
+trash-on-list = id:{ $id } '{ $task }' ya está en la lista.
+trash-restored = id:{ $id } restaurada como tarea { $number }: { $task }
+confirm-empty-trash = ¿Eliminar definitivamente { $count ->
+        [one] 1 tarea
+       *[other] { $count } tareas
+    } de la papelera?
+trash-emptied = { $count ->
+        [one] 1 tarea eliminada
+       *[other] { $count } tareas eliminadas
+    } definitivamente de la papelera.
+trash-dry-run = Simulación: se eliminarían definitivamente { $count ->
+        [one] 1 tarea
+       *[other] { $count } tareas
+    } de la papelera.
+task-archived = Movida al archivo.
+prune-no-age = Indica hace cuánto deben haberse completado las tareas, p. ej. --completed-before 90d, o configura prune_after.
+prune-nothing = Ninguna tarea se completó hace más de { $days } días.
+pruned-archived = { $count ->
+        [one] 1 tarea archivada
+       *[other] { $count } tareas archivadas
+    }, completadas hace más de { $days } días.
+pruned-deleted = { $count ->
+        [one] 1 tarea eliminada
+       *[other] { $count } tareas eliminadas
+    }, completadas hace más de { $days } días.
+estimate-set = Se espera que la tarea { $number } ({ $task }) lleve { $time }.
+estimate-cleared = La tarea { $number } ({ $task }) ya no tiene estimación.
+duration-invalid = '{ $input }' no es una duración (se esperaba p. ej. 45m, 2h o 1h30m)
+time-group-invalid = No se puede agrupar por '{ $input }' (se esperaba task, project o tag)
+time-started = Cronometrando la tarea { $number }: { $task }
+time-already-running = La tarea { $number } ({ $task }) se cronometra desde { $time }.
+time-stopped = Cronómetro de la tarea { $number } ({ $task }) parado tras { $time }; { $total } dedicados en total.
+time-not-running = No se está cronometrando ninguna tarea.
+time-running = Cronometrando la tarea { $number } ({ $task }): { $time } desde { $since }
+time-logged = Registrado { $time } en la tarea { $number } ({ $task }); { $total } dedicados en total.
+time-log-empty = El tiempo registrado debe ser de al menos un minuto.
+time-task-completed = La tarea { $number } ({ $task }) ya está completada.
+time-no-estimates = Ninguna tarea tiene estimación. Pon una con 'estimate TAREA 2h'.
+time-no-project = (sin proyecto)
+estimates-tag = Etiqueta
+time-no-tag = (sin etiqueta)
+time-report-until = Tiempo dedicado hasta el { $date }
+time-report-hours = Tiempo
+time-report-tasks = Tareas
+estimates-task = Tarea
+estimates-project = Proyecto
+estimates-estimate = Estimado
+estimates-actual = Real
+estimates-total = Total
+estimates-summary = { $side ->
+        [over] El trabajo llevó el { $percent }% de lo estimado: { $time } más de lo previsto.
+        [under] El trabajo llevó el { $percent }% de lo estimado: { $time } menos de lo previsto.
+       *[exact] El trabajo llevó exactamente lo estimado.
+    }
+task-timing = ⏱ { $time }
+show-estimate = Estimación
+show-tracked = Tiempo dedicado
+show-tracked-sessions = { $time } en { $count ->
+        [one] 1 sesión
+       *[other] { $count } sesiones
+    }
+show-timer-running = Cronómetro en marcha desde { $time }
+time-spent-on = Tiempo dedicado el { $date }
+time-spent-between = Tiempo dedicado del { $first } al { $last }
+time-spent-none = No se registró tiempo.
+push-no-service = No hay servicio de tiempo configurado. Pon time_service a toggl o harvest.
+push-no-account = Enviar a { $service } necesita time_account y time_token.
+push-id-invalid = '{ $id }' no es un id válido: los ids son números
+push-no-harvest-project = El proyecto '{ $project }' no tiene PROJECT_ID/TASK_ID de Harvest en [time_projects].
+push-entry = Enviado { $time } ({ $duration }): { $task }
+push-entry-dry-run = Se enviaría { $time } ({ $duration }): { $task }
+push-harvest-needs-project = '{ $task }' no tiene proyecto, y Harvest lo necesita.
+push-rejected = el servicio respondió { $status }: { $body }
+push-failed = No se pudo enviar el tiempo de '{ $task }': { $error }
+push-done = { $count ->
+        [one] 1 entrada de tiempo enviada
+       *[other] { $count } entradas de tiempo enviadas
+    } a { $service }.
+push-some-failed = { $count ->
+        [one] 1 sesión no se envió; se volverá
+       *[other] { $count } sesiones no se enviaron; se volverán
+    } a intentar la próxima vez.
+search-regex-invalid = Expresión regular no válida '{ $pattern }': { $error }.
+query-is-invalid = '{ $input }' no es algo por lo que is: pregunte (se esperaba pending, done, overdue, waiting, someday o habit).
+query-unknown-field = Condición desconocida '{ $field }:' (se esperaba prio, due, due.before, due.after, tag, project, context, status, owner o is).
+query-unexpected = '{ $token }' inesperado en la expresión.
+query-unclosed-quote = Unas comillas de la expresión nunca se cierran.
+query-unclosed-paren = Un paréntesis de la expresión nunca se cierra.
+query-missing = Nada sigue a '{ $after }' en la expresión.
+view-format-invalid = '{ $input }' no es un formato de vista (se esperaba list, compact o json).
+view-unknown = No hay ninguna vista llamada '{ $name }'. Vistas: { $known }
+view-name-taken = '{ $name }' ya es un comando o un alias, así que una vista no puede llamarse así.
+view-saved = Vista '{ $name }' guardada.
+view-deleted = Vista '{ $name }' eliminada.
+view-none = Aún no hay vistas. Guarda una con 'view save NOMBRE EXPRESIÓN'.
+context-unknown = No hay ningún contexto llamado '{ $name }'. Contextos: { $known }
+context-note = El contexto '{ $name }' está activo ('context none' lo desactiva).
+context-set = Cambiado al contexto '{ $name }'.
+context-cleared = Ya no hay ningún contexto activo.
+context-current = Contexto: { $name }
+context-off = No hay ningún contexto activo.
+context-defined = Contexto '{ $name }' guardado.
+context-deleted = Contexto '{ $name }' eliminado.
+context-name-missing = Indica el nombre del contexto.
+context-expression-missing = Indica la expresión del contexto '{ $name }', p. ej. +work.
+context-name-reserved = '{ $name }' es una palabra del comando 'context', así que un contexto no puede llamarse así.
+context-none = Aún no hay contextos. Define uno con 'context define NOMBRE EXPRESIÓN'.
+move-moved = La tarea { $number } es ahora la { $position } de { $count } en el orden manual: { $task }
+move-unchanged = La tarea { $number } ya es la { $position } de { $count } en el orden manual: { $task }
+move-no-place = Indica adónde moverla: --before TAREA, --after TAREA, --top o --bottom.
+import-format-missing = Indica qué es el archivo: --markdown o --ics.
+import-unreadable = No se pudo leer { $file }: { $error }
+import-nothing = No hay elementos de lista de tareas en { $file }.
+import-no-events = No hay eventos en { $file } en esos días.
+import-location = Lugar: { $location }
+import-skipped = Se omite '{ $task }', que ya está en la lista.
+import-done = { $count ->
+        [one] Se importó 1 tarea
+       *[other] Se importaron { $count } tareas
+    } de { $file }.
+secret-set = Tarea {$number} ahora es secreta: {$task}
+secret-cleared = La tarea {$number} ya no es secreta: {$task}
+secret-already = La tarea {$number} ya es secreta.
+secret-not-secret = La tarea {$number} no es secreta.
+secret-edit = La tarea {$number} es secreta; hazla visible con 'secret {$number} --clear' antes de editarla.
+secret-prompt = Frase de paso
+secret-prompt-again = Frase de paso otra vez
+secret-mismatch = Las frases de paso no coinciden.
+secret-needs-passphrase = Hace falta una frase de paso: ejecútalo en una terminal o define TODO_PASSPHRASE.
+secret-wrong-passphrase = Esa frase de paso no abre las tareas secretas.
+dashboard-serving = Sirviendo el panel en {$url}
+dashboard-title = Lista de tareas
+dashboard-title-list = Lista de tareas: {$list}
+dashboard-text = Texto
+dashboard-tag = Etiqueta
+dashboard-project = Proyecto
+dashboard-context = Contexto
+dashboard-filter = Filtrar
+dashboard-clear = Limpiar
+dashboard-empty = Nada que hacer.
+dashboard-updated = Actualizado {$time}
+qr-too-long = Es demasiado para un código QR ({$length} bytes; 2331 como máximo).
+dashboard-task-missing = Ninguna tarea tiene ese id.
+dashboard-back = Todas las tareas
+ingest-source-missing = Indica de dónde leer el correo: --imap, --mbox ARCHIVO o --maildir DIR.
+ingest-imap-missing = No hay buzón configurado; define imap_url, p. ej. imaps://mail.example.com/INBOX.
+ingest-unreadable = No se pudo leer el correo de {$source}: {$error}
+ingest-no-subject = (sin asunto)
+ingest-nothing = No hay mensajes nuevos que añadir de {$source}.
+ingest-done = { $count ->
+        [one] Se añadió 1 tarea
+       *[other] Se añadieron { $count } tareas
+    } de {$source}.
+mail-smtp-missing = No hay servidor de correo configurado; define smtp_url, p. ej. smtps://smtp.example.com.
+mail-from-missing = Indica quién envía el correo: define mail_from, p. ej. "Ana <ana@example.com>".
+mail-failed = No se pudo enviar el correo: {$error}
+mail-subject = { $count ->
+        [one] 1 tarea pendiente
+       *[other] { $count } tareas pendientes
+    }
+mail-intro = { $count ->
+        [one] Esta es la tarea pendiente
+       *[other] Estas son las { $count } tareas pendientes
+    } a {$date}:
+mail-sent = { $count ->
+        [one] Se envió 1 tarea
+       *[other] Se enviaron { $count } tareas
+    } a {$to}.
+slack-missing = Slack no está configurado; define slack_webhook_url con un webhook entrante, o slack_token y slack_channel.
+slack-channel-missing = Indica dónde publicar: define slack_channel con un canal, p. ej. #equipo, o un ID de miembro para un mensaje directo.
+slack-failed = No se pudo publicar en Slack: {$error}
+slack-posted = Se publicó el resumen en Slack.
+slack-alerted = { $count ->
+        [one] Se avisó a Slack de 1 tarea
+       *[other] Se avisó a Slack de { $count } tareas
+    }.
+slack-alert-overdue = :red_circle: Vencida desde {$date}: {$task}
+slack-alert-high = :exclamation: Prioridad alta: {$task}
+slack-summary-title = *Tareas del {$date}*
+slack-summary-title-list = *Tareas de {$list} del {$date}*
+slack-summary-overdue = Vencidas
+slack-summary-today = Vencen hoy
+slack-summary-high = Prioridad alta
+slack-summary-clear = Nada está vencido, vence hoy ni tiene prioridad alta.
+slack-summary-pending = { $count ->
+        [one] 1 tarea pendiente
+       *[other] { $count } tareas pendientes
+    }
+discord-missing = La lista {$list} no tiene webhook de Discord; define discord_webhooks.{$list}.
+discord-failed = No se pudo publicar en Discord: {$error}
+discord-posted = { $count ->
+        [one] Se publicó en Discord 1 tarea que vence pronto
+       *[other] Se publicaron en Discord { $count } tareas que vencen pronto
+    }.
+discord-done = :white_check_mark: Hecha: {$task}
+discord-done-by = :white_check_mark: {$person} completó {$task}
+discord-due-title = **{ $count ->
+        [one] 1 tarea vence pronto
+       *[other] { $count } tareas vencen pronto
+    }**
+discord-due-overdue = :red_circle: {$task}, vencida desde {$date}
+discord-due-today = :orange_circle: {$task}, vence hoy
+discord-due-on = :calendar: {$task}, vence el {$date}
+telegram-token-missing = No hay token de bot; crea un bot con @BotFather y define telegram_token.
+telegram-no-chats = Ningún chat puede usar el bot aún: escríbele para saber tu ID de chat y define telegram_chats.
+telegram-running = Respondiendo como @{$bot}; pulsa Ctrl-C para parar.
+telegram-failed = Telegram: {$error}
+telegram-not-allowed = Este chat no puede usar el bot. Para permitirlo, añade su ID, {$chat}, a telegram_chats.
+telegram-help = Envía una tarea para añadirla, o:
+    /add TEXTO para añadir una tarea, p. ej. /add leche #compras due:fri
+    /list para ver las tareas pendientes
+    /done TAREA para completar una, por número o parte de su texto
+telegram-unknown = No conozco /{$command}; /help muestra lo que hago.
+telegram-add-missing = ¿Qué añado? P. ej. /add llamar al dentista
+telegram-done-missing = ¿Qué tarea? P. ej. /done 3
+telegram-added = Añadida {$number}. {$task}
+telegram-done = Hecha: {$number}. {$task}
+telegram-already-done = {$task} ya está hecha.
+telegram-empty = Nada que hacer.
+telegram-more = …y { $count } más.
+projects-none = Ninguna tarea está en un proyecto aún.
+projects-percent = { $percent }% hecho
+projects-next-due = la próxima vence el { $date }
+projects-main-list = (lista principal)
+board-todo = Por hacer
+board-doing = En curso
+board-waiting = Esperando
+board-someday = Algún día
+board-done = Hechas
+board-cancelled = Canceladas
+board-no-tag = Sin etiqueta
+board-no-project = Sin proyecto
+board-plain-column = { $column }, { $count ->
+        [one] 1 tarea
+       *[other] { $count } tareas
+    }:
+board-plain-card = Tarea { $number }: { $task }
+bar-overdue = { $count ->
+        [one] 1 vencida
+       *[other] { $count } vencidas
+    }
+bar-today = { $count } para hoy
+bar-pending = { $count ->
+        [one] 1 pendiente
+       *[other] { $count } pendientes
+    }
+segment-format-invalid = '{ $input }' no es un formato de segmento. Usa starship o powerline.
+board-grouping-invalid = '{ $input }' no es una forma de agrupar el tablero. Usa status, tag o project.
+board-unknown-column = No hay ninguna columna '{ $column }'. Usa una de: { $columns }.
+board-moved = Tarea { $number } movida a { $column }: { $task }
+board-no-such-tag = La tarea no tiene la etiqueta '{ $tag }'.
+board-waiting-for = Indica a quién espera la tarea con --for.
+board-which-tag = La tarea tiene varias etiquetas ({ $tags }); indica de cuál moverla con --from.
+reindexed = Se reconstruyó el índice de { $path }
+reindex-none = { $path } no tiene ningún índice que reconstruir.
+man-written = Se escribieron { $count } páginas de manual en { $dir }
+
+## Errores sobre tareas
+
+error-missing-query = Falta el número o el texto de la tarea.
+error-invalid-number = El número de tarea debe ser 1 o mayor.
+error-no-such-number = No hay ninguna tarea con el número { $number }.
+error-stale-number = La tarea { $number } de la última lista ya se eliminó; no se cambió nada.
+    Vuelve a ejecutar 'list' para ver los números actuales.
+error-no-match = Ninguna tarea coincide con '{ $query }'.
+error-ambiguous = '{ $query }' coincide con { $count } tareas:
+error-ambiguous-hint = Usa el número de la tarea, o más parte del texto.
+
+error-parse = No se pudo leer { $path }: { $error } (línea { $line }, columna { $column }).
+    Puede que el archivo esté dañado; corrígelo a mano o recupera una copia de seguridad.
+task-skipped = Se omite la entrada { $number } de { $path }, que no es una tarea válida: { $error }
+tasks-quarantined = Se { $count ->
+        [one] movió 1 entrada no válida
+       *[other] movieron { $count } entradas no válidas
+    } de { $path } a { $quarantine }.
+error-read-only = { $path } está abierto en solo lectura, así que no se guardó nada.
+error-conflict = Otro programa cambió { $path } después de leerlo, así que no se guardó nada.
+    Vuelve a ejecutar el comando para trabajar sobre la lista nueva.
+error-merge-failed = No se pudo combinar: otro programa también cambió o eliminó la tarea '{ $task }'. No se guardó nada.
+list-reloaded = Otro programa cambió la lista mientras tanto; se leyeron sus cambios.
+edit-task-gone = Otro programa eliminó '{ $task }' mientras la editabas, así que tus cambios no se guardaron.
+edit-merged = Otro programa cambió esta tarea mientras tanto; donde ambos cambiasteis el mismo campo, se conservó tu cambio.
+list-changed = Otro programa cambió { $path } después de leerlo.
+confirm-merge = ¿Aplicar tus cambios a la lista nueva?
+
+## Fechas
+
+date-today = hoy
+date-tomorrow = mañana, manana
+date-yesterday = ayer
+date-next-week = la próxima semana, próxima semana, la semana que viene, semana que viene
+date-weekdays = lunes, martes, miércoles, jueves, viernes, sábado, domingo
+date-months = enero, febrero, marzo, abril, mayo, junio, julio, agosto, septiembre, octubre, noviembre, diciembre
+date-invalid = '{ $input }' no es una fecha. Usa AAAA-MM-DD, hoy, mañana, un día de la semana, la próxima semana, o +3d/+3bd/+2w/+1m.
+date-too-many-business-days = Una fecha se puede mover como mucho { $max } días laborables.
+date-no-business-day = No hay ningún día laborable en los { $days } días desde { $date }; revisa el ajuste 'holidays'.
+reminder-invalid = '{ $input }' no es una hora. Usa una fecha y una hora (mañana 9am, vie 14:30), solo una hora, o +30min/+2h.
@@ -0,0 +1,80 @@
+// The tasks with due dates, grouped by how soon they are due, for `due`.
+//
+// Weeks start on the day `week_start` says, Monday unless it is changed, so
+// on the last day of a week nothing is left for "this week" past tomorrow.
+
+use crate::date::Date;
+use crate::task::Task;
+
+/// How soon a task is due, in the order `due` lists them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Bucket {
+    Overdue,
+    Today,
+    Tomorrow,
+    ThisWeek,
+    Later,
+}
+
+impl Bucket {
+    /// Every bucket, soonest first.
+    pub const ALL: [Bucket; 5] = [
+        Bucket::Overdue,
+        Bucket::Today,
+        Bucket::Tomorrow,
+        Bucket::ThisWeek,
+        Bucket::Later,
+    ];
+
+    /// The bucket for a task due on `due`, as of `today`.
+    pub fn of(due: Date, today: Date) -> Bucket {
+        let last = today.start_of_week().add_days(6);
+        match today.days_until(due) {
+            days if days < 0 => Bucket::Overdue,
+            0 => Bucket::Today,
+            1 => Bucket::Tomorrow,
+            _ if due <= last => Bucket::ThisWeek,
+            _ => Bucket::Later,
+        }
+    }
+
+    /// The name of the message that heads the bucket.
+    pub fn message(self) -> &'static str {
+        match self {
+            Bucket::Overdue => "due-overdue",
+            Bucket::Today => "due-today",
+            Bucket::Tomorrow => "due-tomorrow",
+            Bucket::ThisWeek => "due-this-week",
+            Bucket::Later => "due-later",
+        }
+    }
+}
+
+/// The pending tasks with a due date, with their indexes, in buckets as of
+/// `today`. Each bucket is sorted by due date and time, then list order;
+/// empty buckets are left out. Tasks parked as someday/maybe are too. A task
+/// due by a time that has passed today is overdue.
+pub fn group(tasks: &[Task], today: Date) -> Vec<(Bucket, Vec<(usize, &Task)>)> {
+    let mut dated: Vec<(usize, &Task, Date)> = tasks
+        .iter()
+        .enumerate()
+        .filter(|(_, task)| !task.completed && task.someday.is_none())
+        .filter_map(|(index, task)| task.due.map(|due| (index, task, due)))
+        .collect();
+    dated.sort_by_key(|&(index, task, due)| (due, task.due_at, index));
+    Bucket::ALL
+        .iter()
+        .map(|&bucket| {
+            let tasks: Vec<(usize, &Task)> = dated
+                .iter()
+                .filter(|&&(_, task, due)| match task.is_overdue(today) {
+                    true => bucket == Bucket::Overdue,
+                    false => Bucket::of(due, today) == bucket,
+                })
+                .map(|&(index, task, _)| (index, task))
+                .collect();
+            (bucket, tasks)
+        })
+        .filter(|(_, tasks)| !tasks.is_empty())
+        .collect()
+}
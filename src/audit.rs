@@ -0,0 +1,346 @@
+// An append-only record of every change saved to a list.
+//
+// Each saved change adds one line of JSON to `audit.jsonl` next to the config:
+// when it happened, who made it and through what (the command line, the HTTP
+// API, a hook, ...), which task it was, and the old and new value of each
+// field that changed, and the command that made it when it came from the
+// command line. Lines are never rewritten, so the log can be trusted to show
+// how a task came to be the way it is; `history` prints it for a task, or the
+// latest changes to the whole list.
+//
+// The entries saved together share a change id, and updates and removals
+// keep the whole task as it was, so `undo` can take one change back later.
+// A change that makes a task secret keeps its old text and notes out of the
+// log, as the list does; undoing it is `secret --clear`'s job.
+//
+// Hooks are told to say they are hooks through `TODO_VIA`, which the command
+// line reads, so changes a hook makes by running `todo_cli` are credited to it.
+
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::backend::Change;
+use crate::config::config_dir;
+use crate::error::Result;
+use crate::log;
+use crate::secret;
+use crate::sha256;
+use crate::task::Task;
+use crate::timetrack;
+
+/// What happened to a task.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Add,
+    Update,
+    Remove,
+}
+
+/// One change to one task.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    /// Seconds since 1970-01-01 UTC.
+    pub time: u64,
+    /// The user the program ran as.
+    pub user: String,
+    /// What made the change: `cli`, `api`, `mcp`, `rpc`, `script:<name>`,
+    /// `hook:<name>`, ...
+    pub via: String,
+    /// The command that made the change, e.g. `done` or `tag rename`, if it
+    /// was one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// The id shared by the entries saved together, which `undo` takes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub change: Option<String>,
+    /// Where the list is kept, as given by `TaskStore::location`.
+    pub location: String,
+    /// The task's id.
+    pub id: u64,
+    pub action: Action,
+    /// The task's text after the change, or before it for a removal.
+    pub text: String,
+    /// The fields that changed, with their old and new values.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldChange>,
+    /// The whole task before an update or removal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<Task>,
+}
+
+/// A field's value before and after a change; `None` when it was unset.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// The command being run, set once it is known.
+static COMMAND: OnceLock<String> = OnceLock::new();
+
+/// Notes that changes saved from now on are made by `command`, e.g. `done`.
+pub fn set_command(command: impl Into<String>) {
+    let _ = COMMAND.set(command.into());
+}
+
+/// Where the audit log is kept.
+pub fn path() -> PathBuf {
+    config_dir().join("audit.jsonl")
+}
+
+/// What changes are credited to when nothing more specific is known: the
+/// command line, unless `TODO_VIA` says otherwise.
+pub fn default_via() -> String {
+    match env::var("TODO_VIA") {
+        Ok(via) if !via.is_empty() => via,
+        _ => "cli".to_string(),
+    }
+}
+
+/// The entries for `changes`, each with the task it replaced or removed.
+///
+/// Changes that touch no field a user can see, such as giving an old task an
+/// id, are left out.
+pub fn entries(changes: &[(Change, Option<Task>)], location: &str, via: &str) -> Vec<Entry> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let time = now.as_secs();
+    let user = user();
+    let seed = format!("{}:{}:{}", now.as_nanos(), process::id(), location);
+    let id = sha256::hex(&sha256::digest(seed.as_bytes()))[..8].to_string();
+    changes
+        .iter()
+        .filter_map(|(change, before)| {
+            let (action, task, fields) = match (change, before) {
+                (Change::Add(task), _) => (Action::Add, task, diff(None, task)),
+                (Change::Set(_, task), before) => {
+                    (Action::Update, task, diff(before.as_ref(), task))
+                }
+                (Change::Remove(_), Some(before)) => (Action::Remove, before, Vec::new()),
+                (Change::Remove(_), None) => return None,
+            };
+            if action == Action::Update && fields.is_empty() {
+                return None;
+            }
+            let mut entry = Entry {
+                time,
+                user: user.clone(),
+                via: via.to_string(),
+                command: COMMAND.get().cloned(),
+                change: Some(id.clone()),
+                location: location.to_string(),
+                id: task.id,
+                action,
+                text: task.text.clone(),
+                fields,
+                before: before.clone().filter(|_| action != Action::Add),
+            };
+            redact(&mut entry);
+            Some(entry)
+        })
+        .collect()
+}
+
+/// Whether `entry` made its task secret.
+pub fn seals(entry: &Entry) -> bool {
+    entry
+        .fields
+        .iter()
+        .any(|change| change.field == "secret" && change.old.is_none() && change.new.is_some())
+}
+
+/// Masks the text and notes that `entry` sealed, which the task's old
+/// version still has in the clear.
+fn redact(entry: &mut Entry) {
+    if seals(entry) {
+        mask(entry);
+    }
+}
+
+/// Masks the text and notes of a secret task throughout `entry`, for
+/// showing it to someone who hasn't unlocked the task.
+pub fn mask(entry: &mut Entry) {
+    let hide = |value: &mut Option<String>| {
+        if value.is_some() {
+            *value = Some(secret::MASK.to_string());
+        }
+    };
+    entry.text = secret::MASK.to_string();
+    for change in &mut entry.fields {
+        if change.field == "task" || change.field == "notes" {
+            hide(&mut change.old);
+            hide(&mut change.new);
+        }
+    }
+    entry.fields.retain(|change| change.old != change.new);
+    if let Some(before) = &mut entry.before {
+        before.text = secret::MASK.to_string();
+        before.notes = None;
+    }
+}
+
+/// Appends `entries` to the log.
+pub fn append(entries: &[Entry]) -> io::Result<()> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let mut out = String::new();
+    for entry in entries {
+        out.push_str(&serde_json::to_string(entry)?);
+        out.push('\n');
+    }
+    let path = path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // One write, so that entries from programs saving at once don't interleave.
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(out.as_bytes())
+}
+
+/// Every entry for the task with this id in the list at `location`, oldest
+/// first.
+pub fn history(location: &str, id: u64) -> Result<Vec<Entry>> {
+    let mut entries = list_entries(location)?;
+    entries.retain(|entry| entry.id == id);
+    Ok(entries)
+}
+
+/// Every entry for the list at `location`, oldest first.
+pub fn list_entries(location: &str) -> Result<Vec<Entry>> {
+    let file = match fs::File::open(path()) {
+        Ok(file) => file,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut found = Vec::new();
+    for (number, line) in (1..).zip(BufReader::new(file).lines()) {
+        let line = line?;
+        match serde_json::from_str::<Entry>(&line) {
+            Ok(mut entry) if entry.location == location => {
+                redact(&mut entry);
+                found.push(entry);
+            }
+            Ok(_) => {}
+            Err(e) => log::debug(
+                "audit",
+                format_args!("skipping line {} of the audit log: {}", number, e),
+            ),
+        }
+    }
+    Ok(found)
+}
+
+/// The fields that differ between two versions of a task; all the set ones
+/// if there was no version before.
+pub fn diff(before: Option<&Task>, after: &Task) -> Vec<FieldChange> {
+    let old = before.map(fields).unwrap_or_default();
+    fields(after)
+        .into_iter()
+        .enumerate()
+        .map(|(i, (field, new))| FieldChange {
+            field: field.to_string(),
+            old: old.get(i).and_then(|(_, value)| value.clone()),
+            new,
+        })
+        .filter(|change| change.old != change.new)
+        .collect()
+}
+
+/// A task's fields as text, named as in the file.
+fn fields(task: &Task) -> Vec<(&'static str, Option<String>)> {
+    let reminders: Vec<String> = task.reminders.iter().map(|at| at.to_string()).collect();
+    let checklist: Vec<String> = task.checklist.iter().map(|item| item.to_string()).collect();
+    let sessions: Vec<String> = task.sessions.iter().map(|s| s.to_string()).collect();
+    vec![
+        ("task", Some(task.text.clone())),
+        ("completed", Some(task.completed.to_string())),
+        ("status", task.status.clone()),
+        ("owner", task.owner.clone()),
+        ("due", task.due.map(|d| d.to_string())),
+        ("due_at", task.due_at.map(|at| at.to_string())),
+        ("priority", task.priority.map(|p| p.to_string())),
+        ("tags", Some(task.tags.join(", ")).filter(|t| !t.is_empty())),
+        ("project", task.project.clone()),
+        (
+            "contexts",
+            Some(task.contexts.join(", ")).filter(|c| !c.is_empty()),
+        ),
+        ("someday", task.someday.map(|d| d.to_string())),
+        ("pinned", task.pinned.then(|| "true".to_string())),
+        ("secret", task.secret.as_ref().map(|_| "true".to_string())),
+        (
+            "waiting",
+            task.waiting
+                .as_ref()
+                .map(|w| format!("{} since {}", w.person, w.since)),
+        ),
+        (
+            "depends",
+            Some(
+                task.depends
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )
+            .filter(|d| !d.is_empty()),
+        ),
+        (
+            "habit",
+            task.habit.as_ref().map(|habit| {
+                let unit = if habit.business_days { "bd" } else { "d" };
+                match habit.last() {
+                    Some(last) => format!("every {}{}, last done {}", habit.every, unit, last),
+                    None => format!("every {}{}", habit.every, unit),
+                }
+            }),
+        ),
+        (
+            "checklist",
+            Some(checklist.join(", ")).filter(|c| !c.is_empty()),
+        ),
+        (
+            "reminders",
+            Some(reminders.join(", ")).filter(|r| !r.is_empty()),
+        ),
+        ("notes", task.notes.clone()),
+        (
+            "estimate",
+            task.estimate
+                .map(|minutes| timetrack::format_duration(i64::from(minutes))),
+        ),
+        (
+            "sessions",
+            Some(sessions.join(", ")).filter(|s| !s.is_empty()),
+        ),
+        (
+            "comments",
+            task.comments
+                .last()
+                .map(|c| format!("{}: {}", c.author, c.text)),
+        ),
+    ]
+}
+
+/// The name of the user running the program.
+pub fn user() -> String {
+    ["USER", "USERNAME", "LOGNAME"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
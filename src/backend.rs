@@ -0,0 +1,739 @@
+// Where a to-do list is kept.
+//
+// A `TaskStore` works on tasks in memory and leaves reading and writing them
+// to a `StorageBackend`. The backends are:
+//
+//   json     a JSON file on disk, the default
+//   journal  a file of changes, appended to on each save
+//   memory   tasks kept in this process only; nothing survives it exiting
+//   remote   a JSON document fetched with GET and stored with PUT over HTTP
+//
+// `storage_backend` in the config picks one, so commands never need to know
+// which is in use. Any of them can be wrapped in `ReadOnly`, which refuses
+// every write.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::UNIX_EPOCH;
+
+use serde::de::{Deserializer, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use serde_json::error::Category;
+
+use crate::error::{Error, Result};
+use crate::http::{self, Client};
+use crate::i18n::tr;
+use crate::index::Index;
+use crate::log;
+use crate::sha256;
+use crate::task::Task;
+
+/// One change to a list, as recorded by `TaskStore`. Applying a store's
+/// changes in order to the tasks it loaded gives the tasks it holds.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Change {
+    /// A task was added at the end.
+    Add(Task),
+    /// The task at this index was replaced, e.g. because it was completed.
+    Set(usize, Task),
+    /// The task at this index was removed; the ones after it moved up.
+    Remove(usize),
+}
+
+/// Loads and saves a whole list of tasks.
+pub trait StorageBackend: fmt::Debug + Send {
+    /// Reads every task. A list that doesn't exist yet is empty.
+    fn load(&self) -> Result<Vec<Task>>;
+
+    /// Calls `visit` with each task and its index, in order, without keeping
+    /// them. Backends that can read one task at a time should, so that
+    /// listing a huge store doesn't hold all of it in memory; by default the
+    /// whole list is loaded first.
+    fn scan(&self, visit: &mut dyn FnMut(usize, Task)) -> Result<()> {
+        for (index, task) in self.load()?.into_iter().enumerate() {
+            visit(index, task);
+        }
+        Ok(())
+    }
+
+    /// The task at `index`, if there is one. Backends that can find a task
+    /// without reading the others should; by default the whole list is loaded.
+    fn get(&self, index: usize) -> Result<Option<Task>> {
+        Ok(self.load()?.into_iter().nth(index))
+    }
+
+    /// Rebuilds whatever the backend keeps to make `get` fast. Returns
+    /// `false` if it keeps nothing.
+    fn reindex(&self) -> Result<bool> {
+        Ok(false)
+    }
+
+    /// Replaces the stored tasks with `tasks`.
+    fn save(&self, tasks: &[Task]) -> Result<()>;
+
+    /// Saves `changes`, which turned the loaded tasks into `tasks`. Backends
+    /// that can write just the changes should; by default the whole list is
+    /// written.
+    fn save_changes(&self, tasks: &[Task], _changes: &[Change]) -> Result<()> {
+        self.save(tasks)
+    }
+
+    /// Where the tasks are kept, for messages and hooks: a path, a URL, ...
+    fn location(&self) -> String;
+
+    /// Identifies the stored list's current version, so that a store can
+    /// tell whether someone else saved it since it was read. `None` means the
+    /// backend can't tell, and saves always go ahead.
+    fn version(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// The local file holding the tasks, if there is one. Watchers use it to
+    /// notice changes without reloading the list.
+    fn path(&self) -> Option<&Path> {
+        None
+    }
+}
+
+/// Tasks kept as pretty-printed JSON in a file.
+///
+/// Entries of the list that aren't tasks (a field of the wrong type, say,
+/// after a hand edit) are skipped with a warning rather than failing the
+/// whole load. The next save moves them to `<file>.quarantine`, one JSON
+/// object per line, so that nothing is silently lost.
+#[derive(Debug)]
+pub struct JsonFile {
+    path: PathBuf,
+    /// Entries skipped by the last load, as (position, entry, error).
+    rejected: Mutex<Vec<(usize, serde_json::Value, String)>>,
+    /// The (position, error) of each entry warned about, so that reading
+    /// the file again doesn't repeat the warning.
+    warned: Mutex<HashSet<(usize, String)>>,
+}
+
+impl JsonFile {
+    pub fn new(path: impl Into<PathBuf>) -> JsonFile {
+        JsonFile {
+            path: path.into(),
+            rejected: Mutex::new(Vec::new()),
+            warned: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Where entries that aren't tasks are moved to.
+    pub fn quarantine_path(&self) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(".quarantine");
+        PathBuf::from(name)
+    }
+
+    /// Parses the file's contents, skipping entries that aren't tasks.
+    fn parse(&self, data: &str) -> Result<Vec<Task>> {
+        let error = match serde_json::from_str(data) {
+            Ok(tasks) => return Ok(tasks),
+            Err(e) => e,
+        };
+        // Only valid JSON can be taken apart entry by entry.
+        if error.classify() != Category::Data {
+            return Err(parse_error(&self.location(), error));
+        }
+        let Ok(entries) = serde_json::from_str::<Vec<serde_json::Value>>(data) else {
+            return Err(parse_error(&self.location(), error));
+        };
+        self.rejected.lock().unwrap().clear();
+        Ok((1..)
+            .zip(entries)
+            .filter_map(|(number, entry)| self.accept(number, entry))
+            .collect())
+    }
+
+    /// Turns the entry at `number` (counting from 1) into a task, or sets it
+    /// aside with a warning if it isn't one.
+    fn accept(&self, number: usize, entry: serde_json::Value) -> Option<Task> {
+        match Task::deserialize(&entry) {
+            Ok(task) => Some(task),
+            Err(e) => {
+                let error = e.to_string();
+                if self.warned.lock().unwrap().insert((number, error.clone())) {
+                    log::warn(
+                        "store",
+                        format_args!(
+                            "{}",
+                            tr(
+                                "task-skipped",
+                                &[
+                                    ("number", &number),
+                                    ("path", &self.path.display()),
+                                    ("error", &e)
+                                ]
+                            )
+                        ),
+                    );
+                }
+                self.rejected.lock().unwrap().push((number, entry, error));
+                None
+            }
+        }
+    }
+
+    /// Appends the entries skipped since the last save to the quarantine file.
+    fn quarantine(&self) -> Result<()> {
+        let rejected = std::mem::take(&mut *self.rejected.lock().unwrap());
+        if rejected.is_empty() {
+            return Ok(());
+        }
+        let mut out = String::new();
+        for (number, entry, error) in &rejected {
+            let line = serde_json::json!({
+                "position": number,
+                "entry": entry,
+                "error": error,
+            });
+            out.push_str(&line.to_string());
+            out.push('\n');
+        }
+        let path = self.quarantine_path();
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?
+            .write_all(out.as_bytes())?;
+        log::warn(
+            "store",
+            format_args!(
+                "{}",
+                tr(
+                    "tasks-quarantined",
+                    &[
+                        ("count", &rejected.len()),
+                        ("path", &self.path.display()),
+                        ("quarantine", &path.display())
+                    ]
+                )
+            ),
+        );
+        Ok(())
+    }
+
+    /// Updates the index for `data`, just written to or read from the file.
+    ///
+    /// The index only speeds things up, so failing to write it is not an error.
+    fn write_index(&self, data: &[u8]) {
+        let result = Index::build(&self.path, data).and_then(|index| index.write(&self.path));
+        if let Err(e) = result {
+            log::info(
+                "index",
+                format_args!("could not index {}: {}", self.path.display(), e),
+            );
+        }
+    }
+}
+
+impl StorageBackend for JsonFile {
+    fn load(&self) -> Result<Vec<Task>> {
+        match fs::read_to_string(&self.path) {
+            Ok(data) => self.parse(&data),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+                log::debug(
+                    "store",
+                    format_args!("{} does not exist yet; starting empty", self.path.display()),
+                );
+                Ok(Vec::new())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn scan(&self, visit: &mut dyn FnMut(usize, Task)) -> Result<()> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        self.rejected.lock().unwrap().clear();
+        let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(file));
+        deserializer
+            .deserialize_seq(EachTask { file: self, visit })
+            .and_then(|()| deserializer.end())
+            .map_err(|e| parse_error(&self.location(), e))
+    }
+
+    /// Reads just the task's bytes when the index is up to date. Otherwise
+    /// the whole file is read, and the index rebuilt for next time.
+    fn get(&self, index: usize) -> Result<Option<Task>> {
+        if let Some(found) = Index::read(&self.path) {
+            let Some((offset, len)) = found.span(index) else {
+                return Ok(None);
+            };
+            let mut data = vec![0; len as usize];
+            let mut file = File::open(&self.path)?;
+            file.seek(SeekFrom::Start(offset))?;
+            file.read_exact(&mut data)?;
+            // The file may have changed since the index was checked.
+            if let Ok(task) = serde_json::from_slice(&data) {
+                log::debug(
+                    "index",
+                    format_args!("read task {} from the index", index + 1),
+                );
+                return Ok(Some(task));
+            }
+        }
+        let data = match fs::read(&self.path) {
+            Ok(data) => data,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let data = String::from_utf8_lossy(&data);
+        let tasks = self.parse(&data)?;
+        // Positions in the file only match task numbers if no entry was skipped.
+        if self.rejected.lock().unwrap().is_empty() {
+            self.write_index(data.as_bytes());
+        }
+        Ok(tasks.into_iter().nth(index))
+    }
+
+    fn reindex(&self) -> Result<bool> {
+        let data = match fs::read(&self.path) {
+            Ok(data) => data,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => b"[]".to_vec(),
+            Err(e) => return Err(e.into()),
+        };
+        Index::build(&self.path, &data)?.write(&self.path)?;
+        Ok(true)
+    }
+
+    fn save(&self, tasks: &[Task]) -> Result<()> {
+        self.quarantine()?;
+        let data = to_json(tasks);
+        fs::write(&self.path, &data)?;
+        self.write_index(data.as_bytes());
+        Ok(())
+    }
+
+    fn location(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn version(&self) -> Result<Option<String>> {
+        file_version(&self.path)
+    }
+
+    fn path(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+}
+
+/// A file's size and modification time, or `absent` if it doesn't exist.
+fn file_version(path: &Path) -> Result<Option<String>> {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(Some("absent".to_string())),
+        Err(e) => return Err(e.into()),
+    };
+    let modified = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos());
+    Ok(Some(format!("{}@{}", metadata.len(), modified)))
+}
+
+/// Tasks kept as a log of changes, one JSON object per line:
+///
+/// ```text
+/// {"reset":[...]}        the whole list, as of a compaction
+/// {"add":{...}}          then each change since
+/// {"set":[2,{...}]}
+/// {"remove":0}
+/// ```
+///
+/// Saving appends just the new changes. Once the log has grown by
+/// [`Journal::COMPACT_AFTER`] changes, it is rewritten as a single `reset`.
+#[derive(Debug)]
+pub struct Journal {
+    path: PathBuf,
+    /// How many changes follow the last `reset`.
+    pending: AtomicUsize,
+    /// Whether the last line was cut short, so that appending to it would
+    /// corrupt the journal.
+    torn: AtomicBool,
+}
+
+/// A line of the journal.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Entry {
+    Reset(Vec<Task>),
+    #[serde(untagged)]
+    Change(Box<Change>),
+}
+
+impl Journal {
+    /// How many changes may follow the last snapshot before the log is compacted.
+    pub const COMPACT_AFTER: usize = 500;
+
+    pub fn new(path: impl Into<PathBuf>) -> Journal {
+        Journal {
+            path: path.into(),
+            pending: AtomicUsize::new(0),
+            torn: AtomicBool::new(false),
+        }
+    }
+}
+
+impl StorageBackend for Journal {
+    fn load(&self) -> Result<Vec<Task>> {
+        let data = match fs::read_to_string(&self.path) {
+            Ok(data) => data,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let corrupt = |line: usize, column: usize, why: &dyn fmt::Display| Error::Parse {
+            location: self.location(),
+            line,
+            column,
+            message: why.to_string(),
+        };
+        let mut tasks = Vec::new();
+        let mut pending = 0;
+        let lines: Vec<&str> = data.lines().collect();
+        for (number, line) in (1..).zip(&lines) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = match serde_json::from_str(line) {
+                Ok(entry) => entry,
+                // A last line without its newline was cut short while being
+                // written; the changes on it were never saved.
+                Err(e) if number == lines.len() && !data.ends_with('\n') => {
+                    log::info(
+                        "store",
+                        format_args!("ignoring an incomplete last line: {}", e),
+                    );
+                    self.torn.store(true, Ordering::Relaxed);
+                    break;
+                }
+                Err(e) => return Err(corrupt(number, e.column().max(1), &json_message(&e))),
+            };
+            match entry {
+                Entry::Reset(all) => {
+                    tasks = all;
+                    pending = 0;
+                }
+                Entry::Change(change) => {
+                    apply(&mut tasks, *change).map_err(|why| corrupt(number, 1, &why))?;
+                    pending += 1;
+                }
+            }
+        }
+        self.pending.store(pending, Ordering::Relaxed);
+        Ok(tasks)
+    }
+
+    /// Rewrites the journal as a single snapshot of `tasks`.
+    fn save(&self, tasks: &[Task]) -> Result<()> {
+        let mut line = serde_json::to_string(&Entry::Reset(tasks.to_vec()))
+            .expect("Failed to serialize data to JSON.");
+        line.push('\n');
+        // Write a new file and swap it in, so a crash can't lose the list.
+        let temp = self.path.with_extension("journal.tmp");
+        fs::write(&temp, line)?;
+        fs::rename(&temp, &self.path)?;
+        self.pending.store(0, Ordering::Relaxed);
+        self.torn.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn save_changes(&self, tasks: &[Task], changes: &[Change]) -> Result<()> {
+        let pending = self.pending.load(Ordering::Relaxed) + changes.len();
+        if pending > Journal::COMPACT_AFTER || self.torn.load(Ordering::Relaxed) {
+            log::debug("store", format_args!("compacting {}", self.path.display()));
+            return self.save(tasks);
+        }
+        let mut lines = String::new();
+        for change in changes {
+            lines.push_str(
+                &serde_json::to_string(change).expect("Failed to serialize data to JSON."),
+            );
+            lines.push('\n');
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        // One write for all of this run's changes, so they land together.
+        file.write_all(lines.as_bytes())?;
+        file.sync_data()?;
+        self.pending.store(pending, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn location(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn version(&self) -> Result<Option<String>> {
+        file_version(&self.path)
+    }
+
+    fn path(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+}
+
+/// Applies one change to `tasks`, as `TaskStore` did when it was made.
+fn apply(tasks: &mut Vec<Task>, change: Change) -> std::result::Result<(), String> {
+    let missing = |index: usize| format!("there is no task {} to change", index + 1);
+    match change {
+        Change::Add(task) => tasks.push(task),
+        Change::Set(index, task) => *tasks.get_mut(index).ok_or_else(|| missing(index))? = task,
+        Change::Remove(index) => {
+            if index >= tasks.len() {
+                return Err(missing(index));
+            }
+            tasks.remove(index);
+        }
+    }
+    Ok(())
+}
+
+/// Another backend with every write refused, for a list opened with
+/// `--read-only`.
+#[derive(Debug)]
+pub struct ReadOnly(pub Box<dyn StorageBackend>);
+
+impl StorageBackend for ReadOnly {
+    fn load(&self) -> Result<Vec<Task>> {
+        self.0.load()
+    }
+
+    fn scan(&self, visit: &mut dyn FnMut(usize, Task)) -> Result<()> {
+        self.0.scan(visit)
+    }
+
+    /// Loads the whole list, as the backend's own `get` may rebuild its
+    /// index on the way.
+    fn get(&self, index: usize) -> Result<Option<Task>> {
+        Ok(self.0.load()?.into_iter().nth(index))
+    }
+
+    fn reindex(&self) -> Result<bool> {
+        Err(Error::ReadOnly(self.location()))
+    }
+
+    fn save(&self, _tasks: &[Task]) -> Result<()> {
+        Err(Error::ReadOnly(self.location()))
+    }
+
+    fn save_changes(&self, _tasks: &[Task], _changes: &[Change]) -> Result<()> {
+        Err(Error::ReadOnly(self.location()))
+    }
+
+    fn location(&self) -> String {
+        self.0.location()
+    }
+
+    fn version(&self) -> Result<Option<String>> {
+        self.0.version()
+    }
+
+    fn path(&self) -> Option<&Path> {
+        self.0.path()
+    }
+}
+
+/// Tasks kept in memory only. Clones share the same tasks.
+#[derive(Clone, Debug, Default)]
+pub struct Memory {
+    name: String,
+    tasks: Arc<Mutex<Vec<Task>>>,
+}
+
+impl Memory {
+    /// A new, private list holding `tasks`.
+    pub fn new(tasks: Vec<Task>) -> Memory {
+        Memory {
+            name: "memory".to_string(),
+            tasks: Arc::new(Mutex::new(tasks)),
+        }
+    }
+
+    /// The process-wide list called `name`, so that every store opened on it
+    /// during one run (e.g. by `serve`) sees the same tasks.
+    pub fn shared(name: &str) -> Memory {
+        static LISTS: OnceLock<Mutex<HashMap<String, Memory>>> = OnceLock::new();
+        let mut lists = LISTS
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        lists
+            .entry(name.to_string())
+            .or_insert_with(|| Memory {
+                name: match name {
+                    "" => "memory".to_string(),
+                    _ => format!("memory:{}", name),
+                },
+                tasks: Arc::default(),
+            })
+            .clone()
+    }
+}
+
+impl StorageBackend for Memory {
+    fn load(&self) -> Result<Vec<Task>> {
+        Ok(self.tasks.lock().unwrap_or_else(|e| e.into_inner()).clone())
+    }
+
+    fn save(&self, tasks: &[Task]) -> Result<()> {
+        *self.tasks.lock().unwrap_or_else(|e| e.into_inner()) = tasks.to_vec();
+        Ok(())
+    }
+
+    fn location(&self) -> String {
+        self.name.clone()
+    }
+}
+
+/// Tasks kept as a JSON document on a web server, read with `GET` and
+/// written with `PUT`. A 404 is an empty list.
+#[derive(Clone, Debug)]
+pub struct Remote {
+    url: String,
+    client: Client,
+}
+
+impl Remote {
+    pub fn new(url: impl Into<String>, client: Client) -> Remote {
+        Remote {
+            url: url.into(),
+            client,
+        }
+    }
+
+    fn request(&self, method: &str, body: &[u8]) -> Result<http::ClientResponse> {
+        let headers = [
+            ("Content-Type", "application/json"),
+            ("Accept", "application/json"),
+            ("User-Agent", "todo_cli"),
+        ];
+        // Both GET and PUT of the whole list are safe to repeat.
+        self.client
+            .send_with_retries(method, &self.url, &headers, body)
+            .map_err(|e| {
+                Error::Io(io::Error::new(
+                    e.kind(),
+                    format!("{} {} failed: {}", method, self.url, e),
+                ))
+            })
+    }
+}
+
+impl StorageBackend for Remote {
+    fn load(&self) -> Result<Vec<Task>> {
+        let response = self.request("GET", b"")?;
+        match response.status {
+            200..=299 => parse(&String::from_utf8_lossy(&response.body), &self.url),
+            404 => {
+                log::debug(
+                    "store",
+                    format_args!("{} does not exist yet; starting empty", self.url),
+                );
+                Ok(Vec::new())
+            }
+            status => Err(status_error("GET", &self.url, status)),
+        }
+    }
+
+    fn save(&self, tasks: &[Task]) -> Result<()> {
+        let response = self.request("PUT", to_json(tasks).as_bytes())?;
+        match response.status {
+            200..=299 => Ok(()),
+            status => Err(status_error("PUT", &self.url, status)),
+        }
+    }
+
+    fn location(&self) -> String {
+        self.url.clone()
+    }
+
+    /// A hash of the document, fetched again to compare.
+    fn version(&self) -> Result<Option<String>> {
+        let response = self.request("GET", b"")?;
+        match response.status {
+            200..=299 => Ok(Some(sha256::hex(&sha256::digest(&response.body)))),
+            404 => Ok(Some("absent".to_string())),
+            status => Err(status_error("GET", &self.url, status)),
+        }
+    }
+}
+
+fn status_error(method: &str, url: &str, status: u16) -> Error {
+    Error::Io(io::Error::other(format!(
+        "{} {} answered {} {}",
+        method,
+        url,
+        status,
+        http::reason(status)
+    )))
+}
+
+/// Hands the elements of a JSON array of tasks to `visit` as they are read.
+struct EachTask<'a> {
+    file: &'a JsonFile,
+    visit: &'a mut dyn FnMut(usize, Task),
+}
+
+impl<'de> Visitor<'de> for EachTask<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a list of tasks")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<(), A::Error> {
+        let mut index = 0;
+        let mut number = 0;
+        while let Some(entry) = seq.next_element()? {
+            number += 1;
+            if let Some(task) = self.file.accept(number, entry) {
+                (self.visit)(index, task);
+                index += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse(data: &str, location: &str) -> Result<Vec<Task>> {
+    serde_json::from_str(data).map_err(|e| parse_error(location, e))
+}
+
+/// Turns a JSON error into an `Error::Parse` saying where it happened.
+fn parse_error(location: &str, e: serde_json::Error) -> Error {
+    Error::Parse {
+        location: location.to_string(),
+        line: e.line(),
+        column: e.column(),
+        message: json_message(&e),
+    }
+}
+
+/// A JSON error's message without the position it ends with, which we
+/// report separately.
+fn json_message(e: &serde_json::Error) -> String {
+    let message = e.to_string();
+    let suffix = format!(" at line {} column {}", e.line(), e.column());
+    message
+        .strip_suffix(&suffix)
+        .unwrap_or(&message)
+        .to_string()
+}
+
+fn to_json(tasks: &[Task]) -> String {
+    serde_json::to_string_pretty(tasks).expect("Failed to serialize data to JSON.")
+}
@@ -0,0 +1,163 @@
+// Sending tracked time to Toggl Track or Harvest, so that it reaches the
+// billing system without being typed in again.
+//
+// `time push` sends each finished session as a time entry, described by the
+// task's text. The list's projects are matched to the service's through the
+// config's `[time_projects]` table: a Toggl project id, or for Harvest, which
+// files every entry under a project and one of its tasks, `PROJECT/TASK`.
+// Toggl takes entries without a project; Harvest sessions of unmatched
+// projects are left for when they are matched.
+//
+// What was sent is remembered in `~/.config/todo/pushed/<list>.json`, so each
+// session is sent once however often `time push` runs.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use serde_json::json;
+
+use crate::config::{config_dir, TimeService};
+use crate::date::DateTime;
+use crate::error::{Error, Result};
+use crate::http;
+use crate::i18n::tr;
+use crate::task::Task;
+use crate::timetrack::Session;
+
+/// A time entry to send: the url, headers and JSON body of its request.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Request {
+    pub url: String,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: String,
+}
+
+/// The request that sends `session` of `task` to `service`, into `account`
+/// with `token`, under the service's `project` (as in `[time_projects]`).
+/// Fails when an id isn't a number, or Harvest has no project to file it
+/// under.
+pub fn request(
+    service: TimeService,
+    account: &str,
+    token: &str,
+    project: Option<&str>,
+    task: &Task,
+    session: &Session,
+) -> std::result::Result<Request, String> {
+    let end = session.end.unwrap_or(session.start);
+    let id = |id: &str| {
+        id.trim()
+            .parse::<u64>()
+            .map_err(|_| tr("push-id-invalid", &[("id", &id.trim())]))
+    };
+    let account = id(account)?;
+    match service {
+        TimeService::Toggl => {
+            let project = project.map(id).transpose()?;
+            let body = json!({
+                "workspace_id": account,
+                "project_id": project,
+                "description": task.text,
+                "tags": task.tags,
+                "start": rfc3339(session.start),
+                "stop": rfc3339(end),
+                "duration": session.minutes(end) * 60,
+                "created_with": "todo_cli",
+            });
+            Ok(Request {
+                url: format!(
+                    "https://api.track.toggl.com/api/v9/workspaces/{}/time_entries",
+                    account
+                ),
+                headers: vec![
+                    ("Authorization", http::basic_auth(token, "api_token")),
+                    ("Content-Type", "application/json".to_string()),
+                ],
+                body: body.to_string(),
+            })
+        }
+        TimeService::Harvest => {
+            let Some((project, harvest_task)) = project.and_then(|p| p.split_once('/')) else {
+                return Err(match &task.project {
+                    Some(project) => tr("push-no-harvest-project", &[("project", project)]),
+                    None => tr("push-harvest-needs-project", &[("task", &task.text)]),
+                });
+            };
+            let body = json!({
+                "project_id": id(project)?,
+                "task_id": id(harvest_task)?,
+                "spent_date": session.start.date().to_string(),
+                "hours": session.minutes(end) as f64 / 60.0,
+                "notes": task.text,
+            });
+            Ok(Request {
+                url: "https://api.harvestapp.com/v2/time_entries".to_string(),
+                headers: vec![
+                    ("Authorization", format!("Bearer {}", token)),
+                    ("Harvest-Account-Id", account.to_string()),
+                    ("User-Agent", "todo_cli".to_string()),
+                    ("Content-Type", "application/json".to_string()),
+                ],
+                body: body.to_string(),
+            })
+        }
+    }
+}
+
+/// A time as RFC 3339, which both services read. Times on the list are UTC.
+fn rfc3339(at: DateTime) -> String {
+    format!("{}T{:02}:{:02}:00Z", at.date(), at.hour(), at.minute())
+}
+
+/// The sessions already sent from a list.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Pushed {
+    path: PathBuf,
+    sent: BTreeSet<String>,
+}
+
+impl Pushed {
+    /// What was sent from `list`.
+    pub fn load(list: Option<&str>) -> Result<Pushed> {
+        let path = config_dir()
+            .join("pushed")
+            .join(format!("{}.json", list.unwrap_or("main")));
+        let sent = match fs::read_to_string(&path) {
+            Ok(data) => serde_json::from_str(&data).map_err(|e| Error::Parse {
+                location: path.display().to_string(),
+                line: e.line(),
+                column: e.column(),
+                message: e.to_string(),
+            })?,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => BTreeSet::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Pushed { path, sent })
+    }
+
+    /// Whether `session` of `task` was sent to `service`.
+    pub fn contains(&self, service: TimeService, task: &Task, session: &Session) -> bool {
+        self.sent.contains(&key(service, task, session))
+    }
+
+    /// Remembers that `session` of `task` was sent to `service`.
+    pub fn insert(&mut self, service: TimeService, task: &Task, session: &Session) {
+        self.sent.insert(key(service, task, session));
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string(&self.sent).expect("Failed to serialize data to JSON.");
+        fs::write(&self.path, json)?;
+        Ok(())
+    }
+}
+
+/// What identifies a sent session, e.g. `toggl:7@2024-05-01 09:00`.
+fn key(service: TimeService, task: &Task, session: &Session) -> String {
+    format!("{}:{}@{}", service, task.id, session.start)
+}
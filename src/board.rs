@@ -0,0 +1,318 @@
+// The kanban board: tasks laid out side by side in columns, by status, tag or
+// project, and moved from one column to another.
+//
+// By status the columns are the workflow's (`todo`, `doing` and `done`
+// unless configured otherwise), with `waiting` and `someday` before `done`.
+// `someday`, `cancelled` and any status outside the workflow only get a
+// column when a task is in them. By tag or project there is a column for each one in
+// use, and one for pending tasks without any; a task with several tags is in
+// each of their columns. By tag or project, only pending tasks are shown,
+// leaving out those parked as someday/maybe.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::date::Date;
+use crate::i18n::tr;
+use crate::search;
+use crate::task::{Task, Waiting, CANCELLED, DONE, TODO};
+
+/// What the columns stand for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Grouping {
+    Status,
+    Tag,
+    Project,
+}
+
+impl FromStr for Grouping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "status" => Ok(Grouping::Status),
+            "tag" | "tags" => Ok(Grouping::Tag),
+            "project" | "projects" => Ok(Grouping::Project),
+            _ => Err(tr("board-grouping-invalid", &[("input", &s.trim())])),
+        }
+    }
+}
+
+impl fmt::Display for Grouping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Grouping::Status => "status",
+            Grouping::Tag => "tag",
+            Grouping::Project => "project",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The column names when grouping by status, in board order, for the
+/// statuses of `workflow`.
+pub fn statuses(workflow: &[String]) -> Vec<String> {
+    let mut statuses: Vec<String> = workflow.to_vec();
+    let before = statuses
+        .iter()
+        .position(|s| s == DONE)
+        .unwrap_or(statuses.len());
+    statuses.splice(
+        before..before,
+        ["waiting".to_string(), "someday".to_string()],
+    );
+    statuses.push(CANCELLED.to_string());
+    statuses
+}
+
+/// One column of the board and the tasks in it, with their indexes.
+#[derive(Debug)]
+pub struct Column<'a> {
+    /// The status, tag or project; `None` for tasks without a tag or project.
+    pub key: Option<String>,
+    pub cards: Vec<(usize, &'a Task)>,
+}
+
+/// The status column a task belongs in.
+pub fn status(task: &Task) -> &str {
+    if task.completed {
+        task.status()
+    } else if task.someday.is_some() {
+        "someday"
+    } else if task.waiting.is_some() {
+        "waiting"
+    } else {
+        task.status()
+    }
+}
+
+/// Sorts `tasks` into columns, by status with the statuses of `workflow`.
+pub fn columns<'a>(tasks: &'a [Task], by: Grouping, workflow: &[String]) -> Vec<Column<'a>> {
+    let mut columns: Vec<Column> = Vec::new();
+    if by == Grouping::Status {
+        for status in statuses(workflow) {
+            columns.push(Column {
+                key: Some(status),
+                cards: Vec::new(),
+            });
+        }
+        for (index, task) in tasks.iter().enumerate() {
+            let status = status(task);
+            match columns
+                .iter_mut()
+                .find(|c| c.key.as_deref() == Some(status))
+            {
+                Some(column) => column.cards.push((index, task)),
+                None => columns.push(Column {
+                    key: Some(status.to_string()),
+                    cards: vec![(index, task)],
+                }),
+            }
+        }
+        // Only the workflow's own columns are worth showing empty.
+        columns.retain(|c| {
+            !c.cards.is_empty()
+                || c.key
+                    .as_ref()
+                    .is_some_and(|key| workflow.contains(key) || key == "waiting")
+        });
+        return columns;
+    }
+
+    let mut without = Vec::new();
+    for (index, task) in tasks
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| !t.completed && t.someday.is_none())
+    {
+        let keys: Vec<&String> = match by {
+            Grouping::Tag => task.tags.iter().collect(),
+            _ => task.project.iter().collect(),
+        };
+        if keys.is_empty() {
+            without.push((index, task));
+        }
+        for key in keys {
+            match columns.iter_mut().find(|c| c.key.as_ref() == Some(key)) {
+                Some(column) => column.cards.push((index, task)),
+                None => columns.push(Column {
+                    key: Some(key.clone()),
+                    cards: vec![(index, task)],
+                }),
+            }
+        }
+    }
+    columns.sort_by_key(|c| c.key.as_deref().map(search::fold));
+    if !without.is_empty() {
+        columns.push(Column {
+            key: None,
+            cards: without,
+        });
+    }
+    columns
+}
+
+/// Draws columns with these `titles` side by side in `width` characters.
+/// Cards are the task number and text, cut short to fit.
+pub fn render(columns: &[Column], titles: &[String], width: usize, color: bool) -> String {
+    const GAP: usize = 2;
+    let count = columns.len().max(1);
+    let column_width = (width.saturating_sub(GAP * (count - 1)) / count).max(12);
+
+    let mut rows: Vec<Vec<String>> = vec![Vec::new(); 2];
+    for (column, title) in columns.iter().zip(titles) {
+        let title = fit(&format!("{} ({})", title, column.cards.len()), column_width);
+        rows[0].push(title);
+        rows[1].push("─".repeat(column_width));
+    }
+    let depth = columns.iter().map(|c| c.cards.len()).max().unwrap_or(0);
+    for row in 0..depth {
+        rows.push(
+            columns
+                .iter()
+                .map(|column| match column.cards.get(row) {
+                    Some((index, task)) => {
+                        fit(&format!("{}. {}", index + 1, task.text), column_width)
+                    }
+                    None => String::new(),
+                })
+                .collect(),
+        );
+    }
+
+    let mut out = String::new();
+    for (number, row) in rows.iter().enumerate() {
+        let mut line = String::new();
+        for (i, cell) in row.iter().enumerate() {
+            if i > 0 {
+                line.push_str(&" ".repeat(GAP));
+            }
+            let padding = " ".repeat(column_width - display_width(cell));
+            if color && number == 0 {
+                line.push_str(&format!("\x1b[1m{}\x1b[0m{}", cell, padding));
+            } else {
+                line.push_str(cell);
+                line.push_str(&padding);
+            }
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// The columns one after another for plain output: each title with how many
+/// cards it has, then a line for each card.
+pub fn describe(columns: &[Column], titles: &[String]) -> String {
+    let mut out = String::new();
+    for (column, title) in columns.iter().zip(titles) {
+        out.push_str(&tr(
+            "board-plain-column",
+            &[("column", title), ("count", &column.cards.len())],
+        ));
+        out.push('\n');
+        for (index, task) in &column.cards {
+            out.push_str(&tr(
+                "board-plain-card",
+                &[("number", &(index + 1)), ("task", &task.text)],
+            ));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// What moving a task to another column changes.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Move {
+    /// Set the task's status: one of [`statuses`]. Moving to `waiting` needs
+    /// someone to wait for unless the task already waits.
+    Status(String),
+    /// Swap the tag `from` (or the task's only tag) for `to`; `None` removes
+    /// every tag.
+    Tag {
+        from: Option<String>,
+        to: Option<String>,
+    },
+    /// Set the project; `None` clears it.
+    Project(Option<String>),
+}
+
+/// `task` moved as `to` says, as of `today`. Fails if the move can't be
+/// made as asked, e.g. a tag to move from that the task doesn't have.
+pub fn move_task(
+    task: &Task,
+    to: &Move,
+    person: Option<String>,
+    today: Date,
+) -> Result<Task, String> {
+    let mut task = task.clone();
+    match to {
+        Move::Status(status) => {
+            if status != "someday" {
+                task.someday = None;
+            } else if task.someday.is_none() {
+                task.someday = Some(today);
+            }
+            match status.as_str() {
+                "waiting" | "someday" if task.completed => task.set_status(TODO),
+                "waiting" | "someday" => {}
+                status => task.set_status(status),
+            }
+            match (status.as_str(), person) {
+                ("waiting", Some(person)) => {
+                    task.waiting = Some(Waiting {
+                        person,
+                        since: today,
+                    })
+                }
+                ("waiting", None) if task.waiting.is_none() => {
+                    return Err(tr("board-waiting-for", &[]))
+                }
+                // A task that isn't waiting any more is in its own column.
+                ("someday", _) => {}
+                _ if !task.completed => task.waiting = None,
+                _ => {}
+            }
+        }
+        Move::Tag { from, to } => {
+            let from = match from {
+                Some(from) if task.tags.contains(from) => Some(from.clone()),
+                Some(from) => return Err(tr("board-no-such-tag", &[("tag", from)])),
+                None if to.is_none() => None,
+                None => match task.tags.as_slice() {
+                    [] => None,
+                    [only] => Some(only.clone()),
+                    _ => return Err(tr("board-which-tag", &[("tags", &task.tags.join(", "))])),
+                },
+            };
+            match to {
+                None => task.tags.clear(),
+                Some(to) => {
+                    task.tags.retain(|t| Some(t) != from.as_ref());
+                    if !task.tags.contains(to) {
+                        task.tags.push(to.clone());
+                    }
+                }
+            }
+        }
+        Move::Project(project) => task.project = project.clone(),
+    }
+    Ok(task)
+}
+
+/// `text` cut short with an ellipsis to fit in `width` columns.
+fn fit(text: &str, width: usize) -> String {
+    let clusters = search::graphemes(text);
+    if clusters.len() <= width {
+        return text.to_string();
+    }
+    let mut cut: String = clusters[..width.saturating_sub(1)].concat();
+    cut.push('…');
+    cut
+}
+
+/// How many columns `text` takes up, counting each grapheme cluster as one.
+fn display_width(text: &str) -> usize {
+    search::graphemes(text).len()
+}
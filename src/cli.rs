@@ -0,0 +1,2365 @@
+// Command-line parsing.
+//
+// Every command is described by a `CommandSpec` listing its arguments, and the
+// parser turns the raw `env::args` into `Matches` that handlers can query by
+// name. The same specs drive `--help` output, so the two can't drift apart.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// The name the program is invoked as in usage messages.
+pub const BIN_NAME: &str = "todo_cli";
+
+/// A single argument: a positional value, a flag, or an option taking a value.
+#[derive(Debug)]
+pub struct Arg {
+    /// The name handlers use to look the argument up.
+    pub id: &'static str,
+    pub short: Option<char>,
+    pub long: Option<&'static str>,
+    /// Another long name accepted for the same argument.
+    pub alias: Option<&'static str>,
+    /// Shown in usage, e.g. `TASK`. Flags don't take a value and have none.
+    pub value_name: Option<&'static str>,
+    pub help: &'static str,
+    pub required: bool,
+    /// Whether the argument may be given more than once (or, for positionals,
+    /// whether it soaks up all remaining values).
+    pub multiple: bool,
+}
+
+impl Arg {
+    /// A positional argument.
+    pub const fn positional(id: &'static str, value_name: &'static str, help: &'static str) -> Arg {
+        Arg {
+            id,
+            short: None,
+            long: None,
+            alias: None,
+            value_name: Some(value_name),
+            help,
+            required: false,
+            multiple: false,
+        }
+    }
+
+    /// A boolean `--flag`.
+    pub const fn flag(long: &'static str, help: &'static str) -> Arg {
+        Arg {
+            id: long,
+            short: None,
+            long: Some(long),
+            alias: None,
+            value_name: None,
+            help,
+            required: false,
+            multiple: false,
+        }
+    }
+
+    /// An `--option <VALUE>`.
+    pub const fn option(long: &'static str, value_name: &'static str, help: &'static str) -> Arg {
+        Arg {
+            id: long,
+            short: None,
+            long: Some(long),
+            alias: None,
+            value_name: Some(value_name),
+            help,
+            required: false,
+            multiple: false,
+        }
+    }
+
+    pub const fn short(self, short: char) -> Arg {
+        Arg {
+            short: Some(short),
+            ..self
+        }
+    }
+
+    pub const fn alias(self, alias: &'static str) -> Arg {
+        Arg {
+            alias: Some(alias),
+            ..self
+        }
+    }
+
+    pub const fn required(self) -> Arg {
+        Arg {
+            required: true,
+            ..self
+        }
+    }
+
+    pub const fn multiple(self) -> Arg {
+        Arg {
+            multiple: true,
+            ..self
+        }
+    }
+
+    pub fn is_positional(&self) -> bool {
+        self.long.is_none() && self.short.is_none()
+    }
+
+    fn takes_value(&self) -> bool {
+        self.value_name.is_some()
+    }
+
+    /// How the argument appears in a usage line, e.g. `<TASK>...`.
+    fn usage(&self) -> String {
+        let name = self.value_name.unwrap_or(self.id);
+        let mut usage = if self.required {
+            format!("<{}>", name)
+        } else {
+            format!("[{}]", name)
+        };
+        if self.multiple {
+            usage.push_str("...");
+        }
+        usage
+    }
+
+    /// How the argument appears in the help listing, e.g. `-y, --yes`.
+    pub fn signature(&self) -> String {
+        if self.is_positional() {
+            return self.usage();
+        }
+        let mut sig = match self.short {
+            Some(c) => format!("-{}, ", c),
+            None => "    ".to_string(),
+        };
+        if let Some(long) = self.long {
+            sig.push_str(&format!("--{}", long));
+        }
+        if let Some(value) = self.value_name {
+            sig.push_str(&format!(" <{}>", value));
+        }
+        sig
+    }
+}
+
+/// A command (or sub-command) and everything it accepts.
+#[derive(Debug)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    /// Short names that always mean this command, even if they are also a
+    /// prefix of other commands (e.g. `d` for `done`).
+    pub aliases: &'static [&'static str],
+    pub about: &'static str,
+    pub args: &'static [Arg],
+    pub subcommands: &'static [CommandSpec],
+    /// Extra text printed at the end of `--help`, such as examples.
+    pub after_help: &'static str,
+}
+
+impl CommandSpec {
+    pub const fn new(name: &'static str, about: &'static str) -> CommandSpec {
+        CommandSpec {
+            name,
+            aliases: &[],
+            about,
+            args: &[],
+            subcommands: &[],
+            after_help: "",
+        }
+    }
+
+    pub const fn aliases(self, aliases: &'static [&'static str]) -> CommandSpec {
+        CommandSpec { aliases, ..self }
+    }
+
+    pub const fn args(self, args: &'static [Arg]) -> CommandSpec {
+        CommandSpec { args, ..self }
+    }
+
+    pub const fn subcommands(self, subcommands: &'static [CommandSpec]) -> CommandSpec {
+        CommandSpec {
+            subcommands,
+            ..self
+        }
+    }
+
+    pub const fn after_help(self, after_help: &'static str) -> CommandSpec {
+        CommandSpec { after_help, ..self }
+    }
+
+    /// Looks up a sub-command by its exact name or one of its short names.
+    pub fn find(&self, name: &str) -> Option<&'static CommandSpec> {
+        self.subcommands
+            .iter()
+            .find(|cmd| cmd.name == name || cmd.aliases.contains(&name))
+    }
+
+    /// Looks up a sub-command the way users type it.
+    ///
+    /// Besides exact names and short names, any unambiguous prefix works
+    /// (`l` for `list`). On failure the message suggests what was probably meant.
+    pub fn resolve(&self, name: &str) -> Result<&'static CommandSpec, String> {
+        if let Some(cmd) = self.find(name) {
+            return Ok(cmd);
+        }
+
+        let candidates: Vec<&'static CommandSpec> = self
+            .subcommands
+            .iter()
+            .filter(|cmd| !name.is_empty() && cmd.name.starts_with(name))
+            .collect();
+        match candidates.as_slice() {
+            [cmd] => return Ok(cmd),
+            [] => {}
+            _ => {
+                let names: Vec<&str> = candidates.iter().map(|cmd| cmd.name).collect();
+                return Err(format!(
+                    "'{}' is ambiguous: it could be {}.",
+                    name,
+                    names.join(", ")
+                ));
+            }
+        }
+
+        let mut msg = format!("Unknown command '{}'.", name);
+        if let Some(suggestion) = self.closest(name) {
+            msg.push_str(&format!(" Did you mean '{}'?", suggestion));
+        }
+        Err(msg)
+    }
+
+    /// Finds the sub-command name closest to a probable typo, if any is close enough.
+    /// Of two as close, the one used more often wins (see `COMMON`).
+    fn closest(&self, name: &str) -> Option<&'static str> {
+        let name = name.to_lowercase();
+        let rank = |cmd: &str| {
+            COMMON
+                .iter()
+                .position(|common| *common == cmd)
+                .unwrap_or(COMMON.len())
+        };
+        self.subcommands
+            .iter()
+            .map(|cmd| (edit_distance(&name, cmd.name), cmd.name))
+            // Allow roughly one mistake per three letters, and at least one.
+            .filter(|(distance, cmd)| *distance <= (cmd.len() / 3).max(1))
+            .min_by_key(|(distance, cmd)| (*distance, rank(cmd)))
+            .map(|(_, cmd)| cmd)
+    }
+
+    fn find_long(&self, name: &str) -> Option<&Arg> {
+        self.args
+            .iter()
+            .find(|arg| arg.long == Some(name) || arg.alias == Some(name))
+    }
+
+    fn find_short(&self, c: char) -> Option<&Arg> {
+        self.args.iter().find(|arg| arg.short == Some(c))
+    }
+
+    /// The usage line for this command, given the words that lead up to it.
+    pub fn usage(&self, path: &str) -> String {
+        let mut usage = format!("Usage: {}", path);
+        if self.args.iter().any(|arg| !arg.is_positional()) {
+            usage.push_str(" [OPTIONS]");
+        }
+        for arg in self.args.iter().filter(|arg| arg.is_positional()) {
+            usage.push(' ');
+            usage.push_str(&arg.usage());
+        }
+        if !self.subcommands.is_empty() {
+            usage.push_str(" [COMMAND]");
+        }
+        usage
+    }
+
+    /// The full `--help` text for this command.
+    pub fn help(&self, path: &str) -> String {
+        let mut out = format!("{}\n\n{}\n", self.about, self.usage(path));
+
+        if !self.subcommands.is_empty() {
+            out.push_str("\nCommands:\n");
+            let rows: Vec<(String, &str)> = self
+                .subcommands
+                .iter()
+                .map(|cmd| match cmd.aliases {
+                    [] => (cmd.name.to_string(), cmd.about),
+                    aliases => (format!("{} ({})", cmd.name, aliases.join(", ")), cmd.about),
+                })
+                .collect();
+            push_rows(&mut out, &rows);
+        }
+
+        let positionals: Vec<(String, &str)> = self
+            .args
+            .iter()
+            .filter(|arg| arg.is_positional())
+            .map(|arg| (arg.signature(), arg.help))
+            .collect();
+        if !positionals.is_empty() {
+            out.push_str("\nArguments:\n");
+            push_rows(&mut out, &positionals);
+        }
+
+        let mut options: Vec<(String, &str)> = self
+            .args
+            .iter()
+            .filter(|arg| !arg.is_positional())
+            .map(|arg| (arg.signature(), arg.help))
+            .collect();
+        options.push(("-h, --help".to_string(), "Print help"));
+        out.push_str("\nOptions:\n");
+        push_rows(&mut out, &options);
+
+        if !self.after_help.is_empty() {
+            out.push('\n');
+            out.push_str(self.after_help);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// The commands typed most often, most first. A typo as close to one of
+/// these as to a rarer command is taken for it: `doen` is one edit from both
+/// `done` and `down`, and `dne` from both `done` and `due`; both mean `done`.
+const COMMON: &[&str] = &[
+    "list", "add", "done", "edit", "delete", "show", "due", "search", "modify", "undo",
+];
+
+/// How many single-character edits turn `a` into `b`, counting insertions,
+/// deletions, substitutions and swaps of two neighbouring characters (the
+/// restricted Damerau–Levenshtein, or "optimal string alignment", distance),
+/// so `lsit` is one edit from `list`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // d[i][j] is the distance between the first i chars of a and the first j of b.
+    let mut d: Vec<Vec<usize>> = (0..=a.len())
+        .map(|i| (0..=b.len()).map(|j| if i == 0 { j } else { i }).collect())
+        .collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Appends two-column rows, aligning the descriptions.
+fn push_rows(out: &mut String, rows: &[(String, &str)]) {
+    let width = rows.iter().map(|(left, _)| left.len()).max().unwrap_or(0);
+    for (left, right) in rows {
+        out.push_str(&format!("  {:width$}  {}\n", left, right, width = width));
+    }
+}
+
+/// The parsed arguments of one command.
+#[derive(Debug, Default)]
+pub struct Matches {
+    values: BTreeMap<&'static str, Vec<String>>,
+    /// How many times each flag was given, e.g. 2 for `-vv`.
+    flags: BTreeMap<&'static str, usize>,
+    /// The sub-command that was given, if the command has any.
+    pub subcommand: Option<(&'static str, Box<Matches>)>,
+}
+
+impl Matches {
+    /// Returns whether a flag was given.
+    pub fn flag(&self, id: &str) -> bool {
+        self.flags.contains_key(id)
+    }
+
+    /// Returns how many times a flag was given.
+    pub fn count(&self, id: &str) -> usize {
+        self.flags.get(id).copied().unwrap_or(0)
+    }
+
+    /// Returns the (last) value of an argument.
+    pub fn value(&self, id: &str) -> Option<&str> {
+        self.values
+            .get(id)
+            .and_then(|values| values.last())
+            .map(String::as_str)
+    }
+
+    /// Returns every value given for an argument.
+    pub fn values(&self, id: &str) -> &[String] {
+        self.values.get(id).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Returns all values of an argument joined by spaces, e.g. unquoted task text.
+    pub fn joined(&self, id: &str) -> Option<String> {
+        let values = self.values(id);
+        if values.is_empty() {
+            None
+        } else {
+            Some(values.join(" "))
+        }
+    }
+
+    /// Parses the value of an argument into a typed value.
+    pub fn get<T: FromStr>(&self, id: &str) -> Result<Option<T>, CliError>
+    where
+        T::Err: fmt::Display,
+    {
+        match self.value(id) {
+            None => Ok(None),
+            Some(raw) => raw.parse().map(Some).map_err(|e| {
+                CliError::Invalid(format!("Invalid value '{}' for '{}': {}", raw, id, e))
+            }),
+        }
+    }
+}
+
+/// Why parsing stopped.
+#[derive(Debug)]
+pub enum CliError {
+    /// `--help` was requested; holds the text to print.
+    Help(String),
+    /// The arguments were wrong; holds the message plus usage hints.
+    Usage(String),
+    /// A value had the right shape but failed to parse, e.g. `--limit abc`.
+    Invalid(String),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Help(text) => f.write_str(text),
+            CliError::Usage(msg) | CliError::Invalid(msg) => write!(f, "Error: {}", msg),
+        }
+    }
+}
+
+/// Builds a usage error that points at the command's help.
+pub fn usage_error(spec: &CommandSpec, path: &str, msg: String) -> CliError {
+    CliError::Usage(format!(
+        "{}\n\n{}\n\nFor more information, try '{} --help'.",
+        msg,
+        spec.usage(path),
+        path
+    ))
+}
+
+/// Removes the options in `spec` from anywhere in `args`, returning their matches.
+///
+/// This is how global options work: they may appear before or after the
+/// command. Anything after a `--` is left alone.
+pub fn extract(spec: &CommandSpec, args: &mut Vec<String>) -> Result<Matches, CliError> {
+    let mut matches = Matches::default();
+    let mut remaining = Vec::with_capacity(args.len());
+    let mut iter = args.drain(..);
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            remaining.push(arg);
+            remaining.extend(iter.by_ref());
+            break;
+        }
+        let known = match lookup(spec, &arg) {
+            Some(found) => found,
+            None => {
+                remaining.push(arg);
+                continue;
+            }
+        };
+        let (found, inline_value) = known;
+        let inline_value = inline_value.map(String::from);
+        record(
+            spec,
+            BIN_NAME,
+            &mut matches,
+            found,
+            &arg,
+            inline_value,
+            &mut iter,
+        )?;
+    }
+    drop(iter);
+    *args = remaining;
+    Ok(matches)
+}
+
+/// Parses the arguments that follow a command's name.
+///
+/// `path` is how the command was invoked (e.g. `todo_cli config set`) and is
+/// only used in messages.
+pub fn parse(spec: &'static CommandSpec, args: &[String], path: &str) -> Result<Matches, CliError> {
+    let mut matches = Matches::default();
+    let positionals: Vec<&Arg> = spec.args.iter().filter(|a| a.is_positional()).collect();
+    let mut next_positional = 0;
+    let mut only_positionals = false;
+    let mut iter = args.iter().cloned();
+
+    while let Some(arg) = iter.next() {
+        if !only_positionals && arg == "--" {
+            only_positionals = true;
+            continue;
+        }
+        let looks_like_option = arg.starts_with('-') && arg != "-";
+        if !only_positionals && looks_like_option {
+            if arg == "-h" || arg == "--help" {
+                return Err(CliError::Help(spec.help(path)));
+            }
+            match lookup(spec, &arg) {
+                Some((found, inline_value)) => {
+                    let inline_value = inline_value.map(String::from);
+                    record(
+                        spec,
+                        path,
+                        &mut matches,
+                        found,
+                        &arg,
+                        inline_value,
+                        &mut iter,
+                    )?;
+                    continue;
+                }
+                // A negative number is a value, not an option.
+                None if arg[1..].parse::<f64>().is_err() => {
+                    return Err(usage_error(
+                        spec,
+                        path,
+                        format!("Unexpected argument '{}' for '{}'.", arg, spec.name),
+                    ));
+                }
+                None => {}
+            }
+        }
+
+        // For commands with sub-commands, the first positional word names one.
+        if !spec.subcommands.is_empty() {
+            let sub = spec
+                .resolve(&arg)
+                .map_err(|msg| usage_error(spec, path, msg))?;
+            let rest: Vec<String> = iter.collect();
+            let sub_matches = parse(sub, &rest, &format!("{} {}", path, sub.name))?;
+            matches.subcommand = Some((sub.name, Box::new(sub_matches)));
+            break;
+        }
+
+        let Some(positional) = positionals.get(next_positional) else {
+            return Err(usage_error(
+                spec,
+                path,
+                format!("Unexpected argument '{}' for '{}'.", arg, spec.name),
+            ));
+        };
+        matches.values.entry(positional.id).or_default().push(arg);
+        if !positional.multiple {
+            next_positional += 1;
+        }
+    }
+
+    for arg in spec.args.iter().filter(|a| a.required) {
+        if !matches.values.contains_key(arg.id) && !matches.flags.contains_key(arg.id) {
+            return Err(usage_error(
+                spec,
+                path,
+                format!(
+                    "Missing required argument {} for '{}'.",
+                    arg.usage(),
+                    spec.name
+                ),
+            ));
+        }
+    }
+    Ok(matches)
+}
+
+/// Finds the option an argument like `--file=x`, `--yes` or `-y` refers to.
+fn lookup<'a, 'b>(spec: &'a CommandSpec, arg: &'b str) -> Option<(&'a Arg, Option<&'b str>)> {
+    if let Some(long) = arg.strip_prefix("--") {
+        let (name, value) = match long.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (long, None),
+        };
+        return spec.find_long(name).map(|found| (found, value));
+    }
+    let mut chars = arg.strip_prefix('-')?.chars();
+    let c = chars.next()?;
+    let found = spec.find_short(c)?;
+    // `-n5` is the same as `-n 5` for options taking a value, and `-vv` is
+    // `-v -v` for flags.
+    let rest = &arg[1 + c.len_utf8()..];
+    match (rest.is_empty(), found.takes_value()) {
+        (true, _) => Some((found, None)),
+        (false, true) => Some((found, Some(rest))),
+        (false, false) if rest.chars().all(|r| r == c) => Some((found, None)),
+        (false, false) => None,
+    }
+}
+
+/// Stores one matched option, taking its value from `inline_value` or the next argument.
+fn record(
+    spec: &CommandSpec,
+    path: &str,
+    matches: &mut Matches,
+    found: &Arg,
+    arg: &str,
+    inline_value: Option<String>,
+    rest: &mut impl Iterator<Item = String>,
+) -> Result<(), CliError> {
+    if !found.takes_value() {
+        if inline_value.is_some() {
+            let msg = format!("'{}' does not take a value.", found.signature().trim());
+            return Err(usage_error(spec, path, msg));
+        }
+        // A repeated short flag like `-vv` counts once per letter.
+        let times = match arg.strip_prefix('-') {
+            Some(short) if !short.starts_with('-') => short.chars().count(),
+            _ => 1,
+        };
+        *matches.flags.entry(found.id).or_default() += times;
+        return Ok(());
+    }
+
+    let Some(value) = inline_value.or_else(|| rest.next()) else {
+        let msg = format!("Missing value for '{}'.", arg);
+        return Err(usage_error(spec, path, msg));
+    };
+    let values = matches.values.entry(found.id).or_default();
+    if !found.multiple {
+        values.clear();
+    }
+    values.push(value);
+    Ok(())
+}
+
+/// Options accepted before or after any command.
+pub static GLOBAL_ARGS: &[Arg] = &[
+    Arg::option("config", "PATH", "Use a different config file"),
+    Arg::option("file", "PATH", "Use a different to-do list file"),
+    Arg::option(
+        "list",
+        "NAME",
+        "Use a named list stored next to the main file",
+    ),
+    Arg::option(
+        "sort",
+        "ORDER",
+        "Sort the list by none, text, status, due, priority, urgency or manual",
+    ),
+    Arg::option("color", "WHEN", "Color output: auto, always or never"),
+    Arg::flag(
+        "plain",
+        "Write tasks as sentences, without box drawing, columns or color",
+    ),
+    Arg::flag("yes", "Skip confirmation prompts (also --force)")
+        .short('y')
+        .alias("force"),
+    Arg::flag("dry-run", "Show what would change without saving anything"),
+    Arg::flag(
+        "unlock",
+        "Ask for the passphrase to show secret tasks (or use $TODO_PASSPHRASE)",
+    ),
+    Arg::flag(
+        "read-only",
+        "Never write to the list; commands that change it fail",
+    ),
+    Arg::flag(
+        "rpc",
+        "Answer JSON-RPC requests on stdin/stdout, for editor plugins",
+    ),
+    Arg::flag(
+        "verbose",
+        "Report what the program does on stderr (-vv and -vvv for more)",
+    )
+    .short('v'),
+    Arg::flag("version", "Print version").short('V'),
+];
+
+/// The whole program: global options plus every command.
+pub static ROOT: CommandSpec = CommandSpec::new(BIN_NAME, "Rust To-Do List Manager")
+    .args(GLOBAL_ARGS)
+    .subcommands(COMMANDS)
+    .after_help(
+        "Commands may be shortened to any unambiguous prefix, e.g. 'l' for 'list'.\n\
+         A <TASK> is its number from 'list', or any unique part of its text.\n\
+         \n\
+         Environment:\n  \
+         TODO_CONFIG, TODO_STORAGE_BACKEND, TODO_FILE, TODO_STORAGE_URL,\n  \
+         TODO_LIST, TODO_SORT, TODO_URGENCY_DUE, TODO_URGENCY_PRIORITY,\n  \
+         TODO_URGENCY_AGE, TODO_URGENCY_AGE_DAYS, TODO_CONTEXT, TODO_COLOR,\n  \
+         TODO_PLAIN,\n  \
+         TODO_DATE_FORMAT, TODO_WEEK_START, TODO_HOLIDAYS, TODO_DEFAULT_COMMAND,\n  \
+         TODO_HOOKS_DIR,\n  \
+         TODO_REMINDER_LEADS, TODO_WAITING_NUDGE_DAYS, TODO_SOMEDAY_REVIEW_DAYS, TODO_TRASH_DAYS,\n  \
+         TODO_PRUNE_AFTER, TODO_PRUNE_ACTION, TODO_ARCHIVE_ON_DONE,\n  \
+         TODO_TIME_SERVICE, TODO_TIME_ACCOUNT, TODO_TIME_TOKEN,\n  \
+         TODO_WEBHOOK_URLS, TODO_WEBHOOK_SECRET, TODO_IMAP_URL, TODO_IMAP_USER,\n  \
+         TODO_IMAP_PASSWORD, TODO_SMTP_URL, TODO_SMTP_USER, TODO_SMTP_PASSWORD,\n  \
+         TODO_MAIL_FROM, TODO_SLACK_WEBHOOK_URL, TODO_SLACK_TOKEN,\n  \
+         TODO_SLACK_CHANNEL, TODO_SLACK_ALERTS, TODO_TELEGRAM_TOKEN,\n  \
+         TODO_TELEGRAM_CHATS, TODO_HTTP_TIMEOUT,\n  \
+         TODO_HTTP_RETRIES, TODO_HTTP_PROXY, TODO_MAX_TASK_LENGTH,\n  \
+         TODO_WORKFLOW, TODO_LOG_FILE, TODO_LANGUAGE, TODO_TIMEZONE,\n  \
+         TODO_OWNER_ONLY and TODO_READ_ONLY override the config file.\n  \
+         Messages follow LC_ALL, LC_MESSAGES or LANG unless 'language' is set.\n  \
+         Due times are in the zone of TZ, or the system's, unless 'timezone'\n  \
+         is set.\n  \
+         TODO_PASSPHRASE opens secret tasks instead of asking for it.\n  \
+         Flags override environment variables.\n\
+         \n\
+         Exit status:\n  \
+         0  success\n  \
+         1  other failure (e.g. confirmation refused)\n  \
+         2  invalid arguments, values or settings\n  \
+         3  task not found, or the reference matches several tasks\n  \
+         4  the task file could not be read or written\n  \
+         5  the list was changed by another program, so nothing was saved",
+    );
+
+static COMMANDS: &[CommandSpec] = &[
+    CommandSpec::new("add", "Add a new task to the list")
+        .args(&[
+            Arg::positional(
+                "text",
+                "TEXT",
+                "The task description, or '-' to add one task per line of stdin",
+            )
+            .multiple(),
+            Arg::flag(
+                "interactive",
+                "Prompt for the text, due date, priority, tags and list",
+            )
+            .short('i'),
+            Arg::option(
+                "due",
+                "DATE",
+                "Due date, with a time if you like: YYYY-MM-DD, fri 17:00, +3bd, +2h, ...",
+            )
+            .short('d'),
+            Arg::option("priority", "PRIORITY", "Priority: low, medium or high").short('p'),
+            Arg::option("tag", "TAG", "Add a tag (may be repeated)")
+                .short('t')
+                .multiple(),
+            Arg::option(
+                "habit",
+                "SCHEDULE",
+                "Make it a habit, done again and again: daily, weekdays, 3d, 2w, 2bd, ...",
+            ),
+            Arg::flag(
+                "no-dupes",
+                "Refuse a task that matches a pending one, instead of warning",
+            ),
+            Arg::flag("raw", "Keep +project, @context, due:, p1 and the like in the text"),
+            Arg::flag("secret", "Encrypt the text, masked in the list until --unlock"),
+        ])
+        .after_help(
+            "Details can be written into the text: +project, @context, #tag,\n\
+             due:DATE, and p1, p2 or p3 for high, medium or low priority. Put a\n\
+             backslash in front of a word to keep it as text, e.g. \\+1. A due\n\
+             time is local, as in due:fri@17:00, and kept in UTC, so it stays put\n\
+             when you change time zones.\n\
+             \n\
+             A task whose text matches a pending one, ignoring case, punctuation\n\
+             and small typos, is added with a warning; at a terminal you are\n\
+             offered to bump the existing task's priority instead.\n\
+             \n\
+             Examples:\n  \
+             todo_cli add \"Buy milk\"\n  \
+             todo_cli add \"Pay rent\" --due +3d --priority high --tag home\n  \
+             todo_cli add \"email Bob +work @computer due:fri p1\"\n  \
+             todo_cli add \"Call the bank\" --due \"tomorrow 9:30\"\n  \
+             some-command | todo_cli add -\n  \
+             todo_cli add --secret \"renew passport #1234 due:2027-01-31\"\n  \
+             todo_cli add -i",
+        ),
+    CommandSpec::new("paste", "Add a task from the clipboard")
+        .args(&[
+            Arg::option(
+                "due",
+                "DATE",
+                "Due date, with a time if you like: YYYY-MM-DD, fri 17:00, +3bd, +2h, ...",
+            )
+            .short('d'),
+            Arg::option("priority", "PRIORITY", "Priority: low, medium or high").short('p'),
+            Arg::option("tag", "TAG", "Add a tag (may be repeated)")
+                .short('t')
+                .multiple(),
+            Arg::flag(
+                "no-dupes",
+                "Refuse a task that matches a pending one, instead of warning",
+            ),
+        ])
+        .after_help(
+            "The first line of the clipboard becomes the task and the rest its\n\
+             notes. The clipboard is read with pbpaste on macOS, PowerShell on\n\
+             Windows, and wl-paste, xclip or xsel elsewhere.\n\
+             \n\
+             Example:\n  \
+             todo_cli paste --tag reading",
+        ),
+    CommandSpec::new("template", "Add a set of tasks from a template in the config")
+        .subcommands(&[
+            CommandSpec::new("list", "Print the name of every template"),
+            CommandSpec::new("show", "Print a template's tasks").args(&[Arg::positional(
+                "name",
+                "NAME",
+                "The template's name",
+            )
+            .required()]),
+            CommandSpec::new("apply", "Add a template's tasks to the list").args(&[
+                Arg::positional("name", "NAME", "The template's name").required(),
+                Arg::option("var", "NAME=VALUE", "Fill in {NAME} (may be repeated)").multiple(),
+                Arg::option(
+                    "due",
+                    "DATE",
+                    "Due date for tasks that don't give one: YYYY-MM-DD, fri, +3d, ...",
+                )
+                .short('d'),
+                Arg::option(
+                    "priority",
+                    "PRIORITY",
+                    "Priority for tasks that don't give one: low, medium or high",
+                )
+                .short('p'),
+                Arg::option("tag", "TAG", "Add a tag to every task (may be repeated)")
+                    .short('t')
+                    .multiple(),
+            ]),
+        ])
+        .after_help(
+            "Templates live in the [templates] table of the config file. Each\n\
+             line is a task, written as for 'add' (+project, @context, #tag,\n\
+             due:DATE, p1), with {NAME} filled in from --var NAME=VALUE:\n\
+             \n  \
+             [templates]\n  \
+             release-checklist = \"\"\"\n  \
+             Tag v{version} +release p1\n  \
+             Write the release notes for {version} #docs due:+2d\n  \
+             \"\"\"\n\
+             \n\
+             Examples:\n  \
+             todo_cli template list\n  \
+             todo_cli template apply release-checklist --var version=1.4",
+        ),
+    CommandSpec::new("list", "List all tasks")
+        .aliases(&["ls"])
+        .args(&[
+            Arg::option("tag", "TAG", "Only tasks with this tag, e.g. work or OVERDUE (may be repeated)")
+                .short('t')
+                .multiple(),
+            Arg::option("project", "PROJECT", "Only tasks in this project"),
+            Arg::option("context", "CONTEXT", "Only tasks with this context (may be repeated)")
+                .short('c')
+                .multiple(),
+            Arg::flag("pending", "Only tasks that aren't completed"),
+            Arg::flag("done", "Only completed tasks"),
+            Arg::flag("someday", "Only tasks parked as someday/maybe"),
+            Arg::flag("mine", "Only tasks you added"),
+            Arg::option(
+                "status",
+                "STATUS",
+                "Only tasks in this workflow status, e.g. doing (may be repeated)",
+            )
+            .short('s')
+            .multiple(),
+            Arg::option(
+                "stale",
+                "AGE",
+                "Only pending tasks added at least this long ago, e.g. 30d or 8w",
+            ),
+        ])
+        .after_help(
+            "Examples:\n  \
+             todo_cli list\n  \
+             todo_cli list --pending --tag work\n  \
+             todo_cli list --status doing\n  \
+             todo_cli list --mine\n  \
+             todo_cli list --stale 30d\n  \
+             todo_cli list --tag OVERDUE\n\
+             \n\
+             Tags in capitals are virtual, worked out from each task as it is\n\
+             listed: OVERDUE, TODAY, WEEK (due by the end of the week), BLOCKED, PINNED and\n\
+             WAITING.",
+        ),
+    CommandSpec::new("search", "List the tasks whose text, notes or comments match")
+        .args(&[
+            Arg::positional("words", "WORDS", "Words (or their starts) that must all appear").multiple(),
+            Arg::option(
+                "regex",
+                "PATTERN",
+                "A regular expression that must match the text, notes or a comment",
+            )
+            .short('e'),
+            Arg::option("tag", "TAG", "Only tasks with this tag, e.g. work or OVERDUE (may be repeated)")
+                .short('t')
+                .multiple(),
+            Arg::option("project", "PROJECT", "Only tasks in this project"),
+            Arg::option("context", "CONTEXT", "Only tasks with this context (may be repeated)")
+                .short('c')
+                .multiple(),
+            Arg::flag("pending", "Only tasks that aren't completed"),
+            Arg::flag("done", "Only completed tasks"),
+            Arg::flag("someday", "Only tasks parked as someday/maybe"),
+            Arg::flag("mine", "Only tasks you added"),
+            Arg::option(
+                "status",
+                "STATUS",
+                "Only tasks in this workflow status, e.g. doing (may be repeated)",
+            )
+            .short('s')
+            .multiple(),
+            Arg::option(
+                "stale",
+                "AGE",
+                "Only pending tasks added at least this long ago, e.g. 30d or 8w",
+            ),
+        ])
+        .after_help(
+            "Words are found by their start, whatever their case and accents,\n\
+             and the best matches are listed first. A regular\n\
+             expression takes ., [a-z], \\d, \\w, \\s, \\b, ^, $, *, +, ?, {n,m},\n\
+             | and (...); case is ignored unless it has an upper-case letter.\n\
+             \n\
+             Examples:\n  \
+             todo_cli search invoice\n  \
+             todo_cli search --regex '^call .*bank'\n  \
+             todo_cli search --regex '\\bQ[1-4]\\b' --pending --project work",
+        ),
+    CommandSpec::new("view", "Save filters as named views and list their tasks")
+        .subcommands(&[
+            CommandSpec::new("list", "Print every view"),
+            CommandSpec::new("show", "List a view's tasks (also 'todo_cli NAME')").args(&[
+                Arg::positional("name", "NAME", "The view's name").required(),
+            ]),
+            CommandSpec::new("save", "Save a filter expression as a view").args(&[
+                Arg::positional("name", "NAME", "What to call it").required(),
+                Arg::positional("query", "EXPRESSION", "Which tasks, and how to list them")
+                    .required()
+                    .multiple(),
+            ]),
+            CommandSpec::new("delete", "Remove a view").args(&[Arg::positional(
+                "name",
+                "NAME",
+                "The view's name",
+            )
+            .required()]),
+        ])
+        .after_help(
+            "An expression is conditions a task must all meet, unless joined by\n\
+             'or': words of the text, prio:high, due:fri, due.before:tomorrow,\n\
+             due.after:DATE, due:none, #tag or tag:TAG, +project, @context,\n\
+             status:doing, owner:NAME and is:pending, done, overdue, waiting,\n\
+             someday or habit. 'not' or '-' turns a condition around, and\n\
+             parentheses group. sort:ORDER (none, text, status, due, priority,\n\
+             urgency, manual) and format:FORMAT (list, compact, json) say how the tasks\n\
+             are listed. Dates are read when the view is shown. Views are kept in\n\
+             the [views] table of the config file.\n\
+             \n\
+             Examples:\n  \
+             todo_cli view save urgent \"prio:high or due.before:tomorrow\"\n  \
+             todo_cli view save work \"+work -is:done sort:due format:compact\"\n  \
+             todo_cli urgent",
+        ),
+    CommandSpec::new("context", "Keep every command to the tasks of one context")
+        .args(&[
+            Arg::positional(
+                "action",
+                "ACTION",
+                "A context's name to switch to it, none, define or delete [default: show]",
+            ),
+            Arg::positional("args", "ARGS", "The name, then the expression to define").multiple(),
+        ])
+        .after_help(
+            "A context is a filter expression, as for 'view', that stays on until\n\
+             'context none': list, search, next, due, modify and views only see\n\
+             the tasks it lets through, and 'add' gives new tasks the tags,\n\
+             project, contexts and priority it asks for. Contexts are kept in the\n\
+             [contexts] table of the config file, the active one as 'context'.\n\
+             \n\
+             Examples:\n  \
+             todo_cli context define work \"+work -#someday\"\n  \
+             todo_cli context work\n  \
+             todo_cli context none",
+        ),
+    CommandSpec::new("watch", "Keep the list on screen, updating it as it changes")
+        .args(&[
+            Arg::option("tag", "TAG", "Only tasks with this tag, e.g. work or OVERDUE (may be repeated)")
+                .short('t')
+                .multiple(),
+            Arg::option("project", "PROJECT", "Only tasks in this project"),
+            Arg::option("context", "CONTEXT", "Only tasks with this context (may be repeated)")
+                .short('c')
+                .multiple(),
+            Arg::flag("pending", "Only tasks that aren't completed"),
+            Arg::flag("done", "Only completed tasks"),
+            Arg::flag("someday", "Only tasks parked as someday/maybe"),
+            Arg::flag("mine", "Only tasks you added"),
+            Arg::option(
+                "status",
+                "STATUS",
+                "Only tasks in this workflow status, e.g. doing (may be repeated)",
+            )
+            .short('s')
+            .multiple(),
+            Arg::option(
+                "stale",
+                "AGE",
+                "Only pending tasks added at least this long ago, e.g. 30d or 8w",
+            ),
+            Arg::option("interval", "SECONDS", "Redraw at least this often [default: 10]")
+                .short('n'),
+        ])
+        .after_help(
+            "Shows the list like 'list', with the same filters, and draws it again\n\
+             as soon as the file changes (e.g. after a command in another terminal)\n\
+             and every --interval seconds, so due dates stay current. Press Ctrl-C\n\
+             to stop.\n\
+             \n\
+             Examples:\n  \
+             todo_cli watch --pending\n  \
+             todo_cli watch --tag work -n 60",
+        ),
+    CommandSpec::new("due", "List tasks by when they are due").after_help(
+        "Pending tasks with a due date, under the headings Overdue, Today,\n\
+         Tomorrow, This week (to its last day) and Later. Tasks parked as\n\
+         someday/maybe are left out.",
+    ),
+    CommandSpec::new("prompt", "Print a terse task count for a shell prompt").after_help(
+        "Prints the overdue and pending counts as briefly as possible: 3!/7 is\n\
+         3 overdue out of 7 pending, 7 alone means nothing is overdue, and\n\
+         nothing is printed when nothing is pending. Only the counts are read,\n\
+         so it is fast enough to run every time the prompt is drawn.\n\
+         \n\
+         Examples:\n  \
+         PS1='$(todo_cli prompt 2>/dev/null) \\$ '          (bash)\n  \
+         PROMPT='$(todo_cli prompt 2>/dev/null) %# '        (zsh, with prompt_subst)",
+    ),
+    CommandSpec::new("segment", "Print a prompt segment for Starship or powerline-go")
+        .args(&[
+            Arg::option(
+                "format",
+                "FORMAT",
+                "starship or powerline [default: starship]",
+            )
+            .short('f'),
+            Arg::option(
+                "min-pending",
+                "N",
+                "Only show it with at least N pending tasks [default: 1]",
+            ),
+            Arg::option(
+                "min-overdue",
+                "N",
+                "Only show it with at least N overdue tasks [default: 0]",
+            ),
+        ])
+        .after_help(
+            "Prints the counts like 'prompt' (3!/7 is 3 overdue out of 7 pending),\n\
+             in the form a prompt framework expects. For Starship it is the text\n\
+             alone, and nothing when hidden, so the module disappears; style it in\n\
+             starship.toml. For powerline-go it is a plugin's JSON list of\n\
+             segments, red while anything is overdue, and [] when hidden.\n\
+             \n\
+             Examples (starship.toml, then a powerline-go plugin):\n  \
+             [custom.todo]\n  \
+             command = \"todo_cli segment --min-overdue 1\"\n  \
+             when = true\n  \
+             style = \"bold red\"\n\
+             \n  \
+             #!/bin/sh  (saved as powerline-go-todo, then -modules ...,todo)\n  \
+             exec todo_cli segment --format powerline --min-pending 5",
+        ),
+    CommandSpec::new("tmux-status", "Print the due and overdue counts for tmux's status line")
+        .after_help(
+            "Prints e.g. '2 overdue · 1 due today · 7 pending', leaving out counts\n\
+             that are zero and printing nothing when nothing is pending. Overdue\n\
+             is red and due today yellow, in tmux's own markup; --color never\n\
+             leaves the colors out. tmux runs it again every status-interval\n\
+             seconds (15 by default).\n\
+             \n\
+             Example (~/.tmux.conf):\n  \
+             set -g status-right '#(todo_cli tmux-status) %H:%M'",
+        ),
+    CommandSpec::new("show", "Print every detail of one task").args(&[Arg::positional(
+        "task",
+        "TASK",
+        "The task number, id:<id>, or any unique part of its text",
+    )
+    .required()
+    .multiple()]),
+    CommandSpec::new("history", "Show the recorded changes to the list or one task")
+        .args(&[
+            Arg::positional(
+                "task",
+                "TASK",
+                "The task number, id:<id>, or any unique part of its text",
+            )
+            .multiple(),
+            Arg::option(
+                "count",
+                "N",
+                "Without a task, how many of the latest changes to show [default: 20]",
+            )
+            .short('n'),
+        ])
+        .after_help(
+            "Changes are recorded in audit.jsonl next to the config file, with\n\
+             when they were made, by whom, through what (cli, api, mcp, rpc,\n\
+             script:<name> or hook:<name>) and with which command. Without a task,\n\
+             the latest changes to the whole list are shown, oldest first. Use\n\
+             id:<id> for a task that has been removed.\n\
+             \n\
+             Examples:\n  \
+             todo_cli history\n  \
+             todo_cli history -n 100\n  \
+             todo_cli history 3",
+        ),
+    CommandSpec::new("remind", "Set a time to be reminded of a task")
+        .args(&[
+            Arg::positional(
+                "task",
+                "TASK",
+                "The task number, id:<id>, or any unique part of its text",
+            )
+            .required(),
+            Arg::positional(
+                "when",
+                "WHEN",
+                "When to remind you: 'tomorrow 9am', 'fri 14:30', '+2h', ...",
+            )
+            .multiple(),
+            Arg::flag("clear", "Remove the task's reminders"),
+        ])
+        .after_help(
+            "A task can have several reminders. Without WHEN, they are listed.\n\
+             Times are UTC, like the dates 'today' and 'tomorrow'; a date alone\n\
+             means 9:00. The reminder daemon announces each reminder when its\n\
+             time comes, and 'notify' includes those that went off today.\n\
+             \n\
+             Examples:\n  \
+             todo_cli remind 4 tomorrow 9am\n  \
+             todo_cli remind \"Pay rent\" fri 14:30\n  \
+             todo_cli remind 4 --clear",
+        ),
+    CommandSpec::new("wait", "Mark a task as waiting on someone")
+        .args(&[
+            Arg::positional(
+                "task",
+                "TASK",
+                "The task number, id:<id>, or any unique part of its text",
+            )
+            .required()
+            .multiple(),
+            Arg::option("for", "PERSON", "Who the task was handed to or waits on"),
+            Arg::option("since", "DATE", "When it was handed over [default: today]"),
+            Arg::flag("clear", "Stop waiting: the task is yours again"),
+        ])
+        .after_help(
+            "Tasks waiting longer than waiting_nudge_days (default 7) are flagged\n\
+             by 'waiting', and brought up by the reminder daemon and 'notify'.\n\
+             \n\
+             Examples:\n  \
+             todo_cli wait 5 --for bob\n  \
+             todo_cli wait \"contract\" --for legal --since 2025-06-02\n  \
+             todo_cli wait 5 --clear",
+        ),
+    CommandSpec::new("waiting", "List the tasks waiting on someone").args(&[Arg::option(
+        "for",
+        "PERSON",
+        "Only tasks waiting on this person",
+    )]),
+    CommandSpec::new("pin", "Pin a task to keep it in sight")
+        .args(&[
+            Arg::positional(
+                "task",
+                "TASK",
+                "The task number, id:<id>, or any unique part of its text",
+            )
+            .required()
+            .multiple(),
+            Arg::flag("clear", "Unpin the task"),
+        ])
+        .after_help(
+            "Pinned tasks are marked with a pin in the list and have the PINNED\n\
+             virtual tag, so 'list --tag PINNED' shows just them.\n\
+             \n\
+             Examples:\n  \
+             todo_cli pin 4\n  \
+             todo_cli pin \"quarterly report\" --clear",
+        ),
+    CommandSpec::new("qr", "Show a task as a QR code, to scan it onto a phone")
+        .args(&[
+            Arg::positional(
+                "task",
+                "TASK",
+                "The task number, id:<id>, or any unique part of its text",
+            )
+            .required()
+            .multiple(),
+            Arg::option(
+                "link",
+                "URL",
+                "Encode a link to the task's page on the dashboard at URL instead",
+            ),
+            Arg::flag("invert", "Swap dark and light, for light text on a dark terminal"),
+        ])
+        .after_help(
+            "The code holds the task's text, due date, priority, project, contexts\n\
+             and tags, its notes and its checklist, for a phone's camera to read as\n\
+             plain text. With --link it holds the address of the task's page on a\n\
+             'dashboard' the phone can reach instead, which shows it as it is then.\n\
+             Dark modules are drawn as blocks; if the code won't scan, try\n\
+             --invert.\n\
+             \n\
+             Examples:\n  \
+             todo_cli qr 4\n  \
+             todo_cli qr 4 --link http://192.168.1.20:9000",
+        ),
+    CommandSpec::new("move", "Arrange a task by hand, for --sort manual")
+        .args(&[
+            Arg::positional(
+                "task",
+                "TASK",
+                "The task number, id:<id>, or any unique part of its text",
+            )
+            .required()
+            .multiple(),
+            Arg::option("before", "TASK", "Put it just before this task"),
+            Arg::option("after", "TASK", "Put it just after this task"),
+            Arg::flag("top", "Put it first"),
+            Arg::flag("bottom", "Put it last"),
+        ])
+        .after_help(
+            "The order is kept with the tasks, and '--sort manual' (or default_sort =\n\
+             \"manual\") lists them in it. Task numbers don't change. Tasks added\n\
+             since come last, until they are moved too.\n\
+             \n\
+             Examples:\n  \
+             todo_cli move 7 --top\n  \
+             todo_cli move 7 --before 2\n  \
+             todo_cli --sort manual list",
+        ),
+    CommandSpec::new("up", "Move a task up the manual order").args(&[
+        Arg::positional(
+            "task",
+            "TASK",
+            "The task number, id:<id>, or any unique part of its text",
+        )
+        .required(),
+        Arg::positional("steps", "STEPS", "How many places [default: 1]"),
+    ]),
+    CommandSpec::new("down", "Move a task down the manual order").args(&[
+        Arg::positional(
+            "task",
+            "TASK",
+            "The task number, id:<id>, or any unique part of its text",
+        )
+        .required(),
+        Arg::positional("steps", "STEPS", "How many places [default: 1]"),
+    ]),
+    CommandSpec::new("secret", "Encrypt a task's text and notes")
+        .args(&[
+            Arg::positional(
+                "task",
+                "TASK",
+                "The task number, id:<id>, or any unique part of its text",
+            )
+            .required()
+            .multiple(),
+            Arg::flag("clear", "Decrypt it for good"),
+        ])
+        .after_help(
+            "A secret task's text and notes are sealed with a passphrase and\n\
+             shown as a mask until '--unlock' is given; its dates, tags and the\n\
+             rest stay readable, so it is still listed, sorted and reminded of.\n\
+             The passphrase is asked for at the terminal, or read from\n\
+             $TODO_PASSPHRASE. Secret tasks are found by number, id or hash, and\n\
+             must be made plain again with --clear before their text is edited.\n\
+             What a task said before it was made secret stays in the audit log,\n\
+             the undo history and snapshots taken before.\n\
+             \n\
+             Examples:\n  \
+             todo_cli secret 4\n  \
+             todo_cli --unlock list\n  \
+             todo_cli secret 4 --clear",
+        ),
+    CommandSpec::new("someday", "Park a task as someday/maybe, out of the list")
+        .args(&[
+            Arg::positional(
+                "task",
+                "TASK",
+                "The task number, id:<id>, or any unique part of its text",
+            )
+            .required()
+            .multiple(),
+            Arg::flag("clear", "Bring the task back into the list"),
+        ])
+        .after_help(
+            "Parked tasks are left out of 'list' and of due-date notifications.\n\
+             'list --someday' shows them, and 'review' brings each one up again\n\
+             after someday_review_days (default 30).\n\
+             \n\
+             Examples:\n  \
+             todo_cli someday 4\n  \
+             todo_cli someday \"learn piano\" --clear",
+        ),
+    CommandSpec::new(
+        "review",
+        "Look again at someday/maybe tasks that have been parked a while",
+    )
+    .after_help(
+        "At a terminal you are asked, for each task, whether to bring it back\n\
+             into the list; tasks left parked come up again after another\n\
+             someday_review_days. Otherwise the tasks are only listed.",
+    ),
+    CommandSpec::new("heatmap", "Show a year of completed tasks as a grid of days")
+        .args(&[Arg::option(
+            "weeks",
+            "WEEKS",
+            "How many weeks to show, up to this one [default: 53]",
+        )
+        .short('w')])
+        .after_help(
+            "Each column is a week and each row a weekday; the darker a day, the\n\
+             more tasks were completed on it. Completions are read from the audit\n\
+             log and from when the tasks still on the list were completed.\n\
+             \n\
+             Examples:\n  \
+             todo_cli heatmap\n  \
+             todo_cli heatmap --weeks 26",
+        ),
+    CommandSpec::new("holidays", "List the public holidays business days skip")
+        .args(&[Arg::option(
+            "year",
+            "YEAR",
+            "The holidays of a whole year [default: the next twelve months]",
+        )
+        .short('y')])
+        .after_help(
+            "The holidays come from the 'holidays' setting: countries (us, gb, de,\n\
+             fr, es) and iCalendar files, separated by commas. Business-day due\n\
+             dates like +3bd, habits kept on weekdays, and the lead times of\n\
+             'daemon' and 'discord' skip them.\n\
+             \n\
+             Examples:\n  \
+             todo_cli config set holidays us\n  \
+             todo_cli config set holidays de,~/bavaria.ics\n  \
+             todo_cli holidays --year 2027",
+        ),
+    CommandSpec::new("stats", "Show statistics about the list")
+        .args(&[Arg::flag(
+            "csv",
+            "Print the numbers per day, tag and project as CSV",
+        )])
+        .after_help(
+            "The CSV has one row per day, tag and project, with the columns\n\
+             group,key,added,completed,pending; cells that don't apply are empty.\n\
+             Days are read from the audit log, so they include removed tasks.\n\
+             \n\
+             Examples:\n  \
+             todo_cli stats\n  \
+             todo_cli stats --csv > stats.csv",
+        ),
+    CommandSpec::new("tags", "List the tags in use, with counts").after_help(
+        "Each tag is shown with how many pending and completed tasks have it,\n\
+         and when it was last used: the last day the audit log shows it being\n\
+         given to a task, or a task with it being completed.",
+    ),
+    CommandSpec::new("tag", "Rename or merge tags on every task")
+        .subcommands(&[
+            CommandSpec::new("rename", "Rename a tag on every task that has it").args(&[
+                Arg::positional("from", "OLD", "The tag to rename").required(),
+                Arg::positional("to", "NEW", "Its new name, which no task may have yet").required(),
+            ]),
+            CommandSpec::new("merge", "Fold one tag into another").args(&[
+                Arg::positional("from", "TAG", "The tag to fold in and remove").required(),
+                Arg::positional("to", "INTO", "The tag to keep").required(),
+            ]),
+        ])
+        .after_help(
+            "Every task changed is saved at once, so the list is never left half\n\
+             renamed; --dry-run shows what would change. Without a subcommand,\n\
+             the tags are listed like 'tags'.\n\
+             \n\
+             Examples:\n  \
+             todo_cli tag rename wrk work\n  \
+             todo_cli tag merge urgent important",
+        ),
+    CommandSpec::new("prune", "Take long-completed tasks off the list")
+        .args(&[
+            Arg::option(
+                "completed-before",
+                "AGE",
+                "Tasks completed longer ago than this, e.g. 90d or 12w [default: prune_after]",
+            ),
+            Arg::flag("archive", "Move them to the archive [default]"),
+            Arg::flag("delete", "Remove them instead, which puts them in the trash"),
+        ])
+        .after_help(
+            "Keeps the working list small. By default the tasks are moved to the\n\
+             list's archive, an ordinary list called 'archive' (or 'NAME.archive'\n\
+             for the list NAME) that --list archive shows; prune_action = \"delete\"\n\
+             removes them instead. With prune_after set, e.g. to 90d, 'prune' alone\n\
+             applies that policy, to run from cron. Tasks completed before the\n\
+             time of completion was recorded are left alone.\n\
+             \n\
+             Examples:\n  \
+             todo_cli prune --completed-before 90d\n  \
+             todo_cli --dry-run prune --completed-before 4w --delete\n  \
+             0 3 * * * todo_cli prune      (crontab, with prune_after set)",
+        ),
+    CommandSpec::new("trash", "List, restore or empty removed tasks")
+        .subcommands(&[
+            CommandSpec::new("list", "List the tasks in the trash"),
+            CommandSpec::new("restore", "Put tasks from the trash back on the list").args(&[
+                Arg::positional("id", "ID", "The task's id, as 'trash list' shows it")
+                    .required()
+                    .multiple(),
+            ]),
+            CommandSpec::new("empty", "Remove every task in the trash for good"),
+        ])
+        .after_help(
+            "Tasks removed from the list, by delete, clear, a sync or anything\n\
+             else, are kept in the trash for trash_days days (30 by default; 0\n\
+             keeps none) before being removed for good. Restored tasks keep their\n\
+             ids, so their history carries on.\n\
+             \n\
+             Examples:\n  \
+             todo_cli trash list\n  \
+             todo_cli trash restore 7\n  \
+             todo_cli trash empty",
+        ),
+    CommandSpec::new("estimate", "Set how long a task is expected to take")
+        .args(&[
+            Arg::positional(
+                "task",
+                "TASK",
+                "The task number, id:<id>, or any unique part of its text",
+            )
+            .required(),
+            Arg::positional(
+                "duration",
+                "DURATION",
+                "How long, e.g. 45m, 2h or 1h30m; 'none' clears it",
+            )
+            .required(),
+        ])
+        .after_help(
+            "'time estimates' compares the estimates with the time tracked.\n\
+             \n\
+             Examples:\n  \
+             todo_cli estimate 3 2h\n  \
+             todo_cli estimate report 1h30m\n  \
+             todo_cli estimate 3 none",
+        ),
+    CommandSpec::new("time", "Track the time spent on tasks")
+        .subcommands(&[
+            CommandSpec::new("start", "Start timing a task, stopping any other timer").args(&[
+                Arg::positional(
+                    "task",
+                    "TASK",
+                    "The task number, id:<id>, or any unique part of its text",
+                )
+                .required()
+                .multiple(),
+            ]),
+            CommandSpec::new("stop", "Stop the running timer"),
+            CommandSpec::new("log", "Record time spent on a task without a timer").args(&[
+                Arg::positional(
+                    "task",
+                    "TASK",
+                    "The task number, id:<id>, or any unique part of its text",
+                )
+                .required(),
+                Arg::positional("duration", "DURATION", "How long, e.g. 45m or 1h30m")
+                    .required(),
+                Arg::option(
+                    "at",
+                    "TIME",
+                    "When the time started, e.g. '9am' or 'yesterday 14:00' [default: that long ago]",
+                ),
+            ]),
+            CommandSpec::new("estimates", "Compare estimated with actual time").args(&[
+                Arg::option(
+                    "group-by",
+                    "GROUPING",
+                    "One row per task, project or tag [default: task]",
+                )
+                .short('g'),
+                Arg::flag("done", "Only completed tasks, whose time is final"),
+            ]),
+            CommandSpec::new("report", "Add up the time spent per project, tag or task").args(&[
+                Arg::option(
+                    "group-by",
+                    "GROUPING",
+                    "One row per project, tag or task [default: project]",
+                )
+                .short('g'),
+                Arg::option("since", "DATE", "From this day on [default: ever]"),
+                Arg::option("until", "DATE", "Up to and including this day [default: today]"),
+            ]),
+            CommandSpec::new("push", "Send tracked time to Toggl or Harvest").args(&[
+                Arg::option("since", "DATE", "Only time from this day on [default: ever]"),
+            ]),
+        ])
+        .after_help(
+            "Without a subcommand, shows what is being timed. One task is timed at\n\
+             a time, and completing a task stops its timer. 'show' prints the time\n\
+             spent on a task. In a report by tag, a task with several tags counts\n\
+             towards each, but only once towards the total.\n\
+             \n\
+             'push' sends each finished session once to the time_service (toggl\n\
+             or harvest), into time_account (the Toggl workspace id or Harvest\n\
+             account id) with time_token. The [time_projects] table maps the\n\
+             list's projects to the service's: a Toggl project id, or a Harvest\n\
+             PROJECT_ID/TASK_ID, e.g. 'config set time_projects.work 123/456'.\n\
+             \n\
+             Examples:\n  \
+             todo_cli time start 3\n  \
+             todo_cli time stop\n  \
+             todo_cli time log 3 45m --at 'yesterday 16:00'\n  \
+             todo_cli time estimates --group-by project\n  \
+             todo_cli time report --group-by tag --since 2025-01-01\n  \
+             todo_cli time push --since 2025-01-01",
+        ),
+    CommandSpec::new("timesheet", "Show the time spent per day and task")
+        .args(&[
+            Arg::flag("week", "The whole week, from 'week_start' [default: just the day]")
+                .short('w'),
+            Arg::option("date", "DATE", "The day, or a day in the week [default: today]")
+                .short('d'),
+            Arg::flag("csv", "Print one row per day and task as CSV"),
+        ])
+        .after_help(
+            "Adds up the time tracked with 'time' for each task on each day. The\n\
+             CSV has the columns date, id, task, project, hours (to two decimals)\n\
+             and minutes, to paste or import into a time-keeping system. Time on\n\
+             tasks since removed from the list isn't counted.\n\
+             \n\
+             Examples:\n  \
+             todo_cli timesheet --week\n  \
+             todo_cli timesheet --week --date 2025-03-07 --csv > week.csv",
+        ),
+    CommandSpec::new("projects", "List the projects, with how far along each is")
+        .args(&[Arg::flag(
+            "lists",
+            "List every list instead, from the main one to each named list",
+        )])
+        .after_help(
+            "Each project is shown with its pending and completed tasks, how much\n\
+             of it is done and the soonest due date among its pending tasks.\n\
+             \n\
+             Examples:\n  \
+             todo_cli projects\n  \
+             todo_cli projects --lists",
+        ),
+    CommandSpec::new("board", "Show the tasks as a kanban board")
+        .args(&[Arg::option(
+            "by",
+            "GROUPING",
+            "What the columns are: status, tag or project [default: status]",
+        )
+        .short('b')])
+        .subcommands(&[CommandSpec::new("move", "Move a task to another column").args(&[
+            Arg::positional(
+                "task",
+                "TASK",
+                "The task number, id:<id>, or any unique part of its text",
+            )
+            .required(),
+            Arg::positional(
+                "column",
+                "COLUMN",
+                "A status such as todo, doing, waiting or done; a tag or project with --by; or none",
+            )
+            .required(),
+            Arg::option("for", "PERSON", "Who the task waits on, when moving it to waiting"),
+            Arg::option("from", "TAG", "The tag to take off, when the task has several"),
+        ])])
+        .after_help(
+            "By status the columns are those of the 'workflow' setting (todo, doing\n\
+             and done unless configured otherwise), with waiting and someday before\n\
+             done, and cancelled when anything is. By tag or project, each\n\
+             pending task is in the column of each of its tags, or of its project,\n\
+             and tasks without one are in a column of their own.\n\
+             Moving a card changes what puts it in a column: its status, one of\n\
+             its tags, or its project. Columns share the width given by $COLUMNS.\n\
+             \n\
+             Examples:\n  \
+             todo_cli board\n  \
+             todo_cli board --by project\n  \
+             todo_cli board move 4 done\n  \
+             todo_cli board move 4 waiting --for bob\n  \
+             todo_cli board --by tag move 4 review --from draft",
+        ),
+    CommandSpec::new("reindex", "Rebuild the index used to look up tasks by number"),
+    CommandSpec::new("done", "Mark a task as complete")
+        .aliases(&["d"])
+        .args(&[Arg::positional(
+            "task",
+            "TASK",
+            "The task number, id:<id>, or any unique part of its text",
+        )
+        .required()
+        .multiple()]),
+    CommandSpec::new("habit", "List habits and their streaks, or make a task a habit")
+        .args(&[
+            Arg::positional(
+                "task",
+                "TASK",
+                "The task number, id:<id>, or any unique part of its text",
+            )
+            .multiple(),
+            Arg::option(
+                "every",
+                "SCHEDULE",
+                "How often: daily, weekly, weekdays, 3d, 2w, 2bd, ...",
+            ),
+            Arg::flag("stop", "Make the task an ordinary one again"),
+        ])
+        .after_help(
+            "'done' on a habit records the day instead of completing it, and makes\n\
+             it due again a period later. The streak is how many times in a row\n\
+             it was done on schedule; it is kept until the habit is overdue.\n\
+             'weekdays' and business days (2bd) skip Saturdays and Sundays.\n\
+             \n\
+             Examples:\n  \
+             todo_cli add \"Stretch\" --habit daily\n  \
+             todo_cli add \"Check the build\" --habit weekdays\n  \
+             todo_cli habit\n  \
+             todo_cli habit 4 --every 2d",
+        ),
+    CommandSpec::new("depend", "Make a task wait until others are completed")
+        .aliases(&["dep"])
+        .args(&[
+            Arg::positional(
+                "task",
+                "TASK",
+                "The task number, id:<id>, or any unique part of its text",
+            )
+            .required(),
+            Arg::option("on", "TASK", "A task that has to be completed first (may be repeated)")
+                .multiple(),
+            Arg::option("remove", "TASK", "No longer depend on this task (may be repeated)")
+                .multiple(),
+            Arg::flag("clear", "No longer depend on any task"),
+        ])
+        .after_help(
+            "A task is blocked while any task it depends on is pending, and\n\
+             unblocks by itself once they are all completed. 'list' shows what\n\
+             blocks a task, and 'next' leaves blocked tasks out. Without options,\n\
+             the tasks it depends on are listed.\n\
+             \n\
+             Examples:\n  \
+             todo_cli depend 5 --on 3 --on 4\n  \
+             todo_cli depend 5 --remove 4\n  \
+             todo_cli depend 5",
+        ),
+    CommandSpec::new("next", "Suggest what to work on next")
+        .args(&[Arg::option(
+            "count",
+            "COUNT",
+            "How many tasks to suggest [default: 5]",
+        )
+        .short('n')])
+        .after_help(
+            "Suggests pending tasks that can be started now: not blocked by\n\
+             another task, not waiting on anyone and not parked as someday/maybe.\n\
+             The most urgent come first: urgency grows as the due date nears\n\
+             and passes, with priority, and with each day a task goes untouched.\n\
+             The urgency_due, urgency_priority, urgency_age and urgency_age_days\n\
+             settings weigh them (12, 6, 3 and 30 days unless set).",
+        ),
+    CommandSpec::new("start", "Start working on a task")
+        .args(&[Arg::positional(
+            "task",
+            "TASK",
+            "The task number, id:<id>, or any unique part of its text",
+        )
+        .required()
+        .multiple()])
+        .after_help(
+            "Moves the task to the status after 'todo' in the workflow: 'doing'\n\
+             unless the 'workflow' setting says otherwise.",
+        ),
+    CommandSpec::new("cancel", "Drop a task without doing it")
+        .args(&[Arg::positional(
+            "task",
+            "TASK",
+            "The task number, id:<id>, or any unique part of its text",
+        )
+        .required()
+        .multiple()])
+        .after_help(
+            "A cancelled task counts as completed, so 'clear' removes it, but it\n\
+             runs no on-done hook and isn't counted by 'heatmap'. 'status TASK todo'\n\
+             brings it back.",
+        ),
+    CommandSpec::new("status", "Show or change where a task is in the workflow")
+        .args(&[
+            Arg::positional(
+                "task",
+                "TASK",
+                "The task number, id:<id>, or any unique part of its text",
+            )
+            .required(),
+            Arg::positional("status", "STATUS", "The status to move the task to"),
+        ])
+        .after_help(
+            "The statuses are those of the 'workflow' setting, in order (todo,\n\
+             doing and done unless configured otherwise), plus cancelled. 'done'\n\
+             and 'cancelled' complete the task; any other status reopens it.\n\
+             \n\
+             Examples:\n  \
+             todo_cli status 4\n  \
+             todo_cli status 4 review\n  \
+             todo_cli config set workflow todo,doing,review,done",
+        ),
+    CommandSpec::new("delete", "Remove a task from the list")
+        .aliases(&["del", "rm"])
+        .args(&[Arg::positional(
+            "task",
+            "TASK",
+            "The task number, id:<id>, or any unique part of its text",
+        )
+        .required()
+        .multiple()]),
+    CommandSpec::new("edit", "Change a task's text, or edit it in $EDITOR")
+        .args(&[
+            Arg::positional(
+                "task",
+                "TASK",
+                "The task number, id:<id>, or any unique part of its text",
+            )
+            .required(),
+            Arg::positional("text", "TEXT", "The new task text").multiple(),
+            Arg::flag("editor", "Edit the task and its notes in $EDITOR"),
+        ])
+        .after_help(
+            "Without new text, the task is opened in $EDITOR.\n\
+             \n\
+             Examples:\n  \
+             todo_cli edit 2 \"Buy oat milk\"\n  \
+             todo_cli edit milk --editor",
+        ),
+    CommandSpec::new("modify", "Make the same changes to every matching task")
+        .aliases(&["mod"])
+        .args(&[
+            Arg::positional("filter", "FILTER", "Only tasks whose text contains this").multiple(),
+            Arg::option("tag", "TAG", "Only tasks with this tag, e.g. work or OVERDUE (may be repeated)")
+                .short('t')
+                .multiple(),
+            Arg::option("project", "PROJECT", "Only tasks in this project"),
+            Arg::option("context", "CONTEXT", "Only tasks with this context (may be repeated)")
+                .short('c')
+                .multiple(),
+            Arg::flag("pending", "Only tasks that aren't completed"),
+            Arg::flag("done", "Only completed tasks"),
+            Arg::flag("someday", "Only tasks parked as someday/maybe"),
+            Arg::flag("mine", "Only tasks you added"),
+            Arg::option(
+                "status",
+                "STATUS",
+                "Only tasks in this workflow status, e.g. doing (may be repeated)",
+            )
+            .short('s')
+            .multiple(),
+            Arg::option(
+                "stale",
+                "AGE",
+                "Only pending tasks added at least this long ago, e.g. 30d or 8w",
+            ),
+            Arg::flag("all", "Change every task, when no filter is given").short('a'),
+            Arg::option("replace", "/OLD/NEW/", "Replace OLD with NEW in the text"),
+            Arg::option(
+                "set",
+                "FIELD:VALUE",
+                "Set priority, due or project; 'none' clears it (may be repeated)",
+            )
+            .multiple(),
+            Arg::option("add-tag", "TAG", "Add a tag (may be repeated)").multiple(),
+            Arg::option("remove-tag", "TAG", "Remove a tag (may be repeated)").multiple(),
+        ])
+        .after_help(
+            "Every change is checked before any task is touched. Use --dry-run to\n\
+             see what would change in each task without saving.\n\
+             \n\
+             Examples:\n  \
+             todo_cli modify --tag q2 --add-tag q3 --remove-tag q2\n  \
+             todo_cli modify report --replace /draft/final/ --set prio:high\n  \
+             todo_cli --dry-run modify --pending --project home --set due:sat",
+        ),
+    CommandSpec::new("check", "Keep a checklist of small steps inside a task")
+        .args(&[
+            Arg::positional(
+                "task",
+                "TASK",
+                "The task number, id:<id>, or any unique part of its text",
+            )
+            .required(),
+            Arg::positional("action", "ACTION", "list, add, done, undo or remove [default: list]"),
+            Arg::positional("item", "ITEM", "The text to add, or the numbers of the items").multiple(),
+        ])
+        .after_help(
+            "Items are numbered from 1 in the order they were added. 'list' shows\n\
+             how many of a task's items are done, e.g. [2/5].\n\
+             \n\
+             Examples:\n  \
+             todo_cli check 4 add \"book hotel\"\n  \
+             todo_cli check 4 done 2\n  \
+             todo_cli check 4",
+        ),
+    CommandSpec::new("comment", "Comment on a task, or read its comments")
+        .args(&[
+            Arg::positional(
+                "task",
+                "TASK",
+                "The task number, id:<id>, or any unique part of its text",
+            )
+            .required(),
+            Arg::positional("text", "TEXT", "What to say; without it, the comments are listed")
+                .multiple(),
+            Arg::option("author", "NAME", "Who the comment is from [default: $USER]"),
+        ])
+        .after_help(
+            "Comments are kept with the task, oldest first, each with its author\n\
+             and time. Unlike notes they are never rewritten, which suits lists\n\
+             shared by several people. 'show' prints them too.\n\
+             \n\
+             Examples:\n  \
+             todo_cli comment 4 \"Hotel booked, waiting on flights\"\n  \
+             todo_cli comment 4",
+        ),
+    CommandSpec::new("import", "Add the tasks written in another format")
+        .args(&[
+            Arg::positional("file", "FILE", "The file to read, or - for standard input").required(),
+            Arg::flag("markdown", "Read the checklists of a Markdown file"),
+            Arg::flag("ics", "Read the events of an iCalendar (.ics) file"),
+            Arg::option("from", "DATE", "With --ics, the first day to take events from [default: today]"),
+            Arg::option("to", "DATE", "With --ics, the last day to take events from [default: 30 days on]"),
+            Arg::option("match", "WORD", "With --ics, only events whose summary or description has this"),
+            Arg::option("prefix", "TEXT", "With --ics, put this before each event's summary"),
+            Arg::flag("raw", "Don't read +project, #tag, due:DATE and the like from the text"),
+            Arg::flag("no-dupes", "Leave out tasks the list already has"),
+        ])
+        .after_help(
+            "With --markdown, each '- [ ]' item becomes a task, done if it is\n\
+             '- [x]'. Items nested under one become the steps of its checklist, and\n\
+             a heading puts the items under it in a project named after it:\n\
+             '## Home Renovation' in home-renovation.\n\
+             \n\
+             With --ics, each event from --from to --to becomes a task due on its\n\
+             day, with its location and description as the notes. A repeating\n\
+             event gives a task for each time it happens; with --no-dupes, events\n\
+             already imported for that day are left out.\n\
+             \n\
+             Examples:\n  \
+             todo_cli import --markdown notes.md\n  \
+             pbpaste | todo_cli import --markdown - --no-dupes\n  \
+             todo_cli import --ics work.ics --match meeting --prefix \"Prepare for\" --to +2w",
+        ),
+    CommandSpec::new("ingest", "Add the flagged emails in a mailbox as tasks")
+        .args(&[
+            Arg::flag("imap", "Read the IMAP mailbox set by imap_url"),
+            Arg::option("mbox", "FILE", "Read an mbox file instead"),
+            Arg::option("maildir", "DIR", "Read a maildir instead"),
+            Arg::option(
+                "to",
+                "ADDRESS",
+                "Also take the messages sent to this address, e.g. me+todo@example.com",
+            ),
+            Arg::option("every", "SECONDS", "Keep checking the mailbox this often"),
+            Arg::flag("raw", "Don't read +project, #tag, due:DATE and the like from the subject"),
+        ])
+        .after_help(
+            "Each flagged (starred) message becomes a task: the subject, without\n\
+             its 'Re:' or 'Fwd:', is the text and the body the notes. With --to,\n\
+             so do the messages sent to that address; one without a +tag matches\n\
+             its tagged forms too. A message is only ever made into a task once,\n\
+             so the mailbox can be read again and again.\n\
+             \n\
+             --imap logs in as imap_user with imap_password (or $TODO_IMAP_PASSWORD)\n\
+             through the curl program, which must be installed; the server marks\n\
+             the messages fetched as read.\n\
+             \n\
+             Examples:\n  \
+             todo_cli config set imap_url imaps://mail.example.com/INBOX\n  \
+             todo_cli ingest --imap --to me+todo@example.com --every 300\n  \
+             todo_cli ingest --maildir ~/Mail/INBOX",
+        ),
+    CommandSpec::new("mail", "Send tasks to someone by email")
+        .args(&[
+            Arg::positional("filter", "FILTER", "Only tasks whose text contains this").multiple(),
+            Arg::option("to", "ADDRESS", "Who to send them to (may be repeated)")
+                .required()
+                .multiple(),
+            Arg::option("subject", "SUBJECT", "The subject [default: how many tasks are outstanding]"),
+            Arg::flag("html", "Send an HTML version besides the plain text"),
+            Arg::option("tag", "TAG", "Only tasks with this tag, e.g. work or OVERDUE (may be repeated)")
+                .short('t')
+                .multiple(),
+            Arg::option("project", "PROJECT", "Only tasks in this project"),
+            Arg::option("context", "CONTEXT", "Only tasks with this context (may be repeated)")
+                .short('c')
+                .multiple(),
+            Arg::flag("pending", "Only tasks that aren't completed (the default)"),
+            Arg::flag("done", "Only completed tasks"),
+            Arg::flag("someday", "Only tasks parked as someday/maybe"),
+            Arg::flag("mine", "Only tasks you added"),
+            Arg::option(
+                "status",
+                "STATUS",
+                "Only tasks in this workflow status, e.g. doing (may be repeated)",
+            )
+            .short('s')
+            .multiple(),
+        ])
+        .after_help(
+            "Sends the pending tasks the filters let through, one line each with\n\
+             its due date, priority, project, contexts and tags, in the order\n\
+             'list' shows them. Mail goes out through the SMTP server set by\n\
+             smtp_url, logged in to as smtp_user with smtp_password (or\n\
+             $TODO_SMTP_PASSWORD) if set, from mail_from, using the curl program.\n\
+             '--dry-run mail' prints the message instead of sending it.\n\
+             \n\
+             Examples:\n  \
+             todo_cli config set smtp_url smtps://smtp.example.com\n  \
+             todo_cli mail --project home --to family@example.com --html\n  \
+             todo_cli mail --tag OVERDUE --to boss@example.com --subject \"Still open\"",
+        ),
+    CommandSpec::new("slack", "Post a summary of the list, or alerts, to Slack")
+        .subcommands(&[
+            CommandSpec::new(
+                "summary",
+                "Post what is overdue, due today and high priority",
+            ),
+            CommandSpec::new(
+                "alerts",
+                "Post the tasks that became overdue or high priority since the last run",
+            )
+            .args(&[Arg::option("every", "SECONDS", "Keep checking the list this often")]),
+        ])
+        .after_help(
+            "Posts through the incoming webhook set by slack_webhook_url, or with\n\
+             the bot token in slack_token (or $TODO_SLACK_TOKEN) to slack_channel:\n\
+             a channel, or a person's member ID for a direct message. An alert is\n\
+             posted once for each overdue or high-priority task, so 'alerts' can\n\
+             run often; with slack_alerts on, adding such a task alerts at once.\n\
+             '--dry-run slack' prints the message instead of posting it.\n\
+             \n\
+             Examples:\n  \
+             todo_cli config set slack_webhook_url https://hooks.slack.com/services/...\n  \
+             todo_cli slack alerts --every 300\n  \
+             0 8 * * 1-5 todo_cli slack summary",
+        ),
+    CommandSpec::new("discord", "Post the tasks coming due to the list's Discord channel")
+        .args(&[
+            Arg::option(
+                "within",
+                "DAYS",
+                "Post tasks due this many days ahead, or sooner [default: 1]",
+            ),
+            Arg::option("every", "SECONDS", "Keep checking the list this often"),
+        ])
+        .after_help(
+            "Each list posts to the Discord webhook the [discord_webhooks] table\n\
+             gives it, the main list as 'main'; lists without one post nothing.\n\
+             Completing a task announces it there straight away, with who did it.\n\
+             'discord' posts the pending tasks due within --within days, overdue\n\
+             ones too, each once for its due date, so it can run often.\n\
+             '--dry-run discord' prints the message instead of posting it.\n\
+             \n\
+             Examples:\n  \
+             todo_cli config set discord_webhooks.club https://discord.com/api/webhooks/...\n  \
+             todo_cli --list club discord --within 2 --every 600",
+        ),
+    CommandSpec::new("telegram-bot", "Run a Telegram bot that adds, lists and completes tasks")
+        .after_help(
+            "Make a bot by talking to @BotFather, then set its token in\n\
+             telegram_token (or $TODO_TELEGRAM_TOKEN). The bot only answers the\n\
+             chats in telegram_chats; write to it and it tells you your chat ID.\n\
+             In the chat, '/add TEXT' adds a task (plain text does too), '/list'\n\
+             lists the pending ones and '/done TASK' completes one. The bot asks\n\
+             Telegram for messages, so it needs no open port, and runs until\n\
+             stopped; requests go through the curl program.\n\
+             \n\
+             Examples:\n  \
+             todo_cli config set telegram_token 123456:ABC-DEF...\n  \
+             todo_cli config set telegram_chats 987654321\n  \
+             todo_cli telegram-bot",
+        ),
+    CommandSpec::new("export", "Print the list for other programs")
+        .args(&[
+            Arg::positional("filter", "FILTER", "Only tasks whose text contains this").multiple(),
+            Arg::flag("markdown", "Print a Markdown checklist instead"),
+            Arg::flag(
+                "waybar",
+                "Print the JSON a Waybar custom module reads",
+            ),
+            Arg::option("tag", "TAG", "Only tasks with this tag, e.g. work or OVERDUE (may be repeated)")
+                .short('t')
+                .multiple(),
+            Arg::option("project", "PROJECT", "Only tasks in this project"),
+            Arg::option("context", "CONTEXT", "Only tasks with this context (may be repeated)")
+                .short('c')
+                .multiple(),
+            Arg::flag("pending", "Only tasks that aren't completed"),
+            Arg::flag("done", "Only completed tasks"),
+            Arg::flag("someday", "Only tasks parked as someday/maybe"),
+            Arg::flag("mine", "Only tasks you added"),
+            Arg::option(
+                "status",
+                "STATUS",
+                "Only tasks in this workflow status, e.g. doing (may be repeated)",
+            )
+            .short('s')
+            .multiple(),
+        ])
+        .after_help(
+            "Prints every task as JSON, the way the list stores them, or only\n\
+             those the filters and the active context let through.\n\
+             \n\
+             With --markdown it prints a checklist to paste into a pull request,\n\
+             wiki or meeting notes: a '- [ ]' line per task, '- [x]' once done,\n\
+             with its checklist nested under it and a heading per project. Due\n\
+             dates, priorities, contexts and tags follow the text as 'add' reads\n\
+             them, so 'import --markdown' brings the tasks back.\n\
+             \n\
+             With --waybar it prints one object instead: the counts as text (3!/7\n\
+             is 3 overdue out of 7 pending; empty, hiding the module, when nothing\n\
+             is pending), the tasks due today or overdue as tooltip, and a class of\n\
+             overdue, today, pending or empty for the stylesheet.\n\
+             \n\
+             Examples:\n  \
+             todo_cli export > backup.json\n  \
+             todo_cli export --markdown --project release --pending\n\
+             \n  \
+             \"custom/todo\": {          (~/.config/waybar/config)\n    \
+             \"exec\": \"todo_cli export --waybar\",\n    \
+             \"return-type\": \"json\",\n    \
+             \"interval\": 60,\n    \
+             \"on-click\": \"foot -e todo_cli watch --pending\"\n  \
+             }",
+        ),
+    CommandSpec::new("snapshot", "Save a copy of the whole list to restore later")
+        .args(&[Arg::positional(
+            "name",
+            "NAME",
+            "What to call it [default: the date and time]",
+        )])
+        .after_help(
+            "Takes a checkpoint before something risky, like a big import or\n\
+             cleanup. Snapshots are kept per list under\n\
+             ~/.config/todo/snapshots/, as JSON files like the list's own.\n\
+             \n\
+             Examples:\n  \
+             todo_cli snapshot before-import\n  \
+             todo_cli snapshots\n  \
+             todo_cli restore before-import",
+        ),
+    CommandSpec::new("snapshots", "List the saved snapshots of the list"),
+    CommandSpec::new("restore", "Put the list back as it was in a snapshot")
+        .args(&[Arg::positional("name", "NAME", "The snapshot to restore").required()])
+        .after_help(
+            "Every task is put back as the snapshot has it: tasks added since are\n\
+             removed, and removed ones come back with their ids. Asks first;\n\
+             --yes skips the question.",
+        ),
+    CommandSpec::new("diff", "Show what changed between two lists or snapshots")
+        .args(&[
+            Arg::positional("a", "A", "A list file or snapshot name").required(),
+            Arg::positional(
+                "b",
+                "B",
+                "Another list file or snapshot name [default: the list now]",
+            ),
+        ])
+        .after_help(
+            "Tasks are matched by their permanent id, so a task whose text changed\n\
+             is still the same task. Each added (+), removed (-) or changed (~)\n\
+             task is shown with its id, and changed ones with each field's old and\n\
+             new value. A file of the given name is read if there is one;\n\
+             otherwise it is taken as a snapshot of the list.\n\
+             \n\
+             Examples:\n  \
+             todo_cli diff before-import\n  \
+             todo_cli diff backup.json todos.json",
+        ),
+    CommandSpec::new("undo", "Take back one change, keeping later ones")
+        .args(&[Arg::positional(
+            "change",
+            "CHANGE",
+            "The change id, as 'history' shows it [default: the latest change]",
+        )])
+        .after_help(
+            "A change is everything one command saved. Undoing it removes the\n\
+             tasks it added, brings back those it removed and sets the fields it\n\
+             changed back, leaving alone whatever was changed after it. If a later\n\
+             change gets in the way (the same field changed again, or the task\n\
+             removed), nothing is undone and the conflicts are listed. The undo is\n\
+             a change too, so undoing it again redoes the original.\n\
+             \n\
+             Examples:\n  \
+             todo_cli history\n  \
+             todo_cli undo 3f9a0c12\n  \
+             todo_cli undo",
+        ),
+    CommandSpec::new("clear", "Remove completed tasks").args(&[Arg::flag(
+        "all",
+        "Remove every task, not just completed ones",
+    )
+    .short('a')]),
+    CommandSpec::new(
+        "notify",
+        "Show a desktop notification for due and overdue tasks",
+    )
+    .args(&[Arg::flag(
+        "print",
+        "Print the notification instead of showing it",
+    )])
+    .after_help(
+        "Notifications are shown with notify-send on Linux and osascript on\n\
+             macOS. Nothing is shown when no pending task is due today or overdue.\n\
+             \n\
+             Example (crontab, every morning at 9):\n  \
+             0 9 * * * todo_cli notify",
+    ),
+    CommandSpec::new(
+        "notify-due",
+        "Notify of tasks that came due since the last run, for cron",
+    )
+    .args(&[Arg::flag(
+        "print",
+        "Print the notifications instead of showing them",
+    )])
+    .after_help(
+        "Announces what the daemon would: tasks due today or overdue (again\n\
+             each day while they stay overdue), tasks coming up in reminder_leads,\n\
+             reminders whose time came and tasks waiting too long. What it\n\
+             announced is remembered, so running it often announces each thing\n\
+             once, and a run with nothing new prints nothing. With --print, cron\n\
+             mails what it prints.\n\
+             \n\
+             Examples (crontab, every 10 minutes):\n  \
+             */10 * * * * DISPLAY=:0 todo_cli notify-due\n  \
+             */10 * * * * todo_cli notify-due --print",
+    ),
+    CommandSpec::new("daemon", "Run the background reminder daemon")
+        .subcommands(&[
+            CommandSpec::new("start", "Start the daemon in the background"),
+            CommandSpec::new("stop", "Stop the running daemon"),
+            CommandSpec::new("status", "Show whether the daemon is running"),
+            CommandSpec::new("run", "Run the daemon in the foreground"),
+        ])
+        .after_help(
+            "The daemon watches the task file and shows a notification when a\n\
+             pending task is due, every day while it is overdue, and ahead of\n\
+             time as set by reminder_leads (e.g. '1d,1w'), not counting the\n\
+             days in 'holidays'.\n\
+             \n\
+             Examples:\n  \
+             todo_cli config set reminder_leads 1d,1w\n  \
+             todo_cli daemon start",
+        ),
+    CommandSpec::new("serve", "Serve the task lists over an HTTP JSON API")
+        .args(&[
+            Arg::option("host", "HOST", "The address to listen on [default: 127.0.0.1]"),
+            Arg::option("port", "PORT", "The port to listen on [default: 8080]"),
+            Arg::option(
+                "token",
+                "TOKEN",
+                "The token clients must send (default: $TODO_API_TOKEN, or a random one)",
+            ),
+        ])
+        .after_help(
+            "Clients authenticate with an 'Authorization: Bearer TOKEN' header.\n\
+             People listed in the [api_users] table of the config file may use\n\
+             their own token instead; with owner_only set, only a task's owner\n\
+             may complete or delete it.\n\
+             \n\
+             Routes:\n  \
+             GET    /tasks                list tasks\n  \
+             POST   /tasks                add a task, e.g. {\"task\": \"Buy milk\", \"due\": \"fri\"}\n  \
+             GET    /tasks/TASK           show a task\n  \
+             PATCH  /tasks/TASK           change a task's fields\n  \
+             DELETE /tasks/TASK           delete a task\n  \
+             POST   /tasks/TASK/done      complete a task\n  \
+             POST   /tasks/TASK/comments  comment, e.g. {\"text\": \"On it\", \"author\": \"ana\"}\n  \
+             GET    /lists                names of the named lists\n  \
+             DELETE /lists/NAME           delete a named list\n  \
+             /lists/NAME/tasks/...        the task routes, for a named list\n\
+             \n\
+             Example:\n  \
+             curl -H \"Authorization: Bearer $TOKEN\" http://127.0.0.1:8080/tasks",
+        ),
+    CommandSpec::new("dashboard", "Serve a read-only web page of the list")
+        .args(&[
+            Arg::option("host", "HOST", "The address to listen on [default: 127.0.0.1]"),
+            Arg::option("port", "PORT", "The port to listen on [default: 9000]"),
+            Arg::option("refresh", "SECONDS", "How often the page reloads itself [default: 60]"),
+        ])
+        .after_help(
+            "The page lists the pending tasks as 'list' does, overdue ones in red,\n\
+             and reloads itself to keep up with changes, for a screen on the wall\n\
+             or an old tablet in the kitchen. Its form filters by text, tag,\n\
+             project and context; the filters are kept in the address, e.g.\n\
+             /?tag=shopping, so a display can be pointed at one for good.\n\
+             \n\
+             Nothing on the page changes the list, but there is no token either:\n\
+             anyone who can reach the port can read it. Use --host 0.0.0.0 to let\n\
+             other machines on the network see it. Secret tasks stay masked.\n\
+             \n\
+             Example:\n  \
+             todo_cli dashboard --host 0.0.0.0 --port 9000 --refresh 30",
+        ),
+    CommandSpec::new("sync", "Keep the list in step with other machines on the network")
+        .args(&[
+            Arg::flag("once", "Sync with the instances found now, then exit"),
+            Arg::option("peer", "HOST:PORT", "Sync with this instance instead of looking for them (may be repeated)")
+                .multiple(),
+            Arg::option("port", "PORT", "The port to answer syncs on [default: any free port]"),
+            Arg::option("every", "SECONDS", "How often to look for instances and sync [default: 60]"),
+            Arg::option(
+                "token",
+                "TOKEN",
+                "A secret every instance must share (default: $TODO_SYNC_TOKEN)",
+            ),
+            Arg::flag("insecure", "Answer syncs from anyone on the network, without a token"),
+        ])
+        .after_help(
+            "Instances running 'sync' on the local network find each other over\n\
+             multicast DNS and merge their lists: a task changed on one machine\n\
+             takes that change, one changed on both keeps each side's fields, this\n\
+             machine's where both changed the same one. Only instances syncing the\n\
+             same list (see --list) sync with each other.\n\
+             \n\
+             Instances must share a token: set the same TODO_SYNC_TOKEN (or\n\
+             --token) on every machine. Without one, 'sync' won't answer syncs\n\
+             unless --insecure lets anyone on the network read and replace the list.\n\
+             \n\
+             Examples:\n  \
+             todo_cli sync\n  \
+             todo_cli sync --once\n  \
+             todo_cli sync --once --peer 192.168.1.20:7070",
+        ),
+    CommandSpec::new(
+        "mcp",
+        "Serve the task lists to AI assistants over MCP on stdin/stdout",
+    )
+    .after_help(
+        "Speaks the Model Context Protocol, offering the tools list_tasks,\n\
+             add_task, complete_task and delete_task. Configure your assistant to\n\
+             start 'todo_cli mcp' as a stdio server.\n\
+             \n\
+             Example MCP client configuration:\n  \
+             {\"mcpServers\": {\"todo\": {\"command\": \"todo_cli\", \"args\": [\"mcp\"]}}}",
+    ),
+    CommandSpec::new("run", "Run a script that reads or changes tasks")
+        .args(&[
+            Arg::positional(
+                "script",
+                "SCRIPT",
+                "A script file, or the name of one in the scripts directory",
+            )
+            .required(),
+            Arg::positional("args", "ARGS", "Values passed to the script as 'args'").multiple(),
+        ])
+        .after_help(
+            "Scripts are looked up as a path first, then as NAME or NAME.todo in\n\
+             ~/.config/todo/scripts. Put '--' before arguments starting with '-'.\n\
+             \n\
+             Example script:\n  \
+             for task in tasks() {\n  \
+             \x20   if !task.completed && task.tags.contains(\"someday\") {\n  \
+             \x20       task.due = \"+1w\"\n  \
+             \x20   }\n  \
+             }\n\
+             \n\
+             Examples:\n  \
+             todo_cli run postpone-someday\n  \
+             todo_cli run ./report.todo -- --verbose",
+        ),
+    CommandSpec::new("config", "Show or change settings in the config file").subcommands(&[
+        CommandSpec::new("get", "Print the value of a setting").args(&[Arg::positional(
+            "key",
+            "KEY",
+            "The setting, e.g. default_sort, aliases.ls or templates.weekly",
+        )
+        .required()]),
+        CommandSpec::new("set", "Change a setting").args(&[
+            Arg::positional(
+                "key",
+                "KEY",
+                "The setting, e.g. default_sort, aliases.ls or templates.weekly",
+            )
+                .required(),
+            Arg::positional("value", "VALUE", "The new value").required(),
+        ]),
+        CommandSpec::new("list", "Print every setting"),
+        CommandSpec::new("path", "Print where the config file lives"),
+    ]),
+    CommandSpec::new("man", "Print or install man pages")
+        .args(&[
+            Arg::positional("command", "COMMAND", "The command to show the page for").multiple(),
+            Arg::option(
+                "out-dir",
+                "DIR",
+                "Write pages for the program and every command into DIR",
+            ),
+        ])
+        .after_help(
+            "Examples:\n  \
+             todo_cli man | man -l -\n  \
+             todo_cli man config set\n  \
+             todo_cli man --out-dir /usr/local/share/man/man1",
+        ),
+    CommandSpec::new("help", "Show help for the program or one command").args(&[Arg::positional(
+        "command",
+        "COMMAND",
+        "The command to show help for",
+    )]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_edits() {
+        assert_eq!(edit_distance("list", "list"), 0);
+        assert_eq!(edit_distance("", "add"), 3);
+        assert_eq!(edit_distance("ad", "add"), 1);
+        assert_eq!(edit_distance("edti", "edit"), 1);
+        assert_eq!(edit_distance("lsit", "list"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        // A swap counts once only when nothing else edits the same letters.
+        assert_eq!(edit_distance("ca", "abc"), 3);
+    }
+
+    #[test]
+    fn resolves_names_aliases_and_prefixes() {
+        let name = |typed: &str| ROOT.resolve(typed).map(|cmd| cmd.name);
+        assert_eq!(name("list"), Ok("list"));
+        assert_eq!(name("ls"), Ok("list"));
+        assert_eq!(name("hol"), Ok("holidays"));
+        assert_eq!(
+            name("ti"),
+            Err("'ti' is ambiguous: it could be time, timesheet.".to_string())
+        );
+    }
+
+    #[test]
+    fn suggests_the_closest_command() {
+        let error = |typed: &str| ROOT.resolve(typed).unwrap_err();
+        assert_eq!(
+            error("doen"),
+            "Unknown command 'doen'. Did you mean 'done'?"
+        );
+        // `due` is as close, but `done` is used more often.
+        assert_eq!(error("dne"), "Unknown command 'dne'. Did you mean 'done'?");
+        assert_eq!(
+            error("LSIT"),
+            "Unknown command 'LSIT'. Did you mean 'list'?"
+        );
+        assert_eq!(error("xyzzy"), "Unknown command 'xyzzy'.");
+    }
+}
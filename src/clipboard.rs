@@ -0,0 +1,65 @@
+// Reading the system clipboard.
+//
+// Like notifications, this is left to the tools that come with the desktop:
+// `pbpaste` on macOS, PowerShell on Windows, and `wl-paste`, `xclip` or
+// `xsel` elsewhere, whichever is installed, so no extra libraries are needed.
+
+use std::io;
+use std::process::{Command, Stdio};
+
+use crate::log;
+
+/// The commands that print the clipboard, in the order they are tried.
+#[cfg(target_os = "macos")]
+const READERS: &[&[&str]] = &[&["pbpaste"]];
+
+#[cfg(windows)]
+const READERS: &[&[&str]] = &[&["powershell", "-NoProfile", "-Command", "Get-Clipboard"]];
+
+#[cfg(not(any(target_os = "macos", windows)))]
+const READERS: &[&[&str]] = &[
+    &["wl-paste", "--no-newline"],
+    &["xclip", "-selection", "clipboard", "-out"],
+    &["xsel", "--clipboard", "--output"],
+];
+
+/// Returns the text on the clipboard.
+///
+/// Fails if none of the tools is installed, or if the one that is fails.
+pub fn read() -> io::Result<String> {
+    for reader in READERS {
+        let (program, args) = reader.split_first().expect("readers are not empty");
+        let output = match Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()
+        {
+            Ok(output) => output,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                log::debug("clipboard", format_args!("'{}' is not installed", program));
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(io::Error::other(format!(
+                "'{}' exited with {}: {}",
+                program,
+                output.status,
+                stderr.trim()
+            )));
+        }
+        log::debug(
+            "clipboard",
+            format_args!("read the clipboard with '{}'", program),
+        );
+        return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+    }
+    let names: Vec<&str> = READERS.iter().map(|reader| reader[0]).collect();
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("no clipboard tool found (tried {})", names.join(", ")),
+    ))
+}
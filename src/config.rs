@@ -0,0 +1,1276 @@
+// User configuration, read from `~/.config/todo/config.toml` at startup.
+//
+// Every setting has a sensible default, so the file is entirely optional.
+// Settings are layered with a fixed precedence, highest first:
+//
+//   1. command-line flags (`--file`, `--list`, `--sort`, `--color`)
+//   2. environment variables (`TODO_FILE`, `TODO_LIST`, `TODO_COLOR`, ...)
+//   3. the config file
+//   4. built-in defaults
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::backend::{Journal, JsonFile, Memory, ReadOnly, Remote, StorageBackend};
+use crate::error::Error;
+use crate::holiday::Source;
+use crate::http::{Client, Url};
+use crate::log;
+use crate::store::TaskStore;
+use crate::toml::{self, Table, Value};
+use crate::trash::Trash;
+use crate::urgency::Urgency;
+
+/// The keys that `config get/set` understands, besides `aliases.<name>`,
+/// `templates.<name>`, `api_users.<name>`, `time_projects.<name>`,
+/// `discord_webhooks.<list>`, `views.<name>` and `contexts.<name>`.
+pub const KEYS: &[&str] = &[
+    "storage_backend",
+    "storage_path",
+    "storage_url",
+    "list",
+    "default_sort",
+    "urgency_due",
+    "urgency_priority",
+    "urgency_age",
+    "urgency_age_days",
+    "context",
+    "color",
+    "plain",
+    "date_format",
+    "week_start",
+    "holidays",
+    "default_command",
+    "hooks_dir",
+    "reminder_leads",
+    "waiting_nudge_days",
+    "someday_review_days",
+    "trash_days",
+    "prune_after",
+    "prune_action",
+    "archive_on_done",
+    "time_service",
+    "time_account",
+    "time_token",
+    "webhook_urls",
+    "webhook_secret",
+    "imap_url",
+    "imap_user",
+    "imap_password",
+    "smtp_url",
+    "smtp_user",
+    "smtp_password",
+    "mail_from",
+    "slack_webhook_url",
+    "slack_token",
+    "slack_channel",
+    "slack_alerts",
+    "telegram_token",
+    "telegram_chats",
+    "http_timeout",
+    "http_retries",
+    "http_proxy",
+    "max_task_length",
+    "workflow",
+    "log_file",
+    "language",
+    "timezone",
+    "owner_only",
+    "read_only",
+];
+
+/// Environment variables and the config keys they override.
+pub const ENV_VARS: &[(&str, &str)] = &[
+    ("TODO_STORAGE_BACKEND", "storage_backend"),
+    ("TODO_FILE", "storage_path"),
+    ("TODO_STORAGE_URL", "storage_url"),
+    ("TODO_LIST", "list"),
+    ("TODO_SORT", "default_sort"),
+    ("TODO_URGENCY_DUE", "urgency_due"),
+    ("TODO_URGENCY_PRIORITY", "urgency_priority"),
+    ("TODO_URGENCY_AGE", "urgency_age"),
+    ("TODO_URGENCY_AGE_DAYS", "urgency_age_days"),
+    ("TODO_CONTEXT", "context"),
+    ("TODO_COLOR", "color"),
+    ("TODO_PLAIN", "plain"),
+    ("TODO_DATE_FORMAT", "date_format"),
+    ("TODO_WEEK_START", "week_start"),
+    ("TODO_HOLIDAYS", "holidays"),
+    ("TODO_DEFAULT_COMMAND", "default_command"),
+    ("TODO_HOOKS_DIR", "hooks_dir"),
+    ("TODO_REMINDER_LEADS", "reminder_leads"),
+    ("TODO_WAITING_NUDGE_DAYS", "waiting_nudge_days"),
+    ("TODO_SOMEDAY_REVIEW_DAYS", "someday_review_days"),
+    ("TODO_TRASH_DAYS", "trash_days"),
+    ("TODO_PRUNE_AFTER", "prune_after"),
+    ("TODO_PRUNE_ACTION", "prune_action"),
+    ("TODO_ARCHIVE_ON_DONE", "archive_on_done"),
+    ("TODO_TIME_SERVICE", "time_service"),
+    ("TODO_TIME_ACCOUNT", "time_account"),
+    ("TODO_TIME_TOKEN", "time_token"),
+    ("TODO_WEBHOOK_URLS", "webhook_urls"),
+    ("TODO_WEBHOOK_SECRET", "webhook_secret"),
+    ("TODO_IMAP_URL", "imap_url"),
+    ("TODO_IMAP_USER", "imap_user"),
+    ("TODO_IMAP_PASSWORD", "imap_password"),
+    ("TODO_SMTP_URL", "smtp_url"),
+    ("TODO_SMTP_USER", "smtp_user"),
+    ("TODO_SMTP_PASSWORD", "smtp_password"),
+    ("TODO_MAIL_FROM", "mail_from"),
+    ("TODO_SLACK_WEBHOOK_URL", "slack_webhook_url"),
+    ("TODO_SLACK_TOKEN", "slack_token"),
+    ("TODO_SLACK_CHANNEL", "slack_channel"),
+    ("TODO_SLACK_ALERTS", "slack_alerts"),
+    ("TODO_TELEGRAM_TOKEN", "telegram_token"),
+    ("TODO_TELEGRAM_CHATS", "telegram_chats"),
+    ("TODO_HTTP_TIMEOUT", "http_timeout"),
+    ("TODO_HTTP_RETRIES", "http_retries"),
+    ("TODO_HTTP_PROXY", "http_proxy"),
+    ("TODO_MAX_TASK_LENGTH", "max_task_length"),
+    ("TODO_WORKFLOW", "workflow"),
+    ("TODO_LOG_FILE", "log_file"),
+    ("TODO_LANGUAGE", "language"),
+    ("TODO_TIMEZONE", "timezone"),
+    ("TODO_OWNER_ONLY", "owner_only"),
+    ("TODO_READ_ONLY", "read_only"),
+];
+
+/// How `list` orders tasks when no other order is requested.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SortOrder {
+    /// The order tasks were added in.
+    None,
+    /// Alphabetically by task text.
+    Text,
+    /// Pending tasks first, then completed ones.
+    Status,
+    /// Soonest due first, tasks without a due date last.
+    Due,
+    /// Highest priority first, tasks without a priority last.
+    Priority,
+    /// Most urgent first; see `urgency`.
+    Urgency,
+    /// As arranged with `move`, `up` and `down`; see `order`.
+    Manual,
+}
+
+impl FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(SortOrder::None),
+            "text" => Ok(SortOrder::Text),
+            "status" => Ok(SortOrder::Status),
+            "due" => Ok(SortOrder::Due),
+            "priority" => Ok(SortOrder::Priority),
+            "urgency" => Ok(SortOrder::Urgency),
+            "manual" => Ok(SortOrder::Manual),
+            _ => Err(format!(
+                "'{}' is not a valid sort order \
+                 (expected none, text, status, due, priority, urgency or manual)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SortOrder::None => "none",
+            SortOrder::Text => "text",
+            SortOrder::Status => "status",
+            SortOrder::Due => "due",
+            SortOrder::Priority => "priority",
+            SortOrder::Urgency => "urgency",
+            SortOrder::Manual => "manual",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Which kind of storage backend holds the lists.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StorageKind {
+    /// JSON files at `storage_path`.
+    Json,
+    /// Logs of changes next to `storage_path`, e.g. `todos.journal`.
+    Journal,
+    /// Memory only; nothing is kept once the program exits.
+    Memory,
+    /// A JSON document at `storage_url`, read and written over HTTP.
+    Remote,
+}
+
+impl FromStr for StorageKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(StorageKind::Json),
+            "journal" => Ok(StorageKind::Journal),
+            "memory" => Ok(StorageKind::Memory),
+            "remote" => Ok(StorageKind::Remote),
+            _ => Err(format!(
+                "'{}' is not a valid storage backend (expected json, journal, memory or remote)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for StorageKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            StorageKind::Json => "json",
+            StorageKind::Journal => "journal",
+            StorageKind::Memory => "memory",
+            StorageKind::Remote => "remote",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Whether output should be colored.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorChoice {
+    /// Color only when writing to a terminal and `NO_COLOR` is unset.
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!(
+                "'{}' is not a valid color setting (expected auto, always or never)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ColorChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ColorChoice::Auto => "auto",
+            ColorChoice::Always => "always",
+            ColorChoice::Never => "never",
+        };
+        f.write_str(name)
+    }
+}
+
+/// What happens to the completed tasks `prune` takes off the list.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PruneAction {
+    /// Move them to the list's archive.
+    Archive,
+    /// Remove them, which puts them in the trash.
+    Delete,
+}
+
+impl FromStr for PruneAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "archive" => Ok(PruneAction::Archive),
+            "delete" => Ok(PruneAction::Delete),
+            _ => Err(format!(
+                "'{}' is not a valid prune action (expected archive or delete)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for PruneAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            PruneAction::Archive => "archive",
+            PruneAction::Delete => "delete",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Where `time push` sends tracked time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimeService {
+    /// Toggl Track.
+    Toggl,
+    Harvest,
+}
+
+impl FromStr for TimeService {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "toggl" => Ok(TimeService::Toggl),
+            "harvest" => Ok(TimeService::Harvest),
+            _ => Err(format!(
+                "'{}' is not a valid time service (expected toggl or harvest)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for TimeService {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TimeService::Toggl => "toggl",
+            TimeService::Harvest => "harvest",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The effective settings for one run of the program.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// Which backend the lists are kept in.
+    pub storage_backend: StorageKind,
+    /// Where the to-do list is stored by the `json` backend.
+    pub storage_path: PathBuf,
+    /// Where the to-do list is stored by the `remote` backend.
+    pub storage_url: Option<String>,
+    /// A named list kept next to the main file instead of in it.
+    pub list: Option<String>,
+    pub default_sort: SortOrder,
+    /// How much a due date, a priority and going untouched make a task
+    /// urgent, for `next` and `--sort urgency`.
+    pub urgency: Urgency,
+    /// The active context: one of `contexts`, whose expression every
+    /// command that lists, changes or adds tasks keeps to.
+    pub context: Option<String>,
+    pub color: ColorChoice,
+    /// Output for screen readers: each task as a sentence, with no box
+    /// drawing, columns or color.
+    pub plain: bool,
+    /// A strftime-style format used when showing dates; `%x` is the
+    /// locale's date.
+    pub date_format: String,
+    /// The day weeks start on, Monday as 0, or `None` for the locale's.
+    pub week_start: Option<u32>,
+    /// Where the public holidays business days skip come from: countries
+    /// and iCalendar files.
+    pub holidays: Vec<Source>,
+    /// The command line to run when no command is given.
+    pub default_command: Option<String>,
+    /// Where `on-add`, `on-done` and the other hook scripts live.
+    pub hooks_dir: PathBuf,
+    /// How many days before a due date the daemon sends extra reminders.
+    pub reminder_leads: Vec<u32>,
+    /// After how many days of waiting on someone a task is worth a nudge;
+    /// zero for never.
+    pub waiting_nudge_days: u32,
+    /// How many days a someday/maybe task stays parked before `review`
+    /// brings it up again.
+    pub someday_review_days: u32,
+    /// How many days removed tasks are kept in the trash; zero for none.
+    pub trash_days: u32,
+    /// How many days after being completed `prune` takes a task off the
+    /// list by default; zero for only when given an age.
+    pub prune_after: u32,
+    /// What `prune` does with the tasks it takes off the list.
+    pub prune_action: PruneAction,
+    /// Whether `done` moves a task straight to the archive.
+    pub archive_on_done: bool,
+    /// Where `time push` sends tracked time, if anywhere.
+    pub time_service: Option<TimeService>,
+    /// The Toggl workspace or Harvest account that time goes to.
+    pub time_account: Option<String>,
+    /// The API token for the time service.
+    pub time_token: Option<String>,
+    /// The time service's project for each project on the list: a Toggl
+    /// project id, or a Harvest project id and task id as `PROJECT/TASK`.
+    pub time_projects: BTreeMap<String, String>,
+    /// The Discord webhook each list posts to, the main list as `main`.
+    pub discord_webhooks: BTreeMap<String, String>,
+    /// URLs that are sent a webhook when a task is added, completed or deleted.
+    pub webhook_urls: Vec<String>,
+    /// The key used to sign webhook payloads, if any.
+    pub webhook_secret: Option<String>,
+    /// The mailbox `ingest --imap` reads, e.g. `imaps://mail.example.com/INBOX`.
+    pub imap_url: Option<String>,
+    /// The user and password to log in to the mailbox with.
+    pub imap_user: Option<String>,
+    pub imap_password: Option<String>,
+    /// The server `mail` sends through, e.g. `smtps://smtp.example.com`.
+    pub smtp_url: Option<String>,
+    /// The user and password to log in to the server with, if it asks.
+    pub smtp_user: Option<String>,
+    pub smtp_password: Option<String>,
+    /// Who `mail` sends as, e.g. `Ana <ana@example.com>`; the SMTP user by
+    /// default.
+    pub mail_from: Option<String>,
+    /// The Slack incoming webhook `slack` posts to.
+    pub slack_webhook_url: Option<String>,
+    /// A Slack bot token, to post with instead of a webhook, and the channel,
+    /// or person's ID for a direct message, to post to.
+    pub slack_token: Option<String>,
+    pub slack_channel: Option<String>,
+    /// Whether adding an overdue or high-priority task alerts Slack at once.
+    pub slack_alerts: bool,
+    /// The token of the bot `telegram-bot` runs as, from @BotFather.
+    pub telegram_token: Option<String>,
+    /// The Telegram chats the bot answers, by chat ID.
+    pub telegram_chats: Vec<i64>,
+    /// How webhooks and remote storage make requests: timeout, retries and proxy.
+    pub http: Client,
+    /// The longest task text accepted, in characters.
+    pub max_task_length: usize,
+    /// The statuses a task moves through, in order, from `todo` to `done`.
+    /// `cancelled` is always there besides.
+    pub workflow: Vec<String>,
+    /// A file that diagnostic messages are appended to.
+    pub log_file: Option<PathBuf>,
+    /// The language for messages, e.g. `es`. Unset means the locale's.
+    pub language: Option<String>,
+    /// The time zone due times are typed and shown in, e.g. `Europe/Berlin`.
+    /// Unset means TZ's, or the system's.
+    pub timezone: Option<String>,
+    /// Extra command names, each expanding to a command line.
+    pub aliases: BTreeMap<String, String>,
+    /// Named sets of tasks for `template apply`, one task per line.
+    pub templates: BTreeMap<String, String>,
+    /// Saved filter expressions, with how to sort and print their tasks.
+    pub views: BTreeMap<String, String>,
+    /// Filter expressions to scope the commands to, one at a time, with
+    /// `context NAME`. Not to be confused with a task's `@contexts`.
+    pub contexts: BTreeMap<String, String>,
+    /// People who may use the HTTP API, each with their own token.
+    pub api_users: BTreeMap<String, String>,
+    /// Whether the HTTP API only lets a task's owner complete or delete it.
+    pub owner_only: bool,
+    /// Never write to the list: every save fails instead.
+    pub read_only: bool,
+    /// Skip confirmation prompts. Only set by `--yes`, never by the file.
+    pub assume_yes: bool,
+    /// Run commands without saving anything. Only set by `--dry-run`.
+    pub dry_run: bool,
+    /// How much to log to stderr. Only set by `-v`, `-vv` or `-vvv`.
+    pub verbosity: usize,
+    /// What secret tasks are sealed with, to show them. Only set by
+    /// `--unlock`, never by the file.
+    pub passphrase: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            storage_backend: StorageKind::Json,
+            storage_path: PathBuf::from("todos.json"),
+            storage_url: None,
+            list: None,
+            default_sort: SortOrder::None,
+            urgency: Urgency::default(),
+            context: None,
+            color: ColorChoice::Auto,
+            plain: false,
+            date_format: "%Y-%m-%d".to_string(),
+            week_start: Some(0),
+            holidays: Vec::new(),
+            default_command: None,
+            hooks_dir: config_dir().join("hooks"),
+            reminder_leads: Vec::new(),
+            waiting_nudge_days: 7,
+            someday_review_days: 30,
+            trash_days: 30,
+            prune_after: 0,
+            prune_action: PruneAction::Archive,
+            archive_on_done: false,
+            time_service: None,
+            time_account: None,
+            time_token: None,
+            time_projects: BTreeMap::new(),
+            discord_webhooks: BTreeMap::new(),
+            webhook_urls: Vec::new(),
+            webhook_secret: None,
+            imap_url: None,
+            imap_user: None,
+            imap_password: None,
+            smtp_url: None,
+            smtp_user: None,
+            smtp_password: None,
+            mail_from: None,
+            slack_webhook_url: None,
+            slack_token: None,
+            slack_channel: None,
+            slack_alerts: false,
+            telegram_token: None,
+            telegram_chats: Vec::new(),
+            http: Client::default(),
+            max_task_length: crate::validate::DEFAULT_MAX_LENGTH,
+            workflow: vec!["todo".to_string(), "doing".to_string(), "done".to_string()],
+            log_file: None,
+            language: None,
+            timezone: None,
+            aliases: BTreeMap::new(),
+            templates: BTreeMap::new(),
+            views: BTreeMap::new(),
+            contexts: BTreeMap::new(),
+            api_users: BTreeMap::new(),
+            owner_only: false,
+            read_only: false,
+            assume_yes: false,
+            dry_run: false,
+            verbosity: 0,
+            passphrase: None,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config file at `path`, falling back to defaults if it doesn't exist.
+    pub fn load(path: &Path) -> crate::Result<Config> {
+        match fs::read_to_string(path) {
+            Ok(data) => {
+                log::debug("config", format_args!("reading {}", path.display()));
+                let table = toml::parse(&data).map_err(|e| invalid(path, &e))?;
+                Config::from_table(&table).map_err(|e| invalid(path, &e))
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Builds a config from a parsed TOML table, validating every known key.
+    fn from_table(table: &Table) -> Result<Config, String> {
+        let mut config = Config::default();
+        for (key, value) in table {
+            match key.as_str() {
+                "aliases" => {
+                    let aliases = value.as_table().ok_or("'aliases' must be a table")?;
+                    for (name, expansion) in aliases {
+                        let expansion = expansion
+                            .as_str()
+                            .ok_or_else(|| format!("alias '{}' must be a string", name))?;
+                        config.aliases.insert(name.clone(), expansion.to_string());
+                    }
+                }
+                "templates" => {
+                    let templates = value.as_table().ok_or("'templates' must be a table")?;
+                    for (name, tasks) in templates {
+                        let tasks = tasks
+                            .as_str()
+                            .ok_or_else(|| format!("template '{}' must be a string", name))?;
+                        config.templates.insert(name.clone(), tasks.to_string());
+                    }
+                }
+                "views" => {
+                    let views = value.as_table().ok_or("'views' must be a table")?;
+                    for (name, view) in views {
+                        let view = view
+                            .as_str()
+                            .ok_or_else(|| format!("view '{}' must be a string", name))?;
+                        config.views.insert(name.clone(), view.to_string());
+                    }
+                }
+                "contexts" => {
+                    let contexts = value.as_table().ok_or("'contexts' must be a table")?;
+                    for (name, context) in contexts {
+                        let context = context
+                            .as_str()
+                            .ok_or_else(|| format!("context '{}' must be a string", name))?;
+                        config.contexts.insert(name.clone(), context.to_string());
+                    }
+                }
+                "api_users" => {
+                    let users = value.as_table().ok_or("'api_users' must be a table")?;
+                    for (name, token) in users {
+                        let token = token
+                            .as_str()
+                            .ok_or_else(|| format!("the token of '{}' must be a string", name))?;
+                        config.api_users.insert(name.clone(), token.to_string());
+                    }
+                }
+                "time_projects" => {
+                    let projects = value.as_table().ok_or("'time_projects' must be a table")?;
+                    for (name, id) in projects {
+                        let id = id.as_str().ok_or_else(|| {
+                            format!("the id of project '{}' must be a string", name)
+                        })?;
+                        config.time_projects.insert(name.clone(), id.to_string());
+                    }
+                }
+                "discord_webhooks" => {
+                    let webhooks = value
+                        .as_table()
+                        .ok_or("'discord_webhooks' must be a table")?;
+                    for (list, url) in webhooks {
+                        let url = url.as_str().ok_or_else(|| {
+                            format!("the Discord webhook of '{}' must be a string", list)
+                        })?;
+                        url.parse::<Url>()?;
+                        config
+                            .discord_webhooks
+                            .insert(list.clone(), url.to_string());
+                    }
+                }
+                _ => {
+                    let text = value
+                        .as_str()
+                        .ok_or_else(|| format!("'{}' must be a string", key))?;
+                    config.apply(key, text)?;
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    /// Overrides settings from any `TODO_*` environment variables that are set.
+    pub fn apply_env(&mut self) -> Result<(), String> {
+        for (var, key) in ENV_VARS {
+            if let Ok(value) = env::var(var) {
+                log::debug("config", format_args!("{} set from ${}", key, var));
+                self.apply(key, &value)
+                    .map_err(|e| format!("{} (from ${})", e, var))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets a single top-level setting from its string form.
+    pub fn apply(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "storage_backend" => self.storage_backend = value.parse()?,
+            "storage_path" => self.storage_path = expand_home(value),
+            "storage_url" if value.is_empty() => self.storage_url = None,
+            "storage_url" => {
+                value.parse::<Url>()?;
+                self.storage_url = Some(value.to_string());
+            }
+            "list" => {
+                if value.contains(['/', '\\']) {
+                    return Err(format!("'{}' is not a valid list name", value));
+                }
+                self.list = non_empty(value);
+            }
+            "default_sort" => self.default_sort = value.parse()?,
+            "urgency_due" => self.urgency.due = parse_coefficient(value)?,
+            "urgency_priority" => self.urgency.priority = parse_coefficient(value)?,
+            "urgency_age" => self.urgency.age = parse_coefficient(value)?,
+            "urgency_age_days" => self.urgency.age_days = parse_days(value)?,
+            "context" => {
+                self.context = non_empty(value).filter(|name| name != "none");
+            }
+            "color" => self.color = value.parse()?,
+            "plain" => self.plain = parse_bool(value)?,
+            "date_format" => self.date_format = value.to_string(),
+            "week_start" if value.eq_ignore_ascii_case("locale") => self.week_start = None,
+            "week_start" => match crate::date::parse_weekday(value) {
+                Some(day) => self.week_start = Some(day),
+                None => return Err(format!("'{}' is not a weekday or 'locale'", value)),
+            },
+            "holidays" => self.holidays = parse_holidays(value)?,
+            "default_command" => self.default_command = non_empty(value),
+            "hooks_dir" => self.hooks_dir = expand_home(value),
+            "reminder_leads" => self.reminder_leads = parse_leads(value)?,
+            "waiting_nudge_days" => self.waiting_nudge_days = parse_days(value)?,
+            "someday_review_days" => self.someday_review_days = parse_days(value)?,
+            "trash_days" => self.trash_days = parse_days(value)?,
+            "prune_after" => self.prune_after = parse_age(value)?,
+            "prune_action" => self.prune_action = value.parse()?,
+            "archive_on_done" => self.archive_on_done = parse_bool(value)?,
+            "time_service" => {
+                self.time_service = match value {
+                    "" => None,
+                    value => Some(value.parse()?),
+                }
+            }
+            "time_account" => self.time_account = non_empty(value),
+            "time_token" => self.time_token = non_empty(value),
+            "webhook_urls" => self.webhook_urls = parse_urls(value)?,
+            "webhook_secret" => self.webhook_secret = non_empty(value),
+            "imap_url" if value.is_empty() => self.imap_url = None,
+            "imap_url" if value.starts_with("imap://") || value.starts_with("imaps://") => {
+                self.imap_url = Some(value.to_string())
+            }
+            "imap_url" => return Err(format!("'{}' is not an imap:// or imaps:// URL", value)),
+            "imap_user" => self.imap_user = non_empty(value),
+            "imap_password" => self.imap_password = non_empty(value),
+            "smtp_url" if value.is_empty() => self.smtp_url = None,
+            "smtp_url" if value.starts_with("smtp://") || value.starts_with("smtps://") => {
+                self.smtp_url = Some(value.to_string())
+            }
+            "smtp_url" => return Err(format!("'{}' is not an smtp:// or smtps:// URL", value)),
+            "smtp_user" => self.smtp_user = non_empty(value),
+            "smtp_password" => self.smtp_password = non_empty(value),
+            "mail_from" => self.mail_from = non_empty(value),
+            "slack_webhook_url" if value.is_empty() => self.slack_webhook_url = None,
+            "slack_webhook_url" => {
+                value.parse::<Url>()?;
+                self.slack_webhook_url = Some(value.to_string());
+            }
+            "slack_token" => self.slack_token = non_empty(value),
+            "slack_channel" => self.slack_channel = non_empty(value),
+            "slack_alerts" => self.slack_alerts = parse_bool(value)?,
+            "telegram_token" => self.telegram_token = non_empty(value),
+            "telegram_chats" => self.telegram_chats = parse_chats(value)?,
+            "http_timeout" => self.http.timeout = parse_seconds(value)?,
+            "http_retries" => {
+                self.http.retries = value
+                    .parse()
+                    .map_err(|_| format!("'{}' is not a number of retries", value))?
+            }
+            "http_proxy" if value.is_empty() => self.http.proxy = None,
+            "http_proxy" => match value.parse::<Url>() {
+                Ok(url) if url.scheme == "http" => self.http.proxy = Some(value.to_string()),
+                _ => return Err(format!("'{}' is not an http:// proxy URL", value)),
+            },
+            "max_task_length" => match value.parse() {
+                Ok(length) if length > 0 => self.max_task_length = length,
+                _ => {
+                    return Err(format!(
+                        "'{}' is not a positive number of characters",
+                        value
+                    ))
+                }
+            },
+            "workflow" => self.workflow = parse_workflow(value)?,
+            "log_file" => self.log_file = non_empty(value).map(|path| expand_home(&path)),
+            "language" if value.is_empty() => self.language = None,
+            "language" => match crate::i18n::normalize(value) {
+                Some(code) => self.language = Some(code.to_string()),
+                None => {
+                    return Err(format!(
+                        "unsupported language '{}' (expected one of: {})",
+                        value,
+                        crate::i18n::LANGUAGES.join(", ")
+                    ))
+                }
+            },
+            "timezone" if value.is_empty() => self.timezone = None,
+            "timezone" => match crate::tz::Zone::named(value) {
+                Some(_) => self.timezone = Some(value.to_string()),
+                None => return Err(format!("unknown time zone '{}'", value)),
+            },
+            "owner_only" => self.owner_only = parse_bool(value)?,
+            "read_only" => self.read_only = parse_bool(value)?,
+            _ => return Err(format!("unknown config key '{}'", key)),
+        }
+        Ok(())
+    }
+
+    /// Returns the current value of a key, as `config get` prints it.
+    pub fn get(&self, key: &str) -> Option<String> {
+        if let Some(name) = key.strip_prefix("aliases.") {
+            return self.aliases.get(name).cloned();
+        }
+        if let Some(name) = key.strip_prefix("templates.") {
+            return self.templates.get(name).cloned();
+        }
+        if let Some(name) = key.strip_prefix("views.") {
+            return self.views.get(name).cloned();
+        }
+        if let Some(name) = key.strip_prefix("contexts.") {
+            return self.contexts.get(name).cloned();
+        }
+        if let Some(name) = key.strip_prefix("api_users.") {
+            return self.api_users.get(name).cloned();
+        }
+        if let Some(name) = key.strip_prefix("time_projects.") {
+            return self.time_projects.get(name).cloned();
+        }
+        if let Some(list) = key.strip_prefix("discord_webhooks.") {
+            return self.discord_webhooks.get(list).cloned();
+        }
+        match key {
+            "storage_backend" => Some(self.storage_backend.to_string()),
+            "storage_path" => Some(self.storage_path.display().to_string()),
+            "storage_url" => self.storage_url.clone(),
+            "list" => self.list.clone(),
+            "default_sort" => Some(self.default_sort.to_string()),
+            "urgency_due" => Some(self.urgency.due.to_string()),
+            "urgency_priority" => Some(self.urgency.priority.to_string()),
+            "urgency_age" => Some(self.urgency.age.to_string()),
+            "urgency_age_days" => Some(self.urgency.age_days.to_string()),
+            "context" => self.context.clone(),
+            "color" => Some(self.color.to_string()),
+            "plain" => Some(self.plain.to_string()),
+            "date_format" => Some(self.date_format.clone()),
+            "week_start" => Some(match self.week_start {
+                Some(day) => crate::date::WEEKDAY_NAMES[day as usize].to_lowercase(),
+                None => "locale".to_string(),
+            }),
+            "holidays" => Some(
+                self.holidays
+                    .iter()
+                    .map(|source| source.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+            "default_command" => self.default_command.clone(),
+            "hooks_dir" => Some(self.hooks_dir.display().to_string()),
+            "reminder_leads" => Some(
+                self.reminder_leads
+                    .iter()
+                    .map(|days| format!("{}d", days))
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+            "waiting_nudge_days" => Some(self.waiting_nudge_days.to_string()),
+            "someday_review_days" => Some(self.someday_review_days.to_string()),
+            "trash_days" => Some(self.trash_days.to_string()),
+            "prune_after" => Some(format!("{}d", self.prune_after)),
+            "prune_action" => Some(self.prune_action.to_string()),
+            "archive_on_done" => Some(self.archive_on_done.to_string()),
+            "time_service" => self.time_service.map(|s| s.to_string()),
+            "time_account" => self.time_account.clone(),
+            "time_token" => self.time_token.clone(),
+            "webhook_urls" => Some(self.webhook_urls.join(",")),
+            "webhook_secret" => self.webhook_secret.clone(),
+            "imap_url" => self.imap_url.clone(),
+            "imap_user" => self.imap_user.clone(),
+            "imap_password" => self.imap_password.clone(),
+            "smtp_url" => self.smtp_url.clone(),
+            "smtp_user" => self.smtp_user.clone(),
+            "smtp_password" => self.smtp_password.clone(),
+            "mail_from" => self.mail_from.clone(),
+            "slack_webhook_url" => self.slack_webhook_url.clone(),
+            "slack_token" => self.slack_token.clone(),
+            "slack_channel" => self.slack_channel.clone(),
+            "slack_alerts" => Some(self.slack_alerts.to_string()),
+            "telegram_token" => self.telegram_token.clone(),
+            "telegram_chats" => Some(
+                self.telegram_chats
+                    .iter()
+                    .map(|chat| chat.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+            "http_timeout" => Some(format!("{}s", self.http.timeout.as_secs())),
+            "http_retries" => Some(self.http.retries.to_string()),
+            "http_proxy" => self.http.proxy.clone(),
+            "max_task_length" => Some(self.max_task_length.to_string()),
+            "workflow" => Some(self.workflow.join(",")),
+            "log_file" => self.log_file.as_ref().map(|p| p.display().to_string()),
+            "language" => self.language.clone(),
+            "timezone" => self.timezone.clone(),
+            "owner_only" => Some(self.owner_only.to_string()),
+            "read_only" => Some(self.read_only.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Returns the file that holds the active list.
+    ///
+    /// The Discord webhook of the list in use, if it has one.
+    pub fn discord_webhook(&self) -> Option<&str> {
+        self.discord_webhooks
+            .get(self.list.as_deref().unwrap_or("main"))
+            .map(String::as_str)
+    }
+
+    /// Named lists live next to the main file, e.g. `work` becomes `work.json`.
+    pub fn store_path(&self) -> PathBuf {
+        match &self.list {
+            Some(name) => self.storage_path.with_file_name(format!("{}.json", name)),
+            None => self.storage_path.clone(),
+        }
+    }
+
+    /// Returns the backend that holds the active list, refusing writes if
+    /// `read_only` is set.
+    ///
+    /// Named lists sit next to the main one: `work.json` beside the main
+    /// file, or in place of the last part of the URL for `remote`.
+    pub fn backend(&self) -> crate::Result<Box<dyn StorageBackend>> {
+        let backend: Box<dyn StorageBackend> = match self.storage_backend {
+            StorageKind::Json => Box::new(JsonFile::new(self.store_path())),
+            StorageKind::Journal => {
+                Box::new(Journal::new(self.store_path().with_extension("journal")))
+            }
+            StorageKind::Memory => Box::new(Memory::shared(self.list.as_deref().unwrap_or(""))),
+            StorageKind::Remote => {
+                let url = self.storage_url.as_deref().ok_or_else(|| {
+                    Error::Config(
+                        "storage_backend is 'remote' but storage_url is not set".to_string(),
+                    )
+                })?;
+                let url = match &self.list {
+                    Some(name) => {
+                        let start = url.find("://").map_or(0, |i| i + 3);
+                        let base = url[start..].rfind('/').map_or(url, |i| &url[..start + i]);
+                        format!("{}/{}.json", base, name)
+                    }
+                    None => url.to_string(),
+                };
+                Box::new(Remote::new(url, self.http.clone()))
+            }
+        };
+        Ok(if self.read_only {
+            Box::new(ReadOnly(backend))
+        } else {
+            backend
+        })
+    }
+
+    /// The settings for the active list's archive, where finished tasks are
+    /// moved to: a list named `archive` beside the main one, or `NAME.archive`
+    /// for the list NAME. It is an ordinary list, so `--list archive` shows it.
+    pub fn archive(&self) -> Config {
+        let name = match &self.list {
+            Some(list) => format!("{}.archive", list),
+            None => "archive".to_string(),
+        };
+        Config {
+            list: Some(name),
+            ..self.clone()
+        }
+    }
+
+    /// The trash of the active list.
+    pub fn trash(&self) -> Trash {
+        Trash::new(self.list.as_deref(), self.trash_days)
+    }
+
+    /// Loads the active list from the configured backend. Saved changes go
+    /// to the audit log, and the tasks they remove to the trash.
+    pub fn open_store(&self) -> crate::Result<TaskStore> {
+        let mut store = TaskStore::with_backend(self.backend()?)?;
+        store.audit_as(crate::audit::default_via());
+        store.set_max_length(self.max_task_length);
+        if self.trash_days > 0 {
+            store.trash_to(self.trash());
+        }
+        Ok(store)
+    }
+}
+
+/// The tables of named settings, such as `aliases.<name>`.
+const GROUPS: &[&str] = &[
+    "aliases",
+    "templates",
+    "views",
+    "contexts",
+    "api_users",
+    "time_projects",
+    "discord_webhooks",
+];
+
+/// The settings that hold passwords and tokens, besides `api_users.<name>`
+/// and `discord_webhooks.<list>`. They are masked when shown, and a config
+/// file holding one is readable by its owner only.
+const SECRETS: &[&str] = &[
+    "time_token",
+    "webhook_secret",
+    "imap_password",
+    "smtp_password",
+    "slack_webhook_url",
+    "slack_token",
+    "telegram_token",
+];
+
+/// The tables of named settings whose values are secrets.
+const SECRET_GROUPS: &[&str] = &["api_users", "discord_webhooks"];
+
+/// Whether `key`, such as `smtp_password` or `api_users.alice`, holds a secret.
+pub fn is_secret(key: &str) -> bool {
+    match key.split_once('.') {
+        Some((group, _)) => SECRET_GROUPS.contains(&group),
+        None => SECRETS.contains(&key),
+    }
+}
+
+/// `value` as `config` shows it for `key`: masked if it is a secret.
+pub fn shown(key: &str, value: &str) -> String {
+    if is_secret(key) && !value.is_empty() {
+        "********".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Writes `table` to the config file at `path`, readable by its owner only
+/// once it holds a secret.
+fn write(path: &Path, table: &Table) -> crate::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        let private = table.iter().any(|(key, value)| match value {
+            Value::Table(t) => SECRET_GROUPS.contains(&key.as_str()) && !t.is_empty(),
+            _ => SECRETS.contains(&key.as_str()),
+        });
+        if private {
+            // Restricted before the secret is written, not after.
+            let file = fs::OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .mode(0o600)
+                .open(path)?;
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    fs::write(path, toml::to_string(table))?;
+    Ok(())
+}
+
+/// Validates and writes one setting to the config file at `path`.
+///
+/// The existing file is re-read so that settings we don't touch are preserved.
+pub fn set(path: &Path, key: &str, value: &str) -> crate::Result<()> {
+    let mut table = match fs::read_to_string(path) {
+        Ok(data) => toml::parse(&data).map_err(|e| invalid(path, &e))?,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Table::new(),
+        Err(e) => return Err(e.into()),
+    };
+
+    let parts = toml::split_key(key).map_err(Error::Invalid)?;
+    match parts.as_slice() {
+        [group, name] if GROUPS.contains(&group.as_str()) => {
+            if group == "discord_webhooks" {
+                value.parse::<Url>().map_err(Error::Invalid)?;
+            }
+            let group_table = table
+                .entry(group.clone())
+                .or_insert_with(|| Value::Table(Table::new()));
+            match group_table {
+                Value::Table(t) => {
+                    t.insert(name.clone(), Value::String(value.to_string()));
+                }
+                _ => return Err(invalid(path, &format!("'{}' must be a table", group))),
+            }
+        }
+        [name] if KEYS.contains(&name.as_str()) => {
+            // Validate before writing so a typo can't leave a broken config behind.
+            Config::default()
+                .apply(name, value)
+                .map_err(Error::Invalid)?;
+            table.insert(name.clone(), Value::String(value.to_string()));
+        }
+        _ => return Err(Error::Invalid(format!("unknown config key '{}'", key))),
+    }
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    write(path, &table)
+}
+
+/// Removes one setting from the config file at `path`, such as a view.
+/// Returns whether it was there.
+pub fn unset(path: &Path, key: &str) -> crate::Result<bool> {
+    let mut table = match fs::read_to_string(path) {
+        Ok(data) => toml::parse(&data).map_err(|e| invalid(path, &e))?,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e.into()),
+    };
+    let parts = toml::split_key(key).map_err(Error::Invalid)?;
+    let removed = match parts.as_slice() {
+        [group, name] if GROUPS.contains(&group.as_str()) => match table.get_mut(group) {
+            Some(Value::Table(t)) => t.remove(name).is_some(),
+            _ => false,
+        },
+        [name] if KEYS.contains(&name.as_str()) => table.remove(name).is_some(),
+        _ => return Err(Error::Invalid(format!("unknown config key '{}'", key))),
+    };
+    if removed {
+        write(path, &table)?;
+    }
+    Ok(removed)
+}
+
+/// Returns the location of the config file, honoring `TODO_CONFIG`.
+pub fn default_path() -> PathBuf {
+    match env::var_os("TODO_CONFIG") {
+        Some(path) if !path.is_empty() => PathBuf::from(path),
+        _ => config_dir().join("config.toml"),
+    }
+}
+
+/// Returns the directory holding our config, following the XDG convention.
+pub fn config_dir() -> PathBuf {
+    let base = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => home_dir().join(".config"),
+    };
+    base.join("todo")
+}
+
+/// Returns the user's home directory, or the current directory if it is unknown.
+fn home_dir() -> PathBuf {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Expands a leading `~/` in a configured path.
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => home_dir().join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Parses lead times such as `1d,1w` into a number of days each.
+fn parse_leads(value: &str) -> Result<Vec<u32>, String> {
+    let mut leads = Vec::new();
+    for lead in value.split(',').map(str::trim).filter(|l| !l.is_empty()) {
+        let (count, unit) = lead.split_at(lead.trim_end_matches(['d', 'w']).len());
+        let days = match (count.parse::<u32>(), unit) {
+            (Ok(n), "" | "d") => n,
+            (Ok(n), "w") => n * 7,
+            _ => {
+                return Err(format!(
+                    "'{}' is not a lead time (expected e.g. 1d or 2w)",
+                    lead
+                ))
+            }
+        };
+        leads.push(days);
+    }
+    leads.sort_unstable();
+    leads.dedup();
+    Ok(leads)
+}
+
+/// Parses an age such as `90`, `90d` or `12w` into a number of days.
+pub fn parse_age(value: &str) -> Result<u32, String> {
+    let value = value.trim();
+    let (count, unit) = value.split_at(value.trim_end_matches(['d', 'w']).len());
+    match (count.parse::<u32>(), unit) {
+        (Ok(n), "" | "d") => Ok(n),
+        (Ok(n), "w") => Ok(n * 7),
+        _ => Err(format!(
+            "'{}' is not an age (expected e.g. 90d or 12w)",
+            value
+        )),
+    }
+}
+
+/// Parses a number of days such as `7` or `7d`.
+fn parse_days(value: &str) -> Result<u32, String> {
+    value
+        .strip_suffix('d')
+        .unwrap_or(value)
+        .parse()
+        .map_err(|_| format!("'{}' is not a number of days", value))
+}
+
+/// Parses how much a part of a task's urgency counts, e.g. `12` or `-2.5`.
+fn parse_coefficient(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(coefficient) if coefficient.is_finite() => Ok(coefficient),
+        _ => Err(format!(
+            "'{}' is not a coefficient (expected a number, e.g. 6)",
+            value
+        )),
+    }
+}
+
+/// Parses a timeout such as `10` or `10s` into a duration.
+fn parse_seconds(value: &str) -> Result<Duration, String> {
+    match value.strip_suffix('s').unwrap_or(value).parse::<u64>() {
+        Ok(seconds) if seconds > 0 => Ok(Duration::from_secs(seconds)),
+        _ => Err(format!(
+            "'{}' is not a timeout (expected a number of seconds, e.g. 10s)",
+            value
+        )),
+    }
+}
+
+/// Parses a comma- or space-separated list of webhook URLs.
+fn parse_urls(value: &str) -> Result<Vec<String>, String> {
+    value
+        .split([',', ' '])
+        .filter(|url| !url.is_empty())
+        .map(|url| {
+            url.parse::<Url>()?;
+            Ok(url.to_string())
+        })
+        .collect()
+}
+
+/// Parses a comma- or space-separated list of Telegram chat IDs; groups'
+/// IDs are negative.
+fn parse_chats(value: &str) -> Result<Vec<i64>, String> {
+    value
+        .split([',', ' '])
+        .filter(|chat| !chat.is_empty())
+        .map(|chat| {
+            chat.parse()
+                .map_err(|_| format!("'{}' is not a Telegram chat ID", chat))
+        })
+        .collect()
+}
+
+/// Parses where holidays come from, e.g. `us,~/holidays.ics`.
+fn parse_holidays(value: &str) -> Result<Vec<Source>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|source| !source.is_empty())
+        .map(|source| match Source::parse(source) {
+            Some(Source::File(path)) => Ok(Source::File(expand_home(&path.to_string_lossy()))),
+            Some(source) => Ok(source),
+            None => Err(format!(
+                "unknown holidays '{}' (expected a country, one of {}, or an .ics file)",
+                source,
+                crate::holiday::COUNTRIES.join(", ")
+            )),
+        })
+        .collect()
+}
+
+/// Parses the workflow statuses, e.g. `todo,doing,review,done`, which must
+/// include `todo` and `done`.
+fn parse_workflow(value: &str) -> Result<Vec<String>, String> {
+    let mut statuses: Vec<String> = Vec::new();
+    for status in value.split([',', ' ']).filter(|s| !s.is_empty()) {
+        let status = crate::validate::status(status).map_err(|e| e.to_string())?;
+        if status == crate::task::CANCELLED {
+            return Err("'cancelled' is always a status; leave it out of the workflow".to_string());
+        }
+        if !statuses.contains(&status) {
+            statuses.push(status);
+        }
+    }
+    for needed in [crate::task::TODO, crate::task::DONE] {
+        if !statuses.iter().any(|s| s == needed) {
+            return Err(format!("the workflow must include '{}'", needed));
+        }
+    }
+    Ok(statuses)
+}
+
+/// Reads a yes/no setting: `true`, `false`, `yes`, `no`, `on`, `off`, `1` or `0`.
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Ok(true),
+        "false" | "no" | "off" | "0" => Ok(false),
+        _ => Err(format!("'{}' is not true or false", value)),
+    }
+}
+
+/// Treats an empty setting as unset, so `TODO_LIST=` clears a configured list.
+fn non_empty(value: &str) -> Option<String> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+fn invalid(path: &Path, msg: &str) -> Error {
+    Error::Config(format!("{}: {}", path.display(), msg))
+}
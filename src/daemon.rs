@@ -0,0 +1,366 @@
+// The reminder daemon: a long-running process that watches the task file and
+// raises notifications as tasks come due.
+//
+// Only one daemon runs at a time. It holds a pid file, created atomically, so
+// a second `daemon start` can tell one is already running; a pid file left
+// behind by a daemon that was killed is noticed and replaced.
+//
+// Each day, a pending task is announced once when its due date is reached
+// (and again every day while it stays overdue), plus once on each configured
+// lead time before it, e.g. a day and a week ahead. Lead times don't count
+// holidays, and aren't announced on one: a task due the day after a holiday
+// is a day ahead the day before it. Reminders set for a time
+// with `remind` are announced once, as soon as that time comes; ones that came
+// while no daemon was running are announced when it starts, if they are less
+// than a day old. Tasks waiting on someone for longer than
+// `waiting_nudge_days` are brought up once a day, as a nudge to chase them.
+//
+// `notify-due` makes the same announcements from cron or a systemd timer
+// instead. What it announced is kept in a file between runs, so running it
+// every few minutes announces each thing once, like the daemon.
+
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Stdio};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::backend::StorageBackend;
+use crate::date::{Date, DateTime};
+use crate::error::{Error, Result};
+use crate::holiday;
+use crate::i18n::tr;
+use crate::log;
+use crate::notify::{self, Notification};
+use crate::task::Task;
+
+/// How often the daemon looks at the task list and the date.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Where the pid file of the running daemon lives.
+pub fn pid_path() -> PathBuf {
+    crate::config::config_dir().join("daemon.pid")
+}
+
+/// Where a daemon started in the background writes its output.
+pub fn log_path() -> PathBuf {
+    crate::config::config_dir().join("daemon.log")
+}
+
+/// The pid of the running daemon, if there is one.
+pub fn running(pid_file: &Path) -> Option<u32> {
+    let pid = fs::read_to_string(pid_file).ok()?.trim().parse().ok()?;
+    is_alive(pid).then_some(pid)
+}
+
+/// The daemon's claim on the pid file. Dropping it removes the file.
+#[derive(Debug)]
+pub struct PidLock {
+    path: PathBuf,
+}
+
+impl PidLock {
+    /// Claims the pid file for this process, failing if another daemon holds it.
+    pub fn acquire(path: &Path) -> Result<PidLock> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Two attempts: the second one after clearing a stale file.
+        for _ in 0..2 {
+            match OpenOptions::new().write(true).create_new(true).open(path) {
+                Ok(mut file) => {
+                    writeln!(file, "{}", process::id())?;
+                    return Ok(PidLock {
+                        path: path.to_path_buf(),
+                    });
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    if let Some(pid) = running(path) {
+                        return Err(Error::Failed(tr(
+                            "daemon-already-running",
+                            &[("pid", &pid)],
+                        )));
+                    }
+                    fs::remove_file(path)?;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Err(Error::Failed(format!(
+            "Could not claim the pid file {}.",
+            path.display()
+        )))
+    }
+}
+
+impl Drop for PidLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Stops the running daemon. Returns its pid, or `None` if none was running.
+pub fn stop(pid_file: &Path) -> Result<Option<u32>> {
+    let Some(pid) = running(pid_file) else {
+        // Clean up after a daemon that died without removing its file.
+        let _ = fs::remove_file(pid_file);
+        return Ok(None);
+    };
+    let status = Command::new("kill").arg(pid.to_string()).status()?;
+    if !status.success() {
+        return Err(Error::Failed(format!("Could not stop process {}.", pid)));
+    }
+    // A killed daemon has no chance to remove its pid file.
+    let _ = fs::remove_file(pid_file);
+    Ok(Some(pid))
+}
+
+fn is_alive(pid: u32) -> bool {
+    // `kill -0` checks that the process exists without signalling it.
+    Command::new("kill")
+        .arg("-0")
+        .arg(pid.to_string())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Where `notify-due` remembers what it announced from `list`.
+pub fn notified_path(list: Option<&str>) -> PathBuf {
+    list_file(&crate::config::config_dir().join("notified"), list)
+}
+
+/// A file in `dir` named after `list`, for keeping what was done for it
+/// between runs.
+pub(crate) fn list_file(dir: &Path, list: Option<&str>) -> PathBuf {
+    let name: String = format!("{}.json", list.unwrap_or("main"))
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || ".-_".contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    dir.join(name)
+}
+
+/// Decides which tasks to announce, remembering what was already announced.
+///
+/// What was announced can be saved and loaded again, for announcing from
+/// separate runs rather than one long-running process.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Reminders {
+    /// How many days ahead of the due date to send extra reminders.
+    #[serde(skip)]
+    leads: Vec<u32>,
+    /// Announcements already made, as (task text, due date, day announced).
+    sent: HashSet<(String, Date, Date)>,
+    /// Reminders already announced, as (task text, reminder time).
+    rung: HashSet<(String, DateTime)>,
+    /// After how many days of waiting to nudge; zero for never.
+    #[serde(skip)]
+    nudge_days: u32,
+    /// Nudges already made, as (task text, day nudged).
+    nudged: HashSet<(String, Date)>,
+}
+
+impl Reminders {
+    pub fn new(leads: &[u32], nudge_days: u32) -> Reminders {
+        Reminders {
+            leads: leads.to_vec(),
+            sent: HashSet::new(),
+            rung: HashSet::new(),
+            nudge_days,
+            nudged: HashSet::new(),
+        }
+    }
+
+    /// Reads what was announced from `path`, saved by [`Reminders::save`];
+    /// nothing was if the file doesn't exist yet.
+    pub fn load(path: &Path, leads: &[u32], nudge_days: u32) -> Result<Reminders> {
+        let mut reminders: Reminders = match fs::read_to_string(path) {
+            Ok(data) => serde_json::from_str(&data).map_err(|e| Error::Parse {
+                location: path.display().to_string(),
+                line: e.line(),
+                column: e.column(),
+                message: e.to_string(),
+            })?,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Reminders::default(),
+            Err(e) => return Err(e.into()),
+        };
+        reminders.leads = leads.to_vec();
+        reminders.nudge_days = nudge_days;
+        Ok(reminders)
+    }
+
+    /// Writes what was announced to `path`, forgetting what can't come up
+    /// again after `now`: announcements and nudges from before today, and
+    /// reminders older than the day [`Reminders::ring`] looks back.
+    pub fn save(&mut self, path: &Path, now: DateTime) -> Result<()> {
+        let today = now.date();
+        let since = now.add_minutes(-24 * 60);
+        self.sent.retain(|(_, _, day)| *day >= today);
+        self.nudged.retain(|(_, day)| *day >= today);
+        self.rung.retain(|(_, at)| *at > since);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string(self).expect("Failed to serialize data to JSON.");
+        fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Returns the tasks to announce today that haven't been announced yet.
+    pub fn check<'a>(&mut self, tasks: &'a [Task], today: Date) -> Vec<(usize, &'a Task)> {
+        let mut due = Vec::new();
+        for (index, task) in tasks.iter().enumerate() {
+            let Some(date) = task
+                .due
+                .filter(|_| !task.completed && task.someday.is_none())
+            else {
+                continue;
+            };
+            let until = holiday::days_until(today, date);
+            let wanted = until <= 0
+                || (!holiday::is_holiday(today)
+                    && self.leads.iter().any(|&lead| until == i64::from(lead)));
+            if wanted && self.sent.insert((task.text.clone(), date, today)) {
+                due.push((index, task));
+            }
+        }
+        due
+    }
+
+    /// Returns the tasks that have waited too long on someone and haven't
+    /// been nudged about today.
+    pub fn nudges<'a>(&mut self, tasks: &'a [Task], today: Date) -> Vec<(usize, &'a Task)> {
+        if self.nudge_days == 0 {
+            return Vec::new();
+        }
+        notify::waiting_tasks(tasks, today, self.nudge_days)
+            .into_iter()
+            .filter(|(_, task)| self.nudged.insert((task.text.clone(), today)))
+            .collect()
+    }
+
+    /// Returns the tasks with a reminder that has gone off by `now` and
+    /// hasn't been announced yet. Reminders more than a day old are skipped.
+    pub fn ring<'a>(&mut self, tasks: &'a [Task], now: DateTime) -> Vec<(usize, &'a Task)> {
+        let since = now.add_minutes(-24 * 60);
+        let mut rung = Vec::new();
+        for (index, task) in notify::reminded_tasks(tasks, since, now) {
+            let mut new = false;
+            for &at in task.reminders.iter().filter(|&&at| since < at && at <= now) {
+                new |= self.rung.insert((task.text.clone(), at));
+            }
+            if new {
+                rung.push((index, task));
+            }
+        }
+        rung
+    }
+}
+
+/// Builds the notification for one round of reminders.
+pub fn reminder(due: &[(usize, &Task)], today: Date) -> Option<Notification> {
+    let (now, ahead): (Vec<_>, Vec<_>) = due
+        .iter()
+        .partition(|(_, task)| task.due.is_some_and(|d| d <= today));
+    let mut notification = notify::summarize(&now, today);
+    if !ahead.is_empty() {
+        let lines: Vec<String> = ahead
+            .iter()
+            .map(|(index, task)| {
+                let days = today.days_until(task.due.expect("only tasks with a due date"));
+                tr(
+                    "reminder-line",
+                    &[
+                        ("number", &(index + 1)),
+                        ("task", &task.text),
+                        ("days", &days),
+                    ],
+                )
+            })
+            .collect();
+        match &mut notification {
+            Some(n) => {
+                n.body.push('\n');
+                n.body.push_str(&lines.join("\n"));
+            }
+            None => {
+                notification = Some(Notification {
+                    title: tr("reminder-coming-up", &[("count", &ahead.len())]),
+                    body: lines.join("\n"),
+                    urgent: false,
+                })
+            }
+        }
+    }
+    notification
+}
+
+/// Watches the list in `backend` forever, calling `announce` whenever reminders are due.
+///
+/// A list kept in a file is only re-read when the file changes; other lists
+/// are re-read on every poll. Errors reading the list are reported through
+/// `on_error` without stopping the daemon.
+pub fn watch(
+    backend: &dyn StorageBackend,
+    leads: &[u32],
+    nudge_days: u32,
+    mut announce: impl FnMut(Notification),
+    mut on_error: impl FnMut(Error),
+) -> ! {
+    let mut reminders = Reminders::new(leads, nudge_days);
+    let mut seen: Option<SystemTime> = None;
+    let mut tasks: Vec<Task> = Vec::new();
+    let mut last_day: Option<Date> = None;
+    loop {
+        let modified = backend
+            .path()
+            .and_then(|path| fs::metadata(path).and_then(|m| m.modified()).ok());
+        let today = Date::today();
+        log::trace("daemon", format_args!("polling {}", backend.location()));
+        if modified.is_none() || modified != seen || last_day != Some(today) {
+            log::debug(
+                "daemon",
+                format_args!("{} changed or a new day began", backend.location()),
+            );
+            match backend.load() {
+                Ok(loaded) => {
+                    tasks = loaded;
+                    seen = modified;
+                }
+                Err(e) => on_error(e),
+            }
+            last_day = Some(today);
+            let due = reminders.check(&tasks, today);
+            if let Some(notification) = reminder(&due, today) {
+                log::info("daemon", format_args!("announcing {} tasks", due.len()));
+                announce(notification);
+            }
+            let waiting = reminders.nudges(&tasks, today);
+            if let Some(notification) = notify::nudge(&waiting, today) {
+                log::info(
+                    "daemon",
+                    format_args!("nudging about {} tasks", waiting.len()),
+                );
+                announce(notification);
+            }
+        }
+        let rung = reminders.ring(&tasks, DateTime::now());
+        if let Some(notification) = notify::remind(&rung) {
+            log::info("daemon", format_args!("reminding of {} tasks", rung.len()));
+            announce(notification);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
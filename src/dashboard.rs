@@ -0,0 +1,292 @@
+// The read-only web page served by `todo_cli dashboard`, for a screen on the
+// wall that shows the list to everyone in the house.
+//
+//   GET /            the pending tasks, as `list` shows them, in a page
+//                    that reloads itself every `--refresh` seconds
+//   GET /tasks/{id}  one task, with its notes and checklist: what the
+//                    links of `qr --link` lead to
+//
+// The page takes the filters as query parameters, `?tag=shopping&project=home`
+// and `q` for text, and has a form to set them; since they stay in the
+// address, a display can be pointed at a filtered page for good. Nothing on
+// the page changes the list and there is no token: anyone who can reach the
+// port can read the list, so it listens on 127.0.0.1 unless told otherwise.
+// Secret tasks stay masked.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::net::{TcpListener, TcpStream};
+
+use todo_cli::config::Config;
+use todo_cli::filter::Filter;
+use todo_cli::http::{Request, Response};
+use todo_cli::i18n::tr;
+use todo_cli::{Date, DateTime, Task};
+
+use crate::{active_context, format_date_time, sort_tasks, warn};
+
+/// Accepts connections on `addr` until the process is stopped.
+pub fn serve(config: &Config, addr: &str, refresh: u64) -> todo_cli::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    println!(
+        "{}",
+        tr(
+            "dashboard-serving",
+            &[("url", &format!("http://{}", listener.local_addr()?))]
+        )
+    );
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(e) = handle_connection(config, refresh, stream) {
+                    warn(&e.to_string());
+                }
+            }
+            Err(e) => warn(&tr("accept-failed", &[("error", &e)])),
+        }
+    }
+    Ok(())
+}
+
+fn handle_connection(config: &Config, refresh: u64, stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(config.http.timeout))?;
+    let response = match Request::read(&stream) {
+        Ok(request) => handle(config, refresh, &request),
+        Err(e) => Response::error(400, &e.to_string()),
+    };
+    response.write_to(&stream)
+}
+
+/// Turns one request into a response.
+fn handle(config: &Config, refresh: u64, request: &Request) -> Response {
+    if request.method != "GET" {
+        return Response::error(405, "method not allowed").with_header("Allow", "GET");
+    }
+    let path: Vec<&str> = request.path.iter().map(String::as_str).collect();
+    let result = match path.as_slice() {
+        [] => page(config, refresh, &request.query).map(Some),
+        ["tasks", id] => match id.parse() {
+            Ok(id) => task_page(config, refresh, id),
+            Err(_) => Ok(None),
+        },
+        _ => return Response::error(404, "not found"),
+    };
+    match result {
+        Ok(Some(html)) => Response::html(200, &html),
+        Ok(None) => Response::html(
+            404,
+            &format!("<p>{}</p>\n", escape(&tr("dashboard-task-missing", &[]))),
+        ),
+        Err(e) => Response::html(500, &format!("<p>{}</p>\n", escape(&e.to_string()))),
+    }
+}
+
+/// The start of a page, up to the heading.
+fn head(title: &str, refresh: u64) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <meta http-equiv=\"refresh\" content=\"{}\">\n<title>{}</title>\n<style>{}</style>\n\
+         </head>\n<body>\n<h1>{}</h1>\n",
+        refresh,
+        escape(title),
+        STYLE,
+        escape(title)
+    )
+}
+
+/// The end of a page, with when it was made.
+fn foot(config: &Config) -> String {
+    format!(
+        "<footer>{}</footer>\n</body>\n</html>\n",
+        escape(&tr(
+            "dashboard-updated",
+            &[("time", &format_date_time(DateTime::now(), config))]
+        ))
+    )
+}
+
+/// The page of the task with `id`, if there is one.
+fn task_page(config: &Config, refresh: u64, id: u64) -> todo_cli::Result<Option<String>> {
+    let store = config.open_store()?;
+    let Some((index, task)) = store
+        .tasks()
+        .iter()
+        .enumerate()
+        .find(|(_, task)| task.id == id)
+    else {
+        return Ok(None);
+    };
+    let mut html = head(&tr("dashboard-title", &[]), refresh);
+    html.push_str(&format!(
+        "<ul>\n{}</ul>\n",
+        item(index, task, config, Date::today())
+    ));
+    if let Some(notes) = &task.notes {
+        html.push_str(&format!(
+            "<p class=\"notes\">{}</p>\n",
+            escape(notes.trim_end())
+        ));
+    }
+    if !task.checklist.is_empty() {
+        html.push_str("<ul class=\"checklist\">\n");
+        for step in &task.checklist {
+            let mark = if step.done { "☑" } else { "☐" };
+            html.push_str(&format!("<li>{} {}</li>\n", mark, escape(&step.text)));
+        }
+        html.push_str("</ul>\n");
+    }
+    html.push_str(&format!(
+        "<p><a href=\"/\">{}</a></p>\n",
+        escape(&tr("dashboard-back", &[]))
+    ));
+    html.push_str(&foot(config));
+    Ok(Some(html))
+}
+
+/// The page for the filters in `query`.
+fn page(
+    config: &Config,
+    refresh: u64,
+    query: &BTreeMap<String, String>,
+) -> todo_cli::Result<String> {
+    let param = |name: &str| {
+        query
+            .get(name)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    };
+    let store = config.open_store()?;
+    let tasks = store.tasks();
+    let mut filter = Filter {
+        text: param("q").map(String::from),
+        completed: Some(false),
+        tags: param("tag").map(String::from).into_iter().collect(),
+        project: param("project").map(String::from),
+        contexts: param("context").map(String::from).into_iter().collect(),
+        scope: active_context(config)?,
+        ..Filter::default()
+    };
+    filter.pending = tasks
+        .iter()
+        .filter(|task| !task.completed)
+        .map(|task| task.id)
+        .collect();
+    let mut numbered: Vec<(usize, Task)> = tasks
+        .iter()
+        .enumerate()
+        .filter(|(_, task)| filter.matches(task))
+        .map(|(index, task)| (index, task.clone()))
+        .collect();
+    sort_tasks(&mut numbered, config.default_sort, &config.urgency);
+
+    let title = match &config.list {
+        Some(list) => tr("dashboard-title-list", &[("list", list)]),
+        None => tr("dashboard-title", &[]),
+    };
+    let mut html = head(&title, refresh);
+
+    html.push_str("<form method=\"get\" action=\"/\">\n");
+    for (name, label) in [
+        ("q", tr("dashboard-text", &[])),
+        ("tag", tr("dashboard-tag", &[])),
+        ("project", tr("dashboard-project", &[])),
+        ("context", tr("dashboard-context", &[])),
+    ] {
+        html.push_str(&format!(
+            "<input name=\"{}\" placeholder=\"{}\" value=\"{}\">\n",
+            name,
+            escape(&label),
+            escape(param(name).unwrap_or_default())
+        ));
+    }
+    html.push_str(&format!(
+        "<button>{}</button> <a href=\"/\">{}</a>\n</form>\n",
+        escape(&tr("dashboard-filter", &[])),
+        escape(&tr("dashboard-clear", &[]))
+    ));
+
+    let today = Date::today();
+    if numbered.is_empty() {
+        html.push_str(&format!(
+            "<p class=\"empty\">{}</p>\n",
+            escape(&tr("dashboard-empty", &[]))
+        ));
+    } else {
+        html.push_str("<ul>\n");
+        for (index, task) in &numbered {
+            html.push_str(&item(*index, task, config, today));
+        }
+        html.push_str("</ul>\n");
+    }
+    html.push_str(&foot(config));
+    Ok(html)
+}
+
+/// One task as a list item: its number, text and details.
+pub fn item(index: usize, task: &Task, config: &Config, today: Date) -> String {
+    let mut details = Vec::new();
+    if let Some(due) = task.due_label(&config.date_format) {
+        let class = match task.due {
+            _ if task.is_overdue(today) => "overdue",
+            Some(due) if due == today => "today",
+            _ => "due",
+        };
+        details.push(format!(
+            "<span class=\"{}\">{}</span>",
+            class,
+            escape(&tr("task-due", &[("date", &due)]))
+        ));
+    }
+    if let Some(priority) = task.priority {
+        details.push(format!("<span class=\"priority\">!{}</span>", priority));
+    }
+    if let Some(project) = &task.project {
+        details.push(format!("+{}", escape(project)));
+    }
+    for context in &task.contexts {
+        details.push(format!("@{}", escape(context)));
+    }
+    for tag in &task.tags {
+        details.push(format!("#{}", escape(tag)));
+    }
+    if let Some((done, total)) = task.progress() {
+        details.push(format!("[{}/{}]", done, total));
+    }
+    if task.pinned {
+        details.push("📌".to_string());
+    }
+    format!(
+        "<li><span class=\"number\">{}.</span> {} <span class=\"details\">{}</span></li>\n",
+        index + 1,
+        escape(&task.text),
+        details.join(" ")
+    )
+}
+
+/// `text` with the characters HTML gives meaning to escaped.
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// How a page, or a mail of tasks, looks.
+pub const STYLE: &str = "\
+body { font-family: sans-serif; font-size: 1.6em; margin: 1em 2em; \
+background: #fdfdf8; color: #222; } \
+h1 { font-size: 1.4em; margin-bottom: 0.3em; } \
+form { font-size: 0.6em; margin-bottom: 1em; } \
+input { font-size: 1em; width: 9em; } \
+ul { list-style: none; padding: 0; } \
+li { padding: 0.35em 0; border-bottom: 1px solid #ddd; } \
+.number { color: #999; } \
+.details { color: #666; font-size: 0.75em; } \
+.overdue { color: #c00; font-weight: bold; } \
+.today { color: #b80; font-weight: bold; } \
+.priority { color: #a40; } \
+.empty { color: #888; } \
+.notes { white-space: pre-wrap; font-size: 0.75em; } \
+.checklist li { border: none; font-size: 0.75em; } \
+footer { color: #999; font-size: 0.5em; margin-top: 1em; }";
@@ -0,0 +1,812 @@
+// Calendar dates: parsing what people type, formatting for display, and the
+// day arithmetic behind due dates.
+//
+// Dates are stored in the JSON file as `YYYY-MM-DD` strings, and times
+// (reminders, due times) as `YYYY-MM-DD HH:MM` in UTC; `today` is the local
+// date, and `tz` turns times into local ones and back. The English
+// date words always work; the words of the current language (see `i18n`) are
+// accepted as well, and used for month and weekday names when formatting.
+//
+// Weeks start on Monday unless `week_start` says otherwise; `locale` takes
+// the day usual where LC_ALL, LC_TIME or LANG says the user is, which also
+// gives the date order `%x` formats with (e.g. 10/15/2026 for en_US).
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::i18n;
+
+const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// Weekday names, starting from Monday.
+pub const WEEKDAY_NAMES: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+/// The day weeks start on (Monday = 0), or `UNSET` for the locale's.
+static WEEK_START: AtomicU32 = AtomicU32::new(UNSET);
+const UNSET: u32 = u32::MAX;
+
+/// Regions where weeks start on Sunday.
+const SUNDAY_REGIONS: &[&str] = &[
+    "US", "CA", "MX", "BR", "JP", "KR", "TW", "HK", "PH", "IN", "IL", "ZA", "SA",
+];
+
+/// A day in the proleptic Gregorian calendar.
+/// The most business days an offset may move a date: a few centuries.
+pub const MAX_BUSINESS_DAYS: i64 = 100_000;
+
+/// The most days walked looking for business days among holidays: about
+/// three years.
+const MAX_WALK: i64 = 3 * 366;
+
+/// The first and last dates an offset may reach, or holidays be worked out
+/// for: the years 1 to 9999.
+pub const FIRST: Date = Date {
+    year: 1,
+    month: 1,
+    day: 1,
+};
+pub const LAST: Date = Date {
+    year: 9999,
+    month: 12,
+    day: 31,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: i32,
+    month: u32,
+    day: u32,
+}
+
+impl Date {
+    /// Builds a date, returning `None` if it doesn't exist (e.g. February 30th).
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Date> {
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Date { year, month, day })
+    }
+
+    /// Today's date in the local time zone.
+    pub fn today() -> Date {
+        crate::tz::now().date()
+    }
+
+    /// The number of days since 1970-01-01.
+    pub fn days(self) -> i64 {
+        // Howard Hinnant's days_from_civil algorithm.
+        let y = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = y.div_euclid(400);
+        let yoe = y - era * 400;
+        let m = i64::from(self.month);
+        let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + i64::from(self.day) - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146_097 + doe - 719_468
+    }
+
+    /// The date `days` days after 1970-01-01.
+    pub fn from_days(days: i64) -> Date {
+        // Howard Hinnant's civil_from_days algorithm.
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (yoe + era * 400 + i64::from(month <= 2)) as i32;
+        Date { year, month, day }
+    }
+
+    /// Moves the date by a number of days, which may be negative.
+    pub fn add_days(self, days: i64) -> Date {
+        Date::from_days(self.days() + days)
+    }
+
+    /// Like [`Date::add_days`], but `None` if the date would fall outside the
+    /// years 1 to 9999.
+    pub fn checked_add_days(self, days: i64) -> Option<Date> {
+        let days = self.days().checked_add(days)?;
+        (FIRST.days()..=LAST.days())
+            .contains(&days)
+            .then(|| Date::from_days(days))
+    }
+
+    /// Whether the date is a business day: a weekday, Monday to Friday,
+    /// that isn't a holiday.
+    pub fn is_business_day(self) -> bool {
+        self.weekday() < 5 && !crate::holiday::is_holiday(self)
+    }
+
+    /// Moves the date by a number of business days, which may be negative,
+    /// skipping weekends: `+1` from a Friday, Saturday or Sunday is Monday.
+    /// Fails for more than `MAX_BUSINESS_DAYS`, past the years 1 to 9999,
+    /// or when holidays leave no business day for `MAX_WALK` days.
+    pub fn add_business_days(self, days: i64) -> Result<Date, String> {
+        let too_many = || {
+            i18n::tr(
+                "date-too-many-business-days",
+                &[("max", &MAX_BUSINESS_DAYS)],
+            )
+        };
+        if days.abs() > MAX_BUSINESS_DAYS {
+            return Err(too_many());
+        }
+        let step = days.signum();
+        // Any seven days in a row hold five weekdays, so whole weeks are
+        // jumped; what is left, and the holidays jumped over, are walked.
+        let weeks = days.abs() / 5;
+        let mut date = self
+            .checked_add_days(step * 7 * weeks)
+            .ok_or_else(too_many)?;
+        let mut left = days.abs() % 5;
+        if weeks > 0 {
+            left += match step {
+                1 => weekday_holidays(self.add_days(1), date),
+                _ => weekday_holidays(date, self.add_days(-1)),
+            };
+        }
+        // A holiday calendar could mark every weekday; give up rather than
+        // walk on for ever.
+        let mut walked = 0;
+        while left > 0 {
+            date = date.checked_add_days(step).ok_or_else(too_many)?;
+            walked += 1;
+            if walked > MAX_WALK {
+                return Err(i18n::tr(
+                    "date-no-business-day",
+                    &[("date", &self), ("days", &MAX_WALK)],
+                ));
+            }
+            if date.is_business_day() {
+                left -= 1;
+            }
+        }
+        Ok(date)
+    }
+
+    /// How many business days from `self` until `other`: those after `self`
+    /// up to `other`, negative if `other` is earlier.
+    pub fn business_days_until(self, other: Date) -> i64 {
+        let (from, to, sign) = match self <= other {
+            true => (self, other, 1),
+            false => (other, self, -1),
+        };
+        let days = from.days_until(to);
+        if days == 0 {
+            return 0;
+        }
+        // Five in each whole week, then the weekdays among the days left.
+        let weeks = days / 7;
+        let rest = (1..=days % 7)
+            .filter(|&day| from.add_days(weeks * 7 + day).weekday() < 5)
+            .count() as i64;
+        let count = weeks * 5 + rest - weekday_holidays(from.add_days(1), to);
+        sign * count
+    }
+
+    /// Moves the date by a number of months, clamping to the end of shorter months.
+    pub fn add_months(self, months: i32) -> Date {
+        let index = self.year * 12 + self.month as i32 - 1 + months;
+        let year = index.div_euclid(12);
+        let month = index.rem_euclid(12) as u32 + 1;
+        let day = self.day.min(days_in_month(year, month));
+        Date { year, month, day }
+    }
+
+    /// Like [`Date::add_months`], but `None` if the date would fall outside
+    /// the years 1 to 9999.
+    pub fn checked_add_months(self, months: i64) -> Option<Date> {
+        let index = i64::from(self.year) * 12 + i64::from(self.month) - 1;
+        let year = index.checked_add(months)?.div_euclid(12);
+        if !(i64::from(FIRST.year)..=i64::from(LAST.year)).contains(&year) {
+            return None;
+        }
+        Some(self.add_months(i32::try_from(months).ok()?))
+    }
+
+    /// How many days from `self` until `other` (negative if `other` is earlier).
+    pub fn days_until(self, other: Date) -> i64 {
+        other.days() - self.days()
+    }
+
+    /// The year.
+    pub fn year(self) -> i32 {
+        self.year
+    }
+
+    /// The month, from 1 for January.
+    pub fn month(self) -> u32 {
+        self.month
+    }
+
+    /// The day of the month, from 1.
+    pub fn day(self) -> u32 {
+        self.day
+    }
+
+    /// The first day of the week the date is in, as `week_start` says.
+    pub fn start_of_week(self) -> Date {
+        self.add_days(-i64::from((self.weekday() + 7 - week_start()) % 7))
+    }
+
+    /// The day of the week, with Monday as 0 and Sunday as 6.
+    pub fn weekday(self) -> u32 {
+        // 1970-01-01 was a Thursday.
+        (self.days() + 3).rem_euclid(7) as u32
+    }
+
+    /// Parses a date the way a person would type it.
+    ///
+    /// Accepts `YYYY-MM-DD`, `today`, `tomorrow`, `yesterday`, weekday names
+    /// (`fri`, `friday`, meaning the next one), `next week` (its first day),
+    /// and offsets such as `+3d`, `+3bd` (business days), `+2w` or `+1m`.
+    /// Relative dates count from `today`.
+    pub fn parse_relative(input: &str, today: Date) -> Result<Date, String> {
+        let text = input.trim().to_lowercase();
+        match text.as_str() {
+            "today" | "tod" => return Ok(today),
+            "tomorrow" | "tom" => return Ok(today.add_days(1)),
+            "yesterday" => return Ok(today.add_days(-1)),
+            _ => {}
+        }
+        let is_word = |id: &str| i18n::tr_list(id).iter().any(|w| fold(w) == fold(&text));
+        if is_word("date-today") {
+            return Ok(today);
+        } else if is_word("date-tomorrow") {
+            return Ok(today.add_days(1));
+        } else if is_word("date-yesterday") {
+            return Ok(today.add_days(-1));
+        } else if text == "next week" || is_word("date-next-week") {
+            return Ok(today.start_of_week().add_days(7));
+        }
+
+        if let Some(weekday) = parse_weekday(&text) {
+            // Always a day in the future: "fri" on a Friday means next week.
+            let ahead = (weekday as i64 - today.weekday() as i64).rem_euclid(7);
+            return Ok(today.add_days(if ahead == 0 { 7 } else { ahead }));
+        }
+
+        if let Some(offset) = text.strip_prefix('+') {
+            let split = offset
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(offset.len());
+            let (count, unit) = offset.split_at(split);
+            if let Ok(count) = count.parse::<i64>() {
+                let date = match unit {
+                    "" | "d" => Some(today.checked_add_days(count)),
+                    "bd" => return today.add_business_days(count),
+                    "w" => Some(count.checked_mul(7).and_then(|d| today.checked_add_days(d))),
+                    "m" => Some(today.checked_add_months(count)),
+                    "y" => Some(
+                        count
+                            .checked_mul(12)
+                            .and_then(|m| today.checked_add_months(m)),
+                    ),
+                    _ => None,
+                };
+                // A count too big for the calendar is as invalid as a typo.
+                if let Some(date) = date {
+                    return date
+                        .ok_or_else(|| i18n::tr("date-invalid", &[("input", &input.trim())]));
+                }
+            }
+        }
+
+        input
+            .trim()
+            .parse()
+            .map_err(|_| i18n::tr("date-invalid", &[("input", &input.trim())]))
+    }
+
+    /// Formats the date using strftime-style specifiers.
+    ///
+    /// Supports `%Y`, `%y`, `%m`, `%d`, `%e`, `%j`, `%b`, `%B`, `%a`, `%A`, `%%`,
+    /// and `%x` for the date as the user's locale writes it.
+    pub fn format(self, pattern: &str) -> String {
+        let mut out = String::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('Y') => out.push_str(&self.year.to_string()),
+                Some('y') => out.push_str(&format!("{:02}", self.year.rem_euclid(100))),
+                Some('m') => out.push_str(&format!("{:02}", self.month)),
+                Some('d') => out.push_str(&format!("{:02}", self.day)),
+                Some('e') => out.push_str(&format!("{:>2}", self.day)),
+                Some('j') => {
+                    let start = Date::new(self.year, 1, 1).expect("January 1st always exists");
+                    out.push_str(&format!("{:03}", start.days_until(self) + 1));
+                }
+                Some('b') => {
+                    let name = &names("date-months", &MONTH_NAMES)[self.month as usize - 1];
+                    out.extend(name.chars().take(3));
+                }
+                Some('B') => {
+                    out.push_str(&names("date-months", &MONTH_NAMES)[self.month as usize - 1])
+                }
+                Some('a') => {
+                    let name = &names("date-weekdays", &WEEKDAY_NAMES)[self.weekday() as usize];
+                    out.extend(name.chars().take(3));
+                }
+                Some('A') => {
+                    out.push_str(&names("date-weekdays", &WEEKDAY_NAMES)[self.weekday() as usize])
+                }
+                Some('x') => out.push_str(&self.format(locale_format())),
+                Some('%') => out.push('%'),
+                // Unknown specifiers are printed as-is rather than dropped.
+                Some(other) => {
+                    out.push('%');
+                    out.push(other);
+                }
+                None => out.push('%'),
+            }
+        }
+        out
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for Date {
+    type Err = String;
+
+    /// Parses an ISO `YYYY-MM-DD` date.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || format!("'{}' is not a valid YYYY-MM-DD date", s);
+        let mut parts = s.splitn(3, '-');
+        let (Some(y), Some(m), Some(d)) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(err());
+        };
+        let year = y.parse().map_err(|_| err())?;
+        let month = m.parse().map_err(|_| err())?;
+        let day = d.parse().map_err(|_| err())?;
+        Date::new(year, month, day).ok_or_else(err)
+    }
+}
+
+impl Serialize for Date {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// A date and a time of day, to the minute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateTime {
+    date: Date,
+    /// Minutes since midnight.
+    minutes: u32,
+}
+
+/// The time a reminder given only a date goes off at: 9:00.
+const DEFAULT_MINUTES: u32 = 9 * 60;
+
+impl DateTime {
+    /// Builds a date and time, returning `None` if the time doesn't exist.
+    pub fn new(date: Date, hour: u32, minute: u32) -> Option<DateTime> {
+        (hour < 24 && minute < 60).then_some(DateTime {
+            date,
+            minutes: hour * 60 + minute,
+        })
+    }
+
+    /// The current time in UTC, to the minute.
+    pub fn now() -> DateTime {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        DateTime::from_secs(secs)
+    }
+
+    /// The time `secs` seconds after 1970-01-01 00:00.
+    pub fn from_secs(secs: i64) -> DateTime {
+        DateTime {
+            date: Date::from_days(secs.div_euclid(86_400)),
+            minutes: (secs.rem_euclid(86_400) / 60) as u32,
+        }
+    }
+
+    /// The seconds from 1970-01-01 00:00 until the time.
+    pub fn secs(self) -> i64 {
+        self.date.days() * 86_400 + i64::from(self.minutes) * 60
+    }
+
+    pub fn date(self) -> Date {
+        self.date
+    }
+
+    pub fn hour(self) -> u32 {
+        self.minutes / 60
+    }
+
+    pub fn minute(self) -> u32 {
+        self.minutes % 60
+    }
+
+    /// How many minutes from `self` until `other` (negative if `other` is
+    /// earlier).
+    pub fn minutes_until(self, other: DateTime) -> i64 {
+        let minutes = |at: DateTime| at.date.days() * 1440 + i64::from(at.minutes);
+        minutes(other) - minutes(self)
+    }
+
+    /// Moves the time by a number of minutes, which may be negative.
+    pub fn add_minutes(self, minutes: i64) -> DateTime {
+        let total = self.date.days() * 1440 + i64::from(self.minutes) + minutes;
+        DateTime::from_secs(total * 60)
+    }
+
+    /// Like [`DateTime::add_minutes`], but `None` if the time would fall
+    /// outside the years 1 to 9999.
+    pub fn checked_add_minutes(self, minutes: i64) -> Option<DateTime> {
+        let total = i64::from(self.minutes).checked_add(minutes)?;
+        Some(DateTime {
+            date: self.date.checked_add_days(total.div_euclid(1440))?,
+            minutes: total.rem_euclid(1440) as u32,
+        })
+    }
+
+    /// Parses a time the way a person would type it.
+    ///
+    /// Accepts a date as `Date::parse_relative` does followed by a time of
+    /// day (`tomorrow 9am`, `fri 14:30`, `2025-06-30 at noon`), a time alone
+    /// (today's, or tomorrow's if it has passed), a date alone (at 9:00), and
+    /// offsets such as `+30min` or `+2h`. Relative times count from `now`.
+    pub fn parse_relative(input: &str, now: DateTime) -> Result<DateTime, String> {
+        let invalid = || i18n::tr("reminder-invalid", &[("input", &input.trim())]);
+        let text = input.trim().to_lowercase();
+        if let Ok(at) = input.trim().parse() {
+            return Ok(at);
+        }
+        if let Some(offset) = text.strip_prefix('+') {
+            let split = offset
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(offset.len());
+            let (count, unit) = offset.split_at(split);
+            let minutes = match (count.parse::<i64>(), unit.trim()) {
+                (Ok(count), "min" | "mins") => Some(count),
+                (Ok(count), "h" | "hr" | "hrs") => Some(count.checked_mul(60).ok_or_else(invalid)?),
+                _ => None,
+            };
+            if let Some(minutes) = minutes {
+                return now.checked_add_minutes(minutes).ok_or_else(invalid);
+            }
+        }
+
+        let mut words: Vec<&str> = text.split_whitespace().collect();
+        // The time may come last ("tomorrow 9am") or first ("9am tomorrow").
+        let minutes = match (words.first(), words.last()) {
+            (_, Some(last)) if parse_time(last).is_some() => words.pop().and_then(parse_time),
+            (Some(first), _) if parse_time(first).is_some() => parse_time(words.remove(0)),
+            _ => None,
+        };
+        words.retain(|&word| word != "at");
+        let date = match (words.is_empty(), minutes) {
+            (true, None) => return Err(invalid()),
+            // A time alone means the next time the clock shows it.
+            (true, Some(minutes)) if minutes <= now.minutes => now.date.add_days(1),
+            (true, Some(_)) => now.date,
+            (false, _) => {
+                Date::parse_relative(&words.join(" "), now.date).map_err(|_| invalid())?
+            }
+        };
+        Ok(DateTime {
+            date,
+            minutes: minutes.unwrap_or(DEFAULT_MINUTES),
+        })
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:02}:{:02}", self.date, self.hour(), self.minute())
+    }
+}
+
+impl FromStr for DateTime {
+    type Err = String;
+
+    /// Parses `YYYY-MM-DD HH:MM`, or `YYYY-MM-DDTHH:MM` as in ISO 8601.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || format!("'{}' is not a valid YYYY-MM-DD HH:MM time", s);
+        let (date, time) = s.split_once([' ', 'T']).ok_or_else(err)?;
+        let (hour, minute) = time.split_once(':').ok_or_else(err)?;
+        let date = date.parse().map_err(|_| err())?;
+        let hour = hour.parse().map_err(|_| err())?;
+        let minute = minute.parse().map_err(|_| err())?;
+        DateTime::new(date, hour, minute).ok_or_else(err)
+    }
+}
+
+impl Serialize for DateTime {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for DateTime {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Parses a time of day as minutes since midnight: `9am`, `9:30pm`, `14:30`,
+/// `noon` or `midnight`.
+fn parse_time(word: &str) -> Option<u32> {
+    match word {
+        "noon" => return Some(12 * 60),
+        "midnight" => return Some(0),
+        _ => {}
+    }
+    let (clock, half) = match word.strip_suffix("am") {
+        Some(clock) => (clock, Some(0)),
+        None => match word.strip_suffix("pm") {
+            Some(clock) => (clock, Some(12)),
+            None => (word, None),
+        },
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => (hour, minute.parse().ok()?),
+        Some(_) => return None,
+        // A bare number is only a time with am or pm after it.
+        None if half.is_some() => (clock, 0),
+        None => return None,
+    };
+    let hour: u32 = hour.parse().ok()?;
+    let hour = match half {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(offset) => hour % 12 + offset,
+        None => hour,
+    };
+    (hour < 24 && minute < 60).then_some(hour * 60 + minute)
+}
+
+/// Chooses the day weeks start on from now on (Monday = 0), or the locale's
+/// with `None`.
+pub fn set_week_start(weekday: Option<u32>) {
+    WEEK_START.store(weekday.map_or(UNSET, |day| day % 7), Ordering::Relaxed);
+}
+
+/// The day weeks start on (Monday = 0).
+pub fn week_start() -> u32 {
+    match WEEK_START.load(Ordering::Relaxed) {
+        UNSET => locale_week_start(),
+        day => day,
+    }
+}
+
+/// The day weeks start on in the user's locale: Sunday in the Americas and
+/// parts of Asia, Monday elsewhere and without a locale.
+pub fn locale_week_start() -> u32 {
+    match locale() {
+        Some((_, Some(region))) if SUNDAY_REGIONS.contains(&region.as_str()) => 6,
+        _ => 0,
+    }
+}
+
+/// The date pattern usual in the user's locale, which `%x` formats with.
+/// Without a locale, dates are written as YYYY-MM-DD.
+pub fn locale_format() -> &'static str {
+    static FORMAT: OnceLock<&'static str> = OnceLock::new();
+    FORMAT.get_or_init(|| {
+        let Some((language, region)) = locale() else {
+            return "%Y-%m-%d";
+        };
+        match (language.as_str(), region.as_deref()) {
+            (_, Some("US" | "PH")) => "%m/%d/%Y",
+            ("ja" | "zh", _) => "%Y/%m/%d",
+            ("ko", _) => "%Y. %m. %d.",
+            ("sv" | "lt" | "hu", _) | ("en" | "fr", Some("CA")) => "%Y-%m-%d",
+            ("nl", _) => "%d-%m-%Y",
+            ("de" | "ru" | "pl" | "cs" | "sk" | "fi" | "nb" | "no" | "da" | "tr" | "uk", _) => {
+                "%d.%m.%Y"
+            }
+            _ => "%d/%m/%Y",
+        }
+    })
+}
+
+/// The language and region of the locale dates follow, from LC_ALL, LC_TIME
+/// or LANG: `("en", Some("US"))` for `en_US.UTF-8`. `None` for the C locale.
+fn locale() -> Option<(String, Option<String>)> {
+    let value = ["LC_ALL", "LC_TIME", "LANG"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|value| !value.is_empty())?;
+    let name = value.split(['.', '@']).next().unwrap_or_default();
+    if name.is_empty() || name == "C" || name == "POSIX" {
+        return None;
+    }
+    let (language, region) = match name.split_once(['_', '-']) {
+        Some((language, region)) => (language, Some(region.to_uppercase())),
+        None => (name, None),
+    };
+    Some((language.to_lowercase(), region))
+}
+
+/// Returns the weekday (Monday = 0) for a full or abbreviated name, in
+/// English or the current language.
+pub fn parse_weekday(text: &str) -> Option<u32> {
+    let text = fold(text);
+    if text.chars().count() < 2 {
+        return None;
+    }
+    let find = |names: &[String]| names.iter().position(|name| fold(name).starts_with(&text));
+    let english: Vec<String> = WEEKDAY_NAMES.iter().map(|n| n.to_string()).collect();
+    find(&english)
+        .or_else(|| find(&names("date-weekdays", &WEEKDAY_NAMES)))
+        .map(|i| i as u32)
+}
+
+/// The month or weekday names of the current language, or `english` if its
+/// list doesn't have the right number of names.
+fn names(id: &str, english: &[&str]) -> Vec<String> {
+    let names = i18n::tr_list(id);
+    if names.len() == english.len() {
+        names
+    } else {
+        english.iter().map(|n| n.to_string()).collect()
+    }
+}
+
+/// Lowercases a word and drops accents, so "miercoles" matches "miércoles".
+fn fold(word: &str) -> String {
+    word.to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ä' => 'a',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'í' | 'ì' | 'î' | 'ï' => 'i',
+            'ó' | 'ò' | 'ô' | 'ö' => 'o',
+            'ú' | 'ù' | 'û' | 'ü' => 'u',
+            'ñ' => 'n',
+            c => c,
+        })
+        .collect()
+}
+
+pub fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+/// How many holidays from `from` to `to`, both included, fall on weekdays,
+/// the days a business day could otherwise be.
+fn weekday_holidays(from: Date, to: Date) -> i64 {
+    crate::holiday::between(from, to)
+        .iter()
+        .filter(|(date, _)| date.weekday() < 5)
+        .count() as i64
+}
+
+pub fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::holiday::{self, Source};
+    use std::sync::Mutex;
+
+    /// Held by every test that counts business days, since the holiday
+    /// calendar is shared by the whole program.
+    static HOLIDAYS: Mutex<()> = Mutex::new(());
+
+    fn date(year: i32, month: u32, day: u32) -> Date {
+        Date::new(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn adds_business_days_over_weekends() {
+        let _guard = HOLIDAYS.lock().unwrap_or_else(|e| e.into_inner());
+        holiday::init(&[]).unwrap();
+        // Thursday 2026-10-15.
+        let thursday = date(2026, 10, 15);
+        assert_eq!(thursday.add_business_days(1), Ok(date(2026, 10, 16)));
+        assert_eq!(thursday.add_business_days(2), Ok(date(2026, 10, 19)));
+        assert_eq!(thursday.add_business_days(5), Ok(date(2026, 10, 22)));
+        assert_eq!(thursday.add_business_days(-4), Ok(date(2026, 10, 9)));
+        assert_eq!(thursday.add_business_days(0), Ok(thursday));
+        // From a weekend, +1 is the Monday and -1 the Friday.
+        assert_eq!(
+            date(2026, 10, 17).add_business_days(1),
+            Ok(date(2026, 10, 19))
+        );
+        assert_eq!(
+            date(2026, 10, 18).add_business_days(-1),
+            Ok(date(2026, 10, 16))
+        );
+        assert!(thursday.add_business_days(MAX_BUSINESS_DAYS + 1).is_err());
+        assert!(LAST.add_business_days(1).is_err());
+    }
+
+    #[test]
+    fn counts_business_days_over_weekends() {
+        let _guard = HOLIDAYS.lock().unwrap_or_else(|e| e.into_inner());
+        holiday::init(&[]).unwrap();
+        let thursday = date(2026, 10, 15);
+        assert_eq!(thursday.business_days_until(thursday), 0);
+        assert_eq!(thursday.business_days_until(date(2026, 10, 18)), 1);
+        assert_eq!(thursday.business_days_until(date(2026, 10, 22)), 5);
+        assert_eq!(date(2026, 10, 22).business_days_until(thursday), -5);
+        for days in [1, 3, 7, 30, 365, -1, -12, -400] {
+            let later = thursday.add_business_days(days).unwrap();
+            assert_eq!(thursday.business_days_until(later), days);
+        }
+    }
+
+    #[test]
+    fn skips_holidays() {
+        let _guard = HOLIDAYS.lock().unwrap_or_else(|e| e.into_inner());
+        holiday::init(&[Source::Country("us".to_string())]).unwrap();
+        // Christmas 2026 is a Friday, and New Year's Day 2027 the next.
+        let christmas_eve = date(2026, 12, 24);
+        let new_year = date(2027, 1, 1);
+        let result = (
+            christmas_eve.add_business_days(1),
+            date(2026, 12, 21).add_business_days(10),
+            date(2027, 1, 4).add_business_days(-1),
+            christmas_eve.business_days_until(new_year),
+            christmas_eve.business_days_until(date(2027, 1, 8)),
+        );
+        holiday::init(&[]).unwrap();
+        assert_eq!(result.0, Ok(date(2026, 12, 28)));
+        assert_eq!(result.1, Ok(date(2027, 1, 6)));
+        assert_eq!(result.2, Ok(date(2026, 12, 31)));
+        assert_eq!(result.3, 4);
+        assert_eq!(result.4, 9);
+        // Without them, the same days are business days.
+        assert_eq!(christmas_eve.add_business_days(1), Ok(date(2026, 12, 25)));
+        assert_eq!(christmas_eve.business_days_until(new_year), 6);
+    }
+}
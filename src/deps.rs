@@ -0,0 +1,74 @@
+// Dependencies between tasks, and the blocked state that follows from them.
+//
+// A task lists the ids of the tasks it depends on. It is blocked while any of
+// them is still pending. Nothing is stored for that, so a task unblocks by
+// itself the moment its last blocker is completed, cancelled or removed.
+
+use std::collections::HashSet;
+
+use crate::date::DateTime;
+use crate::task::Task;
+use crate::urgency::Urgency;
+
+/// The pending tasks in `tasks` that `task` depends on, with their indexes.
+pub fn blockers<'a>(task: &Task, tasks: &'a [Task]) -> Vec<(usize, &'a Task)> {
+    tasks
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| !t.completed && task.depends.contains(&t.id))
+        .collect()
+}
+
+/// Whether any task `task` depends on is still pending.
+pub fn is_blocked(task: &Task, tasks: &[Task]) -> bool {
+    !blockers(task, tasks).is_empty()
+}
+
+/// Whether making the task `id` depend on the task `on` would close a loop,
+/// because `on` already depends on `id`, directly or through other tasks.
+pub fn would_cycle(tasks: &[Task], id: u64, on: u64) -> bool {
+    let mut seen = HashSet::new();
+    let mut next = vec![on];
+    while let Some(current) = next.pop() {
+        if current == id {
+            return true;
+        }
+        if !seen.insert(current) {
+            continue;
+        }
+        if let Some(task) = tasks.iter().find(|t| t.id == current) {
+            next.extend(&task.depends);
+        }
+    }
+    false
+}
+
+/// The tasks that depended on the task `id` and, now that it is completed,
+/// aren't blocked by anything else.
+pub fn unblocked_by(id: u64, tasks: &[Task]) -> Vec<(usize, &Task)> {
+    tasks
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| !t.completed && t.depends.contains(&id) && !is_blocked(t, tasks))
+        .collect()
+}
+
+/// The tasks that can be worked on, best first: pending, not parked, waiting
+/// on no one and not blocked. The most urgent at `now` come first, then the
+/// list's own order.
+pub fn next<'a>(tasks: &'a [Task], urgency: &Urgency, now: DateTime) -> Vec<(usize, &'a Task)> {
+    let mut ready: Vec<(usize, &Task)> = tasks
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| {
+            !t.completed && t.someday.is_none() && t.waiting.is_none() && !is_blocked(t, tasks)
+        })
+        .collect();
+    ready.sort_by(|(a, x), (b, y)| {
+        urgency
+            .score(y, now)
+            .total_cmp(&urgency.score(x, now))
+            .then(a.cmp(b))
+    });
+    ready
+}
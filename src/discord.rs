@@ -0,0 +1,217 @@
+// Posting to Discord channels through their webhooks: an announcement when
+// a task is completed, and alerts for tasks coming due, for groups that
+// share a list and talk in Discord.
+//
+// Each list posts to its own channel, or not at all, as the config's
+// `[discord_webhooks]` table says, the main list under `main`:
+//
+//   [discord_webhooks]
+//   main = "https://discord.com/api/webhooks/..."
+//   club = "https://discord.com/api/webhooks/..."
+//
+// Completions are announced as they are saved, like webhooks. Tasks coming
+// due are posted by `discord`, each once for its due date, so it can run
+// from cron; what was posted is kept in a file per list.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::date::Date;
+use crate::error::{Error, Result};
+use crate::http::Client;
+use crate::i18n::tr;
+use crate::log;
+use crate::task::Task;
+
+/// The most characters Discord takes in one message.
+const MAX_LENGTH: usize = 2000;
+
+/// Posts `text` to the webhook at `url`, in as many messages as it takes,
+/// split between lines. Mentions in it don't ping anyone.
+pub fn post(client: &Client, url: &str, text: &str) -> io::Result<()> {
+    for content in split(text) {
+        let body = json!({
+            "content": content,
+            "allowed_mentions": { "parse": [] },
+        })
+        .to_string();
+        log::debug("discord", format_args!("posting {} bytes", body.len()));
+        let response = client.send_with_retries(
+            "POST",
+            url,
+            &[("Content-Type", "application/json")],
+            body.as_bytes(),
+        )?;
+        if !(200..300).contains(&response.status) {
+            return Err(io::Error::other(format!(
+                "{} {}",
+                response.status,
+                String::from_utf8_lossy(&response.body).trim()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// `text` in pieces Discord takes, breaking between lines where it can.
+fn split(text: &str) -> Vec<String> {
+    let mut pieces: Vec<String> = Vec::new();
+    let mut piece = String::new();
+    for line in text.lines() {
+        let mut line: String = line.to_string();
+        while !line.is_empty() {
+            let room = MAX_LENGTH - piece.chars().count() - usize::from(!piece.is_empty());
+            if line.chars().count() <= room {
+                if !piece.is_empty() {
+                    piece.push('\n');
+                }
+                piece.push_str(&line);
+                break;
+            }
+            if piece.is_empty() {
+                // A line too long for a message of its own is cut up.
+                let cut = line
+                    .char_indices()
+                    .nth(MAX_LENGTH)
+                    .map_or(line.len(), |(i, _)| i);
+                pieces.push(line[..cut].to_string());
+                line = line[cut..].to_string();
+            } else {
+                pieces.push(std::mem::take(&mut piece));
+            }
+        }
+    }
+    if !piece.is_empty() {
+        pieces.push(piece);
+    }
+    pieces
+}
+
+/// The announcement of the `done` tasks, with the person who completed them
+/// if known.
+pub fn completed(done: &[&Task], by: Option<&str>) -> Option<String> {
+    let lines: Vec<String> = done
+        .iter()
+        .map(|task| match by {
+            Some(by) => tr(
+                "discord-done-by",
+                &[("task", &escape(&task.text)), ("person", &escape(by))],
+            ),
+            None => tr("discord-done", &[("task", &escape(&task.text))]),
+        })
+        .collect();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Where `discord` remembers what it posted from `list`.
+pub fn posted_path(list: Option<&str>) -> PathBuf {
+    crate::daemon::list_file(&crate::config::config_dir().join("discord"), list)
+}
+
+/// The tasks coming due that were already posted, so none is posted twice.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Posted {
+    /// As (task ID, due date).
+    due: HashSet<(u64, Date)>,
+}
+
+impl Posted {
+    /// Reads what was posted from `path`; nothing was if it doesn't exist.
+    pub fn load(path: &Path) -> Result<Posted> {
+        match fs::read_to_string(path) {
+            Ok(data) => serde_json::from_str(&data).map_err(|e| Error::Parse {
+                location: path.display().to_string(),
+                line: e.line(),
+                column: e.column(),
+                message: e.to_string(),
+            }),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(Posted::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Writes what was posted to `path`, forgetting the tasks no longer
+    /// pending in `tasks`.
+    pub fn save(&mut self, path: &Path, tasks: &[Task]) -> Result<()> {
+        let pending: HashSet<u64> = tasks
+            .iter()
+            .filter(|task| !task.completed)
+            .map(|task| task.id)
+            .collect();
+        self.due.retain(|(id, _)| pending.contains(id));
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string(self).expect("Failed to serialize data to JSON.");
+        fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Returns the pending tasks due within `days` days of `today`, not
+    /// counting holidays, or overdue, that haven't been posted for that due date, and marks them
+    /// posted.
+    pub fn due_soon<'a>(
+        &mut self,
+        tasks: &'a [Task],
+        today: Date,
+        days: u32,
+    ) -> Vec<(usize, &'a Task)> {
+        tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| !task.completed && task.someday.is_none())
+            .filter(|(_, task)| {
+                task.due.is_some_and(|due| {
+                    crate::holiday::days_until(today, due) <= i64::from(days)
+                        && self.due.insert((task.id, due))
+                })
+            })
+            .collect()
+    }
+}
+
+/// One message with the tasks coming due, or `None` if there are none.
+pub fn due_soon(due: &[(usize, &Task)], today: Date, date_format: &str) -> Option<String> {
+    if due.is_empty() {
+        return None;
+    }
+    let mut lines = vec![tr("discord-due-title", &[("count", &due.len())])];
+    for (index, task) in due {
+        let Some(date) = task.due else {
+            continue;
+        };
+        let until = match today.days_until(date) {
+            0 if task.is_overdue(today) => -1,
+            until => until,
+        };
+        let date = task.due_label(date_format).unwrap_or_default();
+        let task = format!("{}. {}", index + 1, escape(&task.text));
+        lines.push(match until {
+            ..0 => tr("discord-due-overdue", &[("task", &task), ("date", &date)]),
+            0 => tr("discord-due-today", &[("task", &task)]),
+            _ => tr("discord-due-on", &[("task", &task), ("date", &date)]),
+        });
+    }
+    Some(lines.join("\n"))
+}
+
+/// `text` with the characters Discord's Markdown reads as markup escaped.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '*' | '_' | '~' | '`' | '|' | '>' | '#' | '[' | ']'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
@@ -0,0 +1,147 @@
+// The error type returned by library operations.
+//
+// Each kind of failure maps to its own process exit code, so scripts can tell
+// a typo in the arguments from a missing task or a disk problem.
+
+use std::fmt;
+use std::io;
+
+use crate::i18n::tr;
+
+/// Exit code for failures that don't fit a more specific code.
+pub const EXIT_FAILURE: i32 = 1;
+/// Exit code for bad arguments, values or settings.
+pub const EXIT_USAGE: i32 = 2;
+/// Exit code when the referenced task doesn't exist or isn't unique.
+pub const EXIT_NOT_FOUND: i32 = 3;
+/// Exit code when the task file or config file can't be read or written.
+pub const EXIT_STORAGE: i32 = 4;
+/// Exit code when the list changed underneath us and nothing was saved.
+pub const EXIT_CONFLICT: i32 = 5;
+
+/// Everything that can go wrong when working with a task store.
+#[derive(Debug)]
+pub enum Error {
+    /// A value given by the user was rejected, e.g. a bad date or an empty task.
+    Invalid(String),
+    /// The config file couldn't be parsed or holds a bad setting.
+    Config(String),
+    /// Any other failure, e.g. the editor crashed or confirmation was refused.
+    Failed(String),
+    /// An empty task reference.
+    MissingQuery,
+    /// A task number of zero; numbers start at 1.
+    InvalidTaskNumber,
+    /// No task has this number.
+    NoSuchNumber(usize),
+    /// The task shown with this number by the last listing has since been
+    /// removed.
+    StaleNumber(usize),
+    /// No task text matches this query.
+    NoMatch(String),
+    /// The query matched several tasks, listed as `(index, text)`.
+    Ambiguous {
+        query: String,
+        matches: Vec<(usize, String)>,
+    },
+    /// The stored list at this location was changed by someone else after it
+    /// was read, so saving would have overwritten their changes.
+    Conflict(String),
+    /// The list at this location was opened read-only, so nothing may be
+    /// written to it.
+    ReadOnly(String),
+    /// Changes couldn't be merged into the stored list because the task with
+    /// this text was changed or removed there too.
+    MergeFailed(String),
+    /// The stored list isn't valid: its text at `line` and `column` (both
+    /// counted from 1) doesn't parse.
+    Parse {
+        location: String,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// Reading or writing the store failed.
+    Io(io::Error),
+}
+
+impl Error {
+    /// The process exit code a command-line tool should use for this error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Invalid(_)
+            | Error::Config(_)
+            | Error::MissingQuery
+            | Error::InvalidTaskNumber => EXIT_USAGE,
+            Error::NoSuchNumber(_)
+            | Error::StaleNumber(_)
+            | Error::NoMatch(_)
+            | Error::Ambiguous { .. } => EXIT_NOT_FOUND,
+            Error::Io(_) | Error::Parse { .. } | Error::ReadOnly(_) => EXIT_STORAGE,
+            Error::Conflict(_) | Error::MergeFailed(_) => EXIT_CONFLICT,
+            Error::Failed(_) => EXIT_FAILURE,
+        }
+    }
+}
+
+/// A `Result` whose error is the library's `Error`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Invalid(msg) | Error::Config(msg) | Error::Failed(msg) => f.write_str(msg),
+            Error::MissingQuery => f.write_str(&tr("error-missing-query", &[])),
+            Error::InvalidTaskNumber => f.write_str(&tr("error-invalid-number", &[])),
+            Error::NoSuchNumber(number) => {
+                f.write_str(&tr("error-no-such-number", &[("number", number)]))
+            }
+            Error::StaleNumber(number) => {
+                f.write_str(&tr("error-stale-number", &[("number", number)]))
+            }
+            Error::NoMatch(query) => f.write_str(&tr("error-no-match", &[("query", query)])),
+            Error::Ambiguous { query, matches } => {
+                let count = matches.len();
+                let header = tr("error-ambiguous", &[("query", query), ("count", &count)]);
+                f.write_str(&header)?;
+                for (index, text) in matches {
+                    write!(f, "\n  {}. {}", index + 1, text)?;
+                }
+                write!(f, "\n{}", tr("error-ambiguous-hint", &[]))
+            }
+            Error::Conflict(path) => f.write_str(&tr("error-conflict", &[("path", path)])),
+            Error::ReadOnly(path) => f.write_str(&tr("error-read-only", &[("path", path)])),
+            Error::MergeFailed(task) => f.write_str(&tr("error-merge-failed", &[("task", task)])),
+            Error::Parse {
+                location,
+                line,
+                column,
+                message,
+            } => f.write_str(&tr(
+                "error-parse",
+                &[
+                    ("path", location),
+                    ("line", line),
+                    ("column", column),
+                    ("error", message),
+                ],
+            )),
+            Error::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
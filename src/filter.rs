@@ -0,0 +1,178 @@
+// Which tasks a command applies to.
+//
+// `list` shows, and `modify` changes, the tasks that pass every condition
+// given: words of their text, status, workflow status, tags, project,
+// contexts and owner. Text, tags, projects and contexts are compared folded (see `search`), so `--tag Café`
+// finds a task tagged `cafe`. `search` also looks for words (see
+// `fulltext`), or a regular expression, in the notes and comments.
+//
+// Tags in capitals are virtual: no task is given them, but a task has them
+// while it is in some state, worked out when the filter is used. Since real
+// tags are stored lowercase, `--tag OVERDUE` can't be mistaken for one.
+
+use std::collections::HashSet;
+
+use crate::date::{Date, DateTime};
+use crate::fulltext;
+use crate::query::Query;
+use crate::regex::Regex;
+use crate::search;
+use crate::task::{normalize_context, normalize_project, normalize_tag, Task};
+
+/// The conditions a task must meet; the default lets every task through
+/// except those parked as someday/maybe.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Filter {
+    /// Text the task's text must contain.
+    pub text: Option<String>,
+    /// Words that must each start a word of the task's text, notes or
+    /// comments.
+    pub words: Option<String>,
+    /// A regular expression that the task's text, notes or one of its
+    /// comments must match.
+    pub pattern: Option<Regex>,
+    /// Only completed tasks (`true`) or only pending ones (`false`).
+    pub completed: Option<bool>,
+    /// Only someday/maybe tasks, rather than only the others.
+    pub someday: bool,
+    /// Workflow statuses the task must be in one of, e.g. `doing`.
+    pub statuses: Vec<String>,
+    /// Tags the task must all have.
+    pub tags: Vec<String>,
+    pub project: Option<String>,
+    /// Contexts the task must all have.
+    pub contexts: Vec<String>,
+    /// The user who must have added the task.
+    pub owner: Option<String>,
+    /// The time the task must have been added before. Tasks added before
+    /// this was recorded never pass.
+    pub created_before: Option<DateTime>,
+    /// The ids of the pending tasks, which say whether a task is `BLOCKED`;
+    /// filled in by whoever reads the list.
+    pub pending: HashSet<u64>,
+    /// The expression of the active context, which the task must also
+    /// meet. Not a condition of its own: a filter with only this is empty.
+    pub scope: Option<Query>,
+}
+
+impl Filter {
+    /// Whether the filter would pass every task that isn't someday/maybe.
+    pub fn is_empty(&self) -> bool {
+        self.text.as_deref().is_none_or(|t| t.trim().is_empty())
+            && self.words.as_deref().is_none_or(|w| w.trim().is_empty())
+            && self.pattern.is_none()
+            && self.completed.is_none()
+            && self.statuses.is_empty()
+            && self.tags.is_empty()
+            && self.project.is_none()
+            && self.contexts.is_empty()
+            && self.owner.is_none()
+            && self.created_before.is_none()
+    }
+
+    /// Whether `task` meets every condition.
+    pub fn matches(&self, task: &Task) -> bool {
+        let same = |a: &str, b: &str| search::fold(a) == search::fold(b);
+        self.completed
+            .is_none_or(|completed| task.completed == completed)
+            && task.someday.is_some() == self.someday
+            && (self.statuses.is_empty()
+                || self
+                    .statuses
+                    .iter()
+                    .any(|s| s.trim().eq_ignore_ascii_case(task.status())))
+            && self
+                .text
+                .as_deref()
+                .is_none_or(|text| search::contains(&task.text, text.trim()))
+            && self
+                .words
+                .as_deref()
+                .is_none_or(|words| fulltext::matches(task, words))
+            && self
+                .pattern
+                .as_ref()
+                .is_none_or(|pattern| task.written().any(|written| pattern.is_match(written)))
+            && self
+                .scope
+                .as_ref()
+                .is_none_or(|scope| scope.matches(task, Date::today(), &self.pending))
+            && self.tags.iter().all(|tag| match VirtualTag::parse(tag) {
+                Some(tag) => tag.applies(task, Date::today(), &self.pending),
+                None => {
+                    let tag = normalize_tag(tag);
+                    task.tags.iter().any(|t| same(t, &tag))
+                }
+            })
+            && self.project.as_deref().is_none_or(|project| {
+                let project = normalize_project(project);
+                task.project.as_deref().is_some_and(|p| same(p, &project))
+            })
+            && self.contexts.iter().all(|context| {
+                let context = normalize_context(context);
+                task.contexts.iter().any(|c| same(c, &context))
+            })
+            && self
+                .owner
+                .as_deref()
+                .is_none_or(|owner| task.owner.as_deref() == Some(owner))
+            && self
+                .created_before
+                .is_none_or(|before| task.created_at.is_some_and(|at| at < before))
+    }
+}
+
+/// A tag a task has by what it is at the moment, rather than by being given
+/// it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VirtualTag {
+    /// Pending and due before today.
+    Overdue,
+    /// Pending and due today.
+    Today,
+    /// Pending and due from today to Sunday.
+    Week,
+    /// Pending and waiting for a task that is pending too.
+    Blocked,
+    /// Marked with `pin`.
+    Pinned,
+    /// Pending and handed to someone else.
+    Waiting,
+}
+
+impl VirtualTag {
+    /// Every virtual tag, as it is written.
+    pub const NAMES: &'static [&'static str] =
+        &["OVERDUE", "TODAY", "WEEK", "BLOCKED", "PINNED", "WAITING"];
+
+    /// The virtual tag `tag` stands for, if it is one: its name in
+    /// capitals, with or without a `#`.
+    pub fn parse(tag: &str) -> Option<VirtualTag> {
+        match tag.trim().trim_start_matches('#') {
+            "OVERDUE" => Some(VirtualTag::Overdue),
+            "TODAY" => Some(VirtualTag::Today),
+            "WEEK" => Some(VirtualTag::Week),
+            "BLOCKED" => Some(VirtualTag::Blocked),
+            "PINNED" => Some(VirtualTag::Pinned),
+            "WAITING" => Some(VirtualTag::Waiting),
+            _ => None,
+        }
+    }
+
+    /// Whether `task` has the tag on `today`, with `pending` the ids of the
+    /// pending tasks.
+    pub fn applies(self, task: &Task, today: Date, pending: &HashSet<u64>) -> bool {
+        let last = today.start_of_week().add_days(6);
+        let due = task.due.filter(|_| !task.completed);
+        match self {
+            VirtualTag::Overdue => !task.completed && task.is_overdue(today),
+            VirtualTag::Today => due == Some(today),
+            VirtualTag::Week => due.is_some_and(|due| today <= due && due <= last),
+            VirtualTag::Blocked => {
+                !task.completed && task.depends.iter().any(|id| pending.contains(id))
+            }
+            VirtualTag::Pinned => task.pinned,
+            VirtualTag::Waiting => !task.completed && task.waiting.is_some(),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+// Finding tasks by the words written on them, best matches first.
+//
+// `search` splits what is written on each task (its text, notes and
+// comments) into words, folded as for the rest of the search, and indexes
+// them by word. Each word of the query is looked up as the start of a word,
+// so `inv` finds "invoice" and "Invoices", and a task must have all of them.
+// The tasks found are ranked with BM25, the formula most search engines
+// start from: a word counts for more the fewer tasks have it and the more
+// often it comes up in a task, with diminishing returns and less weight in
+// long notes. Words in the task's own text count double, and a word written
+// out in full beats one that only starts with it.
+//
+// The index is built as the list is read, one task at a time, so a search
+// costs one pass over the list however many words it has. Words are kept
+// sorted, so the words a prefix covers are found by a range lookup instead
+// of going through every word of every task.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::search;
+use crate::task::Task;
+
+/// How quickly more of the same word stops adding to a task's score.
+const K1: f64 = 1.2;
+/// How much a long task's score is held back for its length.
+const B: f64 = 0.75;
+/// How much more a word counts in the task's text than in its notes.
+const TEXT_WEIGHT: f64 = 2.0;
+/// How much a word counts when the query only gives its start.
+const PREFIX_WEIGHT: f64 = 0.7;
+
+/// The words of `text`, folded: runs of letters and digits.
+pub fn words(text: &str) -> Vec<String> {
+    search::fold(text)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Whether every word of `query` starts a word written on `task`.
+pub fn matches(task: &Task, query: &str) -> bool {
+    let written: Vec<String> = task.written().flat_map(words).collect();
+    words(query)
+        .iter()
+        .all(|word| written.iter().any(|w| w.starts_with(word.as_str())))
+}
+
+/// The words written on a set of tasks, and where.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Index {
+    /// For each word, the tasks it is written on and how often, weighted
+    /// for where.
+    postings: BTreeMap<String, Vec<(usize, f64)>>,
+    /// How many words are written on each task, weighted the same way.
+    lengths: Vec<f64>,
+}
+
+impl Index {
+    pub fn new() -> Index {
+        Index::default()
+    }
+
+    /// Indexes `task`'s words under the next number, from 0.
+    pub fn add(&mut self, task: &Task) -> usize {
+        let doc = self.lengths.len();
+        let mut counts: HashMap<String, f64> = HashMap::new();
+        for (i, text) in task.written().enumerate() {
+            let weight = if i == 0 { TEXT_WEIGHT } else { 1.0 };
+            for word in words(text) {
+                *counts.entry(word).or_default() += weight;
+            }
+        }
+        self.lengths.push(counts.values().sum());
+        for (word, count) in counts {
+            self.postings.entry(word).or_default().push((doc, count));
+        }
+        doc
+    }
+
+    /// The tasks that have every word of `query`, as their numbers in the
+    /// order they were added, with their scores, best first.
+    pub fn search(&self, query: &str) -> Vec<(usize, f64)> {
+        let docs = self.lengths.len();
+        if docs == 0 {
+            return Vec::new();
+        }
+        let average = self.lengths.iter().sum::<f64>() / docs as f64;
+        let mut scores: HashMap<usize, (usize, f64)> = HashMap::new();
+        let query = words(query);
+        for (i, word) in query.iter().enumerate() {
+            // The best this query word does in each task, from any of the
+            // words it starts.
+            let mut best: HashMap<usize, f64> = HashMap::new();
+            for (term, postings) in self
+                .postings
+                .range(word.clone()..)
+                .take_while(|(term, _)| term.starts_with(word.as_str()))
+            {
+                let exact = if term == word { 1.0 } else { PREFIX_WEIGHT };
+                for &(doc, count) in postings {
+                    let length = 1.0 - B + B * self.lengths[doc] / average.max(1.0);
+                    let score = exact * count * (K1 + 1.0) / (count + K1 * length);
+                    let entry = best.entry(doc).or_default();
+                    *entry = entry.max(score);
+                }
+            }
+            // How rare the query word is, counting every word it starts as it.
+            let found = best.len() as f64;
+            let idf = (1.0 + (docs as f64 - found + 0.5) / (found + 0.5)).ln();
+            for (doc, score) in best {
+                let score = idf * score;
+                let entry = scores.entry(doc).or_default();
+                // Only tasks that had every earlier word are still in the
+                // running.
+                if entry.0 == i {
+                    *entry = (i + 1, entry.1 + score);
+                }
+            }
+        }
+        let mut found: Vec<(usize, f64)> = scores
+            .into_iter()
+            .filter(|(_, (words, _))| *words == query.len())
+            .map(|(doc, (_, score))| (doc, score))
+            .collect();
+        found.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        found
+    }
+}
@@ -0,0 +1,150 @@
+// Habits: tasks that are never finished, only done again and again on a
+// schedule, e.g. daily, every 3 days, or every weekday. A schedule in
+// business days skips weekends: a weekday habit done on Friday is next
+// expected on Monday, and a weekend doesn't break its streak.
+//
+// Completing a habit records the day instead of completing the task, and
+// moves its due date to when it is next expected, so a missed day shows up
+// as overdue like any other task. Streaks are worked out from the days it
+// was done: each one counts if it came no later than the schedule allows
+// after the one before.
+
+use serde::{Deserialize, Serialize};
+
+use crate::date::{Date, MAX_BUSINESS_DAYS};
+use crate::i18n::tr;
+
+/// How often a habit is expected, and the days it was done.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Habit {
+    /// Days between one time and the next: 1 for daily, 7 for weekly.
+    pub every: u32,
+    /// Whether `every` counts business days rather than all days.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub business_days: bool,
+    /// The days the habit was done, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub done: Vec<Date>,
+}
+
+impl Habit {
+    /// A habit expected every `every` days, or business days, never done
+    /// yet.
+    pub fn new(every: u32, business_days: bool) -> Habit {
+        Habit {
+            every,
+            business_days,
+            done: Vec::new(),
+        }
+    }
+
+    /// Reads a schedule: `daily`, `weekly`, `weekdays`, or a number of days,
+    /// weeks or business days such as `3d`, `2w` or `2bd`. Returns the
+    /// number, and whether it counts business days.
+    pub fn parse_every(input: &str) -> Result<(u32, bool), String> {
+        let input = input.trim().to_lowercase();
+        let every = match input.as_str() {
+            "daily" | "day" => Some((1, false)),
+            "weekly" | "week" => Some((7, false)),
+            "weekdays" | "weekday" | "every weekday" | "business days" => Some((1, true)),
+            _ => {
+                let (count, unit) = input.split_at(input.trim_end_matches(['b', 'd', 'w']).len());
+                match (count.parse::<u32>(), unit) {
+                    (Ok(n), "" | "d") => Some((n, false)),
+                    (Ok(n), "w") => n.checked_mul(7).map(|days| (days, false)),
+                    (Ok(n), "bd") if i64::from(n) <= MAX_BUSINESS_DAYS => Some((n, true)),
+                    _ => None,
+                }
+            }
+        };
+        every
+            .filter(|&(every, _)| every > 0)
+            .ok_or_else(|| tr("habit-every-invalid", &[("input", &input)]))
+    }
+
+    /// The schedule as it is shown, e.g. `daily` or `every 3 days`.
+    pub fn schedule(&self) -> String {
+        match (self.every, self.business_days) {
+            (1, true) => tr("habit-weekdays", &[]),
+            (days, true) => tr("habit-every-business", &[("days", &days)]),
+            (1, false) => tr("habit-daily", &[]),
+            (7, false) => tr("habit-weekly", &[]),
+            (days, false) => tr("habit-every", &[("days", &days)]),
+        }
+    }
+
+    /// How many days, or business days, from `from` until `to`.
+    fn gap(&self, from: Date, to: Date) -> i64 {
+        match self.business_days {
+            true => from.business_days_until(to),
+            false => from.days_until(to),
+        }
+    }
+
+    /// The last day the habit was done.
+    pub fn last(&self) -> Option<Date> {
+        self.done.last().copied()
+    }
+
+    /// Records that the habit was done `today`. Returns `false` if it already
+    /// was.
+    pub fn check(&mut self, today: Date) -> bool {
+        if self.done.contains(&today) {
+            return false;
+        }
+        self.done.push(today);
+        self.done.sort();
+        true
+    }
+
+    /// When the habit is next expected: a period after it was last done, or
+    /// `today` if it never was (the next business day, if it counts them).
+    pub fn next_due(&self, today: Date) -> Date {
+        match self.last() {
+            Some(last) if self.business_days => last
+                .add_business_days(i64::from(self.every))
+                .unwrap_or_else(|_| last.add_days(i64::from(self.every))),
+            Some(last) => last.add_days(i64::from(self.every)),
+            None if self.business_days && !today.is_business_day() => {
+                today.add_business_days(1).unwrap_or(today)
+            }
+            None => today,
+        }
+    }
+
+    /// Whether the habit has been done for the current period, so nothing is
+    /// expected of it `today`.
+    pub fn is_done_for(&self, today: Date) -> bool {
+        self.last()
+            .is_some_and(|last| self.gap(last, today) < i64::from(self.every))
+    }
+
+    /// How many times in a row the habit has been done, up to `today`. The
+    /// streak is kept until the habit is overdue.
+    pub fn streak(&self, today: Date) -> usize {
+        match (self.runs().last(), self.last()) {
+            (Some(&run), Some(last)) if self.gap(last, today) <= i64::from(self.every) => run,
+            _ => 0,
+        }
+    }
+
+    /// The longest streak there has ever been.
+    pub fn best(&self) -> usize {
+        self.runs().into_iter().max().unwrap_or(0)
+    }
+
+    /// How long the streak was at each day the habit was done.
+    fn runs(&self) -> Vec<usize> {
+        let mut runs: Vec<usize> = Vec::new();
+        for (i, day) in self.done.iter().enumerate() {
+            let kept = i > 0 && self.gap(self.done[i - 1], *day) <= i64::from(self.every);
+            let run = if kept { runs[i - 1] + 1 } else { 1 };
+            runs.push(run);
+        }
+        runs
+    }
+}
+
+fn is_false(flag: &bool) -> bool {
+    !flag
+}
@@ -0,0 +1,150 @@
+// The completion heatmap: the days of the past year in a grid, one column per
+// week and one row per weekday, each shaded by how many tasks were completed
+// that day, like the contribution graph on a GitHub profile.
+//
+// Completions are counted from two places: the audit log, which remembers
+// tasks that have since been removed, and the `completed_at` time of tasks
+// still on the list, which covers tasks completed while nothing was logged.
+// Each task counts once, on the day it was last completed. Cancelled tasks
+// don't count.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::audit::Entry;
+use crate::date::{Date, DateTime};
+use crate::task::{Task, CANCELLED};
+
+/// The shades for no completions, then for a few up to the most in a day.
+const SHADES: [char; 5] = ['·', '░', '▒', '▓', '█'];
+
+/// The same shades as 256-color codes, from grey to bright green.
+const COLORS: [u8; 5] = [238, 22, 28, 34, 40];
+
+/// How wide the weekday names at the start of each row are.
+const LABEL_WIDTH: usize = 4;
+
+/// How many tasks were completed on each day.
+pub fn completions(tasks: &[Task], entries: &[Entry]) -> BTreeMap<Date, usize> {
+    let mut completed: HashMap<u64, Date> = HashMap::new();
+    for entry in entries {
+        let change = entry.fields.iter().find(|f| f.field == "completed");
+        let cancelled = entry
+            .fields
+            .iter()
+            .any(|f| f.field == "status" && f.new.as_deref() == Some(CANCELLED));
+        match change.and_then(|f| f.new.as_deref()) {
+            Some("true") if !cancelled => {
+                let day = DateTime::from_secs(entry.time as i64).date();
+                completed.insert(entry.id, day);
+            }
+            Some(_) => {
+                completed.remove(&entry.id);
+            }
+            None => {}
+        }
+    }
+    for task in tasks {
+        if task.is_cancelled() {
+            completed.remove(&task.id);
+        } else if let Some(at) = task.completed_at.filter(|_| task.completed) {
+            completed.insert(task.id, at.date());
+        }
+    }
+    let mut days = BTreeMap::new();
+    for day in completed.into_values() {
+        *days.entry(day).or_insert(0) += 1;
+    }
+    days
+}
+
+/// The first day shown in a grid of `weeks` weeks ending with `today`'s:
+/// the first day of a week.
+pub fn start(today: Date, weeks: usize) -> Date {
+    let weeks = weeks.max(1) as i64;
+    today.start_of_week().add_days(-7 * (weeks - 1))
+}
+
+/// Draws the grid for the `weeks` weeks up to `today`, with month names above
+/// the weeks they begin in.
+pub fn render(days: &BTreeMap<Date, usize>, today: Date, weeks: usize, color: bool) -> String {
+    let start = start(today, weeks);
+    let most = days
+        .range(start..=today)
+        .map(|(_, &n)| n)
+        .max()
+        .unwrap_or(0);
+
+    let mut out = " ".repeat(LABEL_WIDTH);
+    for week in 0..weeks.max(1) {
+        let week_start = start.add_days(7 * week as i64);
+        let first = (0..7)
+            .map(|d| week_start.add_days(d))
+            .find(|d| d.day() == 1 && *d <= today);
+        // Skip a name that would run into the one before it.
+        if let Some(first) = first.filter(|_| out.chars().count() <= LABEL_WIDTH + week) {
+            let padding = LABEL_WIDTH + week - out.chars().count();
+            out.push_str(&" ".repeat(padding));
+            out.push_str(&first.format("%b"));
+        }
+    }
+    out = out.trim_end().to_string();
+    out.push('\n');
+
+    for weekday in 0..7 {
+        // Label every other row, as there is no room for all of them.
+        let label = match weekday % 2 {
+            0 => start.add_days(weekday).format("%a"),
+            _ => String::new(),
+        };
+        out.push_str(&format!("{:width$}", label, width = LABEL_WIDTH));
+        for week in 0..weeks.max(1) {
+            let day = start.add_days(7 * week as i64 + weekday);
+            if day > today {
+                break;
+            }
+            let level = level(days.get(&day).copied().unwrap_or(0), most);
+            out.push_str(&shade(level, color));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// The weeks of the grid with any completions, by their first day, with how
+/// many there were: the grid in words, for plain output.
+pub fn weeks(days: &BTreeMap<Date, usize>, today: Date, weeks: usize) -> Vec<(Date, usize)> {
+    let start = start(today, weeks);
+    (0..weeks.max(1))
+        .map(|week| start.add_days(7 * week as i64))
+        .map(|first| {
+            let count = days
+                .range(first..=first.add_days(6).min(today))
+                .map(|(_, &n)| n)
+                .sum();
+            (first, count)
+        })
+        .filter(|&(_, count)| count > 0)
+        .collect()
+}
+
+/// The shades from fewest to most completions, for a legend.
+pub fn legend(color: bool) -> String {
+    (0..SHADES.len()).map(|level| shade(level, color)).collect()
+}
+
+/// Which shade `count` gets when the busiest day had `most`: 0 for none, then
+/// 1 to 4 by quarters of `most`.
+fn level(count: usize, most: usize) -> usize {
+    if count == 0 || most == 0 {
+        return 0;
+    }
+    (count * 4).div_ceil(most).clamp(1, 4)
+}
+
+fn shade(level: usize, color: bool) -> String {
+    if color {
+        format!("\x1b[38;5;{}m{}\x1b[0m", COLORS[level], SHADES[level])
+    } else {
+        SHADES[level].to_string()
+    }
+}
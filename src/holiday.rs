@@ -0,0 +1,318 @@
+// Public holidays, so that business days skip them as they skip weekends:
+// `+3bd` due dates, habits kept on weekdays, and how many days ahead
+// `daemon` and `discord` warn of tasks coming due.
+//
+// The `holidays` setting names where they come from, separated by commas:
+// a country, whose holidays are worked out for any year, or an iCalendar
+// file of them, such as a calendar app exports. The countries known are:
+//
+//   us   federal holidays, moved to the Friday or Monday when they fall on
+//        a weekend, as they are observed
+//   gb   bank holidays in England and Wales, with substitute days
+//   de   Germany's nationwide holidays
+//   fr   France's public holidays
+//   es   Spain's nationwide holidays
+//
+// Regional holidays are left out; a file has them, e.g. `de,~/bayern.ics`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::date::{days_in_month, Date, FIRST, LAST};
+use crate::error::{Error, Result};
+use crate::i18n::tr;
+use crate::ics::{self, Event};
+
+/// The countries with holidays built in, by code.
+pub const COUNTRIES: &[&str] = &["us", "gb", "de", "fr", "es"];
+
+/// The calendar chosen with `init`, or `None` for no holidays.
+static CALENDAR: Mutex<Option<Calendar>> = Mutex::new(None);
+
+/// Where holidays come from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Source {
+    /// A country in `COUNTRIES`.
+    Country(String),
+    /// An iCalendar file; each of its events is a holiday.
+    File(PathBuf),
+}
+
+impl Source {
+    /// Reads a source as the setting names it: a country's code, or the
+    /// path of an `.ics` file.
+    pub fn parse(value: &str) -> Option<Source> {
+        let code = value.to_ascii_lowercase();
+        if COUNTRIES.contains(&code.as_str()) {
+            Some(Source::Country(code))
+        } else if value.contains('/') || code.ends_with(".ics") {
+            Some(Source::File(PathBuf::from(value)))
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::Country(code) => f.write_str(code),
+            Source::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// The holidays of some countries and files, worked out a year at a time.
+#[derive(Debug, Default)]
+pub struct Calendar {
+    countries: Vec<String>,
+    events: Vec<Event>,
+    /// The holidays of each year looked at so far, with their names.
+    years: HashMap<i32, BTreeMap<Date, String>>,
+}
+
+impl Calendar {
+    /// The calendar of `sources`, reading the files among them.
+    pub fn load(sources: &[Source]) -> Result<Calendar> {
+        let mut calendar = Calendar::default();
+        for source in sources {
+            match source {
+                Source::Country(code) => calendar.countries.push(code.clone()),
+                Source::File(path) => {
+                    let text = fs::read_to_string(path).map_err(|e| {
+                        Error::Failed(tr(
+                            "holidays-unreadable",
+                            &[("path", &path.display()), ("error", &e)],
+                        ))
+                    })?;
+                    calendar.events.extend(ics::parse(&text));
+                }
+            }
+        }
+        Ok(calendar)
+    }
+
+    /// The holidays of `year`, by date; none outside the years 1 to 9999.
+    fn year(&mut self, year: i32) -> &BTreeMap<Date, String> {
+        let (countries, events) = (&self.countries, &self.events);
+        self.years.entry(year).or_insert_with(|| {
+            let mut holidays = BTreeMap::new();
+            if !(FIRST.year()..=LAST.year()).contains(&year) {
+                return holidays;
+            }
+            let (first, last) = (
+                Date::new(year, 1, 1).expect("valid date"),
+                Date::new(year, 12, 31).expect("valid date"),
+            );
+            for (date, event) in ics::between(events, first, last) {
+                holidays
+                    .entry(date)
+                    .or_insert_with(|| event.summary.clone());
+            }
+            for code in countries {
+                // A holiday moved to the day it is observed may cross into
+                // the year before, like a New Year's Day on a Saturday.
+                let near = (year - 1).max(FIRST.year())..=(year + 1).min(LAST.year());
+                for near in near {
+                    for (date, name) in country(code, near) {
+                        if date.year() == year {
+                            holidays.entry(date).or_insert(name);
+                        }
+                    }
+                }
+            }
+            holidays
+        })
+    }
+}
+
+/// Chooses the holidays from `sources`; none if it is empty.
+pub fn init(sources: &[Source]) -> Result<()> {
+    let calendar = (!sources.is_empty())
+        .then(|| Calendar::load(sources))
+        .transpose()?;
+    *CALENDAR.lock().unwrap_or_else(|e| e.into_inner()) = calendar;
+    Ok(())
+}
+
+/// The name of the holiday on `date`, if it is one.
+pub fn name(date: Date) -> Option<String> {
+    let mut calendar = CALENDAR.lock().unwrap_or_else(|e| e.into_inner());
+    calendar.as_mut()?.year(date.year()).get(&date).cloned()
+}
+
+/// Whether `date` is a holiday.
+pub fn is_holiday(date: Date) -> bool {
+    let mut calendar = CALENDAR.lock().unwrap_or_else(|e| e.into_inner());
+    calendar
+        .as_mut()
+        .is_some_and(|calendar| calendar.year(date.year()).contains_key(&date))
+}
+
+/// The holidays from `from` to `to`, both included, in order.
+pub fn between(from: Date, to: Date) -> Vec<(Date, String)> {
+    let mut calendar = CALENDAR.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(calendar) = calendar.as_mut() else {
+        return Vec::new();
+    };
+    let mut found = Vec::new();
+    for year in from.year()..=to.year() {
+        found.extend(
+            calendar
+                .year(year)
+                .range(from..=to)
+                .map(|(date, name)| (*date, name.clone())),
+        );
+    }
+    found
+}
+
+/// How many days from `from` until `to`, like [`Date::days_until`], but not
+/// counting the holidays in between: a task due the day after a holiday is
+/// a day away the day before it.
+pub fn days_until(from: Date, to: Date) -> i64 {
+    let days = from.days_until(to);
+    if days < 2 {
+        return days;
+    }
+    days - between(from.add_days(1), to.add_days(-1)).len() as i64
+}
+
+/// The holidays of the country with `code` in `year`, unordered.
+fn country(code: &str, year: i32) -> Vec<(Date, String)> {
+    let date = |month, day| Date::new(year, month, day).expect("valid date");
+    let easter = easter(year);
+    let named = |list: Vec<(Date, &str)>| -> Vec<(Date, String)> {
+        list.into_iter()
+            .map(|(date, name)| (date, name.to_string()))
+            .collect()
+    };
+    match code {
+        "us" => {
+            let mut fixed = vec![
+                (date(1, 1), "New Year's Day"),
+                (date(7, 4), "Independence Day"),
+                (date(11, 11), "Veterans Day"),
+                (date(12, 25), "Christmas Day"),
+            ];
+            if year >= 2021 {
+                fixed.push((date(6, 19), "Juneteenth"));
+            }
+            // Saturdays are observed the Friday before, Sundays the Monday after.
+            let mut holidays: Vec<(Date, String)> = fixed
+                .into_iter()
+                .map(|(date, name)| match date.weekday() {
+                    5 => (date.add_days(-1), format!("{} (observed)", name)),
+                    6 => (date.add_days(1), format!("{} (observed)", name)),
+                    _ => (date, name.to_string()),
+                })
+                .collect();
+            holidays.extend(named(vec![
+                (nth_weekday(year, 1, 0, 3), "Martin Luther King Jr. Day"),
+                (nth_weekday(year, 2, 0, 3), "Washington's Birthday"),
+                (nth_weekday(year, 5, 0, -1), "Memorial Day"),
+                (nth_weekday(year, 9, 0, 1), "Labor Day"),
+                (nth_weekday(year, 10, 0, 2), "Columbus Day"),
+                (nth_weekday(year, 11, 3, 4), "Thanksgiving Day"),
+            ]));
+            holidays
+        }
+        "gb" => {
+            let mut holidays = named(vec![
+                (easter.add_days(-2), "Good Friday"),
+                (easter.add_days(1), "Easter Monday"),
+                (nth_weekday(year, 5, 0, 1), "Early May bank holiday"),
+                (nth_weekday(year, 5, 0, -1), "Spring bank holiday"),
+                (nth_weekday(year, 8, 0, -1), "Summer bank holiday"),
+            ]);
+            let fixed = [
+                (date(1, 1), "New Year's Day"),
+                (date(12, 25), "Christmas Day"),
+                (date(12, 26), "Boxing Day"),
+            ];
+            let (weekend, weekdays): (Vec<_>, Vec<_>) =
+                fixed.into_iter().partition(|(date, _)| date.weekday() >= 5);
+            holidays.extend(named(weekdays));
+            // One on a weekend is made up for on the next weekday that
+            // isn't a holiday already.
+            for (date, name) in weekend {
+                let mut day = date;
+                while day.weekday() >= 5 || holidays.iter().any(|(taken, _)| *taken == day) {
+                    day = day.add_days(1);
+                }
+                holidays.push((day, format!("{} (substitute day)", name)));
+            }
+            holidays
+        }
+        "de" => named(vec![
+            (date(1, 1), "New Year's Day"),
+            (easter.add_days(-2), "Good Friday"),
+            (easter.add_days(1), "Easter Monday"),
+            (date(5, 1), "Labour Day"),
+            (easter.add_days(39), "Ascension Day"),
+            (easter.add_days(50), "Whit Monday"),
+            (date(10, 3), "German Unity Day"),
+            (date(12, 25), "Christmas Day"),
+            (date(12, 26), "Second Day of Christmas"),
+        ]),
+        "fr" => named(vec![
+            (date(1, 1), "New Year's Day"),
+            (easter.add_days(1), "Easter Monday"),
+            (date(5, 1), "Labour Day"),
+            (date(5, 8), "Victory in Europe Day"),
+            (easter.add_days(39), "Ascension Day"),
+            (easter.add_days(50), "Whit Monday"),
+            (date(7, 14), "Bastille Day"),
+            (date(8, 15), "Assumption Day"),
+            (date(11, 1), "All Saints' Day"),
+            (date(11, 11), "Armistice Day"),
+            (date(12, 25), "Christmas Day"),
+        ]),
+        "es" => named(vec![
+            (date(1, 1), "New Year's Day"),
+            (date(1, 6), "Epiphany"),
+            (easter.add_days(-2), "Good Friday"),
+            (date(5, 1), "Labour Day"),
+            (date(8, 15), "Assumption Day"),
+            (date(10, 12), "National Day"),
+            (date(11, 1), "All Saints' Day"),
+            (date(12, 6), "Constitution Day"),
+            (date(12, 8), "Immaculate Conception"),
+            (date(12, 25), "Christmas Day"),
+        ]),
+        _ => Vec::new(),
+    }
+}
+
+/// Easter Sunday in `year`, by the Gregorian calendar.
+fn easter(year: i32) -> Date {
+    let a = year % 19;
+    let (b, c) = (year / 100, year % 100);
+    let (d, e) = (b / 4, b % 4);
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let (i, k) = (c / 4, c % 4);
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    Date::new(year, month as u32, day as u32).expect("valid date")
+}
+
+/// The `n`th `weekday` (Monday as 0) of a month, counting from the end when
+/// `n` is negative: -1 is the last.
+fn nth_weekday(year: i32, month: u32, weekday: u32, n: i32) -> Date {
+    if n > 0 {
+        let first = Date::new(year, month, 1).expect("valid date");
+        let offset = (weekday + 7 - first.weekday()) % 7;
+        first.add_days(i64::from(offset) + 7 * i64::from(n - 1))
+    } else {
+        let last = Date::new(year, month, days_in_month(year, month)).expect("valid date");
+        let offset = (last.weekday() + 7 - weekday) % 7;
+        last.add_days(-i64::from(offset) - 7 * i64::from(-n - 1))
+    }
+}
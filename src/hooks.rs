@@ -0,0 +1,111 @@
+// User hook scripts, run after the list changes.
+//
+// A hook is an executable file in the hooks directory named after the event it
+// handles: `on-add`, `on-done`, `on-delete` or `on-save`. It receives the
+// affected task as JSON on stdin (the whole list for `on-save`), plus a few
+// `TODO_*` environment variables. `TODO_VIA` credits the changes a hook makes
+// by running `todo_cli` to the hook in the audit log. Hooks run after the
+// change has been saved, so they can't block it; a failing hook is reported
+// but nothing is undone.
+//
+// Whatever a hook prints goes to stderr, so it can't get mixed into output
+// that other programs parse, such as the `mcp` protocol stream.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+
+use crate::log;
+
+/// Something that happened to the list and that hooks can react to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// A task was added.
+    Add,
+    /// A task was marked as completed.
+    Done,
+    /// A task was removed, by `delete` or `clear`.
+    Delete,
+    /// The list was written to disk. Runs once per command, after the others.
+    Save,
+}
+
+impl Event {
+    /// The file name of the hook that handles this event.
+    pub fn hook_name(self) -> &'static str {
+        match self {
+            Event::Add => "on-add",
+            Event::Done => "on-done",
+            Event::Delete => "on-delete",
+            Event::Save => "on-save",
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Event::Add => "add",
+            Event::Done => "done",
+            Event::Delete => "delete",
+            Event::Save => "save",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Returns the hook for `event` in `dir`, if one is installed and executable.
+pub fn find(dir: &Path, event: Event) -> Option<PathBuf> {
+    let path = dir.join(event.hook_name());
+    is_executable(&path).then_some(path)
+}
+
+/// Runs the hook for `event`, passing `input` on stdin.
+///
+/// `store` is where the changed list is kept (a file path, or a URL for
+/// `remote` storage); hooks see it as `TODO_FILE`.
+/// Returns `Ok(None)` when no hook is installed for the event.
+pub fn run(dir: &Path, event: Event, input: &str, store: &str) -> io::Result<Option<ExitStatus>> {
+    let Some(hook) = find(dir, event) else {
+        log::trace(
+            "hooks",
+            format_args!("no {} hook in {}", event.hook_name(), dir.display()),
+        );
+        return Ok(None);
+    };
+    log::info("hooks", format_args!("running {}", hook.display()));
+    let mut child = Command::new(&hook)
+        .env("TODO_EVENT", event.to_string())
+        .env("TODO_FILE", store)
+        .env("TODO_VIA", format!("hook:{}", event.hook_name()))
+        .stdin(Stdio::piped())
+        .stdout(io::stderr())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that ignores its input may exit before reading it; that's fine.
+        match stdin.write_all(input.as_bytes()) {
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(e),
+            _ => {}
+        }
+    }
+    let status = child.wait()?;
+    log::debug(
+        "hooks",
+        format_args!("{} exited with {}", hook.display(), status),
+    );
+    Ok(Some(status))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
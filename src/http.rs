@@ -0,0 +1,609 @@
+// Just enough HTTP/1.1 for the built-in API server, and a client for
+// webhooks and remote storage.
+//
+// One request is made per connection and the connection is closed after the
+// response, so there is no keep-alive to deal with. Request bodies must come
+// with a `Content-Length`.
+//
+// Outgoing `https://` requests are handed to the `curl` program, since we
+// have no TLS implementation of our own.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+use crate::log;
+
+/// The largest request head (request line plus headers) we accept.
+const MAX_HEAD: usize = 64 * 1024;
+/// The largest request body we accept.
+const MAX_BODY: usize = 1024 * 1024;
+/// The largest response, head and body, we read from a server.
+const MAX_RESPONSE: usize = 64 * 1024 * 1024;
+
+/// A parsed HTTP request.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Request {
+    pub method: String,
+    /// The path without the query string, percent-decoded segment by segment.
+    pub path: Vec<String>,
+    pub query: BTreeMap<String, String>,
+    /// Header names are lowercase.
+    pub headers: BTreeMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Reads one request from a connection.
+    pub fn read(stream: impl Read) -> io::Result<Request> {
+        Request::read_up_to(stream, MAX_BODY)
+    }
+
+    /// Reads one request from a connection, with a body of up to `max_body`
+    /// bytes rather than the usual limit.
+    pub fn read_up_to(stream: impl Read, max_body: usize) -> io::Result<Request> {
+        let mut reader = BufReader::new(stream);
+        let mut head_len = 0;
+        let mut line = String::new();
+
+        let mut next_line = |reader: &mut BufReader<_>, line: &mut String| -> io::Result<()> {
+            line.clear();
+            let n = reader.by_ref().take(MAX_HEAD as u64).read_line(line)?;
+            head_len += n;
+            if n == 0 || head_len > MAX_HEAD {
+                return Err(invalid("incomplete or oversized request head"));
+            }
+            let trimmed = line.trim_end_matches(['\r', '\n']).len();
+            line.truncate(trimmed);
+            Ok(())
+        };
+
+        next_line(&mut reader, &mut line)?;
+        let mut parts = line.split(' ');
+        let (Some(method), Some(target), Some(version)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid("malformed request line"));
+        };
+        if !version.starts_with("HTTP/1.") {
+            return Err(invalid("unsupported HTTP version"));
+        }
+        let mut request = Request {
+            method: method.to_string(),
+            ..Request::default()
+        };
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        request.path = path
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(percent_decode)
+            .collect();
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            request
+                .query
+                .insert(percent_decode(key), percent_decode(value));
+        }
+
+        loop {
+            next_line(&mut reader, &mut line)?;
+            if line.is_empty() {
+                break;
+            }
+            let (name, value) = line
+                .split_once(':')
+                .ok_or_else(|| invalid("malformed header"))?;
+            request
+                .headers
+                .insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+
+        if let Some(length) = request.headers.get("content-length") {
+            let length: usize = length
+                .parse()
+                .map_err(|_| invalid("invalid Content-Length"))?;
+            if length > max_body {
+                return Err(invalid("request body too large"));
+            }
+            request.body = vec![0; length];
+            reader.read_exact(&mut request.body)?;
+        }
+        Ok(request)
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+}
+
+/// An HTTP response, written with `Connection: close`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// A response with a JSON body.
+    pub fn json(status: u16, body: &serde_json::Value) -> Response {
+        Response {
+            status,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: format!("{}\n", body).into_bytes(),
+        }
+    }
+
+    /// A response with an HTML page as its body.
+    pub fn html(status: u16, page: &str) -> Response {
+        Response {
+            status,
+            headers: vec![(
+                "Content-Type".to_string(),
+                "text/html; charset=utf-8".to_string(),
+            )],
+            body: page.as_bytes().to_vec(),
+        }
+    }
+
+    /// A `{"error": message}` response.
+    pub fn error(status: u16, message: &str) -> Response {
+        Response::json(status, &serde_json::json!({ "error": message }))
+    }
+
+    /// A response with no body, such as 204 No Content.
+    pub fn empty(status: u16) -> Response {
+        Response {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Response {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn write_to(&self, mut stream: impl Write) -> io::Result<()> {
+        let mut head = format!("HTTP/1.1 {} {}\r\n", self.status, reason(self.status));
+        for (name, value) in &self.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        head.push_str(&format!(
+            "Content-Length: {}\r\nConnection: close\r\n\r\n",
+            self.body.len()
+        ));
+        stream.write_all(head.as_bytes())?;
+        stream.write_all(&self.body)?;
+        stream.flush()
+    }
+}
+
+/// The standard reason phrase for the status codes we use.
+pub fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        500 => "Internal Server Error",
+        _ => "Unknown",
+    }
+}
+
+/// Decodes `%XX` escapes (and `+` as a space, as forms send it).
+pub fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    let hex = |i: usize| bytes.get(i).and_then(|&b| (b as char).to_digit(16));
+    while i < bytes.len() {
+        match (bytes[i], hex(i + 1), hex(i + 2)) {
+            (b'%', Some(high), Some(low)) => {
+                out.push((high * 16 + low) as u8);
+                i += 3;
+                continue;
+            }
+            (b'+', _, _) => out.push(b' '),
+            (byte, _, _) => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// The `Authorization` header value for HTTP basic authentication.
+pub fn basic_auth(user: &str, password: &str) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let input = format!("{}:{}", user, password);
+    let mut out = String::from("Basic ");
+    for chunk in input.as_bytes().chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| {
+            bits | u32::from(byte) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(bits >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// A URL, split into the parts needed to make a request.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Url {
+    pub scheme: String,
+    pub host: String,
+    pub port: u16,
+    /// The path and query, starting with `/`.
+    pub path: String,
+}
+
+impl FromStr for Url {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || format!("'{}' is not a valid http:// or https:// URL", s);
+        let (scheme, rest) = s.split_once("://").ok_or_else(err)?;
+        let default_port = match scheme.to_lowercase().as_str() {
+            "http" => 80,
+            "https" => 443,
+            _ => return Err(err()),
+        };
+        let (authority, path) = match rest.find(['/', '?']) {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        // Credentials in the URL aren't supported; drop anything before '@'.
+        let authority = authority.rsplit('@').next().unwrap_or(authority);
+        // IPv6 addresses are bracketed, e.g. `[::1]:8080`.
+        let port_start = match authority.rfind(']') {
+            Some(end) => authority[end..].find(':').map(|i| end + i),
+            None => authority.rfind(':'),
+        };
+        let (host, port) = match port_start {
+            Some(i) => (
+                &authority[..i],
+                authority[i + 1..].parse().map_err(|_| err())?,
+            ),
+            None => (authority, default_port),
+        };
+        if host.is_empty() {
+            return Err(err());
+        }
+        let path = if path.starts_with('?') {
+            format!("/{}", path)
+        } else {
+            path.to_string()
+        };
+        Ok(Url {
+            scheme: scheme.to_lowercase(),
+            host: host
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .to_string(),
+            port,
+            path,
+        })
+    }
+}
+
+/// The status and body of a response to a request we sent.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClientResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+/// How outgoing requests are made: the settings shared by webhooks, remote
+/// storage and anything else that talks to another server.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Client {
+    /// Bounds connecting, and each read and write separately.
+    pub timeout: Duration,
+    /// How many more times a failed request is tried before giving up.
+    pub retries: u32,
+    /// An `http://` proxy to send requests through. Without one, the usual
+    /// `http_proxy` and `https_proxy` variables are honored, as is `no_proxy`.
+    pub proxy: Option<String>,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Client {
+            timeout: Duration::from_secs(10),
+            retries: 2,
+            proxy: None,
+        }
+    }
+}
+
+impl Client {
+    /// Sends a request to `url` once and waits for the response.
+    pub fn send(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: &[u8],
+    ) -> io::Result<ClientResponse> {
+        let parsed: Url = url
+            .parse()
+            .map_err(|e: String| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let proxy = self.proxy_for(&parsed);
+        if parsed.scheme == "https" {
+            log::debug("http", format_args!("{} {} (via curl)", method, url));
+            return send_with_curl(method, url, headers, body, self.timeout, proxy.as_deref());
+        }
+        match &proxy {
+            Some(proxy) => log::debug("http", format_args!("{} {} (via {})", method, url, proxy)),
+            None => log::debug("http", format_args!("{} {}", method, url)),
+        }
+        // Through a proxy, we connect to it and ask for the whole URL.
+        let (connect, target) = match &proxy {
+            Some(proxy) => {
+                let proxy: Url = proxy
+                    .parse()
+                    .map_err(|e: String| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+                (proxy, url.to_string())
+            }
+            None => (parsed.clone(), parsed.path.clone()),
+        };
+        send_plain(
+            method,
+            &connect,
+            &target,
+            &parsed.host,
+            headers,
+            body,
+            self.timeout,
+        )
+    }
+
+    /// Sends a request like [`Client::send`], trying again on network errors
+    /// and on 429 and 5xx responses with a growing delay: 1s, 2s, 4s, ...
+    ///
+    /// Returns the last response, or why the last attempt failed. Only use it
+    /// for requests that are safe to repeat.
+    pub fn send_with_retries(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: &[u8],
+    ) -> io::Result<ClientResponse> {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            log::debug(
+                "http",
+                format_args!("attempt {} of {} to {}", attempt, self.retries + 1, url),
+            );
+            let result = self.send(method, url, headers, body);
+            match &result {
+                Ok(response) => log::info(
+                    "http",
+                    format_args!("{} {} answered {}", method, url, response.status),
+                ),
+                Err(e) => log::info("http", format_args!("{} {} failed: {}", method, url, e)),
+            }
+            let retry = match &result {
+                Ok(response) => response.status == 429 || response.status >= 500,
+                // A URL we can't even parse won't get better by trying again.
+                Err(e) => e.kind() != io::ErrorKind::InvalidInput,
+            };
+            if !retry || attempt > self.retries {
+                return result;
+            }
+            thread::sleep(Duration::from_secs(1 << (attempt - 1).min(6)));
+        }
+    }
+
+    /// The proxy to use for `url`, if any.
+    fn proxy_for(&self, url: &Url) -> Option<String> {
+        if bypasses_proxy(&url.host) {
+            return None;
+        }
+        if self.proxy.is_some() {
+            return self.proxy.clone();
+        }
+        // curl reads these itself for https.
+        if url.scheme == "https" {
+            return None;
+        }
+        ["http_proxy", "HTTP_PROXY", "all_proxy", "ALL_PROXY"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|value| !value.is_empty())
+    }
+}
+
+/// Whether `no_proxy` (or `NO_PROXY`) says to reach `host` directly.
+///
+/// It is a comma-separated list of host names, each also matching its
+/// subdomains, or `*` for every host.
+fn bypasses_proxy(host: &str) -> bool {
+    let Some(list) = ["no_proxy", "NO_PROXY"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|value| !value.is_empty())
+    else {
+        return false;
+    };
+    let host = host.to_lowercase();
+    list.split(',')
+        .map(|entry| entry.trim().trim_start_matches('.').to_lowercase())
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| {
+            entry == "*"
+                || host == entry
+                || host
+                    .strip_suffix(entry.as_str())
+                    .is_some_and(|rest| rest.ends_with('.'))
+        })
+}
+
+/// Makes one plain-text request to `connect`, asking for `target`.
+fn send_plain(
+    method: &str,
+    connect: &Url,
+    target: &str,
+    host: &str,
+    headers: &[(&str, &str)],
+    body: &[u8],
+    timeout: Duration,
+) -> io::Result<ClientResponse> {
+    let addr = (connect.host.as_str(), connect.port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| invalid("the host name did not resolve"))?;
+    let mut stream = TcpStream::connect_timeout(&addr, timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+
+    let mut head = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        method,
+        target,
+        host,
+        body.len()
+    );
+    for (name, value) in headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(body)?;
+
+    let mut raw = Vec::new();
+    stream.take(MAX_RESPONSE as u64 + 1).read_to_end(&mut raw)?;
+    if raw.len() > MAX_RESPONSE {
+        return Err(invalid("the response is too large"));
+    }
+    parse_response(&raw)
+}
+
+/// Parses a complete response, as read from a connection that the server closed.
+fn parse_response(raw: &[u8]) -> io::Result<ClientResponse> {
+    let split = raw
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .ok_or_else(|| invalid("incomplete response"))?;
+    let head = String::from_utf8_lossy(&raw[..split]);
+    let mut lines = head.split("\r\n");
+    let status = lines
+        .next()
+        .and_then(|line| line.split(' ').nth(1))
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| invalid("malformed status line"))?;
+    let chunked = lines.any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.trim().eq_ignore_ascii_case("transfer-encoding")
+                && value.trim().eq_ignore_ascii_case("chunked")
+        })
+    });
+    let body = &raw[split + 4..];
+    let body = if chunked {
+        dechunk(body)?
+    } else {
+        body.to_vec()
+    };
+    Ok(ClientResponse { status, body })
+}
+
+/// Decodes a `Transfer-Encoding: chunked` body.
+fn dechunk(mut body: &[u8]) -> io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    loop {
+        let line_end = body
+            .windows(2)
+            .position(|w| w == b"\r\n")
+            .ok_or_else(|| invalid("malformed chunk"))?;
+        let size_text = String::from_utf8_lossy(&body[..line_end]);
+        let size_text = size_text.split(';').next().unwrap_or_default().trim();
+        let size =
+            usize::from_str_radix(size_text, 16).map_err(|_| invalid("malformed chunk size"))?;
+        body = &body[line_end + 2..];
+        if size == 0 {
+            return Ok(out);
+        }
+        let end = size
+            .checked_add(2)
+            .filter(|&end| end <= body.len())
+            .ok_or_else(|| invalid("truncated chunk"))?;
+        if &body[size..end] != b"\r\n" {
+            return Err(invalid("malformed chunk"));
+        }
+        out.extend_from_slice(&body[..size]);
+        body = &body[end..];
+    }
+}
+
+fn send_with_curl(
+    method: &str,
+    url: &str,
+    headers: &[(&str, &str)],
+    body: &[u8],
+    timeout: Duration,
+    proxy: Option<&str>,
+) -> io::Result<ClientResponse> {
+    let mut command = Command::new("curl");
+    command
+        .args(["--silent", "--show-error", "--request", method])
+        .arg("--max-time")
+        .arg(timeout.as_secs().max(1).to_string())
+        .args(["--data-binary", "@-"])
+        // Print the status code on a line of its own after the body.
+        .args(["--write-out", "\n%{http_code}"]);
+    if let Some(proxy) = proxy {
+        command.arg("--proxy").arg(proxy);
+    }
+    for (name, value) in headers {
+        command.arg("--header").arg(format!("{}: {}", name, value));
+    }
+    let mut child = command
+        .arg("--")
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| io::Error::new(e.kind(), format!("could not run curl: {}", e)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(body)?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let msg = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(msg.trim().to_string()));
+    }
+    let stdout = output.stdout;
+    let split = stdout.iter().rposition(|&b| b == b'\n').unwrap_or(0);
+    let status = String::from_utf8_lossy(&stdout[split..])
+        .trim()
+        .parse()
+        .map_err(|_| invalid("curl did not report a status code"))?;
+    Ok(ClientResponse {
+        status,
+        body: stdout[..split].to_vec(),
+    })
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
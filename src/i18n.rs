@@ -0,0 +1,336 @@
+// Translations of the messages the program shows to people.
+//
+// Messages are kept in Fluent files (`locales/<language>.ftl`), built into the
+// binary. We read the subset of Fluent that our messages use:
+//
+//   # a comment
+//   task-added = Adding task: { $task }
+//   tasks-removed = Removed { $count ->
+//       [one] one task
+//      *[other] { $count } tasks
+//   }.
+//
+// The language is the `language` setting if there is one, or else taken
+// from `LC_ALL`, `LC_MESSAGES` or `LANG`, as other programs do. English is
+// the fallback, both for unsupported languages and for any message a
+// translation is missing. Messages meant for programs rather than people
+// (logs, API errors, help text) stay in English.
+
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+/// The languages we have translations for, as (code, Fluent source).
+const CATALOGS: &[(&str, &str)] = &[
+    ("en", include_str!("../locales/en.ftl")),
+    ("es", include_str!("../locales/es.ftl")),
+];
+
+/// The codes of the supported languages.
+pub const LANGUAGES: &[&str] = &["en", "es"];
+
+/// An index into `CATALOGS`, or `UNSET` until a language is chosen.
+static CURRENT: AtomicUsize = AtomicUsize::new(UNSET);
+const UNSET: usize = usize::MAX;
+
+/// Chooses the language to use from now on.
+///
+/// `language` is the configured one; without it, the environment decides.
+pub fn init(language: Option<&str>) {
+    let chosen = language
+        .and_then(find)
+        .or_else(|| detect().and_then(|l| find(&l)))
+        .unwrap_or(0);
+    CURRENT.store(chosen, Ordering::Relaxed);
+}
+
+/// The code of the language in use, e.g. `"en"`.
+pub fn language() -> &'static str {
+    CATALOGS[current()].0
+}
+
+/// Turns a locale name such as `es_ES.UTF-8` or `es` into a supported language code.
+pub fn normalize(locale: &str) -> Option<&'static str> {
+    find(locale).map(|i| CATALOGS[i].0)
+}
+
+fn find(locale: &str) -> Option<usize> {
+    let code = locale
+        .split(['_', '-', '.', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    CATALOGS.iter().position(|(c, _)| *c == code)
+}
+
+/// The language asked for by the locale environment variables, if any.
+fn detect() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|value| !value.is_empty())
+        // "C" and "POSIX" mean no particular language.
+        .filter(|value| value != "C" && value != "POSIX")
+}
+
+fn current() -> usize {
+    match CURRENT.load(Ordering::Relaxed) {
+        UNSET => {
+            init(None);
+            CURRENT.load(Ordering::Relaxed)
+        }
+        index => index,
+    }
+}
+
+/// Looks up a message and fills in its arguments.
+///
+/// Unknown message ids are returned as they are, so a missing message is
+/// visible instead of silently blank.
+pub fn tr(id: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    let catalogs = catalogs();
+    let message = catalogs[current()].get(id).or_else(|| catalogs[0].get(id));
+    match message {
+        Some(pattern) => {
+            let args: Vec<(&str, String)> = args
+                .iter()
+                .map(|(name, value)| (*name, value.to_string()))
+                .collect();
+            let mut out = String::new();
+            pattern.format(&args, &mut out);
+            out
+        }
+        None => id.to_string(),
+    }
+}
+
+/// Returns a message that holds a comma-separated list, e.g. weekday names.
+pub fn tr_list(id: &str) -> Vec<String> {
+    tr(id, &[])
+        .split(',')
+        .map(|word| word.trim().to_string())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+type Catalog = HashMap<String, Pattern>;
+
+fn catalogs() -> &'static [Catalog] {
+    static PARSED: OnceLock<Vec<Catalog>> = OnceLock::new();
+    PARSED.get_or_init(|| {
+        CATALOGS
+            .iter()
+            .map(|(code, source)| {
+                parse(source).unwrap_or_else(|e| panic!("locales/{}.ftl: {}", code, e))
+            })
+            .collect()
+    })
+}
+
+/// A message's text, with placeholders.
+#[derive(Debug, Default)]
+struct Pattern(Vec<Element>);
+
+#[derive(Debug)]
+enum Element {
+    Text(String),
+    Variable(String),
+    /// `{ $var -> [key] ... *[other] ... }`; the default variant is `default`.
+    Select {
+        variable: String,
+        variants: Vec<(String, Pattern)>,
+        default: usize,
+    },
+}
+
+impl Pattern {
+    fn format(&self, args: &[(&str, String)], out: &mut String) {
+        let arg = |name: &str| args.iter().find(|(n, _)| *n == name).map(|(_, v)| v);
+        for element in &self.0 {
+            match element {
+                Element::Text(text) => out.push_str(text),
+                Element::Variable(name) => match arg(name) {
+                    Some(value) => out.push_str(value),
+                    None => {
+                        out.push_str("{$");
+                        out.push_str(name);
+                        out.push('}');
+                    }
+                },
+                Element::Select {
+                    variable,
+                    variants,
+                    default,
+                } => {
+                    let value = arg(variable).map(String::as_str).unwrap_or_default();
+                    // An exact match wins, then the plural category of a number.
+                    let category = plural_category(value);
+                    let chosen = variants
+                        .iter()
+                        .position(|(key, _)| key == value)
+                        .or_else(|| {
+                            variants
+                                .iter()
+                                .position(|(key, _)| Some(key.as_str()) == category)
+                        })
+                        .unwrap_or(*default);
+                    variants[chosen].1.format(args, out);
+                }
+            }
+        }
+    }
+}
+
+/// The CLDR plural category of a number in English and Spanish: `one` or `other`.
+fn plural_category(value: &str) -> Option<&'static str> {
+    let number: f64 = value.parse().ok()?;
+    Some(if number == 1.0 { "one" } else { "other" })
+}
+
+/// Parses a Fluent file into its messages.
+fn parse(source: &str) -> Result<Catalog, String> {
+    let mut messages = Catalog::new();
+    let mut current: Option<(String, String)> = None;
+    for (number, line) in source.lines().enumerate() {
+        let indented = line.starts_with([' ', '\t']);
+        if indented && !line.trim().is_empty() {
+            // A continuation of the message above.
+            match &mut current {
+                Some((_, value)) => {
+                    value.push('\n');
+                    value.push_str(line.trim());
+                }
+                None => {
+                    return Err(format!(
+                        "line {}: indented line outside a message",
+                        number + 1
+                    ))
+                }
+            }
+            continue;
+        }
+        if let Some((id, value)) = current.take() {
+            messages.insert(id, parse_pattern(value.trim())?);
+        }
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (id, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("line {}: expected 'id = message'", number + 1))?;
+        current = Some((id.trim().to_string(), value.trim().to_string()));
+    }
+    if let Some((id, value)) = current {
+        messages.insert(id, parse_pattern(value.trim())?);
+    }
+    Ok(messages)
+}
+
+fn parse_pattern(text: &str) -> Result<Pattern, String> {
+    let mut elements = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        if start > 0 {
+            elements.push(Element::Text(rest[..start].to_string()));
+        }
+        let end = start + matching_brace(&rest[start..])?;
+        elements.push(parse_placeable(rest[start + 1..end].trim())?);
+        rest = &rest[end + 1..];
+    }
+    if !rest.is_empty() {
+        elements.push(Element::Text(rest.to_string()));
+    }
+    Ok(Pattern(elements))
+}
+
+/// Returns the offset of the `}` closing the `{` that `text` starts with.
+fn matching_brace(text: &str) -> Result<usize, String> {
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    Err(format!("unclosed '{{' in '{}'", text))
+}
+
+fn parse_placeable(inner: &str) -> Result<Element, String> {
+    // A string literal, used to write a literal brace: { "{" }.
+    if let Some(literal) = inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+        return Ok(Element::Text(literal.to_string()));
+    }
+    let Some((selector, variants)) = inner.split_once("->") else {
+        return variable_name(inner).map(Element::Variable);
+    };
+    let variable = variable_name(selector.trim())?;
+
+    // Each variant is `[key] pattern`, the default one marked with `*`.
+    let mut parsed = Vec::new();
+    let mut default = None;
+    let mut rest = variants.trim();
+    while !rest.is_empty() {
+        let is_default = rest.starts_with('*');
+        let open = rest.trim_start_matches('*');
+        let close = open
+            .find(']')
+            .filter(|_| open.starts_with('['))
+            .ok_or_else(|| format!("expected a '[key]' variant in '{}'", inner))?;
+        let key = open[1..close].trim().to_string();
+        let body = &open[close + 1..];
+        let end = next_variant(body);
+        if is_default {
+            default = Some(parsed.len());
+        }
+        parsed.push((key, parse_pattern(body[..end].trim())?));
+        rest = body[end..].trim_start();
+    }
+    let default = default.ok_or_else(|| format!("no default '*[...]' variant in '{}'", inner))?;
+    Ok(Element::Select {
+        variable,
+        variants: parsed,
+        default,
+    })
+}
+
+/// Returns where the next variant starts in `body`, outside any placeable.
+fn next_variant(body: &str) -> usize {
+    let mut depth = 0;
+    for (i, c) in body.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            '[' | '*' if depth == 0 && body[..i].ends_with(['\n', ' ']) => {
+                let rest = body[i..].trim_start_matches('*');
+                if rest.starts_with('[') && rest.contains(']') {
+                    return i;
+                }
+            }
+            _ => {}
+        }
+    }
+    body.len()
+}
+
+fn variable_name(text: &str) -> Result<String, String> {
+    match text.strip_prefix('$') {
+        Some(name)
+            if !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '-') =>
+        {
+            Ok(name.to_string())
+        }
+        _ => Err(format!("expected a '$variable', found '{}'", text)),
+    }
+}
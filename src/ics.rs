@@ -0,0 +1,380 @@
+// iCalendar (.ics) files, as calendars export them, read into events.
+//
+// Only what `import --ics` needs is read from each VEVENT: its summary,
+// description and location, when it starts, and how it repeats. Lines
+// folded onto the next are joined and text values unescaped first.
+//
+// Repeating events are expanded from their RRULE for the common rules:
+//
+//   FREQ      DAILY, WEEKLY, MONTHLY or YEARLY, every INTERVAL of them
+//   BYDAY     weekdays, e.g. MO,WE,FR; for MONTHLY, also the nth one of
+//             the month, e.g. 2TU or -1FR
+//   BYMONTHDAY  days of the month, e.g. 1,15 or -1 for the last
+//   COUNT, UNTIL  when the repeating stops
+//
+// EXDATE leaves single occurrences out, and an event with a RECURRENCE-ID
+// stands in for the occurrence it names. Cancelled events are left out.
+// Occurrences keep the day of the event's own time zone; the zone of the
+// start time, from its TZID or a `Z` for UTC, is kept for reading the time.
+
+use std::collections::HashSet;
+
+use crate::date::Date;
+
+/// One event of a calendar.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Event {
+    pub uid: Option<String>,
+    pub summary: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    /// The day it starts, or the first time for a repeating event.
+    pub start: Option<Date>,
+    /// The hour and minute it starts, unless it lasts all day.
+    pub time: Option<(u32, u32)>,
+    /// The time zone of `time`: `UTC`, or the name in its TZID. `None` means
+    /// the time is the same wherever the calendar is read.
+    pub zone: Option<String>,
+    /// How it repeats, as its RRULE, e.g. `FREQ=WEEKLY;BYDAY=MO`.
+    pub rule: Option<String>,
+    /// Occurrences left out of the rule.
+    pub except: Vec<Date>,
+    /// The occurrence of a repeating event this one replaces.
+    pub recurrence_id: Option<Date>,
+    pub cancelled: bool,
+}
+
+/// The events of a calendar, in the order they are written.
+pub fn parse(text: &str) -> Vec<Event> {
+    let mut events = Vec::new();
+    let mut event: Option<Event> = None;
+    // Components inside an event, such as its alarms, are skipped.
+    let mut nested = 0;
+    for line in unfold(text) {
+        let Some((name, zone, value)) = property(&line) else {
+            continue;
+        };
+        match (name.as_str(), value) {
+            ("BEGIN", "VEVENT") if event.is_none() => event = Some(Event::default()),
+            ("BEGIN", _) if event.is_some() => nested += 1,
+            ("END", "VEVENT") if nested == 0 => events.extend(event.take()),
+            ("END", _) if nested > 0 => nested -= 1,
+            _ if nested > 0 => {}
+            _ => {
+                if let Some(event) = event.as_mut() {
+                    read_property(event, &name, zone, value);
+                }
+            }
+        }
+    }
+    events
+}
+
+fn read_property(event: &mut Event, name: &str, zone: Option<&str>, value: &str) {
+    match name {
+        "UID" => event.uid = Some(value.to_string()),
+        "SUMMARY" => event.summary = unescape(value),
+        "DESCRIPTION" => event.description = Some(unescape(value)).filter(|d| !d.is_empty()),
+        "LOCATION" => event.location = Some(unescape(value)).filter(|l| !l.is_empty()),
+        "DTSTART" => {
+            if let Some((date, time)) = date_time(value) {
+                event.start = Some(date);
+                event.time = time;
+                event.zone = match value.trim().ends_with(['Z', 'z']) {
+                    true => Some("UTC".to_string()),
+                    false => zone.map(String::from),
+                };
+            }
+        }
+        "RRULE" => event.rule = Some(value.to_string()),
+        "EXDATE" => event
+            .except
+            .extend(value.split(',').filter_map(|v| Some(date_time(v)?.0))),
+        "RECURRENCE-ID" => event.recurrence_id = date_time(value).map(|(date, _)| date),
+        "STATUS" => event.cancelled = value.eq_ignore_ascii_case("CANCELLED"),
+        _ => {}
+    }
+}
+
+/// The lines of `text`, with folded lines joined back together.
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// The name, in capitals, TZID parameter and value of a content line, e.g.
+/// `DTSTART;TZID=Europe/Paris:20261020T090000`; other parameters are dropped.
+fn property(line: &str) -> Option<(String, Option<&str>, &str)> {
+    // Parameter values may be quoted and hold a colon.
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(i, c)| {
+        match c {
+            '"' => quoted = !quoted,
+            ':' if !quoted => return Some(i),
+            _ => {}
+        }
+        None
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut params = head.split(';');
+    let name = params.next().unwrap_or(head);
+    let zone = params.find_map(|param| {
+        let (key, zone) = param.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case("TZID")
+            .then(|| zone.trim().trim_matches('"'))
+    });
+    Some((name.trim().to_ascii_uppercase(), zone, value.trim_end()))
+}
+
+/// A date, `20261020`, or date and time, `20261020T090000` (with a `Z` when
+/// in UTC), as the day and the hour and minute.
+fn date_time(value: &str) -> Option<(Date, Option<(u32, u32)>)> {
+    let value = value.trim();
+    let number = |range: std::ops::Range<usize>| value.get(range)?.parse::<u32>().ok();
+    let date = Date::new(number(0..4)? as i32, number(4..6)?, number(6..8)?)?;
+    match value.get(8..9) {
+        Some("T") => {
+            let (hour, minute) = (number(9..11)?, number(11..13)?);
+            (hour < 24 && minute < 60).then_some((date, Some((hour, minute))))
+        }
+        _ => Some((date, None)),
+    }
+}
+
+/// A text value with its escapes undone: `\n`, `\,`, `\;` and `\\`.
+fn unescape(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => text.push('\n'),
+            Some(c) => text.push(c),
+            None => text.push('\\'),
+        }
+    }
+    text.trim().to_string()
+}
+
+/// The occurrences of `events` from `from` to `to`, both included, in order.
+pub fn between(events: &[Event], from: Date, to: Date) -> Vec<(Date, &Event)> {
+    // Occurrences replaced by another event are only taken from that one.
+    let replaced: HashSet<(&str, Date)> = events
+        .iter()
+        .filter_map(|event| Some((event.uid.as_deref()?, event.recurrence_id?)))
+        .collect();
+    let mut found = Vec::new();
+    for event in events.iter().filter(|event| !event.cancelled) {
+        for date in occurrences(event, from, to) {
+            let stands_in = event.recurrence_id.is_some();
+            if !stands_in
+                && event
+                    .uid
+                    .as_deref()
+                    .is_some_and(|uid| replaced.contains(&(uid, date)))
+            {
+                continue;
+            }
+            found.push((date, event));
+        }
+    }
+    found.sort_by_key(|&(date, event)| (date, event.time));
+    found
+}
+
+/// The days `event` happens on from `from` to `to`, both included.
+pub fn occurrences(event: &Event, from: Date, to: Date) -> Vec<Date> {
+    let Some(start) = event.start else {
+        return Vec::new();
+    };
+    let rule = match &event.rule {
+        Some(rule) if event.recurrence_id.is_none() => Rule::parse(rule),
+        _ => None,
+    };
+    let Some(rule) = rule else {
+        return if (from..=to).contains(&start) {
+            vec![start]
+        } else {
+            Vec::new()
+        };
+    };
+    let mut dates = Vec::new();
+    let mut count = 0;
+    // A rule that never matches a day gives up after this many periods.
+    for period in 0..10_000 {
+        let mut days = rule.period(start, period);
+        days.sort();
+        for date in days.into_iter().filter(|&date| date >= start) {
+            if rule.until.is_some_and(|until| date > until) || date > to {
+                return dates;
+            }
+            count += 1;
+            if rule.count.is_some_and(|limit| count > limit) {
+                return dates;
+            }
+            if date >= from && !event.except.contains(&date) {
+                dates.push(date);
+            }
+        }
+    }
+    dates
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// The parts of an RRULE this reads.
+#[derive(Debug)]
+struct Rule {
+    frequency: Frequency,
+    interval: i64,
+    count: Option<u32>,
+    until: Option<Date>,
+    /// Weekdays (Monday as 0), each with its place in the month, if given.
+    by_day: Vec<(Option<i32>, u32)>,
+    by_month_day: Vec<i32>,
+}
+
+impl Rule {
+    fn parse(rule: &str) -> Option<Rule> {
+        let mut parsed = Rule {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+        };
+        let mut frequency = None;
+        for part in rule.split(';') {
+            let (name, value) = part.split_once('=')?;
+            match name.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return None,
+                    })
+                }
+                "INTERVAL" => parsed.interval = value.parse().ok().filter(|&i| i > 0)?,
+                "COUNT" => parsed.count = Some(value.parse().ok()?),
+                "UNTIL" => parsed.until = Some(date_time(value)?.0),
+                "BYDAY" => {
+                    for day in value.split(',') {
+                        let split = day.len().checked_sub(2)?;
+                        let weekday = ["MO", "TU", "WE", "TH", "FR", "SA", "SU"]
+                            .iter()
+                            .position(|name| day[split..].eq_ignore_ascii_case(name))?;
+                        let nth = match &day[..split] {
+                            "" => None,
+                            nth => Some(nth.trim_start_matches('+').parse().ok()?),
+                        };
+                        parsed.by_day.push((nth, weekday as u32));
+                    }
+                }
+                "BYMONTHDAY" => {
+                    for day in value.split(',') {
+                        parsed.by_month_day.push(day.parse().ok()?);
+                    }
+                }
+                // Rules this doesn't understand would give wrong days.
+                "WKST" => {}
+                _ => return None,
+            }
+        }
+        parsed.frequency = frequency?;
+        Some(parsed)
+    }
+
+    /// The days of the `period`th period after the one `start` is in.
+    fn period(&self, start: Date, period: i64) -> Vec<Date> {
+        let steps = period * self.interval;
+        let on_day = |date: Date| {
+            self.by_day.is_empty() || self.by_day.iter().any(|&(_, day)| day == date.weekday())
+        };
+        match self.frequency {
+            Frequency::Daily => {
+                let date = start.add_days(steps);
+                if on_day(date) {
+                    vec![date]
+                } else {
+                    Vec::new()
+                }
+            }
+            Frequency::Weekly => {
+                let monday = start.add_days(7 * steps - i64::from(start.weekday()));
+                if self.by_day.is_empty() {
+                    return vec![monday.add_days(i64::from(start.weekday()))];
+                }
+                self.by_day
+                    .iter()
+                    .map(|&(_, day)| monday.add_days(i64::from(day)))
+                    .collect()
+            }
+            Frequency::Monthly => {
+                let first = start.add_months(steps as i32);
+                let Some(first) = Date::new(first.year(), first.month(), 1) else {
+                    return Vec::new();
+                };
+                self.month_days(first, start.day())
+            }
+            Frequency::Yearly => Date::new(start.year() + steps as i32, start.month(), start.day())
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    /// The days of the month starting on `first` the rule picks; `day`, the
+    /// start's day of the month, if it picks none itself.
+    fn month_days(&self, first: Date, day: u32) -> Vec<Date> {
+        let length = crate::date::days_in_month(first.year(), first.month()) as i32;
+        let in_month = |day: i32| {
+            let day = if day < 0 { length + 1 + day } else { day };
+            (1..=length)
+                .contains(&day)
+                .then(|| first.add_days(i64::from(day - 1)))
+        };
+        let mut days: Vec<Date> = self
+            .by_month_day
+            .iter()
+            .filter_map(|&day| in_month(day))
+            .collect();
+        for &(nth, weekday) in &self.by_day {
+            let matching: Vec<Date> = (1..=length)
+                .filter_map(in_month)
+                .filter(|date| date.weekday() == weekday)
+                .collect();
+            match nth {
+                None => days.extend(matching),
+                Some(nth) if nth > 0 => days.extend(matching.get(nth as usize - 1)),
+                Some(nth) => days.extend(
+                    matching
+                        .len()
+                        .checked_sub(nth.unsigned_abs() as usize)
+                        .and_then(|i| matching.get(i)),
+                ),
+            }
+        }
+        if self.by_month_day.is_empty() && self.by_day.is_empty() {
+            days.extend(in_month(day as i32));
+        }
+        days
+    }
+}
@@ -0,0 +1,186 @@
+// An index of where each task sits in a JSON list file, so that one task can
+// be read without parsing the whole list.
+//
+// It is kept next to the list as `<file>.idx`, a small text file:
+//
+//   todo-index 1 <file length> <file modification time, ns>
+//   <offset> <length>        one line per task, in order
+//
+// The first line identifies the version of the list file the index was built
+// from. If the file has changed since (edited by hand, written by another
+// program, ...) the index is stale and ignored until it is rebuilt, which
+// every save does.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::log;
+
+const HEADER: &str = "todo-index 1";
+
+/// The byte offset and length of each task in a list file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Index {
+    stamp: (u64, u128),
+    spans: Vec<(u64, u64)>,
+}
+
+impl Index {
+    /// Indexes `data`, the current contents of the list file at `list`.
+    pub fn build(list: &Path, data: &[u8]) -> io::Result<Index> {
+        let spans = spans(data).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} is not a JSON list", list.display()),
+            )
+        })?;
+        Ok(Index {
+            stamp: stamp(list)?,
+            spans,
+        })
+    }
+
+    /// Reads the index for the list file at `list`, if there is one and it is
+    /// up to date.
+    pub fn read(list: &Path) -> Option<Index> {
+        let data = fs::read_to_string(path_for(list)).ok()?;
+        let mut lines = data.lines();
+        let mut header = lines.next()?.rsplitn(3, ' ');
+        let modified = header.next()?.parse().ok()?;
+        let len = header.next()?.parse().ok()?;
+        if header.next()? != HEADER {
+            return None;
+        }
+        let spans = lines
+            .map(|line| {
+                let (offset, len) = line.split_once(' ')?;
+                Some((offset.parse().ok()?, len.parse().ok()?))
+            })
+            .collect::<Option<_>>()?;
+        let index = Index {
+            stamp: (len, modified),
+            spans,
+        };
+        if stamp(list).ok()? != index.stamp {
+            log::debug(
+                "index",
+                format_args!("{} changed since it was indexed", list.display()),
+            );
+            return None;
+        }
+        Some(index)
+    }
+
+    /// Writes the index next to the list file at `list`.
+    pub fn write(&self, list: &Path) -> io::Result<()> {
+        let mut out = format!("{} {} {}\n", HEADER, self.stamp.0, self.stamp.1);
+        for (offset, len) in &self.spans {
+            out.push_str(&format!("{} {}\n", offset, len));
+        }
+        fs::write(path_for(list), out)
+    }
+
+    /// Where the task at `index` is in the file, as (offset, length).
+    pub fn span(&self, index: usize) -> Option<(u64, u64)> {
+        self.spans.get(index).copied()
+    }
+
+    /// How many tasks the list holds.
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+}
+
+/// Where the index for the list file at `list` lives.
+pub fn path_for(list: &Path) -> PathBuf {
+    let mut name = list.as_os_str().to_owned();
+    name.push(".idx");
+    PathBuf::from(name)
+}
+
+/// Identifies a version of a file by its length and modification time.
+fn stamp(path: &Path) -> io::Result<(u64, u128)> {
+    let metadata = fs::metadata(path)?;
+    let modified = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos());
+    Ok((metadata.len(), modified))
+}
+
+/// Finds the byte span of each element of the JSON array in `data`.
+///
+/// Only the structure is checked; the elements themselves are parsed when
+/// they are read.
+fn spans(data: &[u8]) -> Option<Vec<(u64, u64)>> {
+    let mut spans = Vec::new();
+    let mut i = skip_space(data, 0);
+    if data.get(i) != Some(&b'[') {
+        return None;
+    }
+    i = skip_space(data, i + 1);
+    if data.get(i) == Some(&b']') {
+        return Some(spans);
+    }
+    loop {
+        let end = value_end(data, i)?;
+        spans.push((i as u64, (end - i) as u64));
+        i = skip_space(data, end);
+        match data.get(i)? {
+            b',' => i = skip_space(data, i + 1),
+            b']' => return Some(spans),
+            _ => return None,
+        }
+    }
+}
+
+fn skip_space(data: &[u8], mut i: usize) -> usize {
+    while data.get(i).is_some_and(u8::is_ascii_whitespace) {
+        i += 1;
+    }
+    i
+}
+
+/// Returns where the JSON value starting at `start` ends.
+fn value_end(data: &[u8], start: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut i = start;
+    while let Some(&c) = data.get(i) {
+        if in_string {
+            match c {
+                b'\\' => i += 1,
+                b'"' => {
+                    in_string = false;
+                    if depth == 0 {
+                        return Some(i + 1);
+                    }
+                }
+                _ => {}
+            }
+        } else {
+            match c {
+                b'"' => in_string = true,
+                b'{' | b'[' => depth += 1,
+                b'}' | b']' if depth == 0 => return Some(i),
+                b'}' | b']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(i + 1);
+                    }
+                }
+                b',' if depth == 0 => return Some(i),
+                c if depth == 0 && c.is_ascii_whitespace() => return Some(i),
+                _ => {}
+            }
+        }
+        i += 1;
+    }
+    None
+}
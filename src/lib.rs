@@ -0,0 +1,78 @@
+//! The library behind the `todo_cli` command-line to-do list manager.
+//!
+//! It holds everything that isn't about the terminal: the [`Task`] model, the
+//! [`TaskStore`] that loads, changes and saves a list, the storage
+//! [`backend`]s it saves to, and the [`Config`] that says where lists live.
+//! The binary is a thin layer that parses arguments and prints results, so
+//! other programs can reuse the same logic.
+//!
+//! ```no_run
+//! use todo_cli::{Task, TaskStore};
+//!
+//! let mut store = TaskStore::open("todos.json")?;
+//! let index = store.add(Task::new("Buy milk"))?;
+//! store.complete(index)?;
+//! store.save()?;
+//! # Ok::<(), todo_cli::Error>(())
+//! ```
+
+pub mod agenda;
+pub mod audit;
+pub mod backend;
+pub mod billing;
+pub mod board;
+pub mod clipboard;
+pub mod config;
+pub mod daemon;
+pub mod date;
+pub mod deps;
+pub mod discord;
+pub mod error;
+pub mod filter;
+pub mod fulltext;
+pub mod habit;
+pub mod heatmap;
+pub mod holiday;
+pub mod hooks;
+pub mod http;
+pub mod i18n;
+pub mod ics;
+pub mod index;
+pub mod listing;
+pub mod log;
+pub mod mail;
+pub mod markdown;
+pub mod mdns;
+pub mod modify;
+pub mod notify;
+pub mod order;
+pub mod qr;
+pub mod query;
+pub mod quickadd;
+pub mod regex;
+pub mod script;
+pub mod search;
+pub mod secret;
+pub mod sha256;
+pub mod slack;
+pub mod snapshot;
+pub mod stats;
+pub mod store;
+pub mod summary;
+pub mod sync;
+pub mod task;
+pub mod template;
+pub mod timetrack;
+pub mod toml;
+pub mod trash;
+pub mod tz;
+pub mod undo;
+pub mod urgency;
+pub mod validate;
+pub mod webhooks;
+
+pub use config::Config;
+pub use date::{Date, DateTime};
+pub use error::{Error, Result};
+pub use store::TaskStore;
+pub use task::{Priority, Task};
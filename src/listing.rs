@@ -0,0 +1,65 @@
+// What `list` last showed, so that the numbers it printed keep pointing at
+// the tasks the user saw.
+//
+// A task's number is its position, which shifts when a task before it is
+// removed. Each `list` records the id of the task behind every number, with
+// the version of the list it read. A later command given a number uses the
+// position as it is if the list hasn't changed since; if it has, the number
+// is looked up here and the task found by id, or the command fails rather
+// than act on whichever task has moved into that position.
+//
+// Only the most recent listing is kept, in `listing.json` next to the config.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::config_dir;
+use crate::log;
+
+/// The ids behind the numbers of one listing.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Listing {
+    /// Where the listed tasks are kept, as given by `TaskStore::location`.
+    pub location: String,
+    /// The version of the list that was shown; see `StorageBackend::version`.
+    pub version: Option<String>,
+    /// The id of the task numbered `n` at index `n - 1`.
+    pub ids: Vec<u64>,
+}
+
+impl Listing {
+    /// Where the last listing is kept.
+    pub fn path() -> PathBuf {
+        config_dir().join("listing.json")
+    }
+
+    /// Reads the last listing, if it was of the list at `location`.
+    pub fn read(location: &str) -> Option<Listing> {
+        let data = fs::read_to_string(Listing::path()).ok()?;
+        let listing: Listing = match serde_json::from_str(&data) {
+            Ok(listing) => listing,
+            Err(e) => {
+                log::debug("listing", format_args!("ignoring the last listing: {}", e));
+                return None;
+            }
+        };
+        (listing.location == location).then_some(listing)
+    }
+
+    /// Replaces the last listing with this one.
+    pub fn write(&self) -> io::Result<()> {
+        let path = Listing::path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)
+    }
+
+    /// The id of the task that was shown as `number`, if there was one.
+    pub fn id(&self, number: usize) -> Option<u64> {
+        self.ids.get(number.checked_sub(1)?).copied()
+    }
+}
@@ -0,0 +1,123 @@
+// Diagnostic logging, for finding out what went wrong.
+//
+// Messages record what the program did: which files it read and wrote, which
+// hooks it ran, what a webhook receiver answered, what the daemon noticed.
+// Each comes from a subsystem, its `target`, and has a level:
+//
+//   warn   something the user should know   (always)
+//   info   one line per thing done          (-v)
+//   debug  the details of how it went       (-vv)
+//   trace  everything, including polling    (-vvv)
+//
+// Only warnings are shown by default, as `Warning: ...` like the program's
+// other messages. `-v` and friends send more to stderr, and a configured log
+// file gets them too, at least at the info level, stamped with the time in UTC.
+
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::date::Date;
+use crate::i18n::tr;
+
+/// How much detail a message goes into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Warn = 0,
+    Info = 1,
+    Debug = 2,
+    Trace = 3,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        };
+        f.pad(name)
+    }
+}
+
+/// The most detailed level written to stderr, or 0 for just warnings.
+static STDERR_LEVEL: AtomicU8 = AtomicU8::new(0);
+/// The most detailed level written to the log file, or 0 for none.
+static FILE_LEVEL: AtomicU8 = AtomicU8::new(0);
+static FILE: Mutex<Option<File>> = Mutex::new(None);
+
+/// Turns logging on. `verbosity` is the number of `-v` flags given.
+///
+/// May be called again, e.g. once a log file has been configured.
+pub fn init(verbosity: usize, file: Option<&Path>) -> io::Result<()> {
+    let level = verbosity.min(Level::Trace as usize) as u8;
+    STDERR_LEVEL.store(level, Ordering::Relaxed);
+    if let Some(path) = file {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        *FILE.lock().unwrap_or_else(|e| e.into_inner()) = Some(file);
+        FILE_LEVEL.store(level.max(Level::Info as u8), Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+/// Returns whether messages at `level` are written anywhere.
+pub fn enabled(level: Level) -> bool {
+    let level = level as u8;
+    level <= STDERR_LEVEL.load(Ordering::Relaxed) || level <= FILE_LEVEL.load(Ordering::Relaxed)
+}
+
+/// Logs a message from `target`, e.g. `log(Level::Info, "store", format_args!(...))`.
+pub fn log(level: Level, target: &str, args: fmt::Arguments<'_>) {
+    if level == Level::Warn {
+        eprintln!("{}", tr("warning", &[("message", &args)]));
+    } else if level as u8 <= STDERR_LEVEL.load(Ordering::Relaxed) {
+        eprintln!("[{} {}] {}", level, target, args);
+    }
+    if level as u8 <= FILE_LEVEL.load(Ordering::Relaxed) {
+        let mut file = FILE.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(file) = file.as_mut() {
+            // Losing a log line isn't worth failing the command over.
+            let _ = writeln!(file, "{} {:5} {}: {}", timestamp(), level, target, args);
+        }
+    }
+}
+
+pub fn warn(target: &str, args: fmt::Arguments<'_>) {
+    log(Level::Warn, target, args);
+}
+
+pub fn info(target: &str, args: fmt::Arguments<'_>) {
+    log(Level::Info, target, args);
+}
+
+pub fn debug(target: &str, args: fmt::Arguments<'_>) {
+    log(Level::Debug, target, args);
+}
+
+pub fn trace(target: &str, args: fmt::Arguments<'_>) {
+    log(Level::Trace, target, args);
+}
+
+/// The current time in UTC, e.g. `2024-05-01T09:30:00Z`.
+fn timestamp() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64);
+    let date = Date::from_days(secs.div_euclid(86_400));
+    let time = secs.rem_euclid(86_400);
+    format!(
+        "{}T{:02}:{:02}:{:02}Z",
+        date,
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
@@ -0,0 +1,814 @@
+// Email messages, read for `ingest` to turn into tasks, and written for
+// `mail` to send a list to someone.
+//
+// Messages come from a local mbox file or maildir, or from an IMAP mailbox.
+// IMAP servers want TLS, which we have no implementation of, so as with
+// `https://` requests the `curl` program does the talking: it asks the
+// server for the flagged messages (and those sent to an address, if given)
+// and fetches each one whole. Sending goes through `curl` to an SMTP server
+// the same way.
+//
+// Only as much of a message is understood as a task needs: its headers,
+// with encoded words (`=?utf-8?B?...?=`) decoded, and its text, the first
+// `text/plain` part (or `text/html` with the tags taken out), with quoted-
+// printable or base64 undone. Character sets other than UTF-8 are read as
+// Latin-1, which is what most of the rest are close enough to.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use crate::config::config_dir;
+use crate::date::DateTime;
+use crate::sha256;
+
+/// One email message.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Message {
+    /// The headers in order, names as written, folded lines joined.
+    pub headers: Vec<(String, String)>,
+    /// The body as it came, still encoded.
+    pub body: Vec<u8>,
+    /// Whether the message is flagged (starred) in its mailbox.
+    pub flagged: bool,
+}
+
+impl Message {
+    /// Reads a message in RFC 5322 form.
+    pub fn parse(raw: &[u8]) -> Message {
+        let (head, body) = split_head(raw);
+        Message {
+            headers: parse_headers(&String::from_utf8_lossy(head)),
+            body: body.to_vec(),
+            flagged: false,
+        }
+    }
+
+    /// The first header called `name`, any case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The subject, decoded, without the `Re:` and `Fwd:` in front.
+    pub fn subject(&self) -> String {
+        let mut subject = decode_words(self.header("subject").unwrap_or_default());
+        loop {
+            let trimmed = subject.trim_start();
+            let lower = trimmed.to_lowercase();
+            let Some(prefix) = ["re:", "fwd:", "fw:", "aw:", "wg:"]
+                .iter()
+                .find(|prefix| lower.starts_with(*prefix))
+            else {
+                return trimmed.trim_end().to_string();
+            };
+            subject = trimmed[prefix.len()..].to_string();
+        }
+    }
+
+    /// What tells this message apart from others: its `Message-ID`, or a
+    /// hash of it without one.
+    pub fn id(&self) -> String {
+        match self.header("message-id") {
+            Some(id) if !id.trim().is_empty() => id.trim().to_string(),
+            _ => {
+                let mut all = Vec::new();
+                for (name, value) in &self.headers {
+                    all.extend_from_slice(format!("{}: {}\n", name, value).as_bytes());
+                }
+                all.extend_from_slice(&self.body);
+                sha256::hex(&sha256::digest(&all))
+            }
+        }
+    }
+
+    /// Whether the message was sent to `address`, in `To`, `Cc` or
+    /// `Delivered-To`. An address with a `+tag` matches only itself; one
+    /// without matches its tagged forms too.
+    pub fn addressed_to(&self, address: &str) -> bool {
+        let wanted = address.trim().to_lowercase();
+        self.headers
+            .iter()
+            .filter(|(name, _)| {
+                ["to", "cc", "delivered-to", "x-original-to"]
+                    .iter()
+                    .any(|header| name.eq_ignore_ascii_case(header))
+            })
+            .flat_map(|(_, value)| addresses(value))
+            .any(|got| got == wanted || untagged(&got) == wanted)
+    }
+
+    /// The text of the message, for a task's notes.
+    pub fn text(&self) -> String {
+        let content_type = self.header("content-type").unwrap_or("text/plain");
+        let encoding = self.header("content-transfer-encoding").unwrap_or("7bit");
+        body_text(content_type, encoding, &self.body)
+            .unwrap_or_default()
+            .trim()
+            .to_string()
+    }
+}
+
+/// The messages of an mbox file; those marked `F` in their `Status` or
+/// `X-Status` header are flagged.
+pub fn read_mbox(data: &[u8]) -> Vec<Message> {
+    let mut messages = Vec::new();
+    let mut current: Option<Vec<u8>> = None;
+    let mut blank = true;
+    for line in data.split_inclusive(|&b| b == b'\n') {
+        if blank && line.starts_with(b"From ") {
+            if let Some(raw) = current.take() {
+                messages.push(raw);
+            }
+            current = Some(Vec::new());
+            blank = false;
+            continue;
+        }
+        blank = line.iter().all(|b| b.is_ascii_whitespace());
+        if let Some(raw) = current.as_mut() {
+            // `>From ` is how a line starting `From ` is kept in the body.
+            let quotes = line.iter().take_while(|&&b| b == b'>').count();
+            let line = match quotes > 0 && line[quotes..].starts_with(b"From ") {
+                true => &line[1..],
+                false => line,
+            };
+            raw.extend_from_slice(line);
+        }
+    }
+    messages.extend(current);
+    messages
+        .iter()
+        .map(|raw| {
+            let mut message = Message::parse(raw);
+            message.flagged = ["status", "x-status"].iter().any(|name| {
+                message
+                    .header(name)
+                    .is_some_and(|value| value.contains('F'))
+            });
+            message
+        })
+        .collect()
+}
+
+/// The messages of a maildir, in `new` and `cur`; those whose file name ends
+/// in flags with an `F` are flagged.
+pub fn read_maildir(dir: &Path) -> io::Result<Vec<Message>> {
+    let mut files = Vec::new();
+    for sub in ["new", "cur"] {
+        let entries = match fs::read_dir(dir.join(sub)) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        for entry in entries {
+            files.push(entry?.path());
+        }
+    }
+    if files.is_empty() && !dir.join("cur").is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} is not a maildir", dir.display()),
+        ));
+    }
+    files.sort();
+    let mut messages = Vec::new();
+    for path in files {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let flagged = name
+            .rsplit_once(":2,")
+            .is_some_and(|(_, flags)| flags.contains('F'));
+        let mut message = Message::parse(&fs::read(&path)?);
+        message.flagged = flagged;
+        messages.push(message);
+    }
+    Ok(messages)
+}
+
+/// An IMAP mailbox, reached through `curl`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Imap {
+    /// The mailbox, e.g. `imaps://mail.example.com/INBOX`.
+    pub url: String,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    pub timeout: Duration,
+}
+
+impl Imap {
+    /// The flagged messages in the mailbox, and with `to` those sent to
+    /// that address as well. The server marks the messages fetched as
+    /// read.
+    pub fn fetch(&self, to: Option<&str>) -> io::Result<Vec<Message>> {
+        let url = self.url.trim_end_matches('/');
+        let search = match to {
+            Some(to) => format!("UID SEARCH OR FLAGGED TO {}", quoted(to)),
+            None => "UID SEARCH FLAGGED".to_string(),
+        };
+        let found = String::from_utf8_lossy(&self.curl(url, Some(&search))?).into_owned();
+        let uids: Vec<&str> = found
+            .lines()
+            .filter_map(|line| line.strip_prefix("* SEARCH"))
+            .flat_map(str::split_whitespace)
+            .collect();
+        let mut messages = Vec::new();
+        for uid in uids {
+            let raw = self.curl(&format!("{};UID={}", url, uid), None)?;
+            let mut message = Message::parse(&raw);
+            // Those sent to the address weren't necessarily flagged, but
+            // they were asked for all the same.
+            message.flagged = true;
+            messages.push(message);
+        }
+        Ok(messages)
+    }
+
+    /// Runs `curl` on `url`, with `command` as a custom IMAP command. The
+    /// credentials go in on standard input, out of sight of `ps`.
+    fn curl(&self, url: &str, command: Option<&str>) -> io::Result<Vec<u8>> {
+        let mut curl = Command::new("curl");
+        curl.args(["--silent", "--show-error", "--config", "-"])
+            .arg("--max-time")
+            .arg(self.timeout.as_secs().max(1).to_string());
+        if let Some(command) = command {
+            curl.arg("--request").arg(command);
+        }
+        let mut child = curl
+            .arg("--")
+            .arg(url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| io::Error::new(e.kind(), format!("could not run curl: {}", e)))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            if let Some(user) = &self.user {
+                let password = self.password.as_deref().unwrap_or_default();
+                let credentials = format!("{}:{}", user, password);
+                writeln!(stdin, "user = {}", quoted(&credentials))?;
+            }
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            let msg = String::from_utf8_lossy(&output.stderr);
+            return Err(io::Error::other(msg.trim().to_string()));
+        }
+        Ok(output.stdout)
+    }
+}
+
+/// A message to send: plain text, with an HTML version besides if given.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Outgoing {
+    pub from: String,
+    pub to: Vec<String>,
+    pub subject: String,
+    pub text: String,
+    pub html: Option<String>,
+}
+
+impl Outgoing {
+    /// The message in RFC 5322 form, lines ending in CRLF, dated `now`
+    /// (UTC). Non-ASCII text is base64-encoded, in the body and subject.
+    pub fn compose(&self, now: DateTime) -> String {
+        let mut out = String::new();
+        let mut header = |name: &str, value: &str| {
+            out.push_str(&format!("{}: {}\r\n", name, value));
+        };
+        header("From", &encode_address(&self.from));
+        let to: Vec<String> = self.to.iter().map(|to| encode_address(to)).collect();
+        header("To", &to.join(", "));
+        header("Subject", &encode_word(&self.subject));
+        header("Date", &rfc5322_date(now));
+        let id = sha256::hex(&sha256::digest(
+            format!("{}{:?}{}", self.subject, now, std::process::id()).as_bytes(),
+        ));
+        let domain = self.from.rsplit('@').next().unwrap_or("localhost");
+        header(
+            "Message-ID",
+            &format!("<{}@{}>", &id[..32], domain.trim_end_matches('>')),
+        );
+        header("MIME-Version", "1.0");
+        match &self.html {
+            None => out.push_str(&part("text/plain", &self.text)),
+            Some(html) => {
+                let boundary = format!("=_{}", &id[32..]);
+                out.push_str(&format!(
+                    "Content-Type: multipart/alternative; boundary=\"{}\"\r\n\r\n",
+                    boundary
+                ));
+                for (media, body) in [("text/plain", &self.text), ("text/html", html)] {
+                    out.push_str(&format!("--{}\r\n{}", boundary, part(media, body)));
+                }
+                out.push_str(&format!("--{}--\r\n", boundary));
+            }
+        }
+        out
+    }
+}
+
+/// The headers and body of one part of a message, the body in base64.
+fn part(media: &str, body: &str) -> String {
+    let mut out = format!(
+        "Content-Type: {}; charset=utf-8\r\nContent-Transfer-Encoding: base64\r\n\r\n",
+        media
+    );
+    let encoded = encode_base64(body.replace('\n', "\r\n").as_bytes());
+    for line in encoded.as_bytes().chunks(76) {
+        out.push_str(&String::from_utf8_lossy(line));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// A header value as an encoded word if it isn't plain ASCII.
+fn encode_word(value: &str) -> String {
+    match value.is_ascii() {
+        true => value.to_string(),
+        false => format!("=?UTF-8?B?{}?=", encode_base64(value.as_bytes())),
+    }
+}
+
+/// An address with its display name as an encoded word if needed:
+/// `Zoë <zoe@example.com>`.
+fn encode_address(address: &str) -> String {
+    match address.split_once('<') {
+        Some((name, rest)) if !name.trim().is_empty() => {
+            format!("{} <{}", encode_word(name.trim()), rest)
+        }
+        _ => address.to_string(),
+    }
+}
+
+/// A time as the `Date` header writes it, e.g. `Thu, 15 Oct 2026 09:30:00 +0000`.
+fn rfc5322_date(at: DateTime) -> String {
+    const DAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let date = at.date();
+    let month: usize = date.format("%m").parse().unwrap_or(1);
+    format!(
+        "{}, {} {} {} {:02}:{:02}:00 +0000",
+        DAYS[date.weekday() as usize],
+        date.day(),
+        MONTHS[month - 1],
+        date.format("%Y"),
+        at.hour(),
+        at.minute()
+    )
+}
+
+fn encode_base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::new();
+    for chunk in data.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| {
+            bits | u32::from(byte) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(bits >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// An SMTP server to send mail through, reached through `curl`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Smtp {
+    /// The server, e.g. `smtps://smtp.example.com` or
+    /// `smtp://localhost:25`.
+    pub url: String,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    pub timeout: Duration,
+}
+
+impl Smtp {
+    /// Sends `message` to each of its recipients.
+    pub fn send(&self, message: &Outgoing) -> io::Result<()> {
+        // curl takes the credentials on standard input, so the message goes
+        // in a file only we can read.
+        let path = env::temp_dir().join(format!("todo-mail-{}.eml", std::process::id()));
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&path)?;
+        file.write_all(message.compose(DateTime::now()).as_bytes())?;
+        drop(file);
+        let sent = self.curl(message, &path);
+        let _ = fs::remove_file(&path);
+        sent
+    }
+
+    fn curl(&self, message: &Outgoing, path: &Path) -> io::Result<()> {
+        let mut curl = Command::new("curl");
+        curl.args(["--silent", "--show-error", "--config", "-", "--upload-file"])
+            .arg(path)
+            .arg("--max-time")
+            .arg(self.timeout.as_secs().max(1).to_string())
+            .arg("--mail-from")
+            .arg(bare_address(&message.from));
+        for to in &message.to {
+            curl.arg("--mail-rcpt").arg(bare_address(to));
+        }
+        // The path of an SMTP URL is the name curl greets the server with;
+        // without one it would take the file's name.
+        let url = self.url.trim_end_matches('/');
+        let url = match url.split_once("://") {
+            Some((_, rest)) if !rest.contains('/') => {
+                let domain = bare_address(&message.from)
+                    .rsplit_once('@')
+                    .map_or("localhost".to_string(), |(_, domain)| domain.to_string());
+                format!("{}/{}", url, domain)
+            }
+            _ => url.to_string(),
+        };
+        let mut child = curl
+            .arg("--")
+            .arg(url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| io::Error::new(e.kind(), format!("could not run curl: {}", e)))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            if let Some(user) = &self.user {
+                let password = self.password.as_deref().unwrap_or_default();
+                let credentials = format!("{}:{}", user, password);
+                writeln!(stdin, "user = {}", quoted(&credentials))?;
+            }
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            let msg = String::from_utf8_lossy(&output.stderr);
+            return Err(io::Error::other(msg.trim().to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// The address alone of `Ana <ana@example.com>`.
+fn bare_address(address: &str) -> String {
+    addresses(address)
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| address.trim().to_string())
+}
+
+/// `text` in double quotes, as IMAP and curl's config files both read it.
+fn quoted(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The ids of the messages already made into tasks, for each list, so that
+/// reading a mailbox again adds only what is new. Kept in `ingested.json`
+/// next to the config.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Ingested {
+    lists: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Ingested {
+    fn path() -> PathBuf {
+        config_dir().join("ingested.json")
+    }
+
+    /// Reads the record, or starts one if there is none.
+    pub fn read() -> io::Result<Ingested> {
+        match fs::read_to_string(Ingested::path()) {
+            Ok(data) => Ok(Ingested {
+                lists: serde_json::from_str(&data)?,
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Ingested::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Whether the message `id` was made into a task on the list at
+    /// `location`.
+    pub fn contains(&self, location: &str, id: &str) -> bool {
+        self.lists.get(location).is_some_and(|ids| ids.contains(id))
+    }
+
+    pub fn insert(&mut self, location: &str, id: String) {
+        self.lists
+            .entry(location.to_string())
+            .or_default()
+            .insert(id);
+    }
+
+    pub fn write(&self) -> io::Result<()> {
+        let path = Ingested::path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(&self.lists)?)
+    }
+}
+
+/// A message split at the blank line ending its headers.
+fn split_head(raw: &[u8]) -> (&[u8], &[u8]) {
+    for (at, window) in raw.windows(2).enumerate() {
+        if window == b"\n\n" {
+            return (&raw[..at + 1], &raw[at + 2..]);
+        }
+        if raw[at..].starts_with(b"\r\n\r\n") {
+            return (&raw[..at + 2], &raw[at + 4..]);
+        }
+    }
+    (raw, &[])
+}
+
+fn parse_headers(head: &str) -> Vec<(String, String)> {
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in head.lines() {
+        let line = line.trim_end_matches('\r');
+        if line.starts_with([' ', '\t']) {
+            // A folded line goes on the header before it.
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+            continue;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    headers
+}
+
+/// The value of `parameter` in a header such as `Content-Type`, e.g. the
+/// `boundary` of `multipart/mixed; boundary="abc"`.
+fn parameter<'a>(header: &'a str, parameter: &str) -> Option<&'a str> {
+    header.split(';').skip(1).find_map(|part| {
+        let (name, value) = part.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case(parameter)
+            .then(|| value.trim().trim_matches('"'))
+    })
+}
+
+/// The readable text of a body of `content_type`, if it has any.
+fn body_text(content_type: &str, encoding: &str, body: &[u8]) -> Option<String> {
+    let media = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+    if media.starts_with("multipart/") {
+        let boundary = parameter(content_type, "boundary")?;
+        let parts: Vec<Message> = multipart(body, boundary)
+            .into_iter()
+            .map(Message::parse)
+            .collect();
+        // Plain text is better than HTML for notes, wherever it comes.
+        let text = |wanted: bool| {
+            parts.iter().find_map(|part| {
+                let content_type = part.header("content-type").unwrap_or("text/plain");
+                let plain = content_type.to_lowercase().starts_with("text/plain");
+                let encoding = part.header("content-transfer-encoding").unwrap_or("7bit");
+                let attachment = part
+                    .header("content-disposition")
+                    .is_some_and(|d| d.to_lowercase().starts_with("attachment"));
+                (!attachment && (plain == wanted || content_type.starts_with("multipart/")))
+                    .then(|| body_text(content_type, encoding, &part.body))
+                    .flatten()
+            })
+        };
+        return text(true).or_else(|| text(false));
+    }
+    if !media.starts_with("text/") {
+        return None;
+    }
+    let bytes = match encoding.trim().to_lowercase().as_str() {
+        "quoted-printable" => decode_quoted_printable(body, false),
+        "base64" => decode_base64(body),
+        _ => body.to_vec(),
+    };
+    let text = decode_charset(
+        &bytes,
+        parameter(content_type, "charset").unwrap_or("utf-8"),
+    );
+    Some(match media.as_str() {
+        "text/html" => strip_html(&text),
+        _ => text,
+    })
+}
+
+/// The parts of a multipart body, between its `--boundary` lines.
+fn multipart<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{}", boundary);
+    let mut parts = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut at = 0;
+    for line in body.split_inclusive(|&b| b == b'\n') {
+        let trimmed = line.trim_ascii_end();
+        if trimmed.starts_with(delimiter.as_bytes()) {
+            if let Some(start) = start {
+                parts.push(&body[start..at]);
+            }
+            if trimmed == format!("{}--", delimiter).as_bytes() {
+                return parts;
+            }
+            start = Some(at + line.len());
+        }
+        at += line.len();
+    }
+    parts
+}
+
+/// Decodes the encoded words of a header, such as `=?UTF-8?Q?caf=C3=A9?=`.
+/// White space between two encoded words is dropped.
+pub fn decode_words(header: &str) -> String {
+    let mut out = String::new();
+    let mut rest = header;
+    let mut after_word = false;
+    while let Some(start) = rest.find("=?") {
+        let decoded = rest[start + 2..].find("?=").and_then(|len| {
+            let word = &rest[start + 2..start + 2 + len];
+            let mut fields = word.splitn(3, '?');
+            let (charset, encoding, text) = (fields.next()?, fields.next()?, fields.next()?);
+            let bytes = match encoding.to_ascii_uppercase().as_str() {
+                "B" => decode_base64(text.as_bytes()),
+                "Q" => decode_quoted_printable(text.as_bytes(), true),
+                _ => return None,
+            };
+            // RFC 2231 lets a language follow the charset after a `*`.
+            let charset = charset.split('*').next().unwrap_or_default();
+            Some((decode_charset(&bytes, charset), start + 2 + len + 2))
+        });
+        let Some((decoded, end)) = decoded else {
+            out.push_str(&rest[..start + 2]);
+            rest = &rest[start + 2..];
+            after_word = false;
+            continue;
+        };
+        let between = &rest[..start];
+        if !(after_word && between.trim().is_empty()) {
+            out.push_str(between);
+        }
+        out.push_str(&decoded);
+        rest = &rest[end..];
+        after_word = true;
+    }
+    out.push_str(rest);
+    out
+}
+
+fn decode_quoted_printable(data: &[u8], header: bool) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        match data[i] {
+            b'=' => {
+                let hex = data
+                    .get(i + 1..i + 3)
+                    .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+                if let Some(byte) = hex {
+                    out.push(byte);
+                    i += 3;
+                    continue;
+                }
+                // `=` at the end of a line joins it to the next.
+                let rest = &data[i + 1..];
+                let skip = if rest.starts_with(b"\r\n") {
+                    2
+                } else if rest.starts_with(b"\n") {
+                    1
+                } else {
+                    out.push(b'=');
+                    0
+                };
+                i += 1 + skip;
+            }
+            b'_' if header => {
+                out.push(b' ');
+                i += 1;
+            }
+            byte => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+fn decode_base64(data: &[u8]) -> Vec<u8> {
+    let value = |c: u8| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' | b'-' => Some(62),
+        b'/' | b'_' => Some(63),
+        _ => None,
+    };
+    let mut out = Vec::with_capacity(data.len() * 3 / 4);
+    let mut bits: u32 = 0;
+    let mut count = 0;
+    for sextet in data.iter().filter_map(|&c| value(c)) {
+        bits = bits << 6 | u32::from(sextet);
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            out.push((bits >> count) as u8);
+        }
+    }
+    out
+}
+
+/// `bytes` as text: UTF-8 (and ASCII) as such, anything else as Latin-1.
+fn decode_charset(bytes: &[u8], charset: &str) -> String {
+    match charset.trim().to_lowercase().as_str() {
+        "utf-8" | "utf8" | "us-ascii" | "ascii" | "" => String::from_utf8_lossy(bytes).into_owned(),
+        _ => match std::str::from_utf8(bytes) {
+            Ok(text) => text.to_string(),
+            Err(_) => bytes.iter().map(|&b| char::from(b)).collect(),
+        },
+    }
+}
+
+/// The text of an HTML body, roughly: tags dropped, line breaks kept, the
+/// common entities turned back into characters.
+fn strip_html(html: &str) -> String {
+    let mut out = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('>')
+            .map_or(rest.len(), |end| start + end + 1);
+        let tag = rest[start..end].to_lowercase();
+        if ["<br", "<p", "</p", "<div", "</div", "<li", "<tr"]
+            .iter()
+            .any(|name| tag.starts_with(name))
+        {
+            out.push('\n');
+        }
+        rest = &rest[end..];
+        // Neither styles nor scripts are text.
+        for skipped in ["style", "script"] {
+            if tag.starts_with(&format!("<{}", skipped)) {
+                let close = format!("</{}", skipped);
+                let at = rest.to_lowercase().find(&close).unwrap_or(rest.len());
+                rest = &rest[at..];
+            }
+        }
+    }
+    out.push_str(rest);
+    let out = out
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    let lines: Vec<&str> = out.lines().map(str::trim).collect();
+    lines
+        .join("\n")
+        .split("\n\n\n")
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// The email addresses in a header such as `To`, lowercase: those in angle
+/// brackets, or the bare ones.
+fn addresses(header: &str) -> Vec<String> {
+    header
+        .split(',')
+        .filter_map(|part| {
+            let address = match (part.find('<'), part.rfind('>')) {
+                (Some(start), Some(end)) if start < end => &part[start + 1..end],
+                _ => part,
+            };
+            let address = address.trim();
+            address.contains('@').then(|| address.to_lowercase())
+        })
+        .collect()
+}
+
+/// An address without its `+tag`: `me+todo@example.com` as `me@example.com`.
+fn untagged(address: &str) -> String {
+    match address.split_once('@') {
+        Some((local, domain)) => {
+            let local = local.split('+').next().unwrap_or(local);
+            format!("{}@{}", local, domain)
+        }
+        None => address.to_string(),
+    }
+}
@@ -1,164 +1,6063 @@
-use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
 use std::fs;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::thread;
+use std::time::{Duration, Instant};
 
-// Define the structure for a single To-Do item.
-// The `#[derive(...)]` attribute automatically implements traits for our struct.
-// - `Serialize`, `Deserialize`: Required by `serde` to convert this struct to/from JSON.
-// - `Clone`: Allows us to create copies of a Todo item.
-// - `Debug`: Allows us to print the struct for debugging purposes using `{:?}`.
-#[derive(Serialize, Deserialize, Clone, Debug)]
-struct Todo {
-    task: String,
-    completed: bool,
+use todo_cli::agenda;
+use todo_cli::audit::{self, Action, FieldChange};
+use todo_cli::backend::StorageBackend;
+use todo_cli::billing;
+use todo_cli::board::{self, Grouping, Move};
+use todo_cli::config::{self, ColorChoice, Config, PruneAction, SortOrder};
+use todo_cli::filter::Filter;
+use todo_cli::fulltext;
+use todo_cli::hooks::{self, Event};
+use todo_cli::i18n::{self, tr};
+use todo_cli::listing::Listing;
+use todo_cli::modify::Edits;
+use todo_cli::query::{Format, Query, View};
+use todo_cli::regex::Regex;
+use todo_cli::slack::Slack;
+use todo_cli::snapshot::Difference;
+use todo_cli::summary::{Segment, Summary};
+use todo_cli::task::{CheckItem, Comment, Waiting, CANCELLED, DONE, SHORT_HASH, TODO};
+use todo_cli::urgency::Urgency;
+use todo_cli::{
+    clipboard, daemon, date, deps, discord, error, habit, heatmap, holiday, http, ics, log, mail,
+    markdown, mdns, notify, order, qr, quickadd, script, search, secret, slack, snapshot, stats,
+    store, sync, template, timetrack, toml, tz, undo, validate, webhooks, Date, DateTime, Error,
+    Priority, Task, TaskStore,
+};
+
+mod cli;
+mod dashboard;
+mod man;
+mod mcp;
+mod rpc;
+mod serve;
+mod telegram;
+
+use cli::{CliError, Matches};
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("{}", tr("error", &[("error", &e)]));
+        process::exit(e.exit_code());
+    }
+}
+
+/// Runs one command. Errors are reported by `main`, which picks the exit code.
+fn run() -> todo_cli::Result<()> {
+    // Collect command-line arguments into a vector of strings, skipping the program name.
+    let mut args: Vec<String> = env::args().skip(1).collect();
+
+    // Pull out the global options wherever they appear, then layer the settings:
+    // config file, then environment variables, then the options themselves.
+    let globals = cli::extract(&cli::ROOT, &mut args).unwrap_or_else(|e| exit_with(e));
+    if globals.flag("version") {
+        println!("{} {}", cli::BIN_NAME, env!("CARGO_PKG_VERSION"));
+        return Ok(());
+    }
+    // Log to stderr right away; the log file is only known once the config is read.
+    log::init(globals.count("verbose"), None)?;
+    let config_path = match globals.value("config") {
+        Some(path) => PathBuf::from(path),
+        None => config::default_path(),
+    };
+    let mut config = Config::load(&config_path)?;
+    config.apply_env().map_err(Error::Invalid)?;
+    apply_globals(&globals, &mut config).unwrap_or_else(|e| exit_with(e));
+    i18n::init(config.language.as_deref());
+    tz::init(config.timezone.as_deref());
+    date::set_week_start(config.week_start);
+    holiday::init(&config.holidays)?;
+    log::init(config.verbosity, config.log_file.as_deref()).map_err(|e| {
+        let path = config
+            .log_file
+            .as_deref()
+            .unwrap_or(Path::new(""))
+            .display();
+        Error::Failed(tr("log-file-failed", &[("path", &path), ("error", &e)]))
+    })?;
+    log::debug(
+        "config",
+        format_args!(
+            "using {} and {} storage",
+            config_path.display(),
+            config.storage_backend
+        ),
+    );
+
+    if globals.flag("unlock") {
+        config.passphrase = Some(passphrase(&config, false)?);
+    }
+
+    if globals.flag("rpc") {
+        if let Some(command) = args.first() {
+            return Err(Error::Invalid(tr(
+                "rpc-with-command",
+                &[("command", command)],
+            )));
+        }
+        // A dry run would print its report into the protocol stream.
+        if config.dry_run {
+            return Err(Error::Invalid(tr("dry-run-rpc", &[])));
+        }
+        rpc::serve(&config)?;
+        return Ok(());
+    }
+
+    // Without a command, run the configured default or show the help.
+    if args.is_empty() {
+        match &config.default_command {
+            Some(command) => args.extend(command.split_whitespace().map(String::from)),
+            None => {
+                print!("{}", cli::ROOT.help(cli::BIN_NAME));
+                return Ok(());
+            }
+        }
+    }
+    expand_alias(&mut args, &config);
+
+    // Find the command, then parse the rest of the arguments against its spec.
+    let name = &args[0];
+    if name == "-h" || name == "--help" {
+        print!("{}", cli::ROOT.help(cli::BIN_NAME));
+        return Ok(());
+    }
+    let spec = cli::ROOT
+        .resolve(name)
+        .unwrap_or_else(|msg| exit_with(cli::usage_error(&cli::ROOT, cli::BIN_NAME, msg)));
+    let path = format!("{} {}", cli::BIN_NAME, spec.name);
+    let matches = cli::parse(spec, &args[1..], &path).unwrap_or_else(|e| exit_with(e));
+    audit::set_command(match &matches.subcommand {
+        Some((sub, _)) => format!("{} {}", spec.name, sub),
+        None => spec.name.to_string(),
+    });
+    // Refused before it runs, so that nothing says it was done.
+    if config.read_only && !config.dry_run && writes(spec.name, &matches) {
+        return Err(Error::ReadOnly(config.backend()?.location()));
+    }
+
+    match spec.name {
+        "add" => add_task(&matches, &config)?,
+        "paste" => paste_task(&matches, &config)?,
+        "template" => template_command(&matches, &config)?,
+        "view" => view_command(&matches, &config, &config_path)?,
+        "context" => context_command(&matches, &config, &config_path)?,
+        "list" => list_tasks(&matches, &config)?,
+        "search" => search_tasks(&matches, &config)?,
+        "watch" => watch_tasks(&matches, &config)?,
+        "due" => list_due(&config)?,
+        "prompt" => print_prompt(&config)?,
+        "segment" => print_segment(&matches, &config)?,
+        "tmux-status" => print_tmux_status(&config)?,
+        "snapshot" => take_snapshot(&matches, &config)?,
+        "snapshots" => list_snapshots(&config)?,
+        "restore" => restore_snapshot(&matches, &config)?,
+        "diff" => diff_lists(&matches, &config)?,
+        "undo" => undo_change(&matches, &config)?,
+        "trash" => trash_command(&matches, &config)?,
+        "estimate" => estimate_task(&matches, &config)?,
+        "time" => time_command(&matches, &config)?,
+        "timesheet" => show_timesheet(&matches, &config)?,
+        "prune" => prune_tasks(&matches, &config)?,
+        "import" => import_tasks(&matches, &config)?,
+        "export" => export_tasks(&matches, &config)?,
+        "done" => complete_task(&matches, &config)?,
+        "habit" => habit_command(&matches, &config)?,
+        "depend" => depend_task(&matches, &config)?,
+        "next" => next_tasks(&matches, &config)?,
+        "start" => start_task(&matches, &config)?,
+        "cancel" => cancel_task(&matches, &config)?,
+        "status" => status_task(&matches, &config)?,
+        "delete" => delete_task(&matches, &config)?,
+        "edit" => edit_task(&matches, &config)?,
+        "check" => check_task(&matches, &config)?,
+        "comment" => comment_task(&matches, &config)?,
+        "modify" => modify_tasks(&matches, &config)?,
+        "show" => show_task(&matches, &config)?,
+        "history" => show_history(&matches, &config)?,
+        "remind" => remind_task(&matches, &config)?,
+        "heatmap" => show_heatmap(&matches, &config)?,
+        "holidays" => list_holidays(&matches, &config)?,
+        "stats" => show_stats(&matches, &config)?,
+        "tags" => list_tags(&config)?,
+        "tag" => tag_command(&matches, &config)?,
+        "projects" => list_projects(&matches, &config)?,
+        "board" => show_board(&matches, &config)?,
+        "wait" => wait_task(&matches, &config)?,
+        "waiting" => list_waiting(&matches, &config)?,
+        "someday" => park_task(&matches, &config)?,
+        "pin" => pin_task(&matches, &config)?,
+        "secret" => secret_task(&matches, &config)?,
+        "qr" => qr_task(&matches, &config)?,
+        "ingest" => ingest_command(&matches, &config)?,
+        "mail" => mail_tasks(&matches, &config)?,
+        "slack" => slack_command(&matches, &config)?,
+        "discord" => discord_command(&matches, &config)?,
+        "telegram-bot" => telegram_bot(&config)?,
+        "move" => move_task(&matches, &config)?,
+        "up" => step_task(&matches, -1, &config)?,
+        "down" => step_task(&matches, 1, &config)?,
+        "review" => review(&config)?,
+        "reindex" => reindex(&config)?,
+        "clear" => clear_tasks(&matches, &config)?,
+        "notify" => notify_due(&matches, &config)?,
+        "notify-due" => notify_new(&matches, &config)?,
+        "daemon" => daemon_command(&matches, &config, &config_path)?,
+        "run" => run_script(&matches, &config)?,
+        "serve" => serve_command(&matches, &config)?,
+        "dashboard" => dashboard_command(&matches, &config)?,
+        "sync" => sync_command(&matches, &config)?,
+        "mcp" => mcp_command(&config)?,
+        "config" => config_command(&matches, &config, &config_path)?,
+        "man" => man_command(&matches)?,
+        "help" => print_help(&matches),
+        other => unreachable!("command '{}' has a spec but no handler", other),
+    }
+
+    Ok(())
+}
+
+/// Whether the command `name` changes the list, as run with `matches`.
+fn writes(name: &str, matches: &Matches) -> bool {
+    let sub = matches.subcommand.as_ref().map(|(sub, _)| *sub);
+    match name {
+        "add" | "paste" | "done" | "depend" | "start" | "cancel" | "delete" | "edit" | "modify"
+        | "remind" | "wait" | "someday" | "pin" | "secret" | "move" | "up" | "down" | "clear"
+        | "undo" | "restore" | "prune" | "import" | "ingest" | "estimate" | "tag" | "review" => {
+            true
+        }
+        "template" => sub == Some("apply"),
+        "trash" => matches!(sub, Some("restore" | "empty")),
+        "time" => matches!(sub, Some("start" | "stop" | "log")),
+        "habit" => matches.joined("task").is_some(),
+        "status" => matches.value("status").is_some(),
+        "comment" => matches.joined("text").is_some(),
+        "check" => matches
+            .value("action")
+            .is_some_and(|action| action != "list"),
+        _ => false,
+    }
+}
+
+/// Applies the global options on top of the settings loaded so far.
+fn apply_globals(globals: &Matches, config: &mut Config) -> Result<(), CliError> {
+    for (option, key) in [("file", "storage_path"), ("list", "list")] {
+        if let Some(value) = globals.value(option) {
+            config.apply(key, value).map_err(CliError::Invalid)?;
+        }
+    }
+    if let Some(sort) = globals.get::<SortOrder>("sort")? {
+        config.default_sort = sort;
+    }
+    if let Some(color) = globals.get::<ColorChoice>("color")? {
+        config.color = color;
+    }
+    config.plain |= globals.flag("plain");
+    config.assume_yes = globals.flag("yes");
+    config.verbosity = globals.count("verbose");
+    config.dry_run = globals.flag("dry-run");
+    config.read_only |= globals.flag("read-only");
+    Ok(())
+}
+
+/// Prints help or a usage error from the parser and exits.
+///
+/// Usage errors go to stderr with exit code 2, like other command-line tools.
+fn exit_with(err: CliError) -> ! {
+    match err {
+        CliError::Help(text) => {
+            print!("{}", text);
+            process::exit(0)
+        }
+        err => {
+            eprintln!("{}", err);
+            process::exit(error::EXIT_USAGE)
+        }
+    }
+}
+
+/// Replaces a user-defined alias in the command position with its expansion,
+/// and the name of a saved view with `view show NAME`.
+///
+/// Built-in commands always win, so an alias can never shadow one of them.
+fn expand_alias(args: &mut Vec<String>, config: &Config) {
+    let Some(command) = args.first() else { return };
+    if cli::ROOT.find(command).is_some() {
+        return;
+    }
+    if let Some(expansion) = config.aliases.get(command) {
+        let words: Vec<String> = expansion.split_whitespace().map(String::from).collect();
+        args.splice(0..1, words);
+    } else if config.views.contains_key(command) {
+        args.splice(0..0, ["view".to_string(), "show".to_string()]);
+    }
+}
+
+/// Adds new tasks to the list.
+///
+/// The description comes from the arguments, or from stdin when it is `-` or
+/// when no text is given and input is piped in. Each non-empty line read from
+/// stdin becomes its own task. With `-i`, the user is walked through prompts.
+fn add_task(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    // Details given as flags apply to every task added by this command, and
+    // then what the active context asks for, so the new tasks stay in it.
+    let mut template = task_template(matches)?;
+    if let Some(scope) = active_context(config)? {
+        scope.implied(&mut template);
+    }
+
+    let text = matches.values("text");
+    if matches.flag("interactive") {
+        return add_interactive(text.join(" "), template, matches.flag("no-dupes"), config);
+    }
+
+    let from_stdin = match text {
+        [dash] if dash == "-" => true,
+        [] => !io::stdin().is_terminal(),
+        _ => false,
+    };
+
+    let descriptions: Vec<String> = if from_stdin {
+        io::stdin()
+            .lines()
+            .map(|line| line.map(|l| l.trim().to_string()))
+            .filter(|line| !matches!(line, Ok(l) if l.is_empty()))
+            .collect::<io::Result<_>>()?
+    } else if text.is_empty() {
+        Vec::new()
+    } else {
+        vec![text.join(" ")]
+    };
+
+    if descriptions.is_empty() {
+        return Err(Error::Invalid(tr("add-missing-text", &[])));
+    }
+
+    let mut store = config.open_store()?;
+    let sealing = match matches.flag("secret") {
+        true => Some(sealing_passphrase(&store, config)?),
+        false => None,
+    };
+
+    let today = Date::today();
+    let mut added = Vec::new();
+    for task_description in descriptions {
+        let mut new_task = Task {
+            text: task_description,
+            ..template.clone()
+        };
+        if !matches.flag("raw") {
+            new_task = quickadd::apply(new_task, today)?;
+        }
+        if let Some((passphrase, like)) = &sealing {
+            // Its text is only a mask now, so there is nothing to compare.
+            secret::hide(&mut new_task, passphrase, like.as_deref())?;
+            added.push(add_announced(&mut store, new_task)?);
+            continue;
+        }
+        added.extend(add_checked(
+            &mut store,
+            new_task,
+            matches.flag("no-dupes"),
+            config,
+        )?);
+    }
+    let events: Vec<_> = added
+        .iter()
+        .map(|&i| (Event::Add, &store.tasks()[i]))
+        .collect();
+    save(config, &store, &events)
+}
+
+/// A task with the due date, priority and tags given as options, for `add`
+/// and `paste`.
+fn task_template(matches: &Matches) -> todo_cli::Result<Task> {
+    let mut template = Task::default();
+    if let Some(due) = matches.value("due") {
+        template.set_due(Some(validate::due_time(due, Date::today())?));
+    }
+    if let Some(priority) = matches.value("priority") {
+        template.priority = Some(validate::priority(priority)?);
+    }
+    template.tags = matches
+        .values("tag")
+        .iter()
+        .map(|t| validate::tag(t))
+        .collect::<todo_cli::Result<_>>()?;
+    if let Some(every) = matches.value("habit") {
+        let (every, business_days) = validate::habit(every)?;
+        template.habit = Some(habit::Habit::new(every, business_days));
+        // A new habit is expected today.
+        template.due = template.due.or_else(|| Some(Date::today()));
+    }
+    Ok(template)
+}
+
+/// Lists, saves, deletes or shows the views in the config file.
+fn view_command(matches: &Matches, config: &Config, config_path: &Path) -> todo_cli::Result<()> {
+    let find = |name: &str| {
+        config.views.get(name).ok_or_else(|| {
+            let known: Vec<&str> = config.views.keys().map(String::as_str).collect();
+            Error::Invalid(tr(
+                "view-unknown",
+                &[("name", &name), ("known", &known.join(", "))],
+            ))
+        })
+    };
+    match &matches.subcommand {
+        Some(("show", sub)) => {
+            let name = sub.value("name").unwrap_or_default();
+            show_view(&View::parse(find(name)?, Date::today())?, config)?;
+        }
+        Some(("save", sub)) => {
+            let name = sub.value("name").unwrap_or_default();
+            // `todo NAME` could never reach a view hidden by a command or alias.
+            if cli::ROOT.find(name).is_some() || config.aliases.contains_key(name) {
+                return Err(Error::Invalid(tr("view-name-taken", &[("name", &name)])));
+            }
+            let definition = sub.values("query").join(" ");
+            View::parse(&definition, Date::today())?;
+            let key = format!("views.{}", name);
+            if config.dry_run {
+                println!(
+                    "{}",
+                    tr("dry-run-config", &[("key", &key), ("value", &definition)])
+                );
+                return Ok(());
+            }
+            config::set(config_path, &key, &definition)?;
+            println!("{}", tr("view-saved", &[("name", &name)]));
+        }
+        Some(("delete", sub)) => {
+            let name = sub.value("name").unwrap_or_default();
+            find(name)?;
+            if !config.dry_run {
+                config::unset(config_path, &format!("views.{}", name))?;
+            }
+            println!("{}", tr("view-deleted", &[("name", &name)]));
+        }
+        _ => {
+            if config.views.is_empty() {
+                println!("{}", tr("view-none", &[]));
+            }
+            for (name, definition) in &config.views {
+                println!("{}: {}", name, definition);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Shows, switches, defines or deletes the contexts in the config file.
+fn context_command(matches: &Matches, config: &Config, config_path: &Path) -> todo_cli::Result<()> {
+    const RESERVED: &[&str] = &["none", "define", "delete"];
+    let args = matches.values("args");
+    let find = |name: &str| {
+        config.contexts.get(name).ok_or_else(|| {
+            let known: Vec<&str> = config.contexts.keys().map(String::as_str).collect();
+            Error::Invalid(tr(
+                "context-unknown",
+                &[("name", &name), ("known", &known.join(", "))],
+            ))
+        })
+    };
+    let name = || {
+        args.first()
+            .map(String::as_str)
+            .ok_or_else(|| Error::Invalid(tr("context-name-missing", &[])))
+    };
+    // Writes `key` to the config file, or says what it would write.
+    let set = |key: &str, value: &str| -> todo_cli::Result<()> {
+        if config.dry_run {
+            println!(
+                "{}",
+                tr("dry-run-config", &[("key", &key), ("value", &value)])
+            );
+            return Ok(());
+        }
+        config::set(config_path, key, value)
+    };
+    // Removes `key` from the config file, or says what it would remove.
+    let unset = |key: &str| -> todo_cli::Result<()> {
+        if config.dry_run {
+            println!("{}", tr("dry-run-unset", &[("key", &key)]));
+            return Ok(());
+        }
+        config::unset(config_path, key).map(|_| ())
+    };
+    match matches.value("action") {
+        None => {
+            match &config.context {
+                Some(name) => println!("{}", tr("context-current", &[("name", name)])),
+                None => println!("{}", tr("context-off", &[])),
+            }
+            if config.contexts.is_empty() {
+                println!("{}", tr("context-none", &[]));
+            }
+            for (name, expression) in &config.contexts {
+                println!("{}: {}", name, expression);
+            }
+        }
+        Some("none") => {
+            unset("context")?;
+            if !config.dry_run {
+                println!("{}", tr("context-cleared", &[]));
+            }
+        }
+        Some("define") => {
+            let name = name()?;
+            if RESERVED.contains(&name) {
+                return Err(Error::Invalid(tr(
+                    "context-name-reserved",
+                    &[("name", &name)],
+                )));
+            }
+            let expression = args[1..].join(" ");
+            if expression.trim().is_empty() {
+                return Err(Error::Invalid(tr(
+                    "context-expression-missing",
+                    &[("name", &name)],
+                )));
+            }
+            Query::parse(&expression, Date::today())?;
+            set(&format!("contexts.{}", name), &expression)?;
+            if !config.dry_run {
+                println!("{}", tr("context-defined", &[("name", &name)]));
+            }
+        }
+        Some("delete") => {
+            let name = name()?;
+            find(name)?;
+            unset(&format!("contexts.{}", name))?;
+            if config.context.as_deref() == Some(name) {
+                unset("context")?;
+            }
+            if !config.dry_run {
+                println!("{}", tr("context-deleted", &[("name", &name)]));
+            }
+        }
+        Some(name) => {
+            find(name)?;
+            set("context", name)?;
+            if !config.dry_run {
+                println!("{}", tr("context-set", &[("name", &name)]));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Lists the tasks of a view, sorted and printed as it says.
+fn show_view(view: &View, config: &Config) -> todo_cli::Result<()> {
+    let today = Date::today();
+    let backend = config.backend()?;
+    let mut listing = Listing {
+        location: backend.location(),
+        version: backend.version()?,
+        ids: Vec::new(),
+    };
+    let pending = scan_pending(backend.as_ref())?;
+    let pending_ids: HashSet<u64> = pending.keys().copied().collect();
+    let scope = active_context(config)?;
+    let mut numbered: Vec<(usize, Task)> = Vec::new();
+    backend.scan(&mut |index, task| {
+        listing.ids.push(task.id);
+        if view.query.matches(&task, today, &pending_ids)
+            && scope
+                .as_ref()
+                .is_none_or(|scope| scope.matches(&task, today, &pending_ids))
+        {
+            numbered.push((index, task));
+        }
+    })?;
+    remember(listing);
+    sort_tasks(
+        &mut numbered,
+        view.sort.unwrap_or(config.default_sort),
+        &config.urgency,
+    );
+
+    match view.format {
+        Format::Json => {
+            let tasks: Vec<&Task> = numbered.iter().map(|(_, task)| task).collect();
+            let json =
+                serde_json::to_string_pretty(&tasks).expect("Failed to serialize data to JSON.");
+            println!("{}", json);
+        }
+        _ if numbered.is_empty() => println!("{}", tr("list-no-matches", &[])),
+        Format::Compact => {
+            for (index, task) in &numbered {
+                println!("{}. {}", index + 1, task.text);
+            }
+        }
+        Format::List => {
+            let color = use_color(config);
+            print_list_header(config);
+            for (index, task) in &numbered {
+                print_task(*index, task, &pending, config, today, color);
+            }
+            print_list_footer(numbered.len(), config);
+        }
+    }
+    Ok(())
+}
+
+/// Lists, shows or applies the templates in the config file.
+fn template_command(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let find = |name: &str| {
+        config.templates.get(name).ok_or_else(|| {
+            let known: Vec<&str> = config.templates.keys().map(String::as_str).collect();
+            Error::Invalid(tr(
+                "template-unknown",
+                &[("name", &name), ("known", &known.join(", "))],
+            ))
+        })
+    };
+    match &matches.subcommand {
+        Some(("show", sub)) => {
+            let tasks = find(sub.value("name").unwrap_or_default())?;
+            for line in template::lines(tasks) {
+                println!("{}", line);
+            }
+        }
+        Some(("apply", sub)) => {
+            let tasks = find(sub.value("name").unwrap_or_default())?;
+            let vars = sub
+                .values("var")
+                .iter()
+                .map(|spec| template::parse_var(spec))
+                .collect::<todo_cli::Result<_>>()?;
+            // Fill in every task before adding any, so a missing variable
+            // leaves the list alone.
+            let mut defaults = task_template(sub)?;
+            if let Some(scope) = active_context(config)? {
+                scope.implied(&mut defaults);
+            }
+            let today = Date::today();
+            let new_tasks = template::expand(tasks, &vars)?
+                .into_iter()
+                .map(|text| {
+                    quickadd::apply(
+                        Task {
+                            text,
+                            ..defaults.clone()
+                        },
+                        today,
+                    )
+                })
+                .collect::<todo_cli::Result<Vec<_>>>()?;
+
+            let mut store = config.open_store()?;
+            let mut added = Vec::new();
+            for task in new_tasks {
+                added.extend(add_checked(&mut store, task, false, config)?);
+            }
+            let events: Vec<_> = added
+                .iter()
+                .map(|&i| (Event::Add, &store.tasks()[i]))
+                .collect();
+            save(config, &store, &events)?;
+        }
+        _ if config.templates.is_empty() => println!("{}", tr("template-none", &[])),
+        _ => {
+            for (name, tasks) in &config.templates {
+                let count = template::lines(tasks).count();
+                let vars = template::variables(tasks)
+                    .iter()
+                    .map(|name| format!(" --var {}=...", name))
+                    .collect::<String>();
+                println!(
+                    "{}",
+                    tr(
+                        "template-line",
+                        &[("name", name), ("count", &count), ("vars", &vars)]
+                    )
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Adds a task from the clipboard: its first line is the text, and anything
+/// after it the notes.
+fn paste_task(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let mut task = task_template(matches)?;
+    if let Some(scope) = active_context(config)? {
+        scope.implied(&mut task);
+    }
+    let contents =
+        clipboard::read().map_err(|e| Error::Failed(tr("paste-failed", &[("error", &e)])))?;
+    let mut lines = contents.trim_start().lines();
+    task.text = lines.next().unwrap_or_default().to_string();
+    if task.text.trim().is_empty() {
+        return Err(Error::Invalid(tr("paste-empty", &[])));
+    }
+    task.notes = validate::notes(&lines.collect::<Vec<_>>().join("\n"));
+
+    let mut store = config.open_store()?;
+    match add_checked(&mut store, task, matches.flag("no-dupes"), config)? {
+        Some(index) => save(config, &store, &[(Event::Add, &store.tasks()[index])]),
+        None => save(config, &store, &[]),
+    }
+}
+
+/// Walks the user through adding one task, prompting for each field.
+///
+/// Anything already given on the command line becomes the default answer.
+fn add_interactive(
+    text: String,
+    template: Task,
+    no_dupes: bool,
+    config: &Config,
+) -> todo_cli::Result<()> {
+    if !io::stdin().is_terminal() {
+        return Err(Error::Invalid(tr("add-needs-terminal", &[])));
+    }
+    let today = Date::today();
+
+    let text = ask(&tr("prompt-task", &[]), &text, |answer| {
+        validate::text(answer, config.max_task_length).map_err(|e| e.to_string())
+    })?;
+
+    let due_default = match template.due_at {
+        Some(at) => tz::local(at).to_string(),
+        None => template.due.map(|d| d.to_string()).unwrap_or_default(),
+    };
+    let due = ask(&tr("prompt-due", &[]), &due_default, |answer| {
+        if answer.trim().is_empty() {
+            Ok(None)
+        } else {
+            validate::due_time(answer, today)
+                .map(Some)
+                .map_err(|e| e.to_string())
+        }
+    })?;
+
+    let priority_default = template.priority.map(|p| p.to_string()).unwrap_or_default();
+    let priority = ask(&tr("prompt-priority", &[]), &priority_default, |answer| {
+        if answer.trim().is_empty() {
+            Ok(None)
+        } else {
+            validate::priority(answer)
+                .map(Some)
+                .map_err(|e| e.to_string())
+        }
+    })?;
+
+    let tags = ask(
+        &tr("prompt-tags", &[]),
+        &template.tags.join(" "),
+        |answer| {
+            answer
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|t| !t.is_empty())
+                .map(|t| validate::tag(t).map_err(|e| e.to_string()))
+                .collect::<Result<Vec<_>, _>>()
+        },
+    )?;
+
+    let list_default = config.list.clone().unwrap_or_default();
+    let list = ask(&tr("prompt-list", &[]), &list_default, |answer| {
+        let mut check = config.clone();
+        check.apply("list", answer.trim()).map(|()| check)
+    })?;
+
+    let mut new_task = Task {
+        text,
+        priority,
+        tags,
+        ..template
+    };
+    new_task.set_due(due);
+    let mut store = list.open_store()?;
+    match add_checked(&mut store, new_task, no_dupes, &list)? {
+        Some(index) => save(&list, &store, &[(Event::Add, &store.tasks()[index])]),
+        None => save(&list, &store, &[]),
+    }
+}
+
+/// Adds `task`, unless it duplicates a pending task. Returns its index, or
+/// `None` if the existing task was bumped instead.
+///
+/// A duplicate is refused with `--no-dupes`. Otherwise it is added with a
+/// warning, unless the user, asked at a terminal, would rather bump the
+/// existing task: its priority goes up a level, it takes the new due date
+/// if that is sooner, and it gains the new tags.
+fn add_checked(
+    store: &mut TaskStore,
+    task: Task,
+    no_dupes: bool,
+    config: &Config,
+) -> todo_cli::Result<Option<usize>> {
+    let Some(index) = store.find_duplicate(&task.text) else {
+        return add_announced(store, task).map(Some);
+    };
+    let number = index + 1;
+    let existing = store.tasks()[index].clone();
+    if no_dupes {
+        return Err(Error::Invalid(tr(
+            "add-duplicate-refused",
+            &[
+                ("task", &task.text),
+                ("number", &number),
+                ("existing", &existing.text),
+            ],
+        )));
+    }
+    log::warn(
+        "add",
+        format_args!(
+            "{}",
+            tr(
+                "add-duplicate",
+                &[("number", &number), ("task", &existing.text)]
+            )
+        ),
+    );
+    let offer = io::stdin().is_terminal() && !config.assume_yes && !config.dry_run;
+    if !offer || !ask_yes(&tr("confirm-bump", &[("number", &number)]))? {
+        return add_announced(store, task).map(Some);
+    }
+    let mut bumped = existing;
+    let priority = Priority::raised(bumped.priority);
+    bumped.priority = Some(priority);
+    // The sooner due date wins, with its time if it has one.
+    if task.due.is_some() && bumped.due.is_none_or(|current| task.due < Some(current)) {
+        bumped.due = task.due;
+        bumped.due_at = task.due_at;
+    }
+    for tag in task.tags {
+        if !bumped.tags.contains(&tag) {
+            bumped.tags.push(tag);
+        }
+    }
+    println!(
+        "{}",
+        tr(
+            "task-bumped",
+            &[
+                ("number", &number),
+                ("task", &bumped.text),
+                ("priority", &priority)
+            ]
+        )
+    );
+    store.replace(index, bumped)?;
+    Ok(None)
+}
+
+/// Adds `task` and says so, with its text as stored.
+fn add_announced(store: &mut TaskStore, task: Task) -> todo_cli::Result<usize> {
+    let index = store.add(task)?;
+    println!(
+        "{}",
+        tr("task-adding", &[("task", &store.tasks()[index].text)])
+    );
+    Ok(index)
+}
+
+/// Asks one question on the terminal until `validate` accepts the answer.
+///
+/// An empty answer means the default, which is shown in brackets.
+fn ask<T>(
+    question: &str,
+    default: &str,
+    validate: impl Fn(&str) -> Result<T, String>,
+) -> todo_cli::Result<T> {
+    loop {
+        if default.is_empty() {
+            print!("{}: ", question);
+        } else {
+            print!("{} [{}]: ", question, default);
+        }
+        io::stdout().flush()?;
+
+        let mut answer = String::new();
+        if io::stdin().read_line(&mut answer)? == 0 {
+            // Ctrl-D: there's no sensible way to continue.
+            println!();
+            return Err(Error::Failed(tr("input-closed", &[])));
+        }
+        let answer = answer.trim_end_matches(['\r', '\n']);
+        let answer = if answer.trim().is_empty() {
+            default
+        } else {
+            answer
+        };
+        match validate(answer) {
+            Ok(value) => return Ok(value),
+            Err(msg) => println!("  {}", msg),
+        }
+    }
+}
+
+/// Lists the current tasks that pass the filters, showing their status.
+///
+/// Tasks are read one at a time and only those passing the filters are kept,
+/// so a huge list costs little memory. Without a sort order they aren't kept
+/// at all: each is printed as soon as it is read.
+fn list_tasks(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let mut filter = task_filter(matches, config)?;
+    if let Some(name) = config.context.as_deref().filter(|_| filter.scope.is_some()) {
+        eprintln!("{}", tr("context-note", &[("name", &name)]));
+    }
+
+    let color = use_color(config);
+    let today = Date::today();
+    let streaming = config.default_sort == SortOrder::None;
+    let mut total = 0;
+    // Keep each task's original number so `done` still works after sorting.
+    let mut numbered: Vec<(usize, Task)> = Vec::new();
+    let mut printed = 0;
+    let backend = config.backend()?;
+    let mut listing = Listing {
+        location: backend.location(),
+        version: backend.version()?,
+        ids: Vec::new(),
+    };
+    // Which tasks are pending, to tell which tasks they block.
+    let pending = scan_pending(backend.as_ref())?;
+    filter.pending = pending.keys().copied().collect();
+    backend.scan(&mut |index, task| {
+        total += 1;
+        listing.ids.push(task.id);
+        if !filter.matches(&task) {
+            return;
+        }
+        if !streaming {
+            numbered.push((index, task));
+            return;
+        }
+        if printed == 0 {
+            print_list_header(config);
+        }
+        print_task(index, &task, &pending, config, today, color);
+        printed += 1;
+    })?;
+    remember(listing);
+
+    if total == 0 {
+        println!("{}", tr("list-empty", &[]));
+        return Ok(());
+    }
+    sort_tasks(&mut numbered, config.default_sort, &config.urgency);
+    if printed == 0 && numbered.is_empty() {
+        println!("{}", tr("list-no-matches", &[]));
+        return Ok(());
+    }
+    if !streaming {
+        print_list_header(config);
+    }
+    for (index, task) in &numbered {
+        print_task(*index, task, &pending, config, today, color);
+    }
+    print_list_footer(printed + numbered.len(), config);
+    Ok(())
+}
+
+/// Prints the rule above a list of tasks; a screen reader is spared it.
+fn print_list_header(config: &Config) {
+    if !config.plain {
+        println!("{}", tr("list-header", &[]));
+    }
+}
+
+/// Prints the rule below a list of `count` tasks, or in plain output, how
+/// many there were.
+fn print_list_footer(count: usize, config: &Config) {
+    match config.plain {
+        true => println!("{}", tr("plain-list-end", &[("count", &count)])),
+        false => println!("{}", tr("list-footer", &[])),
+    }
+}
+
+/// Sorts tasks, each with its index, in `order`, keeping list order among
+/// equals.
+fn sort_tasks(numbered: &mut [(usize, Task)], order: SortOrder, urgency: &Urgency) {
+    match order {
+        SortOrder::None => {}
+        SortOrder::Text => numbered.sort_by_key(|(_, task)| search::fold(&task.text)),
+        SortOrder::Status => numbered.sort_by_key(|(_, task)| task.completed),
+        SortOrder::Due => {
+            numbered.sort_by_key(|(_, task)| (task.due.is_none(), task.due, task.due_at))
+        }
+        SortOrder::Priority => numbered.sort_by_key(|(_, task)| std::cmp::Reverse(task.priority)),
+        SortOrder::Urgency => {
+            let now = DateTime::now();
+            numbered
+                .sort_by(|(_, a), (_, b)| urgency.score(b, now).total_cmp(&urgency.score(a, now)));
+        }
+        SortOrder::Manual => numbered.sort_by_key(|(_, task)| (task.rank.is_none(), task.rank)),
+    }
+}
+
+/// Lists the tasks that have the words searched for, best matches first, or
+/// just those matching the filters in list order without any words.
+fn search_tasks(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let mut filter = task_filter(matches, config)?;
+    // The index finds the words, among the tasks passing the other filters.
+    let Some(query) = filter.words.take().filter(|words| !words.trim().is_empty()) else {
+        return list_tasks(matches, config);
+    };
+    let backend = config.backend()?;
+    let mut listing = Listing {
+        location: backend.location(),
+        version: backend.version()?,
+        ids: Vec::new(),
+    };
+    let pending = scan_pending(backend.as_ref())?;
+    filter.pending = pending.keys().copied().collect();
+    let mut index = fulltext::Index::new();
+    let mut candidates: Vec<(usize, Task)> = Vec::new();
+    backend.scan(&mut |number, task| {
+        listing.ids.push(task.id);
+        if filter.matches(&task) {
+            index.add(&task);
+            candidates.push((number, task));
+        }
+    })?;
+    remember(listing);
+
+    let found = index.search(&query);
+    if found.is_empty() {
+        println!("{}", tr("list-no-matches", &[]));
+        return Ok(());
+    }
+    let color = use_color(config);
+    let today = Date::today();
+    print_list_header(config);
+    for (doc, _) in &found {
+        let (number, task) = &candidates[*doc];
+        print_task(*number, task, &pending, config, today, color);
+    }
+    print_list_footer(found.len(), config);
+    Ok(())
+}
+
+/// Keeps the list on screen, drawing it again whenever the file changes and
+/// every `--interval` seconds, until the process is stopped.
+fn watch_tasks(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let interval: u64 = matches
+        .get("interval")
+        .unwrap_or_else(|e| exit_with(e))
+        .unwrap_or(10);
+    let interval = Duration::from_secs(interval.max(1));
+    let backend = config.backend()?;
+    let terminal = io::stdout().is_terminal();
+    let modified = || {
+        backend
+            .path()
+            .and_then(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+    };
+    loop {
+        let seen = modified();
+        if terminal {
+            // Clear the screen and start again at the top.
+            print!("\x1b[2J\x1b[H");
+        }
+        println!(
+            "{}",
+            tr(
+                "watch-header",
+                &[
+                    ("seconds", &interval.as_secs()),
+                    ("time", &format_date_time(DateTime::now(), config))
+                ]
+            )
+        );
+        if let Err(e) = list_tasks(matches, config) {
+            eprintln!("{}", tr("error", &[("error", &e)]));
+        }
+        io::stdout().flush()?;
+        // A list kept in a file is drawn again as soon as it changes.
+        let next = Instant::now() + interval;
+        while Instant::now() < next && modified() == seen {
+            thread::sleep(Duration::from_millis(500));
+        }
+        if !terminal {
+            println!();
+        }
+    }
+}
+
+/// Lists the pending tasks with due dates under headings for how soon they
+/// are due: overdue, today, tomorrow, this week and later.
+fn list_due(config: &Config) -> todo_cli::Result<()> {
+    let store = config.open_store()?;
+    let today = Date::today();
+    let pending = pending_ids(store.tasks());
+    let mut groups = agenda::group(store.tasks(), today);
+    if let Some(scope) = active_context(config)? {
+        let ids: HashSet<u64> = pending.keys().copied().collect();
+        for (_, tasks) in &mut groups {
+            tasks.retain(|(_, task)| scope.matches(task, today, &ids));
+        }
+        groups.retain(|(_, tasks)| !tasks.is_empty());
+    }
+    if groups.is_empty() {
+        println!("{}", tr("due-none", &[]));
+        return Ok(());
+    }
+    let color = use_color(config);
+    for (i, (bucket, tasks)) in groups.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let heading = tr(
+            "due-heading",
+            &[
+                ("bucket", &tr(bucket.message(), &[])),
+                ("count", &tasks.len()),
+            ],
+        );
+        match bucket {
+            agenda::Bucket::Overdue if color => println!("\x1b[1;31m{}\x1b[0m", heading),
+            _ if color => println!("\x1b[1m{}\x1b[0m", heading),
+            _ => println!("{}", heading),
+        }
+        for (index, task) in tasks {
+            print_task(*index, task, &pending, config, today, color);
+        }
+    }
+    Ok(())
+}
+
+/// Prints the terse summary for a shell prompt, e.g. `3!/7`. Only the counts
+/// are read, so it is cheap enough to run every time the prompt is drawn.
+fn print_prompt(config: &Config) -> todo_cli::Result<()> {
+    let summary = Summary::read(config.backend()?.as_ref(), Date::today())?;
+    let terse = summary.terse();
+    if !terse.is_empty() {
+        println!("{}", terse);
+    }
+    Ok(())
+}
+
+/// Prints the prompt segment in the form `--format` asks for, hidden unless
+/// the counts reach `--min-pending` and `--min-overdue`.
+fn print_segment(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let format: Segment = match matches.value("format") {
+        Some(format) => format.parse().map_err(Error::Invalid)?,
+        None => Segment::Starship,
+    };
+    let min_pending: usize = matches
+        .get("min-pending")
+        .unwrap_or_else(|e| exit_with(e))
+        .unwrap_or(1);
+    let min_overdue: usize = matches
+        .get("min-overdue")
+        .unwrap_or_else(|e| exit_with(e))
+        .unwrap_or(0);
+    let summary = Summary::read(config.backend()?.as_ref(), Date::today())?;
+    let segment = summary.segment(format, summary.shows(min_pending, min_overdue));
+    if !segment.is_empty() {
+        println!("{}", segment);
+    }
+    Ok(())
+}
+
+/// Prints the counts for tmux's status line, colored with tmux markup unless
+/// color is turned off.
+fn print_tmux_status(config: &Config) -> todo_cli::Result<()> {
+    let summary = Summary::read(config.backend()?.as_ref(), Date::today())?;
+    let status = summary.tmux(config.color != ColorChoice::Never);
+    if !status.is_empty() {
+        println!("{}", status);
+    }
+    Ok(())
+}
+
+/// Prints every task as JSON, or with `--waybar` the JSON a Waybar custom
+/// module reads.
+fn export_tasks(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let backend = config.backend()?;
+    if matches.flag("waybar") {
+        let mut due = Vec::new();
+        let summary = Summary::read_with(backend.as_ref(), Date::today(), &mut |index, task| {
+            due.push((index, task))
+        })?;
+        println!("{}", summary.waybar(&due));
+        return Ok(());
+    }
+    let mut filter = task_filter(matches, config)?;
+    let tasks = backend.load()?;
+    // Without any filter, every task, as a backup wants, someday/maybe too.
+    let filtered = !filter.is_empty() || filter.someday || filter.scope.is_some();
+    filter.pending = pending_ids(&tasks).into_keys().collect();
+    let tasks: Vec<&Task> = tasks
+        .iter()
+        .filter(|task| !filtered || filter.matches(task))
+        .collect();
+    if matches.flag("markdown") {
+        print!("{}", markdown::render(&tasks));
+        return Ok(());
+    }
+    let json = serde_json::to_string_pretty(&tasks).expect("Failed to serialize data to JSON.");
+    println!("{}", json);
+    Ok(())
+}
+
+/// Sends the tasks the filters let through by email, pending ones unless
+/// asked otherwise.
+fn mail_tasks(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let url = config
+        .smtp_url
+        .clone()
+        .ok_or_else(|| Error::Config(tr("mail-smtp-missing", &[])))?;
+    let from = config
+        .mail_from
+        .clone()
+        .or_else(|| config.smtp_user.clone().filter(|user| user.contains('@')))
+        .ok_or_else(|| Error::Config(tr("mail-from-missing", &[])))?;
+
+    let mut filter = task_filter(matches, config)?;
+    filter.completed.get_or_insert(false);
+    let tasks = config.backend()?.load()?;
+    filter.pending = pending_ids(&tasks).into_keys().collect();
+    let mut numbered: Vec<(usize, Task)> = tasks
+        .into_iter()
+        .enumerate()
+        .filter(|(_, task)| filter.matches(task))
+        .collect();
+    if numbered.is_empty() {
+        println!("{}", tr("list-no-matches", &[]));
+        return Ok(());
+    }
+    sort_tasks(&mut numbered, config.default_sort, &config.urgency);
+
+    let today = Date::today();
+    let count = numbered.len();
+    let intro = tr(
+        "mail-intro",
+        &[
+            ("count", &count),
+            ("date", &today.format(&config.date_format)),
+        ],
+    );
+    let mut text = format!("{}\n\n", intro);
+    for (index, task) in &numbered {
+        let mark = if task.completed { "[x]" } else { "[ ]" };
+        text.push_str(&format!("{} {}. {}", mark, index + 1, task.text));
+        for detail in plain_details(task, config) {
+            text.push(' ');
+            text.push_str(&detail);
+        }
+        text.push('\n');
+    }
+    let html = matches.flag("html").then(|| {
+        let items: String = numbered
+            .iter()
+            .map(|(index, task)| dashboard::item(*index, task, config, today))
+            .collect();
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<style>{}</style>\n</head>\n\
+             <body>\n<p>{}</p>\n<ul>\n{}</ul>\n</body>\n</html>\n",
+            dashboard::STYLE,
+            dashboard::escape(&intro),
+            items
+        )
+    });
+    let message = mail::Outgoing {
+        from,
+        to: matches.values("to").to_vec(),
+        subject: match matches.value("subject") {
+            Some(subject) => subject.to_string(),
+            None => tr("mail-subject", &[("count", &count)]),
+        },
+        text,
+        html,
+    };
+    if config.dry_run {
+        print!("{}", message.compose(DateTime::now()).replace("\r\n", "\n"));
+        return Ok(());
+    }
+    let smtp = mail::Smtp {
+        url,
+        user: config.smtp_user.clone(),
+        password: config.smtp_password.clone(),
+        timeout: config.http.timeout,
+    };
+    smtp.send(&message)
+        .map_err(|e| Error::Failed(tr("mail-failed", &[("error", &e)])))?;
+    println!(
+        "{}",
+        tr(
+            "mail-sent",
+            &[("count", &count), ("to", &message.to.join(", "))]
+        )
+    );
+    Ok(())
+}
+
+/// Where Slack messages go: the bot token's channel, or else the webhook.
+fn slack_target(config: &Config) -> todo_cli::Result<Slack> {
+    match (&config.slack_token, &config.slack_webhook_url) {
+        (Some(token), _) => {
+            let channel = config
+                .slack_channel
+                .clone()
+                .ok_or_else(|| Error::Config(tr("slack-channel-missing", &[])))?;
+            Ok(Slack::Bot {
+                token: token.clone(),
+                channel,
+            })
+        }
+        (None, Some(url)) => Ok(Slack::Webhook(url.clone())),
+        (None, None) => Err(Error::Config(tr("slack-missing", &[]))),
+    }
+}
+
+/// Posts a summary of the list to Slack, or alerts for the tasks that need
+/// attention: once, or every `--every` seconds until the process is stopped.
+fn slack_command(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let target = slack_target(config)?;
+    let Some(("alerts", sub)) = &matches.subcommand else {
+        let tasks = config.backend()?.load()?;
+        let text = slack::summary(
+            &tasks,
+            Date::today(),
+            &config.date_format,
+            config.list.as_deref(),
+        );
+        if post_to_slack(&target, config, &text)? {
+            println!("{}", tr("slack-posted", &[]));
+        }
+        return Ok(());
+    };
+    let every: Option<u64> = sub.get("every").unwrap_or_else(|e| exit_with(e));
+    loop {
+        match slack_alerts(&target, config) {
+            // Slack being out of reach now doesn't stop the next check.
+            Err(e) if every.is_some() => eprintln!("{}", tr("error", &[("error", &e)])),
+            result => result?,
+        }
+        let Some(every) = every else {
+            return Ok(());
+        };
+        thread::sleep(Duration::from_secs(every.max(1)));
+    }
+}
+
+/// Posts one alert for the tasks that became overdue or high priority since
+/// the last time, remembering them so they aren't alerted about again.
+fn slack_alerts(target: &Slack, config: &Config) -> todo_cli::Result<()> {
+    let path = slack::alerted_path(config.list.as_deref());
+    let mut alerted = slack::Alerted::load(&path)?;
+    let tasks = config.backend()?.load()?;
+    let alerts = alerted.check(&tasks, Date::today());
+    if let Some(text) = slack::alert(&alerts, &config.date_format) {
+        if post_to_slack(target, config, &text)? {
+            println!("{}", tr("slack-alerted", &[("count", &alerts.len())]));
+        }
+    }
+    if config.dry_run {
+        return Ok(());
+    }
+    alerted.save(&path, &tasks)
+}
+
+/// Posts `text` to Slack, or prints it with --dry-run. Returns whether it
+/// was posted.
+fn post_to_slack(target: &Slack, config: &Config, text: &str) -> todo_cli::Result<bool> {
+    if config.dry_run {
+        println!("{}", text);
+        return Ok(false);
+    }
+    target
+        .post(&config.http, text)
+        .map_err(|e| Error::Failed(tr("slack-failed", &[("error", &e)])))?;
+    Ok(true)
+}
+
+/// Posts the tasks coming due to the list's Discord channel: once, or every
+/// `--every` seconds until the process is stopped.
+fn discord_command(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let url = config.discord_webhook().ok_or_else(|| {
+        Error::Config(tr(
+            "discord-missing",
+            &[("list", &config.list.as_deref().unwrap_or("main"))],
+        ))
+    })?;
+    let within: u32 = matches
+        .get("within")
+        .unwrap_or_else(|e| exit_with(e))
+        .unwrap_or(1);
+    let every: Option<u64> = matches.get("every").unwrap_or_else(|e| exit_with(e));
+    loop {
+        match post_due_soon(config, url, within) {
+            // Discord being out of reach now doesn't stop the next check.
+            Err(e) if every.is_some() => eprintln!("{}", tr("error", &[("error", &e)])),
+            result => result?,
+        }
+        let Some(every) = every else {
+            return Ok(());
+        };
+        thread::sleep(Duration::from_secs(every.max(1)));
+    }
+}
+
+/// Posts the tasks due within `within` days that weren't posted before,
+/// remembering them so they aren't posted again.
+fn post_due_soon(config: &Config, url: &str, within: u32) -> todo_cli::Result<()> {
+    let path = discord::posted_path(config.list.as_deref());
+    let mut posted = discord::Posted::load(&path)?;
+    let tasks = config.backend()?.load()?;
+    let today = Date::today();
+    let due = posted.due_soon(&tasks, today, within);
+    if let Some(text) = discord::due_soon(&due, today, &config.date_format) {
+        if config.dry_run {
+            println!("{}", text);
+            return Ok(());
+        }
+        discord::post(&config.http, url, &text)
+            .map_err(|e| Error::Failed(tr("discord-failed", &[("error", &e)])))?;
+        println!("{}", tr("discord-posted", &[("count", &due.len())]));
+    }
+    if config.dry_run {
+        return Ok(());
+    }
+    posted.save(&path, &tasks)
+}
+
+/// Runs the Telegram bot until the process is stopped.
+fn telegram_bot(config: &Config) -> todo_cli::Result<()> {
+    let token = config
+        .telegram_token
+        .as_deref()
+        .ok_or_else(|| Error::Config(tr("telegram-token-missing", &[])))?;
+    if config.telegram_chats.is_empty() {
+        eprintln!("{}", tr("telegram-no-chats", &[]));
+    }
+    telegram::run(config, token)
+}
+
+/// Adds the tasks of a file in another format: the items of Markdown
+/// checklists, or the events of a calendar.
+fn import_tasks(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let file = matches.value("file").unwrap_or_default();
+    let ics = matches.flag("ics");
+    if matches.flag("markdown") == ics {
+        return Err(Error::Invalid(tr("import-format-missing", &[])));
+    }
+    let text = match file {
+        "-" => io::read_to_string(io::stdin())?,
+        path => fs::read_to_string(path).map_err(|e| {
+            Error::Failed(tr("import-unreadable", &[("file", &path), ("error", &e)]))
+        })?,
+    };
+    let tasks = match ics {
+        true => calendar_tasks(matches, &text)?,
+        false => markdown::parse(&text),
+    };
+    if tasks.is_empty() {
+        let id = if ics {
+            "import-no-events"
+        } else {
+            "import-nothing"
+        };
+        println!("{}", tr(id, &[("file", &file)]));
+        return Ok(());
+    }
+
+    let mut store = config.open_store()?;
+    let today = Date::today();
+    let mut added = Vec::new();
+    for mut task in tasks {
+        if !matches.flag("raw") {
+            // What the text says beats the heading it is under, but not the
+            // day of the event it came from.
+            let heading = task.project.take();
+            let due = task.due.map(|date| (date, task.due_at));
+            task = quickadd::apply(task, today)?;
+            task.project = task.project.or(heading);
+            if due.is_some() {
+                task.set_due(due);
+            }
+        }
+        // Done tasks count too, so importing a file twice adds nothing. A
+        // repeating event makes tasks with the same text on different days.
+        let had = |task: &Task| {
+            let same = |t: &Task| {
+                search::fold(&t.text) == search::fold(&task.text) && (!ics || t.due == task.due)
+            };
+            (!ics && store.find_duplicate(&task.text).is_some()) || store.tasks().iter().any(same)
+        };
+        if matches.flag("no-dupes") && had(&task) {
+            println!("{}", tr("import-skipped", &[("task", &task.text)]));
+            continue;
+        }
+        added.push(add_announced(&mut store, task)?);
+    }
+    println!(
+        "{}",
+        tr("import-done", &[("count", &added.len()), ("file", &file)])
+    );
+    let events: Vec<_> = added
+        .iter()
+        .map(|&i| (Event::Add, &store.tasks()[i]))
+        .collect();
+    save(config, &store, &events)
+}
+
+/// A task for each day an event of the calendar in `text` happens on, from
+/// `--from` to `--to`, due that day.
+fn calendar_tasks(matches: &Matches, text: &str) -> todo_cli::Result<Vec<Task>> {
+    let today = Date::today();
+    let from = match matches.value("from") {
+        Some(from) => validate::due(from, today)?,
+        None => today,
+    };
+    let to = match matches.value("to") {
+        Some(to) => validate::due(to, today)?,
+        None => from.add_days(30),
+    };
+    let keyword = matches.value("match").map(search::fold);
+    let events = ics::parse(text);
+    let tasks = ics::between(&events, from, to)
+        .into_iter()
+        .filter(|(_, event)| !event.summary.is_empty())
+        .filter(|(_, event)| {
+            keyword.as_ref().is_none_or(|keyword| {
+                [Some(&event.summary), event.description.as_ref()]
+                    .into_iter()
+                    .flatten()
+                    .any(|text| search::fold(text).contains(keyword.as_str()))
+            })
+        })
+        .map(|(date, event)| {
+            let summary = event
+                .summary
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            let text = match matches.value("prefix") {
+                Some(prefix) => format!("{} {}", prefix.trim(), summary),
+                None => summary,
+            };
+            let location = event
+                .location
+                .as_ref()
+                .map(|location| tr("import-location", &[("location", location)]));
+            let notes: Vec<String> = location
+                .into_iter()
+                .chain(event.description.clone())
+                .collect();
+            // A timed event is due when it starts, read in its own zone.
+            let due_at = event.time.and_then(|(hour, minute)| {
+                let start = DateTime::new(date, hour, minute)?;
+                Some(match event.zone.as_deref().and_then(tz::Zone::named) {
+                    Some(zone) => zone.utc_of(start),
+                    None => tz::utc(start),
+                })
+            });
+            Task {
+                text,
+                due: Some(due_at.map_or(date, |at| tz::local(at).date())),
+                due_at,
+                notes: (!notes.is_empty()).then(|| notes.join("\n\n")),
+                ..Task::default()
+            }
+        })
+        .collect();
+    Ok(tasks)
+}
+
+/// Makes tasks of the flagged emails in a mailbox, and of those sent to
+/// `--to`: once, or every `--every` seconds until the process is stopped.
+fn ingest_command(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let every: Option<u64> = matches.get("every").unwrap_or_else(|e| exit_with(e));
+    let sources = ["imap", "mbox", "maildir"]
+        .iter()
+        .filter(|source| matches.flag(source) || matches.value(source).is_some())
+        .count();
+    if sources != 1 {
+        return Err(Error::Invalid(tr("ingest-source-missing", &[])));
+    }
+    loop {
+        match ingest_mail(matches, config, every.is_none()) {
+            // A mailbox that can't be reached now may be back next time.
+            Err(e) if every.is_some() => eprintln!("{}", tr("error", &[("error", &e)])),
+            result => result?,
+        }
+        let Some(every) = every else {
+            return Ok(());
+        };
+        thread::sleep(Duration::from_secs(every.max(1)));
+    }
+}
+
+/// Reads the mailbox once, adding a task for each message not seen before:
+/// its subject as the text, its body as the notes.
+fn ingest_mail(matches: &Matches, config: &Config, once: bool) -> todo_cli::Result<()> {
+    let (source, messages) = if let Some(path) = matches.value("mbox") {
+        let messages = fs::read(path).map(|data| mail::read_mbox(&data));
+        (path.to_string(), messages)
+    } else if let Some(dir) = matches.value("maildir") {
+        (dir.to_string(), mail::read_maildir(Path::new(dir)))
+    } else {
+        let url = config
+            .imap_url
+            .clone()
+            .ok_or_else(|| Error::Config(tr("ingest-imap-missing", &[])))?;
+        let imap = mail::Imap {
+            url: url.clone(),
+            user: config.imap_user.clone(),
+            password: config.imap_password.clone(),
+            timeout: config.http.timeout,
+        };
+        (url, imap.fetch(matches.value("to")))
+    };
+    let messages = messages.map_err(|e| {
+        Error::Failed(tr(
+            "ingest-unreadable",
+            &[("source", &source), ("error", &e)],
+        ))
+    })?;
+
+    let mut store = config.open_store()?;
+    let location = store.location();
+    let mut ingested = mail::Ingested::read()?;
+    let today = Date::today();
+    let mut added = Vec::new();
+    for message in messages {
+        let wanted = message.flagged
+            || matches
+                .value("to")
+                .is_some_and(|to| message.addressed_to(to));
+        let id = message.id();
+        if !wanted || ingested.contains(&location, &id) {
+            continue;
+        }
+        let subject = message.subject();
+        let text = match subject.is_empty() {
+            true => tr("ingest-no-subject", &[]),
+            false => subject.chars().take(config.max_task_length).collect(),
+        };
+        let notes = message.text();
+        let mut task = Task {
+            text,
+            notes: (!notes.is_empty()).then_some(notes),
+            ..Task::default()
+        };
+        if !matches.flag("raw") {
+            // A subject is no place to insist on the quick-add syntax.
+            task = quickadd::apply(task.clone(), today).unwrap_or(task);
+        }
+        added.push(add_announced(&mut store, task)?);
+        ingested.insert(&location, id);
+    }
+    if added.is_empty() {
+        if once {
+            println!("{}", tr("ingest-nothing", &[("source", &source)]));
+        }
+        return Ok(());
+    }
+    println!(
+        "{}",
+        tr(
+            "ingest-done",
+            &[("count", &added.len()), ("source", &source)]
+        )
+    );
+    let events: Vec<_> = added
+        .iter()
+        .map(|&i| (Event::Add, &store.tasks()[i]))
+        .collect();
+    save(config, &store, &events)?;
+    if !config.dry_run {
+        ingested.write()?;
+    }
+    Ok(())
+}
+
+/// The filter given by the options `list` and `modify` share.
+fn task_filter(matches: &Matches, config: &Config) -> todo_cli::Result<Filter> {
+    let stale = match matches.value("stale") {
+        Some(age) => Some(config::parse_age(age).map_err(Error::Invalid)?),
+        None => None,
+    };
+    // Asking for both pending and done tasks is the same as asking for neither.
+    // Stale tasks are pending ones unless done ones are asked for.
+    let completed = match (matches.flag("pending"), matches.flag("done")) {
+        (true, false) => Some(false),
+        (false, true) => Some(true),
+        _ if stale.is_some() => Some(false),
+        _ => None,
+    };
+    Ok(Filter {
+        text: matches.joined("filter"),
+        words: matches.joined("words"),
+        pattern: match matches.value("regex") {
+            Some(pattern) => Some(Regex::new(pattern).map_err(|e| {
+                Error::Invalid(tr(
+                    "search-regex-invalid",
+                    &[("pattern", &pattern), ("error", &e)],
+                ))
+            })?),
+            None => None,
+        },
+        completed,
+        // Someday/maybe tasks are only included when asked for, and then alone.
+        someday: matches.flag("someday"),
+        statuses: matches.values("status").to_vec(),
+        tags: matches.values("tag").to_vec(),
+        project: matches.value("project").map(String::from),
+        contexts: matches.values("context").to_vec(),
+        owner: matches.flag("mine").then(audit::user),
+        created_before: stale.map(|days| DateTime::now().add_minutes(-i64::from(days) * 24 * 60)),
+        // Known once the list is read.
+        pending: HashSet::new(),
+        scope: active_context(config)?,
+    })
+}
+
+/// The expression of the context `context NAME` made active, if any.
+fn active_context(config: &Config) -> todo_cli::Result<Option<Query>> {
+    let Some(name) = &config.context else {
+        return Ok(None);
+    };
+    let expression = config.contexts.get(name).ok_or_else(|| {
+        let known: Vec<&str> = config.contexts.keys().map(String::as_str).collect();
+        Error::Config(tr(
+            "context-unknown",
+            &[("name", name), ("known", &known.join(", "))],
+        ))
+    })?;
+    Query::parse(expression, Date::today()).map(Some)
+}
+
+/// Records what `list` showed, so that later commands can tell which task a
+/// number meant even if the list changes in between.
+fn remember(mut listing: Listing) {
+    // Tasks without ids yet get the ones the store will give them.
+    store::assign_ids(&mut listing.ids);
+    if let Err(e) = listing.write() {
+        log::info(
+            "listing",
+            format_args!("could not write {}: {}", Listing::path().display(), e),
+        );
+    }
+}
+
+/// Opens the active list, with task numbers meaning what `list` last showed.
+fn open_listed(config: &Config) -> todo_cli::Result<TaskStore> {
+    let store = config.open_store()?;
+    let listing = Listing::read(&store.location());
+    Ok(store.with_listing(listing))
+}
+
+/// The indexes of the pending tasks on the list, by id, read before the
+/// tasks themselves so that each can tell which tasks block it.
+fn scan_pending(backend: &dyn StorageBackend) -> todo_cli::Result<HashMap<u64, usize>> {
+    let mut pending = HashMap::new();
+    backend.scan(&mut |index, task| {
+        if !task.completed {
+            pending.insert(task.id, index);
+        }
+    })?;
+    Ok(pending)
+}
+
+/// The indexes of the pending tasks in `tasks`, by id.
+fn pending_ids(tasks: &[Task]) -> HashMap<u64, usize> {
+    tasks
+        .iter()
+        .enumerate()
+        .filter(|(_, task)| !task.completed)
+        .map(|(index, task)| (task.id, index))
+        .collect()
+}
+
+/// Prints one line of `list`: the check box, number, text and details.
+/// `pending` has the indexes of the pending tasks by id, for telling what
+/// blocks the task.
+fn print_task(
+    index: usize,
+    task: &Task,
+    pending: &HashMap<u64, usize>,
+    config: &Config,
+    today: Date,
+    color: bool,
+) {
+    // A secret task's own text, once unlocked.
+    let revealed = config
+        .passphrase
+        .as_deref()
+        .and_then(|passphrase| secret::reveal(task, passphrase));
+    if config.plain {
+        let text = revealed.as_ref().map_or(&task.text, |task| &task.text);
+        println!(
+            "{}",
+            describe_plain(index, task, text, pending, config, today)
+        );
+        return;
+    }
+    let details = describe_details(task, pending, config, today, color);
+    let task = revealed.as_ref().unwrap_or(task);
+    let habit_done = task.habit.as_ref().is_some_and(|h| h.is_done_for(today));
+    let status = match task.completed {
+        _ if task.is_cancelled() => "[-]",
+        true => "[x]",
+        false if habit_done => "[x]",
+        false => "[ ]",
+    };
+    if color && task.completed {
+        // Green check box, dimmed text for finished tasks.
+        println!(
+            "\x1b[32m{}\x1b[0m {}. \x1b[2m{}\x1b[0m{}",
+            status,
+            index + 1,
+            task.text,
+            details
+        );
+    } else {
+        println!("{} {}. {}{}", status, index + 1, task.text, details);
+    }
+}
+
+/// Formats a task's due date, priority and tags for the end of its list line.
+fn describe_details(
+    task: &Task,
+    pending: &HashMap<u64, usize>,
+    config: &Config,
+    today: Date,
+    color: bool,
+) -> String {
+    let mut details = String::new();
+    if let Some(due) = task.due_label(&config.date_format) {
+        let text = tr("task-due", &[("date", &due)]);
+        // Overdue tasks are red and tasks due today are yellow.
+        let code = match task.due {
+            _ if task.completed || !color => None,
+            _ if task.is_overdue(today) => Some("31"),
+            Some(due) if due == today => Some("33"),
+            _ => None,
+        };
+        match code {
+            Some(code) => details.push_str(&format!(" \x1b[{}m{}\x1b[0m", code, text)),
+            None => details.push_str(&format!(" {}", text)),
+        }
+    }
+    if let Some(priority) = task.priority {
+        details.push_str(&format!(" !{}", priority));
+    }
+    if let Some(project) = &task.project {
+        details.push_str(&format!(" +{}", project));
+    }
+    for context in &task.contexts {
+        details.push_str(&format!(" @{}", context));
+    }
+    for tag in &task.tags {
+        details.push_str(&format!(" #{}", tag));
+    }
+    if let Some((done, total)) = task.progress() {
+        details.push_str(&format!(" [{}/{}]", done, total));
+    }
+    if task.pinned {
+        details.push_str(" 📌");
+    }
+    if task.secret.is_some() {
+        details.push_str(" 🔒");
+    }
+
+    if let Some(waiting) = task.waiting.as_ref().filter(|_| !task.completed) {
+        details.push_str(&format!(
+            " {}",
+            tr("task-waiting", &[("person", &waiting.person)])
+        ));
+    }
+    if task.status.is_some() {
+        details.push_str(&format!(
+            " {}",
+            tr("task-status", &[("status", &status_label(task.status()))])
+        ));
+    }
+    if let Some(habit) = task.habit.as_ref().filter(|_| !task.completed) {
+        details.push_str(&format!(
+            " {}",
+            tr(
+                "task-habit",
+                &[
+                    ("schedule", &habit.schedule()),
+                    ("streak", &habit.streak(today))
+                ]
+            )
+        ));
+    }
+    // Habits come back again and again, so only one-off tasks age.
+    let age = task
+        .age(today)
+        .filter(|_| !task.completed && task.habit.is_none());
+    if let Some(start) = task.timer() {
+        let minutes = start.minutes_until(DateTime::now());
+        details.push_str(&format!(
+            " {}",
+            tr(
+                "task-timing",
+                &[("time", &timetrack::format_duration(minutes))]
+            )
+        ));
+    }
+    if let Some((marker, code)) = age.and_then(age_marker) {
+        if color {
+            details.push_str(&format!(" \x1b[{}m{}\x1b[0m", code, marker));
+        } else {
+            details.push_str(&format!(" {}", marker));
+        }
+    }
+    let blockers: Vec<String> = task
+        .depends
+        .iter()
+        .filter_map(|id| pending.get(id))
+        .map(|index| (index + 1).to_string())
+        .collect();
+    if !task.completed && !blockers.is_empty() {
+        details.push_str(&format!(
+            " {}",
+            tr("task-blocked", &[("tasks", &blockers.join(", "))])
+        ));
+    }
+    // Last and dimmed, as it is only there to be typed back.
+    if task.id != 0 {
+        let hash = &task.hash()[..SHORT_HASH];
+        if color {
+            details.push_str(&format!(" \x1b[2m{}\x1b[0m", hash));
+        } else {
+            details.push_str(&format!(" {}", hash));
+        }
+    }
+    details
+}
+
+/// One task as a sentence for a screen reader, e.g. "Task 3, high priority,
+/// due tomorrow: Buy milk", with what `describe_details` shows in words.
+fn describe_plain(
+    index: usize,
+    task: &Task,
+    text: &str,
+    pending: &HashMap<u64, usize>,
+    config: &Config,
+    today: Date,
+) -> String {
+    let mut details = Vec::new();
+    if task.is_cancelled() {
+        details.push(tr("status-cancelled", &[]));
+    } else if task.completed {
+        details.push(tr("plain-done", &[]));
+    } else if task.habit.as_ref().is_some_and(|h| h.is_done_for(today)) {
+        details.push(tr("plain-done-today", &[]));
+    } else if task.status.is_some() {
+        details.push(status_label(task.status()));
+    }
+    if let Some(priority) = task.priority {
+        details.push(tr(&format!("plain-priority-{}", priority), &[]));
+    }
+    if let Some(due) = task.due {
+        let day = match today.days_until(due) {
+            0 => tr("plain-today", &[]),
+            1 => tr("plain-tomorrow", &[]),
+            -1 => tr("plain-yesterday", &[]),
+            _ => due.format(&config.date_format),
+        };
+        let when = match task.due_at.map(tz::local) {
+            Some(at) => {
+                let time = format!("{:02}:{:02}", at.hour(), at.minute());
+                tr("plain-at", &[("day", &day), ("time", &time)])
+            }
+            None => day,
+        };
+        match task.is_overdue(today) && !task.completed {
+            true => details.push(tr("plain-overdue", &[("when", &when)])),
+            false => details.push(tr("plain-due", &[("when", &when)])),
+        }
+    }
+    if let Some(project) = &task.project {
+        details.push(tr("plain-project", &[("project", project)]));
+    }
+    for context in &task.contexts {
+        details.push(tr("plain-context", &[("context", context)]));
+    }
+    if !task.tags.is_empty() {
+        details.push(tr(
+            "plain-tags",
+            &[
+                ("count", &task.tags.len()),
+                ("tags", &spoken_list(&task.tags)),
+            ],
+        ));
+    }
+    if let Some((done, total)) = task.progress() {
+        details.push(tr("plain-steps", &[("done", &done), ("total", &total)]));
+    }
+    if task.pinned {
+        details.push(tr("plain-pinned", &[]));
+    }
+    if task.secret.is_some() {
+        details.push(tr("plain-secret", &[]));
+    }
+    if let Some(waiting) = task.waiting.as_ref().filter(|_| !task.completed) {
+        details.push(tr("plain-waiting", &[("person", &waiting.person)]));
+    }
+    if let Some(habit) = task.habit.as_ref().filter(|_| !task.completed) {
+        details.push(tr(
+            "plain-habit",
+            &[
+                ("schedule", &habit.schedule()),
+                ("streak", &habit.streak(today)),
+            ],
+        ));
+    }
+    if let Some(start) = task.timer() {
+        let minutes = start.minutes_until(DateTime::now());
+        details.push(tr(
+            "plain-timing",
+            &[("time", &timetrack::format_duration(minutes))],
+        ));
+    }
+    if let Some(days) = task
+        .age(today)
+        .filter(|&days| !task.completed && task.habit.is_none() && age_marker(days).is_some())
+    {
+        details.push(tr("plain-age", &[("days", &days)]));
+    }
+    let blockers: Vec<String> = task
+        .depends
+        .iter()
+        .filter_map(|id| pending.get(id))
+        .map(|index| (index + 1).to_string())
+        .collect();
+    if !task.completed && !blockers.is_empty() {
+        details.push(tr(
+            "plain-blocked",
+            &[
+                ("count", &blockers.len()),
+                ("tasks", &spoken_list(&blockers)),
+            ],
+        ));
+    }
+    let details: String = details
+        .iter()
+        .map(|detail| format!(", {}", detail))
+        .collect();
+    tr(
+        "plain-task",
+        &[
+            ("number", &(index + 1)),
+            ("details", &details),
+            ("task", &text),
+        ],
+    )
+}
+
+/// `items` joined as they would be said: "a, b and c".
+fn spoken_list(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [item] => item.clone(),
+        [rest @ .., last] => format!("{} {} {}", rest.join(", "), tr("plain-and", &[]), last),
+    }
+}
+
+/// How a pending task that has existed for `days` days is marked as aging,
+/// with the color code for the marker: nothing for its first week, then one
+/// dim square, two yellow ones from a month and three red ones from three
+/// months.
+fn age_marker(days: i64) -> Option<(&'static str, &'static str)> {
+    match days {
+        90.. => Some(("▪▪▪", "31")),
+        30.. => Some(("▪▪", "33")),
+        7.. => Some(("▪", "2")),
+        _ => None,
+    }
+}
+
+/// Prints one task with all of its details.
+///
+/// A task number is looked up through the backend's index, so only that task
+/// is read, unless the list changed since it was last listed. Text has to be
+/// matched against the whole list.
+fn show_task(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let query = matches.joined("task").unwrap_or_default();
+    let backend = config.backend()?;
+    let listing = Listing::read(&backend.location());
+    let current = match &listing {
+        Some(listing) => listing.version.is_some() && listing.version == backend.version()?,
+        None => true,
+    };
+    let (index, mut task) = match query.trim().parse::<usize>() {
+        Ok(number) if number > 0 && current => match backend.get(number - 1)? {
+            Some(task) => (number - 1, task),
+            None => return Err(Error::NoSuchNumber(number)),
+        },
+        _ => {
+            let store = TaskStore::with_backend(backend)?.with_listing(listing);
+            let index = store.resolve(&query)?;
+            (index, store.tasks()[index].clone())
+        }
+    };
+
+    let secret = match (&task.secret, &config.passphrase) {
+        (None, _) => None,
+        (Some(_), None) => Some(tr("show-secret-locked", &[])),
+        (Some(_), Some(passphrase)) => match secret::reveal(&task, passphrase) {
+            Some(revealed) => {
+                task = revealed;
+                Some(tr("show-secret-unlocked", &[]))
+            }
+            None => Some(tr("show-secret-wrong", &[])),
+        },
+    };
+    println!("{}. {}", index + 1, task.text);
+    let mut fields = vec![(tr("show-status", &[]), status_label(task.status()))];
+    if task.id != 0 {
+        fields.push((tr("show-id", &[]), task.id.to_string()));
+        fields.push((tr("show-hash", &[]), task.hash()[..12].to_string()));
+    }
+    if let Some(owner) = &task.owner {
+        fields.push((tr("show-owner", &[]), owner.clone()));
+    }
+    if let Some(at) = task.created_at {
+        let days = at.date().days_until(Date::today());
+        let added = tr(
+            "show-added-ago",
+            &[("time", &format_date_time(at, config)), ("days", &days)],
+        );
+        fields.push((tr("show-added", &[]), added));
+    }
+    if let Some(due) = task.due_label(&config.date_format) {
+        fields.push((tr("show-due", &[]), due));
+    }
+    if let Some(priority) = task.priority {
+        fields.push((tr("show-priority", &[]), priority.to_string()));
+    }
+    if !task.completed {
+        let urgency = config.urgency.score(&task, DateTime::now());
+        fields.push((tr("show-urgency", &[]), format!("{:.1}", urgency)));
+    }
+    if let Some(project) = &task.project {
+        fields.push((tr("show-project", &[]), format!("+{}", project)));
+    }
+    if !task.contexts.is_empty() {
+        let contexts: Vec<String> = task.contexts.iter().map(|c| format!("@{}", c)).collect();
+        fields.push((tr("show-contexts", &[]), contexts.join(" ")));
+    }
+    if !task.tags.is_empty() {
+        let tags: Vec<String> = task.tags.iter().map(|tag| format!("#{}", tag)).collect();
+        fields.push((tr("show-tags", &[]), tags.join(" ")));
+    }
+    if task.pinned {
+        fields.push((tr("show-pinned", &[]), tr("show-pinned-yes", &[])));
+    }
+    if let Some(secret) = secret {
+        fields.push((tr("show-secret", &[]), secret));
+    }
+    if let Some(since) = task.someday {
+        let since = tr(
+            "show-someday-since",
+            &[("date", &since.format(&config.date_format))],
+        );
+        fields.push((tr("show-someday", &[]), since));
+    }
+    if let Some(waiting) = &task.waiting {
+        let since = tr(
+            "show-waiting-since",
+            &[
+                ("person", &waiting.person),
+                ("date", &waiting.since.format(&config.date_format)),
+                ("days", &waiting.days(Date::today())),
+            ],
+        );
+        fields.push((tr("show-waiting", &[]), since));
+    }
+    if let Some(habit) = &task.habit {
+        fields.push((
+            tr("show-habit", &[]),
+            describe_streaks(habit, Date::today(), config),
+        ));
+    }
+    if !task.depends.is_empty() {
+        let store = config.open_store()?;
+        let depends: Vec<String> = task
+            .depends
+            .iter()
+            .map(|&id| describe_dependency(id, store.tasks()))
+            .collect();
+        fields.push((tr("show-depends", &[]), depends.join("\n")));
+    }
+    if !task.checklist.is_empty() {
+        let items: Vec<String> = task.checklist.iter().map(|item| item.to_string()).collect();
+        fields.push((tr("show-checklist", &[]), items.join("\n")));
+    }
+    if !task.reminders.is_empty() {
+        let times: Vec<String> = task
+            .reminders
+            .iter()
+            .map(|&at| format_date_time(at, config))
+            .collect();
+        fields.push((tr("show-reminders", &[]), times.join(", ")));
+    }
+    if let Some(estimate) = task.estimate {
+        fields.push((
+            tr("show-estimate", &[]),
+            timetrack::format_duration(i64::from(estimate)),
+        ));
+    }
+    if !task.sessions.is_empty() {
+        let now = DateTime::now();
+        let mut tracked = tr(
+            "show-tracked-sessions",
+            &[
+                ("time", &timetrack::format_duration(task.tracked(now))),
+                ("count", &task.sessions.len()),
+            ],
+        );
+        if let Some(start) = task.timer() {
+            tracked.push_str(&format!(
+                "\n{}",
+                tr(
+                    "show-timer-running",
+                    &[("time", &format_date_time(start, config))]
+                )
+            ));
+        }
+        fields.push((tr("show-tracked", &[]), tracked));
+    }
+    if let Some(notes) = &task.notes {
+        fields.push((tr("show-notes", &[]), notes.clone()));
+    }
+    if !task.comments.is_empty() {
+        let comments: Vec<String> = task
+            .comments
+            .iter()
+            .map(|comment| format_comment(comment, config))
+            .collect();
+        fields.push((tr("show-comments", &[]), comments.join("\n")));
+    }
+    // Room for the longest label and its colon.
+    let width = fields
+        .iter()
+        .map(|(label, _)| label.chars().count() + 1)
+        .max()
+        .unwrap_or(0);
+    for (label, value) in &fields {
+        if config.plain {
+            println!("{}: {}", label, value);
+            continue;
+        }
+        // Line up the values, and indent notes that span several lines.
+        let value = value.replace('\n', &format!("\n   {:width$}  ", ""));
+        println!("   {:width$}  {}", format!("{}:", label), value);
+    }
+    Ok(())
+}
+
+/// Prints the audit log entries for one task, oldest first.
+///
+/// `id:<id>` works for removed tasks too, which can't be found any other way.
+fn show_history(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let Some(query) = matches.joined("task") else {
+        return show_list_history(matches, config);
+    };
+    let store = open_listed(config)?;
+    let id = match query.trim().strip_prefix("id:").map(|id| id.trim().parse()) {
+        Some(Ok(id)) => id,
+        _ => store.tasks()[store.resolve(&query)?].id,
+    };
+    let mut entries = audit::history(&store.location(), id)?;
+    // A secret task's history shows no more of it than the list does.
+    if let Some(index) = store.position(id) {
+        if is_locked(&store.tasks()[index], config) {
+            entries.iter_mut().for_each(audit::mask);
+        }
+    }
+    let text = match (store.position(id), entries.last()) {
+        (Some(index), _) => store.tasks()[index].text.clone(),
+        (None, Some(entry)) => entry.text.clone(),
+        (None, None) => return Err(Error::NoMatch(query)),
+    };
+    println!("{}", tr("history-header", &[("task", &text), ("id", &id)]));
+    if entries.is_empty() {
+        println!("{}", tr("history-empty", &[]));
+    }
+    for entry in &entries {
+        println!("{}  {}", entry_time(entry, config), describe_entry(entry));
+        print_field_changes(&entry.fields, entry.action == Action::Add);
+    }
+    Ok(())
+}
+
+/// Shows the latest `-n` changes to the whole list, oldest first.
+fn show_list_history(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let count: usize = matches
+        .get("count")
+        .unwrap_or_else(|e| exit_with(e))
+        .unwrap_or(20);
+    let backend = config.backend()?;
+    let mut entries = audit::list_entries(&backend.location())?;
+    if entries.is_empty() {
+        println!("{}", tr("history-list-empty", &[]));
+        return Ok(());
+    }
+    let locked: HashSet<u64> = backend
+        .load()?
+        .iter()
+        .filter(|task| is_locked(task, config))
+        .map(|task| task.id)
+        .collect();
+    for entry in entries
+        .iter_mut()
+        .filter(|entry| locked.contains(&entry.id))
+    {
+        audit::mask(entry);
+    }
+    for entry in &entries[entries.len().saturating_sub(count)..] {
+        println!(
+            "{}  id:{} {}: {}",
+            entry_time(entry, config),
+            entry.id,
+            entry.text,
+            describe_entry(entry)
+        );
+        print_field_changes(&entry.fields, entry.action == Action::Add);
+    }
+    Ok(())
+}
+
+/// Whether `task` is secret and `--unlock` didn't give what opens it.
+fn is_locked(task: &Task, config: &Config) -> bool {
+    task.secret.is_some()
+        && config
+            .passphrase
+            .as_deref()
+            .and_then(|passphrase| secret::reveal(task, passphrase))
+            .is_none()
+}
+
+/// When an audit entry was made, followed by its change id if it has one.
+fn entry_time(entry: &audit::Entry, config: &Config) -> String {
+    match &entry.change {
+        Some(change) => format!("{}  {}", format_time(entry.time, config), change),
+        None => format_time(entry.time, config),
+    }
+}
+
+/// What an audit entry did, who did it and how, e.g. `changed by ana via
+/// cli, running 'done'`.
+fn describe_entry(entry: &audit::Entry) -> String {
+    let message = match entry.action {
+        Action::Add => "history-added",
+        Action::Update => "history-updated",
+        Action::Remove => "history-removed",
+    };
+    let what = tr(message, &[("user", &entry.user), ("via", &entry.via)]);
+    match &entry.command {
+        Some(command) => tr("history-command", &[("what", &what), ("command", command)]),
+        None => what,
+    }
+}
+
+/// Prints each field that changed with its old and new value, or just the
+/// new one for a task that was `added`.
+fn print_field_changes(fields: &[FieldChange], added: bool) {
+    let unset = tr("history-unset", &[]);
+    for field in fields {
+        let new = field.new.as_deref().unwrap_or(&unset);
+        match &field.old {
+            _ if added => println!("    {}: {}", field.field, new),
+            old => println!(
+                "    {}: {} -> {}",
+                field.field,
+                old.as_deref().unwrap_or(&unset),
+                new
+            ),
+        }
+    }
+}
+
+/// Formats seconds since 1970 as the configured date and a UTC time of day.
+fn format_time(time: u64, config: &Config) -> String {
+    let at = DateTime::from_secs(time as i64);
+    format!("{} UTC", format_date_time(at, config))
+}
+
+/// Formats a UTC time as the configured date and the time of day, in local
+/// time.
+fn format_date_time(at: DateTime, config: &Config) -> String {
+    let at = tz::local(at);
+    format!(
+        "{} {:02}:{:02}",
+        at.date().format(&config.date_format),
+        at.hour(),
+        at.minute()
+    )
+}
+
+/// Adds a reminder to a task, removes them all with `--clear`, or lists them
+/// when no time is given.
+fn remind_task(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let mut store = open_listed(config)?;
+    let task_index = store.resolve(matches.value("task").unwrap_or_default())?;
+    let task_number = task_index + 1;
+    let mut task = store.tasks()[task_index].clone();
+
+    if matches.flag("clear") {
+        if task.reminders.is_empty() {
+            println!("{}", tr("reminders-none", &[("number", &task_number)]));
+            return Ok(());
+        }
+        task.reminders.clear();
+        println!(
+            "{}",
+            tr(
+                "reminders-cleared",
+                &[("number", &task_number), ("task", &task.text)]
+            )
+        );
+    } else if let Some(when) = matches.joined("when") {
+        let now = DateTime::now();
+        let at = validate::reminder(&when, now)?;
+        let time = format_date_time(at, config);
+        if at <= now {
+            return Err(Error::Invalid(tr("reminder-past", &[("time", &time)])));
+        }
+        task.reminders.push(at);
+        println!(
+            "{}",
+            tr(
+                "reminder-set",
+                &[
+                    ("number", &task_number),
+                    ("task", &task.text),
+                    ("time", &time)
+                ]
+            )
+        );
+    } else {
+        if task.reminders.is_empty() {
+            println!("{}", tr("reminders-none", &[("number", &task_number)]));
+        }
+        for &at in &task.reminders {
+            println!("{}", format_date_time(at, config));
+        }
+        return Ok(());
+    }
+    store.replace(task_index, task)?;
+    save(config, &store, &[])
+}
+
+/// Marks a task as waiting on someone, or no longer waiting with `--clear`.
+fn wait_task(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let mut store = open_listed(config)?;
+    let task_index = store.resolve(&matches.joined("task").unwrap_or_default())?;
+    let task_number = task_index + 1;
+    let mut task = store.tasks()[task_index].clone();
+
+    if matches.flag("clear") {
+        if task.waiting.take().is_none() {
+            println!("{}", tr("wait-not-waiting", &[("number", &task_number)]));
+            return Ok(());
+        }
+        println!(
+            "{}",
+            tr(
+                "wait-cleared",
+                &[("number", &task_number), ("task", &task.text)]
+            )
+        );
+    } else {
+        let Some(person) = matches.value("for") else {
+            return Err(Error::Invalid(tr("wait-missing-for", &[])));
+        };
+        let since = match matches.value("since") {
+            Some(since) => validate::due(since, Date::today())?,
+            None => Date::today(),
+        };
+        let person = validate::person(person)?;
+        println!(
+            "{}",
+            tr(
+                "wait-set",
+                &[
+                    ("number", &task_number),
+                    ("task", &task.text),
+                    ("person", &person)
+                ]
+            )
+        );
+        task.waiting = Some(Waiting { person, since });
+    }
+    store.replace(task_index, task)?;
+    save(config, &store, &[])
+}
+
+/// Parks a task as someday/maybe, out of the normal list, or brings it back
+/// with `--clear`.
+fn park_task(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let mut store = open_listed(config)?;
+    let task_index = store.resolve(&matches.joined("task").unwrap_or_default())?;
+    let task_number = task_index + 1;
+    let mut task = store.tasks()[task_index].clone();
+
+    let message = match (matches.flag("clear"), task.someday) {
+        (true, None) => {
+            println!("{}", tr("someday-not-parked", &[("number", &task_number)]));
+            return Ok(());
+        }
+        (true, Some(_)) => {
+            task.someday = None;
+            "someday-cleared"
+        }
+        (false, Some(_)) => {
+            println!("{}", tr("someday-already", &[("number", &task_number)]));
+            return Ok(());
+        }
+        (false, None) => {
+            task.someday = Some(Date::today());
+            "someday-set"
+        }
+    };
+    println!(
+        "{}",
+        tr(message, &[("number", &task_number), ("task", &task.text)])
+    );
+    store.replace(task_index, task)?;
+    save(config, &store, &[])
+}
+
+/// Pins a task, which gives it the `PINNED` virtual tag, or unpins it with
+/// `--clear`.
+fn pin_task(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let mut store = open_listed(config)?;
+    let task_index = store.resolve(&matches.joined("task").unwrap_or_default())?;
+    let task_number = task_index + 1;
+    let mut task = store.tasks()[task_index].clone();
+
+    let message = match (matches.flag("clear"), task.pinned) {
+        (true, false) => {
+            println!("{}", tr("pin-not-pinned", &[("number", &task_number)]));
+            return Ok(());
+        }
+        (false, true) => {
+            println!("{}", tr("pin-already", &[("number", &task_number)]));
+            return Ok(());
+        }
+        (true, true) => "pin-cleared",
+        (false, false) => "pin-set",
+    };
+    task.pinned = !task.pinned;
+    println!(
+        "{}",
+        tr(message, &[("number", &task_number), ("task", &task.text)])
+    );
+    store.replace(task_index, task)?;
+    save(config, &store, &[])
+}
+
+/// Prints a task as a QR code, to scan it onto a phone: its text and
+/// details, or with `--link` the address of its page on the dashboard.
+fn qr_task(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let store = open_listed(config)?;
+    let task_index = store.resolve(&matches.joined("task").unwrap_or_default())?;
+    let task = &store.tasks()[task_index];
+    let revealed = config
+        .passphrase
+        .as_deref()
+        .and_then(|passphrase| secret::reveal(task, passphrase));
+    let task = revealed.as_ref().unwrap_or(task);
+
+    let payload = match matches.value("link") {
+        Some(base) => format!("{}/tasks/{}", base.trim_end_matches('/'), task.id),
+        None => qr_text(task, config),
+    };
+    let code = qr::QrCode::encode(payload.as_bytes())
+        .ok_or_else(|| Error::Invalid(tr("qr-too-long", &[("length", &payload.len())])))?;
+    print!("{}", code.render(matches.flag("invert")));
+    println!("{}. {}", task_index + 1, task.text);
+    Ok(())
+}
+
+/// A task as plain text for a phone: its text, a line of details, its notes
+/// and its checklist.
+fn qr_text(task: &Task, config: &Config) -> String {
+    let details = plain_details(task, config);
+    let mut text = task.text.clone();
+    if !details.is_empty() {
+        text.push('\n');
+        text.push_str(&details.join(" "));
+    }
+    if let Some(notes) = &task.notes {
+        text.push_str("\n\n");
+        text.push_str(notes.trim_end());
+    }
+    if !task.checklist.is_empty() {
+        text.push('\n');
+    }
+    for item in &task.checklist {
+        let mark = if item.done { "[x]" } else { "[ ]" };
+        text.push_str(&format!("\n- {} {}", mark, item.text));
+    }
+    text
+}
+
+/// A task's due date, priority, project, contexts and tags, without color,
+/// for text that leaves the terminal.
+fn plain_details(task: &Task, config: &Config) -> Vec<String> {
+    let mut details = Vec::new();
+    if let Some(due) = task.due_label(&config.date_format) {
+        details.push(tr("task-due", &[("date", &due)]));
+    }
+    if let Some(priority) = task.priority {
+        details.push(format!("!{}", priority));
+    }
+    if let Some(project) = &task.project {
+        details.push(format!("+{}", project));
+    }
+    details.extend(task.contexts.iter().map(|context| format!("@{}", context)));
+    details.extend(task.tags.iter().map(|tag| format!("#{}", tag)));
+    details
+}
+
+/// Moves a task in the manual order: before or after another task, or to
+/// the top or bottom.
+fn move_task(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let store = open_listed(config)?;
+    let index = store.resolve(&matches.joined("task").unwrap_or_default())?;
+    let order = order::arranged(store.tasks());
+    let at = |index: usize| order.iter().position(|&i| i == index).unwrap_or_default();
+    let from = at(index);
+    // Places count among the other tasks, so those after it move up one.
+    let to = if let Some(other) = matches.value("before") {
+        let other = at(store.resolve(other)?);
+        if other > from {
+            other - 1
+        } else {
+            other
+        }
+    } else if let Some(other) = matches.value("after") {
+        let other = at(store.resolve(other)?);
+        if other > from {
+            other
+        } else {
+            other + 1
+        }
+    } else if matches.flag("top") {
+        0
+    } else if matches.flag("bottom") {
+        order.len() - 1
+    } else {
+        return Err(Error::Invalid(tr("move-no-place", &[])));
+    };
+    arrange(store, index, to, config)
+}
+
+/// Moves a task `direction` places up (-1) or down (1) in the manual order,
+/// times the steps asked for.
+fn step_task(matches: &Matches, direction: i64, config: &Config) -> todo_cli::Result<()> {
+    let steps: i64 = matches
+        .get("steps")
+        .unwrap_or_else(|e| exit_with(e))
+        .unwrap_or(1);
+    let store = open_listed(config)?;
+    let index = store.resolve(matches.value("task").unwrap_or_default())?;
+    let order = order::arranged(store.tasks());
+    let to = order::step(store.tasks(), &order, index, direction * steps);
+    arrange(store, index, to, config)
+}
+
+/// Puts the task at `index` at place `to` of the manual order and says
+/// where it ended up.
+fn arrange(mut store: TaskStore, index: usize, to: usize, config: &Config) -> todo_cli::Result<()> {
+    let ranks = order::place(store.tasks(), index, to);
+    let task = &store.tasks()[index];
+    let message = if ranks.is_empty() {
+        "move-unchanged"
+    } else {
+        "move-moved"
+    };
+    println!(
+        "{}",
+        tr(
+            message,
+            &[
+                ("number", &(index + 1)),
+                ("position", &(to + 1)),
+                ("count", &store.tasks().len()),
+                ("task", &task.text)
+            ]
+        )
+    );
+    if ranks.is_empty() {
+        return Ok(());
+    }
+    for (i, rank) in ranks {
+        let task = Task {
+            rank: Some(rank),
+            ..store.tasks()[i].clone()
+        };
+        store.replace(i, task)?;
+    }
+    save(config, &store, &[])
+}
+
+/// Makes a task secret, encrypting its text and notes, or with `--clear`
+/// decrypts them for good.
+fn secret_task(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let mut store = open_listed(config)?;
+    let task_index = store.resolve(&matches.joined("task").unwrap_or_default())?;
+    let task_number = task_index + 1;
+    let task = store.tasks()[task_index].clone();
+
+    let task = match (matches.flag("clear"), task.secret.is_some()) {
+        (true, false) => {
+            println!("{}", tr("secret-not-secret", &[("number", &task_number)]));
+            return Ok(());
+        }
+        (false, true) => {
+            println!("{}", tr("secret-already", &[("number", &task_number)]));
+            return Ok(());
+        }
+        (true, true) => {
+            let passphrase = passphrase(config, false)?;
+            let task = secret::reveal(&task, &passphrase)
+                .ok_or_else(|| Error::Invalid(tr("secret-wrong-passphrase", &[])))?;
+            println!(
+                "{}",
+                tr(
+                    "secret-cleared",
+                    &[("number", &task_number), ("task", &task.text)]
+                )
+            );
+            task
+        }
+        (false, false) => {
+            let (passphrase, like) = sealing_passphrase(&store, config)?;
+            println!(
+                "{}",
+                tr(
+                    "secret-set",
+                    &[("number", &task_number), ("task", &task.text)]
+                )
+            );
+            let mut task = task;
+            secret::hide(&mut task, &passphrase, like.as_deref())?;
+            task
+        }
+    };
+    store.replace(task_index, task)?;
+    save(config, &store, &[])
+}
+
+/// The passphrase to seal tasks with, and a task already sealed, whose salt
+/// new ones share. When there is one, the passphrase must open it, so that
+/// a typo doesn't seal a task for good; when there isn't, a passphrase typed
+/// in is asked for twice.
+fn sealing_passphrase(
+    store: &TaskStore,
+    config: &Config,
+) -> todo_cli::Result<(String, Option<String>)> {
+    let sealed: Vec<&Task> = store
+        .tasks()
+        .iter()
+        .filter(|task| task.secret.is_some())
+        .collect();
+    let passphrase = passphrase(config, sealed.is_empty())?;
+    if sealed.is_empty() {
+        return Ok((passphrase, None));
+    }
+    match sealed
+        .iter()
+        .find(|task| secret::reveal(task, &passphrase).is_some())
+    {
+        Some(task) => Ok((passphrase, task.secret.clone())),
+        None => Err(Error::Invalid(tr("secret-wrong-passphrase", &[]))),
+    }
+}
+
+/// The passphrase for secret tasks: the one `--unlock` took, or
+/// `$TODO_PASSPHRASE`, or else typed in at the terminal without being
+/// shown, twice if `confirm` is set.
+fn passphrase(config: &Config, confirm: bool) -> todo_cli::Result<String> {
+    if let Some(passphrase) = &config.passphrase {
+        return Ok(passphrase.clone());
+    }
+    if let Some(passphrase) = env::var("TODO_PASSPHRASE").ok().filter(|p| !p.is_empty()) {
+        return Ok(passphrase);
+    }
+    if !io::stdin().is_terminal() {
+        return Err(Error::Invalid(tr("secret-needs-passphrase", &[])));
+    }
+    let passphrase = read_hidden(&tr("secret-prompt", &[]))?;
+    if passphrase.is_empty() {
+        return Err(Error::Invalid(tr("secret-needs-passphrase", &[])));
+    }
+    if confirm && read_hidden(&tr("secret-prompt-again", &[]))? != passphrase {
+        return Err(Error::Invalid(tr("secret-mismatch", &[])));
+    }
+    Ok(passphrase)
+}
+
+/// Reads a line from the terminal with echo turned off, as `stty` does it.
+fn read_hidden(prompt: &str) -> io::Result<String> {
+    eprint!("{}: ", prompt);
+    io::stderr().flush()?;
+    let stty = |arg: &str| Command::new("stty").arg(arg).status();
+    let hidden = stty("-echo").is_ok_and(|status| status.success());
+    let mut line = String::new();
+    let read = io::stdin().read_line(&mut line);
+    if hidden {
+        stty("echo")?;
+        eprintln!();
+    }
+    read?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Brings up the someday/maybe tasks that have been parked for
+/// `someday_review_days` or more.
+///
+/// At a terminal, each one can be made active again; those that stay parked
+/// won't come up again for another `someday_review_days`. Otherwise they are
+/// only listed.
+fn review(config: &Config) -> todo_cli::Result<()> {
+    let mut store = open_listed(config)?;
+    let today = Date::today();
+    let days = i64::from(config.someday_review_days);
+    let due: Vec<u64> = store
+        .tasks()
+        .iter()
+        .filter(|task| {
+            !task.completed
+                && task
+                    .someday
+                    .is_some_and(|since| since.days_until(today) >= days)
+        })
+        .map(|task| task.id)
+        .collect();
+    if due.is_empty() {
+        println!("{}", tr("review-nothing", &[]));
+        return Ok(());
+    }
+    println!("{}", tr("review-someday", &[("count", &due.len())]));
+    let interactive = io::stdin().is_terminal() && !config.dry_run;
+    for id in due {
+        let Some(index) = store.position(id) else {
+            continue;
+        };
+        let task = &store.tasks()[index];
+        let since = task.someday.unwrap_or(today);
+        println!(
+            "{}",
+            tr(
+                "review-line",
+                &[
+                    ("number", &(index + 1)),
+                    ("task", &task.text),
+                    ("date", &since.format(&config.date_format))
+                ]
+            )
+        );
+        if !interactive {
+            continue;
+        }
+        let activate = ask_yes(&tr("confirm-activate", &[]))?;
+        // The list may have changed while we waited for an answer.
+        if store.reload()? {
+            println!("{}", tr("list-reloaded", &[]));
+        }
+        let Some(index) = store.position(id) else {
+            continue;
+        };
+        let mut task = store.tasks()[index].clone();
+        if activate {
+            task.someday = None;
+            println!(
+                "{}",
+                tr(
+                    "someday-cleared",
+                    &[("number", &(index + 1)), ("task", &task.text)]
+                )
+            );
+        } else {
+            task.someday = Some(today);
+        }
+        store.replace(index, task)?;
+    }
+    save(config, &store, &[])
+}
+
+/// Lists the pending tasks that are waiting on someone, longest waiting
+/// first, flagging those that are due a nudge.
+fn list_waiting(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let store = config.open_store()?;
+    let today = Date::today();
+    let person = matches.value("for").map(search::fold);
+    let mut waiting: Vec<(usize, &Task, &Waiting)> = store
+        .tasks()
+        .iter()
+        .enumerate()
+        .filter(|(_, task)| !task.completed)
+        .filter_map(|(index, task)| Some((index, task, task.waiting.as_ref()?)))
+        .filter(|(_, _, w)| {
+            person
+                .as_ref()
+                .is_none_or(|p| search::fold(&w.person) == *p)
+        })
+        .collect();
+    if waiting.is_empty() {
+        println!("{}", tr("waiting-none", &[]));
+        return Ok(());
+    }
+    waiting.sort_by_key(|(_, _, w)| w.since);
+    let color = use_color(config);
+    for (index, task, w) in waiting {
+        let days = w.days(today);
+        let line = tr(
+            "waiting-line",
+            &[
+                ("number", &(index + 1)),
+                ("task", &task.text),
+                ("person", &w.person),
+                ("days", &days),
+            ],
+        );
+        let nudge = config.waiting_nudge_days > 0 && days >= i64::from(config.waiting_nudge_days);
+        match (nudge, color) {
+            (true, true) => println!("\x1b[31m{} !\x1b[0m", line),
+            (true, false) => println!("{} !", line),
+            (false, _) => println!("{}", line),
+        }
+    }
+    Ok(())
+}
+
+/// Prints the weeks up to today as a grid of days, shaded by how many tasks
+/// were completed on each.
+fn show_heatmap(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let weeks: usize = matches
+        .get("weeks")
+        .unwrap_or_else(|e| exit_with(e))
+        .unwrap_or(53);
+    if weeks == 0 {
+        return Err(Error::Invalid(tr("heatmap-no-weeks", &[])));
+    }
+    let store = config.open_store()?;
+    let entries = audit::list_entries(&store.location())?;
+    let days = heatmap::completions(store.tasks(), &entries);
+    let today = Date::today();
+    let color = use_color(config);
+    let total: usize = days
+        .range(heatmap::start(today, weeks)..=today)
+        .map(|(_, &count)| count)
+        .sum();
+    if config.plain {
+        for (first, count) in heatmap::weeks(&days, today, weeks) {
+            println!(
+                "{}",
+                tr(
+                    "heatmap-plain-week",
+                    &[
+                        ("date", &first.format(&config.date_format)),
+                        ("count", &count)
+                    ]
+                )
+            );
+        }
+        println!(
+            "{}",
+            tr("heatmap-total", &[("count", &total), ("weeks", &weeks)])
+        );
+        return Ok(());
+    }
+    print!("{}", heatmap::render(&days, today, weeks, color));
+
+    println!();
+    println!(
+        "{}   {}",
+        tr("heatmap-total", &[("count", &total), ("weeks", &weeks)]),
+        tr("heatmap-legend", &[("shades", &heatmap::legend(color))])
+    );
+    Ok(())
+}
+
+/// Prints a summary of the list's statistics, or with `--csv` the numbers
+/// behind it.
+fn show_stats(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let store = config.open_store()?;
+    let entries = audit::list_entries(&store.location())?;
+    let today = Date::today();
+    let summary = stats::gather(store.tasks(), &entries, today);
+    if matches.flag("csv") {
+        print!("{}", stats::csv(&summary));
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        tr(
+            "stats-total",
+            &[
+                ("total", &(summary.total.pending + summary.total.completed)),
+                ("pending", &summary.total.pending),
+                ("completed", &summary.total.completed),
+                ("overdue", &summary.overdue)
+            ]
+        )
+    );
+    let week = stats::between(&summary, today.add_days(-6), today);
+    println!(
+        "{}",
+        tr(
+            "stats-week",
+            &[("added", &week.added), ("completed", &week.completed)]
+        )
+    );
+    let groups = [
+        ("stats-by-tag", "#", &summary.tags),
+        ("stats-by-project", "+", &summary.projects),
+    ];
+    for (heading, sigil, counts) in groups {
+        if counts.is_empty() {
+            continue;
+        }
+        println!();
+        println!("{}", tr(heading, &[]));
+        print_counts(sigil, counts, false, config);
+    }
+    Ok(())
+}
+
+/// Lists the holidays of a year, or of the twelve months from today.
+fn list_holidays(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    if config.holidays.is_empty() {
+        println!("{}", tr("holidays-none", &[]));
+        return Ok(());
+    }
+    let year: Option<i32> = matches.get("year").unwrap_or_else(|e| exit_with(e));
+    let (from, to) = match year {
+        Some(year) => match (Date::new(year, 1, 1), Date::new(year, 12, 31)) {
+            (Some(from), Some(to)) if from >= date::FIRST && to <= date::LAST => (from, to),
+            _ => return Err(Error::Invalid(tr("holidays-bad-year", &[("year", &year)]))),
+        },
+        None => {
+            let today = Date::today();
+            (today, today.add_months(12).add_days(-1))
+        }
+    };
+    let holidays = holiday::between(from, to);
+    if holidays.is_empty() {
+        println!("{}", tr("holidays-empty", &[]));
+    }
+    for (date, name) in holidays {
+        println!(
+            "{}  {}  {}",
+            date.format(&config.date_format),
+            date.format("%a"),
+            name
+        );
+    }
+    Ok(())
+}
+
+/// Lists every tag in use, with how many tasks have it and when it was last
+/// used.
+fn list_tags(config: &Config) -> todo_cli::Result<()> {
+    let store = config.open_store()?;
+    let entries = audit::list_entries(&store.location())?;
+    let summary = stats::gather(store.tasks(), &entries, Date::today());
+    if summary.tags.is_empty() {
+        println!("{}", tr("tags-none", &[]));
+        return Ok(());
+    }
+    print_counts("#", &summary.tags, false, config);
+    Ok(())
+}
+
+/// Renames or merges tags across the whole list; without a subcommand,
+/// lists the tags like `tags`.
+fn tag_command(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    match &matches.subcommand {
+        Some(("rename", sub)) => retag(sub, false, config),
+        Some(("merge", sub)) => retag(sub, true, config),
+        _ => list_tags(config),
+    }
+}
+
+/// Gives every task tagged `from` the tag `to` instead, saving them all
+/// together. Renaming refuses a tag that is already in use; merging into
+/// one is the point.
+fn retag(matches: &Matches, merge: bool, config: &Config) -> todo_cli::Result<()> {
+    let from = validate::tag(matches.value("from").unwrap_or_default())?;
+    let to = validate::tag(matches.value("to").unwrap_or_default())?;
+    if from == to {
+        return Err(Error::Invalid(tr("tag-same", &[("tag", &from)])));
+    }
+    let mut store = config.open_store()?;
+    let tagged = |tag: &str| -> Vec<usize> {
+        let has = |task: &Task| task.tags.iter().any(|t| t == tag);
+        (0..store.tasks().len())
+            .filter(|&i| has(&store.tasks()[i]))
+            .collect()
+    };
+    let indexes = tagged(&from);
+    if indexes.is_empty() {
+        return Err(Error::Invalid(tr("tag-unused", &[("tag", &from)])));
+    }
+    if !merge && !tagged(&to).is_empty() {
+        return Err(Error::Invalid(tr(
+            "tag-in-use",
+            &[("from", &from), ("to", &to)],
+        )));
+    }
+    for &index in &indexes {
+        let mut task = store.tasks()[index].clone();
+        task.rename_tag(&from, &to);
+        store.replace(index, task)?;
+    }
+    println!(
+        "{}",
+        tr(
+            if merge { "tag-merged" } else { "tag-renamed" },
+            &[("from", &from), ("to", &to), ("count", &indexes.len())]
+        )
+    );
+    save(config, &store, &[])
+}
+
+/// Lists every project with how far along it is and when its next task is
+/// due, or with `--lists` every list.
+fn list_projects(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let today = Date::today();
+    if matches.flag("lists") {
+        let mut counts = BTreeMap::new();
+        let names = std::iter::once(None).chain(serve::list_names(config)?.into_iter().map(Some));
+        for name in names {
+            let config = Config {
+                list: name.clone(),
+                ..config.clone()
+            };
+            let store = config.open_store()?;
+            let total = stats::gather(store.tasks(), &[], today).total;
+            counts.insert(name.unwrap_or_else(|| tr("projects-main-list", &[])), total);
+        }
+        print_counts("", &counts, true, config);
+        return Ok(());
+    }
+
+    let store = config.open_store()?;
+    let summary = stats::gather(store.tasks(), &[], today);
+    if summary.projects.is_empty() {
+        println!("{}", tr("projects-none", &[]));
+        return Ok(());
+    }
+    print_counts("+", &summary.projects, true, config);
+    Ok(())
+}
+
+/// Prints the pending and completed counts of each tag, project or list,
+/// marked with `sigil`. A `rollup` adds how much is done and the next due
+/// date; otherwise each says when it was last used.
+fn print_counts(
+    sigil: &str,
+    counts: &BTreeMap<String, stats::Count>,
+    rollup: bool,
+    config: &Config,
+) {
+    let width = counts
+        .keys()
+        .map(|key| key.chars().count() + sigil.len())
+        .max()
+        .unwrap_or(0);
+    // Plain output reads as one sentence per line rather than columns.
+    let gap = if config.plain { ", " } else { "  " };
+    for (key, count) in counts {
+        let count_text = tr(
+            "stats-count",
+            &[("pending", &count.pending), ("completed", &count.completed)],
+        );
+        let mut line = match config.plain {
+            true => format!("{}: {}", key, count_text),
+            false => format!("  {:width$}  {}", format!("{}{}", sigil, key), count_text),
+        };
+        if rollup {
+            if let Some(percent) = count.percent_done() {
+                line.push_str(&format!(
+                    "{}{}",
+                    gap,
+                    tr("projects-percent", &[("percent", &percent)])
+                ));
+            }
+            if let Some(due) = count.next_due {
+                line.push_str(&format!(
+                    "{}{}",
+                    gap,
+                    tr(
+                        "projects-next-due",
+                        &[("date", &due.format(&config.date_format))]
+                    )
+                ));
+            }
+        } else if let Some(day) = count.last_used {
+            line.push_str(&format!(
+                "{}{}",
+                gap,
+                tr(
+                    "stats-last-used",
+                    &[("date", &day.format(&config.date_format))]
+                )
+            ));
+        }
+        println!("{}", line);
+    }
+}
+
+/// Shows the tasks as a kanban board, or moves one to another column.
+fn show_board(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let by: Grouping = match matches.value("by") {
+        Some(by) => by.parse().map_err(Error::Invalid)?,
+        None => Grouping::Status,
+    };
+    if let Some(("move", sub)) = &matches.subcommand {
+        return move_card(sub, by, config);
+    }
+
+    let store = config.open_store()?;
+    if store.tasks().is_empty() {
+        println!("{}", tr("list-empty", &[]));
+        return Ok(());
+    }
+    let mut columns = board::columns(store.tasks(), by, &config.workflow);
+    if let Some(scope) = active_context(config)? {
+        let today = Date::today();
+        let ids: HashSet<u64> = pending_ids(store.tasks()).keys().copied().collect();
+        for column in &mut columns {
+            column
+                .cards
+                .retain(|(_, task)| scope.matches(task, today, &ids));
+        }
+        // As without a context, only the workflow's columns stay when empty.
+        columns.retain(|column| {
+            !column.cards.is_empty()
+                || (by == Grouping::Status
+                    && column
+                        .key
+                        .as_ref()
+                        .is_some_and(|key| config.workflow.contains(key) || key == "waiting"))
+        });
+    }
+    let titles: Vec<String> = columns
+        .iter()
+        .map(|column| match (by, &column.key) {
+            (Grouping::Status, Some(status)) => match status.as_str() {
+                TODO | "doing" | "waiting" | "someday" | DONE | CANCELLED => {
+                    tr(&format!("board-{}", status), &[])
+                }
+                other => other.to_string(),
+            },
+            (Grouping::Tag, Some(tag)) => format!("#{}", tag),
+            (Grouping::Project, Some(project)) => format!("+{}", project),
+            (Grouping::Tag, None) => tr("board-no-tag", &[]),
+            (_, None) => tr("board-no-project", &[]),
+        })
+        .collect();
+    let width = env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .unwrap_or(80);
+    if config.plain {
+        print!("{}", board::describe(&columns, &titles));
+        return Ok(());
+    }
+    print!(
+        "{}",
+        board::render(&columns, &titles, width, use_color(config))
+    );
+    Ok(())
+}
+
+/// Moves a task to another column of the board, changing whatever puts it
+/// there: its status, a tag or its project.
+fn move_card(matches: &Matches, by: Grouping, config: &Config) -> todo_cli::Result<()> {
+    let column = matches.value("column").unwrap_or_default();
+    let none = column.eq_ignore_ascii_case("none");
+    let to = match by {
+        Grouping::Status => {
+            let wanted = match column.trim().to_lowercase().as_str() {
+                "pending" => TODO.to_string(),
+                other => other.to_string(),
+            };
+            let statuses = board::statuses(&config.workflow);
+            if !statuses.contains(&wanted) {
+                return Err(Error::Invalid(tr(
+                    "board-unknown-column",
+                    &[("column", &column), ("columns", &statuses.join(", "))],
+                )));
+            }
+            Move::Status(wanted)
+        }
+        Grouping::Tag => Move::Tag {
+            from: matches.value("from").map(validate::tag).transpose()?,
+            to: (!none).then(|| validate::tag(column)).transpose()?,
+        },
+        Grouping::Project => Move::Project((!none).then(|| validate::project(column)).transpose()?),
+    };
+    let person = matches.value("for").map(validate::person).transpose()?;
+
+    let mut store = open_listed(config)?;
+    let index = store.resolve(&matches.joined("task").unwrap_or_default())?;
+    let task = &store.tasks()[index];
+    let was_done = task.status() == DONE;
+    let moved = board::move_task(task, &to, person, Date::today()).map_err(Error::Invalid)?;
+    println!(
+        "{}",
+        tr(
+            "board-moved",
+            &[
+                ("number", &(index + 1)),
+                ("column", &column),
+                ("task", &moved.text)
+            ]
+        )
+    );
+    store.replace(index, moved)?;
+    let task = &store.tasks()[index];
+    let events: &[(Event, &Task)] = if task.status() == DONE && !was_done {
+        &[(Event::Done, task)]
+    } else {
+        &[]
+    };
+    save(config, &store, events)
+}
+
+/// Rebuilds the active list's lookup index.
+fn reindex(config: &Config) -> todo_cli::Result<()> {
+    let backend = config.backend()?;
+    let path = backend.location();
+    if backend.reindex()? {
+        println!("{}", tr("reindexed", &[("path", &path)]));
+    } else {
+        println!("{}", tr("reindex-none", &[("path", &path)]));
+    }
+    Ok(())
+}
+
+/// Marks a task as complete, by number or by part of its text.
+fn complete_task(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let mut store = open_listed(config)?;
+    let task_index = store.resolve(&matches.joined("task").unwrap_or_default())?;
+    let task_number = task_index + 1;
+
+    if store.complete(task_index)? {
+        let task = &store.tasks()[task_index];
+        match task.habit.as_ref().filter(|_| !task.completed) {
+            Some(habit) => {
+                let today = Date::today();
+                println!(
+                    "{}",
+                    tr(
+                        "habit-done",
+                        &[
+                            ("number", &task_number),
+                            ("task", &task.text),
+                            ("streak", &habit.streak(today)),
+                            ("best", &habit.best())
+                        ]
+                    )
+                );
+            }
+            None => {
+                println!(
+                    "{}",
+                    tr(
+                        "task-completed",
+                        &[("number", &task_number), ("task", &task.text)]
+                    )
+                );
+                report_unblocked(task.id, store.tasks());
+                if config.archive_on_done {
+                    let task = task.clone();
+                    archive_tasks(config, &mut store, &[task_index])?;
+                    println!("{}", tr("task-archived", &[]));
+                    return save(config, &store, &[(Event::Done, &task)]);
+                }
+            }
+        }
+        save(config, &store, &[(Event::Done, task)])?;
+    } else if store.tasks()[task_index].habit.is_some() && !store.tasks()[task_index].completed {
+        println!("{}", tr("habit-already-done", &[("number", &task_number)]));
+    } else {
+        println!(
+            "{}",
+            tr("task-already-completed", &[("number", &task_number)])
+        );
+    }
+
+    Ok(())
+}
+
+/// Lists the habits with their streaks, or makes a task a habit (or no
+/// longer one).
+fn habit_command(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let today = Date::today();
+    let Some(query) = matches.joined("task") else {
+        let store = config.open_store()?;
+        let habits: Vec<(usize, &Task)> = store
+            .tasks()
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| task.habit.is_some() && !task.completed)
+            .collect();
+        if habits.is_empty() {
+            println!("{}", tr("habit-none", &[]));
+        }
+        for (index, task) in habits {
+            println!("{}", describe_habit(index, task, today, config));
+        }
+        return Ok(());
+    };
+
+    let mut store = open_listed(config)?;
+    let task_index = store.resolve(&query)?;
+    let task_number = task_index + 1;
+    let mut task = store.tasks()[task_index].clone();
+    if matches.flag("stop") {
+        if task.habit.take().is_none() {
+            println!("{}", tr("habit-not", &[("number", &task_number)]));
+            return Ok(());
+        }
+        println!(
+            "{}",
+            tr(
+                "habit-stopped",
+                &[("number", &task_number), ("task", &task.text)]
+            )
+        );
+    } else if let Some(every) = matches.value("every") {
+        let (every, business_days) = validate::habit(every)?;
+        match &mut task.habit {
+            Some(habit) => (habit.every, habit.business_days) = (every, business_days),
+            None => {
+                task.habit = Some(habit::Habit::new(every, business_days));
+                task.due = task.due.or(Some(today));
+            }
+        }
+        if task.completed {
+            task.set_status(TODO);
+        }
+        let schedule = task.habit.as_ref().map(habit::Habit::schedule);
+        println!(
+            "{}",
+            tr(
+                "habit-set",
+                &[
+                    ("number", &task_number),
+                    ("schedule", &schedule.unwrap_or_default()),
+                    ("task", &task.text)
+                ]
+            )
+        );
+    } else {
+        match &task.habit {
+            Some(_) => println!("{}", describe_habit(task_index, &task, today, config)),
+            None => println!("{}", tr("habit-not", &[("number", &task_number)])),
+        }
+        return Ok(());
+    }
+    store.replace(task_index, task)?;
+    save(config, &store, &[])
+}
+
+/// One line of `habit`: the task, its schedule, its streaks and when it was
+/// last done.
+fn describe_habit(index: usize, task: &Task, today: Date, config: &Config) -> String {
+    let Some(habit) = &task.habit else {
+        return format!("{}. {}", index + 1, task.text);
+    };
+    tr(
+        "habit-line",
+        &[
+            ("number", &(index + 1)),
+            ("task", &task.text),
+            ("details", &describe_streaks(habit, today, config)),
+        ],
+    )
+}
+
+/// A habit's schedule, streaks and when it was last done, for `habit` and
+/// `show`.
+fn describe_streaks(habit: &habit::Habit, today: Date, config: &Config) -> String {
+    let last = match habit.last() {
+        Some(last) => last.format(&config.date_format),
+        None => tr("habit-never", &[]),
+    };
+    tr(
+        "habit-streaks",
+        &[
+            ("schedule", &habit.schedule()),
+            ("streak", &habit.streak(today)),
+            ("best", &habit.best()),
+            ("last", &last),
+        ],
+    )
+}
+
+/// Prints the tasks that completing the task `id` left with nothing to wait on.
+fn report_unblocked(id: u64, tasks: &[Task]) {
+    for (index, task) in deps::unblocked_by(id, tasks) {
+        println!(
+            "{}",
+            tr(
+                "task-unblocked",
+                &[("number", &(index + 1)), ("task", &task.text)]
+            )
+        );
+    }
+}
+
+/// Makes a task depend on others, or no longer; without options, lists what
+/// it depends on.
+fn depend_task(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let mut store = open_listed(config)?;
+    let task_index = store.resolve(matches.value("task").unwrap_or_default())?;
+    let task_number = task_index + 1;
+    let mut task = store.tasks()[task_index].clone();
+
+    let (on, remove) = (matches.values("on"), matches.values("remove"));
+    if on.is_empty() && remove.is_empty() && !matches.flag("clear") {
+        if task.depends.is_empty() {
+            println!("{}", tr("depend-none", &[("number", &task_number)]));
+        }
+        for &id in &task.depends {
+            println!("  {}", describe_dependency(id, store.tasks()));
+        }
+        return Ok(());
+    }
+
+    // Check every task named before changing anything.
+    let mut messages = Vec::new();
+    if matches.flag("clear") {
+        task.depends.clear();
+        messages.push(tr("depend-cleared", &[("number", &task_number)]));
+    }
+    for other in remove {
+        let other_index = store.resolve(other)?;
+        let id = store.tasks()[other_index].id;
+        if !task.depends.contains(&id) {
+            return Err(Error::Invalid(tr(
+                "depend-not",
+                &[("number", &task_number), ("other", &(other_index + 1))],
+            )));
+        }
+        task.depends.retain(|&d| d != id);
+        messages.push(tr(
+            "depend-removed",
+            &[("number", &task_number), ("other", &(other_index + 1))],
+        ));
+    }
+    for other in on {
+        let other_index = store.resolve(other)?;
+        let other = &store.tasks()[other_index];
+        if other_index == task_index {
+            return Err(Error::Invalid(tr("depend-self", &[])));
+        }
+        if deps::would_cycle(store.tasks(), task.id, other.id) {
+            return Err(Error::Invalid(tr(
+                "depend-cycle",
+                &[("number", &task_number), ("other", &(other_index + 1))],
+            )));
+        }
+        if !task.depends.contains(&other.id) {
+            task.depends.push(other.id);
+        }
+        messages.push(tr(
+            "depend-added",
+            &[
+                ("number", &task_number),
+                ("other", &(other_index + 1)),
+                ("task", &other.text),
+            ],
+        ));
+    }
+    for message in messages {
+        println!("{}", message);
+    }
+    store.replace(task_index, task)?;
+    save(config, &store, &[])
+}
+
+/// One task that another depends on, as `depend` and `show` list it.
+fn describe_dependency(id: u64, tasks: &[Task]) -> String {
+    match tasks.iter().position(|task| task.id == id) {
+        Some(index) => tr(
+            "depend-line",
+            &[
+                ("number", &(index + 1)),
+                ("task", &tasks[index].text),
+                ("status", &status_label(tasks[index].status())),
+            ],
+        ),
+        None => tr("depend-gone", &[("id", &id)]),
+    }
+}
+
+/// Recommends what to work on next: the tasks that can be started now,
+/// soonest due and most important first.
+fn next_tasks(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let count: usize = matches
+        .get("count")
+        .unwrap_or_else(|e| exit_with(e))
+        .unwrap_or(5);
+    let store = config.open_store()?;
+    let (today, color) = (Date::today(), use_color(config));
+    let pending = pending_ids(store.tasks());
+    let mut next = deps::next(store.tasks(), &config.urgency, DateTime::now());
+    if let Some(scope) = active_context(config)? {
+        let ids: HashSet<u64> = pending.keys().copied().collect();
+        next.retain(|(_, task)| scope.matches(task, today, &ids));
+    }
+    if next.is_empty() {
+        println!("{}", tr("next-none", &[]));
+        return Ok(());
+    }
+    println!("{}", tr("next-header", &[]));
+    for (index, task) in next.into_iter().take(count) {
+        print_task(index, task, &pending, config, today, color);
+    }
+    Ok(())
+}
+
+/// Starts a task: moves it to the status after `todo` in the workflow.
+fn start_task(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let started = config
+        .workflow
+        .iter()
+        .skip_while(|status| *status != TODO)
+        .nth(1)
+        .filter(|status| *status != DONE)
+        .ok_or_else(|| Error::Invalid(tr("status-no-start", &[])))?;
+    change_status(&matches.joined("task").unwrap_or_default(), started, config)
+}
+
+/// Cancels a task: drops it without it being done.
+fn cancel_task(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    change_status(
+        &matches.joined("task").unwrap_or_default(),
+        CANCELLED,
+        config,
+    )
+}
+
+/// Prints where a task is in the workflow, or moves it to another status.
+fn status_task(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let query = matches.value("task").unwrap_or_default();
+    let Some(status) = matches.value("status") else {
+        let store = open_listed(config)?;
+        let index = store.resolve(query)?;
+        let task = &store.tasks()[index];
+        println!(
+            "{}",
+            tr(
+                "status-current",
+                &[
+                    ("number", &(index + 1)),
+                    ("status", &status_label(task.status())),
+                    ("task", &task.text)
+                ]
+            )
+        );
+        return Ok(());
+    };
+    let status = validate::status(status)?;
+    if status != CANCELLED && !config.workflow.contains(&status) {
+        let mut statuses = config.workflow.clone();
+        statuses.push(CANCELLED.to_string());
+        return Err(Error::Invalid(tr(
+            "status-unknown",
+            &[("status", &status), ("statuses", &statuses.join(", "))],
+        )));
+    }
+    change_status(query, &status, config)
+}
+
+/// Moves the task `query` refers to to another workflow status.
+fn change_status(query: &str, status: &str, config: &Config) -> todo_cli::Result<()> {
+    let mut store = open_listed(config)?;
+    let index = store.resolve(query)?;
+    let number = index + 1;
+    let task = &store.tasks()[index];
+    if task.status() == status {
+        println!(
+            "{}",
+            tr(
+                "status-unchanged",
+                &[("number", &number), ("status", &status_label(status))]
+            )
+        );
+        return Ok(());
+    }
+    let mut moved = task.clone();
+    moved.set_status(status);
+    // A task someone is working on isn't parked any more.
+    if !moved.completed && status != TODO {
+        moved.someday = None;
+    }
+    println!(
+        "{}",
+        tr(
+            "status-changed",
+            &[
+                ("number", &number),
+                ("status", &status_label(status)),
+                ("task", &moved.text)
+            ]
+        )
+    );
+    store.replace(index, moved)?;
+    let task = &store.tasks()[index];
+    if task.completed {
+        report_unblocked(task.id, store.tasks());
+    }
+    let events: &[(Event, &Task)] = if status == DONE {
+        &[(Event::Done, task)]
+    } else {
+        &[]
+    };
+    save(config, &store, events)
+}
+
+/// Removes a task from the list, by number or by part of its text.
+fn delete_task(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let mut store = open_listed(config)?;
+    let task_index = store.resolve(&matches.joined("task").unwrap_or_default())?;
+
+    let prompt = tr(
+        "confirm-delete",
+        &[
+            ("number", &(task_index + 1)),
+            ("task", &store.tasks()[task_index].text),
+        ],
+    );
+    if !confirm(&prompt, config)? {
+        return Ok(());
+    }
+
+    let removed = store.remove(task_index)?;
+    println!(
+        "{}",
+        tr(
+            "task-deleted",
+            &[("number", &(task_index + 1)), ("task", &removed.text)]
+        )
+    );
+    save(config, &store, &[(Event::Delete, &removed)])
+}
+
+/// Changes a task's text, or opens it in `$EDITOR` with `--editor`.
+fn edit_task(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let mut store = open_listed(config)?;
+    let task_index = store.resolve(matches.value("task").unwrap_or_default())?;
+    let task_number = task_index + 1;
+    let current = store.tasks()[task_index].clone();
+    if current.secret.is_some() {
+        return Err(Error::Invalid(tr(
+            "secret-edit",
+            &[("number", &task_number)],
+        )));
+    }
+
+    // Without new text there is nothing to apply, so fall back to the editor.
+    let Some(text) = matches.joined("text").filter(|_| !matches.flag("editor")) else {
+        let edited =
+            edit_in_editor(&current, task_number, config.max_task_length)?.map(|edited| Task {
+                id: current.id,
+                created_at: current.created_at,
+                modified_at: current.modified_at,
+                estimate: current.estimate,
+                sessions: current.sessions.clone(),
+                someday: current.someday,
+                pinned: current.pinned,
+                rank: current.rank,
+                secret: current.secret.clone(),
+                waiting: current.waiting.clone(),
+                comments: current.comments.clone(),
+                owner: current.owner.clone(),
+                depends: current.depends.clone(),
+                habit: current.habit.clone(),
+                ..edited
+            });
+        match edited {
+            Some(edited) if edited == current => println!("{}", tr("edit-no-changes", &[])),
+            Some(edited) => {
+                // Someone may have changed the list while the editor was open;
+                // apply the edit to the task as it is now.
+                if store.reload()? {
+                    println!("{}", tr("list-reloaded", &[]));
+                }
+                let Some(index) = store.position(current.id) else {
+                    return Err(Error::Failed(tr(
+                        "edit-task-gone",
+                        &[("task", &current.text)],
+                    )));
+                };
+                let (edited, conflict) = sync::merge_task(&current, &edited, &store.tasks()[index]);
+                if conflict {
+                    println!("{}", tr("edit-merged", &[]));
+                }
+                println!(
+                    "{}",
+                    tr(
+                        "task-updated",
+                        &[("number", &(index + 1)), ("task", &edited.text)]
+                    )
+                );
+                store.replace(index, edited)?;
+                save(config, &store, &[])?;
+            }
+            None => {}
+        }
+        return Ok(());
+    };
+
+    let edited = Task {
+        text: validate::text(&text, config.max_task_length)?,
+        ..current.clone()
+    };
+    println!(
+        "{}",
+        tr(
+            "task-updated",
+            &[("number", &task_number), ("task", &edited.text)]
+        )
+    );
+    store.replace(task_index, edited)?;
+    save(config, &store, &[])
+}
+
+/// Opens a task in the user's editor and returns the edited version.
+///
+/// The task is written out as a small TOML document, much like `git commit`
+/// hands you a message to edit. Returns `None` if the user emptied the buffer
+/// to cancel.
+fn edit_in_editor(
+    task: &Task,
+    task_number: usize,
+    max_length: usize,
+) -> todo_cli::Result<Option<Task>> {
+    let buffer = format!(
+        "{}\n\n\
+         task = {}\nstatus = {}\ndue = {}\npriority = {}\nproject = {}\ncontexts = {}\n\
+         tags = {}\nchecklist = {}\nreminders = {}\nnotes = {}\n",
+        tr("edit-header", &[("number", &task_number)]),
+        toml::Value::String(task.text.clone()),
+        toml::Value::String(task.status().to_string()),
+        toml::Value::String(match task.due_at {
+            Some(at) => tz::local(at).to_string(),
+            None => task.due.map(|d| d.to_string()).unwrap_or_default(),
+        }),
+        toml::Value::String(task.priority.map(|p| p.to_string()).unwrap_or_default()),
+        toml::Value::String(task.project.clone().unwrap_or_default()),
+        toml::Value::Array(
+            task.contexts
+                .iter()
+                .cloned()
+                .map(toml::Value::String)
+                .collect()
+        ),
+        toml::Value::Array(task.tags.iter().cloned().map(toml::Value::String).collect()),
+        toml::Value::Array(
+            task.checklist
+                .iter()
+                .map(|item| toml::Value::String(item.to_string()))
+                .collect()
+        ),
+        toml::Value::Array(
+            task.reminders
+                .iter()
+                .map(|&at| toml::Value::String(tz::local(at).to_string()))
+                .collect()
+        ),
+        toml::Value::String(task.notes.clone().unwrap_or_default()),
+    );
+
+    let path = env::temp_dir().join(format!("task-edit-{}.toml", std::process::id()));
+    fs::write(&path, buffer)?;
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+
+    // Run through the shell so editors configured with arguments ("code --wait") work.
+    let status = if cfg!(windows) {
+        Command::new("cmd")
+            .arg("/C")
+            .arg(&editor)
+            .arg(&path)
+            .status()
+    } else {
+        Command::new("sh")
+            .arg("-c")
+            .arg(format!("{} \"$1\"", editor))
+            .arg("sh")
+            .arg(&path)
+            .status()
+    };
+    let status = match status {
+        Ok(status) => status,
+        Err(e) => {
+            let _ = fs::remove_file(&path);
+            return Err(Error::Failed(tr(
+                "editor-start-failed",
+                &[("editor", &editor), ("error", &e)],
+            )));
+        }
+    };
+    if !status.success() {
+        let _ = fs::remove_file(&path);
+        return Err(Error::Failed(tr(
+            "editor-failed",
+            &[("editor", &editor), ("status", &status)],
+        )));
+    }
+
+    let edited = fs::read_to_string(&path)?;
+    match parse_edited_task(&edited, max_length) {
+        Ok(Some(task)) => {
+            fs::remove_file(&path)?;
+            Ok(Some(task))
+        }
+        Ok(None) => {
+            println!("{}", tr("edit-cancelled", &[]));
+            fs::remove_file(&path)?;
+            Ok(None)
+        }
+        // Keep the file so the user doesn't lose their work.
+        Err(msg) => Err(Error::Invalid(tr(
+            "edit-kept",
+            &[("error", &msg), ("path", &path.display())],
+        ))),
+    }
+}
+
+/// Validates the buffer returned from the editor and turns it back into a task.
+///
+/// Returns `Ok(None)` when the buffer was emptied, which cancels the edit.
+fn parse_edited_task(buffer: &str, max_length: usize) -> Result<Option<Task>, String> {
+    let table = toml::parse(buffer)?;
+    if table.is_empty() {
+        return Ok(None);
+    }
+
+    let mut task = Task::default();
+    for (key, value) in &table {
+        match (key.as_str(), value) {
+            ("task", toml::Value::String(text)) => task.text = text.clone(),
+            ("completed", toml::Value::Boolean(done)) => task.completed = *done,
+            ("status", toml::Value::String(status)) => {
+                task.set_status(&validate::status(status).map_err(|e| e.to_string())?);
+            }
+            ("due", toml::Value::String(due)) if due.trim().is_empty() => task.set_due(None),
+            ("due", toml::Value::String(due)) => {
+                task.set_due(Some(
+                    validate::due_time(due, Date::today()).map_err(|e| e.to_string())?,
+                ));
+            }
+            ("priority", toml::Value::String(p)) if p.trim().is_empty() => task.priority = None,
+            ("priority", toml::Value::String(p)) => {
+                task.priority = Some(validate::priority(p).map_err(|e| e.to_string())?);
+            }
+            ("project", toml::Value::String(p)) if p.trim().is_empty() => task.project = None,
+            ("project", toml::Value::String(p)) => {
+                task.project = Some(validate::project(p).map_err(|e| e.to_string())?);
+            }
+            ("contexts", toml::Value::Array(contexts)) => {
+                task.contexts = Vec::new();
+                for context in contexts {
+                    let context = context
+                        .as_str()
+                        .ok_or_else(|| tr("edit-contexts-not-list", &[]))?;
+                    task.contexts
+                        .push(validate::context(context).map_err(|e| e.to_string())?);
+                }
+            }
+            ("tags", toml::Value::Array(tags)) => {
+                task.tags = Vec::new();
+                for tag in tags {
+                    let tag = tag.as_str().ok_or_else(|| tr("edit-tags-not-list", &[]))?;
+                    task.tags
+                        .push(validate::tag(tag).map_err(|e| e.to_string())?);
+                }
+            }
+            ("checklist", toml::Value::Array(items)) => {
+                task.checklist = items
+                    .iter()
+                    .map(|item| item.as_str().map(CheckItem::parse))
+                    .collect::<Option<_>>()
+                    .ok_or_else(|| tr("edit-checklist-not-list", &[]))?;
+            }
+            ("reminders", toml::Value::Array(times)) => {
+                task.reminders = Vec::new();
+                for at in times {
+                    let at = at
+                        .as_str()
+                        .ok_or_else(|| tr("edit-reminders-not-list", &[]))?;
+                    task.reminders
+                        .push(validate::reminder(at, DateTime::now()).map_err(|e| e.to_string())?);
+                }
+            }
+            ("notes", toml::Value::String(notes)) => task.notes = validate::notes(notes),
+            ("task" | "notes" | "due" | "priority" | "project" | "status", _) => {
+                return Err(tr("edit-not-string", &[("field", key)]))
+            }
+            ("tags", _) => return Err(tr("edit-tags-not-list", &[])),
+            ("contexts", _) => return Err(tr("edit-contexts-not-list", &[])),
+            ("checklist", _) => return Err(tr("edit-checklist-not-list", &[])),
+            ("reminders", _) => return Err(tr("edit-reminders-not-list", &[])),
+            ("completed", _) => return Err(tr("edit-completed-not-bool", &[])),
+            _ => return Err(tr("edit-unknown-field", &[("field", key)])),
+        }
+    }
+    validate::task(task, max_length)
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
+/// Makes the same edits to every task that matches a filter, printing what
+/// changed in each; with `--dry-run`, only prints it.
+fn modify_tasks(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let mut filter = task_filter(matches, config)?;
+    if filter.is_empty() && !matches.flag("all") {
+        return Err(Error::Invalid(tr("modify-no-filter", &[])));
+    }
+    let today = Date::today();
+    let mut edits = Edits::default();
+    if let Some(spec) = matches.value("replace") {
+        edits.replace(spec)?;
+    }
+    for spec in matches.values("set") {
+        edits.set(spec, today)?;
+    }
+    for tag in matches.values("add-tag") {
+        edits.add_tag(tag)?;
+    }
+    for tag in matches.values("remove-tag") {
+        edits.remove_tag(tag)?;
+    }
+    if edits.is_empty() {
+        return Err(Error::Invalid(tr("modify-no-edits", &[])));
+    }
+
+    let mut store = config.open_store()?;
+    filter.pending = pending_ids(store.tasks()).into_keys().collect();
+    // Work out every change first, so that a task the edits would make
+    // invalid fails the command before anything is printed or stored.
+    let mut changed = Vec::new();
+    let mut matched = 0;
+    for (index, task) in store.tasks().iter().enumerate() {
+        if !filter.matches(task) {
+            continue;
+        }
+        matched += 1;
+        let edited = validate::task(edits.apply(task), config.max_task_length)?;
+        if edited != *task {
+            changed.push((index, edited));
+        }
+    }
+    if matched == 0 {
+        println!("{}", tr("list-no-matches", &[]));
+        return Ok(());
+    }
+    if config.dry_run {
+        println!("{}", tr("dry-run-header", &[]));
+    }
+    for (index, edited) in &changed {
+        let before = &store.tasks()[*index];
+        println!("~ {}. {}", index + 1, before.text);
+        print_field_changes(&audit::diff(Some(before), edited), false);
+    }
+    println!(
+        "{}",
+        tr(
+            "modify-done",
+            &[("count", &changed.len()), ("matched", &matched)]
+        )
+    );
+    if config.dry_run || changed.is_empty() {
+        return Ok(());
+    }
+    for (index, edited) in changed {
+        store.replace(index, edited)?;
+    }
+    save(config, &store, &[])
+}
+
+/// Lists, adds to or ticks off the checklist inside a task.
+fn check_task(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let mut store = open_listed(config)?;
+    let task_index = store.resolve(matches.value("task").unwrap_or_default())?;
+    let task_number = task_index + 1;
+    let mut task = store.tasks()[task_index].clone();
+    let args = matches.values("item");
+
+    let action = matches.value("action").unwrap_or("list");
+    match action {
+        "list" | "ls" => {
+            if task.checklist.is_empty() {
+                println!("{}", tr("check-empty", &[("number", &task_number)]));
+            }
+            for (i, item) in task.checklist.iter().enumerate() {
+                let mark = if item.done { "[x]" } else { "[ ]" };
+                println!("{} {}. {}", mark, i + 1, item.text);
+            }
+            return Ok(());
+        }
+        "add" => {
+            let text = validate::text(&args.join(" "), config.max_task_length)?;
+            task.checklist.push(CheckItem { text, done: false });
+            println!(
+                "{}",
+                tr(
+                    "check-added",
+                    &[
+                        ("item", &task.checklist.len()),
+                        ("number", &task_number),
+                        ("text", &task.checklist[task.checklist.len() - 1].text)
+                    ]
+                )
+            );
+        }
+        "done" | "undo" | "remove" | "rm" => {
+            if args.is_empty() {
+                return Err(Error::Invalid(tr("check-missing-item", &[])));
+            }
+            let mut items = Vec::new();
+            for arg in args {
+                match arg.parse::<usize>() {
+                    Ok(item) if (1..=task.checklist.len()).contains(&item) => items.push(item),
+                    _ => {
+                        return Err(Error::Invalid(tr(
+                            "check-no-item",
+                            &[
+                                ("item", arg),
+                                ("number", &task_number),
+                                ("count", &task.checklist.len()),
+                            ],
+                        )))
+                    }
+                }
+            }
+            // Highest first, so that removing one doesn't renumber the rest.
+            items.sort_unstable_by(|a, b| b.cmp(a));
+            items.dedup();
+            for item in items {
+                let message = match action {
+                    "done" => {
+                        task.checklist[item - 1].done = true;
+                        "check-done"
+                    }
+                    "undo" => {
+                        task.checklist[item - 1].done = false;
+                        "check-undone"
+                    }
+                    _ => {
+                        task.checklist.remove(item - 1);
+                        "check-removed"
+                    }
+                };
+                println!(
+                    "{}",
+                    tr(message, &[("item", &item), ("number", &task_number)])
+                );
+            }
+        }
+        other => {
+            return Err(Error::Invalid(tr(
+                "check-unknown-action",
+                &[("action", &other)],
+            )))
+        }
+    }
+    if let Some((done, total)) = task.progress() {
+        println!(
+            "{}",
+            tr("check-progress", &[("done", &done), ("total", &total)])
+        );
+    }
+    store.replace(task_index, task)?;
+    save(config, &store, &[])
+}
+
+/// Adds a comment to a task, or lists its comments when no text is given.
+fn comment_task(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let mut store = open_listed(config)?;
+    let task_index = store.resolve(matches.value("task").unwrap_or_default())?;
+    let task_number = task_index + 1;
+    let mut task = store.tasks()[task_index].clone();
+
+    let Some(text) = matches.joined("text") else {
+        if task.comments.is_empty() {
+            println!("{}", tr("comments-none", &[("number", &task_number)]));
+        }
+        for comment in &task.comments {
+            println!("{}", format_comment(comment, config));
+        }
+        return Ok(());
+    };
+    let author = match matches.value("author") {
+        Some(author) => validate::person(author)?,
+        None => audit::user(),
+    };
+    task.comments.push(Comment {
+        author,
+        at: DateTime::now(),
+        text: validate::comment(&text)?,
+    });
+    println!(
+        "{}",
+        tr(
+            "comment-added",
+            &[("number", &task_number), ("task", &task.text)]
+        )
+    );
+    store.replace(task_index, task)?;
+    save(config, &store, &[])
+}
+
+/// A comment as `comment` and `show` print it: when, who, and what.
+fn format_comment(comment: &Comment, config: &Config) -> String {
+    format!(
+        "{} UTC  {}: {}",
+        format_date_time(comment.at, config),
+        comment.author,
+        comment.text
+    )
+}
+
+/// Removes completed tasks, or every task with `--all`.
+fn clear_tasks(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let all = matches.flag("all");
+
+    let mut store = config.open_store()?;
+    let count = if all {
+        store.tasks().len()
+    } else {
+        store.tasks().iter().filter(|task| task.completed).count()
+    };
+    if count == 0 {
+        println!("{}", tr("clear-nothing", &[]));
+        return Ok(());
+    }
+
+    let (question, done) = if all {
+        ("confirm-clear-all", "cleared-all")
+    } else {
+        ("confirm-clear-completed", "cleared-completed")
+    };
+    if !confirm(&tr(question, &[("count", &count)]), config)? {
+        return Ok(());
+    }
+
+    let removed = store.clear(all);
+    println!("{}", tr(done, &[("count", &removed.len())]));
+    let events: Vec<_> = removed.iter().map(|task| (Event::Delete, task)).collect();
+    save(config, &store, &events)
+}
+
+/// Saves a copy of the whole list under a name, by default when it was taken.
+fn take_snapshot(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let tasks = config.backend()?.load()?;
+    let list = config.list.as_deref();
+    let name = match matches.value("name") {
+        Some(name) => name.to_string(),
+        None => {
+            // Two snapshots in the same minute get a number after the time.
+            let base = snapshot::default_name(DateTime::now());
+            let mut name = base.clone();
+            let mut n = 1;
+            while snapshot::path(list, &name)?.exists() {
+                n += 1;
+                name = format!("{}-{}", base, n);
+            }
+            name
+        }
+    };
+    if config.read_only {
+        let path = snapshot::path(list, &name)?;
+        return Err(Error::ReadOnly(path.display().to_string()));
+    }
+    if config.dry_run {
+        println!(
+            "{}",
+            tr(
+                "snapshot-dry-run",
+                &[("name", &name), ("count", &tasks.len())]
+            )
+        );
+        return Ok(());
+    }
+    snapshot::take(list, &name, &tasks)?;
+    println!(
+        "{}",
+        tr(
+            "snapshot-taken",
+            &[("name", &name), ("count", &tasks.len())]
+        )
+    );
+    Ok(())
+}
+
+/// Lists the list's snapshots, oldest first.
+fn list_snapshots(config: &Config) -> todo_cli::Result<()> {
+    let snapshots = snapshot::list(config.list.as_deref())?;
+    if snapshots.is_empty() {
+        println!("{}", tr("snapshots-none", &[]));
+        return Ok(());
+    }
+    let width = snapshots
+        .iter()
+        .map(|s| s.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    for snapshot in &snapshots {
+        println!(
+            "{:width$}  {}  {}",
+            snapshot.name,
+            snapshot.taken,
+            tr("snapshot-tasks", &[("count", &snapshot.tasks)]),
+            width = width
+        );
+    }
+    Ok(())
+}
+
+/// Puts the list back as it was in a snapshot, after asking. Tasks keep
+/// their ids, so each one's history carries on.
+fn restore_snapshot(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let name = matches.value("name").unwrap_or_default();
+    let tasks = snapshot::load(config.list.as_deref(), name)?;
+    let mut store = config.open_store()?;
+    if sync::differences(store.tasks(), &tasks) == 0 {
+        println!("{}", tr("restore-nothing", &[("name", &name)]));
+        return Ok(());
+    }
+    let question = tr(
+        "confirm-restore",
+        &[("name", &name), ("count", &store.tasks().len())],
+    );
+    if !confirm(&question, config)? {
+        return Ok(());
+    }
+    let changed = sync::apply(&mut store, &tasks)?;
+    println!(
+        "{}",
+        tr("restored", &[("name", &name), ("count", &changed)])
+    );
+    save(config, &store, &[])
 }
 
-// Define the file path where the to-do list will be stored.
-const DB_PATH: &str = "todos.json";
+/// Shows the tasks added, removed and changed between two list files or
+/// snapshots, or between one and the list as it is now.
+fn diff_lists(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    // A file of that name, or else a snapshot.
+    let read = |what: &str| {
+        let path = Path::new(what);
+        if path.is_file() {
+            snapshot::read(path)
+        } else {
+            snapshot::load(config.list.as_deref(), what)
+        }
+    };
+    let a = read(matches.value("a").unwrap_or_default())?;
+    let b = match matches.value("b") {
+        Some(b) => read(b)?,
+        None => config.backend()?.load()?,
+    };
+    let differences = snapshot::compare(&a, &b);
+    if differences.is_empty() {
+        println!("{}", tr("diff-none", &[]));
+        return Ok(());
+    }
+    let color = use_color(config);
+    let (mut added, mut removed, mut changed) = (0, 0, 0);
+    for difference in &differences {
+        let task = difference.task();
+        let (sign, code) = match difference {
+            Difference::Added(_) => {
+                added += 1;
+                ('+', "32")
+            }
+            Difference::Removed(_) => {
+                removed += 1;
+                ('-', "31")
+            }
+            Difference::Changed { .. } => {
+                changed += 1;
+                ('~', "33")
+            }
+        };
+        let line = format!("{} id:{} {}", sign, task.id, task.text);
+        if color {
+            println!("\x1b[{}m{}\x1b[0m", code, line);
+        } else {
+            println!("{}", line);
+        }
+        if let Difference::Changed { fields, .. } = difference {
+            print_field_changes(fields, false);
+        }
+    }
+    println!(
+        "{}",
+        tr(
+            "diff-summary",
+            &[
+                ("added", &added),
+                ("removed", &removed),
+                ("changed", &changed)
+            ]
+        )
+    );
+    Ok(())
+}
 
-fn main() -> io::Result<()> {
-    // Collect command-line arguments into a vector of strings.
-    let args: Vec<String> = env::args().collect();
+/// Takes back one change, given by the id `history` shows, or the latest
+/// change to the list, keeping everything changed since. Refuses, saying
+/// why, if a later change stands in the way.
+fn undo_change(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let mut store = config.open_store()?;
+    let mut entries = audit::list_entries(&store.location())?;
+    let change = match matches.value("change") {
+        Some(change) => change.trim().to_string(),
+        None => match entries.iter().rev().find_map(|e| e.change.clone()) {
+            Some(change) => change,
+            None => return Err(Error::Invalid(tr("undo-no-changes", &[]))),
+        },
+    };
+    entries.retain(|entry| entry.change.as_deref() == Some(change.as_str()));
+    if entries.is_empty() {
+        return Err(Error::Invalid(tr("undo-unknown", &[("change", &change)])));
+    }
+    let tasks = match undo::undo(store.tasks(), &entries) {
+        Ok(tasks) => tasks,
+        Err(conflicts) => {
+            for conflict in &conflicts {
+                let line = match conflict {
+                    undo::Conflict::Removed { id, text } => {
+                        tr("undo-conflict-removed", &[("id", id), ("task", text)])
+                    }
+                    undo::Conflict::Changed { id, text, field } => tr(
+                        "undo-conflict-changed",
+                        &[("id", id), ("task", text), ("field", field)],
+                    ),
+                    undo::Conflict::Unrecorded { id, text } => {
+                        tr("undo-conflict-unrecorded", &[("id", id), ("task", text)])
+                    }
+                    undo::Conflict::Sealed { id, text } => {
+                        tr("undo-conflict-sealed", &[("id", id), ("task", text)])
+                    }
+                };
+                eprintln!("  {}", line);
+            }
+            return Err(Error::Failed(tr("undo-conflicts", &[("change", &change)])));
+        }
+    };
+    let changed = sync::apply(&mut store, &tasks)?;
+    if changed == 0 {
+        println!("{}", tr("undo-nothing", &[("change", &change)]));
+        return Ok(());
+    }
+    println!(
+        "{}",
+        tr("undone", &[("change", &change), ("count", &changed)])
+    );
+    save(config, &store, &[])
+}
 
-    // The first argument is the program name, so we need at least one more for a command.
-    if args.len() < 2 {
-        print_help();
+/// Takes the tasks completed more than an age ago off the list, archiving
+/// them or, per `--delete` or `prune_action`, removing them.
+fn prune_tasks(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let days = match matches.value("completed-before") {
+        Some(age) => config::parse_age(age).map_err(Error::Invalid)?,
+        None if config.prune_after > 0 => config.prune_after,
+        None => return Err(Error::Invalid(tr("prune-no-age", &[]))),
+    };
+    let delete = match (matches.flag("delete"), matches.flag("archive")) {
+        (true, _) => true,
+        (false, true) => false,
+        _ => config.prune_action == PruneAction::Delete,
+    };
+    let cutoff = DateTime::now().add_minutes(-i64::from(days) * 24 * 60);
+    let mut store = config.open_store()?;
+    let stale: Vec<usize> = store
+        .tasks()
+        .iter()
+        .enumerate()
+        .filter(|(_, task)| task.completed && task.completed_at.is_some_and(|at| at < cutoff))
+        .map(|(index, _)| index)
+        .collect();
+    if stale.is_empty() {
+        println!("{}", tr("prune-nothing", &[("days", &days)]));
         return Ok(());
     }
+    if !delete {
+        let archived = archive_tasks(config, &mut store, &stale)?;
+        println!(
+            "{}",
+            tr(
+                "pruned-archived",
+                &[("count", &archived.len()), ("days", &days)]
+            )
+        );
+        return save(config, &store, &[]);
+    }
+    let mut removed = Vec::new();
+    for &index in stale.iter().rev() {
+        removed.push(store.remove(index)?);
+    }
+    println!(
+        "{}",
+        tr(
+            "pruned-deleted",
+            &[("count", &removed.len()), ("days", &days)]
+        )
+    );
+    let events: Vec<_> = removed.iter().map(|task| (Event::Delete, task)).collect();
+    save(config, &store, &events)
+}
 
-    // Match the command provided by the user (the second argument).
-    let command = &args[1];
-    match command.as_str() {
-        "add" => add_task(&args)?,
-        "list" => list_tasks()?,
-        "done" => complete_task(&args)?,
-        "help" => print_help(),
-        _ => {
-            println!("Error: Unknown command '{}'", command);
-            print_help();
+/// Moves the tasks at `indices` from `store` to the list's archive and saves
+/// the archive; the caller saves `store`. Returns the tasks moved.
+fn archive_tasks(
+    config: &Config,
+    store: &mut TaskStore,
+    indices: &[usize],
+) -> todo_cli::Result<Vec<Task>> {
+    let archive_config = config.archive();
+    let mut archive = archive_config.open_store()?;
+    let mut indices = indices.to_vec();
+    indices.sort_unstable();
+    let mut moved = Vec::new();
+    // Removing from the back keeps the earlier indices valid.
+    for &index in indices.iter().rev() {
+        let task = store.archive(index)?;
+        // Ids are only unique within a list.
+        if archive.position(task.id).is_some() {
+            archive.add(task.clone())?;
+        } else {
+            archive.insert(task.clone())?;
+        }
+        moved.push(task);
+    }
+    moved.reverse();
+    save(&archive_config, &archive, &[])?;
+    Ok(moved)
+}
+
+/// Lists the tasks in the trash, puts some back or empties it.
+fn trash_command(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    match &matches.subcommand {
+        Some(("restore", sub)) => restore_trashed(sub, config),
+        Some(("empty", _)) => empty_trash(config),
+        _ => list_trash(config),
+    }
+}
+
+/// Lists the tasks in the trash, oldest first.
+fn list_trash(config: &Config) -> todo_cli::Result<()> {
+    let trashed = config.trash().load(DateTime::now())?;
+    if trashed.is_empty() {
+        println!("{}", tr("trash-empty", &[]));
+        return Ok(());
+    }
+    for t in &trashed {
+        println!(
+            "id:{}  {}  {}",
+            t.task.id,
+            format_date_time(t.deleted, config),
+            t.task.text
+        );
+    }
+    Ok(())
+}
+
+/// Puts tasks from the trash back on the list, with their ids.
+fn restore_trashed(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let trash = config.trash();
+    let mut trashed = trash.load(DateTime::now())?;
+    let mut store = config.open_store()?;
+    let mut restored = Vec::new();
+    for id in matches.values("id") {
+        let Some(found) = id
+            .trim()
+            .trim_start_matches("id:")
+            .parse::<u64>()
+            .ok()
+            .and_then(|id| trashed.iter().position(|t| t.task.id == id))
+        else {
+            return Err(Error::Invalid(tr("trash-not-found", &[("id", &id)])));
+        };
+        let task = trashed.remove(found).task;
+        if store.position(task.id).is_some() {
+            return Err(Error::Invalid(tr(
+                "trash-on-list",
+                &[("id", &task.id), ("task", &task.text)],
+            )));
         }
+        restored.push(store.insert(task)?);
+    }
+    for &index in &restored {
+        let task = &store.tasks()[index];
+        println!(
+            "{}",
+            tr(
+                "trash-restored",
+                &[
+                    ("id", &task.id),
+                    ("task", &task.text),
+                    ("number", &(index + 1))
+                ]
+            )
+        );
+    }
+    let events: Vec<_> = restored
+        .iter()
+        .map(|&i| (Event::Add, &store.tasks()[i]))
+        .collect();
+    save(config, &store, &events)?;
+    if !config.dry_run {
+        trash.save(&trashed)?;
     }
+    Ok(())
+}
 
+/// Removes every task in the trash for good, after asking.
+fn empty_trash(config: &Config) -> todo_cli::Result<()> {
+    let trash = config.trash();
+    let count = trash.load(DateTime::now())?.len();
+    if count == 0 {
+        println!("{}", tr("trash-empty", &[]));
+        return Ok(());
+    }
+    if config.read_only {
+        return Err(Error::ReadOnly(trash.path().display().to_string()));
+    }
+    if config.dry_run {
+        println!("{}", tr("trash-dry-run", &[("count", &count)]));
+        return Ok(());
+    }
+    if !confirm(&tr("confirm-empty-trash", &[("count", &count)]), config)? {
+        return Ok(());
+    }
+    trash.save(&[])?;
+    println!("{}", tr("trash-emptied", &[("count", &count)]));
     Ok(())
 }
 
-/// Adds a new task to the list.
-fn add_task(args: &[String]) -> io::Result<()> {
-    if args.len() < 3 {
-        println!("Error: Missing task description for 'add' command.");
-        println!("Example: todo_cli add \"Buy milk\"");
+/// Sets how long a task is expected to take, or clears it with `none`.
+fn estimate_task(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let mut store = open_listed(config)?;
+    let index = store.resolve(matches.value("task").unwrap_or_default())?;
+    let mut task = store.tasks()[index].clone();
+    task.estimate = match matches.value("duration").unwrap_or_default().trim() {
+        "none" => None,
+        duration => Some(timetrack::parse_duration(duration).map_err(Error::Invalid)?),
+    };
+    let message = match task.estimate {
+        Some(minutes) => tr(
+            "estimate-set",
+            &[
+                ("number", &(index + 1)),
+                ("task", &task.text),
+                ("time", &timetrack::format_duration(i64::from(minutes))),
+            ],
+        ),
+        None => tr(
+            "estimate-cleared",
+            &[("number", &(index + 1)), ("task", &task.text)],
+        ),
+    };
+    println!("{}", message);
+    store.replace(index, task)?;
+    save(config, &store, &[])
+}
+
+/// Starts or stops a task's timer, logs time, or compares estimates with the
+/// time spent; by default shows what is being timed.
+fn time_command(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    match &matches.subcommand {
+        Some(("start", sub)) => start_timer(sub, config),
+        Some(("stop", _)) => stop_timers(config),
+        Some(("log", sub)) => log_time(sub, config),
+        Some(("estimates", sub)) => show_estimates(sub, config),
+        Some(("report", sub)) => show_time_report(sub, config),
+        Some(("push", sub)) => push_time(sub, config),
+        _ => show_timers(config),
+    }
+}
+
+/// Starts timing a task, stopping the timer of any other.
+fn start_timer(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let mut store = open_listed(config)?;
+    let index = store.resolve(&matches.joined("task").unwrap_or_default())?;
+    let now = DateTime::now();
+    let task = &store.tasks()[index];
+    if task.completed {
+        return Err(Error::Invalid(tr(
+            "time-task-completed",
+            &[("number", &(index + 1)), ("task", &task.text)],
+        )));
+    }
+    if let Some(start) = task.timer() {
+        println!(
+            "{}",
+            tr(
+                "time-already-running",
+                &[
+                    ("number", &(index + 1)),
+                    ("task", &task.text),
+                    ("time", &format_date_time(start, config))
+                ]
+            )
+        );
+        return Ok(());
+    }
+    stop_running(&mut store, now)?;
+    let mut task = store.tasks()[index].clone();
+    task.sessions.push(timetrack::Session {
+        start: now,
+        end: None,
+    });
+    println!(
+        "{}",
+        tr(
+            "time-started",
+            &[("number", &(index + 1)), ("task", &task.text)]
+        )
+    );
+    store.replace(index, task)?;
+    save(config, &store, &[])
+}
+
+/// Stops the running timer.
+fn stop_timers(config: &Config) -> todo_cli::Result<()> {
+    let mut store = open_listed(config)?;
+    if !stop_running(&mut store, DateTime::now())? {
+        println!("{}", tr("time-not-running", &[]));
         return Ok(());
     }
-    let task_description = args[2..].join(" ");
+    save(config, &store, &[])
+}
+
+/// Stops every running timer in `store` at `now`, printing how long each
+/// ran. Returns whether any was.
+fn stop_running(store: &mut TaskStore, now: DateTime) -> todo_cli::Result<bool> {
+    let running: Vec<usize> = (0..store.tasks().len())
+        .filter(|&index| store.tasks()[index].timer().is_some())
+        .collect();
+    for &index in &running {
+        let mut task = store.tasks()[index].clone();
+        let session = task.timer().map_or(0, |start| start.minutes_until(now));
+        task.stop_timer(now);
+        println!(
+            "{}",
+            tr(
+                "time-stopped",
+                &[
+                    ("number", &(index + 1)),
+                    ("task", &task.text),
+                    ("time", &timetrack::format_duration(session)),
+                    ("total", &timetrack::format_duration(task.tracked(now)))
+                ]
+            )
+        );
+        store.replace(index, task)?;
+    }
+    Ok(!running.is_empty())
+}
+
+/// Records a session spent on a task after the fact: by default one that
+/// ended just now.
+fn log_time(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let mut store = open_listed(config)?;
+    let index = store.resolve(matches.value("task").unwrap_or_default())?;
+    let minutes = timetrack::parse_duration(matches.value("duration").unwrap_or_default())
+        .map_err(Error::Invalid)?;
+    if minutes == 0 {
+        return Err(Error::Invalid(tr("time-log-empty", &[])));
+    }
+    let now = DateTime::now();
+    let start = match matches.value("at") {
+        Some(at) => validate::reminder(at, now)?,
+        None => now.add_minutes(-i64::from(minutes)),
+    };
+    let mut task = store.tasks()[index].clone();
+    task.sessions.push(timetrack::Session {
+        start,
+        end: Some(start.add_minutes(i64::from(minutes))),
+    });
+    // Oldest first, with a running timer staying last.
+    task.sessions.sort_by_key(|s| (s.end.is_none(), s.start));
+    println!(
+        "{}",
+        tr(
+            "time-logged",
+            &[
+                ("number", &(index + 1)),
+                ("task", &task.text),
+                ("time", &timetrack::format_duration(i64::from(minutes))),
+                ("total", &timetrack::format_duration(task.tracked(now)))
+            ]
+        )
+    );
+    store.replace(index, task)?;
+    save(config, &store, &[])
+}
 
-    let mut todos = load_todos()?;
+/// Shows the task being timed, and for how long.
+fn show_timers(config: &Config) -> todo_cli::Result<()> {
+    let store = open_listed(config)?;
+    let now = DateTime::now();
+    let mut running = false;
+    for (index, task) in store.tasks().iter().enumerate() {
+        let Some(start) = task.timer() else {
+            continue;
+        };
+        running = true;
+        println!(
+            "{}",
+            tr(
+                "time-running",
+                &[
+                    ("number", &(index + 1)),
+                    ("task", &task.text),
+                    (
+                        "time",
+                        &timetrack::format_duration(start.minutes_until(now))
+                    ),
+                    ("since", &format_date_time(start, config))
+                ]
+            )
+        );
+    }
+    if !running {
+        println!("{}", tr("time-not-running", &[]));
+    }
+    Ok(())
+}
 
-    let new_todo = Todo {
-        task: task_description,
-        completed: false,
+/// Sends the finished sessions not sent yet to the configured time service.
+/// Sessions that can't be sent are reported and left for the next time.
+fn push_time(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let Some(service) = config.time_service else {
+        return Err(Error::Invalid(tr("push-no-service", &[])));
+    };
+    let (Some(account), Some(token)) = (&config.time_account, &config.time_token) else {
+        return Err(Error::Invalid(tr(
+            "push-no-account",
+            &[("service", &service)],
+        )));
     };
+    let since = match matches.value("since") {
+        Some(since) => Some(validate::due(since, Date::today())?),
+        None => None,
+    };
+    let store = config.open_store()?;
+    let mut pushed = billing::Pushed::load(config.list.as_deref())?;
+    let (mut sent, mut failed) = (0, 0);
+    for task in store.tasks() {
+        let project = task
+            .project
+            .as_ref()
+            .and_then(|project| config.time_projects.get(project))
+            .map(String::as_str);
+        for session in &task.sessions {
+            let new = session.end.is_some()
+                && session.minutes(DateTime::now()) > 0
+                && since.is_none_or(|since| session.start.date() >= since)
+                && !pushed.contains(service, task, session);
+            if !new {
+                continue;
+            }
+            let line = tr(
+                if config.dry_run {
+                    "push-entry-dry-run"
+                } else {
+                    "push-entry"
+                },
+                &[
+                    ("time", &format_date_time(session.start, config)),
+                    (
+                        "duration",
+                        &timetrack::format_duration(session.minutes(DateTime::now())),
+                    ),
+                    ("task", &task.text),
+                ],
+            );
+            let request = match billing::request(service, account, token, project, task, session) {
+                Ok(request) => request,
+                Err(message) => {
+                    eprintln!("{}", tr("warning", &[("message", &message)]));
+                    failed += 1;
+                    continue;
+                }
+            };
+            if config.dry_run {
+                println!("{}", line);
+                continue;
+            }
+            let headers: Vec<(&str, &str)> = request
+                .headers
+                .iter()
+                .map(|(name, value)| (*name, value.as_str()))
+                .collect();
+            // Not retried: a request that timed out may still have made the entry.
+            let error =
+                match config
+                    .http
+                    .send("POST", &request.url, &headers, request.body.as_bytes())
+                {
+                    Ok(response) if (200..300).contains(&response.status) => {
+                        println!("{}", line);
+                        pushed.insert(service, task, session);
+                        sent += 1;
+                        continue;
+                    }
+                    Ok(response) => tr(
+                        "push-rejected",
+                        &[
+                            ("status", &response.status),
+                            ("body", &String::from_utf8_lossy(&response.body).trim()),
+                        ],
+                    ),
+                    Err(e) => e.to_string(),
+                };
+            let message = tr("push-failed", &[("task", &task.text), ("error", &error)]);
+            eprintln!("{}", tr("warning", &[("message", &message)]));
+            failed += 1;
+        }
+    }
+    if config.dry_run {
+        println!("{}", tr("dry-run-header", &[]));
+        return Ok(());
+    }
+    pushed.save()?;
+    println!(
+        "{}",
+        tr("push-done", &[("count", &sent), ("service", &service)])
+    );
+    if failed > 0 {
+        return Err(Error::Failed(tr("push-some-failed", &[("count", &failed)])));
+    }
+    Ok(())
+}
+
+/// The heading of a time report's first column.
+fn group_heading(by: timetrack::Group) -> String {
+    match by {
+        timetrack::Group::Task => tr("estimates-task", &[]),
+        timetrack::Group::Project => tr("estimates-project", &[]),
+        timetrack::Group::Tag => tr("estimates-tag", &[]),
+    }
+}
+
+/// The name of a row of a time report: `name`, or what stands for the tasks
+/// without a project or tag.
+fn group_name(name: Option<&str>, by: timetrack::Group) -> String {
+    match (name, by) {
+        (Some(name), _) => name.to_string(),
+        (None, timetrack::Group::Tag) => tr("time-no-tag", &[]),
+        (None, _) => tr("time-no-project", &[]),
+    }
+}
 
-    println!("Adding task: {}", new_todo.task);
-    todos.push(new_todo);
-    save_todos(&todos)
+/// Prints the time spent per project, tag or task over a range of days, with
+/// each one's share of the total.
+fn show_time_report(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let by: timetrack::Group = match matches.value("group-by") {
+        Some(by) => by.parse().map_err(Error::Invalid)?,
+        None => timetrack::Group::Project,
+    };
+    let today = Date::today();
+    let since = match matches.value("since") {
+        Some(since) => Some(validate::due(since, today)?),
+        None => None,
+    };
+    let until = match matches.value("until") {
+        Some(until) => validate::due(until, today)?,
+        None => today,
+    };
+    let store = config.open_store()?;
+    let now = DateTime::now();
+    let rows = timetrack::report(store.tasks(), by, since, until, now);
+    let heading = match since {
+        Some(since) => tr(
+            "time-spent-between",
+            &[
+                ("first", &since.format(&config.date_format)),
+                ("last", &until.format(&config.date_format)),
+            ],
+        ),
+        None => tr(
+            "time-report-until",
+            &[("date", &until.format(&config.date_format))],
+        ),
+    };
+    println!("{}", heading);
+    if rows.is_empty() {
+        println!("{}", tr("time-spent-none", &[]));
+        return Ok(());
+    }
+    // Added up per task, since a task with several tags is in several rows.
+    let total: i64 = timetrack::report(store.tasks(), timetrack::Group::Task, since, until, now)
+        .iter()
+        .map(|row| row.minutes)
+        .sum();
+    let total_label = tr("estimates-total", &[]);
+    let names: Vec<String> = rows
+        .iter()
+        .map(|row| group_name(row.name.as_deref(), by))
+        .collect();
+    let heading = group_heading(by);
+    let width = names
+        .iter()
+        .chain([&total_label, &heading])
+        .map(|name| name.chars().count())
+        .max()
+        .unwrap_or(0);
+    if config.plain {
+        let (hours, count) = (tr("time-report-hours", &[]), tr("time-report-tasks", &[]));
+        for (row, name) in rows.iter().zip(&names) {
+            let cells = [
+                (hours.clone(), timetrack::format_duration(row.minutes)),
+                (count.clone(), row.tasks.to_string()),
+                (
+                    String::new(),
+                    format!("{}%", (row.minutes * 100 + total / 2) / total.max(1)),
+                ),
+            ];
+            println!("{}", plain_row(name, &cells));
+        }
+        let cells = [(hours, timetrack::format_duration(total))];
+        println!("{}", plain_row(&total_label, &cells));
+        return Ok(());
+    }
+    println!(
+        "  {:width$}  {:>8}  {:>5}  {:>5}",
+        heading,
+        tr("time-report-hours", &[]),
+        tr("time-report-tasks", &[]),
+        "%"
+    );
+    for (row, name) in rows.iter().zip(&names) {
+        println!(
+            "  {:width$}  {:>8}  {:>5}  {:>4}%",
+            name,
+            timetrack::format_duration(row.minutes),
+            row.tasks,
+            (row.minutes * 100 + total / 2) / total.max(1)
+        );
+    }
+    println!(
+        "  {:width$}  {:>8}  {:>5}  {:>4}%",
+        total_label,
+        timetrack::format_duration(total),
+        "",
+        100
+    );
+    Ok(())
 }
 
-/// Lists all current tasks, showing their status.
-fn list_tasks() -> io::Result<()> {
-    let todos = load_todos()?;
+/// Prints the estimated and actual time of each task, project or tag with an
+/// estimate, and how the two compare overall.
+fn show_estimates(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let by: timetrack::Group = match matches.value("group-by") {
+        Some(by) => by.parse().map_err(Error::Invalid)?,
+        None => timetrack::Group::Task,
+    };
+    let store = config.open_store()?;
+    let tasks: Vec<Task> = store
+        .tasks()
+        .iter()
+        .filter(|task| task.completed || !matches.flag("done"))
+        .cloned()
+        .collect();
+    let now = DateTime::now();
+    let rows = timetrack::estimates(&tasks, by, now);
+    if rows.is_empty() {
+        println!("{}", tr("time-no-estimates", &[]));
+        return Ok(());
+    }
+    // Added up per task, since a task with several tags is in several rows.
+    let per_task = timetrack::estimates(&tasks, timetrack::Group::Task, now);
+    let total = timetrack::Estimate {
+        name: Some(tr("estimates-total", &[])),
+        estimate: per_task.iter().map(|row| row.estimate).sum(),
+        actual: per_task.iter().map(|row| row.actual).sum(),
+        tasks: per_task.len(),
+    };
+    let name = |row: &timetrack::Estimate| group_name(row.name.as_deref(), by);
+    let heading = group_heading(by);
+    let width = rows
+        .iter()
+        .chain([&total])
+        .map(|row| name(row).chars().count())
+        .chain([heading.chars().count()])
+        .max()
+        .unwrap_or(0);
+    if !config.plain {
+        println!(
+            "  {:width$}  {:>8}  {:>8}  {:>6}",
+            heading,
+            tr("estimates-estimate", &[]),
+            tr("estimates-actual", &[]),
+            "%",
+        );
+    }
+    for row in rows.iter().chain([&total]) {
+        let percent = row.percent().map(|p| format!("{}%", p)).unwrap_or_default();
+        if config.plain {
+            let cells = [
+                (
+                    tr("estimates-estimate", &[]),
+                    timetrack::format_duration(row.estimate),
+                ),
+                (
+                    tr("estimates-actual", &[]),
+                    timetrack::format_duration(row.actual),
+                ),
+                (String::new(), percent),
+            ];
+            println!("{}", plain_row(&name(row), &cells));
+            continue;
+        }
+        println!(
+            "  {:width$}  {:>8}  {:>8}  {:>6}",
+            name(row),
+            timetrack::format_duration(row.estimate),
+            timetrack::format_duration(row.actual),
+            percent,
+        );
+    }
+    if let Some(percent) = total.percent() {
+        let side = match total.actual.cmp(&total.estimate) {
+            std::cmp::Ordering::Greater => "over",
+            std::cmp::Ordering::Less => "under",
+            std::cmp::Ordering::Equal => "exact",
+        };
+        println!(
+            "{}",
+            tr(
+                "estimates-summary",
+                &[
+                    ("percent", &percent),
+                    (
+                        "time",
+                        &timetrack::format_duration((total.actual - total.estimate).abs())
+                    ),
+                    ("side", &side)
+                ]
+            )
+        );
+    }
+    Ok(())
+}
 
-    if todos.is_empty() {
-        println!("No tasks yet! Add one with the 'add' command.");
+/// Prints the time spent per task on a day, or on each day of a week, as a
+/// table or as CSV.
+fn show_timesheet(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let today = Date::today();
+    let date = match matches.value("date") {
+        Some(date) => validate::due(date, today)?,
+        None => today,
+    };
+    let (first, last) = if matches.flag("week") {
+        let first = date.start_of_week();
+        (first, first.add_days(6))
+    } else {
+        (date, date)
+    };
+    let store = config.open_store()?;
+    let rows = timetrack::timesheet(store.tasks(), first, last, DateTime::now());
+    if matches.flag("csv") {
+        print!("{}", timetrack::timesheet_csv(&rows, first));
+        return Ok(());
+    }
+    let heading = if first == last {
+        tr(
+            "time-spent-on",
+            &[("date", &first.format(&config.date_format))],
+        )
     } else {
-        println!("--- To-Do List ---");
-        for (i, todo) in todos.iter().enumerate() {
-            let status = if todo.completed { "[x]" } else { "[ ]" };
-            println!("{} {}. {}", status, i + 1, todo.task);
+        tr(
+            "time-spent-between",
+            &[
+                ("first", &first.format(&config.date_format)),
+                ("last", &last.format(&config.date_format)),
+            ],
+        )
+    };
+    println!("{}", heading);
+    if rows.is_empty() {
+        println!("{}", tr("time-spent-none", &[]));
+        return Ok(());
+    }
+    let days: Vec<Date> = (0..=first.days_until(last))
+        .map(|day| first.add_days(day))
+        .collect();
+    let names: Vec<String> = rows
+        .iter()
+        .map(|row| format!("{}. {}", row.index + 1, row.task.text))
+        .collect();
+    let total_label = tr("estimates-total", &[]);
+    let width = names
+        .iter()
+        .chain([&total_label])
+        .map(|name| name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let cell = |minutes: i64| match minutes {
+        0 => String::new(),
+        minutes => timetrack::format_duration(minutes),
+    };
+    if config.plain {
+        let mut totals = vec![0; days.len()];
+        for (row, name) in rows.iter().zip(&names) {
+            let mut cells: Vec<(String, String)> = days
+                .iter()
+                .zip(&row.minutes)
+                .map(|(day, &minutes)| (day.format("%a %d"), cell(minutes)))
+                .collect();
+            if days.len() > 1 {
+                cells.push((total_label.clone(), cell(row.total())));
+            }
+            for (total, minutes) in totals.iter_mut().zip(&row.minutes) {
+                *total += minutes;
+            }
+            println!("{}", plain_row(name, &cells));
+        }
+        let mut cells: Vec<(String, String)> = days
+            .iter()
+            .zip(&totals)
+            .map(|(day, &minutes)| (day.format("%a %d"), cell(minutes)))
+            .collect();
+        if days.len() > 1 {
+            cells.push((String::new(), cell(totals.iter().sum())));
+        }
+        println!("{}", plain_row(&total_label, &cells));
+        return Ok(());
+    }
+    let mut line = format!("  {:width$}", "");
+    for day in &days {
+        line.push_str(&format!("  {:>7}", day.format("%a %d")));
+    }
+    if days.len() > 1 {
+        line.push_str(&format!("  {:>7}", total_label));
+    }
+    println!("{}", line);
+    let mut totals = vec![0; days.len()];
+    for (row, name) in rows.iter().zip(&names) {
+        let mut line = format!("  {:width$}", name);
+        for (day, &minutes) in row.minutes.iter().enumerate() {
+            totals[day] += minutes;
+            line.push_str(&format!("  {:>7}", cell(minutes)));
         }
-        println!("------------------");
+        if days.len() > 1 {
+            line.push_str(&format!("  {:>7}", cell(row.total())));
+        }
+        println!("{}", line);
+    }
+    let mut line = format!("  {:width$}", total_label);
+    for &minutes in &totals {
+        line.push_str(&format!("  {:>7}", cell(minutes)));
+    }
+    if days.len() > 1 {
+        line.push_str(&format!("  {:>7}", cell(totals.iter().sum())));
+    }
+    println!("{}", line);
+    Ok(())
+}
+
+/// A row of a table as one line for plain output: its name, then each
+/// column's heading and value, leaving out the empty ones.
+fn plain_row(name: &str, cells: &[(String, String)]) -> String {
+    let cells: Vec<String> = cells
+        .iter()
+        .filter(|(_, value)| !value.is_empty())
+        .map(|(heading, value)| match heading.is_empty() {
+            true => value.clone(),
+            false => format!("{} {}", heading, value),
+        })
+        .collect();
+    format!("{}: {}", name, cells.join(", "))
+}
+
+/// Asks the user to confirm a destructive operation.
+///
+/// `--yes` skips the question. On a terminal the user is prompted; otherwise we
+/// fail, since there is nobody to answer and guessing could lose data.
+fn confirm(prompt: &str, config: &Config) -> todo_cli::Result<bool> {
+    // A dry run never changes anything, so there is nothing to confirm.
+    if config.assume_yes || config.dry_run {
+        return Ok(true);
+    }
+    if !io::stdin().is_terminal() {
+        return Err(Error::Failed(tr("confirm-refused", &[])));
+    }
+
+    let confirmed = ask_yes(prompt)?;
+    if !confirmed {
+        println!("{}", tr("cancelled", &[]));
+    }
+    Ok(confirmed)
+}
+
+/// Asks a yes/no question on the terminal; anything but yes is no.
+fn ask_yes(prompt: &str) -> io::Result<bool> {
+    print!("{} {} ", prompt, tr("confirm-choices", &[]));
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    let answer = answer.trim().to_lowercase();
+    Ok(i18n::tr_list("confirm-yes").contains(&answer))
+}
+
+/// Shows a desktop notification listing the tasks that are due or overdue.
+fn notify_due(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let store = config.open_store()?;
+    let today = Date::today();
+    let due = notify::due_tasks(store.tasks(), today);
+    // Reminders that went off today, up to now.
+    let midnight = tz::utc(DateTime::new(today, 0, 0).expect("midnight always exists"));
+    let reminded = notify::reminded_tasks(store.tasks(), midnight, DateTime::now());
+    let waiting = match config.waiting_nudge_days {
+        0 => Vec::new(),
+        days => notify::waiting_tasks(store.tasks(), today, days),
+    };
+    let notifications: Vec<_> = [
+        notify::summarize(&due, today),
+        notify::remind(&reminded),
+        notify::nudge(&waiting, today),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if notifications.is_empty() && matches.flag("print") {
+        println!("{}", tr("notify-nothing", &[]));
+    }
+    if config.dry_run {
+        println!("{}", tr("dry-run-notify", &[]));
+    }
+    for notification in &notifications {
+        if matches.flag("print") || config.dry_run {
+            println!("{}\n{}", notification.title, notification.body);
+            continue;
+        }
+        notify::send(notification)
+            .map_err(|e| Error::Failed(tr("notify-failed", &[("error", &e)])))?;
     }
     Ok(())
 }
 
-/// Marks a task as complete by its number.
-fn complete_task(args: &[String]) -> io::Result<()> {
-    if args.len() < 3 {
-        println!("Error: Missing task number for 'done' command.");
-        println!("Example: todo_cli done 2");
+/// Announces what has come due since the last run, like the daemon would,
+/// for running from cron or a systemd timer. What was announced is saved, so
+/// nothing is announced twice, and a run with nothing new prints nothing.
+fn notify_new(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let path = daemon::notified_path(config.list.as_deref());
+    let mut reminders =
+        daemon::Reminders::load(&path, &config.reminder_leads, config.waiting_nudge_days)?;
+    let tasks = config.backend()?.load()?;
+    let now = DateTime::now();
+    let today = now.date();
+    let due = reminders.check(&tasks, today);
+    let waiting = reminders.nudges(&tasks, today);
+    let rung = reminders.ring(&tasks, now);
+    // In the order they were marked as announced above, bar the nudges.
+    let notifications = [
+        daemon::reminder(&due, today),
+        notify::remind(&rung),
+        notify::nudge(&waiting, today),
+    ];
+    // A dry run shows what would be announced, and remembers none of it.
+    if config.dry_run {
+        println!("{}", tr("dry-run-notify", &[]));
+    }
+    for (sent, notification) in notifications.iter().enumerate() {
+        let Some(notification) = notification else {
+            continue;
+        };
+        if matches.flag("print") || config.dry_run {
+            println!("{}\n{}", notification.title, notification.body);
+            continue;
+        }
+        if let Err(e) = notify::send(notification) {
+            // What went out before the failure is remembered; the rest is
+            // tried again next time.
+            let mut delivered =
+                daemon::Reminders::load(&path, &config.reminder_leads, config.waiting_nudge_days)?;
+            if sent > 0 {
+                delivered.check(&tasks, today);
+            }
+            if sent > 1 {
+                delivered.ring(&tasks, now);
+            }
+            delivered.save(&path, now)?;
+            return Err(Error::Failed(tr("notify-failed", &[("error", &e)])));
+        }
+    }
+    if config.dry_run {
         return Ok(());
     }
+    reminders.save(&path, now)
+}
 
-    let task_number_str = &args[2];
-    let task_number: usize = match task_number_str.parse() {
-        Ok(num) => num,
-        Err(_) => {
-            println!("Error: '{}' is not a valid number.", task_number_str);
-            return Ok(());
+/// Starts, stops or runs the reminder daemon.
+fn daemon_command(matches: &Matches, config: &Config, config_path: &Path) -> todo_cli::Result<()> {
+    let pid_file = daemon::pid_path();
+    match &matches.subcommand {
+        Some(("start", _)) => start_daemon(config, config_path, &pid_file)?,
+        Some(("stop", _)) => match daemon::stop(&pid_file)? {
+            Some(pid) => println!("{}", tr("daemon-stopped", &[("pid", &pid)])),
+            None => println!("{}", tr("daemon-not-running", &[])),
+        },
+        Some(("run", _)) => {
+            let _lock = daemon::PidLock::acquire(&pid_file)?;
+            let backend = config.backend()?;
+            println!(
+                "{}",
+                tr(
+                    "daemon-watching",
+                    &[("pid", &process::id()), ("path", &backend.location())]
+                )
+            );
+            daemon::watch(
+                backend.as_ref(),
+                &config.reminder_leads,
+                config.waiting_nudge_days,
+                |notification| {
+                    println!("{}: {}", Date::today(), notification.title);
+                    if let Err(e) = notify::send(&notification) {
+                        warn(&tr("notify-failed", &[("error", &e)]));
+                    }
+                },
+                |e| warn(&e.to_string()),
+            );
+        }
+        _ => match daemon::running(&pid_file) {
+            Some(pid) => println!("{}", tr("daemon-running", &[("pid", &pid)])),
+            None => println!("{}", tr("daemon-not-running", &[])),
+        },
+    }
+    Ok(())
+}
+
+/// Launches `daemon run` as a detached background process.
+fn start_daemon(config: &Config, config_path: &Path, pid_file: &Path) -> todo_cli::Result<()> {
+    if let Some(pid) = daemon::running(pid_file) {
+        return Err(Error::Failed(tr(
+            "daemon-already-running",
+            &[("pid", &pid)],
+        )));
+    }
+    let log_path = daemon::log_path();
+    if let Some(dir) = log_path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let log = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)?;
+
+    // Pass on the settings that were given as flags, so the daemon watches the same list.
+    let mut command = Command::new(env::current_exe()?);
+    command
+        .arg("--config")
+        .arg(config_path)
+        .arg("--file")
+        .arg(&config.storage_path)
+        .arg("--list")
+        .arg(config.list.as_deref().unwrap_or_default())
+        .args(vec!["-v"; config.verbosity])
+        .args(["daemon", "run"])
+        .stdin(process::Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log);
+    // Its own process group keeps it running after the terminal closes.
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    let mut child = command.spawn()?;
+
+    // Give it a moment, so a daemon that fails straight away is reported here.
+    std::thread::sleep(std::time::Duration::from_millis(300));
+    if let Some(status) = child.try_wait()? {
+        return Err(Error::Failed(tr(
+            "daemon-exited",
+            &[("status", &status), ("path", &log_path.display())],
+        )));
+    }
+    println!(
+        "{}",
+        tr(
+            "daemon-started",
+            &[("pid", &child.id()), ("path", &log_path.display())]
+        )
+    );
+    Ok(())
+}
+
+/// Serves the API until the process is stopped.
+fn serve_command(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let host = matches.value("host").unwrap_or("127.0.0.1");
+    let port: u16 = matches
+        .get("port")
+        .unwrap_or_else(|e| exit_with(e))
+        .unwrap_or(8080);
+    let token = match matches.value("token").map(String::from) {
+        Some(token) => token,
+        None => match env::var("TODO_API_TOKEN") {
+            Ok(token) if !token.is_empty() => token,
+            _ => {
+                let token = serve::generate_token()
+                    .map_err(|e| Error::Failed(tr("token-failed", &[("error", &e)])))?;
+                println!("{}", tr("api-token", &[("token", &token)]));
+                token
+            }
+        },
+    };
+    serve::serve(config, &format!("{}:{}", host, port), &token)
+}
+
+/// Serves the read-only web page of the list until the process is stopped.
+fn dashboard_command(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let host = matches.value("host").unwrap_or("127.0.0.1");
+    let port: u16 = matches
+        .get("port")
+        .unwrap_or_else(|e| exit_with(e))
+        .unwrap_or(9000);
+    let refresh: u64 = matches
+        .get("refresh")
+        .unwrap_or_else(|e| exit_with(e))
+        .unwrap_or(60);
+    dashboard::serve(config, &format!("{}:{}", host, port), refresh.max(1))
+}
+
+/// Syncs the list with other instances on the local network: once, or for
+/// as long as the process runs, answering their syncs in between.
+fn sync_command(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let name = sync::instance_name();
+    let token = matches
+        .value("token")
+        .map(String::from)
+        .or_else(|| env::var("TODO_SYNC_TOKEN").ok())
+        .filter(|token| !token.is_empty());
+    let port: u16 = matches
+        .get("port")
+        .unwrap_or_else(|e| exit_with(e))
+        .unwrap_or(0);
+    let every: u64 = matches
+        .get("every")
+        .unwrap_or_else(|e| exit_with(e))
+        .unwrap_or(60);
+    let given = matches.values("peer");
+    let list = config.list.as_deref();
+
+    // Finds the peers to sync with. Two instances that find each other
+    // would both start a sync; only the one with the lower name does.
+    // Returns how many were found, and how many of them failed.
+    let round = |once: bool| {
+        let mut peers: Vec<(String, String)> = given
+            .iter()
+            .map(|addr| (addr.clone(), addr.clone()))
+            .collect();
+        if given.is_empty() {
+            match mdns::browse(Duration::from_secs(2)) {
+                Ok(found) => peers.extend(
+                    found
+                        .into_iter()
+                        .filter(|peer| peer.name != name && peer.list.as_deref() == list)
+                        .filter(|peer| once || peer.name > name)
+                        .map(|peer| (peer.name, peer.addr.to_string())),
+                ),
+                Err(e) => eprintln!("{}", tr("sync-browse-failed", &[("error", &e)])),
+            }
         }
+        let mut failed = 0;
+        for (peer, addr) in &peers {
+            match sync::sync_with(config, &name, addr, token.as_deref()) {
+                Ok(synced) => print_synced(&synced),
+                Err(e) => {
+                    eprintln!("{}", tr("sync-failed", &[("peer", peer), ("error", &e)]));
+                    failed += 1;
+                }
+            }
+        }
+        (peers.len(), failed)
     };
 
-    if task_number == 0 {
-        println!("Error: Task number must be 1 or greater.");
+    if matches.flag("once") {
+        let (found, failed) = round(true);
+        if found == 0 {
+            println!("{}", tr("sync-no-peers", &[]));
+        }
+        if failed > 0 {
+            return Err(Error::Failed(tr("sync-incomplete", &[("count", &failed)])));
+        }
         return Ok(());
     }
 
-    let mut todos = load_todos()?;
-    let task_index = task_number - 1;
+    // Anyone on the network could read and replace an unguarded list.
+    if token.is_none() && !matches.flag("insecure") {
+        return Err(Error::Invalid(tr("sync-token-missing", &[])));
+    }
+    let listener = TcpListener::bind(("0.0.0.0", port))?;
+    let port = listener.local_addr()?.port();
+    listener.set_nonblocking(true)?;
+    match mdns::Responder::bind(&name, port, list) {
+        Ok(responder) => {
+            thread::spawn(move || {
+                let e = responder.run();
+                eprintln!("{}", tr("sync-announce-failed", &[("error", &e)]));
+            });
+        }
+        Err(e) => eprintln!("{}", tr("sync-announce-failed", &[("error", &e)])),
+    }
+    println!(
+        "{}",
+        tr(
+            "sync-listening",
+            &[
+                ("name", &name),
+                ("list", &list.unwrap_or("main")),
+                ("port", &port)
+            ]
+        )
+    );
+    let mut next_round = Instant::now();
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false)?;
+                stream.set_read_timeout(Some(config.http.timeout))?;
+                let (response, synced) = match http::Request::read_up_to(&stream, sync::MAX_BODY) {
+                    Ok(request) => sync::handle(config, &name, token.as_deref(), &request),
+                    Err(e) => (http::Response::error(400, &e.to_string()), None),
+                };
+                if let Err(e) = response.write_to(&stream) {
+                    eprintln!("{}", tr("warning", &[("message", &e)]));
+                }
+                if let Some(synced) = synced.filter(|synced| synced.here > 0) {
+                    print_synced(&synced);
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                if Instant::now() >= next_round {
+                    round(false);
+                    next_round = Instant::now() + Duration::from_secs(every.max(1));
+                }
+                thread::sleep(Duration::from_millis(200));
+            }
+            Err(e) => eprintln!("{}", tr("warning", &[("message", &e)])),
+        }
+    }
+}
 
-    if let Some(todo) = todos.get_mut(task_index) {
-        if todo.completed {
-            println!("Task {} was already completed.", task_number);
-        } else {
-            todo.completed = true;
-            println!("Completed task {}: {}", task_number, todo.task);
-            save_todos(&todos)?;
+fn print_synced(synced: &sync::Synced) {
+    println!(
+        "{}",
+        tr(
+            "sync-done",
+            &[
+                ("peer", &synced.peer),
+                ("here", &synced.here),
+                ("there", &synced.there)
+            ]
+        )
+    );
+    if synced.conflicts > 0 {
+        println!("{}", tr("sync-conflicts", &[("count", &synced.conflicts)]));
+    }
+}
+
+/// Answers MCP requests on stdin and stdout until stdin is closed.
+fn mcp_command(config: &Config) -> todo_cli::Result<()> {
+    // A dry run would print its report into the protocol stream.
+    if config.dry_run {
+        return Err(Error::Invalid(tr("dry-run-mcp", &[])));
+    }
+    mcp::serve(config, io::stdin().lock(), io::stdout().lock())?;
+    Ok(())
+}
+
+/// Runs a user script against the active list, then saves what it changed.
+fn run_script(matches: &Matches, config: &Config) -> todo_cli::Result<()> {
+    let name = matches.value("script").unwrap_or_default();
+    let Some(path) = find_script(name) else {
+        return Err(Error::Invalid(tr("script-not-found", &[("name", &name)])));
+    };
+    let source = fs::read_to_string(&path)?;
+
+    let mut store = config.open_store()?;
+    store.audit_as(format!("script:{}", name));
+    let outcome = script::run(
+        &path.display().to_string(),
+        &source,
+        &mut store,
+        matches.values("args"),
+        &mut io::stdout(),
+    )?;
+    if !outcome.changed {
+        return Ok(());
+    }
+    let events: Vec<_> = outcome.events.iter().map(|(e, task)| (*e, task)).collect();
+    save(config, &store, &events)
+}
+
+/// Finds a script by path, or by name in the scripts directory.
+fn find_script(name: &str) -> Option<PathBuf> {
+    let dir = config::config_dir().join("scripts");
+    [
+        PathBuf::from(name),
+        dir.join(name),
+        dir.join(format!("{}.todo", name)),
+    ]
+    .into_iter()
+    .find(|path| path.is_file())
+}
+
+/// Reads or changes settings in the config file.
+fn config_command(matches: &Matches, config: &Config, config_path: &Path) -> todo_cli::Result<()> {
+    match &matches.subcommand {
+        Some(("get", sub)) => {
+            let key = sub.value("key").unwrap_or_default();
+            match config.get(key) {
+                Some(value) => println!("{}", value),
+                None if config::KEYS.contains(&key) => {
+                    return Err(Error::Failed(tr("config-not-set", &[("key", &key)])))
+                }
+                None => return Err(Error::Invalid(tr("config-unknown-key", &[("key", &key)]))),
+            }
         }
-    } else {
-        println!("Error: No task found with number {}.", task_number);
+        Some(("set", sub)) => {
+            let key = sub.value("key").unwrap_or_default();
+            let value = sub.value("value").unwrap_or_default();
+            let shown = config::shown(key, value);
+            if config.dry_run {
+                println!(
+                    "{}",
+                    tr("dry-run-config", &[("key", &key), ("value", &shown)])
+                );
+                return Ok(());
+            }
+            config::set(config_path, key, value)?;
+            println!("{}", tr("config-set", &[("key", &key), ("value", &shown)]));
+        }
+        Some(("path", _)) => println!("{}", config_path.display()),
+        _ => {
+            for key in config::KEYS {
+                let value = config.get(key).unwrap_or_default();
+                println!("{} = {}", key, config::shown(key, &value));
+            }
+            for (name, expansion) in &config.aliases {
+                println!("aliases.{} = {}", name, expansion);
+            }
+            for (name, tasks) in &config.templates {
+                let tasks: Vec<&str> = template::lines(tasks).collect();
+                println!("templates.{} = {}", name, tasks.join(" / "));
+            }
+            for (name, view) in &config.views {
+                println!("views.{} = {}", name, view);
+            }
+            for (name, context) in &config.contexts {
+                println!("contexts.{} = {}", name, context);
+            }
+            for (name, token) in &config.api_users {
+                println!(
+                    "api_users.{} = {}",
+                    name,
+                    config::shown("api_users.", token)
+                );
+            }
+            for (name, id) in &config.time_projects {
+                println!("time_projects.{} = {}", name, id);
+            }
+            for (list, url) in &config.discord_webhooks {
+                println!(
+                    "discord_webhooks.{} = {}",
+                    list,
+                    config::shown("discord_webhooks.", url)
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Decides whether to emit ANSI colors, based on the config and the environment.
+fn use_color(config: &Config) -> bool {
+    if config.plain {
+        return false;
+    }
+    match config.color {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => env::var_os("NO_COLOR").is_none() && io::stdout().is_terminal(),
     }
+}
+
+/// Writes the store back to its file, then runs the hooks and sends the
+/// webhooks for `events`.
+///
+/// With `--dry-run` nothing is written and no hooks or webhooks run; the
+/// changes are printed instead.
+fn save(config: &Config, store: &TaskStore, events: &[(Event, &Task)]) -> todo_cli::Result<()> {
+    if config.dry_run {
+        print_changes(&store.saved()?, store.tasks());
+        return Ok(());
+    }
+    let merged;
+    let saved = match store.save() {
+        Ok(()) => store.tasks(),
+        // Someone else saved the list since we read it. Offer to apply our
+        // changes to theirs; --yes does so without asking.
+        Err(e @ Error::Conflict(_)) => {
+            if !config.assume_yes && !io::stdin().is_terminal() {
+                return Err(e);
+            }
+            eprintln!("{}", tr("list-changed", &[("path", &store.location())]));
+            if !confirm(&tr("confirm-merge", &[]), config)? {
+                return Err(e);
+            }
+            merged = store.save_merged()?;
+            &merged
+        }
+        Err(e) => return Err(e),
+    };
 
+    for (event, task) in events {
+        let json = serde_json::to_string(task).expect("Failed to serialize data to JSON.");
+        run_hook(config, store, *event, &json);
+    }
+    for (event, task) in events {
+        send_webhooks(config, *event, task);
+    }
+    if config.slack_alerts {
+        alert_slack(config, saved, events);
+    }
+    announce_on_discord(config, events);
+    let json = serde_json::to_string(saved).expect("Failed to serialize data to JSON.");
+    run_hook(config, store, Event::Save, &json);
     Ok(())
 }
 
-/// Loads the list of todos from the JSON file.
-fn load_todos() -> io::Result<Vec<Todo>> {
-    // Try to read the file. If it doesn't exist, that's okay, just return an empty list.
-    match fs::read_to_string(DB_PATH) {
-        Ok(data) => {
-            // If we read data, try to parse it as JSON into our Vec<Todo>.
-            let todos = serde_json::from_str(&data)
-                .expect("Failed to parse todos.json. The file might be corrupted.");
-            Ok(todos)
+/// Runs one hook. The change is already saved, so failures are only reported.
+fn run_hook(config: &Config, store: &TaskStore, event: Event, input: &str) {
+    let name = event.hook_name();
+    match hooks::run(&config.hooks_dir, event, input, &store.location()) {
+        Ok(Some(status)) if !status.success() => {
+            warn(&tr("hook-failed", &[("hook", &name), ("status", &status)]))
         }
-        Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
-            // If the file doesn't exist, return a new, empty vector.
-            Ok(Vec::new())
+        Ok(_) => {}
+        Err(e) => warn(&tr("hook-run-failed", &[("hook", &name), ("error", &e)])),
+    }
+}
+
+/// Sends the webhooks for one event, to all URLs at once so that one slow
+/// receiver doesn't hold up the others. Like hooks, failures are only reported.
+fn send_webhooks(config: &Config, event: Event, task: &Task) {
+    if config.webhook_urls.is_empty() {
+        return;
+    }
+    let body = webhooks::payload(event, task, config.list.as_deref());
+    let secret = config.webhook_secret.as_deref();
+    let results: Vec<_> = thread::scope(|scope| {
+        let deliveries: Vec<_> = config
+            .webhook_urls
+            .iter()
+            .map(|url| scope.spawn(|| webhooks::deliver(&config.http, url, &body, secret)))
+            .collect();
+        deliveries
+            .into_iter()
+            .map(|delivery| delivery.join().expect("webhook delivery panicked"))
+            .collect()
+    });
+    for (url, result) in config.webhook_urls.iter().zip(results) {
+        match result {
+            Ok(status) if !(200..300).contains(&status) => {
+                warn(&tr("webhook-status", &[("url", url), ("status", &status)]))
+            }
+            Ok(_) => {}
+            Err(e) => warn(&tr("webhook-failed", &[("url", url), ("error", &e)])),
         }
-        Err(e) => {
-            // For any other read error, propagate it up.
-            Err(e)
+    }
+}
+
+/// Alerts Slack straight away of the tasks just added overdue or high
+/// priority. The change is already saved, so failures are only reported.
+fn alert_slack(config: &Config, tasks: &[Task], events: &[(Event, &Task)]) {
+    let added: HashSet<u64> = events
+        .iter()
+        .filter(|(event, _)| matches!(event, Event::Add))
+        .map(|(_, task)| task.id)
+        .collect();
+    if added.is_empty() {
+        return;
+    }
+    let result = slack_target(config).and_then(|target| {
+        let path = slack::alerted_path(config.list.as_deref());
+        let mut alerted = slack::Alerted::load(&path)?;
+        let today = Date::today();
+        let mut alerts = Vec::new();
+        for (index, task) in tasks.iter().enumerate() {
+            if added.contains(&task.id) {
+                for reason in alerted.check_task(task, today) {
+                    alerts.push((index, task, reason));
+                }
+            }
+        }
+        let Some(text) = slack::alert(&alerts, &config.date_format) else {
+            return Ok(());
+        };
+        post_to_slack(&target, config, &text)?;
+        alerted.save(&path, tasks)
+    });
+    if let Err(e) = result {
+        warn(&e.to_string());
+    }
+}
+
+/// Announces the tasks just completed in the list's Discord channel, if it
+/// has one. The change is already saved, so failures are only reported.
+fn announce_on_discord(config: &Config, events: &[(Event, &Task)]) {
+    let Some(url) = config.discord_webhook() else {
+        return;
+    };
+    let done: Vec<&Task> = events
+        .iter()
+        .filter(|(event, _)| matches!(event, Event::Done))
+        .map(|(_, task)| *task)
+        .collect();
+    let user = audit::user();
+    let by = Some(user.as_str()).filter(|&user| user != "unknown");
+    let Some(text) = discord::completed(&done, by) else {
+        return;
+    };
+    if let Err(e) = discord::post(&config.http, url, &text) {
+        warn(&tr("discord-failed", &[("error", &e)]));
+    }
+}
+
+/// Prints a warning on stderr.
+fn warn(message: &str) {
+    eprintln!("{}", tr("warning", &[("message", &message)]));
+}
+
+/// Prints how the list would change from `before` to `after`, for `--dry-run`.
+fn print_changes(before: &[Task], after: &[Task]) {
+    // Tasks are matched by id, so an edit shows as the fields it changed;
+    // tasks without one can only be told apart by being equal.
+    let old_by_id: HashMap<u64, &Task> = before
+        .iter()
+        .filter(|task| task.id != 0)
+        .map(|task| (task.id, task))
+        .collect();
+    let new_ids: HashSet<u64> = after.iter().map(|task| task.id).collect();
+    let removed: Vec<&Task> = before
+        .iter()
+        .filter(|old| match old.id {
+            0 => !after.contains(old),
+            id => !new_ids.contains(&id),
+        })
+        .collect();
+    let mut added: Vec<&Task> = Vec::new();
+    let mut changed: Vec<(&Task, &Task)> = Vec::new();
+    for new in after {
+        match old_by_id.get(&new.id).filter(|_| new.id != 0) {
+            Some(old) if *old != new => changed.push((old, new)),
+            Some(_) => {}
+            None if new.id == 0 && before.contains(new) => {}
+            None => added.push(new),
+        }
+    }
+
+    println!("{}", tr("dry-run-header", &[]));
+    if removed.is_empty() && added.is_empty() && changed.is_empty() {
+        println!("  {}", tr("dry-run-nothing", &[]));
+        return;
+    }
+    for old in removed {
+        println!("  - {}", old.text);
+    }
+    for (old, new) in changed {
+        // A secret task's text is only a mask, and what it hides is sealed.
+        match (old.secret.is_some(), new.secret.is_some()) {
+            (false, true) => {
+                println!("  ~ {}: {}", old.text, tr("dry-run-encrypted", &[]));
+                continue;
+            }
+            (true, false) => {
+                println!("  ~ {}: {}", new.text, tr("dry-run-decrypted", &[]));
+                continue;
+            }
+            _ => {}
+        }
+        println!("  ~ {}", new.text);
+        let fields = audit::diff(Some(old), new);
+        if fields.is_empty() {
+            println!("    {}", tr("dry-run-sealed", &[]));
         }
+        print_field_changes(&fields, false);
+    }
+    for new in added {
+        println!("  + {}", new.text);
+    }
+}
+
+/// A workflow status as it is shown: translated if it is one of the usual ones.
+fn status_label(status: &str) -> String {
+    match status {
+        TODO => tr("status-pending", &[]),
+        DONE => tr("status-completed", &[]),
+        "doing" | CANCELLED => tr(&format!("status-{}", status), &[]),
+        other => other.to_string(),
     }
 }
 
-/// Saves the current list of todos to the JSON file.
-fn save_todos(todos: &[Todo]) -> io::Result<()> {
-    // Serialize the `todos` vector into a nicely formatted JSON string.
-    let json_data = serde_json::to_string_pretty(todos)
-        .expect("Failed to serialize data to JSON.");
-    
-    // Write the JSON string to our file.
-    fs::write(DB_PATH, json_data)
+/// Prints the man page for the program or a command, or writes them all to a directory.
+fn man_command(matches: &Matches) -> io::Result<()> {
+    if let Some(dir) = matches.value("out-dir") {
+        let count = man::write_all(Path::new(dir))?;
+        println!("{}", tr("man-written", &[("count", &count), ("dir", &dir)]));
+        return Ok(());
+    }
+
+    // Walk down the command tree, e.g. `man config set`.
+    let mut spec = &cli::ROOT;
+    let mut path = cli::BIN_NAME.to_string();
+    for name in matches.values("command") {
+        match spec.resolve(name) {
+            Ok(sub) => {
+                spec = sub;
+                path = format!("{} {}", path, sub.name);
+            }
+            Err(msg) => exit_with(cli::usage_error(spec, &path, msg)),
+        }
+    }
+    print!("{}", man::render(spec, &path));
+    Ok(())
 }
 
-/// Prints the help message showing available commands.
-fn print_help() {
-    println!("\nRust To-Do List Manager");
-    println!("Usage: todo_cli <COMMAND> [ARGUMENTS]");
-    println!("\nCommands:");
-    println!("  add \"<task>\"   - Adds a new task to the list.");
-    println!("  list           - Lists all tasks.");
-    println!("  done <number>  - Marks a task as complete.");
-    println!("  help           - Shows this help message.");
+/// Prints the help message for the whole program, or for one command.
+fn print_help(matches: &Matches) {
+    let Some(name) = matches.value("command") else {
+        print!("{}", cli::ROOT.help(cli::BIN_NAME));
+        return;
+    };
+    match cli::ROOT.resolve(name) {
+        Ok(spec) => print!("{}", spec.help(&format!("{} {}", cli::BIN_NAME, spec.name))),
+        Err(msg) => exit_with(cli::usage_error(&cli::ROOT, cli::BIN_NAME, msg)),
+    }
 }
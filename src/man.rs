@@ -0,0 +1,132 @@
+// Man page generation.
+//
+// Pages are rendered in roff straight from the command specs in `cli`, so they
+// always match `--help`. `todo_cli man` prints a page; `--out-dir` writes one
+// page per command, ready for packaging under `share/man/man1`.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::cli::{CommandSpec, BIN_NAME, ROOT};
+
+/// Renders the page for `spec`, which is invoked as `path` (e.g. `todo_cli config`).
+pub fn render(spec: &CommandSpec, path: &str) -> String {
+    let name = page_name(path);
+    let mut out = String::new();
+
+    out.push_str(&format!(
+        ".TH {} 1 \"\" \"{} {}\" \"User Commands\"\n",
+        escape(&name.to_uppercase()),
+        BIN_NAME,
+        env!("CARGO_PKG_VERSION")
+    ));
+
+    out.push_str(".SH NAME\n");
+    out.push_str(&format!("{} \\- {}\n", escape(&name), escape(spec.about)));
+
+    out.push_str(".SH SYNOPSIS\n");
+    let usage = spec.usage(path);
+    let usage = usage.strip_prefix("Usage: ").unwrap_or(&usage);
+    let (command, rest) = usage.split_at(path.len());
+    out.push_str(&format!("\\fB{}\\fR{}\n", escape(command), escape(rest)));
+
+    out.push_str(".SH DESCRIPTION\n");
+    out.push_str(&format!("{}.\n", escape(spec.about)));
+
+    let positionals: Vec<_> = spec.args.iter().filter(|a| a.is_positional()).collect();
+    if !positionals.is_empty() {
+        out.push_str(".SH ARGUMENTS\n");
+        for arg in positionals {
+            out.push_str(&format!(
+                ".TP\n\\fI{}\\fR\n{}\n",
+                escape(&arg.signature()),
+                escape(arg.help)
+            ));
+        }
+    }
+
+    out.push_str(".SH OPTIONS\n");
+    for arg in spec.args.iter().filter(|a| !a.is_positional()) {
+        let mut names = Vec::new();
+        if let Some(c) = arg.short {
+            names.push(format!("\\fB\\-{}\\fR", c));
+        }
+        for long in arg.long.iter().chain(arg.alias.iter()) {
+            names.push(format!("\\fB\\-\\-{}\\fR", escape(long)));
+        }
+        let mut term = names.join(", ");
+        if let Some(value) = arg.value_name {
+            term.push_str(&format!(" \\fI{}\\fR", escape(value)));
+        }
+        out.push_str(&format!(".TP\n{}\n{}\n", term, escape(arg.help)));
+    }
+    out.push_str(".TP\n\\fB\\-h\\fR, \\fB\\-\\-help\\fR\nPrint help\n");
+
+    if !spec.subcommands.is_empty() {
+        out.push_str(".SH COMMANDS\n");
+        for sub in spec.subcommands {
+            let sub_page = page_name(&format!("{} {}", path, sub.name));
+            out.push_str(&format!(
+                ".TP\n\\fB{}\\fR\n{}. See \\fB{}\\fR(1).\n",
+                escape(sub.name),
+                escape(sub.about),
+                escape(&sub_page)
+            ));
+        }
+    }
+
+    if !spec.after_help.is_empty() {
+        out.push_str(".SH NOTES\n");
+        for line in spec.after_help.lines() {
+            if line.is_empty() {
+                out.push_str(".PP\n");
+            } else if let Some(indented) = line.strip_prefix("  ") {
+                // Indented lines are examples or lists; keep them on their own lines.
+                out.push_str(&format!(".br\n\\ \\ {}\n", escape(indented)));
+            } else {
+                out.push_str(&format!("{}\n", escape(line)));
+            }
+        }
+    }
+
+    if path != BIN_NAME {
+        out.push_str(".SH SEE ALSO\n");
+        out.push_str(&format!("\\fB{}\\fR(1)\n", BIN_NAME));
+    }
+    out
+}
+
+/// Writes a page for the program and every (sub-)command into `dir`.
+///
+/// Returns the number of pages written.
+pub fn write_all(dir: &Path) -> io::Result<usize> {
+    fs::create_dir_all(dir)?;
+    write_tree(dir, &ROOT, BIN_NAME)
+}
+
+fn write_tree(dir: &Path, spec: &CommandSpec, path: &str) -> io::Result<usize> {
+    let file = dir.join(format!("{}.1", page_name(path)));
+    fs::write(file, render(spec, path))?;
+    let mut count = 1;
+    for sub in spec.subcommands {
+        count += write_tree(dir, sub, &format!("{} {}", path, sub.name))?;
+    }
+    Ok(count)
+}
+
+/// The page name for a command path, e.g. `todo_cli config set` -> `todo_cli-config-set`.
+fn page_name(path: &str) -> String {
+    path.replace(' ', "-")
+}
+
+/// Escapes text so roff prints it literally.
+fn escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    // A leading dot or quote would be read as a roff request.
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{}", escaped)
+    } else {
+        escaped
+    }
+}
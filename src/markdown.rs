@@ -0,0 +1,165 @@
+// Markdown checklists, as kept in notes and READMEs, read into tasks and
+// written out from them.
+//
+// `import --markdown` reads GitHub-style task lists:
+//
+//   # Home                 a heading: the project of the items under it
+//   - [ ] paint the shed   a task; `[x]` for one already done
+//     - [x] buy paint      nested under it: a step of its checklist
+//
+// Only items with a checkbox are read; other text, plain list items among
+// it, is left out. `-`, `*`, `+` and numbered items all count. A task has
+// one level of steps, so items nested deeper are steps of the same task.
+// A heading's project is the heading as one word, `Home Renovation` as
+// `home-renovation`, and lasts until the next heading of its level or
+// above.
+//
+// `export --markdown` writes the same form back: tasks without a project
+// first, then a heading for each project, and each task's due date,
+// priority, contexts and tags as the quick-add words `add` reads, so that
+// what is exported imports again as it was.
+
+use std::collections::BTreeMap;
+
+use crate::task::{normalize_project, CheckItem, Priority, Task};
+
+/// The tasks in the checklists of `text`, in order.
+pub fn parse(text: &str) -> Vec<Task> {
+    let mut tasks: Vec<Task> = Vec::new();
+    // The headings over the current line, as (level, project).
+    let mut headings: Vec<(usize, String)> = Vec::new();
+    // How far the last task was indented; deeper items are its steps.
+    let mut indent: Option<usize> = None;
+    for line in text.lines() {
+        if let Some((level, title)) = heading(line) {
+            headings.retain(|&(outer, _)| outer < level);
+            let project = project_name(title);
+            if !project.is_empty() {
+                headings.push((level, project));
+            }
+            indent = None;
+            continue;
+        }
+        let Some((depth, done, text)) = item(line) else {
+            continue;
+        };
+        match (indent, tasks.last_mut()) {
+            (Some(top), Some(task)) if depth > top => task.checklist.push(CheckItem {
+                text: text.to_string(),
+                done,
+            }),
+            _ => {
+                indent = Some(depth);
+                tasks.push(Task {
+                    text: text.to_string(),
+                    completed: done,
+                    project: headings.last().map(|(_, project)| project.clone()),
+                    ..Task::default()
+                });
+            }
+        }
+    }
+    tasks
+}
+
+/// The level and title of a heading line such as `## Errands`.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let title = line[level..].strip_prefix([' ', '\t'])?;
+    (1..=6)
+        .contains(&level)
+        .then(|| (level, title.trim().trim_end_matches('#').trim()))
+}
+
+/// How deep a checklist item is indented, whether it is ticked, and its
+/// text, e.g. `  - [x] buy paint`.
+fn item(line: &str) -> Option<(usize, bool, &str)> {
+    let rest = line.trim_start();
+    let depth = line[..line.len() - rest.len()]
+        .chars()
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum();
+    let rest = match rest.strip_prefix(['-', '*', '+']) {
+        Some(rest) => rest,
+        None => {
+            let digits = rest.chars().take_while(char::is_ascii_digit).count();
+            rest[digits..]
+                .strip_prefix(['.', ')'])
+                .filter(|_| digits > 0)?
+        }
+    };
+    let rest = rest.strip_prefix([' ', '\t'])?.trim_start();
+    let done = match rest.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+    let text = rest[3..].trim();
+    (!text.is_empty()).then_some((depth, done, text))
+}
+
+/// A heading as a project name: its words, lowercase, joined by dashes,
+/// without Markdown's emphasis and code marks.
+fn project_name(title: &str) -> String {
+    let words: Vec<&str> = title
+        .split(|c: char| c.is_whitespace() || matches!(c, '*' | '_' | '`'))
+        .filter(|word| !word.is_empty())
+        .collect();
+    normalize_project(&words.join("-"))
+}
+
+/// `tasks` as a checklist, grouped under a heading per project.
+pub fn render(tasks: &[&Task]) -> String {
+    let mut projects: BTreeMap<Option<&str>, Vec<&Task>> = BTreeMap::new();
+    for task in tasks {
+        projects
+            .entry(task.project.as_deref())
+            .or_default()
+            .push(task);
+    }
+    let mut out = String::new();
+    for (project, tasks) in projects {
+        if let Some(project) = project {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(&format!("## {}\n\n", project));
+        }
+        for task in tasks {
+            out.push_str(&format!("- {} {}\n", checkbox(task.completed), line(task)));
+            for step in &task.checklist {
+                out.push_str(&format!("  - {} {}\n", checkbox(step.done), step.text));
+            }
+        }
+    }
+    out
+}
+
+fn checkbox(done: bool) -> &'static str {
+    if done {
+        "[x]"
+    } else {
+        "[ ]"
+    }
+}
+
+/// A task's text followed by its details as quick-add words.
+fn line(task: &Task) -> String {
+    let mut line = task.text.clone();
+    if let Some(due) = task.due {
+        line.push_str(&format!(" due:{}", due));
+    }
+    match task.priority {
+        Some(Priority::High) => line.push_str(" p1"),
+        Some(Priority::Medium) => line.push_str(" p2"),
+        Some(Priority::Low) => line.push_str(" p3"),
+        None => {}
+    }
+    for context in &task.contexts {
+        line.push_str(&format!(" @{}", context));
+    }
+    for tag in &task.tags {
+        line.push_str(&format!(" #{}", tag));
+    }
+    line
+}
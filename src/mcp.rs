@@ -0,0 +1,254 @@
+// The Model Context Protocol server run by `todo_cli mcp`.
+//
+// AI assistants start the program as a subprocess and talk JSON-RPC 2.0 over
+// its stdin and stdout, one message per line. We only offer tools: listing,
+// adding, completing and deleting tasks. Since stdout carries the protocol,
+// nothing else may be printed there; warnings go to stderr as usual.
+//
+// Tool failures (an unknown task, a bad due date, ...) are reported as tool
+// results with `isError` set, so the model can see them and try again.
+// Protocol errors use the standard JSON-RPC error codes.
+
+use std::io::{self, BufRead, Write};
+
+use serde_json::{json, Map, Value};
+use todo_cli::config::Config;
+use todo_cli::hooks::Event;
+use todo_cli::{Error, Task, TaskStore};
+
+use crate::save;
+use crate::serve::{apply_fields, task_json};
+
+/// The protocol version we implement, offered when a client asks for another.
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+// Standard JSON-RPC error codes, also used by `--rpc`.
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+
+/// Answers messages from `input` on `output` until the input is closed.
+pub fn serve(config: &Config, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => handle(config, &message),
+            Err(e) => Some(error(
+                Value::Null,
+                PARSE_ERROR,
+                &format!("invalid JSON: {}", e),
+            )),
+        };
+        if let Some(response) = response {
+            writeln!(output, "{}", response)?;
+            output.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Handles one message, returning the response. Notifications get none.
+pub fn handle(config: &Config, message: &Value) -> Option<Value> {
+    let Some(method) = message.get("method").and_then(Value::as_str) else {
+        let id = message.get("id").cloned().unwrap_or(Value::Null);
+        return Some(error(id, INVALID_REQUEST, "not a JSON-RPC request"));
+    };
+    // Notifications (no id) never get a response, not even an error.
+    let id = message.get("id")?.clone();
+    let params = message.get("params").cloned().unwrap_or(json!({}));
+
+    let result = match method {
+        "initialize" => Ok(json!({
+            "protocolVersion": params
+                .get("protocolVersion")
+                .and_then(Value::as_str)
+                .unwrap_or(PROTOCOL_VERSION),
+            "capabilities": {"tools": {}},
+            "serverInfo": {"name": "todo_cli", "version": env!("CARGO_PKG_VERSION")},
+        })),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tools() })),
+        "tools/call" => call_tool(config, &params),
+        _ => Err((METHOD_NOT_FOUND, format!("unknown method '{}'", method))),
+    };
+    Some(match result {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err((code, msg)) => error(id, code, &msg),
+    })
+}
+
+/// A JSON-RPC error response.
+pub fn error(id: Value, code: i64, msg: &str) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": msg}})
+}
+
+/// The tools we offer, with JSON schemas for their arguments.
+fn tools() -> Value {
+    let list = json!({
+        "type": "string",
+        "description": "A named list to use instead of the default one",
+    });
+    let task_ref = json!({
+        "type": "string",
+        "description": "The task number, id:<id>, or any unique part of its text",
+    });
+    json!([
+        {
+            "name": "list_tasks",
+            "description": "List the tasks in the to-do list, with their numbers.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "list": list,
+                    "include_completed": {
+                        "type": "boolean",
+                        "description": "Include completed tasks (default: true)",
+                    },
+                },
+            },
+        },
+        {
+            "name": "add_task",
+            "description": "Add a task to the to-do list.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "task": {"type": "string", "description": "What needs doing"},
+                    "due": {
+                        "type": "string",
+                        "description": "Due date: YYYY-MM-DD, today, tomorrow, a weekday, or +3d/+2w; may have a local time, e.g. fri 17:00",
+                    },
+                    "priority": {"type": "string", "enum": ["low", "medium", "high"]},
+                    "tags": {"type": "array", "items": {"type": "string"}},
+                    "notes": {"type": "string"},
+                    "list": list,
+                },
+                "required": ["task"],
+            },
+        },
+        {
+            "name": "complete_task",
+            "description": "Mark a task as completed.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {"task": task_ref, "list": list},
+                "required": ["task"],
+            },
+        },
+        {
+            "name": "delete_task",
+            "description": "Remove a task from the to-do list.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {"task": task_ref, "list": list},
+                "required": ["task"],
+            },
+        },
+    ])
+}
+
+fn call_tool(config: &Config, params: &Value) -> Result<Value, (i64, String)> {
+    let name = params
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or((INVALID_PARAMS, "missing tool name".to_string()))?;
+    let args = match params.get("arguments") {
+        None | Some(Value::Null) => Map::new(),
+        Some(Value::Object(args)) => args.clone(),
+        Some(_) => return Err((INVALID_PARAMS, "arguments must be an object".to_string())),
+    };
+    let result = match name {
+        "list_tasks" => list_tasks(config, &args),
+        "add_task" => add_task(config, &args),
+        "complete_task" => complete_task(config, &args),
+        "delete_task" => delete_task(config, &args),
+        _ => return Err((INVALID_PARAMS, format!("unknown tool '{}'", name))),
+    };
+    let (text, is_error) = match result {
+        Ok(value) => (
+            serde_json::to_string_pretty(&value).expect("Failed to serialize data to JSON."),
+            false,
+        ),
+        Err(e) => (e.to_string(), true),
+    };
+    Ok(json!({
+        "content": [{"type": "text", "text": text}],
+        "isError": is_error,
+    }))
+}
+
+/// Opens the list named in the arguments, or the configured one. Changes are
+/// credited to `via` in the audit log.
+pub fn open(
+    config: &Config,
+    args: &mut Map<String, Value>,
+    via: &str,
+) -> todo_cli::Result<(Config, TaskStore)> {
+    let mut config = config.clone();
+    match args.remove("list") {
+        None | Some(Value::Null) => {}
+        Some(Value::String(name)) => config.apply("list", &name).map_err(Error::Invalid)?,
+        Some(_) => return Err(Error::Invalid("'list' must be a string".to_string())),
+    }
+    let mut store = config.open_store()?;
+    store.audit_as(via);
+    Ok((config, store))
+}
+
+/// The required `task` argument: a number or part of a task's text.
+pub fn task_arg(args: &Map<String, Value>) -> todo_cli::Result<&str> {
+    match args.get("task") {
+        Some(Value::String(task)) => Ok(task),
+        _ => Err(Error::MissingQuery),
+    }
+}
+
+fn list_tasks(config: &Config, args: &Map<String, Value>) -> todo_cli::Result<Value> {
+    let mut args = args.clone();
+    let include_completed = args
+        .remove("include_completed")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    let (_, store) = open(config, &mut args, "mcp")?;
+    let tasks: Vec<Value> = store
+        .tasks()
+        .iter()
+        .enumerate()
+        .filter(|(_, task)| include_completed || !task.completed)
+        .map(|(i, task)| task_json(i, task))
+        .collect();
+    Ok(Value::Array(tasks))
+}
+
+fn add_task(config: &Config, args: &Map<String, Value>) -> todo_cli::Result<Value> {
+    let mut args = args.clone();
+    let (config, mut store) = open(config, &mut args, "mcp")?;
+    let mut task = Task::default();
+    apply_fields(&mut task, &args)?;
+    let index = store.add(task)?;
+    save(&config, &store, &[(Event::Add, &store.tasks()[index])])?;
+    Ok(task_json(index, &store.tasks()[index]))
+}
+
+fn complete_task(config: &Config, args: &Map<String, Value>) -> todo_cli::Result<Value> {
+    let mut args = args.clone();
+    let (config, mut store) = open(config, &mut args, "mcp")?;
+    let index = store.resolve(task_arg(&args)?)?;
+    if store.complete(index)? {
+        save(&config, &store, &[(Event::Done, &store.tasks()[index])])?;
+    }
+    Ok(task_json(index, &store.tasks()[index]))
+}
+
+fn delete_task(config: &Config, args: &Map<String, Value>) -> todo_cli::Result<Value> {
+    let mut args = args.clone();
+    let (config, mut store) = open(config, &mut args, "mcp")?;
+    let index = store.resolve(task_arg(&args)?)?;
+    let removed = store.remove(index)?;
+    save(&config, &store, &[(Event::Delete, &removed)])?;
+    Ok(json!({ "deleted": removed }))
+}
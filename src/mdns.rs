@@ -0,0 +1,398 @@
+// Just enough multicast DNS (RFC 6762) and DNS service discovery (RFC 6763)
+// for instances of this program to find each other on the local network.
+//
+// Each instance running `sync` answers queries for `_todo-cli._tcp.local`
+// with its instance name, the port it syncs on and the list it syncs, in a
+// TXT record `list=NAME`. Browsing sends such a query from an ordinary port,
+// which asks every responder to answer it directly; their answers come from
+// the machines they run on, so no address records are needed.
+//
+// Answering needs UDP port 5353. If another responder (e.g. Avahi) holds it,
+// this instance can still find others, but they can't find it; give them its
+// address with `sync --peer` instead.
+
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+use crate::log;
+
+/// The service instances of this program are registered under.
+pub const SERVICE: &str = "_todo-cli._tcp.local";
+
+const GROUP: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+const PORT: u16 = 5353;
+
+const PTR: u16 = 12;
+const TXT: u16 = 16;
+const SRV: u16 = 33;
+const ANY: u16 = 255;
+const IN: u16 = 1;
+/// Set on the class of a record that replaces any cached before.
+const CACHE_FLUSH: u16 = 0x8000;
+/// How long answers may be cached, in seconds. Answers to one-shot queries
+/// are meant to be cached briefly.
+const TTL: u32 = 10;
+
+/// Another instance found on the network.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Peer {
+    pub name: String,
+    pub addr: SocketAddr,
+    /// The list it syncs; `None` for the main list.
+    pub list: Option<String>,
+}
+
+/// Answers queries for this instance.
+#[derive(Debug)]
+pub struct Responder {
+    socket: UdpSocket,
+    name: String,
+    port: u16,
+    list: Option<String>,
+}
+
+impl Responder {
+    /// Joins the mDNS group to answer for the instance `name`, syncing
+    /// `list` on `port`.
+    pub fn bind(name: &str, port: u16, list: Option<&str>) -> io::Result<Responder> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, PORT))?;
+        socket.join_multicast_v4(&GROUP, &Ipv4Addr::UNSPECIFIED)?;
+        Ok(Responder {
+            socket,
+            name: name.to_string(),
+            port,
+            list: list.map(String::from),
+        })
+    }
+
+    /// Answers queries until reading from the network fails.
+    pub fn run(&self) -> io::Error {
+        let mut buf = [0; 9000];
+        loop {
+            let (len, from) = match self.socket.recv_from(&mut buf) {
+                Ok(received) => received,
+                Err(e) => return e,
+            };
+            let Some(answer) = self.answer(&buf[..len]) else {
+                continue;
+            };
+            // Queries from port 5353 come from other responders, which
+            // listen for answers on the group; anyone else wants them back.
+            let to = if from.port() == PORT {
+                SocketAddr::from((GROUP, PORT))
+            } else {
+                from
+            };
+            log::debug("mdns", format_args!("answering a query from {}", from));
+            if let Err(e) = self.socket.send_to(&answer, to) {
+                log::info("mdns", format_args!("could not answer {}: {}", from, e));
+            }
+        }
+    }
+
+    /// The answer to `packet`, if it is a query for our service.
+    fn answer(&self, packet: &[u8]) -> Option<Vec<u8>> {
+        let mut reader = Reader::new(packet);
+        let id = reader.u16()?;
+        let flags = reader.u16()?;
+        let questions = reader.u16()?;
+        reader.skip(6)?;
+        if flags & 0x8000 != 0 {
+            return None;
+        }
+        for _ in 0..questions {
+            let name = reader.name()?;
+            let kind = reader.u16()?;
+            reader.u16()?;
+            if same_name(&name, &labels(SERVICE)) && (kind == PTR || kind == ANY) {
+                return Some(self.response(id));
+            }
+        }
+        None
+    }
+
+    fn response(&self, id: u16) -> Vec<u8> {
+        let service = labels(SERVICE);
+        let mut instance = vec![self.name.clone()];
+        instance.extend(service.iter().cloned());
+        let host = self.name.rsplit('@').next().unwrap_or(&self.name);
+        let target = vec![host.replace('.', "-"), "local".to_string()];
+
+        let mut packet = Vec::new();
+        // An authoritative answer, echoing the question as one-shot
+        // queriers expect.
+        put_u16(&mut packet, id);
+        put_u16(&mut packet, 0x8400);
+        put_u16(&mut packet, 1);
+        put_u16(&mut packet, 3);
+        put_u16(&mut packet, 0);
+        put_u16(&mut packet, 0);
+        put_name(&mut packet, &service);
+        put_u16(&mut packet, PTR);
+        put_u16(&mut packet, IN);
+
+        let mut data = Vec::new();
+        put_name(&mut data, &instance);
+        put_record(&mut packet, &service, PTR, IN, &data);
+
+        let mut data = Vec::new();
+        put_u16(&mut data, 0);
+        put_u16(&mut data, 0);
+        put_u16(&mut data, self.port);
+        put_name(&mut data, &target);
+        put_record(&mut packet, &instance, SRV, IN | CACHE_FLUSH, &data);
+
+        let mut data = Vec::new();
+        let entry = match &self.list {
+            Some(list) => format!("list={}", list),
+            None => "list".to_string(),
+        };
+        put_label(&mut data, &entry);
+        put_record(&mut packet, &instance, TXT, IN | CACHE_FLUSH, &data);
+        packet
+    }
+}
+
+/// Asks the network for instances and collects the answers that come within
+/// `wait`.
+pub fn browse(wait: Duration) -> io::Result<Vec<Peer>> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    let mut query = Vec::new();
+    put_u16(&mut query, 0);
+    put_u16(&mut query, 0);
+    put_u16(&mut query, 1);
+    put_u16(&mut query, 0);
+    put_u16(&mut query, 0);
+    put_u16(&mut query, 0);
+    put_name(&mut query, &labels(SERVICE));
+    put_u16(&mut query, PTR);
+    put_u16(&mut query, IN);
+    socket.send_to(&query, (GROUP, PORT))?;
+
+    let mut peers: Vec<Peer> = Vec::new();
+    let deadline = Instant::now() + wait;
+    let mut buf = [0; 9000];
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            break;
+        }
+        socket.set_read_timeout(Some(left))?;
+        let (len, from) = match socket.recv_from(&mut buf) {
+            Ok(received) => received,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                break
+            }
+            Err(e) => return Err(e),
+        };
+        for peer in peers_in(&buf[..len], from) {
+            if !peers.contains(&peer) {
+                log::debug("mdns", format_args!("found {} at {}", peer.name, peer.addr));
+                peers.push(peer);
+            }
+        }
+    }
+    Ok(peers)
+}
+
+/// The instances an answer from `from` tells of.
+fn peers_in(packet: &[u8], from: SocketAddr) -> Vec<Peer> {
+    let mut peers = Vec::new();
+    let Some(records) = records(packet) else {
+        return peers;
+    };
+    let service = labels(SERVICE);
+    for record in &records {
+        if record.kind != PTR || !same_name(&record.name, &service) {
+            continue;
+        }
+        let Some(instance) = Reader::at(packet, record.data).name() else {
+            continue;
+        };
+        let Some(name) = instance.first() else {
+            continue;
+        };
+        let of_instance = |kind| {
+            records
+                .iter()
+                .find(|r| r.kind == kind && same_name(&r.name, &instance))
+        };
+        let Some(port) = of_instance(SRV).and_then(|srv| {
+            let mut reader = Reader::at(packet, srv.data);
+            reader.skip(4)?;
+            reader.u16()
+        }) else {
+            continue;
+        };
+        let list = of_instance(TXT)
+            .and_then(|txt| txt_value(&packet[txt.data..txt.data + txt.len], "list"))
+            .filter(|list| !list.is_empty());
+        peers.push(Peer {
+            name: name.clone(),
+            addr: SocketAddr::new(from.ip(), port),
+            list,
+        });
+    }
+    peers
+}
+
+/// A resource record, with where its data starts in the packet.
+struct Record {
+    name: Vec<String>,
+    kind: u16,
+    data: usize,
+    len: usize,
+}
+
+/// The answer, authority and additional records of a response.
+fn records(packet: &[u8]) -> Option<Vec<Record>> {
+    let mut reader = Reader::new(packet);
+    reader.skip(2)?;
+    let flags = reader.u16()?;
+    if flags & 0x8000 == 0 {
+        return None;
+    }
+    let questions = reader.u16()?;
+    let count = reader.u16()? as usize + reader.u16()? as usize + reader.u16()? as usize;
+    for _ in 0..questions {
+        reader.name()?;
+        reader.skip(4)?;
+    }
+    let mut records = Vec::new();
+    for _ in 0..count {
+        let name = reader.name()?;
+        let kind = reader.u16()?;
+        reader.skip(6)?;
+        let len = reader.u16()? as usize;
+        let data = reader.pos;
+        reader.skip(len)?;
+        records.push(Record {
+            name,
+            kind,
+            data,
+            len,
+        });
+    }
+    Some(records)
+}
+
+/// The value of `key` in TXT record data made of `key=value` strings.
+fn txt_value(data: &[u8], key: &str) -> Option<String> {
+    let mut rest = data;
+    while let Some((&len, tail)) = rest.split_first() {
+        let entry = String::from_utf8_lossy(tail.get(..len as usize)?);
+        match entry.split_once('=') {
+            Some((k, value)) if k.eq_ignore_ascii_case(key) => return Some(value.to_string()),
+            None if entry.eq_ignore_ascii_case(key) => return Some(String::new()),
+            _ => {}
+        }
+        rest = &tail[len as usize..];
+    }
+    None
+}
+
+/// Reads big-endian numbers and names from a packet.
+struct Reader<'a> {
+    packet: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(packet: &'a [u8]) -> Reader<'a> {
+        Reader::at(packet, 0)
+    }
+
+    fn at(packet: &'a [u8], pos: usize) -> Reader<'a> {
+        Reader { packet, pos }
+    }
+
+    fn skip(&mut self, len: usize) -> Option<()> {
+        if self.pos + len > self.packet.len() {
+            return None;
+        }
+        self.pos += len;
+        Some(())
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        let bytes = self.packet.get(self.pos..self.pos + 2)?;
+        self.pos += 2;
+        Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// A name's labels, following compression pointers.
+    fn name(&mut self) -> Option<Vec<String>> {
+        let mut labels = Vec::new();
+        let mut pos = self.pos;
+        let mut jumps = 0;
+        loop {
+            let len = *self.packet.get(pos)? as usize;
+            if len & 0xc0 == 0xc0 {
+                let low = *self.packet.get(pos + 1)? as usize;
+                if jumps == 0 {
+                    self.pos = pos + 2;
+                }
+                jumps += 1;
+                // A loop of pointers would never end.
+                if jumps > 32 {
+                    return None;
+                }
+                pos = (len & 0x3f) << 8 | low;
+                continue;
+            }
+            if len == 0 {
+                if jumps == 0 {
+                    self.pos = pos + 1;
+                }
+                return Some(labels);
+            }
+            let label = self.packet.get(pos + 1..pos + 1 + len)?;
+            labels.push(String::from_utf8_lossy(label).into_owned());
+            pos += 1 + len;
+        }
+    }
+}
+
+fn labels(name: &str) -> Vec<String> {
+    name.split('.').map(String::from).collect()
+}
+
+fn same_name(a: &[String], b: &[String]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.eq_ignore_ascii_case(b))
+}
+
+fn put_u16(packet: &mut Vec<u8>, value: u16) {
+    packet.extend_from_slice(&value.to_be_bytes());
+}
+
+/// Writes a label, cut short to the 63 bytes a label can hold.
+fn put_label(packet: &mut Vec<u8>, label: &str) {
+    let mut end = label.len().min(63);
+    while !label.is_char_boundary(end) {
+        end -= 1;
+    }
+    packet.push(end as u8);
+    packet.extend_from_slice(&label.as_bytes()[..end]);
+}
+
+fn put_name(packet: &mut Vec<u8>, labels: &[String]) {
+    for label in labels {
+        put_label(packet, label);
+    }
+    packet.push(0);
+}
+
+fn put_record(packet: &mut Vec<u8>, name: &[String], kind: u16, class: u16, data: &[u8]) {
+    put_name(packet, name);
+    put_u16(packet, kind);
+    put_u16(packet, class);
+    packet.extend_from_slice(&TTL.to_be_bytes());
+    put_u16(packet, data.len() as u16);
+    packet.extend_from_slice(data);
+}
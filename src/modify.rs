@@ -0,0 +1,124 @@
+// Edits applied to many tasks at once, by `modify`.
+//
+//   --replace /old/new/   replace every `old` in the text with `new`; any
+//                         character may stand in for `/`, e.g. `|a/b|c/d|`
+//   --set FIELD:VALUE     set priority (or prio), due or project; an empty
+//                         value or `none` clears the field
+//   --add-tag TAG         add a tag
+//   --remove-tag TAG      remove a tag
+//
+// Every value is checked when the edits are read, before any task is touched,
+// so that a typo in the last option doesn't leave the list half changed.
+
+use crate::date::{Date, DateTime};
+use crate::error::{Error, Result};
+use crate::i18n::tr;
+use crate::task::{Priority, Task};
+use crate::validate;
+
+/// The changes to make to each task; the default changes nothing.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Edits {
+    /// Text to find and what to replace it with.
+    pub replace: Option<(String, String)>,
+    /// The new priority, `Some(None)` to clear it.
+    pub priority: Option<Option<Priority>>,
+    /// The new due date, with its time if it has one; `Some(None)` to clear
+    /// it.
+    pub due: Option<Option<(Date, Option<DateTime>)>>,
+    pub project: Option<Option<String>>,
+    pub add_tags: Vec<String>,
+    pub remove_tags: Vec<String>,
+}
+
+impl Edits {
+    /// Whether applying the edits would change nothing.
+    pub fn is_empty(&self) -> bool {
+        *self == Edits::default()
+    }
+
+    /// Reads a `/old/new/` substitution.
+    pub fn replace(&mut self, spec: &str) -> Result<()> {
+        let invalid = || Error::Invalid(tr("modify-replace-invalid", &[("input", &spec)]));
+        let mut chars = spec.chars();
+        let delimiter = chars.next().ok_or_else(invalid)?;
+        let rest = chars.as_str();
+        let rest = rest.strip_suffix(delimiter).unwrap_or(rest);
+        let (old, new) = rest.split_once(delimiter).ok_or_else(invalid)?;
+        if old.is_empty() || new.contains(delimiter) || delimiter.is_alphanumeric() {
+            return Err(invalid());
+        }
+        self.replace = Some((old.to_string(), new.to_string()));
+        Ok(())
+    }
+
+    /// Reads a `field:value` assignment, with dates relative to `today`.
+    pub fn set(&mut self, spec: &str, today: Date) -> Result<()> {
+        let Some((field, value)) = spec.split_once(':') else {
+            return Err(Error::Invalid(tr(
+                "modify-set-invalid",
+                &[("input", &spec)],
+            )));
+        };
+        let value = value.trim();
+        let cleared = value.is_empty() || value.eq_ignore_ascii_case("none");
+        match field.trim().to_lowercase().as_str() {
+            "priority" | "prio" => {
+                self.priority = Some((!cleared).then(|| validate::priority(value)).transpose()?);
+            }
+            "due" => {
+                self.due = Some(
+                    (!cleared)
+                        .then(|| validate::due_time(value, today))
+                        .transpose()?,
+                )
+            }
+            "project" => {
+                self.project = Some((!cleared).then(|| validate::project(value)).transpose()?);
+            }
+            _ => {
+                return Err(Error::Invalid(tr(
+                    "modify-unknown-field",
+                    &[("field", &field.trim())],
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds a tag to every task.
+    pub fn add_tag(&mut self, tag: &str) -> Result<()> {
+        self.add_tags.push(validate::tag(tag)?);
+        Ok(())
+    }
+
+    /// Removes a tag from every task that has it.
+    pub fn remove_tag(&mut self, tag: &str) -> Result<()> {
+        self.remove_tags.push(validate::tag(tag)?);
+        Ok(())
+    }
+
+    /// `task` with the edits made. The text may need validating again.
+    pub fn apply(&self, task: &Task) -> Task {
+        let mut task = task.clone();
+        if let Some((old, new)) = &self.replace {
+            task.text = task.text.replace(old.as_str(), new);
+        }
+        if let Some(priority) = self.priority {
+            task.priority = priority;
+        }
+        if let Some(due) = self.due {
+            task.set_due(due);
+        }
+        if let Some(project) = &self.project {
+            task.project = project.clone();
+        }
+        task.tags.retain(|tag| !self.remove_tags.contains(tag));
+        for tag in &self.add_tags {
+            if !task.tags.contains(tag) {
+                task.tags.push(tag.clone());
+            }
+        }
+        task
+    }
+}
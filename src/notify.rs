@@ -0,0 +1,196 @@
+// Desktop notifications for tasks that are due.
+//
+// Notifications are shown with the platform's own tool: `notify-send` on
+// Linux and the BSDs, `osascript` on macOS. Both ship with the desktop, so no
+// extra libraries are needed.
+
+use std::io;
+use std::process::Command;
+
+use crate::date::{Date, DateTime};
+use crate::i18n::tr;
+use crate::task::Task;
+
+/// A notification ready to be shown.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+    /// Whether anything is overdue, which is shown with higher urgency.
+    pub urgent: bool,
+}
+
+/// Returns the pending tasks due on or before `today`, with their indices.
+/// Tasks parked as someday/maybe are left out.
+pub fn due_tasks(tasks: &[Task], today: Date) -> Vec<(usize, &Task)> {
+    tasks
+        .iter()
+        .enumerate()
+        .filter(|(_, task)| {
+            !task.completed && task.someday.is_none() && task.due.is_some_and(|due| due <= today)
+        })
+        .collect()
+}
+
+/// Builds one notification summarizing the due tasks, or `None` if there are none.
+pub fn summarize(due: &[(usize, &Task)], today: Date) -> Option<Notification> {
+    if due.is_empty() {
+        return None;
+    }
+    let overdue = due
+        .iter()
+        .filter(|(_, task)| task.is_overdue(today))
+        .count();
+    let count = due.len();
+    let title = match (count, overdue) {
+        (1, 1) => tr("notify-overdue-one", &[]),
+        (_, 0) | (1, _) => tr("notify-due-today", &[("count", &count)]),
+        (_, _) => tr(
+            "notify-due-overdue",
+            &[("count", &count), ("overdue", &overdue)],
+        ),
+    };
+    let lines: Vec<String> = due
+        .iter()
+        .map(|(index, task)| match task.due {
+            Some(d) if task.is_overdue(today) => tr(
+                "notify-overdue-since",
+                &[("number", &(index + 1)), ("task", &task.text), ("date", &d)],
+            ),
+            _ => format!("{}. {}", index + 1, task.text),
+        })
+        .collect();
+    Some(Notification {
+        title,
+        body: lines.join("\n"),
+        urgent: overdue > 0,
+    })
+}
+
+/// Returns the pending tasks with a reminder later than `after` and no later
+/// than `until`, with their indices.
+pub fn reminded_tasks(tasks: &[Task], after: DateTime, until: DateTime) -> Vec<(usize, &Task)> {
+    tasks
+        .iter()
+        .enumerate()
+        .filter(|(_, task)| {
+            !task.completed && task.reminders.iter().any(|&at| after < at && at <= until)
+        })
+        .collect()
+}
+
+/// Builds one notification for the tasks whose reminders went off, or `None`
+/// if there are none.
+pub fn remind(reminded: &[(usize, &Task)]) -> Option<Notification> {
+    if reminded.is_empty() {
+        return None;
+    }
+    let lines: Vec<String> = reminded
+        .iter()
+        .map(|(index, task)| format!("{}. {}", index + 1, task.text))
+        .collect();
+    Some(Notification {
+        title: tr("reminder-title", &[("count", &reminded.len())]),
+        body: lines.join("\n"),
+        urgent: false,
+    })
+}
+
+/// Returns the pending tasks that have been waiting on someone for at least
+/// `days` days, with their indices.
+pub fn waiting_tasks(tasks: &[Task], today: Date, days: u32) -> Vec<(usize, &Task)> {
+    tasks
+        .iter()
+        .enumerate()
+        .filter(|(_, task)| {
+            !task.completed
+                && task
+                    .waiting
+                    .as_ref()
+                    .is_some_and(|w| w.days(today) >= i64::from(days))
+        })
+        .collect()
+}
+
+/// Builds one notification nudging the user to chase up tasks that have
+/// been waiting too long, or `None` if there are none.
+pub fn nudge(waiting: &[(usize, &Task)], today: Date) -> Option<Notification> {
+    if waiting.is_empty() {
+        return None;
+    }
+    let lines: Vec<String> = waiting
+        .iter()
+        .filter_map(|(index, task)| {
+            let waiting = task.waiting.as_ref()?;
+            Some(tr(
+                "nudge-line",
+                &[
+                    ("number", &(index + 1)),
+                    ("task", &task.text),
+                    ("person", &waiting.person),
+                    ("days", &waiting.days(today)),
+                ],
+            ))
+        })
+        .collect();
+    Some(Notification {
+        title: tr("nudge-title", &[("count", &waiting.len())]),
+        body: lines.join("\n"),
+        urgent: false,
+    })
+}
+
+/// Shows a notification on the desktop.
+pub fn send(notification: &Notification) -> io::Result<()> {
+    let mut command = notifier(notification);
+    let status = command.status().map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!(
+                "could not run '{}': {}",
+                command.get_program().to_string_lossy(),
+                e
+            ),
+        )
+    })?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "'{}' exited with {}",
+            command.get_program().to_string_lossy(),
+            status
+        )))
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn notifier(notification: &Notification) -> Command {
+    // AppleScript strings use backslash escapes, like JSON.
+    let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+    let mut command = Command::new("osascript");
+    command.arg("-e").arg(format!(
+        "display notification {} with title {}",
+        quote(&notification.body),
+        quote(&notification.title)
+    ));
+    command
+}
+
+#[cfg(not(target_os = "macos"))]
+fn notifier(notification: &Notification) -> Command {
+    let mut command = Command::new("notify-send");
+    command
+        .arg("--app-name=todo_cli")
+        .arg(format!(
+            "--urgency={}",
+            if notification.urgent {
+                "critical"
+            } else {
+                "normal"
+            }
+        ))
+        .arg(&notification.title)
+        .arg(&notification.body);
+    command
+}
@@ -0,0 +1,50 @@
+// Tasks arranged by hand, for `--sort manual`.
+//
+// A task's number is its place in the file, which `move`, `up` and `down`
+// leave alone: they give tasks a rank instead, and `--sort manual` lists
+// ranked tasks by rank, then the rest in list order, so a task added since
+// the last arranging turns up at the end. Ranks count from 1 with no gaps:
+// the first move ranks every task, and after that a move only renumbers the
+// tasks it passes. Ranking isn't editing, so it doesn't make a task any
+// less neglected for its urgency.
+
+use crate::task::Task;
+
+/// The indexes of `tasks` in their manual order.
+pub fn arranged(tasks: &[Task]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..tasks.len()).collect();
+    order.sort_by_key(|&index| (tasks[index].rank.is_none(), tasks[index].rank));
+    order
+}
+
+/// Where the task at `index` goes when moved `steps` places up (negative)
+/// or down among the tasks listed with it, as a place in `order` as
+/// [`place`] takes it. Tasks parked as someday/maybe are listed apart, so
+/// the others step over them, and the other way round.
+pub fn step(tasks: &[Task], order: &[usize], index: usize, steps: i64) -> usize {
+    let parked = tasks[index].someday.is_some();
+    let peers: Vec<usize> = (0..order.len())
+        .filter(|&at| tasks[order[at]].someday.is_some() == parked)
+        .collect();
+    let from = peers
+        .iter()
+        .position(|&at| order[at] == index)
+        .expect("the task is in the order");
+    let to = (from as i64 + steps).clamp(0, peers.len() as i64 - 1);
+    peers[to as usize]
+}
+
+/// Moves the task at `index` to place `to` of the manual order, counting
+/// from 0 among the other tasks. Returns the new ranks of the tasks whose
+/// rank changes, by index.
+pub fn place(tasks: &[Task], index: usize, to: usize) -> Vec<(usize, u64)> {
+    let mut order = arranged(tasks);
+    order.retain(|&i| i != index);
+    order.insert(to.min(order.len()), index);
+    order
+        .into_iter()
+        .enumerate()
+        .map(|(at, i)| (i, at as u64 + 1))
+        .filter(|&(i, rank)| tasks[i].rank != Some(rank))
+        .collect()
+}
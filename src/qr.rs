@@ -0,0 +1,481 @@
+// QR codes, drawn in the terminal for `qr` to hand a task to a phone.
+//
+// Only what that needs is here: the data is encoded as bytes, with error
+// correction level M (about 15% of the code can be lost and still read),
+// in the smallest of the 40 versions it fits. The steps are those of the
+// standard (ISO/IEC 18004):
+//
+//   1. the bits: mode, length, data, then padding up to the version's
+//      number of data codewords
+//   2. the codewords split into blocks, each given Reed-Solomon error
+//      correction codewords, and interleaved
+//   3. the function patterns drawn: finders in three corners, timing lines,
+//      alignment patterns, and the format and version information
+//   4. the codewords laid out in the zigzag the standard fixes, then the one
+//      of the eight masks that scores least against its penalty rules
+//
+// A code is printed with two rows of modules to each line of text, using
+// the half block characters, and the quiet zone around it the standard asks
+// for, so it scans straight off the screen.
+
+/// Error correction codewords per block, by version, for level M.
+const ECC_PER_BLOCK: [usize; 41] = [
+    0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28, 28,
+    28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28,
+];
+
+/// Blocks the codewords are split into, by version, for level M.
+const BLOCKS: [usize; 41] = [
+    0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21, 23,
+    25, 26, 28, 29, 31, 33, 35, 37, 38, 40, 43, 45, 47, 49,
+];
+
+/// The two bits that stand for level M in the format information.
+const LEVEL_M: u32 = 0;
+
+/// How many modules of light border a code needs around it.
+const QUIET_ZONE: usize = 4;
+
+/// A QR code: a square of dark and light modules.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QrCode {
+    size: usize,
+    modules: Vec<bool>,
+    /// Which modules belong to the function patterns, and so are neither
+    /// data nor masked.
+    function: Vec<bool>,
+}
+
+impl QrCode {
+    /// The code for `data`, or `None` if it is too long for any version.
+    pub fn encode(data: &[u8]) -> Option<QrCode> {
+        let version = (1..=40).find(|&version| {
+            let count_bits = if version < 10 { 8 } else { 16 };
+            4 + count_bits + data.len() * 8 <= data_codewords(version) * 8
+        })?;
+        let mut code = QrCode {
+            size: version * 4 + 17,
+            modules: vec![false; (version * 4 + 17).pow(2)],
+            function: vec![false; (version * 4 + 17).pow(2)],
+        };
+        code.draw_function_patterns(version);
+        let codewords = with_error_correction(version, &data_codewords_for(version, data));
+        code.draw_codewords(&codewords);
+
+        let mask = (0..8)
+            .min_by_key(|&mask| {
+                code.apply_mask(mask);
+                code.draw_format(mask);
+                let penalty = code.penalty();
+                code.apply_mask(mask);
+                penalty
+            })
+            .expect("there are eight masks");
+        code.apply_mask(mask);
+        code.draw_format(mask);
+        Some(code)
+    }
+
+    /// How many modules wide and high the code is.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Whether the module in column `x` and row `y` is dark.
+    pub fn dark(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+
+    /// The code as lines of text, quiet zone and all, two rows of modules
+    /// to a line. Dark modules are drawn as filled blocks, which suits dark
+    /// text on a light background; `invert` is for the other way round.
+    pub fn render(&self, invert: bool) -> String {
+        let width = self.size + 2 * QUIET_ZONE;
+        // Whether the module at (x, y), counting the quiet zone, is inked.
+        let inked = |x: usize, y: usize| {
+            let dark = x >= QUIET_ZONE
+                && y >= QUIET_ZONE
+                && x < self.size + QUIET_ZONE
+                && y < self.size + QUIET_ZONE
+                && self.dark(x - QUIET_ZONE, y - QUIET_ZONE);
+            dark != invert
+        };
+        let mut out = String::new();
+        for y in (0..width).step_by(2) {
+            for x in 0..width {
+                let lower = y + 1 < width && inked(x, y + 1);
+                out.push(match (inked(x, y), lower) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                });
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    fn set(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.set(x, y, dark);
+        self.function[y * self.size + x] = true;
+    }
+
+    fn draw_function_patterns(&mut self, version: usize) {
+        let size = self.size;
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+        self.draw_finder(3, 3);
+        self.draw_finder(size - 4, 3);
+        self.draw_finder(3, size - 4);
+
+        let positions = alignment_positions(version);
+        let last = positions.len().saturating_sub(1);
+        for (i, &x) in positions.iter().enumerate() {
+            for (j, &y) in positions.iter().enumerate() {
+                // Those in the finders' corners are left out.
+                if (i, j) != (0, 0) && (i, j) != (0, last) && (i, j) != (last, 0) {
+                    self.draw_alignment(x, y);
+                }
+            }
+        }
+
+        // Reserved now, drawn once the mask is chosen.
+        self.draw_format(0);
+        if version >= 7 {
+            let mut rem = version as u32;
+            for _ in 0..12 {
+                rem = (rem << 1) ^ ((rem >> 11) * 0x1F25);
+            }
+            let bits = (version as u32) << 12 | rem;
+            for i in 0..18 {
+                let dark = bits >> i & 1 == 1;
+                let (a, b) = (size - 11 + i % 3, i / 3);
+                self.set_function(a, b, dark);
+                self.set_function(b, a, dark);
+            }
+        }
+    }
+
+    /// A finder pattern centred on (x, y), with its light separator.
+    fn draw_finder(&mut self, x: usize, y: usize) {
+        for dy in -4i64..=4 {
+            for dx in -4i64..=4 {
+                let (xx, yy) = (x as i64 + dx, y as i64 + dy);
+                if (0..self.size as i64).contains(&xx) && (0..self.size as i64).contains(&yy) {
+                    let distance = dx.abs().max(dy.abs());
+                    self.set_function(xx as usize, yy as usize, distance != 2 && distance != 4);
+                }
+            }
+        }
+    }
+
+    fn draw_alignment(&mut self, x: usize, y: usize) {
+        for dy in -2i64..=2 {
+            for dx in -2i64..=2 {
+                let dark = dx.abs().max(dy.abs()) != 1;
+                self.set_function((x as i64 + dx) as usize, (y as i64 + dy) as usize, dark);
+            }
+        }
+    }
+
+    /// The format information, both copies, for level M and `mask`.
+    fn draw_format(&mut self, mask: u32) {
+        let data = LEVEL_M << 3 | mask;
+        let mut rem = data;
+        for _ in 0..10 {
+            rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+        }
+        let bits = (data << 10 | rem) ^ 0x5412;
+        let bit = |i: usize| bits >> i & 1 == 1;
+        let size = self.size;
+
+        for i in 0..6 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        // The one module that is always dark.
+        self.set_function(8, size - 8, true);
+    }
+
+    /// Lays the codewords out two columns at a time, up and down from the
+    /// bottom right, around the function patterns.
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size;
+        let mut i = 0;
+        let mut right = size - 1;
+        loop {
+            // The vertical timing line is skipped over.
+            if right == 6 {
+                right = 5;
+            }
+            let upward = (right + 1) & 2 == 0;
+            for vert in 0..size {
+                for x in [right, right - 1] {
+                    let y = if upward { size - 1 - vert } else { vert };
+                    if !self.function[y * size + x] && i < codewords.len() * 8 {
+                        self.set(x, y, codewords[i / 8] >> (7 - i % 8) & 1 == 1);
+                        i += 1;
+                    }
+                }
+            }
+            if right < 2 {
+                break;
+            }
+            right -= 2;
+        }
+    }
+
+    /// Flips the data modules `mask` picks; applying it twice undoes it.
+    fn apply_mask(&mut self, mask: u32) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let flip = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                let at = y * self.size + x;
+                if flip && !self.function[at] {
+                    self.modules[at] = !self.modules[at];
+                }
+            }
+        }
+    }
+
+    /// How badly the code scores against the standard's rules for a good
+    /// mask: long runs, 2x2 blocks, patterns like a finder's, and an uneven
+    /// share of dark modules all count against it.
+    fn penalty(&self) -> usize {
+        let size = self.size;
+        let mut penalty = 0;
+        let lines = (0..size).flat_map(|i| {
+            let row: Vec<bool> = (0..size).map(|x| self.dark(x, i)).collect();
+            let column: Vec<bool> = (0..size).map(|y| self.dark(i, y)).collect();
+            [row, column]
+        });
+        for line in lines {
+            let mut run = 1;
+            for i in 1..=size {
+                if i < size && line[i] == line[i - 1] {
+                    run += 1;
+                    continue;
+                }
+                if run >= 5 {
+                    penalty += run - 2;
+                }
+                run = 1;
+            }
+            // Dark-light-dark-dark-dark-light-dark, with four light on a side.
+            let finder = [true, false, true, true, true, false, true];
+            for start in 0..size.saturating_sub(6) {
+                if line[start..start + 7] != finder {
+                    continue;
+                }
+                let light = |range: std::ops::Range<usize>| range.into_iter().all(|i| !line[i]);
+                let before = start >= 4 && light(start - 4..start);
+                let after = start + 11 <= size && light(start + 7..start + 11);
+                if before || after {
+                    penalty += 40;
+                }
+            }
+        }
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let dark = self.dark(x, y);
+                if self.dark(x + 1, y) == dark
+                    && self.dark(x, y + 1) == dark
+                    && self.dark(x + 1, y + 1) == dark
+                {
+                    penalty += 3;
+                }
+            }
+        }
+        let dark = self.modules.iter().filter(|&&dark| dark).count();
+        let total = self.modules.len();
+        // Every 5% away from half dark, past the first.
+        let off = (dark * 20).abs_diff(total * 10);
+        penalty + off.div_ceil(total).saturating_sub(1) * 10
+    }
+}
+
+/// The data codewords of `version` at level M.
+fn data_codewords(version: usize) -> usize {
+    raw_modules(version) / 8 - ECC_PER_BLOCK[version] * BLOCKS[version]
+}
+
+/// How many modules of `version` are left for codewords once the function
+/// patterns are drawn.
+fn raw_modules(version: usize) -> usize {
+    let mut modules = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let alignments = version / 7 + 2;
+        modules -= (25 * alignments - 10) * alignments - 55;
+        if version >= 7 {
+            modules -= 36;
+        }
+    }
+    modules
+}
+
+/// Where the alignment patterns are centred, the same across as down.
+fn alignment_positions(version: usize) -> Vec<usize> {
+    if version == 1 {
+        return Vec::new();
+    }
+    let count = version / 7 + 2;
+    let step = match version {
+        32 => 26,
+        _ => (version * 4 + count * 2 + 1) / (count * 2 - 2) * 2,
+    };
+    let mut positions = vec![6];
+    let mut position = version * 4 + 10;
+    for _ in 0..count - 1 {
+        positions.insert(1, position);
+        position -= step;
+    }
+    positions
+}
+
+/// `data` in byte mode, padded to the data codewords of `version`.
+fn data_codewords_for(version: usize, data: &[u8]) -> Vec<u8> {
+    let capacity = data_codewords(version) * 8;
+    let mut bits = Bits::default();
+    bits.push(0b0100, 4);
+    bits.push(data.len() as u32, if version < 10 { 8 } else { 16 });
+    for &byte in data {
+        bits.push(byte.into(), 8);
+    }
+    bits.push(0, (capacity - bits.len).min(4));
+    bits.push(0, (8 - bits.len % 8) % 8);
+    let mut codewords = bits.bytes;
+    for pad in [0xEC, 0x11].into_iter().cycle() {
+        if codewords.len() * 8 >= capacity {
+            break;
+        }
+        codewords.push(pad);
+    }
+    codewords
+}
+
+#[derive(Default)]
+struct Bits {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl Bits {
+    /// Appends the low `count` bits of `value`, high bit first.
+    fn push(&mut self, value: u32, count: usize) {
+        for i in (0..count).rev() {
+            if self.len.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if value >> i & 1 == 1 {
+                *self.bytes.last_mut().expect("a byte was pushed") |= 0x80 >> (self.len % 8);
+            }
+            self.len += 1;
+        }
+    }
+}
+
+/// The data codewords split into blocks, each followed by its error
+/// correction, interleaved as the standard lays them out.
+fn with_error_correction(version: usize, data: &[u8]) -> Vec<u8> {
+    let blocks = BLOCKS[version];
+    let ecc_len = ECC_PER_BLOCK[version];
+    let raw = raw_modules(version) / 8;
+    // The first blocks are a codeword shorter than the rest.
+    let short_blocks = blocks - raw % blocks;
+    let short_len = raw / blocks;
+    let divisor = divisor(ecc_len);
+
+    let mut split = Vec::with_capacity(blocks);
+    let mut at = 0;
+    for i in 0..blocks {
+        let len = short_len - ecc_len + usize::from(i >= short_blocks);
+        let block = &data[at..at + len];
+        at += len;
+        split.push((block.to_vec(), remainder(block, &divisor)));
+    }
+
+    let mut out = Vec::with_capacity(raw);
+    for i in 0..=short_len - ecc_len {
+        for (block, _) in &split {
+            if let Some(&codeword) = block.get(i) {
+                out.push(codeword);
+            }
+        }
+    }
+    for i in 0..ecc_len {
+        for (_, ecc) in &split {
+            out.push(ecc[i]);
+        }
+    }
+    out
+}
+
+/// The Reed-Solomon generator polynomial of `degree`, highest term left
+/// out, coefficients from the highest power down.
+fn divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0; degree];
+    result[degree - 1] = 1;
+    let mut root = 1;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = multiply(result[j], root);
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = multiply(root, 2);
+    }
+    result
+}
+
+/// The error correction codewords of `data`: the remainder of dividing it
+/// by the generator.
+fn remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0; divisor.len()];
+    for &byte in data {
+        let factor = byte ^ result.remove(0);
+        result.push(0);
+        for (r, &d) in result.iter_mut().zip(divisor) {
+            *r ^= multiply(d, factor);
+        }
+    }
+    result
+}
+
+/// Multiplies in GF(2^8), modulo x^8 + x^4 + x^3 + x^2 + 1.
+fn multiply(x: u8, y: u8) -> u8 {
+    let mut z: u32 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x11D);
+        z ^= (u32::from(y) >> i & 1) * u32::from(x);
+    }
+    z as u8
+}
@@ -0,0 +1,424 @@
+// Filter expressions, and the named views saved from them.
+//
+// An expression is a set of conditions, all of which a task must meet unless
+// joined by `or`:
+//
+//   word                 the text contains it ("two words" for both together)
+//   prio:high            priority low, medium or high, or none
+//   due:fri              due that day; due:none and due:any for whether due
+//   due.before:tomorrow  due before that day (due.after for after it)
+//   tag:work  #work      tagged work; #OVERDUE and the other virtual tags
+//                        (see `filter`) too
+//   project:home  +home  in the home project
+//   context:phone  @phone
+//   status:doing         in that workflow status
+//   owner:ana            added by ana
+//   is:overdue           also pending, done, waiting, someday and habit
+//
+// `not` or `-` before a condition turns it around, and parentheses group:
+// `prio:high or (due.before:tomorrow -is:done)`. `and` may be written
+// between conditions but is what they mean anyway. Dates are read as for
+// `add --due`, relative to the day the expression is used.
+//
+// A view is an expression saved under a name, with `sort:` and `format:` to
+// say how its tasks are listed, e.g. `#work is:pending sort:due
+// format:compact`. Views live in the config's `[views]` table.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::config::SortOrder;
+use crate::date::Date;
+use crate::error::{Error, Result};
+use crate::filter::VirtualTag;
+use crate::i18n::tr;
+use crate::search;
+use crate::task::{normalize_context, normalize_project, normalize_tag, Priority, Task};
+use crate::validate;
+
+/// A parsed filter expression.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Query {
+    /// Text the task's text must contain.
+    Word(String),
+    Priority(Option<Priority>),
+    Due(Due),
+    Tag(String),
+    /// A tag in capitals, worked out from the task's state.
+    Virtual(VirtualTag),
+    Project(String),
+    Context(String),
+    Status(String),
+    Owner(String),
+    Is(State),
+    Not(Box<Query>),
+    /// Every one of them; none at all lets every task through.
+    All(Vec<Query>),
+    Any(Vec<Query>),
+}
+
+/// A condition on the due date.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Due {
+    On(Date),
+    Before(Date),
+    After(Date),
+    /// Has no due date.
+    None,
+    /// Has a due date.
+    Any,
+}
+
+/// What `is:` asks about a task.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum State {
+    Pending,
+    Done,
+    Overdue,
+    Waiting,
+    Someday,
+    Habit,
+}
+
+impl FromStr for State {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "pending" => Ok(State::Pending),
+            "done" | "completed" => Ok(State::Done),
+            "overdue" => Ok(State::Overdue),
+            "waiting" => Ok(State::Waiting),
+            "someday" => Ok(State::Someday),
+            "habit" => Ok(State::Habit),
+            _ => Err(tr("query-is-invalid", &[("input", &s.trim())])),
+        }
+    }
+}
+
+impl Query {
+    /// Reads `input`, with dates relative to `today`.
+    pub fn parse(input: &str, today: Date) -> Result<Query> {
+        let tokens = tokenize(input)?;
+        Query::from_tokens(&tokens, today)
+    }
+
+    fn from_tokens(tokens: &[String], today: Date) -> Result<Query> {
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            today,
+        };
+        let query = parser.any()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(query),
+            Some(token) => Err(Error::Invalid(tr("query-unexpected", &[("token", token)]))),
+        }
+    }
+
+    /// Whether `task` meets the conditions, on `today`, with `pending` the
+    /// ids of the pending tasks.
+    pub fn matches(&self, task: &Task, today: Date, pending: &HashSet<u64>) -> bool {
+        let same = |a: &str, b: &str| search::fold(a) == search::fold(b);
+        match self {
+            Query::Word(word) => search::contains(&task.text, word),
+            Query::Priority(priority) => task.priority == *priority,
+            Query::Due(due) => match (due, task.due) {
+                (Due::None, due) => due.is_none(),
+                (Due::Any, due) => due.is_some(),
+                (_, None) => false,
+                (Due::On(date), Some(due)) => due == *date,
+                (Due::Before(date), Some(due)) => due < *date,
+                (Due::After(date), Some(due)) => due > *date,
+            },
+            Query::Tag(tag) => task.tags.iter().any(|t| same(t, tag)),
+            Query::Virtual(tag) => tag.applies(task, today, pending),
+            Query::Project(project) => task.project.as_deref().is_some_and(|p| same(p, project)),
+            Query::Context(context) => task.contexts.iter().any(|c| same(c, context)),
+            Query::Status(status) => status.eq_ignore_ascii_case(task.status()),
+            Query::Owner(owner) => task.owner.as_deref() == Some(owner.as_str()),
+            Query::Is(state) => match state {
+                State::Pending => !task.completed,
+                State::Done => task.completed,
+                State::Overdue => !task.completed && task.is_overdue(today),
+                State::Waiting => task.waiting.is_some(),
+                State::Someday => task.someday.is_some(),
+                State::Habit => task.habit.is_some(),
+            },
+            Query::Not(query) => !query.matches(task, today, pending),
+            Query::All(queries) => queries.iter().all(|q| q.matches(task, today, pending)),
+            Query::Any(queries) => queries.iter().any(|q| q.matches(task, today, pending)),
+        }
+    }
+
+    /// Gives `task` what every task meeting the conditions must have: the
+    /// tags, project, contexts and priority they ask for outright, rather
+    /// than under `or` or `not`. What the task already has is kept.
+    pub fn implied(&self, task: &mut Task) {
+        let has = |list: &[String], name: &str| {
+            list.iter()
+                .any(|item| search::fold(item) == search::fold(name))
+        };
+        match self {
+            Query::Tag(tag) if !has(&task.tags, tag) => task.tags.push(tag.clone()),
+            Query::Project(project) => {
+                task.project.get_or_insert_with(|| project.clone());
+            }
+            Query::Context(context) if !has(&task.contexts, context) => {
+                task.contexts.push(context.clone());
+            }
+            Query::Priority(Some(priority)) => {
+                task.priority.get_or_insert(*priority);
+            }
+            Query::All(queries) => queries.iter().for_each(|q| q.implied(task)),
+            _ => {}
+        }
+    }
+}
+
+/// Splits an expression into words, parentheses and quoted phrases.
+fn tokenize(input: &str) -> Result<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    let mut word = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == '"' {
+                        closed = true;
+                        break;
+                    }
+                    word.push(c);
+                }
+                if !closed {
+                    return Err(Error::Invalid(tr("query-unclosed-quote", &[])));
+                }
+            }
+            '(' | ')' => {
+                if !word.is_empty() {
+                    tokens.push(std::mem::take(&mut word));
+                }
+                tokens.push(c.to_string());
+            }
+            c if c.is_whitespace() => {
+                if !word.is_empty() {
+                    tokens.push(std::mem::take(&mut word));
+                }
+            }
+            c => word.push(c),
+        }
+    }
+    if !word.is_empty() {
+        tokens.push(word);
+    }
+    Ok(tokens)
+}
+
+/// Reads tokens into a query: `or` binds loosest, then `and` (or nothing),
+/// then `not`.
+struct Parser<'a> {
+    tokens: &'a [String],
+    pos: usize,
+    today: Date,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(String::as_str)
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        if self.peek().is_some_and(|t| t.eq_ignore_ascii_case(keyword)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn any(&mut self) -> Result<Query> {
+        let mut queries = vec![self.all()?];
+        while self.keyword("or") {
+            if matches!(self.peek(), None | Some(")")) {
+                return Err(Error::Invalid(tr("query-missing", &[("after", &"or")])));
+            }
+            queries.push(self.all()?);
+        }
+        Ok(match queries.len() {
+            1 => queries.remove(0),
+            _ => Query::Any(queries),
+        })
+    }
+
+    fn all(&mut self) -> Result<Query> {
+        let mut queries = Vec::new();
+        loop {
+            match self.peek() {
+                None | Some(")") => break,
+                Some(t) if t.eq_ignore_ascii_case("or") => break,
+                _ => {}
+            }
+            if self.keyword("and") {
+                continue;
+            }
+            queries.push(self.not()?);
+        }
+        Ok(match queries.len() {
+            1 => queries.remove(0),
+            _ => Query::All(queries),
+        })
+    }
+
+    fn not(&mut self) -> Result<Query> {
+        if self.keyword("not") {
+            return Ok(Query::Not(Box::new(self.operand("not")?)));
+        }
+        if let Some(rest) = self.peek().and_then(|t| t.strip_prefix('-')) {
+            if !rest.is_empty() {
+                let rest = rest.to_string();
+                self.pos += 1;
+                return Ok(Query::Not(Box::new(self.condition(&rest)?)));
+            }
+        }
+        if self.keyword("(") {
+            let query = self.any()?;
+            if !self.keyword(")") {
+                return Err(Error::Invalid(tr("query-unclosed-paren", &[])));
+            }
+            return Ok(query);
+        }
+        let token = self.tokens[self.pos].clone();
+        self.pos += 1;
+        self.condition(&token)
+    }
+
+    /// What follows `not`, which must be there.
+    fn operand(&mut self, after: &str) -> Result<Query> {
+        match self.peek() {
+            None | Some(")") => Err(Error::Invalid(tr("query-missing", &[("after", &after)]))),
+            Some(t) if t.eq_ignore_ascii_case("or") || t.eq_ignore_ascii_case("and") => {
+                Err(Error::Invalid(tr("query-missing", &[("after", &after)])))
+            }
+            _ => self.not(),
+        }
+    }
+
+    /// A single condition, such as `prio:high`, `#work` or a word.
+    fn condition(&self, token: &str) -> Result<Query> {
+        let virtual_tag = token
+            .strip_prefix('#')
+            .or_else(|| token.strip_prefix("tag:"));
+        if let Some(tag) = virtual_tag.and_then(VirtualTag::parse) {
+            return Ok(Query::Virtual(tag));
+        }
+        if let Some(tag) = token.strip_prefix('#').filter(|t| !t.is_empty()) {
+            return Ok(Query::Tag(normalize_tag(tag)));
+        }
+        if let Some(project) = token.strip_prefix('+').filter(|t| !t.is_empty()) {
+            return Ok(Query::Project(normalize_project(project)));
+        }
+        if let Some(context) = token.strip_prefix('@').filter(|t| !t.is_empty()) {
+            return Ok(Query::Context(normalize_context(context)));
+        }
+        let Some((field, value)) = token.split_once(':') else {
+            return Ok(Query::Word(token.to_string()));
+        };
+        let value = value.trim();
+        let none = value.is_empty() || value.eq_ignore_ascii_case("none");
+        let (field, modifier) = match field.split_once('.') {
+            Some((field, modifier)) => (field, Some(modifier)),
+            None => (field, None),
+        };
+        let unknown = || {
+            Error::Invalid(tr(
+                "query-unknown-field",
+                &[("field", &token.split(':').next().unwrap_or_default())],
+            ))
+        };
+        Ok(match (field.to_lowercase().as_str(), modifier) {
+            ("prio" | "priority", None) if none => Query::Priority(None),
+            ("prio" | "priority", None) => Query::Priority(Some(validate::priority(value)?)),
+            ("due", None) if none => Query::Due(Due::None),
+            ("due", None) if value.eq_ignore_ascii_case("any") => Query::Due(Due::Any),
+            ("due", None) => Query::Due(Due::On(validate::due(value, self.today)?)),
+            ("due", Some("before")) => Query::Due(Due::Before(validate::due(value, self.today)?)),
+            ("due", Some("after")) => Query::Due(Due::After(validate::due(value, self.today)?)),
+            ("tag", None) => Query::Tag(validate::tag(value)?),
+            ("project", None) => Query::Project(validate::project(value)?),
+            ("context", None) => Query::Context(validate::context(value)?),
+            ("status", None) => Query::Status(value.to_string()),
+            ("owner", None) => Query::Owner(value.to_string()),
+            ("is", None) => Query::Is(value.parse().map_err(Error::Invalid)?),
+            _ => return Err(unknown()),
+        })
+    }
+}
+
+/// How a view prints its tasks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// As `list` does, with every detail.
+    List,
+    /// Just each task's number and text.
+    Compact,
+    /// The tasks as JSON, as `export` prints them.
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "list" => Ok(Format::List),
+            "compact" => Ok(Format::Compact),
+            "json" => Ok(Format::Json),
+            _ => Err(tr("view-format-invalid", &[("input", &s.trim())])),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Format::List => "list",
+            Format::Compact => "compact",
+            Format::Json => "json",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A saved view: which tasks, in what order and how they are printed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct View {
+    pub query: Query,
+    /// The order to list them in; `None` for the `default_sort`.
+    pub sort: Option<SortOrder>,
+    pub format: Format,
+}
+
+impl View {
+    /// Reads a view as it is saved: an expression with `sort:` and
+    /// `format:` anywhere in it, with dates relative to `today`.
+    pub fn parse(definition: &str, today: Date) -> Result<View> {
+        let mut view = View {
+            query: Query::All(Vec::new()),
+            sort: None,
+            format: Format::List,
+        };
+        let mut tokens = Vec::new();
+        for token in tokenize(definition)? {
+            match token.split_once(':') {
+                Some(("sort", order)) => view.sort = Some(order.parse().map_err(Error::Invalid)?),
+                Some(("format", format)) => view.format = format.parse().map_err(Error::Invalid)?,
+                _ => tokens.push(token),
+            }
+        }
+        view.query = Query::from_tokens(&tokens, today)?;
+        Ok(view)
+    }
+}
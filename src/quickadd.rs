@@ -0,0 +1,90 @@
+// Quick-add syntax: details written into a task's text as it is added.
+//
+//   todo_cli add "email Bob +work @computer #followup due:fri p1"
+//
+// adds "email Bob" with project `work`, context `computer`, tag `followup`,
+// due on Friday and high priority. The tokens may appear anywhere in the
+// text; they are recognized only as whole words:
+//
+//   +project    the project the task belongs to (the last one given wins)
+//   @context    where or with what it can be done (may be repeated)
+//   #tag        a tag (may be repeated)
+//   due:DATE    the due date, in any form `--due` accepts; a time of day
+//               follows an `@`, as in due:fri@17:00
+//   p1 p2 p3    high, medium or low priority; p4 is no priority
+//
+// A word that only looks like a token, such as "+1" or "@", stays in the text,
+// as does any word written with a backslash in front: `\+work`.
+
+use crate::date::Date;
+use crate::error::Result;
+use crate::task::{Priority, Task};
+use crate::validate;
+
+/// Takes the tokens out of `task`'s text and sets the fields they stand for.
+///
+/// Fields the task already has are kept over what the text says: tags and
+/// contexts are added to, but a due date or priority given some other way,
+/// such as with `--due`, wins.
+pub fn apply(mut task: Task, today: Date) -> Result<Task> {
+    let mut words = Vec::new();
+    let mut due = None;
+    let mut priority = None;
+    let mut project = None;
+    for word in task.text.split_whitespace() {
+        if let Some(escaped) = word.strip_prefix('\\').filter(|w| is_token(w)) {
+            words.push(escaped);
+            continue;
+        }
+        match word.split_at(word.chars().next().map_or(0, char::len_utf8)) {
+            ("+", name) if is_name(name) => project = Some(validate::project(name)?),
+            ("@", name) if is_name(name) => push_new(&mut task.contexts, validate::context(name)?),
+            ("#", name) if is_name(name) => push_new(&mut task.tags, validate::tag(name)?),
+            _ => match word.strip_prefix("due:") {
+                Some(date) if !date.is_empty() => due = Some(validate::due_time(date, today)?),
+                _ => match priority_token(word) {
+                    Some(level) => priority = Some(level),
+                    None => words.push(word),
+                },
+            },
+        }
+    }
+    task.text = words.join(" ");
+    if task.due.is_none() {
+        task.set_due(due);
+    }
+    task.priority = task.priority.or(priority.flatten());
+    task.project = task.project.or(project);
+    Ok(task)
+}
+
+/// Whether `word` would be read as a token.
+fn is_token(word: &str) -> bool {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some('+' | '@' | '#') => is_name(chars.as_str()),
+        _ => word.starts_with("due:") || priority_token(word).is_some(),
+    }
+}
+
+/// Whether `name` can follow `+`, `@` or `#`: it must start with a letter.
+fn is_name(name: &str) -> bool {
+    name.chars().next().is_some_and(char::is_alphabetic)
+}
+
+/// The priority a `p1` to `p4` token stands for; `Some(None)` for `p4`.
+fn priority_token(word: &str) -> Option<Option<Priority>> {
+    match word.to_lowercase().as_str() {
+        "p1" => Some(Some(Priority::High)),
+        "p2" => Some(Some(Priority::Medium)),
+        "p3" => Some(Some(Priority::Low)),
+        "p4" => Some(None),
+        _ => None,
+    }
+}
+
+fn push_new(list: &mut Vec<String>, item: String) {
+    if !list.contains(&item) {
+        list.push(item);
+    }
+}
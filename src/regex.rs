@@ -0,0 +1,657 @@
+// Regular expressions, for `search --regex`.
+//
+// The syntax is the core that grep -E, ripgrep and most languages share:
+//
+//   - `.` any character but a newline, `[a-z]`, `[^0-9]` sets of characters,
+//     `\d`, `\w`, `\s` digits, word characters and spaces (`\D`, `\W`, `\S`
+//     for anything else),
+//   - `^` and `$` the start and end of the text, `\b` and `\B` a word
+//     boundary and anything but one,
+//   - `*`, `+`, `?`, `{2}`, `{2,}`, `{2,5}` repetition (a `?` after one is
+//     accepted and changes nothing, since only whether a task matches counts),
+//   - `a|b` either, `(...)` and `(?:...)` grouping, `\` before any other
+//     punctuation for the character itself.
+//
+// Case is ignored unless the pattern has an upper-case letter in it, as with
+// ripgrep's smart case. Unlike the rest of the search, accents aren't: a
+// pattern says exactly which characters it wants.
+//
+// A pattern is compiled to a small program for a machine that follows every
+// way through it at once, one character of the text at a time (Thompson's
+// construction). Matching takes time in proportion to the text times the
+// pattern, so no pattern can make it run away the way patterns such as
+// `(a*)*b` can with backtracking. We carry this rather than a crate to keep
+// the program free of dependencies.
+
+use std::fmt;
+
+/// The most instructions a pattern may compile to, which bounds how far
+/// repetitions such as `(a{100}){100}` can blow up.
+const MAX_PROGRAM: usize = 10_000;
+
+/// The most parts of a pattern compiling it may visit, for repetitions of
+/// what compiles to nothing, such as `((){1000}){1000}`.
+const MAX_STEPS: usize = 100_000;
+
+/// A compiled regular expression.
+#[derive(Clone, PartialEq)]
+pub struct Regex {
+    pattern: String,
+    program: Vec<Inst>,
+    ignore_case: bool,
+}
+
+impl Regex {
+    /// Compiles `pattern`, or says what is wrong with it.
+    pub fn new(pattern: &str) -> Result<Regex, String> {
+        let mut parser = Parser {
+            chars: pattern.chars().collect(),
+            pos: 0,
+        };
+        let node = parser.alternation()?;
+        if parser.pos < parser.chars.len() {
+            // The only thing that stops a top-level alternation early.
+            return Err("unmatched ')'".to_string());
+        }
+        let mut program = Vec::new();
+        compile(&node, &mut program, &mut 0)?;
+        program.push(Inst::Match);
+        Ok(Regex {
+            pattern: pattern.to_string(),
+            program,
+            ignore_case: !has_upper(pattern),
+        })
+    }
+
+    /// The pattern as it was written.
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Whether the pattern matches anywhere in `text`.
+    pub fn is_match(&self, text: &str) -> bool {
+        let chars: Vec<char> = text.chars().collect();
+        let mut current = Threads::new(self.program.len());
+        let mut next = Threads::new(self.program.len());
+        for pos in 0..=chars.len() {
+            // A match may start at any position.
+            if self.add(&mut current, 0, &chars, pos) {
+                return true;
+            }
+            let Some(&c) = chars.get(pos) else {
+                break;
+            };
+            for i in 0..current.list.len() {
+                let pc = current.list[i];
+                let step = match &self.program[pc] {
+                    Inst::Char(want) => same(*want, c, self.ignore_case),
+                    Inst::Any => c != '\n',
+                    Inst::Class(class) => class.matches(c, self.ignore_case),
+                    _ => false,
+                };
+                if step && self.add(&mut next, pc + 1, &chars, pos + 1) {
+                    return true;
+                }
+            }
+            std::mem::swap(&mut current, &mut next);
+            next.clear();
+        }
+        false
+    }
+
+    /// Adds the thread at `pc` to `threads`, following jumps, splits and
+    /// assertions at `pos` to the instructions that read a character.
+    /// Returns whether one of them is the end of the pattern.
+    fn add(&self, threads: &mut Threads, pc: usize, chars: &[char], pos: usize) -> bool {
+        let mut stack = vec![pc];
+        while let Some(pc) = stack.pop() {
+            if threads.seen[pc] {
+                continue;
+            }
+            threads.seen[pc] = true;
+            match &self.program[pc] {
+                Inst::Match => return true,
+                Inst::Jump(to) => stack.push(*to),
+                Inst::Split(a, b) => {
+                    stack.push(*b);
+                    stack.push(*a);
+                }
+                Inst::Assert(assert) => {
+                    if assert.holds(chars, pos) {
+                        stack.push(pc + 1);
+                    }
+                }
+                Inst::Char(_) | Inst::Any | Inst::Class(_) => threads.list.push(pc),
+            }
+        }
+        false
+    }
+}
+
+impl fmt::Debug for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Regex").field(&self.pattern).finish()
+    }
+}
+
+impl fmt::Display for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
+/// Whether `pattern` asks for case to count: whether it has an upper-case
+/// letter that isn't the name of an escape such as `\D`.
+fn has_upper(pattern: &str) -> bool {
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if c.is_uppercase() {
+            return true;
+        }
+    }
+    false
+}
+
+/// Whether `a` and `b` are the same character, perhaps but for case.
+fn same(a: char, b: char, ignore_case: bool) -> bool {
+    a == b || (ignore_case && lower(a) == lower(b))
+}
+
+fn lower(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn upper(c: char) -> char {
+    c.to_uppercase().next().unwrap_or(c)
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// A pattern as it was parsed.
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Empty,
+    Char(char),
+    Any,
+    Class(Class),
+    Assert(Assert),
+    Concat(Vec<Node>),
+    Alternate(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: u32,
+        max: Option<u32>,
+    },
+}
+
+impl Node {
+    /// Whether the node compiles to no instructions at all.
+    fn is_empty(&self) -> bool {
+        match self {
+            Node::Empty => true,
+            Node::Concat(nodes) | Node::Alternate(nodes) => nodes.iter().all(Node::is_empty),
+            Node::Repeat { node, max, .. } => *max == Some(0) || node.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+/// Something that must hold where the text has got to, without reading it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Assert {
+    Start,
+    End,
+    WordBoundary,
+    NotWordBoundary,
+}
+
+impl Assert {
+    fn holds(self, chars: &[char], pos: usize) -> bool {
+        let boundary = || {
+            let before = pos > 0 && is_word(chars[pos - 1]);
+            let after = chars.get(pos).is_some_and(|&c| is_word(c));
+            before != after
+        };
+        match self {
+            Assert::Start => pos == 0,
+            Assert::End => pos == chars.len(),
+            Assert::WordBoundary => boundary(),
+            Assert::NotWordBoundary => !boundary(),
+        }
+    }
+}
+
+/// The kinds of character `\d`, `\w` and `\s` stand for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Perl {
+    Digit,
+    Word,
+    Space,
+}
+
+impl Perl {
+    fn matches(self, c: char) -> bool {
+        match self {
+            Perl::Digit => c.is_ascii_digit(),
+            Perl::Word => is_word(c),
+            Perl::Space => c.is_whitespace(),
+        }
+    }
+}
+
+/// A set of characters, such as `[a-z_]` or `\d`.
+#[derive(Clone, Debug, PartialEq)]
+struct Class {
+    ranges: Vec<(char, char)>,
+    /// `\d`, `\w` and `\s` in it, and whether each is negated (`\D`, ...).
+    perl: Vec<(Perl, bool)>,
+    negated: bool,
+}
+
+impl Class {
+    fn perl(perl: Perl, negated: bool) -> Class {
+        Class {
+            ranges: Vec::new(),
+            perl: vec![(perl, negated)],
+            negated: false,
+        }
+    }
+
+    fn matches(&self, c: char, ignore_case: bool) -> bool {
+        let in_ranges = |c: char| self.ranges.iter().any(|&(a, b)| a <= c && c <= b);
+        let found = in_ranges(c)
+            || (ignore_case && (in_ranges(lower(c)) || in_ranges(upper(c))))
+            || self
+                .perl
+                .iter()
+                .any(|&(perl, negated)| perl.matches(c) != negated);
+        found != self.negated
+    }
+}
+
+/// One step of a compiled pattern.
+#[derive(Clone, Debug, PartialEq)]
+enum Inst {
+    Char(char),
+    Any,
+    Class(Class),
+    Assert(Assert),
+    /// Go on at both places.
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+/// The threads at one position of the text: where each is in the program.
+struct Threads {
+    list: Vec<usize>,
+    seen: Vec<bool>,
+}
+
+impl Threads {
+    fn new(size: usize) -> Threads {
+        Threads {
+            list: Vec::with_capacity(size),
+            seen: vec![false; size],
+        }
+    }
+
+    fn clear(&mut self) {
+        self.list.clear();
+        self.seen.iter_mut().for_each(|seen| *seen = false);
+    }
+}
+
+/// Appends the instructions for `node` to `program`, counting the parts
+/// visited in `steps`.
+fn compile(node: &Node, program: &mut Vec<Inst>, steps: &mut usize) -> Result<(), String> {
+    *steps += 1;
+    if program.len() > MAX_PROGRAM || *steps > MAX_STEPS {
+        return Err("pattern too large".to_string());
+    }
+    match node {
+        // However often it repeats, nothing is still nothing.
+        Node::Empty => {}
+        _ if node.is_empty() => {}
+        Node::Char(c) => program.push(Inst::Char(*c)),
+        Node::Any => program.push(Inst::Any),
+        Node::Class(class) => program.push(Inst::Class(class.clone())),
+        Node::Assert(assert) => program.push(Inst::Assert(*assert)),
+        Node::Concat(nodes) => {
+            for node in nodes {
+                compile(node, program, steps)?;
+            }
+        }
+        Node::Alternate(nodes) => {
+            // Split to each alternative but the last, each jumping past the
+            // rest when done; the jumps are filled in once the end is known.
+            let mut jumps = Vec::new();
+            for (i, node) in nodes.iter().enumerate() {
+                if i + 1 == nodes.len() {
+                    compile(node, program, steps)?;
+                    break;
+                }
+                let split = program.len();
+                program.push(Inst::Split(split + 1, 0));
+                compile(node, program, steps)?;
+                jumps.push(program.len());
+                program.push(Inst::Jump(0));
+                program[split] = Inst::Split(split + 1, program.len());
+            }
+            let end = program.len();
+            for jump in jumps {
+                program[jump] = Inst::Jump(end);
+            }
+        }
+        Node::Repeat { node, min, max } => {
+            for _ in 0..*min {
+                compile(node, program, steps)?;
+            }
+            match max {
+                None => {
+                    let split = program.len();
+                    program.push(Inst::Split(split + 1, 0));
+                    compile(node, program, steps)?;
+                    program.push(Inst::Jump(split));
+                    program[split] = Inst::Split(split + 1, program.len());
+                }
+                Some(max) => {
+                    // Each optional copy may be skipped to the end.
+                    let mut splits = Vec::new();
+                    for _ in *min..*max {
+                        splits.push(program.len());
+                        program.push(Inst::Split(0, 0));
+                        compile(node, program, steps)?;
+                    }
+                    let end = program.len();
+                    for split in splits {
+                        program[split] = Inst::Split(split + 1, end);
+                    }
+                }
+            }
+        }
+    }
+    if program.len() > MAX_PROGRAM {
+        return Err("pattern too large".to_string());
+    }
+    Ok(())
+}
+
+/// Reads a pattern into its parts.
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// `a|b|...`, up to a `)` or the end.
+    fn alternation(&mut self) -> Result<Node, String> {
+        let mut nodes = vec![self.concatenation()?];
+        while self.eat('|') {
+            nodes.push(self.concatenation()?);
+        }
+        Ok(if nodes.len() == 1 {
+            nodes.remove(0)
+        } else {
+            Node::Alternate(nodes)
+        })
+    }
+
+    /// Parts one after the other, up to a `|`, a `)` or the end.
+    fn concatenation(&mut self) -> Result<Node, String> {
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            nodes.push(self.repetition(atom)?);
+        }
+        Ok(match nodes.len() {
+            0 => Node::Empty,
+            1 => nodes.remove(0),
+            _ => Node::Concat(nodes),
+        })
+    }
+
+    fn atom(&mut self) -> Result<Node, String> {
+        let c = self.chars[self.pos];
+        self.pos += 1;
+        Ok(match c {
+            '.' => Node::Any,
+            '^' => Node::Assert(Assert::Start),
+            '$' => Node::Assert(Assert::End),
+            '[' => Node::Class(self.class()?),
+            '\\' => self.escape(false)?,
+            '*' | '+' | '?' => return Err(format!("nothing to repeat before '{}'", c)),
+            '(' => {
+                if self.eat('?') && !self.eat(':') {
+                    return Err("unsupported group flag".to_string());
+                }
+                let node = self.alternation()?;
+                if !self.eat(')') {
+                    return Err("unclosed '('".to_string());
+                }
+                node
+            }
+            c => Node::Char(c),
+        })
+    }
+
+    /// Any `*`, `+`, `?` or `{...}` after `atom`.
+    fn repetition(&mut self, mut atom: Node) -> Result<Node, String> {
+        loop {
+            let (min, max) = match self.peek() {
+                Some('*') => (0, None),
+                Some('+') => (1, None),
+                Some('?') => (0, Some(1)),
+                Some('{') => match self.counted()? {
+                    Some(counts) => counts,
+                    None => return Ok(atom),
+                },
+                _ => return Ok(atom),
+            };
+            // Past the repetition's last character.
+            self.pos += 1;
+            // A lazy repetition matches the same texts.
+            self.eat('?');
+            atom = Node::Repeat {
+                node: Box::new(atom),
+                min,
+                max,
+            };
+        }
+    }
+
+    /// The `{n}`, `{n,}` or `{n,m}` at the current position, up to but not
+    /// past its `}`. `None` if the `{` doesn't start one, and so stands for
+    /// itself.
+    fn counted(&mut self) -> Result<Option<(u32, Option<u32>)>, String> {
+        let rest = &self.chars[self.pos + 1..];
+        let Some(end) = rest.iter().position(|&c| c == '}') else {
+            return Ok(None);
+        };
+        let inside: String = rest[..end].iter().collect();
+        let number = |s: &str| -> Option<Result<u32, String>> {
+            (!s.is_empty() && s.chars().all(|c| c.is_ascii_digit())).then(|| {
+                s.parse::<u32>()
+                    .ok()
+                    .filter(|&n| n <= 1000)
+                    .ok_or_else(|| format!("repetition count {} is too large", s))
+            })
+        };
+        let (min, max) = match inside.split_once(',') {
+            None => match number(&inside) {
+                Some(n) => (n.clone(), Some(n)),
+                None => return Ok(None),
+            },
+            Some((min, "")) => match number(min) {
+                Some(min) => (min, None),
+                None => return Ok(None),
+            },
+            Some((min, max)) => match (number(min), number(max)) {
+                (Some(min), Some(max)) => (min, Some(max)),
+                _ => return Ok(None),
+            },
+        };
+        let (min, max) = (min?, max.transpose()?);
+        if max.is_some_and(|max| max < min) {
+            return Err(format!("invalid repetition {{{}}}", inside));
+        }
+        self.pos += end + 1;
+        Ok(Some((min, max)))
+    }
+
+    /// What follows a `\`: a class such as `\d`, a boundary, or a character.
+    fn escape(&mut self, in_class: bool) -> Result<Node, String> {
+        let Some(c) = self.peek() else {
+            return Err("'\\' at the end of the pattern".to_string());
+        };
+        self.pos += 1;
+        Ok(match c {
+            'd' => Node::Class(Class::perl(Perl::Digit, false)),
+            'D' => Node::Class(Class::perl(Perl::Digit, true)),
+            'w' => Node::Class(Class::perl(Perl::Word, false)),
+            'W' => Node::Class(Class::perl(Perl::Word, true)),
+            's' => Node::Class(Class::perl(Perl::Space, false)),
+            'S' => Node::Class(Class::perl(Perl::Space, true)),
+            'b' if !in_class => Node::Assert(Assert::WordBoundary),
+            'B' if !in_class => Node::Assert(Assert::NotWordBoundary),
+            'n' => Node::Char('\n'),
+            't' => Node::Char('\t'),
+            'r' => Node::Char('\r'),
+            c if c.is_ascii_alphanumeric() => return Err(format!("unknown escape '\\{}'", c)),
+            c => Node::Char(c),
+        })
+    }
+
+    /// The rest of a `[...]` set, after the `[`.
+    fn class(&mut self) -> Result<Class, String> {
+        let mut class = Class {
+            ranges: Vec::new(),
+            perl: Vec::new(),
+            negated: self.eat('^'),
+        };
+        let mut first = true;
+        loop {
+            let Some(c) = self.peek() else {
+                return Err("unclosed '['".to_string());
+            };
+            self.pos += 1;
+            // A `]` first in the set stands for itself.
+            if c == ']' && !first {
+                return Ok(class);
+            }
+            first = false;
+            let start = match c {
+                '\\' => match self.escape(true)? {
+                    Node::Char(c) => c,
+                    Node::Class(perl) => {
+                        class.perl.extend(perl.perl);
+                        continue;
+                    }
+                    _ => unreachable!("escapes in a set are characters or classes"),
+                },
+                c => c,
+            };
+            let is_range =
+                self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|&c| c != ']');
+            if !is_range {
+                class.ranges.push((start, start));
+                continue;
+            }
+            self.pos += 1;
+            let mut end = self.chars[self.pos];
+            self.pos += 1;
+            if end == '\\' {
+                match self.escape(true)? {
+                    Node::Char(c) => end = c,
+                    _ => return Err(format!("invalid range in '[{}-'", start)),
+                }
+            }
+            if end < start {
+                return Err(format!("invalid range '{}-{}'", start, end));
+            }
+            class.ranges.push((start, end));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, text: &str) -> bool {
+        Regex::new(pattern).unwrap().is_match(text)
+    }
+
+    #[test]
+    fn matches_anywhere_unless_anchored() {
+        assert!(matches("bank", "call the bank"));
+        assert!(!matches("^bank", "call the bank"));
+        assert!(matches("bank$", "call the bank"));
+        assert!(matches("^call .* bank$", "call the bank"));
+        assert!(!matches("^call$", "call the bank"));
+    }
+
+    #[test]
+    fn ignores_case_only_for_lower_case_patterns() {
+        assert!(matches("bank", "Call the BANK"));
+        assert!(!matches("Bank", "call the bank"));
+        assert!(matches("Bank", "call the Bank"));
+    }
+
+    #[test]
+    fn repeats_and_alternates() {
+        assert!(matches(r"^\d{3}-\d{4}$", "555-1234"));
+        assert!(!matches(r"^\d{3}-\d{4}$", "55-1234"));
+        assert!(matches("^(ab)+$", "ababab"));
+        assert!(!matches("^(ab)+$", "ababa"));
+        assert!(matches("^a{2,3}$", "aaa"));
+        assert!(!matches("^a{2,3}$", "aaaa"));
+        assert!(matches("^(?:cat|dog)s?$", "dogs"));
+        assert!(matches("colou?r", "color"));
+        assert!(matches("[^0-9]", "a1"));
+        assert!(!matches("^[^0-9]+$", "a1"));
+    }
+
+    #[test]
+    fn knows_word_boundaries() {
+        assert!(matches(r"\bcat\b", "the cat sat"));
+        assert!(!matches(r"\bcat\b", "concatenate"));
+        assert!(matches(r"\Bcat\B", "concatenate"));
+    }
+
+    #[test]
+    fn takes_no_time_over_nested_repetition() {
+        assert!(!matches("(a*)*b", &"a".repeat(10_000)));
+        // Repeats of nothing compile to nothing, however deep.
+        assert!(matches("((((){1000}){1000}){1000})", "anything"));
+        assert!(matches("^x((){1000}){1000}y$", "xy"));
+    }
+
+    #[test]
+    fn refuses_patterns_too_big_or_malformed() {
+        assert!(Regex::new("(a{100}){200}").is_err());
+        assert!(Regex::new("(ab").is_err());
+        assert!(Regex::new("ab)").is_err());
+        assert!(Regex::new("[ab").is_err());
+    }
+}
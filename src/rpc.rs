@@ -0,0 +1,229 @@
+// The JSON-RPC mode started by `todo_cli --rpc`, for editor plugins.
+//
+// Messages are JSON-RPC 2.0, one per line on stdin and stdout, like `mcp`.
+// Parameters are always an object, and every method takes an optional `list`
+// to work on a named list instead of the configured one:
+//
+//   list                    the tasks, with their numbers
+//   add         {task, ...} add a task; also takes due, priority, tags, notes
+//   toggle      {task}      flip a task between pending and completed
+//   delete      {task}      remove a task
+//   subscribe               the tasks, then a `changed` notification with the
+//                           new tasks whenever the list's file changes (not
+//                           for `memory` or `remote` storage)
+//   unsubscribe             stop sending `changed` for a list
+//
+// `changed` is sent for every change to the file, including ones made by
+// other programs and by this session. Errors from the list itself (no such
+// task, an invalid due date, ...) use the program's exit codes as error codes,
+// e.g. 3 when a task isn't found.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use serde_json::{json, Map, Value};
+use todo_cli::config::Config;
+use todo_cli::hooks::Event;
+use todo_cli::{Error, Task};
+
+use crate::mcp::{self, INVALID_PARAMS, INVALID_REQUEST, METHOD_NOT_FOUND, PARSE_ERROR};
+use crate::serve::{apply_fields, task_json};
+use crate::{save, warn};
+
+/// How often subscribed files are checked for changes.
+const WATCH_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A subscribed list: how to open it, its file, and how the file looked
+/// when last seen.
+struct Watched {
+    config: Config,
+    path: PathBuf,
+    stamp: Option<(SystemTime, u64)>,
+}
+
+type Subscriptions = Arc<Mutex<BTreeMap<Option<String>, Watched>>>;
+type Output = Arc<Mutex<io::Stdout>>;
+
+/// Answers requests on stdin until it is closed.
+pub fn serve(config: &Config) -> io::Result<()> {
+    let output: Output = Arc::new(Mutex::new(io::stdout()));
+    let subscriptions: Subscriptions = Arc::default();
+    {
+        let output = Arc::clone(&output);
+        let subscriptions = Arc::clone(&subscriptions);
+        thread::spawn(move || watch(&subscriptions, &output));
+    }
+
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => handle(config, &subscriptions, &message),
+            Err(e) => Some(mcp::error(
+                Value::Null,
+                PARSE_ERROR,
+                &format!("invalid JSON: {}", e),
+            )),
+        };
+        if let Some(response) = response {
+            send(&output, &response)?;
+        }
+    }
+    Ok(())
+}
+
+fn send(output: &Output, message: &Value) -> io::Result<()> {
+    let mut out = output.lock().unwrap_or_else(|e| e.into_inner());
+    writeln!(out, "{}", message)?;
+    out.flush()
+}
+
+fn handle(config: &Config, subscriptions: &Subscriptions, message: &Value) -> Option<Value> {
+    let Some(method) = message.get("method").and_then(Value::as_str) else {
+        let id = message.get("id").cloned().unwrap_or(Value::Null);
+        return Some(mcp::error(id, INVALID_REQUEST, "not a JSON-RPC request"));
+    };
+    let id = message.get("id").cloned();
+    let mut params = match message.get("params") {
+        None | Some(Value::Null) => Map::new(),
+        Some(Value::Object(params)) => params.clone(),
+        Some(_) => {
+            return id.map(|id| mcp::error(id, INVALID_PARAMS, "params must be an object"));
+        }
+    };
+
+    let result = match method {
+        "list" | "add" | "toggle" | "delete" | "subscribe" | "unsubscribe" => {
+            call(config, subscriptions, method, &mut params)
+        }
+        _ => {
+            let msg = format!("unknown method '{}'", method);
+            return id.map(|id| mcp::error(id, METHOD_NOT_FOUND, &msg));
+        }
+    };
+    // Notifications (no id) are carried out, but never answered.
+    let id = id?;
+    Some(match result {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err(e) => mcp::error(id, i64::from(e.exit_code()), &e.to_string()),
+    })
+}
+
+fn call(
+    config: &Config,
+    subscriptions: &Subscriptions,
+    method: &str,
+    params: &mut Map<String, Value>,
+) -> todo_cli::Result<Value> {
+    let (config, mut store) = mcp::open(config, params, "rpc")?;
+    match method {
+        "list" => Ok(tasks_json(store.tasks())),
+        "add" => {
+            let mut task = Task::default();
+            apply_fields(&mut task, params)?;
+            let index = store.add(task)?;
+            save(&config, &store, &[(Event::Add, &store.tasks()[index])])?;
+            Ok(task_json(index, &store.tasks()[index]))
+        }
+        "toggle" => {
+            let index = store.resolve(mcp::task_arg(params)?)?;
+            let mut task = store.tasks()[index].clone();
+            task.completed = !task.completed;
+            store.replace(index, task)?;
+            let task = &store.tasks()[index];
+            let events: &[(Event, &Task)] = if task.completed {
+                &[(Event::Done, task)]
+            } else {
+                &[]
+            };
+            save(&config, &store, events)?;
+            Ok(task_json(index, task))
+        }
+        "delete" => {
+            let index = store.resolve(mcp::task_arg(params)?)?;
+            let removed = store.remove(index)?;
+            save(&config, &store, &[(Event::Delete, &removed)])?;
+            Ok(json!({ "deleted": removed }))
+        }
+        "subscribe" => {
+            let path = store
+                .path()
+                .ok_or_else(|| {
+                    Error::Invalid(format!(
+                        "{} is not a file; only lists kept in files can be watched",
+                        store.location()
+                    ))
+                })?
+                .to_path_buf();
+            let stamp = stamp(&path);
+            let watched = Watched {
+                config: config.clone(),
+                path,
+                stamp,
+            };
+            lock(subscriptions).insert(config.list.clone(), watched);
+            Ok(tasks_json(store.tasks()))
+        }
+        "unsubscribe" => Ok(json!(lock(subscriptions).remove(&config.list).is_some())),
+        _ => unreachable!("method '{}' is dispatched but not handled", method),
+    }
+}
+
+fn tasks_json(tasks: &[Task]) -> Value {
+    tasks
+        .iter()
+        .enumerate()
+        .map(|(i, task)| task_json(i, task))
+        .collect()
+}
+
+fn lock(
+    subscriptions: &Subscriptions,
+) -> std::sync::MutexGuard<'_, BTreeMap<Option<String>, Watched>> {
+    subscriptions.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Identifies a version of a file by its modification time and size.
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Sends `changed` for every subscribed list whose file changes. Runs forever.
+fn watch(subscriptions: &Subscriptions, output: &Output) {
+    loop {
+        thread::sleep(WATCH_INTERVAL);
+        let mut changed = Vec::new();
+        for (list, watched) in lock(subscriptions).iter_mut() {
+            let stamp = stamp(&watched.path);
+            if stamp != watched.stamp {
+                watched.stamp = stamp;
+                changed.push((list.clone(), watched.config.clone()));
+            }
+        }
+        for (list, config) in changed {
+            let tasks = match config.open_store() {
+                Ok(store) => tasks_json(store.tasks()),
+                Err(e) => {
+                    warn(&e.to_string());
+                    continue;
+                }
+            };
+            let notification = json!({
+                "jsonrpc": "2.0",
+                "method": "changed",
+                "params": {"list": list, "tasks": tasks},
+            });
+            if send(output, &notification).is_err() {
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,915 @@
+// A small scripting language for automating the to-do list.
+//
+// `todo_cli run <script>` executes a script against the active list. The
+// language is deliberately tiny, with a syntax close to Rhai or JavaScript:
+//
+//     // Push everything tagged "someday" back a week.
+//     for task in tasks() {
+//         if !task.completed && task.tags.contains("someday") {
+//             task.due = "+1w";
+//             print("Postponed", task.number, task.text);
+//         }
+//     }
+//
+// Statements are `let name = expr`, assignments (to variables or task
+// fields), `if`/`else`, `while`, `for name in list`, and expressions; a `;`
+// between statements is optional. Values are nil, booleans, integers,
+// strings, lists and tasks. Tasks are live: assigning `task.text` or calling
+// `task.done()` changes the list, and the caller saves it afterwards.
+// Deleted tasks are only removed when the script ends, so task numbers don't
+// shift while it runs.
+//
+// Built-in functions:
+//   tasks()           every task on the list, in order
+//   add(text)         adds a task and returns it
+//   find(reference)   the task a number or piece of text refers to
+//   print(values...)  prints the values separated by spaces
+//   len(value)        the length of a string or list
+//   today()           today's date as YYYY-MM-DD
+//   date(text)        parses "fri", "+3d", ... into YYYY-MM-DD
+//
+// Task fields: number, text, completed, due, priority, tags, notes. Task
+// methods: done(), delete(), add_tag(tag), remove_tag(tag). Strings and lists
+// have contains(x) and len(); strings also have lower(), upper() and trim().
+// Command-line arguments after the script name are in the `args` list.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::io::Write;
+
+use crate::date::Date;
+use crate::error::{Error, Result};
+use crate::hooks::Event;
+use crate::store::TaskStore;
+use crate::task::{normalize_tag, Task};
+use crate::validate;
+
+/// What a script did to the list.
+#[derive(Debug, Default)]
+pub struct Outcome {
+    /// Whether the list changed and needs saving.
+    pub changed: bool,
+    /// Tasks that were added, completed or deleted, for running hooks.
+    pub events: Vec<(Event, Task)>,
+}
+
+/// Runs `source` against `store`, writing anything it prints to `out`.
+///
+/// `name` is used in error messages, e.g. `weekly.todo:3: unknown variable 'x'`.
+pub fn run(
+    name: &str,
+    source: &str,
+    store: &mut TaskStore,
+    args: &[String],
+    out: &mut dyn Write,
+) -> Result<Outcome> {
+    let fail = |e: ScriptError| Error::Failed(format!("{}:{}: {}", name, e.line, e.message));
+    let program = Parser::new(tokenize(source).map_err(fail)?)
+        .program()
+        .map_err(fail)?;
+
+    let mut globals = HashMap::new();
+    let args = args.iter().cloned().map(Value::Str).collect();
+    globals.insert("args".to_string(), Value::List(args));
+    let mut interpreter = Interpreter {
+        store,
+        out,
+        scopes: vec![globals],
+        line: 0,
+        added: Vec::new(),
+        completed: Vec::new(),
+        deleted: BTreeSet::new(),
+        changed: false,
+    };
+    interpreter.block(&program).map_err(fail)?;
+    Ok(interpreter.finish())
+}
+
+struct ScriptError {
+    line: usize,
+    message: String,
+}
+
+fn error<T>(line: usize, message: impl Into<String>) -> std::result::Result<T, ScriptError> {
+    Err(ScriptError {
+        line,
+        message: message.into(),
+    })
+}
+
+type ScriptResult<T> = std::result::Result<T, ScriptError>;
+
+// ---------------------------------------------------------------------------
+// Tokens
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Int(i64),
+    Str(String),
+    /// Punctuation and operators, e.g. `(`, `==`, `&&`.
+    Sym(&'static str),
+}
+
+const SYMBOLS: &[&str] = &[
+    "==", "!=", "<=", ">=", "&&", "||", "(", ")", "{", "}", "[", "]", ",", ";", ".", "=", "<", ">",
+    "+", "-", "*", "/", "%", "!",
+];
+
+fn tokenize(source: &str) -> ScriptResult<Vec<(Token, usize)>> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut rest = source;
+    while let Some(c) = rest.chars().next() {
+        if c == '\n' {
+            line += 1;
+            rest = &rest[1..];
+        } else if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+        } else if rest.starts_with("//") || c == '#' {
+            rest = &rest[rest.find('\n').unwrap_or(rest.len())..];
+        } else if c == '"' {
+            let mut text = String::new();
+            let mut chars = rest[1..].char_indices();
+            let end = loop {
+                match chars.next() {
+                    Some((i, '"')) => break i + 2,
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, 'n')) => text.push('\n'),
+                        Some((_, 't')) => text.push('\t'),
+                        Some((_, c @ ('"' | '\\'))) => text.push(c),
+                        _ => return error(line, "invalid escape sequence in string"),
+                    },
+                    Some((_, '\n')) | None => return error(line, "unterminated string"),
+                    Some((_, c)) => text.push(c),
+                }
+            };
+            tokens.push((Token::Str(text), line));
+            rest = &rest[end..];
+        } else if c.is_ascii_digit() {
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit() && c != '_')
+                .unwrap_or(rest.len());
+            let Ok(n) = rest[..end].replace('_', "").parse() else {
+                return error(line, format!("number '{}' is too large", &rest[..end]));
+            };
+            tokens.push((Token::Int(n), line));
+            rest = &rest[end..];
+        } else if c.is_alphabetic() || c == '_' {
+            let end = rest
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            tokens.push((Token::Ident(rest[..end].to_string()), line));
+            rest = &rest[end..];
+        } else {
+            let Some(sym) = SYMBOLS.iter().find(|s| rest.starts_with(*s)) else {
+                return error(line, format!("unexpected character '{}'", c));
+            };
+            tokens.push((Token::Sym(sym), line));
+            rest = &rest[sym.len()..];
+        }
+    }
+    Ok(tokens)
+}
+
+// ---------------------------------------------------------------------------
+// Syntax tree and parser
+
+#[derive(Debug)]
+enum Expr {
+    Literal(Value),
+    Var(String),
+    List(Vec<Expr>),
+    Unary(&'static str, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+    Method(Box<Expr>, String, Vec<Expr>),
+    Field(Box<Expr>, String),
+    Index(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug)]
+enum StmtKind {
+    Let(String, Expr),
+    Assign(Expr, Expr),
+    If(Expr, Vec<Stmt>, Vec<Stmt>),
+    While(Expr, Vec<Stmt>),
+    For(String, Expr, Vec<Stmt>),
+    Expr(Expr),
+}
+
+#[derive(Debug)]
+struct Stmt {
+    kind: StmtKind,
+    line: usize,
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn new(tokens: Vec<(Token, usize)>) -> Parser {
+        Parser { tokens, pos: 0 }
+    }
+
+    fn program(mut self) -> ScriptResult<Vec<Stmt>> {
+        let mut stmts = Vec::new();
+        while self.pos < self.tokens.len() {
+            if !self.eat(";") {
+                stmts.push(self.statement()?);
+            }
+        }
+        Ok(stmts)
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn line(&self) -> usize {
+        match self.tokens.get(self.pos).or(self.tokens.last()) {
+            Some((_, line)) => *line,
+            None => 1,
+        }
+    }
+
+    fn eat(&mut self, sym: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Sym(s)) if *s == sym) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, word: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Ident(w)) if w == word) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, sym: &str) -> ScriptResult<()> {
+        if self.eat(sym) {
+            Ok(())
+        } else {
+            error(self.line(), format!("expected '{}'", sym))
+        }
+    }
+
+    fn ident(&mut self) -> ScriptResult<String> {
+        match self.peek() {
+            Some(Token::Ident(name)) if !is_keyword(name) => {
+                let name = name.clone();
+                self.pos += 1;
+                Ok(name)
+            }
+            _ => error(self.line(), "expected a name"),
+        }
+    }
+
+    fn block(&mut self) -> ScriptResult<Vec<Stmt>> {
+        self.expect("{")?;
+        let mut stmts = Vec::new();
+        while !self.eat("}") {
+            if self.peek().is_none() {
+                return error(self.line(), "expected '}'");
+            }
+            if !self.eat(";") {
+                stmts.push(self.statement()?);
+            }
+        }
+        Ok(stmts)
+    }
+
+    fn statement(&mut self) -> ScriptResult<Stmt> {
+        let line = self.line();
+        let kind = if self.eat_keyword("let") {
+            let name = self.ident()?;
+            self.expect("=")?;
+            StmtKind::Let(name, self.expr()?)
+        } else if self.eat_keyword("if") {
+            self.if_rest()?
+        } else if self.eat_keyword("while") {
+            StmtKind::While(self.expr()?, self.block()?)
+        } else if self.eat_keyword("for") {
+            let name = self.ident()?;
+            if !self.eat_keyword("in") {
+                return error(self.line(), "expected 'in'");
+            }
+            StmtKind::For(name, self.expr()?, self.block()?)
+        } else {
+            let expr = self.expr()?;
+            if self.eat("=") {
+                if !matches!(expr, Expr::Var(_) | Expr::Field(..)) {
+                    return error(line, "can only assign to a variable or a task field");
+                }
+                StmtKind::Assign(expr, self.expr()?)
+            } else {
+                StmtKind::Expr(expr)
+            }
+        };
+        Ok(Stmt { kind, line })
+    }
+
+    fn if_rest(&mut self) -> ScriptResult<StmtKind> {
+        let cond = self.expr()?;
+        let then = self.block()?;
+        let otherwise = if !self.eat_keyword("else") {
+            Vec::new()
+        } else if self.eat_keyword("if") {
+            let line = self.line();
+            vec![Stmt {
+                kind: self.if_rest()?,
+                line,
+            }]
+        } else {
+            self.block()?
+        };
+        Ok(StmtKind::If(cond, then, otherwise))
+    }
+
+    fn expr(&mut self) -> ScriptResult<Expr> {
+        self.binary(0)
+    }
+
+    /// Parses binary operators by precedence level, loosest first.
+    fn binary(&mut self, level: usize) -> ScriptResult<Expr> {
+        const LEVELS: &[&[&str]] = &[
+            &["||"],
+            &["&&"],
+            &["==", "!="],
+            &["<", "<=", ">", ">="],
+            &["+", "-"],
+            &["*", "/", "%"],
+        ];
+        let Some(ops) = LEVELS.get(level) else {
+            return self.unary();
+        };
+        let mut left = self.binary(level + 1)?;
+        while let Some(Token::Sym(sym)) = self.peek() {
+            let Some(op) = ops.iter().find(|op| *op == sym) else {
+                break;
+            };
+            self.pos += 1;
+            let right = self.binary(level + 1)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> ScriptResult<Expr> {
+        for op in ["!", "-"] {
+            if self.eat(op) {
+                return Ok(Expr::Unary(op, Box::new(self.unary()?)));
+            }
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> ScriptResult<Expr> {
+        let mut expr = self.primary()?;
+        loop {
+            if self.eat(".") {
+                let name = self.ident()?;
+                expr = if self.eat("(") {
+                    Expr::Method(Box::new(expr), name, self.arguments()?)
+                } else {
+                    Expr::Field(Box::new(expr), name)
+                };
+            } else if self.eat("[") {
+                let index = self.expr()?;
+                self.expect("]")?;
+                expr = Expr::Index(Box::new(expr), Box::new(index));
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    /// Parses call arguments after the opening parenthesis.
+    fn arguments(&mut self) -> ScriptResult<Vec<Expr>> {
+        self.list_items(")")
+    }
+
+    fn list_items(&mut self, close: &str) -> ScriptResult<Vec<Expr>> {
+        let mut items = Vec::new();
+        while !self.eat(close) {
+            items.push(self.expr()?);
+            if !self.eat(",") {
+                self.expect(close)?;
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    fn primary(&mut self) -> ScriptResult<Expr> {
+        let line = self.line();
+        let Some((token, _)) = self.tokens.get(self.pos).cloned() else {
+            return error(line, "unexpected end of script");
+        };
+        self.pos += 1;
+        match token {
+            Token::Int(n) => Ok(Expr::Literal(Value::Int(n))),
+            Token::Str(s) => Ok(Expr::Literal(Value::Str(s))),
+            Token::Ident(word) => match word.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "nil" => Ok(Expr::Literal(Value::Nil)),
+                _ if is_keyword(&word) => error(line, format!("unexpected '{}'", word)),
+                _ if self.eat("(") => Ok(Expr::Call(word, self.arguments()?)),
+                _ => Ok(Expr::Var(word)),
+            },
+            Token::Sym("(") => {
+                let expr = self.expr()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Token::Sym("[") => Ok(Expr::List(self.list_items("]")?)),
+            Token::Sym(sym) => error(line, format!("unexpected '{}'", sym)),
+        }
+    }
+}
+
+fn is_keyword(word: &str) -> bool {
+    matches!(
+        word,
+        "let" | "if" | "else" | "while" | "for" | "in" | "true" | "false" | "nil"
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Values and evaluation
+
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    Nil,
+    Bool(bool),
+    Int(i64),
+    Str(String),
+    List(Vec<Value>),
+    /// A task on the list, by index.
+    Task(usize),
+}
+
+impl Value {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::Nil => "nil",
+            Value::Bool(_) => "boolean",
+            Value::Int(_) => "integer",
+            Value::Str(_) => "string",
+            Value::List(_) => "list",
+            Value::Task(_) => "task",
+        }
+    }
+
+    fn truthy(&self) -> bool {
+        match self {
+            Value::Nil => false,
+            Value::Bool(b) => *b,
+            Value::Int(n) => *n != 0,
+            Value::Str(s) => !s.is_empty(),
+            Value::List(items) => !items.is_empty(),
+            Value::Task(_) => true,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Nil => f.write_str("nil"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Str(s) => f.write_str(s),
+            Value::List(items) => {
+                let parts: Vec<String> = items.iter().map(|v| v.to_string()).collect();
+                write!(f, "[{}]", parts.join(", "))
+            }
+            Value::Task(index) => write!(f, "task {}", index + 1),
+        }
+    }
+}
+
+struct Interpreter<'a> {
+    store: &'a mut TaskStore,
+    out: &'a mut dyn Write,
+    scopes: Vec<HashMap<String, Value>>,
+    /// The line of the statement being run, for error messages.
+    line: usize,
+    added: Vec<usize>,
+    completed: Vec<usize>,
+    /// Deleted tasks stay in the store until the script ends, so that the
+    /// indices held by other task values stay valid.
+    deleted: BTreeSet<usize>,
+    changed: bool,
+}
+
+impl Interpreter<'_> {
+    fn fail<T>(&self, message: impl Into<String>) -> ScriptResult<T> {
+        error(self.line, message)
+    }
+
+    fn block(&mut self, stmts: &[Stmt]) -> ScriptResult<()> {
+        self.scopes.push(HashMap::new());
+        let result = stmts.iter().try_for_each(|stmt| self.statement(stmt));
+        self.scopes.pop();
+        result
+    }
+
+    fn statement(&mut self, stmt: &Stmt) -> ScriptResult<()> {
+        self.line = stmt.line;
+        match &stmt.kind {
+            StmtKind::Let(name, expr) => {
+                let value = self.eval(expr)?;
+                let scope = self.scopes.last_mut().expect("there is always a scope");
+                scope.insert(name.clone(), value);
+            }
+            StmtKind::Assign(Expr::Var(name), expr) => {
+                let value = self.eval(expr)?;
+                match self.scopes.iter_mut().rev().find_map(|s| s.get_mut(name)) {
+                    Some(slot) => *slot = value,
+                    None => return self.fail(format!("unknown variable '{}'", name)),
+                }
+            }
+            StmtKind::Assign(Expr::Field(target, field), expr) => {
+                let target = self.eval(target)?;
+                let value = self.eval(expr)?;
+                let Value::Task(index) = target else {
+                    return self.fail(format!(
+                        "cannot set '{}' on a {}",
+                        field,
+                        target.type_name()
+                    ));
+                };
+                self.set_field(index, field, value)?;
+            }
+            StmtKind::Assign(..) => unreachable!("the parser only allows variables and fields"),
+            StmtKind::If(cond, then, otherwise) => {
+                if self.eval(cond)?.truthy() {
+                    self.block(then)?;
+                } else {
+                    self.block(otherwise)?;
+                }
+            }
+            StmtKind::While(cond, body) => {
+                while self.eval(cond)?.truthy() {
+                    self.block(body)?;
+                    self.line = stmt.line;
+                }
+            }
+            StmtKind::For(name, list, body) => {
+                let items = match self.eval(list)? {
+                    Value::List(items) => items,
+                    Value::Str(s) => s.chars().map(|c| Value::Str(c.to_string())).collect(),
+                    other => return self.fail(format!("cannot loop over a {}", other.type_name())),
+                };
+                for item in items {
+                    self.scopes.push(HashMap::from([(name.clone(), item)]));
+                    let result = self.block(body);
+                    self.scopes.pop();
+                    result?;
+                }
+            }
+            StmtKind::Expr(expr) => {
+                self.eval(expr)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn eval(&mut self, expr: &Expr) -> ScriptResult<Value> {
+        match expr {
+            Expr::Literal(value) => Ok(value.clone()),
+            Expr::Var(name) => match self.scopes.iter().rev().find_map(|s| s.get(name)) {
+                Some(value) => Ok(value.clone()),
+                None => self.fail(format!("unknown variable '{}'", name)),
+            },
+            Expr::List(items) => Ok(Value::List(
+                items
+                    .iter()
+                    .map(|e| self.eval(e))
+                    .collect::<ScriptResult<_>>()?,
+            )),
+            Expr::Unary(op, operand) => match (*op, self.eval(operand)?) {
+                ("!", value) => Ok(Value::Bool(!value.truthy())),
+                ("-", Value::Int(n)) => Ok(Value::Int(-n)),
+                (op, value) => {
+                    self.fail(format!("cannot apply '{}' to a {}", op, value.type_name()))
+                }
+            },
+            // `&&` and `||` only evaluate their right side when needed.
+            Expr::Binary("&&", left, right) => Ok(Value::Bool(
+                self.eval(left)?.truthy() && self.eval(right)?.truthy(),
+            )),
+            Expr::Binary("||", left, right) => Ok(Value::Bool(
+                self.eval(left)?.truthy() || self.eval(right)?.truthy(),
+            )),
+            Expr::Binary(op, left, right) => {
+                let left = self.eval(left)?;
+                let right = self.eval(right)?;
+                self.binary(op, left, right)
+            }
+            Expr::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|e| self.eval(e))
+                    .collect::<ScriptResult<_>>()?;
+                self.call(name, args)
+            }
+            Expr::Method(target, name, args) => {
+                let target = self.eval(target)?;
+                let args = args
+                    .iter()
+                    .map(|e| self.eval(e))
+                    .collect::<ScriptResult<_>>()?;
+                self.method(target, name, args)
+            }
+            Expr::Field(target, field) => match self.eval(target)? {
+                Value::Task(index) => self.get_field(index, field),
+                other => self.fail(format!("a {} has no field '{}'", other.type_name(), field)),
+            },
+            Expr::Index(target, index) => match (self.eval(target)?, self.eval(index)?) {
+                (Value::List(items), Value::Int(i)) => {
+                    let item = usize::try_from(i).ok().and_then(|i| items.get(i));
+                    Ok(item.cloned().unwrap_or(Value::Nil))
+                }
+                (target, index) => self.fail(format!(
+                    "cannot index a {} with a {}",
+                    target.type_name(),
+                    index.type_name()
+                )),
+            },
+        }
+    }
+
+    fn binary(&self, op: &str, left: Value, right: Value) -> ScriptResult<Value> {
+        use Value::{Bool, Int, List, Str};
+        let value = match (op, left, right) {
+            ("==", l, r) => Bool(l == r),
+            ("!=", l, r) => Bool(l != r),
+            ("+", Int(a), Int(b)) => Int(a.wrapping_add(b)),
+            ("-", Int(a), Int(b)) => Int(a.wrapping_sub(b)),
+            ("*", Int(a), Int(b)) => Int(a.wrapping_mul(b)),
+            ("/" | "%", Int(_), Int(0)) => return self.fail("division by zero"),
+            ("/", Int(a), Int(b)) => Int(a.wrapping_div(b)),
+            ("%", Int(a), Int(b)) => Int(a.wrapping_rem(b)),
+            ("+", Str(a), b) => Str(format!("{}{}", a, b)),
+            ("+", a, Str(b)) => Str(format!("{}{}", a, b)),
+            ("+", List(mut a), List(b)) => {
+                a.extend(b);
+                List(a)
+            }
+            (op @ ("<" | "<=" | ">" | ">="), l, r) => {
+                let ordering = match (&l, &r) {
+                    (Int(a), Int(b)) => a.cmp(b),
+                    (Str(a), Str(b)) => a.cmp(b),
+                    _ => {
+                        return self.fail(format!(
+                            "cannot compare a {} with a {}",
+                            l.type_name(),
+                            r.type_name()
+                        ))
+                    }
+                };
+                Bool(match op {
+                    "<" => ordering.is_lt(),
+                    "<=" => ordering.is_le(),
+                    ">" => ordering.is_gt(),
+                    _ => ordering.is_ge(),
+                })
+            }
+            (op, l, r) => {
+                return self.fail(format!(
+                    "cannot apply '{}' to a {} and a {}",
+                    op,
+                    l.type_name(),
+                    r.type_name()
+                ))
+            }
+        };
+        Ok(value)
+    }
+
+    fn call(&mut self, name: &str, args: Vec<Value>) -> ScriptResult<Value> {
+        match (name, args.as_slice()) {
+            ("tasks", []) => Ok(Value::List(
+                (0..self.store.tasks().len())
+                    .filter(|i| !self.deleted.contains(i))
+                    .map(Value::Task)
+                    .collect(),
+            )),
+            ("add", [Value::Str(text)]) => {
+                let index = self
+                    .store
+                    .add(Task::new(text.as_str()))
+                    .or_else(|e| self.fail(e.to_string()))?;
+                self.added.push(index);
+                self.changed = true;
+                Ok(Value::Task(index))
+            }
+            ("find", [query]) => match self.store.resolve(&query.to_string()) {
+                Ok(index) if !self.deleted.contains(&index) => Ok(Value::Task(index)),
+                Ok(index) => self.fail(format!("task {} was deleted", index + 1)),
+                Err(e) => self.fail(e.to_string()),
+            },
+            ("print", values) => {
+                let parts: Vec<String> = values.iter().map(|v| self.display(v)).collect();
+                writeln!(self.out, "{}", parts.join(" "))
+                    .or_else(|e| self.fail(format!("cannot print: {}", e)))?;
+                Ok(Value::Nil)
+            }
+            ("len", [value]) => self.method(value.clone(), "len", Vec::new()),
+            ("today", []) => Ok(Value::Str(Date::today().to_string())),
+            ("date", [Value::Str(text)]) => match validate::due(text, Date::today()) {
+                Ok(date) => Ok(Value::Str(date.to_string())),
+                Err(e) => self.fail(e.to_string()),
+            },
+            ("tasks" | "add" | "find" | "len" | "today" | "date", _) => {
+                self.fail(format!("wrong arguments for {}()", name))
+            }
+            _ => self.fail(format!("unknown function '{}'", name)),
+        }
+    }
+
+    fn method(&mut self, target: Value, name: &str, args: Vec<Value>) -> ScriptResult<Value> {
+        let value = match (&target, name, args.as_slice()) {
+            (Value::Str(s), "len", []) => Value::Int(s.chars().count() as i64),
+            (Value::Str(s), "contains", [Value::Str(needle)]) => Value::Bool(s.contains(needle)),
+            (Value::Str(s), "lower", []) => Value::Str(s.to_lowercase()),
+            (Value::Str(s), "upper", []) => Value::Str(s.to_uppercase()),
+            (Value::Str(s), "trim", []) => Value::Str(s.trim().to_string()),
+            (Value::List(items), "len", []) => Value::Int(items.len() as i64),
+            (Value::List(items), "contains", [item]) => Value::Bool(items.contains(item)),
+            (Value::Task(index), "done", []) => {
+                let index = self.live(*index)?;
+                if self
+                    .store
+                    .complete(index)
+                    .or_else(|e| self.fail(e.to_string()))?
+                {
+                    self.completed.push(index);
+                    self.changed = true;
+                }
+                Value::Nil
+            }
+            (Value::Task(index), "delete", []) => {
+                let index = self.live(*index)?;
+                self.deleted.insert(index);
+                self.changed = true;
+                Value::Nil
+            }
+            (Value::Task(index), "add_tag" | "remove_tag", [Value::Str(tag)]) => {
+                let mut task = self.task(*index)?.clone();
+                let tag = normalize_tag(tag);
+                task.tags.retain(|t| *t != tag);
+                if name == "add_tag" {
+                    task.tags.push(tag);
+                }
+                self.update(*index, task)?;
+                Value::Nil
+            }
+            _ => {
+                return self.fail(format!(
+                    "a {} has no method '{}' taking {} argument(s)",
+                    target.type_name(),
+                    name,
+                    args.len()
+                ))
+            }
+        };
+        Ok(value)
+    }
+
+    /// Checks that a task hasn't been deleted by the script.
+    fn live(&self, index: usize) -> ScriptResult<usize> {
+        if self.deleted.contains(&index) {
+            self.fail(format!("task {} was deleted", index + 1))
+        } else {
+            Ok(index)
+        }
+    }
+
+    fn task(&self, index: usize) -> ScriptResult<&Task> {
+        let index = self.live(index)?;
+        match self.store.get(index) {
+            Some(task) => Ok(task),
+            None => self.fail(format!("no task {}", index + 1)),
+        }
+    }
+
+    fn update(&mut self, index: usize, task: Task) -> ScriptResult<()> {
+        if self.store.get(index) != Some(&task) {
+            self.store
+                .replace(index, task)
+                .or_else(|e| self.fail(e.to_string()))?;
+            self.changed = true;
+        }
+        Ok(())
+    }
+
+    fn get_field(&self, index: usize, field: &str) -> ScriptResult<Value> {
+        let task = self.task(index)?;
+        let optional = |value: Option<String>| value.map(Value::Str).unwrap_or(Value::Nil);
+        let value = match field {
+            "number" => Value::Int(index as i64 + 1),
+            "text" => Value::Str(task.text.clone()),
+            "completed" => Value::Bool(task.completed),
+            "due" => optional(task.due.map(|d| d.to_string())),
+            "priority" => optional(task.priority.map(|p| p.to_string())),
+            "notes" => optional(task.notes.clone()),
+            "tags" => Value::List(task.tags.iter().cloned().map(Value::Str).collect()),
+            _ => return self.fail(format!("a task has no field '{}'", field)),
+        };
+        Ok(value)
+    }
+
+    fn set_field(&mut self, index: usize, field: &str, value: Value) -> ScriptResult<()> {
+        let mut task = self.task(index)?.clone();
+        let was_completed = task.completed;
+        match (field, value) {
+            ("text", Value::Str(text)) => task.text = text,
+            ("text", _) => return self.fail("a task's text must be a string"),
+            ("completed", Value::Bool(done)) => task.completed = done,
+            ("due", Value::Nil) => task.set_due(None),
+            ("due", Value::Str(text)) => match validate::due_time(&text, Date::today()) {
+                Ok(due) => task.set_due(Some(due)),
+                Err(e) => return self.fail(e.to_string()),
+            },
+            ("priority", Value::Nil) => task.priority = None,
+            ("priority", Value::Str(text)) => match validate::priority(&text) {
+                Ok(priority) => task.priority = Some(priority),
+                Err(e) => return self.fail(e.to_string()),
+            },
+            ("notes", Value::Nil) => task.notes = None,
+            ("notes", Value::Str(notes)) => task.notes = Some(notes),
+            ("tags", Value::List(items)) => {
+                task.tags = Vec::new();
+                for item in items {
+                    match item {
+                        Value::Str(tag) => match validate::tag(&tag) {
+                            Ok(tag) => task.tags.push(tag),
+                            Err(e) => return self.fail(e.to_string()),
+                        },
+                        _ => return self.fail("tags must be strings"),
+                    }
+                }
+            }
+            ("number", _) => return self.fail("a task's number can't be changed"),
+            (field @ ("completed" | "due" | "priority" | "notes" | "tags"), value) => {
+                return self.fail(format!("cannot set '{}' to a {}", field, value.type_name()))
+            }
+            _ => return self.fail(format!("a task has no field '{}'", field)),
+        }
+        if task.completed && !was_completed {
+            self.completed.push(index);
+        }
+        self.update(index, task)
+    }
+
+    /// How `print` shows a value: tasks as their list line, everything else as-is.
+    fn display(&self, value: &Value) -> String {
+        match value {
+            Value::Task(index) => match self.store.get(*index) {
+                Some(task) => {
+                    let status = if task.completed { "[x]" } else { "[ ]" };
+                    format!("{} {}. {}", status, index + 1, task.text)
+                }
+                None => value.to_string(),
+            },
+            Value::List(items) => {
+                let parts: Vec<String> = items.iter().map(|v| self.display(v)).collect();
+                format!("[{}]", parts.join(", "))
+            }
+            _ => value.to_string(),
+        }
+    }
+
+    /// Removes deleted tasks and reports what changed.
+    fn finish(self) -> Outcome {
+        let mut events = Vec::new();
+        for (event, indices) in [(Event::Add, &self.added), (Event::Done, &self.completed)] {
+            for &index in indices {
+                if !self.deleted.contains(&index) {
+                    events.push((event, self.store.tasks()[index].clone()));
+                }
+            }
+        }
+        // Remove from the end so earlier indices stay valid.
+        for &index in self.deleted.iter().rev() {
+            if let Ok(task) = self.store.remove(index) {
+                events.push((Event::Delete, task));
+            }
+        }
+        Outcome {
+            changed: self.changed,
+            events,
+        }
+    }
+}
@@ -0,0 +1,183 @@
+// Matching what people type against task text and tags.
+//
+// Text is compared in a folded form, so that searching for "cafe" finds
+// "Café", "CAFÉ" and "ｃａｆｅ" alike:
+//
+//   - case is folded (`ß` becomes `ss`),
+//   - accents are dropped, whether written as one character (`é`) or as a
+//     letter followed by combining marks (`e` + U+0301),
+//   - compatibility forms are replaced by what they stand for, as Unicode's
+//     NFKC does: full-width letters, ligatures such as `ﬁ`, superscript
+//     digits, unusual spaces, emoji presentation selectors.
+//
+// We carry our own tables for the Latin, Greek and Cyrillic letters people
+// are likely to type rather than the full Unicode database, which keeps the
+// program free of dependencies; other scripts are only lowercased.
+//
+// Fuzzy matching works on grapheme clusters (what a reader sees as one
+// character) so that an emoji made of several code points, like a family or
+// a flag, is never split into parts that match something else.
+
+/// Letters with diacritics, and what they fold to.
+const LETTERS: &[(&str, &str)] = &[
+    ("ÀÁÂÃÄÅàáâãäåĀāĂăĄąǍǎ", "a"),
+    ("Ææ", "ae"),
+    ("ÇçĆćĈĉĊċČč", "c"),
+    ("ĎďĐđÐð", "d"),
+    ("ÈÉÊËèéêëĒēĔĕĖėĘęĚě", "e"),
+    ("ĜĝĞğĠġĢģ", "g"),
+    ("ĤĥĦħ", "h"),
+    ("ÌÍÎÏìíîïĨĩĪīĬĭĮįİıǏǐ", "i"),
+    ("Ĳĳ", "ij"),
+    ("Ĵĵ", "j"),
+    ("Ķķĸ", "k"),
+    ("ĹĺĻļĽľĿŀŁł", "l"),
+    ("ÑñŃńŅņŇňŉŊŋ", "n"),
+    ("ÒÓÔÕÖØòóôõöøŌōŎŏŐőƠơǑǒ", "o"),
+    ("Œœ", "oe"),
+    ("ŔŕŖŗŘř", "r"),
+    ("ŚśŜŝŞşŠšȘșſ", "s"),
+    ("ß", "ss"),
+    ("ŢţŤťŦŧȚț", "t"),
+    ("Þþ", "th"),
+    ("ÙÚÛÜùúûüŨũŪūŬŭŮůŰűŲųƯưǓǔ", "u"),
+    ("Ŵŵ", "w"),
+    ("ÝýÿŶŷŸ", "y"),
+    ("ŹźŻżŽž", "z"),
+    ("Άά", "α"),
+    ("Έέ", "ε"),
+    ("Ήή", "η"),
+    ("Ίίϊΐ", "ι"),
+    ("Όό", "ο"),
+    ("Ύύϋΰ", "υ"),
+    ("Ώώ", "ω"),
+    ("ς", "σ"),
+    ("Ёё", "е"),
+    // Compatibility forms.
+    ("ﬀ", "ff"),
+    ("ﬁ", "fi"),
+    ("ﬂ", "fl"),
+    ("ﬃ", "ffi"),
+    ("ﬄ", "ffl"),
+    ("ﬅﬆ", "st"),
+    ("⁰", "0"),
+    ("¹", "1"),
+    ("²", "2"),
+    ("³", "3"),
+    ("⁴", "4"),
+    ("⁵", "5"),
+    ("⁶", "6"),
+    ("⁷", "7"),
+    ("⁸", "8"),
+    ("⁹", "9"),
+    ("…", "..."),
+    ("™", "tm"),
+];
+
+/// Folds `text` for comparison: see the top of this file.
+pub fn fold(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars() {
+        if is_mark(c) || matches!(c, '\u{FE0E}' | '\u{FE0F}') {
+            continue;
+        }
+        if let Some((_, replacement)) = LETTERS.iter().find(|(from, _)| from.contains(c)) {
+            folded.push_str(replacement);
+            continue;
+        }
+        match c {
+            // Full-width ASCII, as used in East Asian text.
+            '\u{FF01}'..='\u{FF5E}' => {
+                let ascii = char::from_u32(c as u32 - 0xFEE0).unwrap_or(c);
+                folded.extend(ascii.to_lowercase());
+            }
+            '\u{00A0}' | '\u{2000}'..='\u{200A}' | '\u{202F}' | '\u{205F}' | '\u{3000}' => {
+                folded.push(' ')
+            }
+            c => folded.extend(c.to_lowercase()),
+        }
+    }
+    folded
+}
+
+/// Whether `haystack` contains `needle`, once both are folded. Matches start
+/// and end on grapheme cluster boundaries, so part of an emoji doesn't match.
+pub fn contains(haystack: &str, needle: &str) -> bool {
+    let (haystack, needle) = (fold(haystack), fold(needle));
+    if !haystack.contains(&needle) {
+        return false;
+    }
+    let needle = graphemes(&needle);
+    needle.is_empty()
+        || graphemes(&haystack)
+            .windows(needle.len())
+            .any(|w| w == needle)
+}
+
+/// Whether the grapheme clusters of `needle`, other than spaces, appear in
+/// `haystack` in order, once both are folded.
+pub fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let needle = fold(needle);
+    let haystack = fold(haystack);
+    let mut hay = graphemes(&haystack).into_iter();
+    graphemes(&needle)
+        .into_iter()
+        .filter(|g| !g.trim().is_empty())
+        .all(|g| hay.any(|h| h == g))
+}
+
+/// Splits `text` into grapheme clusters.
+///
+/// This follows the parts of Unicode's rules that matter for what people
+/// type: a character keeps the combining marks, variation selectors and
+/// emoji modifiers after it, characters joined by a zero-width joiner stay
+/// together, and regional indicators pair up into flags.
+pub fn graphemes(text: &str) -> Vec<&str> {
+    let mut clusters = Vec::new();
+    let mut start = 0;
+    let mut previous: Option<char> = None;
+    // How many regional indicators the current cluster holds.
+    let mut indicators = 0;
+    for (i, c) in text.char_indices() {
+        let extends = match previous {
+            None => false,
+            Some('\u{200D}') => true,
+            Some(_) => {
+                is_mark(c)
+                    || matches!(c,
+                        '\u{200D}'
+                        | '\u{FE00}'..='\u{FE0F}'
+                        | '\u{1F3FB}'..='\u{1F3FF}'
+                        | '\u{E0020}'..='\u{E007F}')
+                    || (is_regional_indicator(c) && indicators == 1)
+            }
+        };
+        if !extends && i > 0 {
+            clusters.push(&text[start..i]);
+            start = i;
+            indicators = 0;
+        }
+        if is_regional_indicator(c) {
+            indicators += 1;
+        }
+        previous = Some(c);
+    }
+    if start < text.len() {
+        clusters.push(&text[start..]);
+    }
+    clusters
+}
+
+/// Whether `c` is a combining mark that modifies the character before it.
+fn is_mark(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'
+        | '\u{1AB0}'..='\u{1AFF}'
+        | '\u{1DC0}'..='\u{1DFF}'
+        | '\u{20D0}'..='\u{20FF}'
+        | '\u{FE20}'..='\u{FE2F}')
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    matches!(c, '\u{1F1E6}'..='\u{1F1FF}')
+}
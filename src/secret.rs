@@ -0,0 +1,263 @@
+// Encrypting the text and notes of tasks marked secret.
+//
+// A secret task keeps its tags, dates and the rest in the clear, so it still
+// sorts, filters and reminds like any other, but its text and notes are
+// sealed with a passphrase and the list only shows a mask in their place
+// until it is given with `--unlock`.
+//
+// With nothing but SHA-256 to build on, sealing is done the textbook way:
+//
+//   key       PBKDF2-HMAC-SHA256 of the passphrase, over a random salt
+//   cipher    HMAC-SHA256 of a random nonce and a counter, as a keystream
+//             XORed with the text (a stream cipher in counter mode)
+//   tag       HMAC-SHA256 of the nonce and ciphertext, checked before
+//             anything is decrypted, so a wrong passphrase or a changed
+//             file is caught rather than turned into garbage
+//
+// The encryption and MAC keys are derived apart from the same key. Deriving
+// the key is slow on purpose, so tasks sealed in one list share a salt and
+// the key is worked out once per run, not once per task.
+//
+// Sealed text is stored as `v1$SALT$NONCE$CIPHERTEXT$TAG`, each part in hex.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::sha256;
+use crate::task::Task;
+
+/// What a secret task's text is replaced with, in the file and the list.
+pub const MASK: &str = "••••••";
+
+/// How many rounds of HMAC turn a passphrase into a key.
+const ROUNDS: u32 = 100_000;
+
+const SALT: usize = 16;
+const NONCE: usize = 16;
+
+/// A salt and a hash of the passphrase, which together name a key.
+type KeyId = ([u8; SALT], [u8; 32]);
+
+thread_local! {
+    /// The keys derived this run.
+    static KEYS: RefCell<HashMap<KeyId, [u8; 32]>> = RefCell::new(HashMap::new());
+}
+
+/// The text and notes a secret task hides.
+#[derive(Serialize, Deserialize)]
+struct Hidden {
+    text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    notes: Option<String>,
+}
+
+/// Seals `task`'s text and notes under `passphrase`, leaving the mask in
+/// their place. `like` is as for [`seal`].
+pub fn hide(task: &mut Task, passphrase: &str, like: Option<&str>) -> io::Result<()> {
+    let hidden = Hidden {
+        text: std::mem::replace(&mut task.text, MASK.to_string()),
+        notes: task.notes.take(),
+    };
+    let json = serde_json::to_string(&hidden).expect("Failed to serialize data to JSON.");
+    task.secret = Some(seal(&json, passphrase, like)?);
+    Ok(())
+}
+
+/// `task` with its text and notes back, if it is secret and `passphrase`
+/// opens it.
+pub fn reveal(task: &Task, passphrase: &str) -> Option<Task> {
+    let json = open(task.secret.as_deref()?, passphrase)?;
+    let hidden: Hidden = serde_json::from_str(&json).ok()?;
+    Some(Task {
+        text: hidden.text,
+        notes: hidden.notes,
+        secret: None,
+        ..task.clone()
+    })
+}
+
+/// Encrypts `plain` under `passphrase`, with the salt of `like`, another
+/// sealed text, if given and readable, or a new one.
+pub fn seal(plain: &str, passphrase: &str, like: Option<&str>) -> io::Result<String> {
+    let salt = match like.and_then(|sealed| parts(sealed).map(|p| p.salt)) {
+        Some(salt) => salt,
+        None => random_bytes()?,
+    };
+    let nonce: [u8; NONCE] = random_bytes()?;
+    let (encrypt, mac) = keys(passphrase, &salt);
+    let mut data = plain.as_bytes().to_vec();
+    apply_keystream(&encrypt, &nonce, &mut data);
+    let tag = tag(&mac, &nonce, &data);
+    Ok(format!(
+        "v1${}${}${}${}",
+        sha256::hex(&salt),
+        sha256::hex(&nonce),
+        sha256::hex(&data),
+        sha256::hex(&tag)
+    ))
+}
+
+/// Decrypts what [`seal`] made, or `None` if the passphrase is wrong or the
+/// sealed text was changed.
+pub fn open(sealed: &str, passphrase: &str) -> Option<String> {
+    let parts = parts(sealed)?;
+    let (encrypt, mac) = keys(passphrase, &parts.salt);
+    let expected = tag(&mac, &parts.nonce, &parts.data);
+    if !sha256::constant_time_eq(&expected, &parts.tag) {
+        return None;
+    }
+    let mut data = parts.data;
+    apply_keystream(&encrypt, &parts.nonce, &mut data);
+    String::from_utf8(data).ok()
+}
+
+struct Parts {
+    salt: [u8; SALT],
+    nonce: [u8; NONCE],
+    data: Vec<u8>,
+    tag: [u8; 32],
+}
+
+fn parts(sealed: &str) -> Option<Parts> {
+    let mut fields = sealed.split('$');
+    if fields.next()? != "v1" {
+        return None;
+    }
+    let salt = unhex(fields.next()?)?.try_into().ok()?;
+    let nonce = unhex(fields.next()?)?.try_into().ok()?;
+    let data = unhex(fields.next()?)?;
+    let tag = unhex(fields.next()?)?.try_into().ok()?;
+    fields.next().is_none().then_some(Parts {
+        salt,
+        nonce,
+        data,
+        tag,
+    })
+}
+
+fn unhex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// The encryption and MAC keys for `passphrase` and `salt`.
+fn keys(passphrase: &str, salt: &[u8; SALT]) -> ([u8; 32], [u8; 32]) {
+    let id = (*salt, sha256::digest(passphrase.as_bytes()));
+    let key = KEYS.with(|keys| {
+        *keys
+            .borrow_mut()
+            .entry(id)
+            .or_insert_with(|| pbkdf2(passphrase.as_bytes(), salt, ROUNDS))
+    });
+    (sha256::hmac(&key, b"encrypt"), sha256::hmac(&key, b"mac"))
+}
+
+/// PBKDF2 with HMAC-SHA256 (RFC 8018), for one block of output.
+fn pbkdf2(passphrase: &[u8], salt: &[u8], rounds: u32) -> [u8; 32] {
+    let mut block = salt.to_vec();
+    block.extend_from_slice(&1u32.to_be_bytes());
+    let mut u = sha256::hmac(passphrase, &block);
+    let mut key = u;
+    for _ in 1..rounds {
+        u = sha256::hmac(passphrase, &u);
+        for (k, b) in key.iter_mut().zip(u) {
+            *k ^= b;
+        }
+    }
+    key
+}
+
+/// XORs `data` with the keystream for `nonce`, which both encrypts and
+/// decrypts.
+fn apply_keystream(key: &[u8; 32], nonce: &[u8; NONCE], data: &mut [u8]) {
+    for (counter, chunk) in data.chunks_mut(32).enumerate() {
+        let mut block = nonce.to_vec();
+        block.extend_from_slice(&(counter as u64).to_be_bytes());
+        for (byte, key) in chunk.iter_mut().zip(sha256::hmac(key, &block)) {
+            *byte ^= key;
+        }
+    }
+}
+
+fn tag(key: &[u8; 32], nonce: &[u8; NONCE], data: &[u8]) -> [u8; 32] {
+    let mut message = nonce.to_vec();
+    message.extend_from_slice(data);
+    sha256::hmac(key, &message)
+}
+
+/// `N` bytes from the system's secure random source.
+#[cfg(unix)]
+pub fn random_bytes<const N: usize>() -> io::Result<[u8; N]> {
+    use std::fs::File;
+    use std::io::Read;
+    let mut bytes = [0u8; N];
+    File::open("/dev/urandom")?.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Only Unix's `/dev/urandom` is read, so elsewhere there is no random source.
+#[cfg(not(unix))]
+pub fn random_bytes<const N: usize>() -> io::Result<[u8; N]> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "no secure random source on this platform",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pbkdf2_matches_rfc_7914() {
+        assert_eq!(
+            sha256::hex(&pbkdf2(b"passwd", b"salt", 1)),
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc"
+        );
+        assert_eq!(
+            sha256::hex(&pbkdf2(b"Password", b"NaCl", 80_000)),
+            "4ddcd8f60b98be21830cee5ef22701f9641a4418d04c0414aeff08876b34ab56"
+        );
+    }
+
+    #[test]
+    fn opens_what_it_sealed() {
+        let sealed = seal("call the bank", "hunter2", None).unwrap();
+        assert!(!sealed.contains("call the bank"));
+        assert_eq!(open(&sealed, "hunter2").as_deref(), Some("call the bank"));
+        // Sealed again with the same salt, under a new nonce.
+        let again = seal("call the bank", "hunter2", Some(&sealed)).unwrap();
+        assert_ne!(again, sealed);
+        assert_eq!(parts(&again).unwrap().salt, parts(&sealed).unwrap().salt);
+        assert_eq!(open(&again, "hunter2").as_deref(), Some("call the bank"));
+    }
+
+    #[test]
+    fn refuses_a_wrong_passphrase() {
+        let sealed = seal("call the bank", "hunter2", None).unwrap();
+        assert_eq!(open(&sealed, "hunter3"), None);
+    }
+
+    #[test]
+    fn refuses_a_changed_tag_or_ciphertext() {
+        let sealed = seal("call the bank", "hunter2", None).unwrap();
+        let flip_last = |text: &str| {
+            let mut text = text.to_string();
+            let last = if text.ends_with('0') { "1" } else { "0" };
+            text.replace_range(text.len() - 1.., last);
+            text
+        };
+        assert_eq!(open(&flip_last(&sealed), "hunter2"), None);
+        let mut fields: Vec<String> = sealed.split('$').map(String::from).collect();
+        fields[3] = flip_last(&fields[3]);
+        assert_eq!(open(&fields.join("$"), "hunter2"), None);
+    }
+}
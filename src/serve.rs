@@ -0,0 +1,461 @@
+// The HTTP API served by `todo_cli serve`.
+//
+// Every request needs an `Authorization: Bearer <token>` header. Bodies and
+// responses are JSON; tasks look like they do in the task file, plus their
+// `number`. `{task}` is a number or any unique part of the text, just like on
+// the command line.
+//
+//   GET    /tasks                list the active list
+//   POST   /tasks                add a task
+//   GET    /tasks/{task}         show one task
+//   PATCH  /tasks/{task}         change some of a task's fields
+//   DELETE /tasks/{task}         delete a task
+//   POST   /tasks/{task}/done    complete a task
+//   POST   /tasks/{task}/comments  add a comment: `text`, and `author` if not
+//                                   the user making the request
+//   GET    /lists                names of the named lists
+//   DELETE /lists/{name}         delete a named list
+//
+// The task routes also work on a named list under `/lists/{name}`, e.g.
+// `POST /lists/work/tasks`. Requests are handled one at a time, so two
+// clients can never interleave changes to the same file.
+//
+// The server's token stands for the user running it. Each person listed in
+// the config's `[api_users]` table may use their own token instead; tasks
+// they add are theirs, and with `owner_only` set nobody else may complete or
+// delete them, nor a list holding them.
+
+use std::fs;
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Map, Value};
+use todo_cli::config::Config;
+use todo_cli::error::{EXIT_CONFLICT, EXIT_NOT_FOUND, EXIT_USAGE};
+use todo_cli::habit::Habit;
+use todo_cli::hooks::Event;
+use todo_cli::http::{Request, Response};
+use todo_cli::i18n::tr;
+use todo_cli::task::{CheckItem, Comment, Waiting};
+use todo_cli::validate;
+use todo_cli::{secret, sha256};
+use todo_cli::{Date, DateTime, Error, Task};
+
+use crate::{save, warn};
+
+/// Accepts connections on `addr` until the process is stopped.
+pub fn serve(config: &Config, addr: &str, token: &str) -> todo_cli::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    println!("Serving the to-do API on http://{}", listener.local_addr()?);
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(e) = handle_connection(config, token, stream) {
+                    warn(&e.to_string());
+                }
+            }
+            Err(e) => warn(&tr("accept-failed", &[("error", &e)])),
+        }
+    }
+    Ok(())
+}
+
+fn handle_connection(config: &Config, token: &str, stream: TcpStream) -> io::Result<()> {
+    // Don't let a client that never finishes its request hold up everyone else.
+    stream.set_read_timeout(Some(config.http.timeout))?;
+    let response = match Request::read(&stream) {
+        Ok(request) => {
+            let response = handle(config, token, &request);
+            println!(
+                "{} /{} -> {}",
+                request.method,
+                request.path.join("/"),
+                response.status
+            );
+            response
+        }
+        Err(e) => Response::error(400, &e.to_string()),
+    };
+    response.write_to(&stream)
+}
+
+/// Turns one request into a response.
+pub fn handle(config: &Config, token: &str, request: &Request) -> Response {
+    let given = request.header("authorization").unwrap_or_default();
+    let holds = |token: &str| {
+        let expected = format!("Bearer {}", token);
+        sha256::constant_time_eq(given.as_bytes(), expected.as_bytes())
+    };
+    let user = if holds(token) {
+        todo_cli::audit::user()
+    } else {
+        match config.api_users.iter().find(|(_, token)| holds(token)) {
+            Some((name, _)) => name.clone(),
+            None => {
+                return Response::error(401, "missing or invalid token")
+                    .with_header("WWW-Authenticate", "Bearer")
+            }
+        }
+    };
+
+    let path: Vec<&str> = request.path.iter().map(String::as_str).collect();
+    let result = match path.as_slice() {
+        ["lists"] => match request.method.as_str() {
+            "GET" => list_names(config).map(|names| Response::json(200, &json!(names))),
+            _ => Ok(method_not_allowed("GET")),
+        },
+        ["lists", name] => match request.method.as_str() {
+            "DELETE" => delete_list(config, name, &user),
+            _ => Ok(method_not_allowed("DELETE")),
+        },
+        ["lists", name, rest @ ..] => {
+            let mut list = config.clone();
+            match list.apply("list", name) {
+                Ok(()) => task_routes(&list, request, rest, &user),
+                Err(msg) => Err(Error::Invalid(msg)),
+            }
+        }
+        rest => task_routes(config, request, rest, &user),
+    };
+    result.unwrap_or_else(|e| {
+        let status = match e.exit_code() {
+            _ if matches!(e, Error::ReadOnly(_)) => 403,
+            EXIT_USAGE => 400,
+            EXIT_NOT_FOUND => 404,
+            EXIT_CONFLICT => 409,
+            _ => 500,
+        };
+        Response::error(status, &e.to_string())
+    })
+}
+
+/// Handles a request about tasks, made by `user`.
+fn task_routes(
+    config: &Config,
+    request: &Request,
+    path: &[&str],
+    user: &str,
+) -> todo_cli::Result<Response> {
+    let method = request.method.as_str();
+    // With `owner_only`, completing or deleting someone else's task is refused.
+    let forbidden = |task: &Task| match &task.owner {
+        Some(owner) if config.owner_only && owner != user => Some(Response::error(
+            403,
+            &format!("only {} may complete or delete this task", owner),
+        )),
+        _ => None,
+    };
+    let mut store = config.open_store()?;
+    store.audit_as("api");
+    match path {
+        ["tasks"] => match method {
+            "GET" => {
+                let tasks: Vec<Value> = store
+                    .tasks()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, task)| task_json(i, task))
+                    .collect();
+                Ok(Response::json(200, &Value::Array(tasks)))
+            }
+            "POST" => {
+                let mut task = Task {
+                    owner: Some(user.to_string()),
+                    ..Task::default()
+                };
+                apply_fields(&mut task, &body(request)?)?;
+                let index = store.add(task)?;
+                save(config, &store, &[(Event::Add, &store.tasks()[index])])?;
+                Ok(Response::json(
+                    201,
+                    &task_json(index, &store.tasks()[index]),
+                ))
+            }
+            _ => Ok(method_not_allowed("GET, POST")),
+        },
+        ["tasks", reference] => {
+            let index = store.resolve(reference)?;
+            match method {
+                "GET" => Ok(Response::json(
+                    200,
+                    &task_json(index, &store.tasks()[index]),
+                )),
+                "PATCH" => {
+                    let mut task = store.tasks()[index].clone();
+                    let was_completed = task.completed;
+                    apply_fields(&mut task, &body(request)?)?;
+                    if task.completed && !was_completed {
+                        if let Some(response) = forbidden(&task) {
+                            return Ok(response);
+                        }
+                    }
+                    store.replace(index, task)?;
+                    let task = &store.tasks()[index];
+                    let events: &[(Event, &Task)] = if task.completed && !was_completed {
+                        &[(Event::Done, task)]
+                    } else {
+                        &[]
+                    };
+                    save(config, &store, events)?;
+                    Ok(Response::json(200, &task_json(index, task)))
+                }
+                "DELETE" => {
+                    if let Some(response) = forbidden(&store.tasks()[index]) {
+                        return Ok(response);
+                    }
+                    let removed = store.remove(index)?;
+                    save(config, &store, &[(Event::Delete, &removed)])?;
+                    Ok(Response::empty(204))
+                }
+                _ => Ok(method_not_allowed("GET, PATCH, DELETE")),
+            }
+        }
+        ["tasks", reference, "comments"] => {
+            if method != "POST" {
+                return Ok(method_not_allowed("POST"));
+            }
+            let index = store.resolve(reference)?;
+            let fields = body(request)?;
+            let text = match fields.get("text") {
+                Some(Value::String(text)) => validate::comment(text)?,
+                _ => return Err(Error::Invalid("'text' must be a string".to_string())),
+            };
+            let author = match fields.get("author") {
+                Some(Value::String(author)) => validate::person(author)?,
+                None | Some(Value::Null) => user.to_string(),
+                Some(_) => return Err(Error::Invalid("'author' must be a string".to_string())),
+            };
+            let mut task = store.tasks()[index].clone();
+            task.comments.push(Comment {
+                author,
+                at: DateTime::now(),
+                text,
+            });
+            store.replace(index, task)?;
+            save(config, &store, &[])?;
+            Ok(Response::json(
+                201,
+                &task_json(index, &store.tasks()[index]),
+            ))
+        }
+        ["tasks", reference, "done"] => {
+            if method != "POST" {
+                return Ok(method_not_allowed("POST"));
+            }
+            let index = store.resolve(reference)?;
+            if let Some(response) = forbidden(&store.tasks()[index]) {
+                return Ok(response);
+            }
+            if store.complete(index)? {
+                save(config, &store, &[(Event::Done, &store.tasks()[index])])?;
+            }
+            Ok(Response::json(
+                200,
+                &task_json(index, &store.tasks()[index]),
+            ))
+        }
+        _ => Ok(Response::error(404, "no such route")),
+    }
+}
+
+/// A task as the API shows it: the stored fields plus its number.
+pub fn task_json(index: usize, task: &Task) -> Value {
+    let mut value = serde_json::to_value(task).expect("Failed to serialize data to JSON.");
+    if let Value::Object(fields) = &mut value {
+        fields.insert("number".to_string(), json!(index + 1));
+    }
+    value
+}
+
+fn body(request: &Request) -> todo_cli::Result<Map<String, Value>> {
+    match serde_json::from_slice(&request.body) {
+        Ok(Value::Object(fields)) => Ok(fields),
+        Ok(_) => Err(Error::Invalid("the body must be a JSON object".to_string())),
+        Err(e) => Err(Error::Invalid(format!("invalid JSON: {}", e))),
+    }
+}
+
+/// Applies the fields of a request body to a task.
+///
+/// Dates accept the same forms as `add --due`, e.g. "fri" or "+3d", and
+/// `null` clears an optional field. The text is checked when the task is
+/// stored, like any other task's.
+pub fn apply_fields(task: &mut Task, fields: &Map<String, Value>) -> todo_cli::Result<()> {
+    let invalid = |msg: String| Error::Invalid(msg);
+    for (key, value) in fields {
+        match (key.as_str(), value) {
+            ("task" | "text", Value::String(text)) => task.text = text.clone(),
+            ("completed", Value::Bool(done)) => task.completed = *done,
+            ("status", Value::String(status)) => task.set_status(&validate::status(status)?),
+            ("due", Value::Null) => task.set_due(None),
+            ("due", Value::String(due)) => {
+                task.set_due(Some(validate::due_time(due, Date::today())?))
+            }
+            ("priority", Value::Null) => task.priority = None,
+            ("priority", Value::String(p)) => task.priority = Some(validate::priority(p)?),
+            ("notes", Value::Null) => task.notes = None,
+            ("notes", Value::String(notes)) => task.notes = Some(notes.clone()),
+            ("tags", Value::Array(tags)) => {
+                task.tags = tags
+                    .iter()
+                    .map(|tag| match tag.as_str() {
+                        Some(tag) => validate::tag(tag),
+                        None => Err(invalid("'tags' must be a list of strings".to_string())),
+                    })
+                    .collect::<todo_cli::Result<_>>()?;
+            }
+            ("reminders", Value::Array(times)) => {
+                task.reminders = times
+                    .iter()
+                    .map(|at| match at.as_str() {
+                        Some(at) => validate::reminder(at, DateTime::now()),
+                        None => Err(invalid("'reminders' must be a list of strings".to_string())),
+                    })
+                    .collect::<todo_cli::Result<_>>()?;
+            }
+            ("checklist", Value::Array(items)) => {
+                task.checklist = items
+                    .iter()
+                    .map(|item| match item {
+                        Value::String(line) => Ok(CheckItem::parse(line)),
+                        item => serde_json::from_value(item.clone()).map_err(|_| {
+                            invalid("'checklist' must be a list of strings or {\"text\", \"done\"} objects".to_string())
+                        }),
+                    })
+                    .collect::<todo_cli::Result<_>>()?;
+            }
+            ("project", Value::Null) => task.project = None,
+            ("project", Value::String(p)) => task.project = Some(validate::project(p)?),
+            ("contexts", Value::Array(contexts)) => {
+                task.contexts = contexts
+                    .iter()
+                    .map(|context| match context.as_str() {
+                        Some(context) => validate::context(context),
+                        None => Err(invalid("'contexts' must be a list of strings".to_string())),
+                    })
+                    .collect::<todo_cli::Result<_>>()?;
+            }
+            ("someday", Value::Bool(false) | Value::Null) => task.someday = None,
+            ("someday", Value::Bool(true)) => {
+                task.someday = task.someday.or_else(|| Some(Date::today()))
+            }
+            ("waiting", Value::Null) => task.waiting = None,
+            ("waiting", Value::String(person)) => {
+                task.waiting = Some(Waiting {
+                    person: validate::person(person)?,
+                    since: Date::today(),
+                })
+            }
+            ("depends", Value::Array(ids)) => {
+                task.depends = ids
+                    .iter()
+                    .map(|id| {
+                        id.as_u64().ok_or_else(|| {
+                            invalid("'depends' must be a list of task ids".to_string())
+                        })
+                    })
+                    .collect::<todo_cli::Result<_>>()?;
+            }
+            ("habit", Value::Null) => task.habit = None,
+            ("habit", Value::String(every)) => {
+                let (every, business_days) = validate::habit(every)?;
+                match &mut task.habit {
+                    Some(habit) => (habit.every, habit.business_days) = (every, business_days),
+                    None => {
+                        task.habit = Some(Habit::new(every, business_days));
+                        task.due = task.due.or_else(|| Some(Date::today()));
+                    }
+                }
+            }
+            ("id" | "number", _) => {}
+            (
+                "task" | "text" | "completed" | "due" | "priority" | "notes" | "tags" | "reminders"
+                | "waiting" | "someday" | "project" | "contexts" | "checklist" | "status"
+                | "depends" | "habit",
+                _,
+            ) => return Err(invalid(format!("invalid value for '{}'", key))),
+            _ => return Err(invalid(format!("unknown field '{}'", key))),
+        }
+    }
+    Ok(())
+}
+
+/// The files holding named lists, as (name, path): the JSON files beside the
+/// main one that hold a list of tasks. Others, like a `package.json` in the
+/// same directory, are left alone.
+fn named_lists(config: &Config) -> todo_cli::Result<Vec<(String, PathBuf)>> {
+    let dir = match config.storage_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let main = config.storage_path.file_name();
+    let mut lists = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_json = path.extension().is_some_and(|ext| ext == "json");
+        if !is_json || path.file_name() == main || !holds_tasks(&path) {
+            continue;
+        }
+        if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
+            lists.push((name.to_string(), path.clone()));
+        }
+    }
+    lists.sort();
+    Ok(lists)
+}
+
+/// Whether the file at `path` is a list of tasks.
+fn holds_tasks(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|data| serde_json::from_str::<Vec<Task>>(&data).is_ok())
+}
+
+/// The names of the named lists, in order.
+pub fn list_names(config: &Config) -> todo_cli::Result<Vec<String>> {
+    Ok(named_lists(config)?
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect())
+}
+
+/// Deletes the named list `name` for `user`. With `owner_only`, a list
+/// holding someone else's tasks is refused, as deleting them one by one is.
+fn delete_list(config: &Config, name: &str, user: &str) -> todo_cli::Result<Response> {
+    if config.read_only {
+        return Err(Error::ReadOnly(name.to_string()));
+    }
+    match named_lists(config)?.into_iter().find(|(n, _)| n == name) {
+        Some((_, path)) => {
+            if config.owner_only {
+                let data = fs::read_to_string(&path)?;
+                let tasks: Vec<Task> = serde_json::from_str(&data).unwrap_or_default();
+                let others = tasks
+                    .iter()
+                    .filter_map(|task| task.owner.as_deref())
+                    .find(|owner| *owner != user);
+                if let Some(owner) = others {
+                    return Ok(Response::error(
+                        403,
+                        &format!(
+                            "the list holds tasks of {}, which only they may delete",
+                            owner
+                        ),
+                    ));
+                }
+            }
+            fs::remove_file(path)?;
+            Ok(Response::empty(204))
+        }
+        None => Ok(Response::error(404, &format!("no list named '{}'", name))),
+    }
+}
+
+fn method_not_allowed(allowed: &str) -> Response {
+    Response::error(405, "method not allowed").with_header("Allow", allowed)
+}
+
+/// Makes a random token for when none is configured.
+pub fn generate_token() -> io::Result<String> {
+    let bytes: [u8; 16] = secret::random_bytes()?;
+    Ok(sha256::hex(&bytes))
+}
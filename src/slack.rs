@@ -0,0 +1,268 @@
+// Posting the list to Slack: a summary of the day, for a morning cron job,
+// and alerts for tasks that need attention now.
+//
+// Messages go through one of two doors:
+//
+//   slack_webhook_url   an incoming webhook; the channel is the one it was
+//                       made for
+//   slack_token         a bot token, posting with chat.postMessage to
+//                       slack_channel: a channel's name or ID, or a person's
+//                       ID (U...) for a direct message from the bot
+//
+// A token wins when both are set. Alerts are for pending tasks that are
+// overdue or high priority; each is alerted about once for each reason, and
+// again only if it comes due again on a later date, so `slack alerts` can run
+// every few minutes. What was alerted is kept in a file per list, like what
+// `notify-due` announced.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::date::Date;
+use crate::error::{Error, Result};
+use crate::http::Client;
+use crate::i18n::tr;
+use crate::log;
+use crate::task::{Priority, Task};
+
+/// The Web API method bot tokens post with.
+const POST_MESSAGE: &str = "https://slack.com/api/chat.postMessage";
+
+/// Where messages are posted.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Slack {
+    Webhook(String),
+    Bot { token: String, channel: String },
+}
+
+impl Slack {
+    /// Posts `text`, written in Slack's mrkdwn, retrying as `client` allows.
+    pub fn post(&self, client: &Client, text: &str) -> io::Result<()> {
+        match self {
+            Slack::Webhook(url) => {
+                let body = json!({ "text": text }).to_string();
+                log::debug("slack", format_args!("posting to the webhook"));
+                let response = client.send_with_retries(
+                    "POST",
+                    url,
+                    &[("Content-Type", "application/json")],
+                    body.as_bytes(),
+                )?;
+                if (200..300).contains(&response.status) {
+                    return Ok(());
+                }
+                Err(io::Error::other(format!(
+                    "{} {}",
+                    response.status,
+                    String::from_utf8_lossy(&response.body).trim()
+                )))
+            }
+            Slack::Bot { token, channel } => {
+                let body = json!({ "channel": channel, "text": text }).to_string();
+                let authorization = format!("Bearer {}", token);
+                log::debug("slack", format_args!("posting to {}", channel));
+                let response = client.send_with_retries(
+                    "POST",
+                    POST_MESSAGE,
+                    &[
+                        ("Content-Type", "application/json; charset=utf-8"),
+                        ("Authorization", &authorization),
+                    ],
+                    body.as_bytes(),
+                )?;
+                // The Web API answers 200 to most mistakes and says what
+                // went wrong in the body.
+                let answer: Value = serde_json::from_slice(&response.body).unwrap_or_default();
+                if answer["ok"] == true {
+                    return Ok(());
+                }
+                Err(io::Error::other(match answer["error"].as_str() {
+                    Some(error) => error.to_string(),
+                    None => format!("HTTP {}", response.status),
+                }))
+            }
+        }
+    }
+}
+
+/// Where `slack alerts` remembers what it alerted about from `list`.
+pub fn alerted_path(list: Option<&str>) -> PathBuf {
+    crate::daemon::list_file(&crate::config::config_dir().join("slack"), list)
+}
+
+/// Why a task is alerted about.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Reason {
+    Overdue(Date),
+    HighPriority,
+}
+
+/// The alerts already posted, so none is posted twice.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Alerted {
+    /// Overdue tasks, as (task ID, due date).
+    overdue: HashSet<(u64, Date)>,
+    /// High-priority tasks, by ID.
+    high: HashSet<u64>,
+}
+
+impl Alerted {
+    /// Reads what was alerted from `path`; nothing was if it doesn't exist.
+    pub fn load(path: &Path) -> Result<Alerted> {
+        match fs::read_to_string(path) {
+            Ok(data) => serde_json::from_str(&data).map_err(|e| Error::Parse {
+                location: path.display().to_string(),
+                line: e.line(),
+                column: e.column(),
+                message: e.to_string(),
+            }),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(Alerted::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Writes what was alerted to `path`, forgetting the tasks that are no
+    /// longer pending in `tasks`, so they are alerted about again if they
+    /// come back.
+    pub fn save(&mut self, path: &Path, tasks: &[Task]) -> Result<()> {
+        let pending: HashSet<u64> = tasks
+            .iter()
+            .filter(|task| !task.completed)
+            .map(|task| task.id)
+            .collect();
+        self.overdue.retain(|(id, _)| pending.contains(id));
+        self.high.retain(|id| pending.contains(id));
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string(self).expect("Failed to serialize data to JSON.");
+        fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Returns the pending tasks overdue or high priority on `today` that
+    /// haven't been alerted about for that reason, and marks them alerted.
+    pub fn check<'a>(&mut self, tasks: &'a [Task], today: Date) -> Vec<(usize, &'a Task, Reason)> {
+        let mut alerts = Vec::new();
+        for (index, task) in tasks.iter().enumerate() {
+            for reason in self.check_task(task, today) {
+                alerts.push((index, task, reason));
+            }
+        }
+        alerts
+    }
+
+    /// Like [`Alerted::check`], for one task.
+    pub fn check_task(&mut self, task: &Task, today: Date) -> Vec<Reason> {
+        let mut reasons = Vec::new();
+        if task.completed || task.someday.is_some() {
+            return reasons;
+        }
+        if let Some(due) = task.due.filter(|_| task.is_overdue(today)) {
+            if self.overdue.insert((task.id, due)) {
+                reasons.push(Reason::Overdue(due));
+            }
+        }
+        if task.priority == Some(Priority::High) && self.high.insert(task.id) {
+            reasons.push(Reason::HighPriority);
+        }
+        reasons
+    }
+}
+
+/// One message with the `alerts`, or `None` if there are none.
+pub fn alert(alerts: &[(usize, &Task, Reason)], date_format: &str) -> Option<String> {
+    if alerts.is_empty() {
+        return None;
+    }
+    let lines: Vec<String> = alerts
+        .iter()
+        .map(|(index, task, reason)| {
+            let task = format!("{}. {}", index + 1, escape(&task.text));
+            match reason {
+                Reason::Overdue(due) => tr(
+                    "slack-alert-overdue",
+                    &[("task", &task), ("date", &due.format(date_format))],
+                ),
+                Reason::HighPriority => tr("slack-alert-high", &[("task", &task)]),
+            }
+        })
+        .collect();
+    Some(lines.join("\n"))
+}
+
+/// The summary of `tasks` for `today`: what is overdue, due today and high
+/// priority, and how much is pending in all.
+pub fn summary(tasks: &[Task], today: Date, date_format: &str, list: Option<&str>) -> String {
+    let pending: Vec<(usize, &Task)> = tasks
+        .iter()
+        .enumerate()
+        .filter(|(_, task)| !task.completed && task.someday.is_none())
+        .collect();
+    let overdue: Vec<_> = pending
+        .iter()
+        .filter(|(_, task)| task.is_overdue(today))
+        .collect();
+    let due_today: Vec<_> = pending
+        .iter()
+        .filter(|(_, task)| task.due == Some(today) && !task.is_overdue(today))
+        .collect();
+    // High-priority tasks already listed above aren't listed twice.
+    let high: Vec<_> = pending
+        .iter()
+        .filter(|(_, task)| {
+            task.priority == Some(Priority::High) && task.due.is_none_or(|due| due > today)
+        })
+        .collect();
+
+    let date = today.format(date_format);
+    let mut text = match list {
+        Some(list) => tr(
+            "slack-summary-title-list",
+            &[("date", &date), ("list", &list)],
+        ),
+        None => tr("slack-summary-title", &[("date", &date)]),
+    };
+    text.push('\n');
+    for (heading, tasks) in [
+        ("slack-summary-overdue", &overdue),
+        ("slack-summary-today", &due_today),
+        ("slack-summary-high", &high),
+    ] {
+        if tasks.is_empty() {
+            continue;
+        }
+        text.push_str(&format!("\n*{}*\n", tr(heading, &[])));
+        for (index, task) in tasks.iter() {
+            text.push_str(&format!("• {}. {}", index + 1, escape(&task.text)));
+            if let Some(due) = task
+                .due_label(date_format)
+                .filter(|_| task.due_at.is_some() || task.is_overdue(today))
+            {
+                text.push_str(&format!(" _{}_", tr("task-due", &[("date", &due)])));
+            }
+            text.push('\n');
+        }
+    }
+    if overdue.is_empty() && due_today.is_empty() && high.is_empty() {
+        text.push_str(&format!("\n{}\n", tr("slack-summary-clear", &[])));
+    }
+    text.push_str(&format!(
+        "\n{}",
+        tr("slack-summary-pending", &[("count", &pending.len())])
+    ));
+    text
+}
+
+/// `text` with the characters mrkdwn reads as markup escaped.
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
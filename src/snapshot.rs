@@ -0,0 +1,174 @@
+// Named copies of a whole list, taken as a checkpoint before something risky
+// such as a big import or cleanup, and put back with `restore`.
+//
+// Each snapshot is a JSON file of tasks, like the list's own file, under
+// `~/.config/todo/snapshots/<list>/`, so it can also be opened with `--file`
+// or compared with `diff`. When it was taken is the file's modification time.
+//
+// `diff` matches the tasks of two lists by id, so a task whose text changed
+// is still the same task, and one re-added with the same text is not.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::audit::{self, FieldChange};
+use crate::config::config_dir;
+use crate::date::DateTime;
+use crate::error::{Error, Result};
+use crate::i18n::tr;
+use crate::task::Task;
+
+/// A snapshot kept for a list.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    pub name: String,
+    pub taken: DateTime,
+    /// How many tasks it holds.
+    pub tasks: usize,
+}
+
+/// How a task differs between two lists.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Difference {
+    /// Only in the second list.
+    Added(Task),
+    /// Only in the first list.
+    Removed(Task),
+    /// In both, with these fields changed; `task` is the second list's.
+    Changed {
+        task: Task,
+        fields: Vec<FieldChange>,
+    },
+}
+
+impl Difference {
+    /// The task as the list that has it last has it.
+    pub fn task(&self) -> &Task {
+        match self {
+            Difference::Added(task) | Difference::Removed(task) => task,
+            Difference::Changed { task, .. } => task,
+        }
+    }
+}
+
+/// The tasks added, removed and changed going from `a` to `b`, matched by
+/// id and in order of id.
+pub fn compare(a: &[Task], b: &[Task]) -> Vec<Difference> {
+    let before: HashMap<u64, &Task> = a.iter().map(|task| (task.id, task)).collect();
+    let after: HashMap<u64, &Task> = b.iter().map(|task| (task.id, task)).collect();
+    let mut differences: Vec<Difference> = a
+        .iter()
+        .filter(|task| !after.contains_key(&task.id))
+        .map(|task| Difference::Removed(task.clone()))
+        .collect();
+    for task in b {
+        match before.get(&task.id) {
+            None => differences.push(Difference::Added(task.clone())),
+            Some(old) => {
+                let fields = audit::diff(Some(old), task);
+                if !fields.is_empty() {
+                    differences.push(Difference::Changed {
+                        task: task.clone(),
+                        fields,
+                    });
+                }
+            }
+        }
+    }
+    differences.sort_by_key(|d| d.task().id);
+    differences
+}
+
+/// The directory holding the snapshots of `list`.
+pub fn dir(list: Option<&str>) -> PathBuf {
+    config_dir().join("snapshots").join(list.unwrap_or("main"))
+}
+
+/// Where the snapshot `name` of `list` is kept. Names are letters, digits,
+/// `-`, `_` and `.`, so they can't reach outside the directory.
+pub fn path(list: Option<&str>, name: &str) -> Result<PathBuf> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || "-_.".contains(c));
+    if !valid {
+        return Err(Error::Invalid(tr(
+            "snapshot-name-invalid",
+            &[("name", &name)],
+        )));
+    }
+    Ok(dir(list).join(format!("{}.json", name)))
+}
+
+/// A name for a snapshot taken at `now` without one, e.g. `2024-05-01-1430`.
+pub fn default_name(now: DateTime) -> String {
+    format!("{}-{:02}{:02}", now.date(), now.hour(), now.minute())
+}
+
+/// Saves `tasks` as the snapshot `name` of `list`. Fails if there already
+/// is one by that name.
+pub fn take(list: Option<&str>, name: &str, tasks: &[Task]) -> Result<PathBuf> {
+    let path = path(list, name)?;
+    if path.exists() {
+        return Err(Error::Invalid(tr("snapshot-exists", &[("name", &name)])));
+    }
+    fs::create_dir_all(dir(list))?;
+    let json = serde_json::to_string_pretty(tasks).expect("Failed to serialize data to JSON.");
+    fs::write(&path, json)?;
+    Ok(path)
+}
+
+/// The tasks in the snapshot `name` of `list`.
+pub fn load(list: Option<&str>, name: &str) -> Result<Vec<Task>> {
+    read(&path(list, name)?).map_err(|e| match e {
+        Error::Io(ref io) if io.kind() == io::ErrorKind::NotFound => {
+            Error::Invalid(tr("snapshot-not-found", &[("name", &name)]))
+        }
+        e => e,
+    })
+}
+
+/// The tasks in a snapshot or list file at `path`.
+pub fn read(path: &Path) -> Result<Vec<Task>> {
+    let data = fs::read_to_string(path)?;
+    serde_json::from_str(&data).map_err(|e| Error::Parse {
+        location: path.display().to_string(),
+        line: e.line(),
+        column: e.column(),
+        message: e.to_string(),
+    })
+}
+
+/// Every snapshot of `list`, oldest first.
+pub fn list(list: Option<&str>) -> Result<Vec<Snapshot>> {
+    let entries = match fs::read_dir(dir(list)) {
+        Ok(entries) => entries,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut snapshots = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        let secs = fs::metadata(&path)?
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+        snapshots.push(Snapshot {
+            name: name.to_string(),
+            taken: DateTime::from_secs(secs),
+            tasks: read(&path)?.len(),
+        });
+    }
+    snapshots.sort_by(|a, b| (a.taken, &a.name).cmp(&(b.taken, &b.name)));
+    Ok(snapshots)
+}
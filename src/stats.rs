@@ -0,0 +1,156 @@
+// Statistics about a list: how many tasks were added and completed each day,
+// and how many are pending and completed under each tag and project.
+//
+// `stats` prints a summary of them and `stats --csv` the raw numbers, for
+// spreadsheets and plotting tools; `tags` and `projects` list the counts. All of them
+// are worked out by `gather`. Days come from the audit log, like the
+// heatmap's, so they include tasks that have since been removed; tags and
+// projects count the tasks on the list now.
+//
+// A tag or project was last used on the last day the log shows it being
+// given to a task, or a task that has it being completed.
+
+use std::collections::BTreeMap;
+
+use crate::audit::{Action, Entry};
+use crate::date::{Date, DateTime};
+use crate::heatmap;
+use crate::task::Task;
+
+/// How many tasks were added and completed on one day.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Day {
+    pub added: usize,
+    pub completed: usize,
+}
+
+/// How many tasks under one tag or project are pending and completed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Count {
+    pub pending: usize,
+    pub completed: usize,
+    /// When it was last used, if that is known.
+    pub last_used: Option<Date>,
+    /// The soonest due date of the pending tasks.
+    pub next_due: Option<Date>,
+}
+
+impl Count {
+    /// How much of it is completed, as a whole percentage; `None` for none
+    /// of nothing.
+    pub fn percent_done(&self) -> Option<usize> {
+        let total = self.pending + self.completed;
+        (total > 0).then(|| self.completed * 100 / total)
+    }
+}
+
+/// Everything `stats` shows.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    /// Every task on the list, pending or completed.
+    pub total: Count,
+    /// Pending tasks due before today.
+    pub overdue: usize,
+    pub days: BTreeMap<Date, Day>,
+    pub tags: BTreeMap<String, Count>,
+    pub projects: BTreeMap<String, Count>,
+}
+
+/// Works out the statistics for `tasks`, with the list's audit `entries`, as
+/// of `today`.
+pub fn gather(tasks: &[Task], entries: &[Entry], today: Date) -> Stats {
+    let mut stats = Stats::default();
+    for task in tasks {
+        let completed_on = task
+            .completed_at
+            .filter(|_| task.completed)
+            .map(|at| at.date());
+        let tally = |count: &mut Count| {
+            match task.completed {
+                true => count.completed += 1,
+                false => count.pending += 1,
+            }
+            count.last_used = count.last_used.max(completed_on);
+            if let Some(due) = task.due.filter(|_| !task.completed) {
+                count.next_due = Some(count.next_due.map_or(due, |next| next.min(due)));
+            }
+        };
+        tally(&mut stats.total);
+        for tag in &task.tags {
+            tally(stats.tags.entry(tag.clone()).or_default());
+        }
+        if let Some(project) = &task.project {
+            tally(stats.projects.entry(project.clone()).or_default());
+        }
+        if !task.completed && task.is_overdue(today) {
+            stats.overdue += 1;
+        }
+    }
+    for entry in entries {
+        let day = DateTime::from_secs(entry.time as i64).date();
+        if entry.action == Action::Add {
+            stats.days.entry(day).or_default().added += 1;
+        }
+        for change in &entry.fields {
+            let counts = match change.field.as_str() {
+                "tags" => &mut stats.tags,
+                "project" => &mut stats.projects,
+                _ => continue,
+            };
+            for key in change.new.iter().flat_map(|new| new.split(", ")) {
+                if let Some(count) = counts.get_mut(key) {
+                    count.last_used = count.last_used.max(Some(day));
+                }
+            }
+        }
+    }
+    for (day, count) in heatmap::completions(tasks, entries) {
+        stats.days.entry(day).or_default().completed += count;
+    }
+    stats
+}
+
+/// The days from `since` to `until`, added up.
+pub fn between(stats: &Stats, since: Date, until: Date) -> Day {
+    stats
+        .days
+        .range(since..=until)
+        .fold(Day::default(), |total, (_, day)| Day {
+            added: total.added + day.added,
+            completed: total.completed + day.completed,
+        })
+}
+
+/// The statistics as CSV: one row per day, tag and project, saying which it
+/// is in the first column. Cells that don't apply, like a day's pending
+/// count, are left empty.
+pub fn csv(stats: &Stats) -> String {
+    let mut out = String::from("group,key,added,completed,pending\n");
+    for (day, counts) in &stats.days {
+        out.push_str(&format!(
+            "day,{},{},{},\n",
+            day, counts.added, counts.completed
+        ));
+    }
+    for (group, counts) in [("tag", &stats.tags), ("project", &stats.projects)] {
+        for (key, count) in counts {
+            out.push_str(&format!(
+                "{},{},,{},{}\n",
+                group,
+                csv_field(key),
+                count.completed,
+                count.pending
+            ));
+        }
+    }
+    out
+}
+
+/// Quotes a CSV field if it needs it.
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,643 @@
+// Loading, changing and saving the list of tasks.
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::audit;
+use crate::backend::{Change, JsonFile, StorageBackend};
+use crate::date::{Date, DateTime};
+use crate::error::{Error, Result};
+use crate::i18n::tr;
+use crate::listing::Listing;
+use crate::log;
+use crate::search;
+use crate::task::Task;
+use crate::trash::Trash;
+use crate::validate;
+
+/// The tasks in one to-do list, plus the operations on them.
+///
+/// Changes are made in memory; nothing is written until `save` is called, so
+/// a command can make several changes and save them together.
+#[derive(Debug)]
+pub struct TaskStore {
+    backend: Box<dyn StorageBackend>,
+    tasks: Vec<Task>,
+    /// What changed since the tasks were loaded or last saved, in order, each
+    /// with the task it replaced or removed.
+    changes: RefCell<Vec<(Change, Option<Task>)>>,
+    /// The backend's version of the list when it was loaded or last saved.
+    version: RefCell<Option<String>>,
+    /// What the user was last shown, which task numbers refer to.
+    listing: Option<Listing>,
+    /// What saved changes are credited to in the audit log, if they are logged.
+    via: Option<String>,
+    /// Where removed tasks are moved to when saved, if anywhere.
+    trash: Option<Trash>,
+    /// The ids of tasks removed to be kept elsewhere, which aren't trashed.
+    archived: HashSet<u64>,
+    /// The longest task text that `add` and `replace` accept, in characters.
+    max_length: usize,
+}
+
+impl TaskStore {
+    /// Loads the tasks stored in the JSON file at `path`. A missing file is an empty list.
+    pub fn open(path: impl Into<PathBuf>) -> Result<TaskStore> {
+        TaskStore::with_backend(Box::new(JsonFile::new(path)))
+    }
+
+    /// Loads the tasks kept by `backend`.
+    pub fn with_backend(backend: Box<dyn StorageBackend>) -> Result<TaskStore> {
+        // Take the version first: if the list changes while it is read, the
+        // next save sees a conflict instead of missing the change.
+        let version = backend.version()?;
+        let tasks = backend.load()?;
+        log::debug(
+            "store",
+            format_args!("read {} tasks from {}", tasks.len(), backend.location()),
+        );
+        let mut store = TaskStore {
+            backend,
+            tasks,
+            changes: RefCell::default(),
+            version: RefCell::new(version),
+            listing: None,
+            via: None,
+            trash: None,
+            archived: HashSet::new(),
+            max_length: validate::DEFAULT_MAX_LENGTH,
+        };
+        store.assign_ids();
+        Ok(store)
+    }
+
+    /// Makes task numbers refer to the tasks shown in `listing`, if the list
+    /// has changed since; see [`TaskStore::resolve`].
+    pub fn with_listing(mut self, listing: Option<Listing>) -> TaskStore {
+        self.listing = listing;
+        self
+    }
+
+    /// Logs saved changes to the audit log, crediting them to `via`: `cli`,
+    /// `api`, `script:<name>`, ...
+    pub fn audit_as(&mut self, via: impl Into<String>) {
+        self.via = Some(via.into());
+    }
+
+    /// Moves the tasks that saved changes remove to `trash`.
+    pub fn trash_to(&mut self, trash: Trash) {
+        self.trash = Some(trash);
+    }
+
+    /// Sets the longest task text that `add` and `replace` accept.
+    pub fn set_max_length(&mut self, max_length: usize) {
+        self.max_length = max_length;
+    }
+
+    /// Gives an id to each task that has none yet, as lists written before
+    /// tasks had ids don't.
+    ///
+    /// The ids are recorded as changes, to be saved with the next change a
+    /// command makes. Until then, every load assigns the same ones.
+    fn assign_ids(&mut self) {
+        let mut ids: Vec<u64> = self.tasks.iter().map(|task| task.id).collect();
+        if !assign_ids(&mut ids) {
+            return;
+        }
+        for (index, id) in ids.into_iter().enumerate() {
+            if self.tasks[index].id != id {
+                let before = self.tasks[index].clone();
+                self.tasks[index].id = id;
+                let task = self.tasks[index].clone();
+                self.record(Change::Set(index, task), Some(before));
+            }
+        }
+        log::debug(
+            "store",
+            format_args!("gave ids to tasks in {}", self.backend.location()),
+        );
+    }
+
+    /// Writes the changes made since loading back to where the tasks came
+    /// from, all at once. Nothing is written if nothing changed.
+    ///
+    /// Fails with [`Error::Conflict`] if the stored list was changed by
+    /// someone else in the meantime; [`TaskStore::save_merged`] can then
+    /// apply the changes to the new list instead.
+    pub fn save(&self) -> Result<()> {
+        let changes: Vec<Change> = self
+            .changes
+            .borrow()
+            .iter()
+            .map(|(c, _)| c.clone())
+            .collect();
+        if changes.is_empty() {
+            log::debug("store", format_args!("nothing changed; not saving"));
+            return Ok(());
+        }
+        self.check_version(self.version.borrow().as_deref())?;
+        self.backend.save_changes(&self.tasks, &changes)?;
+        log::info(
+            "store",
+            format_args!(
+                "saved {} changes to {}, which now holds {} tasks",
+                changes.len(),
+                self.backend.location(),
+                self.tasks.len()
+            ),
+        );
+        self.saved_changes()
+    }
+
+    /// Applies the changes made since loading to the list as it is stored
+    /// now, and saves the result, which is returned.
+    ///
+    /// This is how a conflict is resolved: tasks this store changed or removed
+    /// are found again by their content, so other programs' changes to other
+    /// tasks are kept. It fails if one of those tasks was itself changed or
+    /// removed.
+    pub fn save_merged(&self) -> Result<Vec<Task>> {
+        let version = self.backend.version()?;
+        let mut tasks = self.backend.load()?;
+        let rebased: Vec<Change> = self
+            .rebase(&mut tasks)?
+            .into_iter()
+            .map(|(change, _)| change)
+            .collect();
+        self.check_version(version.as_deref())?;
+        self.backend.save_changes(&tasks, &rebased)?;
+        log::info(
+            "store",
+            format_args!(
+                "merged {} changes into {}, which now holds {} tasks",
+                rebased.len(),
+                self.backend.location(),
+                tasks.len()
+            ),
+        );
+        self.saved_changes()?;
+        Ok(tasks)
+    }
+
+    /// Reads the list again if someone else saved it since it was read, and
+    /// applies the unsaved changes to it as [`TaskStore::save_merged`] would.
+    /// Returns whether the list was read again.
+    ///
+    /// Commands that wait on the user call it once they have an answer, so
+    /// that it is applied to the list as it is now.
+    pub fn reload(&mut self) -> Result<bool> {
+        let version = self.backend.version()?;
+        if version.is_none() || version == *self.version.borrow() {
+            return Ok(false);
+        }
+        let mut tasks = self.backend.load()?;
+        let rebased = self.rebase(&mut tasks)?;
+        log::info(
+            "store",
+            format_args!(
+                "{} changed; read it again and reapplied {} changes",
+                self.backend.location(),
+                rebased.len()
+            ),
+        );
+        self.tasks = tasks;
+        *self.changes.get_mut() = rebased;
+        *self.version.get_mut() = version;
+        self.assign_ids();
+        Ok(true)
+    }
+
+    /// Applies this store's changes to `tasks`, another version of the list,
+    /// and returns them as they apply there. Tasks this store changed or
+    /// removed are found again by their content.
+    fn rebase(&self, tasks: &mut Vec<Task>) -> Result<Vec<(Change, Option<Task>)>> {
+        let mut rebased = Vec::new();
+        for (change, before) in self.changes.borrow().iter() {
+            // Where the task this change replaces or removes is now.
+            let locate = |tasks: &[Task], index: usize| {
+                let before = before
+                    .as_ref()
+                    .expect("updates and removals record the task they replace");
+                find(tasks, index, before).ok_or_else(|| Error::MergeFailed(before.text.clone()))
+            };
+            let change = match change {
+                Change::Add(task) => {
+                    // Someone else may have added a task with the same id.
+                    let mut task = task.clone();
+                    if tasks.iter().any(|t| t.id == task.id) {
+                        task.id = next_id(tasks);
+                    }
+                    tasks.push(task.clone());
+                    Change::Add(task)
+                }
+                Change::Set(index, task) => {
+                    let index = locate(tasks, *index)?;
+                    tasks[index] = task.clone();
+                    Change::Set(index, task.clone())
+                }
+                Change::Remove(index) => {
+                    let index = locate(tasks, *index)?;
+                    tasks.remove(index);
+                    Change::Remove(index)
+                }
+            };
+            rebased.push((change, before.clone()));
+        }
+        Ok(rebased)
+    }
+
+    /// Fails if the stored list is no longer at version `expected`.
+    fn check_version(&self, expected: Option<&str>) -> Result<()> {
+        let Some(expected) = expected else {
+            return Ok(());
+        };
+        if self.backend.version()?.as_deref() != Some(expected) {
+            return Err(Error::Conflict(self.backend.location()));
+        }
+        Ok(())
+    }
+
+    /// Logs the changes just saved and trashes the tasks they removed, then
+    /// forgets them and notes the new version.
+    fn saved_changes(&self) -> Result<()> {
+        if let Some(trash) = &self.trash {
+            let removed: Vec<Task> = self
+                .changes
+                .borrow()
+                .iter()
+                .filter_map(|(change, before)| match (change, before) {
+                    (Change::Remove(_), Some(task)) if !self.archived.contains(&task.id) => {
+                        Some(task.clone())
+                    }
+                    _ => None,
+                })
+                .collect();
+            // As with the audit log, the changes are saved either way.
+            if let Err(e) = trash.put(removed, DateTime::now()) {
+                log::warn("trash", format_args!("could not keep removed tasks: {}", e));
+            }
+        }
+        if let Some(via) = &self.via {
+            let entries = audit::entries(&self.changes.borrow(), &self.backend.location(), via);
+            // The changes are saved either way; a missing entry is not worth failing over.
+            if let Err(e) = audit::append(&entries) {
+                log::warn(
+                    "audit",
+                    format_args!("could not write to {}: {}", audit::path().display(), e),
+                );
+            }
+        }
+        self.changes.borrow_mut().clear();
+        *self.version.borrow_mut() = self.backend.version()?;
+        Ok(())
+    }
+
+    /// Reads the tasks as they are currently saved, ignoring unsaved changes.
+    pub fn saved(&self) -> Result<Vec<Task>> {
+        self.backend.load()
+    }
+
+    /// Where this store reads from and writes to, e.g. a file path or a URL.
+    pub fn location(&self) -> String {
+        self.backend.location()
+    }
+
+    /// The local file this store reads from and writes to, if it uses one.
+    pub fn path(&self) -> Option<&Path> {
+        self.backend.path()
+    }
+
+    /// All tasks, in the order they were added. A task's number is its index plus one.
+    pub fn tasks(&self) -> &[Task] {
+        &self.tasks
+    }
+
+    /// The task at `index`, if there is one.
+    pub fn get(&self, index: usize) -> Option<&Task> {
+        self.tasks.get(index)
+    }
+
+    /// The index of the task with this id, if it is still in the list.
+    pub fn position(&self, id: u64) -> Option<usize> {
+        self.tasks.iter().position(|task| task.id == id)
+    }
+
+    /// The backend's version of the list as loaded or last saved; see
+    /// [`StorageBackend::version`].
+    pub fn version(&self) -> Option<String> {
+        self.version.borrow().clone()
+    }
+
+    /// Appends a task, giving it the next free id, and returns its index.
+    /// Unless it has an owner already, the user running the program owns it.
+    ///
+    /// The task is checked and cleaned up first; see [`validate::task`].
+    pub fn add(&mut self, task: Task) -> Result<usize> {
+        let mut task = validate::task(task, self.max_length)?;
+        task.id = next_id(&self.tasks);
+        if task.owner.is_none() {
+            task.owner = Some(audit::user());
+        }
+        task.created_at.get_or_insert_with(DateTime::now);
+        stamp_completion(&mut task, None);
+        self.record(Change::Add(task.clone()), None);
+        self.tasks.push(task);
+        Ok(self.tasks.len() - 1)
+    }
+
+    /// Appends a task keeping its id, as one that comes from another copy of
+    /// the list does, and returns its index. Fails if the id is taken.
+    pub fn insert(&mut self, task: Task) -> Result<usize> {
+        let id = task.id;
+        let mut task = validate::task(task, self.max_length)?;
+        if id == 0 || self.tasks.iter().any(|t| t.id == id) {
+            return Err(Error::Invalid(tr("store-id-taken", &[("id", &id)])));
+        }
+        task.id = id;
+        stamp_completion(&mut task, None);
+        self.record(Change::Add(task.clone()), None);
+        self.tasks.push(task);
+        Ok(self.tasks.len() - 1)
+    }
+
+    /// Marks a task as completed. Returns `false` if it already was.
+    ///
+    /// A habit isn't completed: the day is recorded and it is next due a
+    /// period later. Returns `false` if it was already done today.
+    pub fn complete(&mut self, index: usize) -> Result<bool> {
+        let task = self.task_mut(index)?;
+        let before = task.clone();
+        if let Some(habit) = task.habit.as_mut().filter(|_| !task.completed) {
+            let today = Date::today();
+            if !habit.check(today) {
+                return Ok(false);
+            }
+            let next = habit.next_due(today);
+            task.set_due(Some((next, None)));
+            task.modified_at = Some(DateTime::now());
+            let task = task.clone();
+            self.record(Change::Set(index, task), Some(before));
+            return Ok(true);
+        }
+        if task.completed {
+            return Ok(false);
+        }
+        task.completed = true;
+        task.status = None;
+        task.modified_at = Some(DateTime::now());
+        stamp_completion(task, Some(&before));
+        let task = task.clone();
+        self.record(Change::Set(index, task), Some(before));
+        Ok(true)
+    }
+
+    /// Replaces the task at `index` with an edited version, checked and
+    /// cleaned up like a new one. It keeps its id, and the time it was
+    /// completed if it still is, and records when it was edited.
+    pub fn replace(&mut self, index: usize, task: Task) -> Result<()> {
+        let mut task = validate::task(task, self.max_length)?;
+        let current = self.task_mut(index)?;
+        task.id = current.id;
+        // A task is added only once, however it is edited since.
+        task.created_at = current.created_at.or(task.created_at);
+        // Unless the edit says when it was made, as a synced one does. Being
+        // moved in the manual order doesn't count.
+        let edited = Task {
+            rank: current.rank,
+            ..task.clone()
+        };
+        if edited != *current && task.modified_at == current.modified_at {
+            task.modified_at = Some(DateTime::now());
+        }
+        stamp_completion(&mut task, Some(current));
+        let before = std::mem::replace(self.task_mut(index)?, task.clone());
+        self.record(Change::Set(index, task), Some(before));
+        Ok(())
+    }
+
+    /// Removes the task at `index` and returns it.
+    pub fn remove(&mut self, index: usize) -> Result<Task> {
+        self.task_mut(index)?;
+        let removed = self.tasks.remove(index);
+        self.record(Change::Remove(index), Some(removed.clone()));
+        Ok(removed)
+    }
+
+    /// Removes the task at `index` to be kept elsewhere, such as in the
+    /// archive, and returns it. Unlike [`TaskStore::remove`], it isn't put
+    /// in the trash when saved.
+    pub fn archive(&mut self, index: usize) -> Result<Task> {
+        let task = self.remove(index)?;
+        self.archived.insert(task.id);
+        Ok(task)
+    }
+
+    /// Removes completed tasks, or every task if `all` is set. Returns the removed tasks.
+    pub fn clear(&mut self, all: bool) -> Vec<Task> {
+        // Removing from the back keeps the earlier indices valid.
+        for index in (0..self.tasks.len()).rev() {
+            if all || self.tasks[index].completed {
+                let before = self.tasks[index].clone();
+                self.record(Change::Remove(index), Some(before));
+            }
+        }
+        let (removed, kept) = self.tasks.drain(..).partition(|task| all || task.completed);
+        self.tasks = kept;
+        removed
+    }
+
+    fn record(&mut self, change: Change, before: Option<Task>) {
+        self.changes.get_mut().push((change, before));
+    }
+
+    /// Finds the index of the task a user is referring to.
+    ///
+    /// `id:<id>` picks the task with that id. A number picks the task at that
+    /// position, or, if there is a listing and the list changed since it was
+    /// shown, the task that was shown with that number. Three or more hex
+    /// digits also pick the task whose [`Task::hash`] starts with them.
+    /// Anything else is matched against the task text, ignoring case and
+    /// accents (see
+    /// [`search::fold`]): first as a substring, then as a fuzzy match where
+    /// the query's characters must appear in order. Exactly one task must
+    /// match.
+    pub fn resolve(&self, query: &str) -> Result<usize> {
+        let query = query.trim();
+        if let Some(id) = query
+            .strip_prefix("id:")
+            .and_then(|id| id.trim().parse().ok())
+        {
+            return self
+                .position(id)
+                .ok_or_else(|| Error::NoMatch(query.to_string()));
+        }
+        if let Ok(number) = query.parse::<usize>() {
+            return self.resolve_number(number);
+        }
+        if query.is_empty() {
+            return Err(Error::MissingQuery);
+        }
+
+        let needle = search::fold(query);
+        let texts: Vec<String> = self.tasks.iter().map(|t| search::fold(&t.text)).collect();
+
+        // An exact match always wins, even if the text also appears inside other tasks.
+        let exact: Vec<usize> = (0..texts.len()).filter(|&i| texts[i] == needle).collect();
+        if exact.len() == 1 {
+            return Ok(exact[0]);
+        }
+
+        // The start of a task's hash, as `list` shows it. Text that happens
+        // to contain it too makes the query ambiguous rather than one or the
+        // other winning.
+        let hashed: Vec<usize> = if is_hash_prefix(query) {
+            let prefix = query.to_ascii_lowercase();
+            (0..self.tasks.len())
+                .filter(|&i| self.tasks[i].id != 0 && self.tasks[i].hash().starts_with(&prefix))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let mut matches: Vec<usize> = (0..texts.len())
+            .filter(|&i| hashed.contains(&i) || search::contains(&texts[i], &needle))
+            .collect();
+        if matches.is_empty() {
+            matches = (0..texts.len())
+                .filter(|&i| search::is_subsequence(&needle, &texts[i]))
+                .collect();
+        }
+
+        match matches.as_slice() {
+            [] => Err(Error::NoMatch(query.to_string())),
+            [index] => Ok(*index),
+            _ => Err(Error::Ambiguous {
+                query: query.to_string(),
+                matches: matches
+                    .iter()
+                    .map(|&i| (i, self.tasks[i].text.clone()))
+                    .collect(),
+            }),
+        }
+    }
+
+    fn resolve_number(&self, number: usize) -> Result<usize> {
+        if number == 0 {
+            return Err(Error::InvalidTaskNumber);
+        }
+        if let Some(listing) = &self.listing {
+            // Numbers shown before anything changed are still positions.
+            let changed = listing.version.is_none() || listing.version != *self.version.borrow();
+            if let Some(id) = listing.id(number).filter(|_| changed) {
+                let index = self.position(id).ok_or(Error::StaleNumber(number))?;
+                if index + 1 != number {
+                    log::info(
+                        "store",
+                        format_args!("task {} as listed is now task {}", number, index + 1),
+                    );
+                }
+                return Ok(index);
+            }
+        }
+        if number > self.tasks.len() {
+            return Err(Error::NoSuchNumber(number));
+        }
+        Ok(number - 1)
+    }
+
+    /// Finds a pending task that `text` would duplicate: one with the same
+    /// text once case, punctuation and spacing are ignored, or nearly the
+    /// same, allowing about one typo per ten characters.
+    pub fn find_duplicate(&self, text: &str) -> Option<usize> {
+        let wanted = normalize_text(text);
+        if wanted.is_empty() {
+            return None;
+        }
+        let allowed = wanted.chars().count() / 10;
+        self.tasks.iter().position(|task| {
+            !task.completed && edit_distance(&normalize_text(&task.text), &wanted) <= allowed
+        })
+    }
+
+    fn task_mut(&mut self, index: usize) -> Result<&mut Task> {
+        self.tasks
+            .get_mut(index)
+            .ok_or(Error::NoSuchNumber(index + 1))
+    }
+}
+
+/// Fills in the ids that are zero with fresh ones, in order, and returns
+/// whether there were any.
+///
+/// Lists are given ids this way wherever they are read, so that the same list
+/// always ends up with the same ids.
+pub fn assign_ids(ids: &mut [u64]) -> bool {
+    let next = ids.iter().max().map_or(1, |max| max + 1);
+    let mut assigned = false;
+    for (id, fresh) in ids.iter_mut().filter(|id| **id == 0).zip(next..) {
+        *id = fresh;
+        assigned = true;
+    }
+    assigned
+}
+
+/// Records when `task` was completed: now if it just was, as before if it
+/// already was, and not at all if it isn't. A task just completed also stops
+/// being timed.
+fn stamp_completion(task: &mut Task, before: Option<&Task>) {
+    task.completed_at = match before {
+        _ if !task.completed => None,
+        Some(before) if before.completed => before.completed_at.or(task.completed_at),
+        _ => task.completed_at.or_else(|| Some(DateTime::now())),
+    };
+    if task.completed && before.is_none_or(|before| !before.completed) {
+        task.stop_timer(DateTime::now());
+    }
+}
+
+/// Whether `query` could be the start of a task's hash: at least three hex
+/// digits, not all of them decimal, which would make it a number.
+fn is_hash_prefix(query: &str) -> bool {
+    query.len() >= 3
+        && query.chars().all(|c| c.is_ascii_hexdigit())
+        && !query.chars().all(|c| c.is_ascii_digit())
+}
+
+/// The id for a task added to `tasks`.
+fn next_id(tasks: &[Task]) -> u64 {
+    tasks.iter().map(|task| task.id).max().unwrap_or(0) + 1
+}
+
+/// Finds `task` in `tasks`, preferring `index` where it used to be.
+fn find(tasks: &[Task], index: usize, task: &Task) -> Option<usize> {
+    if tasks.get(index) == Some(task) {
+        return Some(index);
+    }
+    tasks.iter().position(|t| t == task)
+}
+
+/// Folds `text` and reduces it to its words, separated by single spaces.
+fn normalize_text(text: &str) -> String {
+    search::fold(text)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The number of characters to insert, remove or replace to turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    // The distances from a prefix of `a` to each prefix of `b`.
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let replaced = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = replaced.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
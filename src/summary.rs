@@ -0,0 +1,192 @@
+// The few numbers a shell prompt or status bar shows about a list: how many
+// tasks are pending and how many of those are overdue or due today.
+//
+// They are counted in one pass over the backend, without building a store or
+// its index, so that a prompt redrawn after every command stays quick even
+// with a large list. Tasks parked as someday/maybe don't count as pending.
+//
+// `prompt` prints them tersely for a shell prompt, and `segment` in the form
+// a prompt framework expects: plain text for a Starship custom module, or a
+// powerline-go plugin's JSON list of segments. `tmux-status` spells them out
+// with tmux's own `#[...]` color markup for the status line, and `export
+// --waybar` gives a Waybar custom module its JSON, with the tasks due by
+// today in the tooltip.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde_json::{json, Value};
+
+use crate::backend::StorageBackend;
+use crate::date::Date;
+use crate::i18n::tr;
+use crate::task::Task;
+use crate::Result;
+
+/// What `segment` prints for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Segment {
+    /// The text alone, for a Starship `[custom]` module; nothing hides it.
+    Starship,
+    /// A powerline-go plugin's JSON list of segments; an empty list hides it.
+    Powerline,
+}
+
+impl FromStr for Segment {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "starship" => Ok(Segment::Starship),
+            "powerline" | "powerline-go" => Ok(Segment::Powerline),
+            _ => Err(tr("segment-format-invalid", &[("input", &s.trim())])),
+        }
+    }
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Segment::Starship => "starship",
+            Segment::Powerline => "powerline",
+        };
+        f.write_str(name)
+    }
+}
+
+/// How many tasks are pending, overdue and due today.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Summary {
+    pub pending: usize,
+    /// Pending tasks due before today.
+    pub overdue: usize,
+    /// Pending tasks due today.
+    pub today: usize,
+}
+
+impl Summary {
+    /// Counts the tasks in `backend` as of `today`.
+    pub fn read(backend: &dyn StorageBackend, today: Date) -> Result<Summary> {
+        Summary::read_with(backend, today, &mut |_, _| {})
+    }
+
+    /// Counts the tasks like [`Summary::read`], calling `due` with each
+    /// pending task that is overdue or due today, and its index.
+    pub fn read_with(
+        backend: &dyn StorageBackend,
+        today: Date,
+        due: &mut dyn FnMut(usize, Task),
+    ) -> Result<Summary> {
+        let mut summary = Summary::default();
+        backend.scan(&mut |index, task| {
+            if task.completed || task.someday.is_some() {
+                return;
+            }
+            summary.pending += 1;
+            match task.due {
+                Some(_) if task.is_overdue(today) => summary.overdue += 1,
+                Some(date) if date == today => summary.today += 1,
+                _ => return,
+            }
+            due(index, task);
+        })?;
+        Ok(summary)
+    }
+
+    /// The shortest form: `3!/7` for 3 overdue out of 7 pending, just `7`
+    /// when nothing is overdue, and nothing at all when nothing is pending.
+    pub fn terse(&self) -> String {
+        match (self.overdue, self.pending) {
+            (_, 0) => String::new(),
+            (0, pending) => pending.to_string(),
+            (overdue, pending) => format!("{}!/{}", overdue, pending),
+        }
+    }
+
+    /// Whether a segment should appear: at least `min_pending` tasks pending
+    /// and `min_overdue` of them overdue. Nothing pending never shows.
+    pub fn shows(&self, min_pending: usize, min_overdue: usize) -> bool {
+        self.pending > 0 && self.pending >= min_pending && self.overdue >= min_overdue
+    }
+
+    /// The segment for `format`, or what hides it when it shouldn't `show`.
+    /// Powerline segments are red while anything is overdue and blue
+    /// otherwise, in the 256-color numbers powerline-go uses.
+    pub fn segment(&self, format: Segment, show: bool) -> String {
+        match format {
+            Segment::Starship if show => self.terse(),
+            Segment::Starship => String::new(),
+            Segment::Powerline if show => {
+                let background = if self.overdue > 0 { 160 } else { 31 };
+                json!([{
+                    "name": "todo",
+                    "content": self.terse(),
+                    "foreground": 15,
+                    "background": background,
+                }])
+                .to_string()
+            }
+            Segment::Powerline => "[]".to_string(),
+        }
+    }
+
+    /// The counts in words, e.g. `2 overdue · 1 due today · 7 pending`,
+    /// leaving out those that are zero; empty when nothing is pending.
+    /// With `color`, overdue is red and due today yellow, in tmux markup.
+    pub fn tmux(&self, color: bool) -> String {
+        let mut parts = Vec::new();
+        for (count, message, style) in [
+            (self.overdue, "bar-overdue", "fg=red,bold"),
+            (self.today, "bar-today", "fg=yellow"),
+            (self.pending, "bar-pending", ""),
+        ] {
+            if count == 0 {
+                continue;
+            }
+            let text = tr(message, &[("count", &count)]);
+            if color && !style.is_empty() {
+                parts.push(format!("#[{}]{}#[default]", style, text));
+            } else {
+                parts.push(text);
+            }
+        }
+        parts.join(" · ")
+    }
+
+    /// What a Waybar custom module with `"return-type": "json"` reads: the
+    /// terse counts as `text` (empty, hiding the module, when nothing is
+    /// pending), the counts in words and the `due` tasks, soonest first, as
+    /// `tooltip`, and a `class` of `overdue`, `today`, `pending` or `empty`
+    /// to style it by.
+    pub fn waybar(&self, due: &[(usize, Task)]) -> Value {
+        let mut tooltip = self.tmux(false);
+        let mut due: Vec<&(usize, Task)> = due.iter().collect();
+        due.sort_by_key(|(index, task)| (task.due, *index));
+        for (index, task) in due {
+            let date = task.due.map(|d| d.to_string()).unwrap_or_default();
+            tooltip.push_str(&format!("\n{}. {} ({})", index + 1, task.text, date));
+        }
+        let class = if self.overdue > 0 {
+            "overdue"
+        } else if self.today > 0 {
+            "today"
+        } else if self.pending > 0 {
+            "pending"
+        } else {
+            "empty"
+        };
+        json!({
+            "text": self.terse(),
+            "tooltip": escape_markup(&tooltip),
+            "class": class,
+        })
+    }
+}
+
+/// `text` with the characters Pango markup gives meaning to escaped, since
+/// Waybar reads tooltips as markup.
+fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
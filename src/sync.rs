@@ -0,0 +1,500 @@
+// Keeping copies of a list on several machines in step over the local
+// network, without a server in between.
+//
+// Each side remembers, for each peer, the list as it was when they last
+// synced: the base. Syncing fetches the peer's list and merges it with ours
+// against the base. A task changed on one side only takes that side's
+// version; one changed on both takes each field from the side that changed
+// it, ours where both changed the same field. A task removed on one side and
+// untouched on the other is removed; one removed on one side but changed on
+// the other is kept. Tasks are matched by id, and by text too, so two tasks
+// added with the same id on either side are both kept, the peer's with a new
+// id. Before the first sync there is no base, and a task on both sides with
+// the same id and text is kept as it is here.
+//
+// The merged list is saved here, then sent to the peer, which saves it unless
+// its list changed after it was fetched; both remember it as the new base.
+//
+//   GET /sync    our name, the list and its version
+//   PUT /sync    the merged list, with the version it was merged from
+//
+// Requests name the instance making them in `X-Todo-Peer` and the list in
+// `X-Todo-List`; a peer only syncs the list it was started for.
+
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+
+use crate::audit;
+use crate::config::{config_dir, Config};
+use crate::error::{Error, Result};
+use crate::http::{self, Request, Response};
+use crate::i18n::tr;
+use crate::log;
+use crate::sha256;
+use crate::store::TaskStore;
+use crate::task::Task;
+
+pub const PEER_HEADER: &str = "X-Todo-Peer";
+pub const LIST_HEADER: &str = "X-Todo-List";
+
+/// A list merged from ours and a peer's.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Merge {
+    pub tasks: Vec<Task>,
+    /// How many tasks had the same field changed on both sides.
+    pub conflicts: usize,
+}
+
+/// What a sync with a peer changed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Synced {
+    pub peer: String,
+    /// How many tasks were added, changed or removed here, and at the peer.
+    pub here: usize,
+    pub there: usize,
+    pub conflicts: usize,
+}
+
+/// The name this instance goes by: the user and the machine.
+pub fn instance_name() -> String {
+    let host = ["HOSTNAME", "COMPUTERNAME"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .chain(fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .find(|name| !name.is_empty())
+        .unwrap_or_else(|| "localhost".to_string());
+    format!("{}@{}", audit::user(), host)
+}
+
+/// Merges `local` and `remote`, which were both `base` when last synced.
+pub fn merge(base: Option<&[Task]>, local: &[Task], remote: &[Task]) -> Merge {
+    let by_id = |tasks: &[Task]| -> HashMap<u64, Task> {
+        tasks.iter().map(|task| (task.id, task.clone())).collect()
+    };
+    let base = base.map(by_id).unwrap_or_default();
+    let theirs_by_id = by_id(remote);
+    let mut matched = HashSet::new();
+    let mut tasks = Vec::new();
+    let mut conflicts = 0;
+
+    for ours in local {
+        let before = base.get(&ours.id);
+        let theirs = theirs_by_id
+            .get(&ours.id)
+            .filter(|theirs| before.is_some() || theirs.text == ours.text);
+        match (before, theirs) {
+            (Some(before), Some(theirs)) => {
+                matched.insert(ours.id);
+                let (task, conflict) = merge_task(before, ours, theirs);
+                conflicts += conflict as usize;
+                tasks.push(task);
+            }
+            // Removed there; keep it only if it changed here since.
+            (Some(before), None) if ours == before => {}
+            (None, Some(theirs)) => {
+                matched.insert(ours.id);
+                conflicts += (ours != theirs) as usize;
+                tasks.push(ours.clone());
+            }
+            _ => tasks.push(ours.clone()),
+        }
+    }
+
+    let ours: HashSet<u64> = local.iter().map(|task| task.id).collect();
+    let mut next = (local.iter().chain(remote).chain(base.values()))
+        .map(|task| task.id)
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let mut renumbered = HashMap::new();
+    let mut added = Vec::new();
+    for theirs in remote {
+        if matched.contains(&theirs.id) {
+            continue;
+        }
+        // Removed here and untouched there.
+        if !ours.contains(&theirs.id) && base.get(&theirs.id) == Some(theirs) {
+            continue;
+        }
+        let mut task = theirs.clone();
+        if ours.contains(&task.id) {
+            renumbered.insert(task.id, next);
+            task.id = next;
+            next += 1;
+        }
+        added.push(task);
+    }
+    for task in &mut added {
+        for id in &mut task.depends {
+            if let Some(&new) = renumbered.get(id) {
+                *id = new;
+            }
+        }
+    }
+    tasks.extend(added);
+    Merge { tasks, conflicts }
+}
+
+/// A task changed on both sides since `before`, field by field, keeping
+/// ours where both changed the same field. Returns whether they did.
+pub fn merge_task(before: &Task, ours: &Task, theirs: &Task) -> (Task, bool) {
+    if ours == theirs || theirs == before {
+        return (ours.clone(), false);
+    }
+    if ours == before {
+        return (theirs.clone(), false);
+    }
+    let fields = |task: &Task| match serde_json::to_value(task) {
+        Ok(Value::Object(fields)) => fields,
+        _ => unreachable!("tasks serialize to objects"),
+    };
+    let (before, mut merged, theirs) = (fields(before), fields(ours), fields(theirs));
+    let ours = merged.clone();
+    let mut conflict = false;
+    let keys: HashSet<&String> = before
+        .keys()
+        .chain(ours.keys())
+        .chain(theirs.keys())
+        .collect();
+    for key in keys {
+        let (was, mine, their) = (before.get(key), ours.get(key), theirs.get(key));
+        if mine == was && their != was {
+            match their {
+                Some(value) => merged.insert(key.clone(), value.clone()),
+                None => merged.remove(key),
+            };
+        } else if mine != was && their != was && mine != their {
+            conflict = true;
+        }
+    }
+    match serde_json::from_value(Value::Object(merged)) {
+        Ok(task) => (task, conflict),
+        Err(_) => (
+            serde_json::from_value(Value::Object(ours)).expect("it was a task"),
+            true,
+        ),
+    }
+}
+
+/// Turns the tasks in `store` into `merged`, keeping their ids. Returns how
+/// many tasks were added, changed or removed.
+pub fn apply(store: &mut TaskStore, merged: &[Task]) -> Result<usize> {
+    let wanted: HashMap<u64, &Task> = merged.iter().map(|task| (task.id, task)).collect();
+    let mut changes = 0;
+    for index in (0..store.tasks().len()).rev() {
+        let task = &store.tasks()[index];
+        match wanted.get(&task.id) {
+            None => {
+                store.remove(index)?;
+                changes += 1;
+            }
+            Some(&new) if new != task => {
+                store.replace(index, new.clone())?;
+                changes += 1;
+            }
+            Some(_) => {}
+        }
+    }
+    for task in merged {
+        if store.position(task.id).is_none() {
+            store.insert(task.clone())?;
+            changes += 1;
+        }
+    }
+    Ok(changes)
+}
+
+/// How many tasks differ between `a` and `b`, matched by id.
+pub fn differences(a: &[Task], b: &[Task]) -> usize {
+    let by_id: HashMap<u64, &Task> = b.iter().map(|task| (task.id, task)).collect();
+    let changed = a
+        .iter()
+        .filter(|task| by_id.get(&task.id) != Some(task))
+        .count();
+    let ids: HashSet<u64> = a.iter().map(|task| task.id).collect();
+    changed + b.iter().filter(|task| !ids.contains(&task.id)).count()
+}
+
+/// Where the base for syncing `list` with `peer` is kept.
+pub fn base_path(peer: &str, list: Option<&str>) -> PathBuf {
+    let name: String = format!("{}.{}.json", peer, list.unwrap_or("main"))
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || "@.-_".contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    config_dir().join("sync").join(name)
+}
+
+fn load_base(path: &Path) -> Result<Option<Vec<Task>>> {
+    match fs::read_to_string(path) {
+        Ok(data) => serde_json::from_str(&data)
+            .map(Some)
+            .map_err(|e| Error::Parse {
+                location: path.display().to_string(),
+                line: e.line(),
+                column: e.column(),
+                message: e.to_string(),
+            }),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn save_base(path: &Path, tasks: &[Task]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_string(tasks).expect("Failed to serialize data to JSON.");
+    fs::write(path, json)?;
+    Ok(())
+}
+
+/// The largest sync a peer accepts: the whole list, which may be far bigger
+/// than other requests.
+pub const MAX_BODY: usize = 64 * 1024 * 1024;
+
+/// Syncs our list with the instance listening at `addr`.
+pub fn sync_with(config: &Config, name: &str, addr: &str, token: Option<&str>) -> Result<Synced> {
+    let url = format!("http://{}/sync", addr);
+    let list = config.list.as_deref().unwrap_or_default();
+    let authorization = token.map(|token| format!("Bearer {}", token));
+    let mut headers = vec![
+        (PEER_HEADER, name),
+        (LIST_HEADER, list),
+        ("Content-Type", "application/json"),
+        ("User-Agent", "todo_cli"),
+    ];
+    if let Some(authorization) = &authorization {
+        headers.push(("Authorization", authorization));
+    }
+    let request = |method: &str, body: &[u8]| -> Result<Value> {
+        let response = config
+            .http
+            .send(method, &url, &headers, body)
+            .map_err(|e| Error::Io(io::Error::new(e.kind(), format!("{}: {}", url, e))))?;
+        let body: Value = serde_json::from_slice(&response.body).unwrap_or(Value::Null);
+        if response.status != 200 {
+            let why = body["error"]
+                .as_str()
+                .unwrap_or(http::reason(response.status));
+            let message = format!("{} {} answered {}: {}", method, url, response.status, why);
+            return Err(match response.status {
+                409 => Error::Conflict(message),
+                _ => Error::Io(io::Error::other(message)),
+            });
+        }
+        Ok(body)
+    };
+
+    let fetched = request("GET", b"")?;
+    let peer = fetched["peer"].as_str().unwrap_or(addr).to_string();
+    let remote: Vec<Task> = serde_json::from_value(fetched["tasks"].clone())
+        .map_err(|e| Error::Io(io::Error::other(format!("{}: {}", url, e))))?;
+    let path = base_path(&peer, config.list.as_deref());
+    let base = load_base(&path)?;
+
+    let mut store = config.open_store()?;
+    store.audit_as(format!("sync:{}", peer));
+    let merged = merge(base.as_deref(), store.tasks(), &remote);
+    let here = apply(&mut store, &merged.tasks)?;
+    let merged_tasks = store.tasks().to_vec();
+    let there = differences(&remote, &merged_tasks);
+    // Sent even when nothing changed there, so the peer's base moves on too.
+    // Nothing is saved here until the peer has taken it: a merge it refused
+    // would have no base next time, and be merged again from scratch.
+    let body = json!({ "version": fetched["version"], "tasks": merged_tasks });
+    request("PUT", body.to_string().as_bytes())?;
+    store.save()?;
+    save_base(&path, &merged_tasks)?;
+    log::info(
+        "sync",
+        format_args!(
+            "synced with {}: {} changes here, {} there",
+            peer, here, there
+        ),
+    );
+    Ok(Synced {
+        peer,
+        here,
+        there,
+        conflicts: merged.conflicts,
+    })
+}
+
+/// Answers a sync request from a peer, as the instance `name`. Requests
+/// must carry `token`; there is none only when `sync --insecure` lets anyone
+/// sync. A `PUT` that was saved is described alongside the response.
+pub fn handle(
+    config: &Config,
+    name: &str,
+    token: Option<&str>,
+    request: &Request,
+) -> (Response, Option<Synced>) {
+    if let Some(token) = token {
+        let expected = format!("Bearer {}", token);
+        let given = request.header("authorization").unwrap_or_default();
+        if !sha256::constant_time_eq(given.as_bytes(), expected.as_bytes()) {
+            let response = Response::error(401, "missing or invalid token")
+                .with_header("WWW-Authenticate", "Bearer");
+            return (response, None);
+        }
+    }
+    if request.path != ["sync"] {
+        return (Response::error(404, "not found"), None);
+    }
+    let Some(peer) = request
+        .header(&PEER_HEADER.to_lowercase())
+        .filter(|peer| !peer.is_empty())
+    else {
+        let message = format!("missing {} header", PEER_HEADER);
+        return (Response::error(400, &message), None);
+    };
+    let list = request
+        .header(&LIST_HEADER.to_lowercase())
+        .filter(|list| !list.is_empty());
+    if list != config.list.as_deref() {
+        let message = tr(
+            "sync-other-list",
+            &[("list", &config.list.as_deref().unwrap_or("main"))],
+        );
+        return (Response::error(409, &message), None);
+    }
+
+    let result = match request.method.as_str() {
+        "GET" => config.open_store().map(|store| {
+            let body = json!({ "peer": name, "version": store.version(), "tasks": store.tasks() });
+            (Response::json(200, &body), None)
+        }),
+        "PUT" => receive(config, peer, &request.body).map(|synced| {
+            let response = Response::json(200, &json!({ "changed": synced.here }));
+            (response, Some(synced))
+        }),
+        _ => Ok((
+            Response::error(405, "method not allowed").with_header("Allow", "GET, PUT"),
+            None,
+        )),
+    };
+    result.unwrap_or_else(|e| {
+        let status = match e {
+            Error::Invalid(_) => 400,
+            Error::Conflict(_) => 409,
+            Error::ReadOnly(_) => 403,
+            _ => 500,
+        };
+        (Response::error(status, &e.to_string()), None)
+    })
+}
+
+/// Saves the merged list a peer sent, unless ours changed since it was fetched.
+fn receive(config: &Config, peer: &str, body: &[u8]) -> Result<Synced> {
+    let body: Value = serde_json::from_slice(body).map_err(|e| Error::Invalid(e.to_string()))?;
+    let merged: Vec<Task> =
+        serde_json::from_value(body["tasks"].clone()).map_err(|e| Error::Invalid(e.to_string()))?;
+    let mut store = config.open_store()?;
+    if store.version().as_deref() != body["version"].as_str() {
+        return Err(Error::Conflict(store.location()));
+    }
+    store.audit_as(format!("sync:{}", peer));
+    let here = apply(&mut store, &merged)?;
+    store.save()?;
+    save_base(&base_path(peer, config.list.as_deref()), store.tasks())?;
+    Ok(Synced {
+        peer: peer.to_string(),
+        here,
+        there: 0,
+        conflicts: 0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::Date;
+
+    fn task(id: u64, text: &str) -> Task {
+        Task {
+            id,
+            ..Task::new(text)
+        }
+    }
+
+    #[test]
+    fn merges_changes_to_different_fields() {
+        let before = task(1, "call the bank");
+        let ours = Task {
+            completed: true,
+            ..before.clone()
+        };
+        let theirs = Task {
+            due: Date::new(2026, 10, 20),
+            ..before.clone()
+        };
+        let (merged, conflict) = merge_task(&before, &ours, &theirs);
+        assert!(!conflict);
+        assert!(merged.completed);
+        assert_eq!(merged.due, Date::new(2026, 10, 20));
+    }
+
+    #[test]
+    fn keeps_ours_when_both_change_a_field() {
+        let before = task(1, "call the bank");
+        let ours = task(1, "call the bank today");
+        let theirs = Task {
+            completed: true,
+            ..task(1, "call the bank at noon")
+        };
+        let (merged, conflict) = merge_task(&before, &ours, &theirs);
+        assert!(conflict);
+        assert_eq!(merged.text, "call the bank today");
+        assert!(merged.completed);
+        // A change on one side only is taken as it is.
+        assert_eq!(merge_task(&before, &before, &ours), (ours.clone(), false));
+        assert_eq!(merge_task(&before, &ours, &before), (ours.clone(), false));
+    }
+
+    #[test]
+    fn follows_additions_and_removals_since_the_base() {
+        let base = [task(1, "a"), task(2, "b"), task(3, "c")];
+        // Here: 2 removed, 3 changed. There: 3 removed, 4 added.
+        let local = [task(1, "a"), task(3, "c!")];
+        let remote = [task(1, "a"), task(2, "b"), task(4, "d")];
+        let merged = merge(Some(&base), &local, &remote);
+        assert_eq!(merged.conflicts, 0);
+        let texts: Vec<&str> = merged.tasks.iter().map(|t| t.text.as_str()).collect();
+        // 3 changed here since, so its removal there loses.
+        assert_eq!(texts, ["a", "c!", "d"]);
+    }
+
+    #[test]
+    fn renumbers_their_tasks_whose_ids_are_taken() {
+        // Never synced: the same id with other text is a different task.
+        let local = [task(1, "a"), task(2, "b")];
+        let remote = [
+            task(1, "a"),
+            task(2, "x"),
+            Task {
+                depends: vec![2],
+                ..task(3, "y")
+            },
+        ];
+        let merged = merge(None, &local, &remote);
+        assert_eq!(merged.conflicts, 0);
+        let ids: Vec<(u64, &str)> = (merged.tasks.iter())
+            .map(|t| (t.id, t.text.as_str()))
+            .collect();
+        assert_eq!(ids, [(1, "a"), (2, "b"), (4, "x"), (3, "y")]);
+        // Their dependency follows the task it named.
+        assert_eq!(merged.tasks[3].depends, [4]);
+    }
+}
@@ -0,0 +1,389 @@
+// The task model: what a single item on the to-do list looks like.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::date::{Date, DateTime};
+use crate::habit::Habit;
+use crate::i18n::tr;
+use crate::sha256;
+use crate::timetrack::Session;
+use crate::tz;
+
+/// How many characters of a task's hash the list shows.
+pub const SHORT_HASH: usize = 5;
+
+/// The status of a pending task that hasn't moved along the workflow.
+pub const TODO: &str = "todo";
+/// The status of a completed task.
+pub const DONE: &str = "done";
+/// The status of a task dropped rather than done. It counts as completed,
+/// but not as done: no `on-done` hook, and no square on the heatmap.
+pub const CANCELLED: &str = "cancelled";
+
+/// A single item on the to-do list.
+///
+/// The `#[derive(...)]` attribute automatically implements traits for our struct:
+/// `Serialize`/`Deserialize` for the JSON file, `PartialEq` so we can tell
+/// whether anything changed, and `Default` to give an empty task to fill in.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct Task {
+    /// Identifies the task for good: unlike its number, it doesn't change
+    /// when tasks before it are removed. Zero until a store assigns one.
+    #[serde(default, skip_serializing_if = "is_unset")]
+    pub id: u64,
+    /// What needs doing. Stored as `task` in the file, as it always has been.
+    #[serde(rename = "task")]
+    pub text: String,
+    pub completed: bool,
+    // The fields below are optional. Older files don't have them, so they fall
+    // back to their defaults and are left out of the file when unset.
+    /// When the task was completed, if it is. Tasks completed before this
+    /// was recorded don't have it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime>,
+    /// When the task was added. Tasks added before this was recorded don't
+    /// have it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime>,
+    /// When the task was last edited, if it has been since this was
+    /// recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_at: Option<DateTime>,
+    /// Where the task is in the workflow when that is neither `todo` nor
+    /// `done`, e.g. `doing` or `cancelled`; see [`Task::status`]. A cancelled
+    /// task is also completed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// The user who added the task, on a list several people share.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<Date>,
+    /// The time the task is due by, in UTC, if it is due at a time of day
+    /// rather than on the day; `due` is then the local date of it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_at: Option<DateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// The project the task is part of.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    /// Where or with what the task can be done, e.g. `computer` or `phone`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contexts: Vec<String>,
+    /// Set while the task is parked as someday/maybe, out of the normal
+    /// list: the day it was parked, or last looked at in a review.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub someday: Option<Date>,
+    /// Where the task comes in the order arranged with `move`, `up` and
+    /// `down`, from 1; see `order`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rank: Option<u64>,
+    /// The text and notes, encrypted, while the task is secret; `text` is
+    /// then only a mask. See `secret`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    /// Marked with `pin` as one to keep in sight.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub pinned: bool,
+    /// Who the task was handed to, if it is waiting on someone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waiting: Option<Waiting>,
+    /// The ids of the tasks that have to be completed first. The task is
+    /// blocked while any of them is pending; see `deps`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends: Vec<u64>,
+    /// Set for a habit: a task done again and again on a schedule, rather
+    /// than completed once.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub habit: Option<Habit>,
+    /// Small steps within the task, ticked off one by one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checklist: Vec<CheckItem>,
+    /// When to remind the user of the task, earliest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reminders: Vec<DateTime>,
+    /// How long the task is expected to take, in minutes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<u32>,
+    /// The time spent on the task, oldest first. Only the last session can
+    /// still be running.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sessions: Vec<Session>,
+    /// What people sharing the list said about the task, oldest first.
+    /// Unlike the notes, comments are only ever added to.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub comments: Vec<Comment>,
+}
+
+impl Task {
+    /// Creates a pending task with the given text and no other details.
+    pub fn new(text: impl Into<String>) -> Task {
+        Task {
+            text: text.into(),
+            ..Task::default()
+        }
+    }
+
+    /// A name for the task that stays the same however it is edited, moved
+    /// or filtered, like a commit's in git: a hash of its id and when it was
+    /// added, in hex. Any unique start of it finds the task.
+    pub fn hash(&self) -> String {
+        let created = self.created_at.map(|at| at.to_string()).unwrap_or_default();
+        let seed = format!("{}:{}", self.id, created);
+        sha256::hex(&sha256::digest(seed.as_bytes()))
+    }
+
+    /// Everything written on the task that a search looks through: its
+    /// text, its notes and its comments.
+    pub fn written(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.text.as_str())
+            .chain(self.notes.as_deref())
+            .chain(self.comments.iter().map(|c| c.text.as_str()))
+    }
+
+    /// Where the task is in the workflow: `todo` or `done` unless it has a
+    /// status of its own.
+    pub fn status(&self) -> &str {
+        match &self.status {
+            Some(status) => status,
+            None if self.completed => DONE,
+            None => TODO,
+        }
+    }
+
+    /// Moves the task to `status`, completing it for `done` or `cancelled`
+    /// and reopening it for any other.
+    pub fn set_status(&mut self, status: &str) {
+        self.completed = status == DONE || status == CANCELLED;
+        self.status = (status != TODO && status != DONE).then(|| status.to_string());
+    }
+
+    /// Makes the task due on a date, and by a UTC time on it if one is
+    /// given, or not due at all.
+    pub fn set_due(&mut self, due: Option<(Date, Option<DateTime>)>) {
+        self.due = due.map(|(date, _)| date);
+        self.due_at = due.and_then(|(_, at)| at);
+    }
+
+    /// Whether the task is past due on `today`: the day is over, or, for a
+    /// task due by a time, it is today and the time has passed.
+    pub fn is_overdue(&self, today: Date) -> bool {
+        match (self.due, self.due_at) {
+            (_, Some(at)) => {
+                let due = tz::local(at).date();
+                due < today || (due == today && at < DateTime::now())
+            }
+            (Some(due), None) => due < today,
+            (None, None) => false,
+        }
+    }
+
+    /// When the task is due, for people to read: the date in `date_format`,
+    /// followed by the local time if it is due by a time.
+    pub fn due_label(&self, date_format: &str) -> Option<String> {
+        match self.due_at {
+            Some(at) => {
+                let at = tz::local(at);
+                Some(format!(
+                    "{} {:02}:{:02}",
+                    at.date().format(date_format),
+                    at.hour(),
+                    at.minute()
+                ))
+            }
+            None => self.due.map(|due| due.format(date_format)),
+        }
+    }
+
+    /// Swaps the tag `from` for `to`, where `from` was, unless the task has
+    /// `to` already. Returns whether the task had `from`.
+    pub fn rename_tag(&mut self, from: &str, to: &str) -> bool {
+        let Some(at) = self.tags.iter().position(|tag| tag == from) else {
+            return false;
+        };
+        if self.tags.iter().any(|tag| tag == to) {
+            self.tags.remove(at);
+        } else {
+            self.tags[at] = to.to_string();
+        }
+        true
+    }
+
+    /// Whether the task was cancelled rather than done.
+    pub fn is_cancelled(&self) -> bool {
+        self.status.as_deref() == Some(CANCELLED)
+    }
+
+    /// How many minutes have been spent on the task by `now`.
+    pub fn tracked(&self, now: DateTime) -> i64 {
+        self.sessions
+            .iter()
+            .map(|session| session.minutes(now))
+            .sum()
+    }
+
+    /// When the task's timer was started, if it is running.
+    pub fn timer(&self) -> Option<DateTime> {
+        self.sessions
+            .last()
+            .filter(|session| session.end.is_none())
+            .map(|session| session.start)
+    }
+
+    /// Stops the task's timer at `now`, if it is running; a session that
+    /// didn't last a minute isn't kept. Returns whether it was running.
+    pub fn stop_timer(&mut self, now: DateTime) -> bool {
+        let Some(session) = self.sessions.last_mut().filter(|s| s.end.is_none()) else {
+            return false;
+        };
+        session.end = Some(now.max(session.start));
+        if session.minutes(now) == 0 {
+            self.sessions.pop();
+        }
+        true
+    }
+
+    /// How many whole days the task has existed by `today`; `None` when it
+    /// isn't known when it was added.
+    pub fn age(&self, today: Date) -> Option<i64> {
+        self.created_at.map(|at| at.date().days_until(today))
+    }
+
+    /// How many checklist items are done, and how many there are; `None`
+    /// without a checklist.
+    pub fn progress(&self) -> Option<(usize, usize)> {
+        let done = self.checklist.iter().filter(|item| item.done).count();
+        (!self.checklist.is_empty()).then_some((done, self.checklist.len()))
+    }
+}
+
+fn is_unset(id: &u64) -> bool {
+    *id == 0
+}
+
+fn is_false(flag: &bool) -> bool {
+    !flag
+}
+
+/// One step on a task's checklist.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CheckItem {
+    pub text: String,
+    #[serde(default)]
+    pub done: bool,
+}
+
+impl CheckItem {
+    /// Reads an item as written by `Display`: `[x] text` or `[ ] text`, or
+    /// just the text for one that isn't done.
+    pub fn parse(line: &str) -> CheckItem {
+        let line = line.trim();
+        let (done, text) = match line.get(..3).map(str::to_lowercase).as_deref() {
+            Some("[x]") => (true, &line[3..]),
+            Some("[ ]") => (false, &line[3..]),
+            _ => (false, line),
+        };
+        CheckItem {
+            text: text.trim().to_string(),
+            done,
+        }
+    }
+}
+
+impl fmt::Display for CheckItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mark = if self.done { "x" } else { " " };
+        write!(f, "[{}] {}", mark, self.text)
+    }
+}
+
+/// A remark left on a task: who wrote it, when, and what it says.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Comment {
+    pub author: String,
+    pub at: DateTime,
+    pub text: String,
+}
+
+/// Who a task is waiting on, and since when.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Waiting {
+    /// The person the task was delegated to, or whatever it waits for.
+    #[serde(rename = "for")]
+    pub person: String,
+    pub since: Date,
+}
+
+impl Waiting {
+    /// How many days the task has been waiting as of `today`.
+    pub fn days(&self, today: Date) -> i64 {
+        self.since.days_until(today)
+    }
+}
+
+/// How important a task is.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    Medium,
+    High,
+}
+
+impl Priority {
+    /// The next priority up; high stays high. A task without a priority
+    /// goes to low.
+    pub fn raised(priority: Option<Priority>) -> Priority {
+        match priority {
+            None => Priority::Low,
+            Some(Priority::Low) => Priority::Medium,
+            Some(Priority::Medium | Priority::High) => Priority::High,
+        }
+    }
+}
+
+impl FromStr for Priority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "l" | "low" => Ok(Priority::Low),
+            "m" | "med" | "medium" => Ok(Priority::Medium),
+            "h" | "high" => Ok(Priority::High),
+            _ => Err(tr("priority-invalid", &[("input", &s.trim())])),
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Priority::Low => "low",
+            Priority::Medium => "medium",
+            Priority::High => "high",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Tags are stored lowercase and without a leading `#`.
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim().trim_start_matches('#').to_lowercase()
+}
+
+/// Projects are stored lowercase and without a leading `+`.
+pub fn normalize_project(project: &str) -> String {
+    project.trim().trim_start_matches('+').to_lowercase()
+}
+
+/// Contexts are stored lowercase and without a leading `@`.
+pub fn normalize_context(context: &str) -> String {
+    context.trim().trim_start_matches('@').to_lowercase()
+}
@@ -0,0 +1,206 @@
+// The Telegram bot run by `todo_cli telegram-bot`, for adding and ticking
+// off tasks from a phone.
+//
+// The bot asks Telegram for new messages with long polling (getUpdates), so
+// it needs no public address, and answers each one:
+//
+//   /add TEXT    add a task; quick-add words work, e.g. `/add milk #shopping`
+//   /list        the pending tasks, numbered as `list` numbers them
+//   /done TASK   complete a task, by its number or part of its text
+//   /help        what the bot understands
+//
+// A message that isn't a command is added as a task. Anyone can find a bot
+// and write to it, so only the chats in `telegram_chats` are answered; any
+// other is told its chat ID, to be added there if it should be allowed.
+
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use todo_cli::config::Config;
+use todo_cli::hooks::Event;
+use todo_cli::http::Client;
+use todo_cli::i18n::tr;
+use todo_cli::{quickadd, Date, Task};
+
+use crate::{plain_details, save, sort_tasks, warn};
+
+/// Where the Bot API lives.
+const API: &str = "https://api.telegram.org";
+
+/// How long one poll waits for a message before asking again.
+const POLL: Duration = Duration::from_secs(30);
+
+/// How long a reply may get; Telegram takes 4096 characters.
+const MAX_REPLY: usize = 4000;
+
+/// Answers messages until the process is stopped.
+pub fn run(config: &Config, token: &str) -> todo_cli::Result<()> {
+    // A poll is held open for up to POLL, on top of the usual timeout.
+    let client = Client {
+        timeout: config.http.timeout + POLL,
+        ..config.http.clone()
+    };
+    let me = call(&client, token, "getMe", &json!({}))
+        .map_err(|e| todo_cli::Error::Failed(tr("telegram-failed", &[("error", &e)])))?;
+    println!(
+        "{}",
+        tr(
+            "telegram-running",
+            &[("bot", &me["username"].as_str().unwrap_or_default())]
+        )
+    );
+    let mut offset = 0;
+    loop {
+        let params = json!({
+            "offset": offset,
+            "timeout": POLL.as_secs(),
+            "allowed_updates": ["message"],
+        });
+        let updates = match call(&client, token, "getUpdates", &params) {
+            Ok(updates) => updates,
+            Err(e) => {
+                // Telegram or the network may be back in a moment.
+                warn(&tr("telegram-failed", &[("error", &e)]));
+                thread::sleep(Duration::from_secs(5));
+                continue;
+            }
+        };
+        for update in updates.as_array().into_iter().flatten() {
+            offset = offset.max(update["update_id"].as_i64().unwrap_or_default() + 1);
+            let message = &update["message"];
+            let (Some(chat), Some(text)) =
+                (message["chat"]["id"].as_i64(), message["text"].as_str())
+            else {
+                continue;
+            };
+            let reply = answer(config, chat, text);
+            let params = json!({ "chat_id": chat, "text": reply });
+            if let Err(e) = call(&client, token, "sendMessage", &params) {
+                warn(&tr("telegram-failed", &[("error", &e)]));
+            }
+        }
+    }
+}
+
+/// Calls a Bot API method and returns its result.
+fn call(client: &Client, token: &str, method: &str, params: &Value) -> io::Result<Value> {
+    let url = format!("{}/bot{}/{}", API, token, method);
+    let response = client.send(
+        "POST",
+        &url,
+        &[("Content-Type", "application/json")],
+        params.to_string().as_bytes(),
+    )?;
+    let mut answer: Value = serde_json::from_slice(&response.body)
+        .map_err(|_| io::Error::other(format!("HTTP {}", response.status)))?;
+    if answer["ok"] == true {
+        return Ok(answer["result"].take());
+    }
+    Err(io::Error::other(
+        answer["description"]
+            .as_str()
+            .map_or_else(|| format!("HTTP {}", response.status), String::from),
+    ))
+}
+
+/// The reply to `text`, sent in `chat`.
+fn answer(config: &Config, chat: i64, text: &str) -> String {
+    if !config.telegram_chats.contains(&chat) {
+        return tr("telegram-not-allowed", &[("chat", &chat)]);
+    }
+    let text = text.trim();
+    let (command, rest) = match text.strip_prefix('/') {
+        Some(command) => {
+            let (command, rest) = command
+                .split_once(char::is_whitespace)
+                .unwrap_or((command, ""));
+            // In groups, commands may name the bot: /add@my_todo_bot.
+            let command = command.split('@').next().unwrap_or_default();
+            (command, rest.trim())
+        }
+        None => ("add", text),
+    };
+    let result = match command {
+        "add" => add(config, rest),
+        "list" => list(config),
+        "done" => done(config, rest),
+        "start" | "help" => Ok(tr("telegram-help", &[])),
+        _ => Ok(tr("telegram-unknown", &[("command", &command)])),
+    };
+    result.unwrap_or_else(|e| e.to_string())
+}
+
+fn add(config: &Config, text: &str) -> todo_cli::Result<String> {
+    if text.is_empty() {
+        return Ok(tr("telegram-add-missing", &[]));
+    }
+    let mut store = config.open_store()?;
+    store.audit_as("telegram");
+    let task = Task {
+        text: text.to_string(),
+        ..Task::default()
+    };
+    let index = store.add(quickadd::apply(task, Date::today())?)?;
+    let task = &store.tasks()[index];
+    save(config, &store, &[(Event::Add, task)])?;
+    Ok(tr(
+        "telegram-added",
+        &[("number", &(index + 1)), ("task", &task.text)],
+    ))
+}
+
+fn list(config: &Config) -> todo_cli::Result<String> {
+    let store = config.open_store()?;
+    let mut numbered: Vec<(usize, Task)> = store
+        .tasks()
+        .iter()
+        .cloned()
+        .enumerate()
+        .filter(|(_, task)| !task.completed && task.someday.is_none())
+        .collect();
+    if numbered.is_empty() {
+        return Ok(tr("telegram-empty", &[]));
+    }
+    sort_tasks(&mut numbered, config.default_sort, &config.urgency);
+    let mut reply = String::new();
+    for (shown, (index, task)) in numbered.iter().enumerate() {
+        let mut line = format!("{}. {}", index + 1, task.text);
+        for detail in plain_details(task, config) {
+            line.push(' ');
+            line.push_str(&detail);
+        }
+        if reply.chars().count() + line.chars().count() > MAX_REPLY {
+            reply.push_str(&tr(
+                "telegram-more",
+                &[("count", &(numbered.len() - shown))],
+            ));
+            break;
+        }
+        reply.push_str(&line);
+        reply.push('\n');
+    }
+    Ok(reply)
+}
+
+fn done(config: &Config, query: &str) -> todo_cli::Result<String> {
+    if query.is_empty() {
+        return Ok(tr("telegram-done-missing", &[]));
+    }
+    let mut store = config.open_store()?;
+    store.audit_as("telegram");
+    let index = store.resolve(query)?;
+    if !store.complete(index)? {
+        return Ok(tr(
+            "telegram-already-done",
+            &[("task", &store.tasks()[index].text)],
+        ));
+    }
+    let task = &store.tasks()[index];
+    save(config, &store, &[(Event::Done, task)])?;
+    Ok(tr(
+        "telegram-done",
+        &[("number", &(index + 1)), ("task", &task.text)],
+    ))
+}
@@ -0,0 +1,116 @@
+// Named templates: sets of tasks written down once in the config file and
+// added together whenever they are needed.
+//
+//   [templates]
+//   release-checklist = """
+//   Tag v{version} +release p1
+//   Write the release notes for {version} #docs due:+2d
+//   Announce {version}
+//   """
+//
+// Each non-empty line is one task, in the quick-add syntax `add` understands.
+// `{name}` stands for the value given with `--var name=value`; `{{` and `}}`
+// are literal braces.
+
+use std::collections::BTreeMap;
+
+use crate::error::{Error, Result};
+use crate::i18n::tr;
+
+/// The template's lines with every variable filled in, one per task.
+pub fn expand(template: &str, vars: &BTreeMap<String, String>) -> Result<Vec<String>> {
+    lines(template)
+        .map(|line| {
+            let mut out = String::new();
+            for piece in pieces(line) {
+                match piece {
+                    Piece::Text(text) => out.push_str(text),
+                    Piece::Var(name) => match vars.get(name) {
+                        Some(value) => out.push_str(value),
+                        None => {
+                            return Err(Error::Invalid(tr(
+                                "template-missing-var",
+                                &[("name", &name)],
+                            )))
+                        }
+                    },
+                }
+            }
+            Ok(out)
+        })
+        .collect()
+}
+
+/// The names of the variables the template uses, in order of first use.
+pub fn variables(template: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    for piece in lines(template).flat_map(pieces) {
+        if let Piece::Var(name) = piece {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    names
+}
+
+/// Reads a `name=value` given with `--var`.
+pub fn parse_var(spec: &str) -> Result<(String, String)> {
+    match spec.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.to_string()))
+        }
+        _ => Err(Error::Invalid(tr(
+            "template-var-invalid",
+            &[("input", &spec)],
+        ))),
+    }
+}
+
+/// The lines of a template that stand for tasks.
+pub fn lines(template: &str) -> impl Iterator<Item = &str> {
+    template
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+}
+
+enum Piece<'a> {
+    Text(&'a str),
+    Var(&'a str),
+}
+
+/// Splits a line into literal text and `{variables}`. A brace that doesn't
+/// start a variable is kept as text.
+fn pieces(line: &str) -> Vec<Piece<'_>> {
+    let mut pieces = Vec::new();
+    let mut rest = line;
+    while let Some(start) = rest.find(['{', '}']) {
+        pieces.push(Piece::Text(&rest[..start]));
+        let after = &rest[start + 1..];
+        if let Some(after) = after.strip_prefix(&rest[start..=start]) {
+            // `{{` or `}}`.
+            pieces.push(Piece::Text(&rest[start..=start]));
+            rest = after;
+            continue;
+        }
+        let name = after.find('}').map(|end| &after[..end]).filter(|name| {
+            !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        });
+        match name {
+            Some(name) if rest[start..].starts_with('{') => {
+                pieces.push(Piece::Var(name));
+                rest = &after[name.len() + 1..];
+            }
+            _ => {
+                pieces.push(Piece::Text(&rest[start..=start]));
+                rest = after;
+            }
+        }
+    }
+    pieces.push(Piece::Text(rest));
+    pieces
+}
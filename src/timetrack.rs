@@ -0,0 +1,332 @@
+// Time spent on tasks, and how it compares with what was expected.
+//
+// A task can have an estimate, set with `estimate`, and the sessions spent on
+// it: timed with `time start` and `time stop`, or logged afterwards with `time
+// log`. Only one task is timed at a time, so starting another one's timer
+// stops the first, and completing a task stops its timer. Like every time in
+// the list, sessions are to the minute.
+//
+// `time estimates` sets the estimates beside the time actually spent, per task
+// or per project, so that the next estimates can be calibrated against them.
+// `timesheet` adds the sessions up per day and task, for a day or a week, as
+// a table or as CSV for a time-keeping system, and `time report` per task,
+// project or tag over any range of days. A session that runs past midnight
+// counts towards both days, and a task with several tags towards each tag.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::date::{Date, DateTime};
+use crate::i18n::tr;
+use crate::stats::csv_field;
+use crate::task::Task;
+
+/// A stretch of time spent on a task.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Session {
+    pub start: DateTime,
+    /// When it ended; `None` while the timer is still running.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<DateTime>,
+}
+
+impl Session {
+    /// How many minutes the session lasted, or has lasted by `now` if it is
+    /// still running.
+    pub fn minutes(&self, now: DateTime) -> i64 {
+        self.start.minutes_until(self.end.unwrap_or(now)).max(0)
+    }
+}
+
+impl fmt::Display for Session {
+    /// `2024-05-01 09:00 to 10:30`, with the end's date only when it is
+    /// another day, or `2024-05-01 09:00 to now` while running.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.end {
+            None => write!(f, "{} to now", self.start),
+            Some(end) if end.date() == self.start.date() => {
+                write!(f, "{} to {:02}:{:02}", self.start, end.hour(), end.minute())
+            }
+            Some(end) => write!(f, "{} to {}", self.start, end),
+        }
+    }
+}
+
+/// Reads a duration such as `45m`, `2h`, `1h30m` or `1.5h` as minutes. A
+/// plain number is minutes.
+pub fn parse_duration(input: &str) -> Result<u32, String> {
+    let invalid = || tr("duration-invalid", &[("input", &input.trim())]);
+    let text = input.trim().to_lowercase().replace(' ', "");
+    if text.is_empty() {
+        return Err(invalid());
+    }
+    if let Ok(minutes) = text.parse::<u32>() {
+        return Ok(minutes);
+    }
+    let mut minutes = 0.0;
+    let mut rest = text.as_str();
+    while !rest.is_empty() {
+        let split = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .ok_or_else(invalid)?;
+        let (count, tail) = rest.split_at(split);
+        let count: f64 = count.parse().map_err(|_| invalid())?;
+        let unit_len = tail
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+        minutes += count
+            * match unit {
+                "h" | "hr" | "hrs" | "hour" | "hours" => 60.0,
+                "m" | "min" | "mins" | "minute" | "minutes" => 1.0,
+                _ => return Err(invalid()),
+            };
+        rest = tail;
+    }
+    Ok(minutes.round() as u32)
+}
+
+/// A number of minutes as it is shown, e.g. `1h 30m`, `2h` or `45m`.
+pub fn format_duration(minutes: i64) -> String {
+    let sign = if minutes < 0 { "-" } else { "" };
+    let minutes = minutes.abs();
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{}{}m", sign, m),
+        (h, 0) => format!("{}{}h", sign, h),
+        (h, m) => format!("{}{}h {}m", sign, h, m),
+    }
+}
+
+/// What the rows of a time report stand for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Group {
+    Task,
+    Project,
+    Tag,
+}
+
+impl Group {
+    /// The rows `task` counts towards: its own, its project's or each of
+    /// its tags'. `None` stands for the tasks without a project or tag.
+    fn keys(self, task: &Task) -> Vec<Option<String>> {
+        match self {
+            Group::Task => vec![Some(task.text.clone())],
+            Group::Project => vec![task.project.clone()],
+            Group::Tag if task.tags.is_empty() => vec![None],
+            Group::Tag => task.tags.iter().cloned().map(Some).collect(),
+        }
+    }
+}
+
+impl FromStr for Group {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "task" | "tasks" => Ok(Group::Task),
+            "project" | "projects" => Ok(Group::Project),
+            "tag" | "tags" => Ok(Group::Tag),
+            _ => Err(tr("time-group-invalid", &[("input", &s.trim())])),
+        }
+    }
+}
+
+impl fmt::Display for Group {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Group::Task => "task",
+            Group::Project => "project",
+            Group::Tag => "tag",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A task, project or tag's estimated and actual time, in minutes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Estimate {
+    /// The task's text, or the project or tag; `None` for the tasks without
+    /// one.
+    pub name: Option<String>,
+    pub estimate: i64,
+    pub actual: i64,
+    /// How many tasks it is made of.
+    pub tasks: usize,
+}
+
+impl Estimate {
+    /// The actual time as a percentage of the estimate, e.g. 150 for half
+    /// as long again; `None` for a zero estimate.
+    pub fn percent(&self) -> Option<i64> {
+        (self.estimate > 0).then(|| (self.actual * 100 + self.estimate / 2) / self.estimate)
+    }
+}
+
+/// The estimated and actual time of the tasks in `tasks` that have an
+/// estimate, as of `now`: one row per task in list order, or per project or
+/// tag in order of name with the tasks without one last.
+pub fn estimates(tasks: &[Task], by: Group, now: DateTime) -> Vec<Estimate> {
+    let mut rows: Vec<Estimate> = Vec::new();
+    for task in tasks {
+        let Some(estimate) = task.estimate else {
+            continue;
+        };
+        for name in by.keys(task) {
+            let index = rows
+                .iter()
+                .position(|row| by != Group::Task && row.name == name)
+                .unwrap_or_else(|| {
+                    rows.push(Estimate {
+                        name,
+                        estimate: 0,
+                        actual: 0,
+                        tasks: 0,
+                    });
+                    rows.len() - 1
+                });
+            let row = &mut rows[index];
+            row.estimate += i64::from(estimate);
+            row.actual += task.tracked(now);
+            row.tasks += 1;
+        }
+    }
+    if by != Group::Task {
+        rows.sort_by(|a, b| by_name(&a.name, &b.name));
+    }
+    rows
+}
+
+/// Orders names ignoring case, with `None` last.
+fn by_name(a: &Option<String>, b: &Option<String>) -> std::cmp::Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.to_lowercase().cmp(&b.to_lowercase()),
+        (a, b) => b.is_some().cmp(&a.is_some()),
+    }
+}
+
+/// The time spent on a task, project or tag over a range of days.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spent {
+    /// The task's text, or the project or tag; `None` for the tasks without
+    /// one.
+    pub name: Option<String>,
+    pub minutes: i64,
+    /// How many tasks it is made of.
+    pub tasks: usize,
+}
+
+/// How many minutes of `task`'s sessions fall between `from` (from the
+/// start if `None`) and `until`, as of `now`.
+pub fn spent_between(task: &Task, from: Option<DateTime>, until: DateTime, now: DateTime) -> i64 {
+    task.sessions
+        .iter()
+        .map(|session| {
+            let start = from.map_or(session.start, |from| session.start.max(from));
+            let end = session.end.unwrap_or(now).min(until);
+            start.minutes_until(end).max(0)
+        })
+        .sum()
+}
+
+/// The time spent from the day `since` (or ever, if `None`) through the day
+/// `until`, as of `now`, per task, project or tag with any, most first.
+pub fn report(
+    tasks: &[Task],
+    by: Group,
+    since: Option<Date>,
+    until: Date,
+    now: DateTime,
+) -> Vec<Spent> {
+    let midnight = |date: Date| DateTime::new(date, 0, 0).expect("midnight always exists");
+    let (from, until) = (since.map(midnight), midnight(until.add_days(1)));
+    let mut rows: Vec<Spent> = Vec::new();
+    for task in tasks {
+        let minutes = spent_between(task, from, until, now);
+        if minutes == 0 {
+            continue;
+        }
+        for name in by.keys(task) {
+            let index = rows
+                .iter()
+                .position(|row| by != Group::Task && row.name == name)
+                .unwrap_or_else(|| {
+                    rows.push(Spent {
+                        name,
+                        minutes: 0,
+                        tasks: 0,
+                    });
+                    rows.len() - 1
+                });
+            rows[index].minutes += minutes;
+            rows[index].tasks += 1;
+        }
+    }
+    rows.sort_by(|a, b| b.minutes.cmp(&a.minutes).then(by_name(&a.name, &b.name)));
+    rows
+}
+
+/// A task's time on each day of a timesheet, in minutes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimesheetRow {
+    /// The task's index in the list.
+    pub index: usize,
+    pub task: Task,
+    /// The minutes spent on each of the timesheet's days, in order.
+    pub minutes: Vec<i64>,
+}
+
+impl TimesheetRow {
+    pub fn total(&self) -> i64 {
+        self.minutes.iter().sum()
+    }
+}
+
+/// The time spent on each task on each day from `first` to `last`, as of
+/// `now`: one row for each task with any, in list order.
+pub fn timesheet(tasks: &[Task], first: Date, last: Date, now: DateTime) -> Vec<TimesheetRow> {
+    let days = first.days_until(last) + 1;
+    let midnight = |date: Date| DateTime::new(date, 0, 0).expect("midnight always exists");
+    let mut rows = Vec::new();
+    for (index, task) in tasks.iter().enumerate() {
+        let minutes: Vec<i64> = (0..days.max(0))
+            .map(|day| {
+                let date = first.add_days(day);
+                let (from, until) = (midnight(date), midnight(date.add_days(1)));
+                spent_between(task, Some(from), until, now)
+            })
+            .collect();
+        if minutes.iter().any(|&m| m > 0) {
+            rows.push(TimesheetRow {
+                index,
+                task: task.clone(),
+                minutes,
+            });
+        }
+    }
+    rows
+}
+
+/// A timesheet as CSV, one row per day and task with time spent, in hours
+/// to two decimals and in minutes.
+pub fn timesheet_csv(rows: &[TimesheetRow], first: Date) -> String {
+    let mut out = String::from("date,id,task,project,hours,minutes\n");
+    let days = rows.first().map_or(0, |row| row.minutes.len());
+    for day in 0..days {
+        let date = first.add_days(day as i64);
+        for row in rows.iter().filter(|row| row.minutes[day] > 0) {
+            let minutes = row.minutes[day];
+            out.push_str(&format!(
+                "{},{},{},{},{:.2},{}\n",
+                date,
+                row.task.id,
+                csv_field(&row.task.text),
+                csv_field(row.task.project.as_deref().unwrap_or_default()),
+                minutes as f64 / 60.0,
+                minutes
+            ));
+        }
+    }
+    out
+}
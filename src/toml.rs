@@ -0,0 +1,412 @@
+// A tiny reader/writer for the subset of TOML used by the config file.
+//
+// We only need flat `key = value` pairs and `[table]` headers, where a value is
+// a string (including `"""` multi-line strings), an integer, a boolean, or an
+// array of those. Keeping this in-house avoids pulling in a full TOML
+// implementation for a handful of settings.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A single TOML value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+    Array(Vec<Value>),
+    Table(Table),
+}
+
+/// A TOML table: keys mapped to values, kept in sorted order for stable output.
+pub type Table = BTreeMap<String, Value>;
+
+impl Value {
+    /// Returns the string contents if this value is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the table if this value is a table.
+    pub fn as_table(&self) -> Option<&Table> {
+        match self {
+            Value::Table(t) => Some(t),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::String(s) => write!(f, "{}", quote(s)),
+            Value::Integer(n) => write!(f, "{}", n),
+            Value::Boolean(b) => write!(f, "{}", b),
+            Value::Array(items) => {
+                let parts: Vec<String> = items.iter().map(|v| v.to_string()).collect();
+                write!(f, "[{}]", parts.join(", "))
+            }
+            // Inline tables are never written by us, but make the output valid anyway.
+            Value::Table(t) => {
+                let parts: Vec<String> = t
+                    .iter()
+                    .map(|(k, v)| format!("{} = {}", format_key(k), v))
+                    .collect();
+                write!(f, "{{ {} }}", parts.join(", "))
+            }
+        }
+    }
+}
+
+/// Parses a TOML document into a table.
+pub fn parse(input: &str) -> Result<Table, String> {
+    let mut root = Table::new();
+    // The path of the `[table]` header we are currently inside, if any.
+    let mut current: Vec<String> = Vec::new();
+
+    let mut lines = input.lines().enumerate();
+    while let Some((line_no, raw_line)) = lines.next() {
+        let line = strip_comment(raw_line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        let err = |msg: &str| format!("line {}: {}", line_no + 1, msg);
+
+        if let Some(header) = line.strip_prefix('[') {
+            let header = header
+                .strip_suffix(']')
+                .ok_or_else(|| err("unterminated table header"))?;
+            current = split_key(header.trim()).map_err(|e| err(&e))?;
+            table_at(&mut root, &current).map_err(|e| err(&e))?;
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| err("expected `key = value`"))?;
+        let mut path = current.clone();
+        path.extend(split_key(key.trim()).map_err(|e| err(&e))?);
+        let value = value.trim();
+        let (value, rest) = if let Some(first) = value.strip_prefix("\"\"\"") {
+            // A multi-line string runs until the closing quotes, which may be on a later line.
+            let mut text = first.to_string();
+            while !text.contains("\"\"\"") {
+                let (_, next) = lines.next().ok_or_else(|| err("unterminated string"))?;
+                text.push('\n');
+                text.push_str(next);
+            }
+            let (body, rest) = text
+                .split_once("\"\"\"")
+                .expect("loop ends once quotes are found");
+            // A newline right after the opening quotes is not part of the string.
+            let body = body.strip_prefix('\n').unwrap_or(body);
+            let value = unescape(body).map_err(|e| err(&e))?;
+            (Value::String(value), rest.to_string())
+        } else {
+            let (value, rest) = parse_value(value).map_err(|e| err(&e))?;
+            (value, rest.to_string())
+        };
+        let rest = strip_comment(&rest);
+        if !rest.trim().is_empty() {
+            return Err(err("unexpected characters after value"));
+        }
+
+        let name = path.pop().expect("split_key never returns an empty path");
+        let table = table_at(&mut root, &path).map_err(|e| err(&e))?;
+        if table.insert(name.clone(), value).is_some() {
+            return Err(err(&format!("duplicate key '{}'", name)));
+        }
+    }
+
+    Ok(root)
+}
+
+/// Serializes a table back into TOML text.
+pub fn to_string(table: &Table) -> String {
+    let mut out = String::new();
+    write_table(&mut out, table, &mut Vec::new());
+    out
+}
+
+fn write_table(out: &mut String, table: &Table, path: &mut Vec<String>) {
+    // Plain values first, then nested tables, as TOML requires.
+    for (key, value) in table {
+        if !matches!(value, Value::Table(_)) {
+            out.push_str(&format!("{} = {}\n", format_key(key), value));
+        }
+    }
+    for (key, value) in table {
+        if let Value::Table(inner) = value {
+            path.push(format_key(key));
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(&format!("[{}]\n", path.join(".")));
+            write_table(out, inner, path);
+            path.pop();
+        }
+    }
+}
+
+/// Returns the (possibly newly created) table at `path`.
+fn table_at<'a>(root: &'a mut Table, path: &[String]) -> Result<&'a mut Table, String> {
+    let mut table = root;
+    for part in path {
+        let entry = table
+            .entry(part.clone())
+            .or_insert_with(|| Value::Table(Table::new()));
+        table = match entry {
+            Value::Table(t) => t,
+            _ => return Err(format!("'{}' is not a table", part)),
+        };
+    }
+    Ok(table)
+}
+
+/// Splits a dotted key such as `aliases.ls` into its parts.
+pub fn split_key(key: &str) -> Result<Vec<String>, String> {
+    let mut parts = Vec::new();
+    let mut rest = key.trim();
+    loop {
+        let (part, remaining) = if rest.starts_with('"') {
+            match parse_value(rest)? {
+                (Value::String(s), remaining) => (s, remaining),
+                _ => unreachable!("quoted keys always parse as strings"),
+            }
+        } else {
+            let end = rest.find('.').unwrap_or(rest.len());
+            let part = rest[..end].trim();
+            let valid = !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+            if !valid {
+                return Err(format!("invalid key '{}'", key));
+            }
+            (part.to_string(), &rest[end..])
+        };
+        parts.push(part);
+        let remaining = remaining.trim_start();
+        match remaining.strip_prefix('.') {
+            Some(next) => rest = next.trim_start(),
+            None if remaining.is_empty() => return Ok(parts),
+            None => return Err(format!("invalid key '{}'", key)),
+        }
+    }
+}
+
+/// Parses one value from the start of `input`, returning it and the unparsed rest.
+fn parse_value(input: &str) -> Result<(Value, &str), String> {
+    if let Some(rest) = input.strip_prefix('"') {
+        let mut escaped = false;
+        for (i, c) in rest.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => return Ok((Value::String(unescape(&rest[..i])?), &rest[i + 1..])),
+                _ => {}
+            }
+        }
+        return Err("unterminated string".to_string());
+    }
+
+    if let Some(rest) = input.strip_prefix('\'') {
+        // Literal strings have no escapes at all.
+        let end = rest.find('\'').ok_or("unterminated string")?;
+        return Ok((Value::String(rest[..end].to_string()), &rest[end + 1..]));
+    }
+
+    if let Some(mut rest) = input.strip_prefix('[') {
+        let mut items = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix(']') {
+                return Ok((Value::Array(items), after));
+            }
+            let (item, after) = parse_value(rest)?;
+            items.push(item);
+            rest = after.trim_start();
+            if let Some(after) = rest.strip_prefix(',') {
+                rest = after;
+            } else if !rest.starts_with(']') {
+                return Err("expected ',' or ']' in array".to_string());
+            }
+        }
+    }
+
+    // Bare values run until a delimiter.
+    let end = input
+        .find(|c: char| c == ',' || c == ']' || c.is_whitespace())
+        .unwrap_or(input.len());
+    let (word, rest) = input.split_at(end);
+    let value = match word {
+        "true" => Value::Boolean(true),
+        "false" => Value::Boolean(false),
+        _ => match word.replace('_', "").parse::<i64>() {
+            Ok(n) => Value::Integer(n),
+            Err(_) => return Err(format!("invalid value '{}'", word)),
+        },
+    };
+    Ok((value, rest))
+}
+
+/// Resolves the backslash escapes in the body of a basic string.
+fn unescape(body: &str) -> Result<String, String> {
+    let mut value = String::new();
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('t') => value.push('\t'),
+            Some('"') => value.push('"'),
+            Some('\\') => value.push('\\'),
+            _ => return Err("invalid escape sequence in string".to_string()),
+        }
+    }
+    Ok(value)
+}
+
+/// Removes a trailing `# comment`, ignoring `#` characters inside strings.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string: Option<char> = None;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match in_string {
+            Some('"') if escaped => escaped = false,
+            Some('"') if c == '\\' => escaped = true,
+            Some(q) if c == q => in_string = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => in_string = Some(c),
+            None if c == '#' => return &line[..i],
+            None => {}
+        }
+    }
+    line
+}
+
+/// Quotes a string using TOML basic-string escaping.
+///
+/// Strings spanning several lines are written as `"""` multi-line strings so
+/// they stay readable when a person edits the file.
+fn quote(s: &str) -> String {
+    let multi_line = s.contains('\n');
+    let mut out = String::from(if multi_line { "\"\"\"\n" } else { "\"" });
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' if multi_line => out.push('\n'),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            _ => out.push(c),
+        }
+    }
+    out.push_str(if multi_line { "\"\"\"" } else { "\"" });
+    out
+}
+
+/// Writes a key bare when possible, quoted otherwise.
+fn format_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        key.to_string()
+    } else {
+        quote(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(value: &str) -> Value {
+        Value::String(value.to_string())
+    }
+
+    #[test]
+    fn parses_values_tables_and_comments() {
+        let table = parse(
+            r#"
+# The list to use.
+file = "~/todo.json"  # a comment after a value
+limit = 1_000
+color = false
+holidays = ["us", 'C:\cal.ics']
+
+[aliases]
+ls = "list --tag=#work"
+"two words" = "search"
+
+[contexts.nested]
+key = -3
+"#,
+        )
+        .unwrap();
+        assert_eq!(table["file"], string("~/todo.json"));
+        assert_eq!(table["limit"], Value::Integer(1000));
+        assert_eq!(table["color"], Value::Boolean(false));
+        assert_eq!(
+            table["holidays"],
+            Value::Array(vec![string("us"), string(r"C:\cal.ics")])
+        );
+        let aliases = table["aliases"].as_table().unwrap();
+        assert_eq!(aliases["ls"], string("list --tag=#work"));
+        assert_eq!(aliases["two words"], string("search"));
+        let contexts = table["contexts"].as_table().unwrap();
+        let nested = contexts["nested"].as_table().unwrap();
+        assert_eq!(nested["key"], Value::Integer(-3));
+    }
+
+    #[test]
+    fn parses_multi_line_strings_and_escapes() {
+        let table =
+            parse("a = \"\"\"\nfirst\nsecond\"\"\"\nb = \"tab\\there \\\"quoted\\\"\"\n").unwrap();
+        assert_eq!(table["a"], string("first\nsecond"));
+        assert_eq!(table["b"], string("tab\there \"quoted\""));
+    }
+
+    #[test]
+    fn refuses_malformed_documents() {
+        let error = |input: &str| parse(input).unwrap_err();
+        assert_eq!(error("a = 1\na = 2"), "line 2: duplicate key 'a'");
+        assert_eq!(error("[aliases"), "line 1: unterminated table header");
+        assert_eq!(error("a = \"open"), "line 1: unterminated string");
+        assert_eq!(
+            error("a = 1 2"),
+            "line 1: unexpected characters after value"
+        );
+        assert_eq!(error("a = maybe"), "line 1: invalid value 'maybe'");
+        assert_eq!(error("just words"), "line 1: expected `key = value`");
+        assert_eq!(error("a = 1\n[a]"), "line 2: 'a' is not a table");
+        assert!(parse("a = \"bad \\q escape\"").is_err());
+    }
+
+    #[test]
+    fn writes_what_it_reads() {
+        let input = "name = \"say \\\"hi\\\"\"\nnotes = \"\"\"\none\ntwo\"\"\"\nsizes = [1, 2]\n\n[aliases]\n\"l s\" = \"list\"\n";
+        let table = parse(input).unwrap();
+        assert_eq!(to_string(&table), input);
+        assert_eq!(parse(&to_string(&table)).unwrap(), table);
+    }
+
+    #[test]
+    fn splits_dotted_and_quoted_keys() {
+        assert_eq!(split_key("aliases.ls").unwrap(), ["aliases", "ls"]);
+        assert_eq!(
+            split_key(r#"views."due soon""#).unwrap(),
+            ["views", "due soon"]
+        );
+        assert!(split_key("a..b").is_err());
+        assert!(split_key("a b").is_err());
+    }
+}
@@ -0,0 +1,89 @@
+// Removed tasks, kept for a while in case they are wanted back.
+//
+// Every task removed from a list, whether deleted, cleared or removed by a
+// sync, is moved to `~/.config/todo/trash/<list>.json` with when it was
+// removed. `trash` lists them, `trash restore` puts one back with its id and
+// `trash empty` removes them for good. They are removed for good by
+// themselves `trash_days` after being deleted (30 by default); with 0 the
+// trash isn't used.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::config_dir;
+use crate::date::DateTime;
+use crate::error::{Error, Result};
+use crate::task::Task;
+
+/// A task in the trash.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Trashed {
+    pub task: Task,
+    /// When it was removed from the list.
+    pub deleted: DateTime,
+}
+
+/// Where a list's removed tasks are kept, and for how long.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trash {
+    path: PathBuf,
+    days: u32,
+}
+
+impl Trash {
+    /// The trash of `list`, keeping tasks for `days` days.
+    pub fn new(list: Option<&str>, days: u32) -> Trash {
+        let name = format!("{}.json", list.unwrap_or("main"));
+        Trash {
+            path: config_dir().join("trash").join(name),
+            days,
+        }
+    }
+
+    /// The file the trash is kept in.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The tasks in the trash as of `now`, oldest first, leaving out those
+    /// kept long enough.
+    pub fn load(&self, now: DateTime) -> Result<Vec<Trashed>> {
+        let mut trashed: Vec<Trashed> = match fs::read_to_string(&self.path) {
+            Ok(data) => serde_json::from_str(&data).map_err(|e| Error::Parse {
+                location: self.path.display().to_string(),
+                line: e.line(),
+                column: e.column(),
+                message: e.to_string(),
+            })?,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let expired = now.add_minutes(-i64::from(self.days) * 24 * 60);
+        trashed.retain(|t| t.deleted > expired);
+        trashed.sort_by_key(|t| t.deleted);
+        Ok(trashed)
+    }
+
+    /// Writes `trashed` as everything in the trash.
+    pub fn save(&self, trashed: &[Trashed]) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string(trashed).expect("Failed to serialize data to JSON.");
+        fs::write(&self.path, json)?;
+        Ok(())
+    }
+
+    /// Moves `tasks`, just removed at `now`, to the trash.
+    pub fn put(&self, tasks: Vec<Task>, now: DateTime) -> Result<()> {
+        if tasks.is_empty() || self.days == 0 {
+            return Ok(());
+        }
+        let mut trashed = self.load(now)?;
+        trashed.extend(tasks.into_iter().map(|task| Trashed { task, deleted: now }));
+        self.save(&trashed)
+    }
+}
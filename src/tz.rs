@@ -0,0 +1,435 @@
+// Time zones, so that due times can be kept in UTC but typed and shown in
+// local time.
+//
+// Zones come from the system's time zone database (/usr/share/zoneinfo).
+// Each file there lists the times the zone's offset from UTC changed, and
+// ends with a POSIX TZ rule for the years after the last one, such as
+// `CET-1CEST,M3.5.0,M10.5.0/3` (an hour ahead of UTC, two in summer, from
+// the last Sunday of March to the last Sunday of October). A rule like that
+// can also be given on its own instead of a zone's name.
+//
+// The zone in use is the `timezone` setting, else the TZ environment
+// variable, else the system's (/etc/localtime), else UTC. Because the file
+// keeps UTC, a task due at 17:00 in Berlin shows as due at 16:00 once the
+// zone is London's, and stays due at the same moment across the change to
+// or from summer time.
+
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::date::{days_in_month, is_leap_year, Date, DateTime};
+
+/// Where the time zone database lives.
+const ZONEINFO: &str = "/usr/share/zoneinfo";
+/// The system's zone, a copy of or a link into the database.
+const LOCALTIME: &str = "/etc/localtime";
+
+const HOUR: i64 = 3600;
+const DAY: i64 = 86_400;
+
+/// The zone chosen with `init`, or `None` until one is.
+static CURRENT: Mutex<Option<Arc<Zone>>> = Mutex::new(None);
+
+/// A time zone: its offset from UTC at any moment.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Zone {
+    name: String,
+    /// When the offset changed, in seconds since 1970-01-01 UTC, and the
+    /// offset from then on, in seconds east of UTC. Oldest first.
+    changes: Vec<(i64, i64)>,
+    /// The offset before the first change.
+    initial: i64,
+    /// The offset after the last change, if the zone has a rule for it.
+    rule: Option<Rule>,
+}
+
+impl Zone {
+    /// Coordinated Universal Time.
+    pub fn utc() -> Zone {
+        Zone {
+            name: "UTC".to_string(),
+            changes: Vec::new(),
+            initial: 0,
+            rule: None,
+        }
+    }
+
+    /// The zone called `name` in the database, e.g. `Europe/Berlin`, or the
+    /// one a POSIX TZ rule describes. `None` if it is neither.
+    pub fn named(name: &str) -> Option<Zone> {
+        let name = name.trim();
+        let name = name.strip_prefix(':').unwrap_or(name);
+        if name.is_empty() {
+            return None;
+        }
+        if name.eq_ignore_ascii_case("utc") || name == "Z" {
+            return Some(Zone::utc());
+        }
+        // Names stay inside the database.
+        let path = if name.starts_with('/') {
+            Path::new(name).to_path_buf()
+        } else if !name.split('/').any(|part| part == "..") {
+            Path::new(ZONEINFO).join(name)
+        } else {
+            return None;
+        };
+        if let Some(zone) = fs::read(&path)
+            .ok()
+            .and_then(|data| Zone::from_tzif(name, &data))
+        {
+            return Some(zone);
+        }
+        let rule = Rule::parse(name)?;
+        Some(Zone {
+            name: name.to_string(),
+            changes: Vec::new(),
+            initial: rule.std,
+            rule: Some(rule),
+        })
+    }
+
+    /// The zone the environment asks for: TZ's, else the system's, else UTC.
+    pub fn system() -> Zone {
+        if let Some(zone) = std::env::var("TZ").ok().as_deref().and_then(Zone::named) {
+            return zone;
+        }
+        let Ok(data) = fs::read(LOCALTIME) else {
+            return Zone::utc();
+        };
+        // /etc/localtime is usually a link to the zone's file, which names it.
+        let name = fs::read_link(LOCALTIME)
+            .ok()
+            .and_then(|target| {
+                let target = target.to_string_lossy().into_owned();
+                target
+                    .split_once("zoneinfo/")
+                    .map(|(_, name)| name.to_string())
+            })
+            .unwrap_or_else(|| "localtime".to_string());
+        Zone::from_tzif(&name, &data).unwrap_or_else(Zone::utc)
+    }
+
+    /// The zone's name, as it was given.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The offset from UTC, in seconds east, at `secs` seconds after
+    /// 1970-01-01 UTC.
+    pub fn offset(&self, secs: i64) -> i64 {
+        let after = self.changes.partition_point(|&(at, _)| at <= secs);
+        match (after, &self.rule) {
+            (0, _) if !self.changes.is_empty() => self.initial,
+            (n, Some(rule)) if n == self.changes.len() => rule.offset(secs),
+            (0, None) => self.initial,
+            (n, _) => self.changes[n - 1].1,
+        }
+    }
+
+    /// `at`, a UTC time, as the zone's clocks show it.
+    pub fn local(&self, at: DateTime) -> DateTime {
+        let secs = at.secs();
+        DateTime::from_secs(secs + self.offset(secs))
+    }
+
+    /// The UTC time at which the zone's clocks show `local`.
+    ///
+    /// A time the clocks skip when they go forward is read with the offset
+    /// from before, so 2:30 on that night is 3:30 in summer time. A time they
+    /// show twice when they go back is the first of the two.
+    pub fn utc_of(&self, local: DateTime) -> DateTime {
+        let secs = local.secs();
+        let before = self.offset(secs - DAY);
+        let after = self.offset(secs + DAY);
+        let utc = [before, after]
+            .into_iter()
+            .map(|offset| secs - offset)
+            .filter(|&utc| self.offset(utc) == secs - utc)
+            .min()
+            .unwrap_or(secs - before);
+        DateTime::from_secs(utc)
+    }
+
+    /// Reads a zone from a file in the database, in the TZif format of
+    /// RFC 8536.
+    fn from_tzif(name: &str, data: &[u8]) -> Option<Zone> {
+        let (mut block, mut counts) = tzif_header(data)?;
+        let mut time_size = 4;
+        if data[4] >= b'2' {
+            // Version 2 and later repeat the data with 64-bit times after
+            // the version 1 block, and add the rule for later years.
+            let rest = data.get(44 + block_len(&counts, 4)..)?;
+            (block, counts) = tzif_header(rest)?;
+            time_size = 8;
+        }
+        let [_, _, _, times, types, _] = counts;
+        let mut changes = Vec::with_capacity(times);
+        let offsets = 44 + times * time_size + times;
+        let offset_of = |index: usize| -> Option<i64> {
+            let at = offsets + index * 6;
+            let bytes = block.get(at..at + 4)?;
+            Some(i64::from(i32::from_be_bytes(bytes.try_into().ok()?)))
+        };
+        for i in 0..times {
+            let at = 44 + i * time_size;
+            let bytes = block.get(at..at + time_size)?;
+            let secs = match time_size {
+                4 => i64::from(i32::from_be_bytes(bytes.try_into().ok()?)),
+                _ => i64::from_be_bytes(bytes.try_into().ok()?),
+            };
+            let index = usize::from(*block.get(44 + times * time_size + i)?);
+            if index >= types {
+                return None;
+            }
+            changes.push((secs, offset_of(index)?));
+        }
+        let initial = if types > 0 { offset_of(0)? } else { 0 };
+        let rule = match time_size {
+            8 => {
+                let footer = block.get(44 + block_len(&counts, 8)..).unwrap_or_default();
+                let footer = String::from_utf8_lossy(footer);
+                Rule::parse(footer.trim_matches('\n'))
+            }
+            _ => None,
+        };
+        Some(Zone {
+            name: name.to_string(),
+            changes,
+            initial,
+            rule,
+        })
+    }
+}
+
+/// The data after the header of a TZif block, and the header's counts:
+/// UT/local indicators, standard/wall indicators, leap seconds, changes,
+/// local time types and abbreviation bytes.
+fn tzif_header(data: &[u8]) -> Option<(&[u8], [usize; 6])> {
+    if data.get(..4)? != b"TZif" {
+        return None;
+    }
+    let mut counts = [0; 6];
+    for (i, count) in counts.iter_mut().enumerate() {
+        let at = 20 + i * 4;
+        *count = u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?) as usize;
+    }
+    Some((data, counts))
+}
+
+/// The length of a TZif block's data after its header, with times of
+/// `time_size` bytes.
+fn block_len(counts: &[usize; 6], time_size: usize) -> usize {
+    let [ut, std, leaps, times, types, chars] = *counts;
+    times * time_size + times + types * 6 + chars + leaps * (time_size + 4) + std + ut
+}
+
+/// A POSIX TZ rule: a standard offset, and a summer offset with the days it
+/// starts and ends on, if the zone has summer time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Rule {
+    /// In seconds east of UTC.
+    std: i64,
+    dst: Option<(i64, Change, Change)>,
+}
+
+/// When summer time starts or ends: a day, and a local time on it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Change {
+    day: Day,
+    /// Seconds after midnight; may be negative or past a day.
+    time: i64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Day {
+    /// `Jn`: the nth day of the year from 1, never counting February 29th.
+    Julian(u32),
+    /// `n`: the nth day of the year from 0, counting February 29th.
+    Ordinal(u32),
+    /// `Mm.w.d`: weekday d (Sunday = 0) of week w (5 is the last) of month m.
+    Weekday { month: u32, week: u32, weekday: u32 },
+}
+
+impl Rule {
+    fn parse(text: &str) -> Option<Rule> {
+        let mut rest = text;
+        zone_name(&mut rest)?;
+        // POSIX offsets are hours west of UTC: `CET-1` is an hour east.
+        let std = -clock(&mut rest)?;
+        if rest.is_empty() {
+            return Some(Rule { std, dst: None });
+        }
+        zone_name(&mut rest)?;
+        let dst = match rest.starts_with(|c: char| c.is_ascii_digit() || c == '+' || c == '-') {
+            true => -clock(&mut rest)?,
+            false => std + HOUR,
+        };
+        // Without days, summer time runs as it has in the US since 2007.
+        let (start, end) = match rest.strip_prefix(',') {
+            Some(days) => {
+                let (start, end) = days.split_once(',')?;
+                (Change::parse(start)?, Change::parse(end)?)
+            }
+            None if rest.is_empty() => (
+                Change::parse("M3.2.0").unwrap(),
+                Change::parse("M11.1.0").unwrap(),
+            ),
+            None => return None,
+        };
+        Some(Rule {
+            std,
+            dst: Some((dst, start, end)),
+        })
+    }
+
+    fn offset(&self, secs: i64) -> i64 {
+        let Some((dst, start, end)) = self.dst else {
+            return self.std;
+        };
+        let year = DateTime::from_secs(secs + self.std).date().year();
+        // Summer time starts by the standard clock and ends by its own.
+        let start = start.at(year, self.std);
+        let end = end.at(year, dst);
+        let summer = match start < end {
+            true => (start..end).contains(&secs),
+            // South of the equator, summer spans the new year.
+            false => !(end..start).contains(&secs),
+        };
+        if summer {
+            dst
+        } else {
+            self.std
+        }
+    }
+}
+
+impl Change {
+    fn parse(text: &str) -> Option<Change> {
+        let (day, time) = match text.split_once('/') {
+            Some((day, time)) => {
+                let mut time = time;
+                let secs = clock(&mut time)?;
+                (day, time.is_empty().then_some(secs)?)
+            }
+            None => (text, 2 * HOUR),
+        };
+        let day = if let Some(spec) = day.strip_prefix('M') {
+            let mut parts = spec.split('.').map(|part| part.parse::<u32>().ok());
+            let (month, week, weekday) = (parts.next()??, parts.next()??, parts.next()??);
+            if parts.next().is_some()
+                || !(1..=12).contains(&month)
+                || !(1..=5).contains(&week)
+                || weekday > 6
+            {
+                return None;
+            }
+            Day::Weekday {
+                month,
+                week,
+                weekday,
+            }
+        } else if let Some(n) = day.strip_prefix('J') {
+            Day::Julian(n.parse().ok().filter(|n| (1..=365).contains(n))?)
+        } else {
+            Day::Ordinal(day.parse().ok().filter(|&n: &u32| n <= 365)?)
+        };
+        Some(Change { day, time })
+    }
+
+    /// The moment of the change in `year`, in seconds since 1970-01-01 UTC,
+    /// with the clocks `offset` seconds ahead of UTC until then.
+    fn at(self, year: i32, offset: i64) -> i64 {
+        let january = Date::new(year, 1, 1).expect("January 1st exists");
+        let date = match self.day {
+            Day::Julian(n) => {
+                let leap = is_leap_year(year) && n >= 60;
+                january.add_days(i64::from(n) - 1 + i64::from(leap))
+            }
+            Day::Ordinal(n) => january.add_days(i64::from(n)),
+            Day::Weekday {
+                month,
+                week,
+                weekday,
+            } => {
+                let first = Date::new(year, month, 1).expect("months run from 1 to 12");
+                // Date weekdays start from Monday, POSIX ones from Sunday.
+                let weekday = (weekday + 6) % 7;
+                let mut day = 1 + (weekday + 7 - first.weekday()) % 7 + (week - 1) * 7;
+                while day > days_in_month(year, month) {
+                    day -= 7;
+                }
+                first.add_days(i64::from(day) - 1)
+            }
+        };
+        date.days() * DAY + self.time - offset
+    }
+}
+
+/// Takes a zone abbreviation such as `CET` or `<+03>` off the front of `rest`.
+fn zone_name(rest: &mut &str) -> Option<()> {
+    let len = match rest.strip_prefix('<') {
+        Some(quoted) => quoted.find('>')? + 2,
+        None => rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len()),
+    };
+    if len < 3 {
+        return None;
+    }
+    *rest = &rest[len..];
+    Some(())
+}
+
+/// Takes a time such as `-1`, `5:30` or `+167` off the front of `rest`, in
+/// seconds.
+fn clock(rest: &mut &str) -> Option<i64> {
+    let (sign, text) = match rest.strip_prefix('-') {
+        Some(text) => (-1, text),
+        None => (1, rest.strip_prefix('+').unwrap_or(rest)),
+    };
+    let len = text
+        .find(|c: char| !c.is_ascii_digit() && c != ':')
+        .unwrap_or(text.len());
+    let mut secs = 0;
+    let mut unit = HOUR;
+    for part in text[..len].split(':') {
+        if unit == 0 || part.is_empty() || part.len() > 3 {
+            return None;
+        }
+        secs += part.parse::<i64>().ok()? * unit;
+        unit /= 60;
+    }
+    *rest = &text[len..];
+    Some(sign * secs)
+}
+
+/// Chooses the zone to use from now on: the one called `name`, or the
+/// system's if there is no such zone or no name.
+pub fn init(name: Option<&str>) {
+    let zone = name.and_then(Zone::named).unwrap_or_else(Zone::system);
+    *CURRENT.lock().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(zone));
+}
+
+/// The zone in use: the one chosen with `init`, else the system's.
+pub fn current() -> Arc<Zone> {
+    let mut current = CURRENT.lock().unwrap_or_else(|e| e.into_inner());
+    current
+        .get_or_insert_with(|| Arc::new(Zone::system()))
+        .clone()
+}
+
+/// `at`, a UTC time, in local time.
+pub fn local(at: DateTime) -> DateTime {
+    current().local(at)
+}
+
+/// `local`, a local time, in UTC.
+pub fn utc(local: DateTime) -> DateTime {
+    current().utc_of(local)
+}
+
+/// The local time now, to the minute.
+pub fn now() -> DateTime {
+    local(DateTime::now())
+}
@@ -0,0 +1,131 @@
+// Taking back one change saved earlier, while keeping the ones made after it.
+//
+// A change is everything one command saved together, as the audit log records
+// it under a change id. Undoing it removes the tasks it added, brings back the
+// ones it removed and sets the fields it changed back to their old values.
+// A later change in the way stops the whole undo: a field it changed that has
+// been changed again since, a task it added that has been edited since, or a
+// task it changed that is gone. Nothing is undone by halves.
+
+use serde_json::Value;
+
+use crate::audit::{self, Action, Entry, FieldChange};
+use crate::task::Task;
+
+/// Why part of a change can't be undone.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Conflict {
+    /// The task the change was to has been removed since.
+    Removed { id: u64, text: String },
+    /// This field has been changed again since.
+    Changed {
+        id: u64,
+        text: String,
+        field: String,
+    },
+    /// The change was logged before the log kept what undoing it needs.
+    Unrecorded { id: u64, text: String },
+    /// The change made the task secret; the log doesn't keep its old text.
+    Sealed { id: u64, text: String },
+}
+
+/// `tasks` with the change made of `entries` undone, or everything standing
+/// in the way. The entries are taken back last first.
+pub fn undo(tasks: &[Task], entries: &[Entry]) -> Result<Vec<Task>, Vec<Conflict>> {
+    let mut tasks = tasks.to_vec();
+    let mut conflicts = Vec::new();
+    for entry in entries.iter().rev() {
+        let position = tasks.iter().position(|task| task.id == entry.id);
+        match (entry.action, position, &entry.before) {
+            // Already gone, or already back: nothing left to undo.
+            (Action::Add, None, _) | (Action::Remove, Some(_), _) => {}
+            (Action::Add, Some(index), _) => {
+                let mut changed = changed_since(&tasks[index], &entry.fields);
+                // Fields set since, which the entry doesn't mention at all.
+                changed.extend(
+                    audit::diff(None, &tasks[index])
+                        .into_iter()
+                        .filter(|now| !entry.fields.iter().any(|f| f.field == now.field))
+                        .map(|now| now.field),
+                );
+                if changed.is_empty() {
+                    tasks.remove(index);
+                }
+                conflicts.extend(changed.into_iter().map(|field| Conflict::Changed {
+                    id: entry.id,
+                    text: entry.text.clone(),
+                    field,
+                }));
+            }
+            (Action::Remove, None, Some(before)) => tasks.push(before.clone()),
+            (Action::Update, None, _) => conflicts.push(Conflict::Removed {
+                id: entry.id,
+                text: entry.text.clone(),
+            }),
+            (Action::Update, Some(_), _) if audit::seals(entry) => {
+                conflicts.push(Conflict::Sealed {
+                    id: entry.id,
+                    text: entry.text.clone(),
+                })
+            }
+            (Action::Update, Some(index), Some(before)) => {
+                let changed = changed_since(&tasks[index], &entry.fields);
+                if changed.is_empty() {
+                    tasks[index] = restore(&tasks[index], before, &entry.fields);
+                }
+                conflicts.extend(changed.into_iter().map(|field| Conflict::Changed {
+                    id: entry.id,
+                    text: entry.text.clone(),
+                    field,
+                }));
+            }
+            (_, _, None) => conflicts.push(Conflict::Unrecorded {
+                id: entry.id,
+                text: entry.text.clone(),
+            }),
+        }
+    }
+    if conflicts.is_empty() {
+        Ok(tasks)
+    } else {
+        Err(conflicts)
+    }
+}
+
+/// The fields among `fields` that `task` has neither the new nor, already
+/// undone, the old value of.
+fn changed_since(task: &Task, fields: &[FieldChange]) -> Vec<String> {
+    let now = audit::diff(None, task);
+    fields
+        .iter()
+        .filter(|change| {
+            let value = now.iter().find(|c| c.field == change.field);
+            let value = value.and_then(|c| c.new.as_ref());
+            value != change.new.as_ref() && value != change.old.as_ref()
+        })
+        .map(|change| change.field.clone())
+        .collect()
+}
+
+/// `task` with `fields` set back to how they are in `before`.
+fn restore(task: &Task, before: &Task, fields: &[FieldChange]) -> Task {
+    let object = |task: &Task| match serde_json::to_value(task) {
+        Ok(Value::Object(fields)) => fields,
+        _ => unreachable!("tasks serialize to objects"),
+    };
+    let (mut restored, before) = (object(task), object(before));
+    for change in fields {
+        // When a task was completed goes with whether it is.
+        let keys: &[&str] = match change.field.as_str() {
+            "completed" => &["completed", "completed_at"],
+            field => &[field],
+        };
+        for &key in keys {
+            match before.get(key) {
+                Some(value) => restored.insert(key.to_string(), value.clone()),
+                None => restored.remove(key),
+            };
+        }
+    }
+    serde_json::from_value(Value::Object(restored)).unwrap_or_else(|_| task.clone())
+}
@@ -0,0 +1,87 @@
+// How pressing a task is, as one number that `next` and `--sort urgency`
+// order tasks by.
+//
+// The score adds up three things, each scaled by a coefficient from the
+// config, so that a list can care more about one than another:
+//
+//   due       1.0 once a week overdue, falling evenly to 0.2 two weeks
+//             before the due date and staying there; 0 without one
+//   priority  1.0 for high, 0.65 for medium and 0.3 for low
+//   age       how long since the task was last changed (or added), as a
+//             share of `urgency_age_days`, up to 1.0
+//
+// The age is what keeps a list honest: a task nobody has touched in weeks
+// climbs a little every day until it is dealt with, edited or dropped.
+// Completed tasks score nothing.
+
+use crate::date::{Date, DateTime};
+use crate::task::{Priority, Task};
+
+/// How much each part counts towards a task's urgency.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Urgency {
+    pub due: f64,
+    pub priority: f64,
+    pub age: f64,
+    /// After how many days untouched a task's age counts in full; zero
+    /// for age not to count.
+    pub age_days: u32,
+}
+
+impl Default for Urgency {
+    fn default() -> Self {
+        Urgency {
+            due: 12.0,
+            priority: 6.0,
+            age: 3.0,
+            age_days: 30,
+        }
+    }
+}
+
+impl Urgency {
+    /// How pressing `task` is at `now`; higher is more urgent.
+    pub fn score(&self, task: &Task, now: DateTime) -> f64 {
+        if task.completed {
+            return 0.0;
+        }
+        self.due * due_factor(task.due, now.date())
+            + self.priority * priority_factor(task.priority)
+            + self.age * self.age_factor(task, now)
+    }
+
+    fn age_factor(&self, task: &Task, now: DateTime) -> f64 {
+        let Some(touched) = task.modified_at.or(task.created_at) else {
+            return 0.0;
+        };
+        if self.age_days == 0 {
+            return 0.0;
+        }
+        let days = touched.minutes_until(now).max(0) as f64 / (24.0 * 60.0);
+        (days / f64::from(self.age_days)).min(1.0)
+    }
+}
+
+fn due_factor(due: Option<Date>, today: Date) -> f64 {
+    let Some(due) = due else {
+        return 0.0;
+    };
+    // Days past the due date: negative while it is still ahead.
+    let late = due.days_until(today) as f64;
+    if late >= 7.0 {
+        1.0
+    } else if late >= -14.0 {
+        (late + 14.0) * 0.8 / 21.0 + 0.2
+    } else {
+        0.2
+    }
+}
+
+fn priority_factor(priority: Option<Priority>) -> f64 {
+    match priority {
+        Some(Priority::High) => 1.0,
+        Some(Priority::Medium) => 0.65,
+        Some(Priority::Low) => 0.3,
+        None => 0.0,
+    }
+}
@@ -0,0 +1,218 @@
+// The rules for what a task may contain, in one place.
+//
+// Every way of creating or changing a task (the command line, the editor, the
+// HTTP API, MCP, JSON-RPC, scripts) runs what it was given through these
+// functions, so they all accept the same things and reject the rest with the
+// same messages, each saying what would be accepted instead.
+//
+//   text      control characters removed, trimmed, not empty, at most
+//             `max_task_length` characters
+//   notes     control characters other than line breaks and tabs removed
+//   due       YYYY-MM-DD, today, tomorrow, a weekday, next week or an offset
+//             (+3d, +3bd in business days, +2w, +1m), with a time of day if
+//             wanted, e.g. fri 17:00; times are kept in UTC
+//   priority  low, medium or high
+//   tags      no spaces, no leading `#`, lowercase
+//   project   the same, without a leading `+`
+//   contexts  the same, without a leading `@`
+//   reminders a date and time of day, e.g. tomorrow 9am, fri 14:30 or +2h
+//   waiting   who a task waits for: not empty, no control characters
+//   checklist each item's text follows the rules for a task's text
+//   comments  not empty; line breaks kept, as in notes
+//   depends   ids of other tasks, each once
+//   habit     daily, weekly, weekdays, or every so many days (3d), weeks (2w)
+//             or business days (2bd)
+//   status    a single word, lowercase; `done` and `cancelled` complete the
+//             task, any other status reopens it
+
+use crate::date::{Date, DateTime};
+use crate::error::{Error, Result};
+use crate::habit::Habit;
+use crate::i18n::tr;
+use crate::task::{
+    normalize_context, normalize_project, normalize_tag, Priority, Task, CANCELLED, DONE, TODO,
+};
+use crate::tz;
+
+/// The longest task text accepted unless `max_task_length` says otherwise.
+pub const DEFAULT_MAX_LENGTH: usize = 500;
+
+/// Cleans up a task's text: line breaks and tabs become spaces, other control
+/// characters are dropped and the ends are trimmed. Fails if nothing is left,
+/// or if more than `max_length` characters are.
+pub fn text(text: &str, max_length: usize) -> Result<String> {
+    let cleaned: String = text
+        .chars()
+        .filter_map(|c| match c {
+            '\n' | '\r' | '\t' => Some(' '),
+            c if c.is_control() => None,
+            c => Some(c),
+        })
+        .collect();
+    let cleaned = cleaned.trim();
+    if cleaned.is_empty() {
+        return Err(Error::Invalid(tr("task-text-empty", &[])));
+    }
+    let length = cleaned.chars().count();
+    if length > max_length {
+        return Err(Error::Invalid(tr(
+            "task-text-too-long",
+            &[("length", &length), ("max", &max_length)],
+        )));
+    }
+    Ok(cleaned.to_string())
+}
+
+/// Cleans up notes, keeping their line breaks and tabs. Notes that are only
+/// whitespace are no notes.
+pub fn notes(notes: &str) -> Option<String> {
+    let cleaned: String = notes
+        .chars()
+        .filter(|&c| !c.is_control() || c == '\n' || c == '\t')
+        .collect();
+    let cleaned = cleaned.trim_end();
+    (!cleaned.trim().is_empty()).then(|| cleaned.to_string())
+}
+
+/// Parses a due date, relative to `today`.
+pub fn due(input: &str, today: Date) -> Result<Date> {
+    Date::parse_relative(input, today).map_err(Error::Invalid)
+}
+
+/// Parses a due date that may have a time of day, as in `fri 17:00`,
+/// `2025-06-30 9am`, `tomorrow@noon` or `+2h`. The time is local; it is
+/// returned in UTC, with the local date it falls on.
+pub fn due_time(input: &str, today: Date) -> Result<(Date, Option<DateTime>)> {
+    let error = match Date::parse_relative(input, today) {
+        Ok(date) => return Ok((date, None)),
+        Err(e) => e,
+    };
+    // A date alone was read above, so this only takes times.
+    let local = DateTime::parse_relative(&input.replace('@', " "), tz::now())
+        .map_err(|_| Error::Invalid(error))?;
+    Ok((local.date(), Some(tz::utc(local))))
+}
+
+/// Parses a priority.
+pub fn priority(input: &str) -> Result<Priority> {
+    input.parse().map_err(Error::Invalid)
+}
+
+/// Normalizes a tag, which must be a single word.
+pub fn tag(input: &str) -> Result<String> {
+    word(normalize_tag(input), || {
+        tr("tag-invalid", &[("tag", &input.trim())])
+    })
+}
+
+/// Normalizes a project name, which must be a single word.
+pub fn project(input: &str) -> Result<String> {
+    word(normalize_project(input), || {
+        tr("project-invalid", &[("project", &input.trim())])
+    })
+}
+
+/// Normalizes a context, which must be a single word.
+pub fn context(input: &str) -> Result<String> {
+    word(normalize_context(input), || {
+        tr("context-invalid", &[("context", &input.trim())])
+    })
+}
+
+/// Fails with `message` unless `word` is one non-empty word.
+fn word(word: String, message: impl FnOnce() -> String) -> Result<String> {
+    if word.is_empty() || word.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(Error::Invalid(message()));
+    }
+    Ok(word)
+}
+
+/// Normalizes a workflow status, which must be a single word.
+pub fn status(input: &str) -> Result<String> {
+    word(input.trim().to_lowercase(), || {
+        tr("status-invalid", &[("status", &input.trim())])
+    })
+}
+
+/// Parses how often a habit is expected, as a number of days and whether
+/// they are business days.
+pub fn habit(input: &str) -> Result<(u32, bool)> {
+    Habit::parse_every(input).map_err(Error::Invalid)
+}
+
+/// Parses a reminder time, relative to `now`. Both are in UTC; the input is
+/// read as local time.
+pub fn reminder(input: &str, now: DateTime) -> Result<DateTime> {
+    DateTime::parse_relative(input, tz::local(now))
+        .map(tz::utc)
+        .map_err(Error::Invalid)
+}
+
+/// Cleans up the name of who a task is waiting for, which can't be empty.
+pub fn person(input: &str) -> Result<String> {
+    let person: String = input.chars().filter(|c| !c.is_control()).collect();
+    let person = person.trim();
+    if person.is_empty() {
+        return Err(Error::Invalid(tr("waiting-person-empty", &[])));
+    }
+    Ok(person.to_string())
+}
+
+/// Cleans up a comment like notes, but a comment can't be empty.
+pub fn comment(input: &str) -> Result<String> {
+    notes(input)
+        .map(|text| text.trim_start().to_string())
+        .ok_or_else(|| Error::Invalid(tr("comment-empty", &[])))
+}
+
+/// Checks a whole task before it is stored, cleaning up what can be cleaned.
+pub fn task(mut task: Task, max_length: usize) -> Result<Task> {
+    task.text = text(&task.text, max_length)?;
+    task.notes = task.notes.as_deref().and_then(notes);
+    task.tags = task
+        .tags
+        .iter()
+        .map(|t| tag(t))
+        .collect::<Result<Vec<_>>>()?;
+    task.tags.dedup();
+    task.project = task.project.as_deref().map(project).transpose()?;
+    task.contexts = task
+        .contexts
+        .iter()
+        .map(|c| context(c))
+        .collect::<Result<Vec<_>>>()?;
+    task.contexts.dedup();
+    if let Some(waiting) = &mut task.waiting {
+        waiting.person = person(&waiting.person)?;
+    }
+    for item in &mut task.checklist {
+        item.text = text(&item.text, max_length)?;
+    }
+    // A due time dates the task where it is now.
+    if let Some(at) = task.due_at {
+        task.due = Some(tz::local(at).date());
+    }
+    task.reminders.sort();
+    task.reminders.dedup();
+    task.depends.sort_unstable();
+    task.depends.dedup();
+    task.depends.retain(|&id| id != task.id);
+    if let Some(habit) = &mut task.habit {
+        if habit.every == 0 {
+            return Err(Error::Invalid(tr("habit-every-invalid", &[("input", &0)])));
+        }
+        habit.done.sort();
+        habit.done.dedup();
+    }
+    // Keep the status and `completed` in step. A task that was completed
+    // without a word about its status is done, wherever it was before.
+    if let Some(input) = task.status.take() {
+        match status(&input)?.as_str() {
+            CANCELLED => task.set_status(CANCELLED),
+            name @ (TODO | DONE) => task.set_status(name),
+            _ if task.completed => {}
+            name => task.status = Some(name.to_string()),
+        }
+    }
+    Ok(task)
+}
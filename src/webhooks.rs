@@ -0,0 +1,67 @@
+// Webhooks: HTTP POSTs sent to configured URLs whenever a task is added,
+// completed or deleted.
+//
+// Each delivery is a JSON object:
+//
+//   {"event": "add", "task": {...}, "list": "work", "timestamp": 1760000000}
+//
+// `list` is null for the main list and `timestamp` is in Unix seconds. When a
+// secret is configured, the body is signed with HMAC-SHA256 and the signature
+// sent as `X-Todo-Signature: sha256=<hex>`, so receivers can check that the
+// request came from us. Failed deliveries are retried as the `http_retries`
+// setting allows; like hooks, webhooks run after the change is saved and a
+// failure never undoes it.
+
+use std::io;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::json;
+
+use crate::hooks::Event;
+use crate::http::Client;
+use crate::log;
+use crate::sha256;
+use crate::task::Task;
+
+/// The header carrying the payload's signature.
+pub const SIGNATURE_HEADER: &str = "X-Todo-Signature";
+
+/// Builds the JSON body sent for `event`.
+pub fn payload(event: Event, task: &Task, list: Option<&str>) -> String {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    json!({
+        "event": event.to_string(),
+        "task": task,
+        "list": list,
+        "timestamp": timestamp,
+    })
+    .to_string()
+}
+
+/// Returns the signature header value for `body`, e.g. `sha256=ab12...`.
+pub fn signature(secret: &str, body: &str) -> String {
+    format!(
+        "sha256={}",
+        sha256::hex(&sha256::hmac(secret.as_bytes(), body.as_bytes()))
+    )
+}
+
+/// Posts `body` to `url`, retrying as `client` allows.
+///
+/// Returns the status of the last response, or why the last attempt failed.
+pub fn deliver(client: &Client, url: &str, body: &str, secret: Option<&str>) -> io::Result<u16> {
+    let signature = secret.map(|secret| signature(secret, body));
+    let mut headers = vec![
+        ("Content-Type", "application/json"),
+        ("User-Agent", "todo_cli"),
+    ];
+    if let Some(signature) = &signature {
+        headers.push((SIGNATURE_HEADER, signature));
+    }
+    log::debug("webhooks", format_args!("delivering to {}", url));
+    client
+        .send_with_retries("POST", url, &headers, body.as_bytes())
+        .map(|response| response.status)
+}